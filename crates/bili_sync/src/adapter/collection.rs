@@ -12,10 +12,14 @@ use sea_orm::entity::prelude::*;
 use sea_orm::sea_query::SimpleExpr;
 use sea_orm::{DatabaseConnection, Unchanged};
 
-use crate::adapter::{_ActiveModel, VideoSource, VideoSourceEnum};
+use crate::adapter::{_ActiveModel, VideoSource, VideoSourceEnum, VideoSourceKey};
 use crate::bilibili::{BiliClient, Collection, CollectionItem, CollectionType, Credential, VideoInfo};
 
 impl VideoSource for collection::Model {
+    fn key(&self) -> VideoSourceKey {
+        VideoSourceKey::Collection(self.id)
+    }
+
     fn display_name(&self) -> Cow<'static, str> {
         format!("{}「{}」", CollectionType::from_expected(self.r#type), self.name).into()
     }
@@ -44,6 +48,14 @@ impl VideoSource for collection::Model {
         })
     }
 
+    fn update_path(&self, path: String) -> _ActiveModel {
+        _ActiveModel::Collection(collection::ActiveModel {
+            id: Unchanged(self.id),
+            path: Set(path),
+            ..Default::default()
+        })
+    }
+
     fn should_take(
         &self,
         _idx: usize,
@@ -74,11 +86,27 @@ impl VideoSource for collection::Model {
         &self.rule
     }
 
+    fn scan_schedule(&self) -> (Option<u32>, Option<&str>) {
+        (self.scan_interval_secs, self.scan_cron.as_deref())
+    }
+
+    fn audio_only(&self) -> bool {
+        self.audio_only
+    }
+
+    fn scan_priority(&self) -> i32 {
+        self.scan_priority
+    }
+
+    fn video_name(&self) -> Option<&str> {
+        self.video_name.as_deref()
+    }
+
     async fn refresh<'a>(
         self,
         bili_client: &'a BiliClient,
         credential: &'a Credential,
-        connection: &'a DatabaseConnection,
+        connection: Option<&'a DatabaseConnection>,
     ) -> Result<(
         VideoSourceEnum,
         Pin<Box<dyn Stream<Item = Result<VideoInfo>> + Send + 'a>>,
@@ -101,13 +129,21 @@ impl VideoSource for collection::Model {
             collection_info,
             collection.collection
         );
-        let updated_model = collection::ActiveModel {
-            id: Unchanged(self.id),
-            name: Set(collection_info.name),
-            ..Default::default()
-        }
-        .update(connection)
-        .await?;
+        let updated_model = match connection {
+            Some(connection) => {
+                collection::ActiveModel {
+                    id: Unchanged(self.id),
+                    name: Set(collection_info.name),
+                    ..Default::default()
+                }
+                .update(connection)
+                .await?
+            }
+            None => collection::Model {
+                name: collection_info.name,
+                ..self
+            },
+        };
         Ok((updated_model.into(), Box::pin(collection.into_video_stream())))
     }
 
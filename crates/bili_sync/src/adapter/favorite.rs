@@ -11,10 +11,14 @@ use sea_orm::entity::prelude::*;
 use sea_orm::sea_query::SimpleExpr;
 use sea_orm::{DatabaseConnection, Unchanged};
 
-use crate::adapter::{_ActiveModel, VideoSource, VideoSourceEnum};
+use crate::adapter::{_ActiveModel, VideoSource, VideoSourceEnum, VideoSourceKey};
 use crate::bilibili::{BiliClient, Credential, FavoriteList, VideoInfo};
 
 impl VideoSource for favorite::Model {
+    fn key(&self) -> VideoSourceKey {
+        VideoSourceKey::Favorite(self.id)
+    }
+
     fn display_name(&self) -> Cow<'static, str> {
         format!("收藏夹「{}」", self.name).into()
     }
@@ -43,15 +47,39 @@ impl VideoSource for favorite::Model {
         })
     }
 
+    fn update_path(&self, path: String) -> _ActiveModel {
+        _ActiveModel::Favorite(favorite::ActiveModel {
+            id: Unchanged(self.id),
+            path: Set(path),
+            ..Default::default()
+        })
+    }
+
     fn rule(&self) -> &Option<Rule> {
         &self.rule
     }
 
+    fn scan_schedule(&self) -> (Option<u32>, Option<&str>) {
+        (self.scan_interval_secs, self.scan_cron.as_deref())
+    }
+
+    fn audio_only(&self) -> bool {
+        self.audio_only
+    }
+
+    fn scan_priority(&self) -> i32 {
+        self.scan_priority
+    }
+
+    fn video_name(&self) -> Option<&str> {
+        self.video_name.as_deref()
+    }
+
     async fn refresh<'a>(
         self,
         bili_client: &'a BiliClient,
         credential: &'a Credential,
-        connection: &'a DatabaseConnection,
+        connection: Option<&'a DatabaseConnection>,
     ) -> Result<(
         VideoSourceEnum,
         Pin<Box<dyn Stream<Item = Result<VideoInfo>> + Send + 'a>>,
@@ -64,13 +92,21 @@ impl VideoSource for favorite::Model {
             favorite_info.id,
             self.f_id
         );
-        let updated_model = favorite::ActiveModel {
-            id: Unchanged(self.id),
-            name: Set(favorite_info.title),
-            ..Default::default()
-        }
-        .update(connection)
-        .await?;
+        let updated_model = match connection {
+            Some(connection) => {
+                favorite::ActiveModel {
+                    id: Unchanged(self.id),
+                    name: Set(favorite_info.title),
+                    ..Default::default()
+                }
+                .update(connection)
+                .await?
+            }
+            None => favorite::Model {
+                name: favorite_info.title,
+                ..self
+            },
+        };
         Ok((updated_model.into(), Box::pin(favorite.into_video_stream())))
     }
 
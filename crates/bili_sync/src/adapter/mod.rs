@@ -24,8 +24,10 @@ use bili_sync_entity::submission::Model as Submission;
 use bili_sync_entity::watch_later::Model as WatchLater;
 
 use crate::bilibili::{BiliClient, Credential, VideoInfo};
+use crate::config::Trigger;
 
 #[enum_dispatch]
+#[derive(Clone)]
 pub enum VideoSourceEnum {
     Favorite,
     Collection,
@@ -33,8 +35,32 @@ pub enum VideoSourceEnum {
     WatchLater,
 }
 
+/// 视频源的唯一标识，由视频源类型与数据库中的 id 组成
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VideoSourceKey {
+    Favorite(i32),
+    Collection(i32),
+    Submission(i32),
+    WatchLater(i32),
+}
+
+impl VideoSourceKey {
+    /// 视频源类型的名称，用于在通知中区分视频源的类型
+    pub fn source_type(&self) -> &'static str {
+        match self {
+            VideoSourceKey::Favorite(_) => "favorite",
+            VideoSourceKey::Collection(_) => "collection",
+            VideoSourceKey::Submission(_) => "submission",
+            VideoSourceKey::WatchLater(_) => "watch_later",
+        }
+    }
+}
+
 #[enum_dispatch(VideoSourceEnum)]
 pub trait VideoSource {
+    /// 获取视频源的唯一标识
+    fn key(&self) -> VideoSourceKey;
+
     /// 获取视频源的名称
     fn display_name(&self) -> Cow<'static, str>;
 
@@ -55,6 +81,9 @@ pub trait VideoSource {
     /// Box<dyn ActiveModelTrait> 又提示 ActiveModelTrait 没有 object safety，因此手写一个 Enum 静态分发
     fn update_latest_row_at(&self, datetime: DateTime) -> _ActiveModel;
 
+    /// 更新视频源的保存路径，与 update_latest_row_at 相同，返回需要更新的 ActiveModel
+    fn update_path(&self, path: String) -> _ActiveModel;
+
     // 判断是否应该继续拉取视频
     fn should_take(
         &self,
@@ -77,6 +106,27 @@ pub trait VideoSource {
 
     fn rule(&self) -> &Option<Rule>;
 
+    /// 获取视频源单独设置的扫描间隔（秒）与扫描 cron 表达式
+    fn scan_schedule(&self) -> (Option<u32>, Option<&str>);
+
+    /// 是否仅下载音频，开启后分页只下载音频流并保存为 m4a
+    fn audio_only(&self) -> bool;
+
+    /// 扫描优先级，数值越大越先扫描
+    fn scan_priority(&self) -> i32;
+
+    /// 视频源单独设置的视频文件夹名模板，未设置时返回 None，表示使用全局的 video_name
+    fn video_name(&self) -> Option<&str>;
+
+    /// 获取视频源单独设置的扫描计划，cron 表达式优先于扫描间隔，均未设置时返回 None，表示跟随全局的 interval
+    fn scan_trigger(&self) -> Option<Trigger> {
+        match self.scan_schedule() {
+            (_, Some(cron)) if !cron.trim().is_empty() => Some(Trigger::Cron(cron.to_owned())),
+            (Some(secs), _) => Some(Trigger::Interval(secs as u64)),
+            _ => None,
+        }
+    }
+
     fn log_refresh_video_start(&self) {
         info!("开始扫描{}..", self.display_name());
     }
@@ -101,11 +151,12 @@ pub trait VideoSource {
         info!("下载{}视频完成", self.display_name());
     }
 
+    /// 请求视频源的最新信息并返回视频流，connection 为 None 时（演练模式）不将最新信息写入数据库
     async fn refresh<'a>(
         self,
         bili_client: &'a BiliClient,
         credential: &'a Credential,
-        connection: &'a DatabaseConnection,
+        connection: Option<&'a DatabaseConnection>,
     ) -> Result<(
         VideoSourceEnum,
         Pin<Box<dyn Stream<Item = Result<VideoInfo>> + Send + 'a>>,
@@ -133,7 +184,7 @@ pub enum _ActiveModel {
 }
 
 impl _ActiveModel {
-    pub async fn save(self, connection: &DatabaseConnection) -> Result<()> {
+    pub async fn save(self, connection: &impl ConnectionTrait) -> Result<()> {
         match self {
             _ActiveModel::Favorite(model) => {
                 model.save(connection).await?;
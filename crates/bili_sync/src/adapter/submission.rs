@@ -10,10 +10,14 @@ use sea_orm::entity::prelude::*;
 use sea_orm::sea_query::SimpleExpr;
 use sea_orm::{DatabaseConnection, Unchanged};
 
-use crate::adapter::{_ActiveModel, VideoSource, VideoSourceEnum};
+use crate::adapter::{_ActiveModel, VideoSource, VideoSourceEnum, VideoSourceKey};
 use crate::bilibili::{BiliClient, Credential, Dynamic, Submission, VideoInfo};
 
 impl VideoSource for submission::Model {
+    fn key(&self) -> VideoSourceKey {
+        VideoSourceKey::Submission(self.id)
+    }
+
     fn display_name(&self) -> std::borrow::Cow<'static, str> {
         format!("「{}」投稿", self.upper_name).into()
     }
@@ -42,6 +46,14 @@ impl VideoSource for submission::Model {
         })
     }
 
+    fn update_path(&self, path: String) -> _ActiveModel {
+        _ActiveModel::Submission(submission::ActiveModel {
+            id: Unchanged(self.id),
+            path: Set(path),
+            ..Default::default()
+        })
+    }
+
     fn should_take(
         &self,
         idx: usize,
@@ -82,11 +94,27 @@ impl VideoSource for submission::Model {
         &self.rule
     }
 
+    fn scan_schedule(&self) -> (Option<u32>, Option<&str>) {
+        (self.scan_interval_secs, self.scan_cron.as_deref())
+    }
+
+    fn audio_only(&self) -> bool {
+        self.audio_only
+    }
+
+    fn scan_priority(&self) -> i32 {
+        self.scan_priority
+    }
+
+    fn video_name(&self) -> Option<&str> {
+        self.video_name.as_deref()
+    }
+
     async fn refresh<'a>(
         self,
         bili_client: &'a BiliClient,
         credential: &'a Credential,
-        connection: &'a DatabaseConnection,
+        connection: Option<&'a DatabaseConnection>,
     ) -> Result<(
         VideoSourceEnum,
         Pin<Box<dyn Stream<Item = Result<VideoInfo>> + Send + 'a>>,
@@ -99,14 +127,23 @@ impl VideoSource for submission::Model {
             upper.mid,
             submission.upper_id
         );
-        let updated_model = submission::ActiveModel {
-            id: Unchanged(self.id),
-            upper_name: Set(upper.name),
-            ..Default::default()
-        }
-        .update(connection)
-        .await?;
-        let video_stream = if self.use_dynamic_api {
+        let use_dynamic_api = self.use_dynamic_api;
+        let updated_model = match connection {
+            Some(connection) => {
+                submission::ActiveModel {
+                    id: Unchanged(self.id),
+                    upper_name: Set(upper.name),
+                    ..Default::default()
+                }
+                .update(connection)
+                .await?
+            }
+            None => submission::Model {
+                upper_name: upper.name,
+                ..self
+            },
+        };
+        let video_stream = if use_dynamic_api {
             // 必须显式写出 dyn，否则 rust 会自动推导到 impl 从而认为 if else 返回类型不一致
             Box::pin(Dynamic::from(submission).into_video_stream()) as Pin<Box<dyn Stream<Item = _> + Send + 'a>>
         } else {
@@ -10,10 +10,14 @@ use sea_orm::entity::prelude::*;
 use sea_orm::sea_query::SimpleExpr;
 use sea_orm::{DatabaseConnection, Unchanged};
 
-use crate::adapter::{_ActiveModel, VideoSource, VideoSourceEnum};
+use crate::adapter::{_ActiveModel, VideoSource, VideoSourceEnum, VideoSourceKey};
 use crate::bilibili::{BiliClient, Credential, VideoInfo, WatchLater};
 
 impl VideoSource for watch_later::Model {
+    fn key(&self) -> VideoSourceKey {
+        VideoSourceKey::WatchLater(self.id)
+    }
+
     fn display_name(&self) -> std::borrow::Cow<'static, str> {
         "稍后再看".into()
     }
@@ -42,15 +46,39 @@ impl VideoSource for watch_later::Model {
         })
     }
 
+    fn update_path(&self, path: String) -> _ActiveModel {
+        _ActiveModel::WatchLater(watch_later::ActiveModel {
+            id: Unchanged(self.id),
+            path: Set(path),
+            ..Default::default()
+        })
+    }
+
     fn rule(&self) -> &Option<Rule> {
         &self.rule
     }
 
+    fn scan_schedule(&self) -> (Option<u32>, Option<&str>) {
+        (self.scan_interval_secs, self.scan_cron.as_deref())
+    }
+
+    fn audio_only(&self) -> bool {
+        self.audio_only
+    }
+
+    fn scan_priority(&self) -> i32 {
+        self.scan_priority
+    }
+
+    fn video_name(&self) -> Option<&str> {
+        self.video_name.as_deref()
+    }
+
     async fn refresh<'a>(
         self,
         bili_client: &'a BiliClient,
         credential: &'a Credential,
-        _connection: &'a DatabaseConnection,
+        _connection: Option<&'a DatabaseConnection>,
     ) -> Result<(
         VideoSourceEnum,
         Pin<Box<dyn Stream<Item = Result<VideoInfo>> + Send + 'a>>,
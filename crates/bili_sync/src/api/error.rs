@@ -6,4 +6,10 @@ pub enum InnerApiError {
     NotFound(i32),
     #[error("Bad request: {0}")]
     BadRequest(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    #[error("Template render error: {0}")]
+    TemplateError(String),
+    #[error("Risk control occurred: {0}")]
+    RiskControl(String),
 }
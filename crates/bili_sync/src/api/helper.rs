@@ -1,9 +1,11 @@
 use std::borrow::Borrow;
 
 use itertools::Itertools;
+use sea_orm::sea_query::{Expr, LikeExpr};
 use sea_orm::{ColumnTrait, Condition, ConnectionTrait, DatabaseTransaction};
 use bili_sync_entity::video;
 
+use crate::api::error::InnerApiError;
 use crate::api::request::StatusFilter;
 use crate::api::response::{PageInfo, SimplePageInfo, SimpleVideoInfo, VideoInfo};
 use crate::utils::status::VideoStatus;
@@ -29,6 +31,41 @@ impl StatusFilter {
     }
 }
 
+/// 根据搜索词构造视频的查询条件，search_fields 用于限定搜索的字段，为空时同时搜索名称、BV 号与 UP 主名称
+pub fn video_search_condition(query_word: &str, search_fields: Option<&[String]>) -> Result<Condition, InnerApiError> {
+    let columns = match search_fields {
+        Some(fields) if !fields.is_empty() => fields
+            .iter()
+            .map(|field| match field.as_str() {
+                "name" => Ok(video::Column::Name),
+                "bvid" => Ok(video::Column::Bvid),
+                "upper_name" => Ok(video::Column::UpperName),
+                _ => Err(InnerApiError::BadRequest(format!("unsupported search field: {}", field))),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => vec![video::Column::Name, video::Column::Bvid, video::Column::UpperName],
+    };
+    Ok(columns
+        .into_iter()
+        .fold(Condition::any(), |condition, column| condition.add(column.contains(query_word))))
+}
+
+/// 根据标签构造视频的查询条件，同时匹配 B 站标签与自动标签
+///
+/// 标签以 JSON 数组的形式保存，匹配时连同两侧的引号一起匹配，避免“教程”命中“Rust教程”这样的部分标签
+pub fn video_tag_condition(tag: &str) -> Condition {
+    let quoted_tag = serde_json::Value::from(tag).to_string();
+    let pattern = format!(
+        "%{}%",
+        quoted_tag.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+    );
+    [video::Column::Tags, video::Column::RuleTags]
+        .into_iter()
+        .fold(Condition::any(), |condition, column| {
+            condition.add(Expr::col(column).like(LikeExpr::new(pattern.clone()).escape('\\')))
+        })
+}
+
 pub trait VideoRecord {
     fn as_id_status_tuple(&self) -> (i32, u32);
 }
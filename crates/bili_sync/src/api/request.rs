@@ -1,5 +1,5 @@
 use bili_sync_entity::rule::Rule;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use validator::Validate;
 
 use crate::bilibili::CollectionType;
@@ -14,7 +14,7 @@ pub enum StatusFilter {
     Paid,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone, Copy)]
 #[serde(rename_all = "snake_case")]
 pub enum VideoSortBy {
     /// 按投稿时间排序
@@ -25,31 +25,69 @@ pub enum VideoSortBy {
     DownloadTime,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum PageSortBy {
+    Cid,
+    Pid,
+    /// 按下载状态排序，升序时依次为失败、等待、成功
+    Status,
+}
+
+#[derive(Deserialize, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum SortOrder {
     Asc,
     Desc,
 }
 
-#[derive(Deserialize)]
+/// 将逗号分隔的字符串反序列化为列表，用于不支持数组的 query 参数
+fn deserialize_comma_separated<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<String>::deserialize(deserializer)?.map(|s| {
+        s.split(',')
+            .map(|field| field.trim().to_owned())
+            .filter(|field| !field.is_empty())
+            .collect()
+    }))
+}
+
+#[derive(Deserialize, Default)]
 pub struct VideosRequest {
     pub collection: Option<i32>,
     pub favorite: Option<i32>,
     pub submission: Option<i32>,
     pub watch_later: Option<i32>,
     pub query: Option<String>,
+    /// 限定 query 搜索的字段，以逗号分隔，可选 name / bvid / upper_name，为空时搜索全部字段
+    #[serde(default, deserialize_with = "deserialize_comma_separated")]
+    pub search_fields: Option<Vec<String>>,
     pub status_filter: Option<StatusFilter>,
+    /// 仅返回带有该标签（B 站标签或自动标签）的视频
+    pub tag: Option<String>,
     pub page: Option<u64>,
     pub page_size: Option<u64>,
     pub sort_by: Option<VideoSortBy>,
     pub sort_order: Option<SortOrder>,
 }
 
+#[derive(Deserialize, Default)]
+pub struct VideoStatsRequest {
+    pub collection: Option<i32>,
+    pub favorite: Option<i32>,
+    pub submission: Option<i32>,
+    pub watch_later: Option<i32>,
+}
+
 #[derive(Deserialize)]
 pub struct ResetVideoStatusRequest {
     #[serde(default)]
     pub force: bool,
+    /// 仅重置分页中这些序号的失败子任务（0 封面、1 视频、2 信息、3 弹幕、4 字幕），为空时重置全部失败的子任务
+    #[serde(default)]
+    pub task_indices: Vec<usize>,
 }
 
 #[derive(Deserialize)]
@@ -62,6 +100,46 @@ pub struct ResetFilteredVideoStatusRequest {
     pub status_filter: Option<StatusFilter>,
     #[serde(default)]
     pub force: bool,
+    /// 仅重置分页中该序号的失败子任务（0 封面、1 视频、2 信息、3 弹幕、4 字幕），为空时重置全部失败的子任务
+    pub task_index: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct ReconcileFilteredVideosRequest {
+    pub collection: Option<i32>,
+    pub favorite: Option<i32>,
+    pub submission: Option<i32>,
+    pub watch_later: Option<i32>,
+    pub query: Option<String>,
+    pub status_filter: Option<StatusFilter>,
+}
+
+#[derive(Deserialize)]
+pub struct RefreshFilteredMetadataRequest {
+    pub collection: Option<i32>,
+    pub favorite: Option<i32>,
+    pub submission: Option<i32>,
+    pub watch_later: Option<i32>,
+    pub query: Option<String>,
+    pub status_filter: Option<StatusFilter>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct RetryFilteredVideoTaskRequest {
+    pub collection: Option<i32>,
+    pub favorite: Option<i32>,
+    pub submission: Option<i32>,
+    pub watch_later: Option<i32>,
+    pub query: Option<String>,
+    /// 限定 query 搜索的字段，含义与 VideosRequest 相同
+    #[serde(default, deserialize_with = "deserialize_comma_separated")]
+    pub search_fields: Option<Vec<String>>,
+    pub status_filter: Option<StatusFilter>,
+    /// 仅重试带有该标签（B 站标签或自动标签）的视频
+    pub tag: Option<String>,
+    /// 任务索引，含义与 RetryVideoTaskRequest 相同
+    #[validate(range(min = 0, max = 4))]
+    pub task_index: usize,
 }
 
 #[derive(Deserialize, Validate)]
@@ -116,6 +194,32 @@ pub struct UpdateFilteredVideoStatusRequest {
     pub is_paid_video: Option<bool>,
 }
 
+#[derive(Deserialize)]
+pub struct SetSinglePageRequest {
+    pub single_page: bool,
+}
+
+#[derive(Deserialize)]
+pub struct DownloadTaskRequest {
+    /// 演练模式，仅输出将要执行的操作，不实际下载与写入
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 获取单个视频详情时分页列表的分页参数，均未传入时返回全部分页
+#[derive(Deserialize, Default)]
+pub struct VideoRequest {
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+    pub pages_sort_by: Option<PageSortBy>,
+    pub pages_sort_order: Option<SortOrder>,
+}
+
+#[derive(Deserialize)]
+pub struct RecentVideosRequest {
+    pub limit: Option<u64>,
+}
+
 #[derive(Deserialize)]
 pub struct FollowedCollectionsRequest {
     pub page_num: Option<i32>,
@@ -129,6 +233,13 @@ pub struct FollowedUppersRequest {
     pub name: Option<String>,
 }
 
+#[derive(Deserialize, Default)]
+pub struct VideoUppersRequest {
+    pub page_num: Option<u64>,
+    pub page_size: Option<u64>,
+    pub name: Option<String>,
+}
+
 #[derive(Deserialize, Validate)]
 pub struct InsertFavoriteRequest {
     pub fid: i64,
@@ -153,6 +264,12 @@ pub struct InsertSubmissionRequest {
     pub path: String,
 }
 
+#[derive(Deserialize, Validate)]
+pub struct RelocateVideoSourceRequest {
+    #[validate(custom(function = "crate::utils::validation::validate_path"))]
+    pub path: String,
+}
+
 #[derive(Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateVideoSourceRequest {
@@ -161,6 +278,19 @@ pub struct UpdateVideoSourceRequest {
     pub enabled: bool,
     pub rule: Option<Rule>,
     pub use_dynamic_api: Option<bool>,
+    // 未传入时保持原有设置不变
+    pub audio_only: Option<bool>,
+    // 未传入时保持原有设置不变
+    pub scan_priority: Option<i32>,
+    // 未传入时保持原有设置不变，传入空字符串时清除单独设置，使用全局的 video_name
+    #[validate(custom(function = "crate::utils::validation::validate_video_name"))]
+    pub video_name: Option<String>,
+    // 未传入时保持原有设置不变，传入 0 时清除单独设置的扫描间隔
+    #[validate(custom(function = "crate::utils::validation::validate_scan_interval_secs"))]
+    pub scan_interval_secs: Option<u32>,
+    // 未传入时保持原有设置不变，传入空字符串时清除单独设置的 cron 表达式，同时设置时 cron 表达式优先
+    #[validate(custom(function = "crate::utils::validation::validate_scan_cron"))]
+    pub scan_cron: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -173,6 +303,13 @@ pub struct PollQrcodeRequest {
     pub qrcode_key: String,
 }
 
+#[derive(Deserialize, Validate)]
+pub struct EnqueueRetriesRequest {
+    /// 需要重试所有失败任务的视频 ID
+    #[validate(length(min = 1))]
+    pub video_ids: Vec<i32>,
+}
+
 #[derive(Deserialize, Validate)]
 pub struct RetryVideoTaskRequest {
     /// 任务索引：0=视频封面, 1=视频信息, 2=UP主头像, 3=UP主信息, 4=分页下载
@@ -186,3 +323,35 @@ pub struct RetryPageTaskRequest {
     #[validate(range(min = 0, max = 4))]
     pub task_index: usize,
 }
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateTarget {
+    Video,
+    Page,
+}
+
+#[derive(Deserialize)]
+pub struct SetLogLevelRequest {
+    /// 与 RUST_LOG 环境变量格式一致的日志等级，如 "None,bili_sync=debug"
+    pub level: String,
+}
+
+#[derive(Deserialize)]
+pub struct PreviewCronRequest {
+    /// 与 interval、daily_summary_cron 格式一致的 Cron 表达式（秒 分 时 日 月 周）
+    pub cron: String,
+    /// 返回的触发时间数量，未传入时返回 5 个
+    pub count: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewTemplateRequest {
+    pub template: String,
+    pub target: TemplateTarget,
+    /// 用于渲染的示例视频，未传入时使用内置的示例数据
+    pub video_id: Option<i32>,
+    /// 用于渲染的示例分页，未传入时使用内置的示例数据
+    pub page_id: Option<i32>,
+}
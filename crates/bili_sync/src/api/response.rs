@@ -4,6 +4,8 @@ use sea_orm::{DerivePartialModel, FromQueryResult};
 use serde::Serialize;
 
 use crate::bilibili::{PollStatus, Qrcode};
+use crate::task::{RetryBatch, RetryJob};
+use crate::utils::progress::VideoProgress;
 use crate::utils::status::{PageStatus, VideoStatus};
 
 #[derive(Serialize)]
@@ -20,10 +22,22 @@ pub struct VideosResponse {
     pub total_count: u64,
 }
 
+#[derive(Serialize)]
+pub struct VideoStatsResponse {
+    pub total: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    pub waiting: u64,
+    pub skipped: u64,
+    pub paid: u64,
+}
+
 #[derive(Serialize)]
 pub struct VideoResponse {
     pub video: VideoInfo,
     pub pages: Vec<PageInfo>,
+    /// 视频的分页总数，不受分页参数影响
+    pub total_pages_count: u64,
 }
 
 #[derive(Serialize)]
@@ -39,6 +53,12 @@ pub struct ClearAndResetVideoStatusResponse {
     pub video: VideoInfo,
 }
 
+#[derive(Serialize)]
+pub struct DeleteVideoFilesResponse {
+    pub warning: Option<String>,
+    pub video: VideoInfo,
+}
+
 #[derive(Serialize)]
 pub struct ResetFilteredVideosResponse {
     pub resetted: bool,
@@ -53,6 +73,113 @@ pub struct UpdateVideoStatusResponse {
     pub pages: Vec<PageInfo>,
 }
 
+#[derive(Serialize)]
+pub struct RetriedTask {
+    /// 分页 ID，为空时表示视频层的任务
+    pub page_id: Option<i32>,
+    pub task_index: usize,
+}
+
+#[derive(Serialize)]
+pub struct RetryAllFailedTasksResponse {
+    pub succeeded: Vec<RetriedTask>,
+    pub failed: Vec<RetriedTask>,
+    pub video: VideoInfo,
+    pub pages: Vec<PageInfo>,
+}
+
+#[derive(Serialize)]
+pub struct RetryFilteredVideoTaskResponse {
+    pub batch: RetryBatch,
+    pub jobs: Vec<RetryJob>,
+}
+
+#[derive(Serialize)]
+pub struct ReconcileVideoResponse {
+    /// 对应文件已存在、被直接标记为成功的子任务
+    pub reconciled: Vec<RetriedTask>,
+    pub video: VideoInfo,
+    pub pages: Vec<PageInfo>,
+}
+
+#[derive(Serialize)]
+pub struct ReconcileFilteredVideosResponse {
+    pub reconciled_videos_count: usize,
+    pub reconciled_pages_count: usize,
+}
+
+#[derive(Serialize)]
+pub struct RefreshMetadataResponse {
+    pub succeeded: Vec<RetriedTask>,
+    pub failed: Vec<RetriedTask>,
+    pub video: VideoInfo,
+    pub pages: Vec<PageInfo>,
+}
+
+#[derive(Serialize)]
+pub struct RefreshFilteredMetadataResponse {
+    pub refreshed_videos_count: usize,
+    /// 刷新过程中执行失败的子任务总数
+    pub failed_tasks_count: usize,
+    /// 无法刷新的视频，单个视频失败不影响其它视频
+    pub failed_videos: Vec<FailedVideo>,
+}
+
+#[derive(Serialize)]
+pub struct FailedVideo {
+    pub id: i32,
+    pub name: String,
+    pub error: String,
+}
+
+#[derive(Serialize)]
+pub struct VideoEventsResponse {
+    pub events: Vec<TaskEventInfo>,
+}
+
+#[derive(Serialize)]
+pub struct VideoProgressResponse {
+    /// 视频未在下载时为空
+    pub progress: Option<VideoProgress>,
+}
+
+#[derive(Serialize, DerivePartialModel, FromQueryResult)]
+#[sea_orm(entity = "task_event::Entity")]
+pub struct TaskEventInfo {
+    pub id: i32,
+    /// 分页 ID，为空时表示视频层的任务
+    pub page_id: Option<i32>,
+    pub task_index: u32,
+    /// 子任务变更后的状态，0b111 表示成功，其余非零值表示失败次数
+    pub status: u32,
+    pub created_at: String,
+}
+
+#[derive(Serialize, DerivePartialModel, FromQueryResult)]
+#[sea_orm(entity = "config_history::Entity")]
+pub struct ConfigHistoryInfo {
+    pub version: i64,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct HealthResponse {
+    /// 数据库是否可用，为 "ok" 或 "error"
+    pub db: &'static str,
+    /// 下载任务调度器是否已启动，为 "ok" 或 "unavailable"
+    pub scheduler: &'static str,
+    /// 距离最近一次下载任务结束的秒数，尚未执行完成过时为空
+    pub last_scan_ago_secs: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct VacuumDatabaseResponse {
+    /// 整理前的数据库文件大小（字节）
+    pub before_size: u64,
+    /// 整理后的数据库文件大小（字节）
+    pub after_size: u64,
+}
+
 #[derive(Serialize)]
 pub struct UpdateFilteredVideoStatusResponse {
     pub success: bool,
@@ -84,11 +211,70 @@ pub struct VideoInfo {
     pub upper_name: String,
     pub should_download: bool,
     pub is_paid_video: bool,
+    pub files_deleted: bool,
     #[serde(serialize_with = "serde_video_download_status")]
     pub download_status: u32,
     pub cover: String,
 }
 
+/// 导出视频列表时每行包含的字段
+#[derive(DerivePartialModel, FromQueryResult)]
+#[sea_orm(entity = "video::Entity")]
+pub struct VideoExportInfo {
+    pub id: i32,
+    pub bvid: String,
+    pub name: String,
+    pub upper_name: String,
+    pub download_status: u32,
+    pub created_at: String,
+}
+
+/// 重复视频中的一条记录，四个视频源 ID 中有且仅有一个不为空
+#[derive(Serialize, DerivePartialModel, FromQueryResult)]
+#[sea_orm(entity = "video::Entity")]
+pub struct DuplicateVideoInfo {
+    pub id: i32,
+    /// 已作为分组的键返回，不再重复序列化
+    #[serde(skip)]
+    pub bvid: String,
+    pub name: String,
+    pub path: String,
+    pub collection_id: Option<i32>,
+    pub favorite_id: Option<i32>,
+    pub watch_later_id: Option<i32>,
+    pub submission_id: Option<i32>,
+}
+
+/// 同一个 bvid 在多个视频源中出现时的记录分组
+#[derive(Serialize)]
+pub struct DuplicateVideoGroup {
+    pub bvid: String,
+    pub videos: Vec<DuplicateVideoInfo>,
+}
+
+#[derive(Serialize)]
+pub struct DuplicateVideosResponse {
+    pub groups: Vec<DuplicateVideoGroup>,
+}
+
+/// 最近下载完成的视频
+#[derive(Serialize)]
+pub struct RecentVideoInfo {
+    pub id: i32,
+    pub bvid: String,
+    pub name: String,
+    pub upper_name: String,
+    pub cover: String,
+    pub created_at: String,
+    /// 所属视频源的展示名称，视频源已被删除时为空
+    pub source_name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct RecentVideosResponse {
+    pub videos: Vec<RecentVideoInfo>,
+}
+
 #[derive(Serialize, DerivePartialModel, FromQueryResult)]
 #[sea_orm(entity = "page::Entity")]
 pub struct PageInfo {
@@ -98,6 +284,8 @@ pub struct PageInfo {
     pub name: String,
     #[serde(serialize_with = "serde_page_download_status")]
     pub download_status: u32,
+    pub file_size: Option<i64>,
+    pub download_duration_ms: Option<i64>,
 }
 
 #[derive(Serialize, DerivePartialModel, FromQueryResult, Clone, Copy)]
@@ -177,6 +365,20 @@ pub struct UppersResponse {
     pub total: i64,
 }
 
+#[derive(Serialize, FromQueryResult)]
+pub struct VideoUpper {
+    pub upper_id: i64,
+    pub upper_name: String,
+    pub upper_face: String,
+    pub video_count: i64,
+}
+
+#[derive(Serialize)]
+pub struct VideoUppersResponse {
+    pub uppers: Vec<VideoUpper>,
+    pub total: u64,
+}
+
 #[derive(Serialize)]
 pub struct VideoSourcesDetailsResponse {
     pub collections: Vec<VideoSourceDetail>,
@@ -224,6 +426,11 @@ pub struct VideoSourceDetail {
     #[serde(default)]
     pub use_dynamic_api: Option<bool>,
     pub enabled: bool,
+    pub audio_only: bool,
+    pub scan_priority: i32,
+    pub video_name: Option<String>,
+    pub scan_interval_secs: Option<u32>,
+    pub scan_cron: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -232,6 +439,30 @@ pub struct UpdateVideoSourceResponse {
     pub rule_display: Option<String>,
 }
 
+#[derive(Serialize)]
+pub struct RelocateVideoSourceResponse {
+    pub moved_files: usize,
+    /// 目标位置已存在同名文件而保留在原位置的文件
+    pub skipped_files: Vec<String>,
+    pub updated_videos: usize,
+    pub updated_pages: usize,
+    /// 存在冲突文件而保留原路径记录的视频与分页数量
+    pub conflicted_videos: usize,
+    pub conflicted_pages: usize,
+}
+
+#[derive(Serialize)]
+pub struct InsertFavoriteResult {
+    pub fid: i64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct InsertFavoritesResponse {
+    pub results: Vec<InsertFavoriteResult>,
+}
+
 pub type GenerateQrcodeResponse = Qrcode;
 
 pub type PollQrcodeResponse = PollStatus;
@@ -1,17 +1,28 @@
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use axum::extract::Extension;
+use axum::extract::{Extension, Path};
+use axum::http::header;
+use axum::response::IntoResponse;
 use axum::routing::{get, post};
 use axum::{Json, Router};
-use sea_orm::DatabaseConnection;
+use bili_sync_entity::*;
+use parking_lot::Mutex;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder};
 
 use serde::Serialize;
 
+use crate::api::error::InnerApiError;
+use crate::api::request::{PreviewCronRequest, PreviewTemplateRequest, SetLogLevelRequest, TemplateTarget};
+use crate::api::response::ConfigHistoryInfo;
 use crate::api::wrapper::{ApiError, ApiResponse, ValidatedJson};
-use crate::bilibili::BiliClient;
-use crate::config::{Config, VersionedConfig};
-use crate::notifier::Notifier;
+use crate::bilibili::{BiliClient, CredentialStatus};
+use crate::config::{CRON_PARSER, Config, VersionedConfig, preview_path_template};
+use crate::notifier::{NOTIFICATION_QUEUE, NotificationQueueStatus, Notifier};
+use crate::task::{CredentialRefreshResult, DownloadTaskManager};
+use crate::utils::format_arg::{page_format_args, video_format_args};
+use crate::utils::set_log_level as reload_log_level;
 
 #[derive(Serialize)]
 pub struct TestNotifierResponse {
@@ -23,9 +34,26 @@ pub struct TestNotifierResponse {
 pub(super) fn router() -> Router {
     Router::new()
         .route("/config", get(get_config).put(update_config))
+        .route("/config/export", get(export_config))
+        .route("/config/import", post(import_config))
+        .route("/config/history", get(get_config_history))
+        .route("/config/rollback/{version}", post(rollback_config))
         .route("/config/notifiers/ping", post(ping_notifiers))
+        .route("/config/notifiers/queue-status", get(get_notification_queue_status))
+        .route("/config/credential/status", get(get_credential_status))
+        .route("/config/credential/refresh", post(refresh_credential))
+        .route("/config/template/preview", post(preview_template))
+        .route("/config/cron/preview", post(preview_cron))
+        .route("/config/log-level", post(set_log_level))
 }
 
+/// 凭据状态的缓存时间，避免前端频繁刷新时反复请求 B 站接口
+const CREDENTIAL_STATUS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// 缓存的凭据状态，与检查时使用的 SESSDATA 绑定，凭据变化后缓存自动失效
+static CREDENTIAL_STATUS_CACHE: LazyLock<Mutex<Option<(Instant, String, CredentialStatus)>>> =
+    LazyLock::new(Default::default);
+
 /// 获取全局配置
 pub async fn get_config() -> Result<ApiResponse<Arc<Config>>, ApiError> {
     Ok(ApiResponse::ok(VersionedConfig::get().snapshot()))
@@ -41,6 +69,157 @@ pub async fn update_config(
     Ok(ApiResponse::ok(new_config))
 }
 
+/// 以 JSON 文件的形式导出完整的配置（包含凭据与 auth token 等敏感信息），用于迁移到其它实例
+pub async fn export_config() -> Result<axum::response::Response, ApiError> {
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"bili-sync-config.json\"",
+        )],
+        Json(VersionedConfig::get().snapshot()),
+    )
+        .into_response())
+}
+
+/// 导入其它实例导出的配置，校验通过后覆盖当前配置
+pub async fn import_config(
+    Extension(db): Extension<DatabaseConnection>,
+    ValidatedJson(config): ValidatedJson<Config>,
+) -> Result<ApiResponse<Arc<Config>>, ApiError> {
+    config.check()?;
+    let new_config = VersionedConfig::get().import(config, &db).await?;
+    Ok(ApiResponse::ok(new_config))
+}
+
+/// 获取保存过的配置历史，按版本号从新到旧排列
+pub async fn get_config_history(
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<ApiResponse<Vec<ConfigHistoryInfo>>, ApiError> {
+    let history = config_history::Entity::find()
+        .order_by_desc(config_history::Column::Version)
+        .into_partial_model::<ConfigHistoryInfo>()
+        .all(&db)
+        .await?;
+    Ok(ApiResponse::ok(history))
+}
+
+/// 将配置回滚到历史中的某个版本，回滚后的配置会获得新的版本号
+pub async fn rollback_config(
+    Extension(db): Extension<DatabaseConnection>,
+    Path(version): Path<u64>,
+) -> Result<ApiResponse<Arc<Config>>, ApiError> {
+    let new_config = VersionedConfig::get().rollback(version, &db).await?;
+    Ok(ApiResponse::ok(new_config))
+}
+
+/// 获取通知消息队列的积压情况，用于排查通知延迟的原因
+pub async fn get_notification_queue_status() -> Result<ApiResponse<NotificationQueueStatus>, ApiError> {
+    Ok(ApiResponse::ok(NOTIFICATION_QUEUE.status()))
+}
+
+/// 检查当前凭据是否有效，仅查询不会修改已保存的凭据
+pub async fn get_credential_status(
+    Extension(bili_client): Extension<Arc<BiliClient>>,
+) -> Result<ApiResponse<CredentialStatus>, ApiError> {
+    let config = VersionedConfig::get().snapshot();
+    if let Some((checked_at, sessdata, status)) = CREDENTIAL_STATUS_CACHE.lock().as_ref()
+        && checked_at.elapsed() < CREDENTIAL_STATUS_CACHE_TTL
+        && *sessdata == config.credential.sessdata
+    {
+        return Ok(ApiResponse::ok(status.clone()));
+    }
+    let status = bili_client.credential_status(&config.credential).await?;
+    *CREDENTIAL_STATUS_CACHE.lock() = Some((Instant::now(), config.credential.sessdata.clone(), status.clone()));
+    Ok(ApiResponse::ok(status))
+}
+
+/// 立即检查并在需要时刷新所有账号的凭据，下载任务执行期间无法手动刷新
+pub async fn refresh_credential() -> Result<ApiResponse<CredentialRefreshResult>, ApiError> {
+    let Some(result) = DownloadTaskManager::get().refresh_credential().await else {
+        return Err(InnerApiError::Conflict("下载任务正在执行，请稍后再试".to_owned()).into());
+    };
+    // 凭据可能已经变化，清空状态缓存以便前端获取到最新的状态
+    CREDENTIAL_STATUS_CACHE.lock().take();
+    Ok(ApiResponse::ok(result))
+}
+
+/// 在运行时调整日志等级，无需重启，重启后恢复为启动参数中的日志等级
+pub async fn set_log_level(Json(request): Json<SetLogLevelRequest>) -> Result<ApiResponse<String>, ApiError> {
+    reload_log_level(&request.level).map_err(|e| InnerApiError::BadRequest(format!("{:#}", e)))?;
+    info!("日志等级已调整为「{}」", &request.level);
+    Ok(ApiResponse::ok(request.level))
+}
+
+/// 单次预览最多返回的触发时间数量
+const CRON_PREVIEW_MAX_COUNT: usize = 20;
+
+/// 解析 Cron 表达式并返回接下来的若干次触发时间（本地时间），便于在保存配置前确认表达式的含义
+pub async fn preview_cron(Json(request): Json<PreviewCronRequest>) -> Result<ApiResponse<Vec<String>>, ApiError> {
+    let cron = CRON_PARSER
+        .parse(&request.cron)
+        .map_err(|e| InnerApiError::BadRequest(format!("Cron 表达式无效：{}", e)))?;
+    let count = request.count.unwrap_or(5).clamp(1, CRON_PREVIEW_MAX_COUNT);
+    let mut fire_times = Vec::with_capacity(count);
+    let mut time = chrono::Local::now();
+    for _ in 0..count {
+        time = cron
+            .find_next_occurrence(&time, false)
+            .map_err(|e| InnerApiError::BadRequest(format!("无法计算 Cron 表达式的触发时间：{}", e)))?;
+        fire_times.push(time.format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+    Ok(ApiResponse::ok(fire_times))
+}
+
+/// 使用示例视频与分页预览候选的文件名模板，便于在保存配置前发现模板错误
+pub async fn preview_template(
+    Extension(db): Extension<DatabaseConnection>,
+    Json(request): Json<PreviewTemplateRequest>,
+) -> Result<ApiResponse<String>, ApiError> {
+    let video_model = match request.video_id {
+        Some(id) => video::Entity::find_by_id(id)
+            .one(&db)
+            .await?
+            .ok_or(InnerApiError::NotFound(id))?,
+        None => sample_video(),
+    };
+    let page_model = match (request.target, request.page_id) {
+        (TemplateTarget::Page, Some(id)) => page::Entity::find_by_id(id)
+            .one(&db)
+            .await?
+            .ok_or(InnerApiError::NotFound(id))?,
+        _ => sample_page(),
+    };
+    let config = VersionedConfig::get().read();
+    let data = match request.target {
+        TemplateTarget::Video => video_format_args(&video_model, &config.time_format),
+        TemplateTarget::Page => page_format_args(&video_model, &page_model, &config.time_format),
+    };
+    let path = preview_path_template(&request.template, &data)
+        .map_err(|e| InnerApiError::TemplateError(format!("{:#}", e)))?;
+    Ok(ApiResponse::ok(path))
+}
+
+fn sample_video() -> video::Model {
+    let now = chrono::Local::now().naive_local();
+    video::Model {
+        bvid: "BV1GJ411x7h7".to_owned(),
+        name: "示例视频".to_owned(),
+        upper_id: 1,
+        upper_name: "示例 UP 主".to_owned(),
+        pubtime: now,
+        favtime: now,
+        ..Default::default()
+    }
+}
+
+fn sample_page() -> page::Model {
+    page::Model {
+        pid: 1,
+        name: "示例分页".to_owned(),
+        ..Default::default()
+    }
+}
+
 pub async fn ping_notifiers(
     Extension(bili_client): Extension<Arc<BiliClient>>,
     Json(mut notifier): Json<Notifier>,
@@ -54,7 +233,7 @@ pub async fn ping_notifiers(
     
     // 尝试发送通知并捕获详细错误（测试消息不参与“重复消息去重”）
     match notifier
-        .notify_without_cache(bili_client.inner_client(), test_message)
+        .notify_without_cache(bili_client.notifier_client(), test_message)
         .await
     {
         Ok(_) => {
@@ -64,6 +243,9 @@ pub async fn ping_notifiers(
                 details: match &notifier {
                     Notifier::Telegram { .. } => Some("请检查 Telegram 是否收到消息".to_string()),
                     Notifier::Webhook { url, .. } => Some(format!("已发送到: {}", url)),
+                    Notifier::Gotify { server, .. } => Some(format!("已推送到 Gotify 服务器: {}", server)),
+                    Notifier::ServerChan { .. } => Some("请检查 Server酱 绑定的渠道是否收到消息".to_string()),
+                    Notifier::WeComBot { .. } => Some("请检查企业微信群是否收到消息".to_string()),
                 },
             }))
         }
@@ -76,6 +258,14 @@ pub async fn ping_notifiers(
                 Notifier::Webhook { url, .. } => {
                     Some(format!("请检查 Webhook URL ({}) 是否可访问，以及模板格式是否正确", url))
                 }
+                Notifier::Gotify { server, .. } => Some(format!(
+                    "请检查 Gotify 服务器地址 ({}) 是否可访问，以及 App Token 是否正确",
+                    server
+                )),
+                Notifier::ServerChan { .. } => Some("请检查 SendKey 是否正确，以及网络连接是否正常".to_string()),
+                Notifier::WeComBot { .. } => {
+                    Some("请检查群机器人的 Webhook Key 是否正确，以及网络连接是否正常".to_string())
+                }
             };
             
             Ok(ApiResponse::ok(TestNotifierResponse {
@@ -86,3 +276,52 @@ pub async fn ping_notifiers(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Timelike;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_preview_cron() {
+        let request = |cron: &str, count: Option<usize>| {
+            Json(PreviewCronRequest {
+                cron: cron.to_owned(),
+                count,
+            })
+        };
+        let Ok(response) = preview_cron(request("0 0 9 * * *", Some(3))).await else {
+            panic!("failed to preview cron expression");
+        };
+        let response = serde_json::to_value(response).expect("failed to serialize response");
+        let fire_times = response["data"]
+            .as_array()
+            .expect("data should be an array")
+            .iter()
+            .map(|time| {
+                chrono::NaiveDateTime::parse_from_str(
+                    time.as_str().expect("time should be a string"),
+                    "%Y-%m-%d %H:%M:%S",
+                )
+                .expect("time should be formatted")
+            })
+            .collect::<Vec<_>>();
+        // 每天早上 9 点触发，相邻两次间隔一天
+        assert_eq!(fire_times.len(), 3);
+        assert!(
+            fire_times
+                .iter()
+                .all(|time| (time.hour(), time.minute(), time.second()) == (9, 0, 0))
+        );
+        assert!(
+            fire_times
+                .windows(2)
+                .all(|w| w[0].date().succ_opt() == Some(w[1].date()))
+        );
+        assert!(fire_times[0] > chrono::Local::now().naive_local());
+        // 缺少秒字段或包含无效值的表达式返回错误
+        assert!(preview_cron(request("0 9 * * *", None)).await.is_err());
+        assert!(preview_cron(request("0 0 25 * * *", None)).await.is_err());
+    }
+}
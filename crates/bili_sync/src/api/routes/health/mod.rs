@@ -0,0 +1,62 @@
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use reqwest::StatusCode;
+use sea_orm::{ConnectionTrait, DatabaseConnection};
+
+use crate::api::response::HealthResponse;
+use crate::task::{DownloadTaskManager, TaskStatus};
+
+pub(super) fn router() -> Router {
+    Router::new().route("/healthz", get(healthz))
+}
+
+/// 检查数据库与下载任务调度器的状态，数据库不可用时返回 503
+pub async fn healthz(Extension(db): Extension<DatabaseConnection>) -> (StatusCode, Json<HealthResponse>) {
+    let task_status = DownloadTaskManager::try_get().map(|task_manager| *task_manager.subscribe().borrow());
+    health_check(&db, task_status).await
+}
+
+async fn health_check(db: &DatabaseConnection, task_status: Option<TaskStatus>) -> (StatusCode, Json<HealthResponse>) {
+    let db_ok = match db.execute_unprepared("SELECT 1;").await {
+        Ok(_) => true,
+        Err(e) => {
+            warn!("健康检查时数据库查询失败：{:#}", e);
+            false
+        }
+    };
+    let last_scan_ago_secs = task_status
+        .and_then(|status| status.last_finish())
+        .map(|last_finish| (chrono::Local::now() - last_finish).num_seconds());
+    let response = HealthResponse {
+        db: if db_ok { "ok" } else { "error" },
+        scheduler: if task_status.is_some() { "ok" } else { "unavailable" },
+        last_scan_ago_secs,
+    };
+    let status_code = if db_ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status_code, Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::TestDatabase;
+
+    #[tokio::test]
+    async fn test_health_check() -> anyhow::Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let (status_code, Json(response)) = health_check(&connection, Some(TaskStatus::default())).await;
+        assert_eq!(status_code, StatusCode::OK);
+        assert_eq!((response.db, response.scheduler), ("ok", "ok"));
+        assert!(response.last_scan_ago_secs.is_none());
+        // 数据库不可用时探针失败
+        let (status_code, Json(response)) = health_check(&DatabaseConnection::Disconnected, None).await;
+        assert_eq!(status_code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!((response.db, response.scheduler), ("error", "unavailable"));
+        Ok(())
+    }
+}
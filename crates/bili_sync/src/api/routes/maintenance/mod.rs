@@ -0,0 +1,25 @@
+use anyhow::Result;
+use axum::Router;
+use axum::routing::post;
+
+use crate::api::error::InnerApiError;
+use crate::api::response::VacuumDatabaseResponse;
+use crate::api::wrapper::{ApiError, ApiResponse};
+use crate::task::DownloadTaskManager;
+
+pub(super) fn router() -> Router {
+    Router::new().route("/maintenance/vacuum", post(vacuum_database))
+}
+
+/// 整理数据库以回收删除数据后留下的空间，下载任务执行期间无法执行
+pub async fn vacuum_database() -> Result<ApiResponse<VacuumDatabaseResponse>, ApiError> {
+    let Some(result) = DownloadTaskManager::get().vacuum_database().await else {
+        return Err(InnerApiError::Conflict("下载任务正在执行，请稍后再试".to_owned()).into());
+    };
+    let (before_size, after_size) = result?;
+    info!("数据库整理完成，大小由 {} 字节变为 {} 字节", before_size, after_size);
+    Ok(ApiResponse::ok(VacuumDatabaseResponse {
+        before_size,
+        after_size,
+    }))
+}
@@ -12,9 +12,12 @@ use crate::config::VersionedConfig;
 
 mod config;
 mod dashboard;
+mod health;
 mod login;
+mod maintenance;
 mod me;
 mod task;
+mod uppers;
 mod video_sources;
 mod videos;
 mod ws;
@@ -22,7 +25,8 @@ mod ws;
 pub use ws::{LogHelper, MAX_HISTORY_LOGS};
 
 pub fn router() -> Router {
-    Router::new().nest(
+    // 健康检查供容器编排等外部探针使用，无需鉴权
+    health::router().nest(
         "/api",
         config::router()
             .merge(me::router())
@@ -32,6 +36,8 @@ pub fn router() -> Router {
             .merge(dashboard::router())
             .merge(ws::router())
             .merge(task::router())
+            .merge(uppers::router())
+            .merge(maintenance::router())
             .layer(middleware::from_fn(auth)),
     )
 }
@@ -1,15 +1,165 @@
+use std::sync::Arc;
+
 use anyhow::Result;
 use axum::Router;
-use axum::routing::post;
+use axum::extract::{Extension, Path, Query};
+use axum::routing::{delete, get, post};
+use bili_sync_entity::*;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
 
-use crate::api::wrapper::{ApiError, ApiResponse};
-use crate::task::DownloadTaskManager;
+use crate::api::error::InnerApiError;
+use crate::api::request::{DownloadTaskRequest, EnqueueRetriesRequest};
+use crate::api::routes::videos::retry_failed_tasks;
+use crate::api::wrapper::{ApiError, ApiResponse, ValidatedJson};
+use crate::bilibili::BiliClient;
+use crate::config::VersionedConfig;
+use crate::notifier::NOTIFICATION_QUEUE;
+use crate::task::{
+    DownloadTaskManager, RETRY_QUEUE, RetryBatch, RetryJob, enqueue_daily_summary, generate_daily_summary,
+};
 
 pub(super) fn router() -> Router {
-    Router::new().route("/task/download", post(new_download_task))
+    Router::new()
+        .route("/task/download", post(new_download_task))
+        .route("/task/pause", post(pause_download_task))
+        .route("/task/resume", post(resume_download_task))
+        .route("/task/daily-summary/run", post(run_daily_summary))
+        .route("/tasks/retries", get(get_retries).post(enqueue_retries))
+        .route("/tasks/retries/{id}", delete(cancel_retry))
+        .route("/tasks/retries/batches/{id}", get(get_retry_batch))
+}
+
+pub async fn new_download_task(Query(params): Query<DownloadTaskRequest>) -> Result<ApiResponse<bool>, ApiError> {
+    let task_manager = DownloadTaskManager::get();
+    ensure_manual_trigger_allowed(task_manager)?;
+    task_manager.download_once(params.dry_run).await?;
+    Ok(ApiResponse::ok(true))
+}
+
+/// 检查当前是否允许手动触发下载任务
+pub(super) fn ensure_manual_trigger_allowed(task_manager: &DownloadTaskManager) -> Result<(), ApiError> {
+    if task_manager.is_paused() {
+        return Err(InnerApiError::Conflict("下载任务已暂停，请先恢复后再执行".to_owned()).into());
+    }
+    // 风控冷却期间拒绝手动触发，避免继续请求延长风控时间
+    if let Some(cooldown_until) = task_manager.cooldown_until() {
+        return Err(InnerApiError::Conflict(format!(
+            "风控冷却中，将于 {} 结束",
+            cooldown_until.format("%Y-%m-%d %H:%M:%S")
+        ))
+        .into());
+    }
+    Ok(())
 }
 
-pub async fn new_download_task() -> Result<ApiResponse<bool>, ApiError> {
-    DownloadTaskManager::get().download_once().await?;
+/// 暂停视频下载任务，正在执行中的任务不受影响
+pub async fn pause_download_task() -> Result<ApiResponse<bool>, ApiError> {
+    DownloadTaskManager::get().pause();
     Ok(ApiResponse::ok(true))
 }
+
+pub async fn resume_download_task() -> Result<ApiResponse<bool>, ApiError> {
+    DownloadTaskManager::get().resume();
+    Ok(ApiResponse::ok(true))
+}
+
+/// 立即生成每日汇总并加入通知队列，便于调试汇总消息的格式，返回生成的汇总消息
+pub async fn run_daily_summary(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(bili_client): Extension<Arc<BiliClient>>,
+) -> Result<ApiResponse<String>, ApiError> {
+    let config = VersionedConfig::get().snapshot();
+    let summary = generate_daily_summary(&db, &config.message_templates).await?;
+    let (message, client) = (summary.message.clone(), bili_client.notifier_client().clone());
+    if !enqueue_daily_summary(&config, &NOTIFICATION_QUEUE, client, summary, true) {
+        info!("未开启每日汇总通知或未配置通知器，每日汇总仅生成不发送");
+    }
+    Ok(ApiResponse::ok(message))
+}
+
+/// 列出重试队列中正在执行与等待执行的任务
+pub async fn get_retries() -> Result<ApiResponse<Vec<RetryJob>>, ApiError> {
+    Ok(ApiResponse::ok(RETRY_QUEUE.list()))
+}
+
+/// 将视频加入重试队列，由后台逐个重试其所有失败的任务，收费视频与无需下载的视频会被跳过
+pub async fn enqueue_retries(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(bili_client): Extension<Arc<BiliClient>>,
+    ValidatedJson(request): ValidatedJson<EnqueueRetriesRequest>,
+) -> Result<ApiResponse<Vec<RetryJob>>, ApiError> {
+    let videos = video::Entity::find()
+        .filter(video::Column::Id.is_in(request.video_ids))
+        .filter(video::Column::IsPaidVideo.eq(false))
+        .filter(video::Column::ShouldDownload.eq(true))
+        .order_by_asc(video::Column::Id)
+        .all(&db)
+        .await?;
+    for video in videos {
+        let (db, bili_client) = (db.clone(), bili_client.clone());
+        let (video_id, video_name) = (video.id, video.name.clone());
+        RETRY_QUEUE.enqueue(video.id, video.name, async move {
+            if let Err(e) = retry_failed_tasks(video_id, db, bili_client).await {
+                error!("重试视频「{}」的失败任务出错：{:#}", video_name, e);
+            }
+        });
+    }
+    Ok(ApiResponse::ok(RETRY_QUEUE.list()))
+}
+
+/// 查询批量重试的执行结果
+pub async fn get_retry_batch(Path(id): Path<i32>) -> Result<ApiResponse<RetryBatch>, ApiError> {
+    match RETRY_QUEUE.batch(id) {
+        Some(batch) => Ok(ApiResponse::ok(batch)),
+        None => Err(InnerApiError::NotFound(id).into()),
+    }
+}
+
+/// 取消尚未开始执行的重试任务，正在执行的任务无法取消
+pub async fn cancel_retry(Path(id): Path<i32>) -> Result<ApiResponse<RetryJob>, ApiError> {
+    if let Some(job) = RETRY_QUEUE.cancel(id) {
+        return Ok(ApiResponse::ok(job));
+    }
+    if RETRY_QUEUE.list().iter().any(|job| job.id == id) {
+        return Err(InnerApiError::Conflict("重试任务正在执行，无法取消".to_owned()).into());
+    }
+    Err(InnerApiError::NotFound(id).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use sea_orm::ActiveModelTrait;
+    use sea_orm::ActiveValue::Set;
+
+    use super::*;
+    use crate::utils::test_utils::{TestDatabase, test_favorite, test_video};
+
+    #[tokio::test]
+    async fn test_run_daily_summary() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let favorite = test_favorite(db.dir.join("videos").to_string_lossy().to_string())
+            .insert(&connection)
+            .await?;
+        video::ActiveModel {
+            favorite_id: Set(Some(favorite.id)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        let expected = generate_daily_summary(&connection, &HashMap::new()).await?.message;
+        // 连续手动触发时每次都返回完整的汇总消息
+        for _ in 0..2 {
+            let Ok(response) =
+                run_daily_summary(Extension(connection.clone()), Extension(Arc::new(BiliClient::new()?))).await
+            else {
+                panic!("failed to run daily summary");
+            };
+            let response = serde_json::to_value(response)?;
+            assert_eq!(response["data"], expected.as_str());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use axum::Router;
+use axum::extract::{Extension, Query};
+use axum::routing::get;
+use bili_sync_entity::*;
+use sea_orm::entity::prelude::*;
+use sea_orm::{Order, QueryOrder, QuerySelect};
+
+use crate::api::request::VideoUppersRequest;
+use crate::api::response::{VideoUpper, VideoUppersResponse};
+use crate::api::wrapper::{ApiError, ApiResponse};
+
+pub(super) fn router() -> Router {
+    Router::new().route("/uppers", get(get_video_uppers))
+}
+
+/// 按 UP 主聚合本地已有的视频，返回每个 UP 主的视频数量，按视频数量从多到少排列
+pub async fn get_video_uppers(
+    Extension(db): Extension<DatabaseConnection>,
+    Query(params): Query<VideoUppersRequest>,
+) -> Result<ApiResponse<VideoUppersResponse>, ApiError> {
+    let (page_num, page_size) = (params.page_num.unwrap_or(1).max(1), params.page_size.unwrap_or(20));
+    let mut query = video::Entity::find()
+        .select_only()
+        .column(video::Column::UpperId)
+        // UP 主可能修改过名称与头像，借助 sqlite 中 MAX 聚合时其余列取自最大值所在行的特性，取最近入库的视频中的信息
+        .column_as(video::Column::Id.max(), "latest_video_id")
+        .column(video::Column::UpperName)
+        .column(video::Column::UpperFace)
+        .column_as(video::Column::Id.count(), "video_count")
+        .group_by(video::Column::UpperId);
+    if let Some(name) = params.name.as_deref().filter(|name| !name.is_empty()) {
+        query = query.filter(video::Column::UpperName.contains(name));
+    }
+    let paginator = query
+        .order_by(Expr::cust("video_count"), Order::Desc)
+        .order_by_asc(video::Column::UpperId)
+        .into_model::<VideoUpper>()
+        .paginate(&db, page_size);
+    let total = paginator.num_items().await?;
+    let uppers = paginator.fetch_page(page_num - 1).await?;
+    Ok(ApiResponse::ok(VideoUppersResponse { uppers, total }))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use sea_orm::ActiveValue::Set;
+
+    use super::*;
+    use crate::utils::test_utils::{TestDatabase, test_video};
+
+    #[tokio::test]
+    async fn test_get_video_uppers() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        for (index, (upper_id, upper_name)) in [(1, "Alice"), (2, "Bob"), (1, "Alice"), (1, "Alice 改名")]
+            .into_iter()
+            .enumerate()
+        {
+            let bvid = format!("BV1test{:05}", index);
+            video::ActiveModel {
+                upper_id: Set(upper_id),
+                upper_name: Set(upper_name.to_string()),
+                upper_face: Set(format!("https://example.com/face{}.jpg", index)),
+                name: Set(bvid.clone()),
+                ..test_video(&bvid)
+            }
+            .insert(&connection)
+            .await?;
+        }
+        let list = |params: VideoUppersRequest| {
+            let connection = connection.clone();
+            async move {
+                let Ok(response) = get_video_uppers(Extension(connection), Query(params)).await else {
+                    panic!("failed to list uppers");
+                };
+                serde_json::to_value(response).expect("failed to serialize response")["data"].clone()
+            }
+        };
+        let response = list(VideoUppersRequest::default()).await;
+        assert_eq!(response["total"], 2);
+        let uppers = response["uppers"].as_array().expect("uppers should be an array");
+        // 按视频数量从多到少排列，名称与头像取自最近入库的视频
+        assert_eq!(uppers[0]["upper_id"], 1);
+        assert_eq!(uppers[0]["upper_name"], "Alice 改名");
+        assert_eq!(uppers[0]["upper_face"], "https://example.com/face3.jpg");
+        assert_eq!(uppers[0]["video_count"], 3);
+        assert_eq!(uppers[1]["upper_id"], 2);
+        assert_eq!(uppers[1]["video_count"], 1);
+        // 分页
+        let response = list(VideoUppersRequest {
+            page_num: Some(2),
+            page_size: Some(1),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(response["total"], 2);
+        assert_eq!(response["uppers"][0]["upper_id"], 2);
+        // 按名称搜索时只统计名称匹配的视频
+        let response = list(VideoUppersRequest {
+            name: Some("bob".to_string()),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(response["total"], 1);
+        assert_eq!(response["uppers"][0]["upper_name"], "Bob");
+        Ok(())
+    }
+}
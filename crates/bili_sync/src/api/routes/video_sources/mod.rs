@@ -1,28 +1,33 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use axum::Router;
 use axum::extract::{Extension, Path, Query};
 use axum::routing::{get, post, put};
+use axum::{Json, Router};
 use bili_sync_entity::rule::Rule;
 use bili_sync_entity::*;
 use bili_sync_migration::Expr;
 use sea_orm::ActiveValue::Set;
 use sea_orm::entity::prelude::*;
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QuerySelect, QueryTrait, TransactionTrait};
+use validator::Validate;
 
 use crate::adapter::{_ActiveModel, VideoSource as _, VideoSourceEnum};
 use crate::api::error::InnerApiError;
 use crate::api::request::{
     DefaultPathRequest, InsertCollectionRequest, InsertFavoriteRequest, InsertSubmissionRequest,
-    UpdateVideoSourceRequest,
+    RelocateVideoSourceRequest, UpdateVideoSourceRequest,
 };
 use crate::api::response::{
-    UpdateVideoSourceResponse, VideoSource, VideoSourceDetail, VideoSourcesDetailsResponse, VideoSourcesResponse,
+    InsertFavoriteResult, InsertFavoritesResponse, RelocateVideoSourceResponse, UpdateVideoSourceResponse, VideoSource,
+    VideoSourceDetail, VideoSourcesDetailsResponse, VideoSourcesResponse,
 };
+use crate::api::routes::task::ensure_manual_trigger_allowed;
 use crate::api::wrapper::{ApiError, ApiResponse, ValidatedJson};
 use crate::bilibili::{BiliClient, Collection, CollectionItem, FavoriteList, Submission};
 use crate::config::{PathSafeTemplate, TEMPLATE, VersionedConfig};
+use crate::task::{DownloadTaskManager, ScanSourceResult};
+use crate::utils::relocate::relocate_video_source;
 use crate::utils::rule::FieldEvaluatable;
 
 pub(super) fn router() -> Router {
@@ -38,7 +43,10 @@ pub(super) fn router() -> Router {
             put(update_video_source).delete(remove_video_source),
         )
         .route("/video-sources/{type}/{id}/evaluate", post(evaluate_video_source))
+        .route("/video-sources/{type}/{id}/scan", post(scan_video_source))
+        .route("/video-sources/{type}/{id}/relocate", post(relocate_video_source_files))
         .route("/video-sources/favorites", post(insert_favorite))
+        .route("/video-sources/favorites/batch", post(insert_favorites))
         .route("/video-sources/collections", post(insert_collection))
         .route("/video-sources/submissions", post(insert_submission))
 }
@@ -109,7 +117,12 @@ pub async fn get_video_sources_details(
                 collection::Column::Name,
                 collection::Column::Path,
                 collection::Column::Rule,
-                collection::Column::Enabled
+                collection::Column::Enabled,
+                collection::Column::AudioOnly,
+                collection::Column::ScanPriority,
+                collection::Column::VideoName,
+                collection::Column::ScanIntervalSecs,
+                collection::Column::ScanCron
             ])
             .into_model::<VideoSourceDetail>()
             .all(&db),
@@ -120,7 +133,12 @@ pub async fn get_video_sources_details(
                 favorite::Column::Name,
                 favorite::Column::Path,
                 favorite::Column::Rule,
-                favorite::Column::Enabled
+                favorite::Column::Enabled,
+                favorite::Column::AudioOnly,
+                favorite::Column::ScanPriority,
+                favorite::Column::VideoName,
+                favorite::Column::ScanIntervalSecs,
+                favorite::Column::ScanCron
             ])
             .into_model::<VideoSourceDetail>()
             .all(&db),
@@ -132,7 +150,12 @@ pub async fn get_video_sources_details(
                 submission::Column::Path,
                 submission::Column::Enabled,
                 submission::Column::Rule,
-                submission::Column::UseDynamicApi
+                submission::Column::UseDynamicApi,
+                submission::Column::AudioOnly,
+                submission::Column::ScanPriority,
+                submission::Column::VideoName,
+                submission::Column::ScanIntervalSecs,
+                submission::Column::ScanCron
             ])
             .into_model::<VideoSourceDetail>()
             .all(&db),
@@ -143,7 +166,12 @@ pub async fn get_video_sources_details(
                 watch_later::Column::Id,
                 watch_later::Column::Path,
                 watch_later::Column::Enabled,
-                watch_later::Column::Rule
+                watch_later::Column::Rule,
+                watch_later::Column::AudioOnly,
+                watch_later::Column::ScanPriority,
+                watch_later::Column::VideoName,
+                watch_later::Column::ScanIntervalSecs,
+                watch_later::Column::ScanCron
             ])
             .into_model::<VideoSourceDetail>()
             .all(&db)
@@ -157,6 +185,11 @@ pub async fn get_video_sources_details(
             rule_display: None,
             use_dynamic_api: None,
             enabled: false,
+            audio_only: false,
+            scan_priority: 0,
+            video_name: None,
+            scan_interval_secs: None,
+            scan_cron: None,
         })
     }
     for sources in [&mut collections, &mut favorites, &mut submissions, &mut watch_later] {
@@ -197,12 +230,38 @@ pub async fn update_video_source(
     ValidatedJson(request): ValidatedJson<UpdateVideoSourceRequest>,
 ) -> Result<ApiResponse<UpdateVideoSourceResponse>, ApiError> {
     let rule_display = request.rule.as_ref().map(|rule| rule.to_string());
+    // 空字符串表示清除视频源单独设置的模板
+    let video_name = request
+        .video_name
+        .map(|video_name| Some(video_name).filter(|video_name| !video_name.trim().is_empty()));
+    // 0 与空字符串表示清除视频源单独设置的扫描计划
+    let scan_interval_secs = request
+        .scan_interval_secs
+        .map(|secs| Some(secs).filter(|secs| *secs > 0));
+    let scan_cron = request
+        .scan_cron
+        .map(|scan_cron| Some(scan_cron).filter(|scan_cron| !scan_cron.trim().is_empty()));
     let active_model = match source_type.as_str() {
         "collections" => collection::Entity::find_by_id(id).one(&db).await?.map(|model| {
             let mut active_model: collection::ActiveModel = model.into();
             active_model.path = Set(request.path);
             active_model.enabled = Set(request.enabled);
             active_model.rule = Set(request.rule);
+            if let Some(audio_only) = request.audio_only {
+                active_model.audio_only = Set(audio_only);
+            }
+            if let Some(scan_priority) = request.scan_priority {
+                active_model.scan_priority = Set(scan_priority);
+            }
+            if let Some(video_name) = &video_name {
+                active_model.video_name = Set(video_name.clone());
+            }
+            if let Some(scan_interval_secs) = scan_interval_secs {
+                active_model.scan_interval_secs = Set(scan_interval_secs);
+            }
+            if let Some(scan_cron) = &scan_cron {
+                active_model.scan_cron = Set(scan_cron.clone());
+            }
             _ActiveModel::Collection(active_model)
         }),
         "favorites" => favorite::Entity::find_by_id(id).one(&db).await?.map(|model| {
@@ -210,6 +269,21 @@ pub async fn update_video_source(
             active_model.path = Set(request.path);
             active_model.enabled = Set(request.enabled);
             active_model.rule = Set(request.rule);
+            if let Some(audio_only) = request.audio_only {
+                active_model.audio_only = Set(audio_only);
+            }
+            if let Some(scan_priority) = request.scan_priority {
+                active_model.scan_priority = Set(scan_priority);
+            }
+            if let Some(video_name) = &video_name {
+                active_model.video_name = Set(video_name.clone());
+            }
+            if let Some(scan_interval_secs) = scan_interval_secs {
+                active_model.scan_interval_secs = Set(scan_interval_secs);
+            }
+            if let Some(scan_cron) = &scan_cron {
+                active_model.scan_cron = Set(scan_cron.clone());
+            }
             _ActiveModel::Favorite(active_model)
         }),
         "submissions" => submission::Entity::find_by_id(id).one(&db).await?.map(|model| {
@@ -217,6 +291,21 @@ pub async fn update_video_source(
             active_model.path = Set(request.path);
             active_model.enabled = Set(request.enabled);
             active_model.rule = Set(request.rule);
+            if let Some(audio_only) = request.audio_only {
+                active_model.audio_only = Set(audio_only);
+            }
+            if let Some(scan_priority) = request.scan_priority {
+                active_model.scan_priority = Set(scan_priority);
+            }
+            if let Some(video_name) = &video_name {
+                active_model.video_name = Set(video_name.clone());
+            }
+            if let Some(scan_interval_secs) = scan_interval_secs {
+                active_model.scan_interval_secs = Set(scan_interval_secs);
+            }
+            if let Some(scan_cron) = &scan_cron {
+                active_model.scan_cron = Set(scan_cron.clone());
+            }
             if let Some(use_dynamic_api) = request.use_dynamic_api {
                 active_model.use_dynamic_api = Set(use_dynamic_api);
             }
@@ -231,6 +320,21 @@ pub async fn update_video_source(
                 active_model.path = Set(request.path);
                 active_model.enabled = Set(request.enabled);
                 active_model.rule = Set(request.rule);
+                if let Some(audio_only) = request.audio_only {
+                    active_model.audio_only = Set(audio_only);
+                }
+                if let Some(scan_priority) = request.scan_priority {
+                    active_model.scan_priority = Set(scan_priority);
+                }
+                if let Some(video_name) = &video_name {
+                    active_model.video_name = Set(video_name.clone());
+                }
+                if let Some(scan_interval_secs) = scan_interval_secs {
+                    active_model.scan_interval_secs = Set(scan_interval_secs);
+                }
+                if let Some(scan_cron) = &scan_cron {
+                    active_model.scan_cron = Set(scan_cron.clone());
+                }
                 Some(_ActiveModel::WatchLater(active_model))
             }
             None => {
@@ -242,6 +346,11 @@ pub async fn update_video_source(
                         path: Set(request.path),
                         enabled: Set(request.enabled),
                         rule: Set(request.rule),
+                        audio_only: Set(request.audio_only.unwrap_or_default()),
+                        scan_priority: Set(request.scan_priority.unwrap_or_default()),
+                        video_name: Set(video_name.flatten()),
+                        scan_interval_secs: Set(scan_interval_secs.flatten()),
+                        scan_cron: Set(scan_cron.flatten()),
                         ..Default::default()
                     }))
                 }
@@ -253,9 +362,97 @@ pub async fn update_video_source(
         return Err(InnerApiError::NotFound(id).into());
     };
     active_model.save(&db).await?;
+    // 扫描计划与启用状态都会影响视频源单独的下载任务，保存后重新创建
+    if let Some(task_manager) = DownloadTaskManager::try_get() {
+        task_manager.reload_scan_schedules().await?;
+    }
     Ok(ApiResponse::ok(UpdateVideoSourceResponse { rule_display }))
 }
 
+/// 在后台立即扫描并下载单个视频源，接口在任务提交后直接返回
+pub async fn scan_video_source(
+    Path((source_type, id)): Path<(String, i32)>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<ApiResponse<bool>, ApiError> {
+    let video_source = match source_type.as_str() {
+        "collections" => collection::Entity::find_by_id(id)
+            .one(&db)
+            .await?
+            .map(|model| (model.enabled, VideoSourceEnum::from(model))),
+        "favorites" => favorite::Entity::find_by_id(id)
+            .one(&db)
+            .await?
+            .map(|model| (model.enabled, VideoSourceEnum::from(model))),
+        "submissions" => submission::Entity::find_by_id(id)
+            .one(&db)
+            .await?
+            .map(|model| (model.enabled, VideoSourceEnum::from(model))),
+        "watch_later" => watch_later::Entity::find_by_id(id)
+            .one(&db)
+            .await?
+            .map(|model| (model.enabled, VideoSourceEnum::from(model))),
+        _ => return Err(InnerApiError::BadRequest("Invalid video source type".to_string()).into()),
+    };
+    let Some((enabled, video_source)) = video_source else {
+        return Err(InnerApiError::NotFound(id).into());
+    };
+    if !enabled {
+        return Err(InnerApiError::BadRequest("Video source is disabled".to_string()).into());
+    }
+    let task_manager = DownloadTaskManager::get();
+    ensure_manual_trigger_allowed(task_manager)?;
+    if task_manager.scan_source(video_source.key()).await? == ScanSourceResult::Busy {
+        return Err(InnerApiError::Conflict("下载任务正在执行，请稍后再试".to_owned()).into());
+    }
+    Ok(ApiResponse::ok(true))
+}
+
+/// 将视频源的文件整体移动到新的路径，并同步更新视频源、视频与分页记录的路径
+pub async fn relocate_video_source_files(
+    Path((source_type, id)): Path<(String, i32)>,
+    Extension(db): Extension<DatabaseConnection>,
+    ValidatedJson(request): ValidatedJson<RelocateVideoSourceRequest>,
+) -> Result<ApiResponse<RelocateVideoSourceResponse>, ApiError> {
+    let video_source: Option<VideoSourceEnum> = match source_type.as_str() {
+        "collections" => collection::Entity::find_by_id(id).one(&db).await?.map(Into::into),
+        "favorites" => favorite::Entity::find_by_id(id).one(&db).await?.map(Into::into),
+        "submissions" => submission::Entity::find_by_id(id).one(&db).await?.map(Into::into),
+        "watch_later" => watch_later::Entity::find_by_id(id).one(&db).await?.map(Into::into),
+        _ => return Err(InnerApiError::BadRequest("Invalid video source type".to_string()).into()),
+    };
+    let Some(video_source) = video_source else {
+        return Err(InnerApiError::NotFound(id).into());
+    };
+    let new_path = std::path::Path::new(&request.path);
+    if new_path.starts_with(video_source.path()) || video_source.path().starts_with(new_path) {
+        return Err(
+            InnerApiError::BadRequest("新路径不能与原路径相同，也不能是原路径的上级或下级目录".to_string()).into(),
+        );
+    }
+    // 移动期间持有下载任务的运行锁，避免下载任务同时读写视频源目录
+    let _lock = match DownloadTaskManager::try_get() {
+        Some(task_manager) => Some(
+            task_manager
+                .try_lock_running()
+                .ok_or_else(|| InnerApiError::Conflict("下载任务正在执行，请稍后再试".to_string()))?,
+        ),
+        None => None,
+    };
+    let stats = relocate_video_source(&video_source, new_path, &db).await?;
+    Ok(ApiResponse::ok(RelocateVideoSourceResponse {
+        moved_files: stats.moved_files,
+        skipped_files: stats
+            .skipped_files
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect(),
+        updated_videos: stats.updated_videos,
+        updated_pages: stats.updated_pages,
+        conflicted_videos: stats.conflicted_videos,
+        conflicted_pages: stats.conflicted_pages,
+    }))
+}
+
 pub async fn remove_video_source(
     Path((source_type, id)): Path<(String, i32)>,
     Extension(db): Extension<DatabaseConnection>,
@@ -271,17 +468,19 @@ pub async fn remove_video_source(
         return Err(InnerApiError::NotFound(id).into());
     };
     let txn = db.begin().await?;
+    let video_ids = video::Entity::find()
+        .filter(video_source.filter_expr())
+        .select_only()
+        .column(video::Column::Id)
+        .as_query()
+        .to_owned();
+    // 任务事件没有外键约束，需要与视频一并删除
+    task_event::Entity::delete_many()
+        .filter(task_event::Column::VideoId.in_subquery(video_ids.clone()))
+        .exec(&txn)
+        .await?;
     page::Entity::delete_many()
-        .filter(
-            page::Column::VideoId.in_subquery(
-                video::Entity::find()
-                    .filter(video_source.filter_expr())
-                    .select_only()
-                    .column(video::Column::Id)
-                    .as_query()
-                    .to_owned(),
-            ),
-        )
+        .filter(page::Column::VideoId.in_subquery(video_ids))
         .exec(&txn)
         .await?;
     video::Entity::delete_many()
@@ -290,6 +489,10 @@ pub async fn remove_video_source(
         .await?;
     video_source.delete_from_db(&txn).await?;
     txn.commit().await?;
+    // 移除已删除视频源单独的扫描任务
+    if let Some(task_manager) = DownloadTaskManager::try_get() {
+        task_manager.reload_scan_schedules().await?;
+    }
     Ok(ApiResponse::ok(true))
 }
 
@@ -393,6 +596,81 @@ pub async fn insert_favorite(
     Ok(ApiResponse::ok(true))
 }
 
+/// 批量新增收藏夹订阅，逐个返回每个收藏夹的订阅结果，请求中重复的 fid 只会处理一次
+pub async fn insert_favorites(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(bili_client): Extension<Arc<BiliClient>>,
+    Json(requests): Json<Vec<InsertFavoriteRequest>>,
+) -> Result<ApiResponse<InsertFavoritesResponse>, ApiError> {
+    let config = VersionedConfig::get().read();
+    let credential = &config.credential;
+    let auto_enable = config.enable_video_source_on_subscribe;
+    let mut favorites = Vec::new();
+    for request in dedup_favorite_requests(requests) {
+        if let Err(e) = request.validate() {
+            favorites.push((request.fid, Err(e.to_string())));
+            continue;
+        }
+        let favorite = FavoriteList::new(bili_client.as_ref(), request.fid.to_string(), credential);
+        let model = match favorite.get_info().await {
+            Ok(favorite_info) => Ok(favorite::ActiveModel {
+                f_id: Set(favorite_info.id),
+                name: Set(favorite_info.title),
+                path: Set(request.path),
+                enabled: Set(auto_enable),
+                ..Default::default()
+            }),
+            Err(e) => Err(format!("获取收藏夹信息失败：{:#}", e)),
+        };
+        favorites.push((request.fid, model));
+    }
+    let results = insert_favorite_models(&db, favorites).await?;
+    Ok(ApiResponse::ok(InsertFavoritesResponse { results }))
+}
+
+/// 按 fid 去除重复的请求，保留每个 fid 第一次出现的请求
+fn dedup_favorite_requests(requests: Vec<InsertFavoriteRequest>) -> Vec<InsertFavoriteRequest> {
+    let mut seen = std::collections::HashSet::new();
+    requests
+        .into_iter()
+        .filter(|request| seen.insert(request.fid))
+        .collect()
+}
+
+/// 在同一个事务中写入所有有效的收藏夹，已经订阅过的收藏夹会被标记为失败
+async fn insert_favorite_models(
+    db: &DatabaseConnection,
+    favorites: Vec<(i64, Result<favorite::ActiveModel, String>)>,
+) -> Result<Vec<InsertFavoriteResult>> {
+    let txn = db.begin().await?;
+    let mut results = Vec::with_capacity(favorites.len());
+    for (fid, model) in favorites {
+        let error = match model {
+            Ok(model) => {
+                let exists = favorite::Entity::find()
+                    .filter(favorite::Column::FId.eq(fid))
+                    .count(&txn)
+                    .await?
+                    > 0;
+                if exists {
+                    Some("该收藏夹已订阅".to_owned())
+                } else {
+                    favorite::Entity::insert(model).exec(&txn).await?;
+                    None
+                }
+            }
+            Err(e) => Some(e),
+        };
+        results.push(InsertFavoriteResult {
+            fid,
+            success: error.is_none(),
+            error,
+        });
+    }
+    txn.commit().await?;
+    Ok(results)
+}
+
 /// 新增合集/列表订阅
 pub async fn insert_collection(
     Extension(db): Extension<DatabaseConnection>,
@@ -449,3 +727,131 @@ pub async fn insert_submission(
     .await?;
     Ok(ApiResponse::ok(true))
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::test_utils::{TestDatabase, test_favorite, test_page, test_video};
+
+    fn favorite_model(fid: i64, path: &str) -> favorite::ActiveModel {
+        favorite::ActiveModel {
+            f_id: Set(fid),
+            name: Set(format!("收藏夹{fid}")),
+            ..test_favorite(path.to_owned())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_insert_favorites_batch() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let requests = [(1, "/收藏夹/1"), (2, "/收藏夹/2"), (1, "/收藏夹/重复")]
+            .into_iter()
+            .map(|(fid, path)| InsertFavoriteRequest {
+                fid,
+                path: path.to_owned(),
+            })
+            .collect();
+        // 重复的 fid 只保留第一次出现的请求
+        let requests = dedup_favorite_requests(requests);
+        assert_eq!(
+            requests.iter().map(|r| (r.fid, r.path.as_str())).collect::<Vec<_>>(),
+            [(1, "/收藏夹/1"), (2, "/收藏夹/2")]
+        );
+        let favorites = requests
+            .iter()
+            .map(|request| (request.fid, Ok(favorite_model(request.fid, &request.path))))
+            .collect();
+        let results = insert_favorite_models(&connection, favorites).await?;
+        assert!(results.iter().all(|result| result.success && result.error.is_none()));
+        assert_eq!(favorite::Entity::find().count(&connection).await?, 2);
+        // 已订阅的收藏夹与获取信息失败的收藏夹会单独报告失败，不影响其它收藏夹的写入
+        let favorites = vec![
+            (2, Ok(favorite_model(2, "/收藏夹/2"))),
+            (3, Err("获取收藏夹信息失败".to_owned())),
+            (4, Ok(favorite_model(4, "/收藏夹/4"))),
+        ];
+        let results = insert_favorite_models(&connection, favorites).await?;
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| (result.fid, result.success))
+                .collect::<Vec<_>>(),
+            [(2, false), (3, false), (4, true)]
+        );
+        assert_eq!(results[0].error.as_deref(), Some("该收藏夹已订阅"));
+        assert_eq!(favorite::Entity::find().count(&connection).await?, 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_video_source_scan_schedule() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let favorite = favorite_model(1, "/收藏夹/1").insert(&connection).await?;
+        let request = |body: serde_json::Value| -> Result<UpdateVideoSourceRequest> {
+            let mut request = json!({"path": "/收藏夹/1", "enabled": true, "rule": null});
+            request
+                .as_object_mut()
+                .unwrap()
+                .extend(body.as_object().unwrap().clone());
+            Ok(serde_json::from_value(request)?)
+        };
+        let update = |request: UpdateVideoSourceRequest| {
+            update_video_source(
+                Path(("favorites".to_owned(), favorite.id)),
+                Extension(connection.clone()),
+                ValidatedJson(request),
+            )
+        };
+        let schedule = || async {
+            let model = favorite::Entity::find_by_id(favorite.id).one(&connection).await?;
+            Result::<_>::Ok(model.map(|model| (model.scan_interval_secs, model.scan_cron)))
+        };
+        // 不超过 60 秒的扫描间隔与无效的 cron 表达式会被拒绝
+        assert!(request(json!({"scanIntervalSecs": 30}))?.validate().is_err());
+        assert!(request(json!({"scanCron": "every day"}))?.validate().is_err());
+        let body = json!({"scanIntervalSecs": 600, "scanCron": "0 0 3 * * *"});
+        assert!(request(body.clone())?.validate().is_ok());
+        assert!(update(request(body)?).await.is_ok());
+        assert_eq!(schedule().await?, Some((Some(600), Some("0 0 3 * * *".to_owned()))));
+        // 未传入的字段保持不变，传入 0 与空字符串时清除单独设置
+        assert!(update(request(json!({"scanCron": ""}))?).await.is_ok());
+        assert_eq!(schedule().await?, Some((Some(600), None)));
+        assert!(update(request(json!({"scanIntervalSecs": 0}))?).await.is_ok());
+        assert_eq!(schedule().await?, Some((None, None)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_remove_video_source() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let favorite = favorite_model(1, "/收藏夹/1").insert(&connection).await?;
+        let video = video::ActiveModel {
+            favorite_id: Set(Some(favorite.id)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        test_page(video.id, 1).insert(&connection).await?;
+        task_event::ActiveModel {
+            video_id: Set(video.id),
+            task_index: Set(0),
+            status: Set(1),
+            created_at: Set("2026-10-17 00:00:00".to_owned()),
+            ..Default::default()
+        }
+        .insert(&connection)
+        .await?;
+        let path = Path(("favorites".to_owned(), favorite.id));
+        assert!(remove_video_source(path, Extension(connection.clone())).await.is_ok());
+        // 视频源下的视频、分页与任务事件一并删除
+        assert_eq!(video::Entity::find().count(&connection).await?, 0);
+        assert_eq!(page::Entity::find().count(&connection).await?, 0);
+        assert_eq!(task_event::Entity::find().count(&connection).await?, 0);
+        Ok(())
+    }
+}
@@ -1,58 +1,95 @@
-use std::collections::HashSet;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{Context, Result};
+use async_stream::try_stream;
+use axum::body::Body;
 use axum::extract::{Extension, Path, Query};
+use axum::http::header;
+use axum::response::IntoResponse;
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use bili_sync_entity::*;
+use futures::{Stream, TryStreamExt};
 use sea_orm::ActiveValue::Set;
+use sea_orm::sea_query::{Expr, SimpleExpr};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, Order, PaginatorTrait,
-    QueryFilter, QueryOrder, TransactionTrait, TryIntoModel,
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel, Order, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Select, TransactionTrait, TryIntoModel,
 };
 
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::adapter::{VideoSource, VideoSourceEnum};
+use crate::adapter::{VideoSource, VideoSourceEnum, VideoSourceKey};
 use crate::api::error::InnerApiError;
-use crate::api::helper::{update_page_download_status, update_video_download_status};
+use crate::api::helper::{
+    update_page_download_status, update_video_download_status, video_search_condition, video_tag_condition,
+};
 use crate::api::request::{
-    ResetFilteredVideoStatusRequest, ResetVideoStatusRequest, RetryPageTaskRequest, RetryVideoTaskRequest,
-    SortOrder, UpdateFilteredVideoStatusRequest, UpdateVideoStatusRequest, VideoSortBy, VideosRequest,
+    PageSortBy, RecentVideosRequest, ReconcileFilteredVideosRequest, RefreshFilteredMetadataRequest,
+    ResetFilteredVideoStatusRequest, ResetVideoStatusRequest, RetryFilteredVideoTaskRequest, RetryPageTaskRequest,
+    RetryVideoTaskRequest, SetSinglePageRequest, SortOrder, StatusFilter, UpdateFilteredVideoStatusRequest,
+    UpdateVideoStatusRequest, VideoRequest, VideoSortBy, VideoStatsRequest, VideosRequest,
 };
 use crate::api::response::{
-    ClearAndResetVideoStatusResponse, PageInfo, ResetFilteredVideosResponse, ResetVideoResponse, SimplePageInfo,
-    SimpleVideoInfo, UpdateFilteredVideoStatusResponse, UpdateVideoStatusResponse, VideoInfo, VideoResponse,
-    VideosResponse,
+    ClearAndResetVideoStatusResponse, DeleteVideoFilesResponse, DuplicateVideoGroup, DuplicateVideoInfo,
+    DuplicateVideosResponse, FailedVideo, PageInfo, RecentVideoInfo, RecentVideosResponse,
+    ReconcileFilteredVideosResponse, ReconcileVideoResponse, RefreshFilteredMetadataResponse, RefreshMetadataResponse,
+    ResetFilteredVideosResponse, ResetVideoResponse, RetriedTask, RetryAllFailedTasksResponse,
+    RetryFilteredVideoTaskResponse, SimplePageInfo, SimpleVideoInfo, TaskEventInfo, UpdateFilteredVideoStatusResponse,
+    UpdateVideoStatusResponse, VideoEventsResponse, VideoExportInfo, VideoInfo, VideoProgressResponse, VideoResponse,
+    VideoStatsResponse, VideosResponse,
 };
 use crate::api::wrapper::{ApiError, ApiResponse, ValidatedJson};
-use crate::bilibili::{BiliClient, PageInfo as BiliPageInfo};
-use crate::config::{PathSafeTemplate, TEMPLATE, VersionedConfig};
+use crate::bilibili::{BiliClient, PageInfo as BiliPageInfo, is_risk_control_error};
+use crate::config::{Config, NFOMode, PathSafeTemplate, TEMPLATE, VersionedConfig};
 use crate::downloader::Downloader;
+use crate::task::{DownloadTaskManager, RETRY_QUEUE};
 use crate::utils::download_context::DownloadContext;
-use crate::utils::format_arg::{page_format_args, video_format_args};
+use crate::utils::format_arg::page_format_args;
+use crate::utils::manifest::update_source_manifest;
+use crate::utils::model::{record_page_task_events, record_video_task_events};
+use crate::utils::post_download::PostDownloadCommand;
+use crate::utils::progress::ProgressRegistry;
+use crate::utils::relocate::detach_dependent_symlinks;
 use crate::error::ExecutionStatus;
-use crate::utils::status::{PageStatus, VideoStatus};
+use crate::utils::status::{PageStatus, STATUS_NOT_STARTED, STATUS_OK, Status, VideoStatus};
 use tracing;
 use crate::workflow::{
-    dispatch_download_page, fetch_page_danmaku, fetch_page_poster, fetch_page_subtitle, fetch_page_video,
-    fetch_upper_face, fetch_video_poster, generate_page_nfo, generate_upper_nfo, generate_video_nfo,
+    DownloadMetrics, PagePaths, dispatch_download_page, fetch_page_chapters, fetch_page_danmaku, fetch_page_poster,
+    fetch_page_subtitle, fetch_page_video, fetch_upper_face, fetch_video_poster, first_page_video_path,
+    generate_page_nfo, generate_upper_nfo, generate_video_nfo, newly_fully_succeeded, page_paths, page_subtitle_files,
+    poster_from_frame, remove_from_watch_later, render_video_name, trim_page_video_extension, upper_base_path,
 };
 
 pub(super) fn router() -> Router {
     Router::new()
         .route("/videos", get(get_videos))
+        .route("/videos/stats", get(get_video_stats))
+        .route("/videos/export", get(export_videos))
+        .route("/videos/duplicates", get(get_duplicate_videos))
+        .route("/videos/recent", get(get_recent_videos))
         .route("/videos/{id}", get(get_video))
+        .route("/videos/{id}/events", get(get_video_events))
+        .route("/videos/{id}/progress", get(get_video_progress))
         .route(
             "/videos/{id}/clear-and-reset-status",
             post(clear_and_reset_video_status),
         )
+        .route("/videos/{id}/delete-files", post(delete_video_files))
         .route("/videos/{id}/reset-status", post(reset_video_status))
         .route("/videos/{id}/update-status", post(update_video_status))
+        .route("/videos/{id}/set-single-page", post(set_video_single_page))
         .route("/videos/{id}/retry-task", post(retry_video_task))
+        .route("/videos/{id}/retry-all-failed", post(retry_all_failed_tasks))
+        .route("/videos/{id}/reconcile", post(reconcile_video))
+        .route("/videos/{id}/refresh-metadata", post(refresh_video_metadata))
         .route("/pages/{id}/retry-task", post(retry_page_task))
         .route("/videos/reset-status", post(reset_filtered_video_status))
+        .route("/videos/reconcile", post(reconcile_filtered_videos))
+        .route("/videos/refresh-metadata", post(refresh_filtered_videos_metadata))
+        .route("/videos/retry-task", post(retry_filtered_video_task))
         .route("/videos/update-status", post(update_filtered_video_status))
 }
 
@@ -61,33 +98,123 @@ pub async fn get_videos(
     Extension(db): Extension<DatabaseConnection>,
     Query(params): Query<VideosRequest>,
 ) -> Result<ApiResponse<VideosResponse>, ApiError> {
-    let mut query = video::Entity::find();
-    for (field, column) in [
-        (params.collection, video::Column::CollectionId),
-        (params.favorite, video::Column::FavoriteId),
-        (params.submission, video::Column::SubmissionId),
-        (params.watch_later, video::Column::WatchLaterId),
-    ] {
-        if let Some(id) = field {
-            query = query.filter(column.eq(id));
-        }
-    }
-    if let Some(query_word) = params.query {
-        query = query.filter(
-            video::Column::Name
-                .contains(&query_word)
-                .or(video::Column::Bvid.contains(query_word)),
-        );
-    }
-    if let Some(status_filter) = params.status_filter {
-        query = query.filter(status_filter.to_video_query());
-    }
+    let query = filtered_videos_query(&params)?;
     let total_count = query.clone().count(&db).await?;
     let (page, page_size) = if let (Some(page), Some(page_size)) = (params.page, params.page_size) {
         (page, page_size)
     } else {
         (0, 10)
     };
+    Ok(ApiResponse::ok(VideosResponse {
+        videos: query.into_partial_model::<VideoInfo>().paginate(&db, page_size).fetch_page(page).await?,
+        total_count,
+    }))
+}
+
+/// 以 CSV 格式导出筛选后的视频列表，筛选与排序参数与 get_videos 一致，忽略分页参数
+///
+/// 查询结果逐行写入响应体，避免大收藏夹导出时将全部结果读入内存
+pub async fn export_videos(
+    Extension(db): Extension<DatabaseConnection>,
+    Query(params): Query<VideosRequest>,
+) -> Result<axum::response::Response, ApiError> {
+    let rows = video_csv_rows(db, filtered_videos_query(&params)?);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"videos.csv\""),
+        ],
+        Body::from_stream(rows),
+    )
+        .into_response())
+}
+
+/// 逐行生成 CSV 内容，首行为表头
+fn video_csv_rows(db: DatabaseConnection, query: Select<video::Entity>) -> impl Stream<Item = Result<String, DbErr>> {
+    try_stream! {
+        yield "id,bvid,name,upper_name,download_status,created_at\n".to_owned();
+        let mut videos = query.into_partial_model::<VideoExportInfo>().stream(&db).await?;
+        while let Some(video) = videos.try_next().await? {
+            yield format!(
+                "{},{},{},{},{},{}\n",
+                video.id,
+                csv_field(&video.bvid),
+                csv_field(&video.name),
+                csv_field(&video.upper_name),
+                video.download_status,
+                csv_field(&video.created_at),
+            );
+        }
+    }
+}
+
+/// 视频列表与批量重试共用的筛选条件，包含来源筛选、搜索、状态筛选与标签筛选
+struct VideoFilter<'a> {
+    sources: [(Option<i32>, video::Column); 4],
+    query: Option<&'a str>,
+    search_fields: Option<&'a [String]>,
+    status_filter: Option<&'a StatusFilter>,
+    tag: Option<&'a str>,
+}
+
+impl VideoFilter<'_> {
+    /// 将筛选条件添加到视频查询中
+    fn apply(&self, mut query: Select<video::Entity>) -> Result<Select<video::Entity>, ApiError> {
+        for (field, column) in self.sources {
+            if let Some(id) = field {
+                query = query.filter(column.eq(id));
+            }
+        }
+        if let Some(query_word) = self.query {
+            query = query.filter(video_search_condition(query_word, self.search_fields)?);
+        }
+        if let Some(status_filter) = self.status_filter {
+            query = query.filter(status_filter.to_video_query());
+        }
+        if let Some(tag) = self.tag {
+            query = query.filter(video_tag_condition(tag));
+        }
+        Ok(query)
+    }
+}
+
+impl<'a> From<&'a VideosRequest> for VideoFilter<'a> {
+    fn from(params: &'a VideosRequest) -> Self {
+        Self {
+            sources: [
+                (params.collection, video::Column::CollectionId),
+                (params.favorite, video::Column::FavoriteId),
+                (params.submission, video::Column::SubmissionId),
+                (params.watch_later, video::Column::WatchLaterId),
+            ],
+            query: params.query.as_deref(),
+            search_fields: params.search_fields.as_deref(),
+            status_filter: params.status_filter.as_ref(),
+            tag: params.tag.as_deref(),
+        }
+    }
+}
+
+impl<'a> From<&'a RetryFilteredVideoTaskRequest> for VideoFilter<'a> {
+    fn from(request: &'a RetryFilteredVideoTaskRequest) -> Self {
+        Self {
+            sources: [
+                (request.collection, video::Column::CollectionId),
+                (request.favorite, video::Column::FavoriteId),
+                (request.submission, video::Column::SubmissionId),
+                (request.watch_later, video::Column::WatchLaterId),
+            ],
+            query: request.query.as_deref(),
+            search_fields: request.search_fields.as_deref(),
+            status_filter: request.status_filter.as_ref(),
+            tag: request.tag.as_deref(),
+        }
+    }
+}
+
+/// 根据请求参数构造视频列表的查询，包含来源筛选、搜索、状态筛选与排序
+fn filtered_videos_query(params: &VideosRequest) -> Result<Select<video::Entity>, ApiError> {
+    let query = VideoFilter::from(params).apply(video::Entity::find())?;
 
     // 排序逻辑：
     // - 如果显式指定 sort_by / sort_order，则按指定排序；
@@ -110,38 +237,224 @@ pub async fn get_videos(
         VideoSortBy::DownloadTime => video::Column::CreatedAt,
     };
 
-    query = query.order_by(
+    Ok(query.order_by(
         order_column,
         match sort_order {
             SortOrder::Asc => Order::Asc,
             SortOrder::Desc => Order::Desc,
         },
-    );
+    ))
+}
 
-    Ok(ApiResponse::ok(VideosResponse {
-        videos: query.into_partial_model::<VideoInfo>().paginate(&db, page_size).fetch_page(page).await?,
-        total_count,
+/// 按 RFC 4180 转义 CSV 字段，包含逗号、引号或换行时使用双引号包裹
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// 统计各个状态的视频数量，支持根据视频来源筛选，状态的判定与列表的状态筛选保持一致
+pub async fn get_video_stats(
+    Extension(db): Extension<DatabaseConnection>,
+    Query(params): Query<VideoStatsRequest>,
+) -> Result<ApiResponse<VideoStatsResponse>, ApiError> {
+    let mut query = video::Entity::find();
+    for (field, column) in [
+        (params.collection, video::Column::CollectionId),
+        (params.favorite, video::Column::FavoriteId),
+        (params.submission, video::Column::SubmissionId),
+        (params.watch_later, video::Column::WatchLaterId),
+    ] {
+        if let Some(id) = field {
+            query = query.filter(column.eq(id));
+        }
+    }
+    let count_by_status = |status_filter: StatusFilter| query.clone().filter(status_filter.to_video_query()).count(&db);
+    let (total, succeeded, failed, waiting, skipped, paid) = tokio::try_join!(
+        query.clone().count(&db),
+        count_by_status(StatusFilter::Succeeded),
+        count_by_status(StatusFilter::Failed),
+        count_by_status(StatusFilter::Waiting),
+        count_by_status(StatusFilter::Skipped),
+        count_by_status(StatusFilter::Paid),
+    )?;
+    Ok(ApiResponse::ok(VideoStatsResponse {
+        total,
+        succeeded,
+        failed,
+        waiting,
+        skipped,
+        paid,
     }))
 }
 
+/// 找出在多个视频源中重复出现的视频（bvid 相同），按 bvid 分组返回各自的记录与保存路径
+pub async fn get_duplicate_videos(
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<ApiResponse<DuplicateVideosResponse>, ApiError> {
+    let bvids = video::Entity::find()
+        .select_only()
+        .column(video::Column::Bvid)
+        .group_by(video::Column::Bvid)
+        .having(Expr::col(video::Column::Id).count().gt(1))
+        .into_tuple::<String>()
+        .all(&db)
+        .await?;
+    if bvids.is_empty() {
+        return Ok(ApiResponse::ok(DuplicateVideosResponse { groups: Vec::new() }));
+    }
+    let videos = video::Entity::find()
+        .filter(video::Column::Bvid.is_in(bvids))
+        .order_by_asc(video::Column::Bvid)
+        .order_by_asc(video::Column::Id)
+        .into_partial_model::<DuplicateVideoInfo>()
+        .all(&db)
+        .await?;
+    let mut groups: Vec<DuplicateVideoGroup> = Vec::new();
+    for video in videos {
+        match groups.last_mut() {
+            Some(group) if group.bvid == video.bvid => group.videos.push(video),
+            _ => groups.push(DuplicateVideoGroup {
+                bvid: video.bvid.clone(),
+                videos: vec![video],
+            }),
+        }
+    }
+    Ok(ApiResponse::ok(DuplicateVideosResponse { groups }))
+}
+
+/// 获取最近下载完成的视频，按入库时间倒序排列，默认返回 10 条，最多 100 条
+pub async fn get_recent_videos(
+    Extension(db): Extension<DatabaseConnection>,
+    Query(params): Query<RecentVideosRequest>,
+) -> Result<ApiResponse<RecentVideosResponse>, ApiError> {
+    let videos = video::Entity::find()
+        .filter(VideoStatus::query_builder().succeeded())
+        .order_by_desc(video::Column::CreatedAt)
+        .order_by_desc(video::Column::Id)
+        .limit(params.limit.unwrap_or(10).clamp(1, 100))
+        .all(&db)
+        .await?;
+    let source_ids =
+        |relation_id: fn(&video::Model) -> Option<i32>| videos.iter().filter_map(relation_id).collect::<HashSet<_>>();
+    let (collections, favorites, submissions, watch_laters) = tokio::try_join!(
+        collection::Entity::find()
+            .filter(collection::Column::Id.is_in(source_ids(|video| video.collection_id)))
+            .all(&db),
+        favorite::Entity::find()
+            .filter(favorite::Column::Id.is_in(source_ids(|video| video.favorite_id)))
+            .all(&db),
+        submission::Entity::find()
+            .filter(submission::Column::Id.is_in(source_ids(|video| video.submission_id)))
+            .all(&db),
+        watch_later::Entity::find()
+            .filter(watch_later::Column::Id.is_in(source_ids(|video| video.watch_later_id)))
+            .all(&db)
+    )?;
+    let source_names = collections
+        .into_iter()
+        .map(VideoSourceEnum::from)
+        .chain(favorites.into_iter().map(VideoSourceEnum::from))
+        .chain(submissions.into_iter().map(VideoSourceEnum::from))
+        .chain(watch_laters.into_iter().map(VideoSourceEnum::from))
+        .map(|video_source| (video_source.key(), video_source.display_name().into_owned()))
+        .collect::<HashMap<_, _>>();
+    let videos = videos
+        .into_iter()
+        .map(|video| {
+            let source_key = video
+                .collection_id
+                .map(VideoSourceKey::Collection)
+                .or(video.favorite_id.map(VideoSourceKey::Favorite))
+                .or(video.submission_id.map(VideoSourceKey::Submission))
+                .or(video.watch_later_id.map(VideoSourceKey::WatchLater));
+            RecentVideoInfo {
+                source_name: source_key.and_then(|key| source_names.get(&key).cloned()),
+                id: video.id,
+                bvid: video.bvid,
+                name: video.name,
+                upper_name: video.upper_name,
+                cover: video.cover,
+                created_at: video.created_at,
+            }
+        })
+        .collect();
+    Ok(ApiResponse::ok(RecentVideosResponse { videos }))
+}
+
 pub async fn get_video(
     Path(id): Path<i32>,
     Extension(db): Extension<DatabaseConnection>,
+    Query(params): Query<VideoRequest>,
 ) -> Result<ApiResponse<VideoResponse>, ApiError> {
-    let (video_info, pages_info) = tokio::try_join!(
+    let order = match params.pages_sort_order.unwrap_or(SortOrder::Asc) {
+        SortOrder::Asc => Order::Asc,
+        SortOrder::Desc => Order::Desc,
+    };
+    let pages_query = page::Entity::find().filter(page::Column::VideoId.eq(id));
+    let pages_query = match params.pages_sort_by.unwrap_or(PageSortBy::Cid) {
+        PageSortBy::Cid => pages_query.order_by(page::Column::Cid, order),
+        PageSortBy::Pid => pages_query.order_by(page::Column::Pid, order),
+        PageSortBy::Status => {
+            let query_builder = PageStatus::query_builder();
+            // 将下载状态映射为失败 < 等待 < 成功，便于快速找到失败的分页
+            let status_rank = Expr::case(query_builder.failed(), 0)
+                .case(query_builder.waiting(), 1)
+                .finally(2);
+            pages_query
+                .order_by(SimpleExpr::from(status_rank), order)
+                .order_by_asc(page::Column::Cid)
+        }
+    };
+    let (video_info, total_pages_count) = tokio::try_join!(
         video::Entity::find_by_id(id).into_partial_model::<VideoInfo>().one(&db),
-        page::Entity::find()
-            .filter(page::Column::VideoId.eq(id))
-            .order_by_asc(page::Column::Cid)
-            .into_partial_model::<PageInfo>()
-            .all(&db)
+        pages_query.clone().count(&db)
     )?;
     let Some(video_info) = video_info else {
         return Err(InnerApiError::NotFound(id).into());
     };
+    // 分页较多的视频可以分批获取，未传入分页参数时返回全部分页
+    let pages_info = match (params.page, params.page_size) {
+        (Some(page), Some(page_size)) => {
+            pages_query
+                .into_partial_model::<PageInfo>()
+                .paginate(&db, page_size)
+                .fetch_page(page)
+                .await?
+        }
+        _ => pages_query.into_partial_model::<PageInfo>().all(&db).await?,
+    };
     Ok(ApiResponse::ok(VideoResponse {
         video: video_info,
         pages: pages_info,
+        total_pages_count,
+    }))
+}
+
+/// 获取视频及其分页的任务状态变更记录，按时间先后排列
+pub async fn get_video_events(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<ApiResponse<VideoEventsResponse>, ApiError> {
+    if video::Entity::find_by_id(id).count(&db).await? == 0 {
+        return Err(InnerApiError::NotFound(id).into());
+    }
+    let events = task_event::Entity::find()
+        .filter(task_event::Column::VideoId.eq(id))
+        .order_by_asc(task_event::Column::CreatedAt)
+        .order_by_asc(task_event::Column::Id)
+        .into_partial_model::<TaskEventInfo>()
+        .all(&db)
+        .await?;
+    Ok(ApiResponse::ok(VideoEventsResponse { events }))
+}
+
+/// 获取视频正在下载时的实时进度，多页视频的各分页累计计算
+pub async fn get_video_progress(Path(id): Path<i32>) -> Result<ApiResponse<VideoProgressResponse>, ApiError> {
+    Ok(ApiResponse::ok(VideoProgressResponse {
+        progress: ProgressRegistry::get().progress(id),
     }))
 }
 
@@ -150,6 +463,13 @@ pub async fn reset_video_status(
     Extension(db): Extension<DatabaseConnection>,
     Json(request): Json<ResetVideoStatusRequest>,
 ) -> Result<ApiResponse<ResetVideoResponse>, ApiError> {
+    if let Some(task_index) = request
+        .task_indices
+        .iter()
+        .find(|&&task_index| task_index >= PageStatus::LEN)
+    {
+        return Err(InnerApiError::BadRequest(format!("Invalid task_index: {}", task_index)).into());
+    }
     let (video_info, pages_info) = tokio::try_join!(
         video::Entity::find_by_id(id).into_partial_model::<VideoInfo>().one(&db),
         page::Entity::find()
@@ -165,7 +485,14 @@ pub async fn reset_video_status(
         .into_iter()
         .filter_map(|mut page_info| {
             let mut page_status = PageStatus::from(page_info.download_status);
-            if (request.force && page_status.force_reset_failed()) || page_status.reset_failed() {
+            let page_resetted = if request.task_indices.is_empty() {
+                (request.force && page_status.force_reset_failed()) || page_status.reset_failed()
+            } else {
+                request.task_indices.iter().fold(false, |resetted, &task_index| {
+                    page_status.reset_failed_at(task_index) || resetted
+                })
+            };
+            if page_resetted {
                 page_info.download_status = page_status.into();
                 Some(page_info)
             } else {
@@ -174,7 +501,9 @@ pub async fn reset_video_status(
         })
         .collect::<Vec<_>>();
     let mut video_status = VideoStatus::from(video_info.download_status);
-    let mut video_resetted = (request.force && video_status.force_reset_failed()) || video_status.reset_failed();
+    // 指定了分页子任务时，视频自身的子任务保持不变
+    let mut video_resetted = request.task_indices.is_empty()
+        && ((request.force && video_status.force_reset_failed()) || video_status.reset_failed());
     if !resetted_pages_info.is_empty() {
         video_status.set(4, 0); //  将“分页下载”重置为 0
         video_resetted = true;
@@ -212,10 +541,13 @@ pub async fn clear_and_reset_video_status(
     let Some(video_info) = video_info else {
         return Err(InnerApiError::NotFound(id).into());
     };
+    // 分页记录删除前处理依赖本视频文件的去重软链接
+    detach_dependent_symlinks(&video_info, &db).await?;
     let txn = db.begin().await?;
     let mut video_info = video_info.into_active_model();
     video_info.single_page = Set(None);
     video_info.download_status = Set(0);
+    video_info.files_deleted = Set(false);
     let video_info = video_info.update(&txn).await?;
     page::Entity::delete_many()
         .filter(page::Column::VideoId.eq(id))
@@ -237,16 +569,74 @@ pub async fn clear_and_reset_video_status(
             upper_name: video_info.upper_name,
             should_download: video_info.should_download,
             is_paid_video: video_info.is_paid_video,
+            files_deleted: video_info.files_deleted,
             download_status: video_info.download_status,
             cover: video_info.cover,
         },
     }))
 }
 
+/// 仅删除视频在本地的文件，保留数据库记录与各个任务的状态，并标记文件已删除，避免定时任务重新下载
+pub async fn delete_video_files(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<ApiResponse<DeleteVideoFilesResponse>, ApiError> {
+    let Some(video_model) = video::Entity::find_by_id(id).one(&db).await? else {
+        return Err(InnerApiError::NotFound(id).into());
+    };
+    let warning = if video_model.path.is_empty() {
+        Some("视频尚未记录本地路径，无需删除".to_string())
+    } else {
+        // 其它视频源可能通过去重软链接引用本视频的文件，删除前先替换为文件副本
+        detach_dependent_symlinks(&video_model, &db).await?;
+        match tokio::fs::remove_dir_all(&video_model.path).await {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Some(format!("本地路径「{}」不存在", video_model.path))
+            }
+            result => {
+                result.context(format!("删除本地路径「{}」失败", video_model.path))?;
+                None
+            }
+        }
+    };
+    let mut video_active_model = video_model.into_active_model();
+    video_active_model.files_deleted = Set(true);
+    video_active_model.update(&db).await?;
+    let video_info = video::Entity::find_by_id(id)
+        .into_partial_model::<VideoInfo>()
+        .one(&db)
+        .await?
+        .ok_or_else(|| InnerApiError::NotFound(id))?;
+    Ok(ApiResponse::ok(DeleteVideoFilesResponse {
+        warning,
+        video: video_info,
+    }))
+}
+
+/// 手动设置视频的 single_page 字段，用于修复自动识别错误或视频信息不完整导致无法重试的情况
+pub async fn set_video_single_page(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Json(request): Json<SetSinglePageRequest>,
+) -> Result<ApiResponse<bool>, ApiError> {
+    let Some(video_model) = video::Entity::find_by_id(id).one(&db).await? else {
+        return Err(InnerApiError::NotFound(id).into());
+    };
+    let mut video_active_model = video_model.into_active_model();
+    video_active_model.single_page = Set(Some(request.single_page));
+    video_active_model.update(&db).await?;
+    Ok(ApiResponse::ok(true))
+}
+
 pub async fn reset_filtered_video_status(
     Extension(db): Extension<DatabaseConnection>,
     Json(request): Json<ResetFilteredVideoStatusRequest>,
 ) -> Result<ApiResponse<ResetFilteredVideosResponse>, ApiError> {
+    if let Some(task_index) = request.task_index
+        && task_index >= PageStatus::LEN
+    {
+        return Err(InnerApiError::BadRequest(format!("Invalid task_index: {}", task_index)).into());
+    }
     let mut query = video::Entity::find();
     for (field, column) in [
         (request.collection, video::Column::CollectionId),
@@ -259,11 +649,7 @@ pub async fn reset_filtered_video_status(
         }
     }
     if let Some(query_word) = request.query {
-        query = query.filter(
-            video::Column::Name
-                .contains(&query_word)
-                .or(video::Column::Bvid.contains(query_word)),
-        );
+        query = query.filter(video_search_condition(&query_word, None)?);
     }
     if let Some(status_filter) = request.status_filter {
         query = query.filter(status_filter.to_video_query());
@@ -278,7 +664,11 @@ pub async fn reset_filtered_video_status(
         .into_iter()
         .filter_map(|mut page_info| {
             let mut page_status = PageStatus::from(page_info.download_status);
-            if (request.force && page_status.force_reset_failed()) || page_status.reset_failed() {
+            let page_resetted = match request.task_index {
+                Some(task_index) => page_status.reset_failed_at(task_index),
+                None => (request.force && page_status.force_reset_failed()) || page_status.reset_failed(),
+            };
+            if page_resetted {
                 page_info.download_status = page_status.into();
                 Some(page_info)
             } else {
@@ -291,8 +681,9 @@ pub async fn reset_filtered_video_status(
         .into_iter()
         .filter_map(|mut video_info| {
             let mut video_status = VideoStatus::from(video_info.download_status);
-            let mut video_resetted =
-                (request.force && video_status.force_reset_failed()) || video_status.reset_failed();
+            // 指定了分页子任务时，视频自身的子任务保持不变
+            let mut video_resetted = request.task_index.is_none()
+                && ((request.force && video_status.force_reset_failed()) || video_status.reset_failed());
             if video_ids_with_resetted_pages.contains(&video_info.id) {
                 video_status.set(4, 0); // 将"分页下载"重置为 0
                 video_resetted = true;
@@ -380,6 +771,8 @@ pub async fn update_video_status(
             let mut video_active_model: video::ActiveModel = video_model.into();
             if let Some(should_download) = request.should_download {
                 video_active_model.should_download = Set(should_download);
+                // 手动设置后不再根据播放量与点赞数重新检查
+                video_active_model.below_stat_threshold = Set(false);
             }
             if let Some(is_paid_video) = request.is_paid_video {
                 video_active_model.is_paid_video = Set(is_paid_video);
@@ -448,11 +841,7 @@ pub async fn update_filtered_video_status(
         }
     }
     if let Some(query_word) = request.query {
-        query = query.filter(
-            video::Column::Name
-                .contains(&query_word)
-                .or(video::Column::Bvid.contains(query_word)),
-        );
+        query = query.filter(video_search_condition(&query_word, None)?);
     }
     if let Some(status_filter) = request.status_filter {
         query = query.filter(status_filter.to_video_query());
@@ -501,6 +890,8 @@ pub async fn update_filtered_video_status(
                 let mut video_active_model: video::ActiveModel = video_model.into();
                 if let Some(should_download) = request.should_download {
                     video_active_model.should_download = Set(should_download);
+                    // 手动设置后不再根据播放量与点赞数重新检查
+                    video_active_model.below_stat_threshold = Set(false);
                 }
                 if let Some(is_paid_video) = request.is_paid_video {
                     video_active_model.is_paid_video = Set(is_paid_video);
@@ -525,7 +916,7 @@ pub async fn update_filtered_video_status(
 async fn get_video_source_from_model(
     video_model: &video::Model,
     db: &DatabaseConnection,
-) -> Result<VideoSourceEnum, ApiError> {
+) -> Result<VideoSourceEnum> {
     if let Some(collection_id) = video_model.collection_id {
         let collection = collection::Entity::find_by_id(collection_id)
             .one(db)
@@ -557,83 +948,90 @@ async fn get_video_source_from_model(
     Err(InnerApiError::BadRequest("Video has no associated video source".to_string()).into())
 }
 
-/// 重试视频的单个任务
-pub async fn retry_video_task(
-    Path(id): Path<i32>,
-    Extension(db): Extension<DatabaseConnection>,
-    Extension(bili_client): Extension<Arc<BiliClient>>,
-    ValidatedJson(request): ValidatedJson<RetryVideoTaskRequest>,
-) -> Result<ApiResponse<UpdateVideoStatusResponse>, ApiError> {
-    let video_model = video::Entity::find_by_id(id)
-        .one(&db)
-        .await?
-        .ok_or_else(|| InnerApiError::NotFound(id))?;
-    
-    // 获取视频源
-    let video_source = get_video_source_from_model(&video_model, &db).await?;
-    
-    // 获取配置和模板
-    let config = VersionedConfig::get().read();
-    let template = TEMPLATE.read();
-    let downloader = Downloader::new(bili_client.client.clone());
-    
-    // 创建下载上下文
-    let cx = DownloadContext::new(
-        &bili_client,
-        &video_source,
-        &template,
-        &db,
-        &downloader,
-        &config,
-    );
-    
-    // 计算路径
+/// 计算视频的保存路径与 UP 主信息的保存路径（与定时任务使用相同的规则）
+fn video_task_paths(
+    video_model: &video::Model,
+    video_source: &VideoSourceEnum,
+    template: &handlebars::Handlebars<'_>,
+    config: &Config,
+) -> Result<(PathBuf, PathBuf)> {
     let base_path = if !video_model.path.is_empty() {
         PathBuf::from(&video_model.path)
     } else {
         video_source.path().join(
-            template
-                .path_safe_render("video", &video_format_args(&video_model, &config.time_format))
-                .map_err(|e| InnerApiError::BadRequest(format!("Template render error: {}", e)))?,
+            render_video_name(template, video_source.video_name(), video_model, &config.time_format)
+                .map_err(|e| InnerApiError::TemplateError(e.to_string()))?,
         )
     };
-    let upper_id = video_model.upper_id.to_string();
-    let base_upper_path = config
-        .upper_path
-        .join(upper_id.chars().next().ok_or_else(|| InnerApiError::BadRequest("upper_id is empty".to_string()))?.to_string())
-        .join(upper_id);
-    let is_single_page = video_model.single_page.ok_or_else(|| InnerApiError::BadRequest("single_page is null".to_string()))?;
-    
-    // 确保视频源目录存在（与定时任务使用相同的规则）
-    video_source.create_dir_all().await
-        .map_err(|e| {
-            tracing::error!("处理视频「{}」创建视频源目录失败: {}", &video_model.name, e);
-            InnerApiError::BadRequest(format!("Failed to create video source directory: {}", e))
-        })?;
-    
-    // 注意：不预先创建 base_path 和 base_upper_path，让下载函数自动创建（与定时任务保持一致）
-    // downloader.fetch() 和 generate_nfo() 会自动创建所需的父目录
-    
-    // 根据 task_index 调用对应的函数
-    let result = match request.task_index {
+    Ok((base_path, upper_base_path(&config.upper_path, video_model.upper_id)))
+}
+
+/// 视频的全部子任务通过重试变为成功时，与定时任务一致地按配置移出稍后再看，并在后台执行下载后命令
+async fn on_video_retried(
+    old_status: VideoStatus,
+    new_status: VideoStatus,
+    video_dir: &std::path::Path,
+    video_model: &video::Model,
+    cx: DownloadContext<'_>,
+) {
+    remove_from_watch_later(old_status, new_status, video_model, cx).await;
+    if newly_fully_succeeded(old_status, new_status)
+        && let Some(command) = PostDownloadCommand::from_config(cx.config, video_dir, video_model)
+    {
+        command.spawn();
+    }
+}
+
+/// 视频的全部子任务在重试后均为成功时，与定时任务一致地按配置将其写入视频源的清单，需在保存视频状态后调用
+async fn update_manifest_after_retry(
+    config: &Config,
+    video_source: &VideoSourceEnum,
+    video_id: i32,
+    video_status: VideoStatus,
+    db: &DatabaseConnection,
+) {
+    if !config.write_source_manifest || !<[u32; 5]>::from(video_status).into_iter().all(|s| s == STATUS_OK) {
+        return;
+    }
+    if let Err(e) = update_source_manifest(video_source.path(), &[video_id], db).await {
+        tracing::warn!("更新 {} 的清单文件失败：{:#}", video_source.display_name(), e);
+    }
+}
+
+/// 执行视频的单个任务
+async fn run_video_task(
+    task_index: usize,
+    video_model: &video::Model,
+    base_path: &std::path::Path,
+    base_upper_path: &std::path::Path,
+    cx: DownloadContext<'_>,
+) -> Result<ExecutionStatus> {
+    let config = cx.config;
+    let is_single_page = video_model
+        .single_page
+        .ok_or_else(|| InnerApiError::BadRequest("single_page is null".to_string()))?;
+    match task_index {
         0 => {
-            // 下载视频封面
-            let poster_path = base_path.join("poster.jpg");
-            let fanart_path = base_path.join("fanart.jpg");
+            // 下载视频封面，从首帧截取时使用第一个分页的视频
+            let frame_source = match poster_from_frame(cx) {
+                true => first_page_video_path(video_model, cx).await,
+                false => None,
+            };
             fetch_video_poster(
                 !is_single_page && !config.skip_option.no_poster,
-                &video_model,
-                poster_path.clone(),
-                fanart_path.clone(),
+                video_model,
+                frame_source.as_deref(),
+                base_path.join(format!("poster.{}", config.cover_format.extension())),
+                base_path.join(format!("fanart.{}", config.cover_format.extension())),
                 cx,
             )
             .await
         }
         1 => {
-            // 生成视频信息的 nfo
+            // 生成视频信息的 nfo，单页视频按剧集生成 NFO 时同样需要 tvshow.nfo
             generate_video_nfo(
-                !is_single_page && !config.skip_option.no_video_nfo,
-                &video_model,
+                (!is_single_page || config.nfo_mode == NFOMode::TvShow) && !config.skip_option.no_video_nfo,
+                video_model,
                 base_path.join("tvshow.nfo"),
                 cx,
             )
@@ -641,11 +1039,10 @@ pub async fn retry_video_task(
         }
         2 => {
             // 下载 UP 主头像
-            let upper_face_path = base_upper_path.join("folder.jpg");
             fetch_upper_face(
                 !config.skip_option.no_upper,
-                &video_model,
-                upper_face_path.clone(),
+                video_model,
+                base_upper_path.join(format!("folder.{}", config.cover_format.extension())),
                 cx,
             )
             .await
@@ -654,7 +1051,7 @@ pub async fn retry_video_task(
             // 生成 UP 主信息的 nfo
             generate_upper_nfo(
                 !config.skip_option.no_upper,
-                &video_model,
+                video_model,
                 base_upper_path.join("person.nfo"),
                 cx,
             )
@@ -664,121 +1061,29 @@ pub async fn retry_video_task(
             // 分页下载任务需要特殊处理，这里触发分页下载
             // 获取所有分页
             let page_models = page::Entity::find()
-                .filter(page::Column::VideoId.eq(id))
+                .filter(page::Column::VideoId.eq(video_model.id))
                 .order_by_asc(page::Column::Cid)
-                .all(&db)
+                .all(cx.connection)
                 .await?;
-            
+
             // 调用 dispatch_download_page 直接处理分页下载
-            dispatch_download_page(true, &video_model, page_models, &base_path, cx).await
-        }
-        _ => return Err(InnerApiError::BadRequest(format!("Invalid task_index: {}", request.task_index)).into()),
-    };
-    
-    // 更新状态（与定时任务使用相同的逻辑）
-    let mut video_status = VideoStatus::from(video_model.download_status);
-    let result_status = result?;
-    
-    // 记录日志（与定时任务使用相同的格式）
-    let task_names = ["封面", "详情", "作者头像", "作者详情", "分页下载"];
-    if let Some(task_name) = task_names.get(request.task_index) {
-        match &result_status {
-            ExecutionStatus::Skipped => {
-                tracing::info!("处理视频「{}」{}已成功过，跳过", &video_model.name, task_name);
-            }
-            ExecutionStatus::Succeeded => {
-                tracing::info!("处理视频「{}」{}成功", &video_model.name, task_name);
-            }
-            ExecutionStatus::Ignored(e) => {
-                tracing::error!(
-                    "处理视频「{}」{}出现常见错误，已忽略：{:#}",
-                    &video_model.name, task_name, e
-                );
-            }
-            ExecutionStatus::Failed(e) => {
-                tracing::error!("处理视频「{}」{}失败：{:#}", &video_model.name, task_name, e);
-            }
-            ExecutionStatus::Fixed(_) => unreachable!(),
+            dispatch_download_page(true, video_model, page_models, base_path, cx).await
         }
+        _ => Err(InnerApiError::BadRequest(format!("Invalid task_index: {}", task_index)).into()),
     }
-    
-    // 创建一个只包含当前任务结果的数组，其他位置用当前状态填充
-    let current_statuses: [u32; 5] = video_status.into();
-    let mut all_results = [
-        ExecutionStatus::Fixed(current_statuses[0]),
-        ExecutionStatus::Fixed(current_statuses[1]),
-        ExecutionStatus::Fixed(current_statuses[2]),
-        ExecutionStatus::Fixed(current_statuses[3]),
-        ExecutionStatus::Fixed(current_statuses[4]),
-    ];
-    all_results[request.task_index] = result_status;
-    video_status.update_status(&all_results);
-    
-    // 在移动 video_model 之前保存路径信息
-    let should_save_path = video_model.path.is_empty();
-    let mut video_active_model: video::ActiveModel = video_model.into();
-    video_active_model.download_status = Set(video_status.into());
-    // 如果路径为空，保存计算出的路径（与定时任务一致）
-    if should_save_path {
-        video_active_model.path = Set(base_path.to_string_lossy().to_string());
-    }
-    video_active_model.save(&db).await?;
-    
-    // 重新查询更新后的数据
-    let (video_info, pages_info) = tokio::try_join!(
-        video::Entity::find_by_id(id).into_partial_model::<VideoInfo>().one(&db),
-        page::Entity::find()
-            .filter(page::Column::VideoId.eq(id))
-            .order_by_asc(page::Column::Cid)
-            .into_partial_model::<PageInfo>()
-            .all(&db)
-    )?;
-    
-    Ok(ApiResponse::ok(UpdateVideoStatusResponse {
-        success: true,
-        video: video_info.ok_or_else(|| InnerApiError::NotFound(id))?,
-        pages: pages_info,
-    }))
 }
 
-/// 重试分页的单个任务
-pub async fn retry_page_task(
-    Path(id): Path<i32>,
-    Extension(db): Extension<DatabaseConnection>,
-    Extension(bili_client): Extension<Arc<BiliClient>>,
-    ValidatedJson(request): ValidatedJson<RetryPageTaskRequest>,
-) -> Result<ApiResponse<UpdateVideoStatusResponse>, ApiError> {
-    let page_model = page::Entity::find_by_id(id)
-        .one(&db)
-        .await?
-        .ok_or_else(|| InnerApiError::NotFound(id))?;
-    
-    let video_id = page_model.video_id;
-    let video_model = video::Entity::find_by_id(video_id)
-        .one(&db)
-        .await?
-        .ok_or_else(|| InnerApiError::NotFound(video_id))?;
-    
-    // 获取视频源
-    let video_source = get_video_source_from_model(&video_model, &db).await?;
-    
-    // 获取配置和模板
-    let config = VersionedConfig::get().read();
-    let template = TEMPLATE.read();
-    let downloader = Downloader::new(bili_client.client.clone());
-    
-    // 创建下载上下文
-    let cx = DownloadContext::new(
-        &bili_client,
-        &video_source,
-        &template,
-        &db,
-        &downloader,
-        &config,
-    );
-    
-    // 计算路径
-    let is_single_page = video_model.single_page.ok_or_else(|| InnerApiError::BadRequest("single_page is null".to_string()))?;
+/// 计算分页各个任务对应的文件路径（与定时任务使用相同的规则）
+fn page_task_paths(
+    video_model: &video::Model,
+    page_model: &page::Model,
+    video_source: &VideoSourceEnum,
+    template: &handlebars::Handlebars<'_>,
+    config: &Config,
+) -> Result<PagePaths> {
+    let is_single_page = video_model
+        .single_page
+        .ok_or_else(|| InnerApiError::BadRequest("single_page is null".to_string()))?;
     let (base_path, base_name): (PathBuf, String) = if let Some(old_video_path) = &page_model.path
         && !old_video_path.is_empty()
     {
@@ -789,8 +1094,11 @@ pub async fn retry_page_task(
             .to_string_lossy();
         if is_single_page {
             (
-                old_video_path.parent().ok_or_else(|| InnerApiError::BadRequest("invalid page path format".to_string()))?.to_path_buf(),
-                old_video_filename.trim_end_matches(".mp4").to_string(),
+                old_video_path
+                    .parent()
+                    .ok_or_else(|| InnerApiError::BadRequest("invalid page path format".to_string()))?
+                    .to_path_buf(),
+                trim_page_video_extension(&old_video_filename).to_string(),
             )
         } else {
             (
@@ -807,51 +1115,33 @@ pub async fn retry_page_task(
             )
         }
     } else {
-        let video_base_path = if !video_model.path.is_empty() {
-            PathBuf::from(&video_model.path)
-        } else {
-            video_source.path().join(
-                template
-                    .path_safe_render("video", &video_format_args(&video_model, &config.time_format))
-                    .map_err(|e| InnerApiError::BadRequest(format!("Template render error: {}", e)))?,
-            )
-        };
+        let (video_base_path, _) = video_task_paths(video_model, video_source, template, config)?;
         let page_name = template
-            .path_safe_render("page", &page_format_args(&video_model, &page_model, &config.time_format))
-            .map_err(|e| InnerApiError::BadRequest(format!("Template render error: {}", e)))?;
+            .path_safe_render("page", &page_format_args(video_model, page_model, &config.time_format))
+            .map_err(|e| InnerApiError::TemplateError(e.to_string()))?;
         (video_base_path, page_name)
     };
-    
-    // 确保视频源目录存在（与定时任务使用相同的规则）
-    video_source.create_dir_all().await
-        .map_err(|e| {
-            tracing::error!("处理视频「{}」第 {} 页创建视频源目录失败: {}", &video_model.name, page_model.pid, e);
-            InnerApiError::BadRequest(format!("Failed to create video source directory: {}", e))
-        })?;
-    
-    // 注意：不预先创建 base_path 和 Season 1 目录，让下载函数自动创建（与定时任务保持一致）
-    // downloader.fetch() 和 generate_nfo() 会自动创建所需的父目录
-    
-    let (poster_path, video_path, nfo_path, danmaku_path, fanart_path, subtitle_path): (PathBuf, PathBuf, PathBuf, PathBuf, Option<PathBuf>, PathBuf) = if is_single_page {
-        (
-            base_path.join(format!("{}-poster.jpg", &base_name)),
-            base_path.join(format!("{}.mp4", &base_name)),
-            base_path.join(format!("{}.nfo", &base_name)),
-            base_path.join(format!("{}.zh-CN.default.ass", &base_name)),
-            Some(base_path.join(format!("{}-fanart.jpg", &base_name))),
-            base_path.join(format!("{}.srt", &base_name)),
-        )
-    } else {
-        (
-            base_path.join("Season 1").join(format!("{} - S01E{:0>2}-thumb.jpg", &base_name, page_model.pid)),
-            base_path.join("Season 1").join(format!("{} - S01E{:0>2}.mp4", &base_name, page_model.pid)),
-            base_path.join("Season 1").join(format!("{} - S01E{:0>2}.nfo", &base_name, page_model.pid)),
-            base_path.join("Season 1").join(format!("{} - S01E{:0>2}.zh-CN.default.ass", &base_name, page_model.pid)),
-            None,
-            base_path.join("Season 1").join(format!("{} - S01E{:0>2}.srt", &base_name, page_model.pid)),
-        )
-    };
-    
+    page_paths(
+        video_model,
+        page_model,
+        &base_path,
+        &base_name,
+        video_source,
+        template,
+        config,
+    )
+    .map_err(|e| InnerApiError::TemplateError(format!("{:#}", e)).into())
+}
+
+/// 执行分页的单个任务，重新下载分页视频时一并返回下载指标
+async fn run_page_task(
+    task_index: usize,
+    video_model: &video::Model,
+    page_model: &page::Model,
+    paths: &PagePaths,
+    cx: DownloadContext<'_>,
+) -> (Result<ExecutionStatus>, Option<DownloadMetrics>) {
+    let config = cx.config;
     let dimension = match (page_model.width, page_model.height) {
         (Some(width), Some(height)) => Some(crate::bilibili::Dimension {
             width,
@@ -862,54 +1152,51 @@ pub async fn retry_page_task(
     };
     let page_info = BiliPageInfo {
         cid: page_model.cid,
+        page: page_model.pid,
         duration: page_model.duration,
         dimension,
         ..Default::default()
     };
-    
-    // 根据 task_index 调用对应的函数
-    let result = match request.task_index {
+    let result = match task_index {
         0 => {
             // 下载分页封面
             fetch_page_poster(
                 !config.skip_option.no_poster,
-                &video_model,
-                &page_model,
-                poster_path.clone(),
-                fanart_path.clone(),
+                video_model,
+                page_model,
+                poster_from_frame(cx).then_some(paths.video.as_path()),
+                paths.poster.clone(),
+                paths.fanart.clone(),
                 cx,
             )
             .await
         }
         1 => {
             // 下载分页视频
-            fetch_page_video(
-                true,
-                &video_model,
-                &page_info,
-                &video_path,
-                cx,
-            )
-            .await
+            let res = fetch_page_video(true, video_model, &page_info, &paths.video, cx).await;
+            ProgressRegistry::get().remove_page(video_model.id, page_info.cid);
+            return match res {
+                Ok((status, metrics)) => (Ok(status), metrics),
+                Err(e) => (Err(e), None),
+            };
         }
         2 => {
             // 生成分页视频信息的 nfo
-            generate_page_nfo(
-                !config.skip_option.no_video_nfo,
-                &video_model,
-                &page_model,
-                nfo_path,
-                cx,
-            )
-            .await
+            let should_run = !config.skip_option.no_video_nfo;
+            let chapters = if should_run {
+                fetch_page_chapters(video_model, &page_info, cx).await
+            } else {
+                Vec::new()
+            };
+            generate_page_nfo(should_run, video_model, page_model, &chapters, paths.nfo.clone(), cx).await
         }
         3 => {
             // 下载分页弹幕
             fetch_page_danmaku(
                 !config.skip_option.no_danmaku,
-                &video_model,
+                video_model,
                 &page_info,
-                danmaku_path,
+                paths.danmaku.clone(),
                 cx,
             )
             .await
@@ -918,23 +1205,279 @@ pub async fn retry_page_task(
             // 下载分页字幕
             fetch_page_subtitle(
                 !config.skip_option.no_subtitle,
-                &video_model,
+                video_model,
                 &page_info,
-                &subtitle_path,
+                &paths.subtitle,
                 cx,
             )
             .await
         }
-        _ => return Err(InnerApiError::BadRequest(format!("Invalid task_index: {}", request.task_index)).into()),
+        _ => Err(InnerApiError::BadRequest(format!("Invalid task_index: {}", task_index)).into()),
     };
+    (result, None)
+}
+
+/// 重试视频的单个任务
+pub async fn retry_video_task(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(bili_client): Extension<Arc<BiliClient>>,
+    ValidatedJson(request): ValidatedJson<RetryVideoTaskRequest>,
+) -> Result<ApiResponse<UpdateVideoStatusResponse>, ApiError> {
+    let video_model = video::Entity::find_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| InnerApiError::NotFound(id))?;
+    let (video_name, queue_db, queue_client) = (video_model.name.clone(), db.clone(), bili_client.clone());
+    run_in_retry_queue(id, video_name, async move {
+        retry_single_video_task(video_model, request.task_index, &queue_db, &queue_client).await
+    })
+    .await??;
+
+    // 重新查询更新后的数据
+    let (video_info, pages_info) = tokio::try_join!(
+        video::Entity::find_by_id(id).into_partial_model::<VideoInfo>().one(&db),
+        page::Entity::find()
+            .filter(page::Column::VideoId.eq(id))
+            .order_by_asc(page::Column::Cid)
+            .into_partial_model::<PageInfo>()
+            .all(&db)
+    )?;
+    
+    Ok(ApiResponse::ok(UpdateVideoStatusResponse {
+        success: true,
+        video: video_info.ok_or_else(|| InnerApiError::NotFound(id))?,
+        pages: pages_info,
+    }))
+}
+
+/// 将筛选出的所有视频作为一个批次加入重试队列，由后台执行指定的单个任务，同时执行的视频数受视频并发数限制
+///
+/// 返回本次的批次与加入队列的任务，批次中成功与失败的数量可以通过 /tasks/retries/batches/{id} 查询
+pub async fn retry_filtered_video_task(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(bili_client): Extension<Arc<BiliClient>>,
+    ValidatedJson(request): ValidatedJson<RetryFilteredVideoTaskRequest>,
+) -> Result<ApiResponse<RetryFilteredVideoTaskResponse>, ApiError> {
+    // 收费视频与无需下载的视频不参与重试
+    let query = VideoFilter::from(&request).apply(
+        video::Entity::find().filter(
+            video::Column::IsPaidVideo
+                .eq(false)
+                .and(video::Column::ShouldDownload.eq(true)),
+        ),
+    )?;
+    let task_index = request.task_index;
+    let jobs = query
+        .order_by_asc(video::Column::Id)
+        .all(&db)
+        .await?
+        .into_iter()
+        .map(|video_model| {
+            let (db, bili_client) = (db.clone(), bili_client.clone());
+            let (video_id, video_name) = (video_model.id, video_model.name.clone());
+            (video_id, video_name.clone(), async move {
+                match retry_single_video_task(video_model, task_index, &db, &bili_client).await {
+                    Ok(succeeded) => succeeded,
+                    Err(e) => {
+                        tracing::warn!("重试视频「{}」的任务 {} 时出错：{:#}", video_name, task_index, e);
+                        false
+                    }
+                }
+            })
+        })
+        .collect();
+    let batch = RETRY_QUEUE.enqueue_batch(jobs);
+    let jobs = RETRY_QUEUE
+        .list()
+        .into_iter()
+        .filter(|job| job.batch_id == Some(batch.id))
+        .collect();
+    Ok(ApiResponse::ok(RetryFilteredVideoTaskResponse { batch, jobs }))
+}
+
+/// 执行视频的单个任务并保存执行后的状态，返回该任务是否执行成功
+async fn retry_single_video_task(
+    video_model: video::Model,
+    task_index: usize,
+    db: &DatabaseConnection,
+    bili_client: &BiliClient,
+) -> Result<bool, ApiError> {
+    // 获取视频源
+    let video_source = get_video_source_from_model(&video_model, db).await?;
+    
+    // 获取配置和模板
+    let config = VersionedConfig::get().read();
+    let template = TEMPLATE.read();
+    let downloader = Downloader::from_config(bili_client.client.clone(), &config);
+    
+    // 创建下载上下文
+    let cx = DownloadContext::new(bili_client, &video_source, &template, db, &downloader, &config, false);
+    
+    // 计算路径
+    let (base_path, base_upper_path) = video_task_paths(&video_model, &video_source, &template, &config)?;
+
+    // 确保视频源目录存在（与定时任务使用相同的规则）
+    video_source.create_dir_all().await
+        .map_err(|e| {
+            tracing::error!("处理视频「{}」创建视频源目录失败: {}", &video_model.name, e);
+            InnerApiError::BadRequest(format!("Failed to create video source directory: {}", e))
+        })?;
+    
+    // 注意：不预先创建 base_path 和 base_upper_path，让下载函数自动创建（与定时任务保持一致）
+    // downloader.fetch() 和 generate_nfo() 会自动创建所需的父目录
+    
+    // 根据 task_index 调用对应的函数
+    let result = run_video_task(task_index, &video_model, &base_path, &base_upper_path, cx).await;
+    
+    // 更新状态（与定时任务使用相同的逻辑）
+    // 与定时任务一致，普通错误计入失败次数，风控错误不改变状态并返回风控错误
+    let mut video_status = VideoStatus::from(video_model.download_status);
+    let result_status = cx.execution_status(result);
+    let succeeded = matches!(result_status, ExecutionStatus::Succeeded | ExecutionStatus::Skipped);
+    
+    // 记录日志（与定时任务使用相同的格式）
+    let task_names = ["封面", "详情", "作者头像", "作者详情", "分页下载"];
+    if let Some(task_name) = task_names.get(task_index) {
+        match &result_status {
+            ExecutionStatus::Skipped => {
+                tracing::info!("处理视频「{}」{}已成功过，跳过", &video_model.name, task_name);
+            }
+            ExecutionStatus::Succeeded => {
+                tracing::info!("处理视频「{}」{}成功", &video_model.name, task_name);
+            }
+            ExecutionStatus::Ignored(e) => {
+                tracing::error!(
+                    "处理视频「{}」{}出现常见错误，已忽略：{:#}",
+                    &video_model.name, task_name, e
+                );
+            }
+            ExecutionStatus::Failed(e) => {
+                tracing::error!("处理视频「{}」{}失败：{:#}", &video_model.name, task_name, e);
+            }
+            ExecutionStatus::Fixed(_) => unreachable!(),
+        }
+    }
+    
+    check_risk_control(apply_task_result(
+        &mut video_status,
+        task_index,
+        result_status,
+        config.max_auto_retries,
+    ))?;
+    record_video_task_events(&video_model, video_status, db).await;
+    on_video_retried(
+        VideoStatus::from(video_model.download_status),
+        video_status,
+        &base_path,
+        &video_model,
+        cx,
+    )
+    .await;
+    
+    // 在移动 video_model 之前保存路径信息
+    let should_save_path = video_model.path.is_empty();
+    let video_id = video_model.id;
+    let mut video_active_model: video::ActiveModel = video_model.into();
+    video_active_model.download_status = Set(video_status.into());
+    // 如果路径为空，保存计算出的路径（与定时任务一致）
+    if should_save_path {
+        video_active_model.path = Set(base_path.to_string_lossy().to_string());
+    }
+    video_active_model.save(db).await?;
+    update_manifest_after_retry(&config, &video_source, video_id, video_status, db).await;
+    Ok(succeeded)
+}
+
+/// 重试分页的单个任务
+pub async fn retry_page_task(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(bili_client): Extension<Arc<BiliClient>>,
+    ValidatedJson(request): ValidatedJson<RetryPageTaskRequest>,
+) -> Result<ApiResponse<UpdateVideoStatusResponse>, ApiError> {
+    let page_model = page::Entity::find_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| InnerApiError::NotFound(id))?;
+    
+    let video_id = page_model.video_id;
+    let video_model = video::Entity::find_by_id(video_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| InnerApiError::NotFound(video_id))?;
+    
+    let (video_name, queue_db, queue_client) = (
+        format!("{} 第 {} 页", video_model.name, page_model.pid),
+        db.clone(),
+        bili_client.clone(),
+    );
+    run_in_retry_queue(video_id, video_name, async move {
+        retry_single_page_task(video_model, page_model, request.task_index, &queue_db, &queue_client).await
+    })
+    .await??;
+
+    // 重新查询更新后的数据
+    let (video_info, pages_info) = tokio::try_join!(
+        video::Entity::find_by_id(video_id).into_partial_model::<VideoInfo>().one(&db),
+        page::Entity::find()
+            .filter(page::Column::VideoId.eq(video_id))
+            .order_by_asc(page::Column::Cid)
+            .into_partial_model::<PageInfo>()
+            .all(&db)
+    )?;
+    
+    Ok(ApiResponse::ok(UpdateVideoStatusResponse {
+        success: true,
+        video: video_info.ok_or_else(|| InnerApiError::NotFound(video_id))?,
+        pages: pages_info,
+    }))
+}
+
+/// 执行分页的单个任务并保存执行后的状态
+async fn retry_single_page_task(
+    video_model: video::Model,
+    page_model: page::Model,
+    task_index: usize,
+    db: &DatabaseConnection,
+    bili_client: &BiliClient,
+) -> Result<(), ApiError> {
+    let video_id = video_model.id;
+    // 获取视频源
+    let video_source = get_video_source_from_model(&video_model, db).await?;
+    
+    // 获取配置和模板
+    let config = VersionedConfig::get().read();
+    let template = TEMPLATE.read();
+    let downloader = Downloader::from_config(bili_client.client.clone(), &config);
+    
+    // 创建下载上下文
+    let cx = DownloadContext::new(bili_client, &video_source, &template, db, &downloader, &config, false);
+    
+    // 计算路径
+    let paths = page_task_paths(&video_model, &page_model, &video_source, &template, &config)?;
+    
+    // 确保视频源目录存在（与定时任务使用相同的规则）
+    video_source.create_dir_all().await
+        .map_err(|e| {
+            tracing::error!("处理视频「{}」第 {} 页创建视频源目录失败: {}", &video_model.name, page_model.pid, e);
+            InnerApiError::BadRequest(format!("Failed to create video source directory: {}", e))
+        })?;
+    
+    // 注意：不预先创建 base_path 和 Season 目录，让下载函数自动创建（与定时任务保持一致）
+    // downloader.fetch() 和 generate_nfo() 会自动创建所需的父目录
+    
+    // 根据 task_index 调用对应的函数
+    let (result, metrics) = run_page_task(task_index, &video_model, &page_model, &paths, cx).await;
     
     // 更新状态（与定时任务使用相同的逻辑）
+    // 与定时任务一致，普通错误计入失败次数，风控错误不改变状态并返回风控错误
     let mut page_status = PageStatus::from(page_model.download_status);
-    let result_status = result?;
+    let result_status = cx.execution_status(result);
     
     // 记录日志（与定时任务使用相同的格式）
     let task_names = ["封面", "视频", "详情", "弹幕", "字幕"];
-    if let Some(task_name) = task_names.get(request.task_index) {
+    if let Some(task_name) = task_names.get(task_index) {
         match &result_status {
             ExecutionStatus::Skipped => {
                 tracing::info!(
@@ -964,31 +1507,30 @@ pub async fn retry_page_task(
         }
     }
     
-    // 创建一个只包含当前任务结果的数组，其他位置用当前状态填充
-    let current_statuses: [u32; 5] = page_status.into();
-    let mut all_results = [
-        ExecutionStatus::Fixed(current_statuses[0]),
-        ExecutionStatus::Fixed(current_statuses[1]),
-        ExecutionStatus::Fixed(current_statuses[2]),
-        ExecutionStatus::Fixed(current_statuses[3]),
-        ExecutionStatus::Fixed(current_statuses[4]),
-    ];
-    all_results[request.task_index] = result_status;
-    page_status.update_status(&all_results);
+    check_risk_control(apply_task_result(
+        &mut page_status,
+        task_index,
+        result_status,
+        config.max_auto_retries,
+    ))?;
+    record_page_task_events(&video_model, &page_model, page_status, db).await;
     
     let mut page_active_model: page::ActiveModel = page_model.into();
     page_active_model.download_status = Set(page_status.into());
     // 保存路径（与定时任务一致）
-    page_active_model.path = Set(Some(video_path.to_string_lossy().to_string()));
-    page_active_model.save(&db).await?;
+    page_active_model.path = Set(Some(paths.video.to_string_lossy().to_string()));
+    if let Some(metrics) = metrics {
+        metrics.set_to(&mut page_active_model);
+    }
+    page_active_model.save(db).await?;
     
     // 如果重试的是分页下载任务（task_index=1），还需要更新视频的"分页下载"状态
-    if request.task_index == 1 {
+    if task_index == 1 {
         let mut video_status = VideoStatus::from(video_model.download_status);
         // 检查所有分页的下载状态，取最小值
         let pages = page::Entity::find()
             .filter(page::Column::VideoId.eq(video_id))
-            .all(&db)
+            .all(db)
             .await?;
         let mut min_status = 7u32; // STATUS_OK
         for page in pages {
@@ -997,24 +1539,1895 @@ pub async fn retry_page_task(
             min_status = min_status.min(separate_status[1]); // task_index 1 是视频下载
         }
         video_status.set(4, min_status); // 视频的 task_index 4 是分页下载
+        record_video_task_events(&video_model, video_status, db).await;
+        let (base_path, _) = video_task_paths(&video_model, &video_source, &template, &config)?;
+        on_video_retried(
+            VideoStatus::from(video_model.download_status),
+            video_status,
+            &base_path,
+            &video_model,
+            cx,
+        )
+        .await;
         let mut video_active_model: video::ActiveModel = video_model.into();
         video_active_model.download_status = Set(video_status.into());
-        video_active_model.save(&db).await?;
+        video_active_model.save(db).await?;
+        update_manifest_after_retry(&config, &video_source, video_id, video_status, db).await;
+    }
+    Ok(())
+}
+
+/// 依次重试视频及其所有分页中失败的任务
+pub async fn retry_all_failed_tasks(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(bili_client): Extension<Arc<BiliClient>>,
+) -> Result<ApiResponse<RetryAllFailedTasksResponse>, ApiError> {
+    let video_name = video::Entity::find_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| InnerApiError::NotFound(id))?
+        .name;
+    let response = run_in_retry_queue(id, video_name, retry_failed_tasks(id, db, bili_client)).await??;
+    Ok(ApiResponse::ok(response))
+}
+
+/// 通过重试队列执行重试，使其可以在重试队列中查看，并能在开始执行前被取消
+async fn run_in_retry_queue<T: Send + 'static>(
+    video_id: i32,
+    video_name: String,
+    future: impl Future<Output = T> + Send + 'static,
+) -> Result<T, ApiError> {
+    RETRY_QUEUE
+        .run(video_id, video_name, future)
+        .await
+        .ok_or_else(|| InnerApiError::Conflict("重试任务已被取消".to_owned()).into())
+}
+
+/// 依次重试视频及其所有分页中失败的任务，供单个视频的重试接口与重试队列共用
+pub(super) async fn retry_failed_tasks(
+    id: i32,
+    db: DatabaseConnection,
+    bili_client: Arc<BiliClient>,
+) -> Result<RetryAllFailedTasksResponse> {
+    let video_model = video::Entity::find_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| InnerApiError::NotFound(id))?;
+    if video_model.is_paid_video || !video_model.should_download {
+        return Err(InnerApiError::BadRequest("收费视频或无需下载的视频不支持重试".to_string()).into());
+    }
+    let page_models = page::Entity::find()
+        .filter(page::Column::VideoId.eq(id))
+        .order_by_asc(page::Column::Cid)
+        .all(&db)
+        .await?;
+
+    let video_source = get_video_source_from_model(&video_model, &db).await?;
+    let config = VersionedConfig::get().read();
+    let template = TEMPLATE.read();
+    let downloader = Downloader::from_config(bili_client.client.clone(), &config);
+    let cx = DownloadContext::new(&bili_client, &video_source, &template, &db, &downloader, &config, false);
+    video_source.create_dir_all().await.map_err(|e| {
+        tracing::error!("处理视频「{}」创建视频源目录失败: {}", &video_model.name, e);
+        InnerApiError::BadRequest(format!("Failed to create video source directory: {}", e))
+    })?;
+
+    let (mut succeeded, mut failed) = (Vec::new(), Vec::new());
+    // 视频层的“分页下载”任务状态由分页决定，此处仅重试前四个任务，分页中失败的任务在下面逐一重试
+    let (base_path, base_upper_path) = video_task_paths(&video_model, &video_source, &template, &config)?;
+    let mut video_status = VideoStatus::from(video_model.download_status);
+    let video_statuses: [u32; 5] = video_status.into();
+    for task_index in failed_task_indices(&video_statuses).filter(|&task_index| task_index < 4) {
+        let result = run_video_task(task_index, &video_model, &base_path, &base_upper_path, cx).await;
+        let task = RetriedTask {
+            page_id: None,
+            task_index,
+        };
+        match apply_task_result(
+            &mut video_status,
+            task_index,
+            cx.execution_status(result),
+            config.max_auto_retries,
+        ) {
+            Ok(()) => succeeded.push(task),
+            Err(e) => {
+                tracing::error!("重试视频「{}」的任务 {} 失败：{:#}", &video_model.name, task_index, e);
+                failed.push(task);
+            }
+        }
+    }
+
+    let mut page_download_status = STATUS_OK;
+    for page_model in page_models {
+        let mut page_status = PageStatus::from(page_model.download_status);
+        let page_statuses: [u32; 5] = page_status.into();
+        let failed_indices = failed_task_indices(&page_statuses).collect::<Vec<_>>();
+        if !failed_indices.is_empty() {
+            let paths = page_task_paths(&video_model, &page_model, &video_source, &template, &config)?;
+            let mut video_metrics = None;
+            for task_index in failed_indices {
+                let (result, metrics) = run_page_task(task_index, &video_model, &page_model, &paths, cx).await;
+                video_metrics = video_metrics.or(metrics);
+                let task = RetriedTask {
+                    page_id: Some(page_model.id),
+                    task_index,
+                };
+                match apply_task_result(
+                    &mut page_status,
+                    task_index,
+                    cx.execution_status(result),
+                    config.max_auto_retries,
+                ) {
+                    Ok(()) => succeeded.push(task),
+                    Err(e) => {
+                        tracing::error!(
+                            "重试视频「{}」第 {} 页的任务 {} 失败：{:#}",
+                            &video_model.name,
+                            page_model.pid,
+                            task_index,
+                            e
+                        );
+                        failed.push(task);
+                    }
+                }
+            }
+            record_page_task_events(&video_model, &page_model, page_status, &db).await;
+            let path = paths.video.to_string_lossy().to_string();
+            let mut page_active_model: page::ActiveModel = page_model.into();
+            page_active_model.download_status = Set(page_status.into());
+            page_active_model.path = Set(Some(path));
+            if let Some(metrics) = video_metrics {
+                metrics.set_to(&mut page_active_model);
+            }
+            page_active_model.save(&db).await?;
+        }
+        // 与定时任务一致，视频层“分页下载”任务的状态取所有分页子任务状态的最小值
+        let page_statuses: [u32; 5] = page_status.into();
+        page_download_status = page_statuses.into_iter().fold(page_download_status, u32::min);
+    }
+    if is_failed(video_statuses[4]) {
+        video_status.set(4, page_download_status);
+    }
+    record_video_task_events(&video_model, video_status, &db).await;
+    on_video_retried(
+        VideoStatus::from(video_model.download_status),
+        video_status,
+        &base_path,
+        &video_model,
+        cx,
+    )
+    .await;
+
+    let should_save_path = video_model.path.is_empty();
+    let mut video_active_model: video::ActiveModel = video_model.into();
+    video_active_model.download_status = Set(video_status.into());
+    if should_save_path {
+        video_active_model.path = Set(base_path.to_string_lossy().to_string());
+    }
+    video_active_model.save(&db).await?;
+    update_manifest_after_retry(&config, &video_source, id, video_status, &db).await;
+
+    let (video_info, pages_info) = tokio::try_join!(
+        video::Entity::find_by_id(id).into_partial_model::<VideoInfo>().one(&db),
+        page::Entity::find()
+            .filter(page::Column::VideoId.eq(id))
+            .order_by_asc(page::Column::Cid)
+            .into_partial_model::<PageInfo>()
+            .all(&db)
+    )?;
+
+    Ok(RetryAllFailedTasksResponse {
+        succeeded,
+        failed,
+        video: video_info.ok_or_else(|| InnerApiError::NotFound(id))?,
+        pages: pages_info,
+    })
+}
+
+/// 检查视频及其所有分页的子任务对应的文件是否已存在，将文件已存在但尚未成功的子任务直接标记为成功
+/// 适用于从备份中恢复文件后，避免定时任务重新下载
+pub async fn reconcile_video(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<ApiResponse<ReconcileVideoResponse>, ApiError> {
+    let video_model = video::Entity::find_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| InnerApiError::NotFound(id))?;
+    let (config, template) = (VersionedConfig::get().snapshot(), TEMPLATE.snapshot());
+    let reconciled = reconcile_video_files(video_model, &config, &template, &db).await?;
+    let (video_info, pages_info) = tokio::try_join!(
+        video::Entity::find_by_id(id).into_partial_model::<VideoInfo>().one(&db),
+        page::Entity::find()
+            .filter(page::Column::VideoId.eq(id))
+            .order_by_asc(page::Column::Cid)
+            .into_partial_model::<PageInfo>()
+            .all(&db)
+    )?;
+    Ok(ApiResponse::ok(ReconcileVideoResponse {
+        reconciled,
+        video: video_info.ok_or_else(|| InnerApiError::NotFound(id))?,
+        pages: pages_info,
+    }))
+}
+
+/// 对筛选出的所有视频执行与 reconcile_video 相同的检查
+pub async fn reconcile_filtered_videos(
+    Extension(db): Extension<DatabaseConnection>,
+    Json(request): Json<ReconcileFilteredVideosRequest>,
+) -> Result<ApiResponse<ReconcileFilteredVideosResponse>, ApiError> {
+    let mut query = video::Entity::find();
+    for (field, column) in [
+        (request.collection, video::Column::CollectionId),
+        (request.favorite, video::Column::FavoriteId),
+        (request.submission, video::Column::SubmissionId),
+        (request.watch_later, video::Column::WatchLaterId),
+    ] {
+        if let Some(id) = field {
+            query = query.filter(column.eq(id));
+        }
+    }
+    if let Some(query_word) = request.query {
+        query = query.filter(video_search_condition(&query_word, None)?);
+    }
+    if let Some(status_filter) = request.status_filter {
+        query = query.filter(status_filter.to_video_query());
+    }
+    let (config, template) = (VersionedConfig::get().snapshot(), TEMPLATE.snapshot());
+    let (mut reconciled_videos_count, mut reconciled_page_ids) = (0, HashSet::new());
+    for video_model in query.all(&db).await? {
+        let reconciled = reconcile_video_files(video_model, &config, &template, &db).await?;
+        if !reconciled.is_empty() {
+            reconciled_videos_count += 1;
+        }
+        reconciled_page_ids.extend(reconciled.iter().filter_map(|task| task.page_id));
+    }
+    Ok(ApiResponse::ok(ReconcileFilteredVideosResponse {
+        reconciled_videos_count,
+        reconciled_pages_count: reconciled_page_ids.len(),
+    }))
+}
+
+/// 按照与定时任务相同的规则计算各个子任务的文件路径，将文件已存在的子任务标记为成功并写入数据库
+async fn reconcile_video_files(
+    video_model: video::Model,
+    config: &Config,
+    template: &handlebars::Handlebars<'_>,
+    db: &DatabaseConnection,
+) -> Result<Vec<RetriedTask>, ApiError> {
+    let page_models = page::Entity::find()
+        .filter(page::Column::VideoId.eq(video_model.id))
+        .order_by_asc(page::Column::Cid)
+        .all(db)
+        .await?;
+    let video_source = get_video_source_from_model(&video_model, db).await?;
+    let mut reconciled = Vec::new();
+    let has_pages = !page_models.is_empty();
+    let mut page_download_status = STATUS_OK;
+    for page_model in page_models {
+        let paths = page_task_paths(&video_model, &page_model, &video_source, &template, &config)?;
+        let exists = [
+            file_exists(&paths.poster).await,
+            file_exists(&paths.video).await,
+            file_exists(&paths.nfo).await,
+            file_exists(&paths.danmaku).await,
+            page_subtitle_files(&paths.subtitle)
+                .await
+                .is_ok_and(|subtitles| !subtitles.is_empty()),
+        ];
+        let mut page_status = PageStatus::from(page_model.download_status);
+        let task_indices = reconcile_status(&mut page_status, exists);
+        let page_statuses: [u32; 5] = page_status.into();
+        page_download_status = page_statuses.into_iter().fold(page_download_status, u32::min);
+        if task_indices.is_empty() {
+            continue;
+        }
+        record_page_task_events(&video_model, &page_model, page_status, db).await;
+        reconciled.extend(task_indices.into_iter().map(|task_index| RetriedTask {
+            page_id: Some(page_model.id),
+            task_index,
+        }));
+        let mut page_active_model: page::ActiveModel = page_model.into();
+        page_active_model.download_status = Set(page_status.into());
+        page_active_model.path = Set(Some(paths.video.to_string_lossy().to_string()));
+        page_active_model.save(db).await?;
+    }
+
+    let (base_path, base_upper_path) = video_task_paths(&video_model, &video_source, &template, &config)?;
+    let image_ext = config.cover_format.extension();
+    let exists = [
+        file_exists(&base_path.join(format!("poster.{}", image_ext))).await,
+        file_exists(&base_path.join("tvshow.nfo")).await,
+        file_exists(&base_upper_path.join(format!("folder.{}", image_ext))).await,
+        file_exists(&base_upper_path.join("person.nfo")).await,
+        // 与定时任务一致，所有分页的子任务均成功时视频层的“分页下载”任务才算成功
+        has_pages && page_download_status == STATUS_OK,
+    ];
+    let mut video_status = VideoStatus::from(video_model.download_status);
+    let task_indices = reconcile_status(&mut video_status, exists);
+    if task_indices.is_empty() {
+        return Ok(reconciled);
+    }
+    record_video_task_events(&video_model, video_status, db).await;
+    reconciled.extend(task_indices.into_iter().map(|task_index| RetriedTask {
+        page_id: None,
+        task_index,
+    }));
+    let should_save_path = video_model.path.is_empty();
+    let mut video_active_model: video::ActiveModel = video_model.into();
+    video_active_model.download_status = Set(video_status.into());
+    if should_save_path {
+        video_active_model.path = Set(base_path.to_string_lossy().to_string());
+    }
+    video_active_model.save(db).await?;
+    Ok(reconciled)
+}
+
+/// 视频层中与元数据相关的子任务：封面、视频 nfo、UP 主头像与 UP 主 nfo
+const VIDEO_METADATA_TASKS: [usize; 4] = [0, 1, 2, 3];
+/// 分页中与元数据相关的子任务：封面与分页 nfo
+const PAGE_METADATA_TASKS: [usize; 2] = [0, 2];
+
+/// 使用当前的配置与模板重新生成视频的元数据（nfo 与封面等），不会重新下载视频与弹幕、字幕
+pub async fn refresh_video_metadata(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(bili_client): Extension<Arc<BiliClient>>,
+) -> Result<ApiResponse<RefreshMetadataResponse>, ApiError> {
+    let video_model = video::Entity::find_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| InnerApiError::NotFound(id))?;
+    if video_model.path.is_empty() {
+        return Err(InnerApiError::BadRequest("视频尚未下载，没有可供刷新的元数据".to_string()).into());
+    }
+    let (succeeded, failed) = refresh_metadata_tasks(video_model, &db, &bili_client).await?;
+    let (video_info, pages_info) = tokio::try_join!(
+        video::Entity::find_by_id(id).into_partial_model::<VideoInfo>().one(&db),
+        page::Entity::find()
+            .filter(page::Column::VideoId.eq(id))
+            .order_by_asc(page::Column::Cid)
+            .into_partial_model::<PageInfo>()
+            .all(&db)
+    )?;
+    Ok(ApiResponse::ok(RefreshMetadataResponse {
+        succeeded,
+        failed,
+        video: video_info.ok_or_else(|| InnerApiError::NotFound(id))?,
+        pages: pages_info,
+    }))
+}
+
+/// 对筛选出的所有已下载视频执行与 refresh_video_metadata 相同的刷新
+pub async fn refresh_filtered_videos_metadata(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(bili_client): Extension<Arc<BiliClient>>,
+    Json(request): Json<RefreshFilteredMetadataRequest>,
+) -> Result<ApiResponse<RefreshFilteredMetadataResponse>, ApiError> {
+    // 尚未下载的视频没有可供刷新的元数据
+    let mut query = video::Entity::find().filter(video::Column::Path.ne(""));
+    for (field, column) in [
+        (request.collection, video::Column::CollectionId),
+        (request.favorite, video::Column::FavoriteId),
+        (request.submission, video::Column::SubmissionId),
+        (request.watch_later, video::Column::WatchLaterId),
+    ] {
+        if let Some(id) = field {
+            query = query.filter(column.eq(id));
+        }
+    }
+    if let Some(query_word) = request.query {
+        query = query.filter(video_search_condition(&query_word, None)?);
+    }
+    if let Some(status_filter) = request.status_filter {
+        query = query.filter(status_filter.to_video_query());
+    }
+    // 刷新期间持有下载任务的运行锁，避免与定时任务同时更新视频与分页的状态
+    let _lock = match DownloadTaskManager::try_get() {
+        Some(task_manager) => Some(
+            task_manager
+                .try_lock_running()
+                .ok_or_else(|| InnerApiError::Conflict("下载任务正在执行，请稍后再试".to_string()))?,
+        ),
+        None => None,
+    };
+    let (mut refreshed_videos_count, mut failed_tasks_count, mut failed_videos) = (0, 0, Vec::new());
+    for video_model in query.all(&db).await? {
+        let (id, name) = (video_model.id, video_model.name.clone());
+        match refresh_metadata_tasks(video_model, &db, &bili_client).await {
+            Ok((_, failed)) => {
+                refreshed_videos_count += 1;
+                failed_tasks_count += failed.len();
+            }
+            Err(e) => {
+                tracing::warn!("刷新视频「{}」的元数据失败：{:#}", name, e);
+                failed_videos.push(FailedVideo {
+                    id,
+                    name,
+                    error: format!("{:#}", e),
+                });
+            }
+        }
+    }
+    Ok(ApiResponse::ok(RefreshFilteredMetadataResponse {
+        refreshed_videos_count,
+        failed_tasks_count,
+        failed_videos,
+    }))
+}
+
+/// 依次执行视频与已下载分页中与元数据相关的子任务，执行成功的子任务标记为成功，失败时保持原有状态
+async fn refresh_metadata_tasks(
+    video_model: video::Model,
+    db: &DatabaseConnection,
+    bili_client: &BiliClient,
+) -> Result<(Vec<RetriedTask>, Vec<RetriedTask>), ApiError> {
+    let page_models = page::Entity::find()
+        .filter(page::Column::VideoId.eq(video_model.id))
+        .order_by_asc(page::Column::Cid)
+        .all(db)
+        .await?;
+    let video_source = get_video_source_from_model(&video_model, db).await?;
+    let config = VersionedConfig::get().read();
+    let template = TEMPLATE.read();
+    let downloader = Downloader::from_config(bili_client.client.clone(), &config);
+    let cx = DownloadContext::new(bili_client, &video_source, &template, db, &downloader, &config, false);
+    let (mut succeeded, mut failed) = (Vec::new(), Vec::new());
+
+    let (base_path, base_upper_path) = video_task_paths(&video_model, &video_source, &template, &config)?;
+    let mut video_status = VideoStatus::from(video_model.download_status);
+    for task_index in VIDEO_METADATA_TASKS {
+        let result = run_video_task(task_index, &video_model, &base_path, &base_upper_path, cx).await;
+        let task = RetriedTask {
+            page_id: None,
+            task_index,
+        };
+        match apply_task_result(
+            &mut video_status,
+            task_index,
+            cx.execution_status(result),
+            config.max_auto_retries,
+        ) {
+            Ok(()) => succeeded.push(task),
+            Err(e) => {
+                tracing::error!(
+                    "刷新视频「{}」的元数据任务 {} 失败：{:#}",
+                    &video_model.name,
+                    task_index,
+                    e
+                );
+                failed.push(task);
+            }
+        }
+    }
+
+    for page_model in page_models {
+        // 尚未下载的分页不处理，避免在视频缺失的情况下单独生成元数据文件
+        if page_model.path.as_deref().is_none_or(str::is_empty) {
+            continue;
+        }
+        let paths = page_task_paths(&video_model, &page_model, &video_source, &template, &config)?;
+        let mut page_status = PageStatus::from(page_model.download_status);
+        for task_index in PAGE_METADATA_TASKS {
+            let (result, _) = run_page_task(task_index, &video_model, &page_model, &paths, cx).await;
+            let task = RetriedTask {
+                page_id: Some(page_model.id),
+                task_index,
+            };
+            match apply_task_result(
+                &mut page_status,
+                task_index,
+                cx.execution_status(result),
+                config.max_auto_retries,
+            ) {
+                Ok(()) => succeeded.push(task),
+                Err(e) => {
+                    tracing::error!(
+                        "刷新视频「{}」第 {} 页的元数据任务 {} 失败：{:#}",
+                        &video_model.name,
+                        page_model.pid,
+                        task_index,
+                        e
+                    );
+                    failed.push(task);
+                }
+            }
+        }
+        record_page_task_events(&video_model, &page_model, page_status, db).await;
+        let mut page_active_model: page::ActiveModel = page_model.into();
+        page_active_model.download_status = Set(page_status.into());
+        page_active_model.save(db).await?;
+    }
+
+    record_video_task_events(&video_model, video_status, db).await;
+    let mut video_active_model: video::ActiveModel = video_model.into();
+    video_active_model.download_status = Set(video_status.into());
+    video_active_model.save(db).await?;
+    Ok((succeeded, failed))
+}
+
+async fn file_exists(path: &std::path::Path) -> bool {
+    tokio::fs::try_exists(path).await.unwrap_or(false)
+}
+
+/// 将尚未成功、但对应文件已存在的子任务标记为成功，返回被标记的子任务序号
+fn reconcile_status<const N: usize, C: Copy>(status: &mut Status<N, C>, exists: [bool; N]) -> Vec<usize> {
+    let statuses: [u32; N] = (*status).into();
+    let task_indices = statuses
+        .into_iter()
+        .zip(exists)
+        .enumerate()
+        .filter(|(_, (task_status, exists))| *exists && *task_status != STATUS_OK)
+        .map(|(task_index, _)| task_index)
+        .collect::<Vec<_>>();
+    for &task_index in &task_indices {
+        status.set(task_index, STATUS_OK);
+    }
+    task_indices
+}
+
+/// 子任务状态既非未开始也非成功时，认为该子任务失败
+fn is_failed(status: u32) -> bool {
+    status != STATUS_NOT_STARTED && status != STATUS_OK
+}
+
+fn failed_task_indices(statuses: &[u32]) -> impl Iterator<Item = usize> + '_ {
+    statuses
+        .iter()
+        .enumerate()
+        .filter(|(_, status)| is_failed(**status))
+        .map(|(task_index, _)| task_index)
+}
+
+/// 将单个子任务的执行结果应用到状态上，其余子任务的状态保持不变，执行失败时返回对应的错误
+/// 执行成功时直接将状态置为 STATUS_OK（即使此前已经达到最大重试次数），失败时与定时任务一样计入失败次数，
+/// 失败次数达到 max_retries 后标记为最终失败，之后不再被定时任务自动重试，但仍可以手动重试
+/// 风控导致的失败只是暂时的，此时不修改状态，由调用方决定是否提示用户稍后再试
+fn apply_task_result<const N: usize, C: Copy>(
+    status: &mut Status<N, C>,
+    task_index: usize,
+    result: ExecutionStatus,
+    max_retries: u32,
+) -> Result<()> {
+    if matches!(result, ExecutionStatus::Succeeded | ExecutionStatus::Skipped) {
+        status.set(task_index, STATUS_OK);
+        return Ok(());
+    }
+    let statuses: [u32; N] = (*status).into();
+    let mut results = statuses.map(ExecutionStatus::Fixed);
+    results[task_index] = result;
+    if let ExecutionStatus::Failed(e) = &results[task_index]
+        && !is_risk_control_error(e)
+    {
+        status.update_status_with_max_retries(&results, max_retries);
+    }
+    match results.into_iter().nth(task_index) {
+        Some(ExecutionStatus::Failed(e) | ExecutionStatus::Ignored(e)) => Err(e),
+        _ => unreachable!(),
+    }
+}
+
+/// 单个子任务的重试接口在触发风控时返回风控错误，其它失败已经记录在状态中，照常返回更新后的状态
+fn check_risk_control(result: Result<()>) -> Result<(), ApiError> {
+    match result {
+        Err(e) if is_risk_control_error(&e) => Err(InnerApiError::RiskControl(format!("{:#}", e)).into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CoverFormat, VideoContainer};
+    use crate::utils::model::filter_unhandled_video_pages;
+    use crate::utils::test_utils::{TestDatabase, test_favorite, test_page, test_video};
+
+    #[tokio::test]
+    async fn test_retry_all_failed_tasks() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let video_dir = test_dir.join("videos");
+        let favorite = test_favorite(video_dir.to_string_lossy().to_string())
+            .insert(&connection)
+            .await?;
+        // 视频层仅“分页下载”失败，两个分页均为“详情”失败
+        let video = video::ActiveModel {
+            favorite_id: Set(Some(favorite.id)),
+            path: Set(video_dir.join("测试视频").to_string_lossy().to_string()),
+            download_status: Set(VideoStatus::from([STATUS_OK, STATUS_OK, STATUS_OK, STATUS_OK, 1]).into()),
+            single_page: Set(Some(false)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        for pid in 1..=2 {
+            page::ActiveModel {
+                download_status: Set(PageStatus::from([STATUS_OK, STATUS_OK, 1, STATUS_OK, STATUS_OK]).into()),
+                ..test_page(video.id, pid)
+            }
+            .insert(&connection)
+            .await?;
+        }
+        let Ok(response) = retry_all_failed_tasks(
+            Path(video.id),
+            Extension(connection.clone()),
+            Extension(Arc::new(BiliClient::new()?)),
+        )
+        .await
+        else {
+            panic!("failed to retry all failed tasks");
+        };
+        let response = serde_json::to_value(response)?;
+        let succeeded = response["data"]["succeeded"]
+            .as_array()
+            .expect("succeeded should be an array");
+        assert_eq!(succeeded.len(), 2);
+        assert!(
+            succeeded
+                .iter()
+                .all(|task| task["task_index"] == 2 && task["page_id"].is_number())
+        );
+        assert!(
+            response["data"]["failed"]
+                .as_array()
+                .is_some_and(|failed| failed.is_empty())
+        );
+        for page in page::Entity::find().all(&connection).await? {
+            let statuses: [u32; 5] = PageStatus::from(page.download_status).into();
+            assert_eq!(statuses, [STATUS_OK; 5]);
+            let nfo_path = PathBuf::from(page.path.expect("page path should be saved")).with_extension("nfo");
+            assert!(nfo_path.exists());
+        }
+        let video = video::Entity::find_by_id(video.id)
+            .one(&connection)
+            .await?
+            .expect("video should exist");
+        let statuses: [u32; 5] = VideoStatus::from(video.download_status).into();
+        assert_eq!(statuses, [STATUS_OK; 5]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_video() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let video_dir = test_dir.join("videos");
+        let favorite = test_favorite(video_dir.to_string_lossy().to_string())
+            .insert(&connection)
+            .await?;
+        let video = video::ActiveModel {
+            favorite_id: Set(Some(favorite.id)),
+            path: Set(video_dir.join("测试视频").to_string_lossy().to_string()),
+            cover: Set(String::new()),
+            single_page: Set(Some(false)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        let mut pages = Vec::new();
+        for pid in 1..=2 {
+            let page = test_page(video.id, pid).insert(&connection).await?;
+            pages.push(page);
+        }
+        let video_source = VideoSourceEnum::Favorite(favorite.clone());
+        let page_paths = {
+            let config = VersionedConfig::get().read();
+            let template = TEMPLATE.read();
+            pages
+                .iter()
+                .map(|page| page_task_paths(&video, page, &video_source, &template, &config))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let Ok(page_paths) = page_paths else {
+            panic!("failed to build page task paths");
+        };
+        // 第一页仅恢复了视频与 nfo
+        tokio::fs::create_dir_all(page_paths[0].video.parent().context("invalid page path")?).await?;
+        tokio::fs::write(&page_paths[0].video, b"video").await?;
+        tokio::fs::write(&page_paths[0].nfo, b"nfo").await?;
+        let Ok(response) = reconcile_video(Path(video.id), Extension(connection.clone())).await else {
+            panic!("failed to reconcile video");
+        };
+        let response = serde_json::to_value(response)?;
+        let reconciled = response["data"]["reconciled"]
+            .as_array()
+            .expect("reconciled should be an array");
+        assert_eq!(reconciled.len(), 2);
+        assert!(reconciled.iter().all(|task| task["page_id"] == pages[0].id));
+        let statuses = |page: &page::Model| <[u32; 5]>::from(PageStatus::from(page.download_status));
+        let page_models = page::Entity::find()
+            .order_by_asc(page::Column::Cid)
+            .all(&connection)
+            .await?;
+        assert_eq!(statuses(&page_models[0]), [0, STATUS_OK, STATUS_OK, 0, 0]);
+        assert_eq!(statuses(&page_models[1]), [0; 5]);
+
+        // 恢复剩余的分页文件后，视频层的“分页下载”任务也会被标记为成功
+        for paths in &page_paths {
+            tokio::fs::create_dir_all(paths.video.parent().context("invalid page path")?).await?;
+            for path in [&paths.poster, &paths.video, &paths.nfo, &paths.danmaku] {
+                tokio::fs::write(path, b"file").await?;
+            }
+            tokio::fs::write(paths.subtitle.with_extension("zh-CN.srt"), b"subtitle").await?;
+        }
+        let Ok(response) = reconcile_filtered_videos(
+            Extension(connection.clone()),
+            Json(ReconcileFilteredVideosRequest {
+                collection: None,
+                favorite: Some(favorite.id),
+                submission: None,
+                watch_later: None,
+                query: None,
+                status_filter: None,
+            }),
+        )
+        .await
+        else {
+            panic!("failed to reconcile filtered videos");
+        };
+        let response = serde_json::to_value(response)?;
+        assert_eq!(response["data"]["reconciled_videos_count"], 1);
+        assert_eq!(response["data"]["reconciled_pages_count"], 2);
+        for page in page::Entity::find().all(&connection).await? {
+            assert_eq!(statuses(&page), [STATUS_OK; 5]);
+        }
+        let video = video::Entity::find_by_id(video.id)
+            .one(&connection)
+            .await?
+            .context("video not found")?;
+        let video_statuses: [u32; 5] = VideoStatus::from(video.download_status).into();
+        assert_eq!(video_statuses, [0, 0, 0, 0, STATUS_OK]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_cover_format() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let video_dir = test_dir.join("videos");
+        let favorite = test_favorite(video_dir.to_string_lossy().to_string())
+            .insert(&connection)
+            .await?;
+        let base_path = video_dir.join("测试视频");
+        let video = video::ActiveModel {
+            favorite_id: Set(Some(favorite.id)),
+            path: Set(base_path.to_string_lossy().to_string()),
+            single_page: Set(Some(true)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        let page = test_page(video.id, 1).insert(&connection).await?;
+        let video_source = VideoSourceEnum::Favorite(favorite);
+        let template = TEMPLATE.snapshot();
+        let upper_path = test_dir.join("upper_face");
+        let (jpg_config, webp_config) = (
+            Config {
+                upper_path: upper_path.clone(),
+                ..Default::default()
+            },
+            Config {
+                cover_format: CoverFormat::Webp,
+                upper_path: upper_path.clone(),
+                ..Default::default()
+            },
+        );
+        let jpg_paths = page_task_paths(&video, &page, &video_source, &template, &jpg_config)?;
+        let webp_paths = page_task_paths(&video, &page, &video_source, &template, &webp_config)?;
+        // 定时任务与状态校准使用相同的路径规则，封面与 fanart 随 cover_format 更换扩展名，其它文件不受影响
+        let base_name = webp_paths
+            .video
+            .file_stem()
+            .context("invalid page path")?
+            .to_string_lossy();
+        let download_paths = page_paths(
+            &video,
+            &page,
+            &base_path,
+            &base_name,
+            &video_source,
+            &template,
+            &webp_config,
+        )?;
+        assert_eq!(download_paths.poster, webp_paths.poster);
+        assert_eq!(download_paths.fanart, webp_paths.fanart);
+        assert_eq!(webp_paths.poster, jpg_paths.poster.with_extension("webp"));
+        let jpg_fanart = jpg_paths.fanart.map(|path| path.with_extension("webp"));
+        assert_eq!(webp_paths.fanart, jpg_fanart);
+        assert_eq!(webp_paths.video, jpg_paths.video);
+        assert_eq!(webp_paths.nfo, jpg_paths.nfo);
+        let upper_dir = upper_base_path(&upper_path, video.upper_id);
+        tokio::fs::create_dir_all(&base_path).await?;
+        tokio::fs::create_dir_all(&upper_dir).await?;
+        // 切换为 webp 之前留下的 jpg 封面不会被当作已完成
+        for path in [
+            &jpg_paths.poster,
+            &webp_paths.video,
+            &base_path.join("poster.jpg"),
+            &upper_dir.join("folder.jpg"),
+        ] {
+            tokio::fs::write(path, b"file").await?;
+        }
+        let Ok(reconciled) = reconcile_video_files(video.clone(), &webp_config, &template, &connection).await else {
+            panic!("failed to reconcile video");
+        };
+        assert_eq!(reconciled.len(), 1);
+        assert_eq!(reconciled[0].task_index, 1);
+        // 写入 webp 封面后，分页与视频层的封面、UP 主头像均被校准为成功
+        for path in [
+            &webp_paths.poster,
+            &base_path.join("poster.webp"),
+            &upper_dir.join("folder.webp"),
+        ] {
+            tokio::fs::write(path, b"file").await?;
+        }
+        let Ok(reconciled) = reconcile_video_files(video.clone(), &webp_config, &template, &connection).await else {
+            panic!("failed to reconcile video");
+        };
+        assert_eq!(reconciled.len(), 3);
+        let page = page::Entity::find_by_id(page.id)
+            .one(&connection)
+            .await?
+            .context("page not found")?;
+        let page_statuses: [u32; 5] = PageStatus::from(page.download_status).into();
+        assert_eq!(page_statuses, [STATUS_OK, STATUS_OK, 0, 0, 0]);
+        let video = video::Entity::find_by_id(video.id)
+            .one(&connection)
+            .await?
+            .context("video not found")?;
+        let video_statuses: [u32; 5] = VideoStatus::from(video.download_status).into();
+        assert_eq!(video_statuses, [STATUS_OK, 0, STATUS_OK, 0, 0]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_refresh_video_metadata() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let video_dir = test_dir.join("videos");
+        let video_path = video_dir.join("测试视频");
+        let favorite = test_favorite(video_dir.to_string_lossy().to_string())
+            .insert(&connection)
+            .await?;
+        let video = video::ActiveModel {
+            favorite_id: Set(Some(favorite.id)),
+            path: Set(video_path.to_string_lossy().to_string()),
+            cover: Set(String::new()),
+            download_status: Set(VideoStatus::from([STATUS_OK; 5]).into()),
+            single_page: Set(Some(true)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        let page_video_path = video_path.join("测试视频.mp4");
+        let page = page::ActiveModel {
+            path: Set(Some(page_video_path.to_string_lossy().to_string())),
+            download_status: Set(PageStatus::from([STATUS_OK; 5]).into()),
+            ..test_page(video.id, 1)
+        }
+        .insert(&connection)
+        .await?;
+        tokio::fs::create_dir_all(&video_path).await?;
+        tokio::fs::write(&page_video_path, b"video").await?;
+        let nfo_path = video_path.join("测试视频.nfo");
+        tokio::fs::write(&nfo_path, b"stale nfo").await?;
+        let Ok(response) = refresh_video_metadata(
+            Path(video.id),
+            Extension(connection.clone()),
+            Extension(Arc::new(BiliClient::new()?)),
+        )
+        .await
+        else {
+            panic!("failed to refresh video metadata");
+        };
+        let response = serde_json::to_value(response)?;
+        let succeeded = response["data"]["succeeded"]
+            .as_array()
+            .expect("succeeded should be an array");
+        assert!(
+            succeeded
+                .iter()
+                .any(|task| task["page_id"] == page.id && task["task_index"] == 2)
+        );
+        // 只执行元数据相关的子任务，视频文件保持原样，nfo 按当前配置重新生成
+        let failed = response["data"]["failed"]
+            .as_array()
+            .expect("failed should be an array");
+        let page_tasks = succeeded
+            .iter()
+            .chain(failed)
+            .filter(|task| task["page_id"].is_number());
+        assert!(page_tasks.all(|task| task["task_index"] == 0 || task["task_index"] == 2));
+        assert_eq!(tokio::fs::read(&page_video_path).await?, b"video");
+        let nfo = tokio::fs::read_to_string(&nfo_path).await?;
+        assert!(nfo != "stale nfo" && nfo.contains("测试视频"));
+
+        // 未下载的视频不会被筛选刷新
+        video::ActiveModel {
+            favorite_id: Set(Some(favorite.id)),
+            name: Set("未下载视频".to_string()),
+            cover: Set(String::new()),
+            ..test_video("BV1test00002")
+        }
+        .insert(&connection)
+        .await?;
+        tokio::fs::write(&nfo_path, b"stale nfo").await?;
+        let Ok(response) = refresh_filtered_videos_metadata(
+            Extension(connection.clone()),
+            Extension(Arc::new(BiliClient::new()?)),
+            Json(RefreshFilteredMetadataRequest {
+                collection: None,
+                favorite: Some(favorite.id),
+                submission: None,
+                watch_later: None,
+                query: None,
+                status_filter: None,
+            }),
+        )
+        .await
+        else {
+            panic!("failed to refresh filtered videos metadata");
+        };
+        let response = serde_json::to_value(response)?;
+        assert_eq!(response["data"]["refreshed_videos_count"], 1);
+        assert_ne!(tokio::fs::read(&nfo_path).await?, b"stale nfo");
+        assert_eq!(tokio::fs::read(&page_video_path).await?, b"video");
+
+        // 单个视频刷新失败时不会中断整批刷新，失败的视频随响应返回
+        let orphan = video::ActiveModel {
+            name: Set("无视频源".to_string()),
+            path: Set(video_dir.join("无视频源").to_string_lossy().to_string()),
+            ..test_video("BV1test00003")
+        }
+        .insert(&connection)
+        .await?;
+        let Ok(response) = refresh_filtered_videos_metadata(
+            Extension(connection.clone()),
+            Extension(Arc::new(BiliClient::new()?)),
+            Json(RefreshFilteredMetadataRequest {
+                collection: None,
+                favorite: None,
+                submission: None,
+                watch_later: None,
+                query: None,
+                status_filter: None,
+            }),
+        )
+        .await
+        else {
+            panic!("failed to refresh filtered videos metadata");
+        };
+        let response = serde_json::to_value(response)?;
+        assert_eq!(response["data"]["refreshed_videos_count"], 1);
+        let failed_videos = response["data"]["failed_videos"]
+            .as_array()
+            .expect("failed_videos should be an array");
+        assert_eq!(failed_videos.len(), 1);
+        assert_eq!(failed_videos[0]["id"], orphan.id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_task_result_risk_control() {
+        let max_retries = Config::default().max_auto_retries;
+        let original = PageStatus::from([STATUS_OK, 2, STATUS_OK, STATUS_OK, STATUS_OK]);
+        // 模拟视频流获取时触发风控，状态保持不变并返回风控错误
+        let mut page_status = original;
+        let result = ExecutionStatus::Failed(
+            anyhow::Error::from(crate::bilibili::BiliError::RiskControlOccurred("-352".to_owned()))
+                .context("fetch video streams failed"),
+        );
+        let Err(e) = check_risk_control(apply_task_result(&mut page_status, 1, result, max_retries)) else {
+            panic!("risk control should be returned as an error");
+        };
+        let response = e.into_response();
+        assert_eq!(response.status(), axum::http::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(u32::from(page_status), u32::from(original));
+        // 其它错误照常计入失败次数，单个子任务的重试接口不返回错误
+        let result = ExecutionStatus::Failed(anyhow::anyhow!("network error"));
+        let result = apply_task_result(&mut page_status, 1, result, max_retries);
+        assert!(result.is_err());
+        assert!(check_risk_control(result).is_ok());
+        let statuses: [u32; 5] = page_status.into();
+        assert_eq!(statuses, [STATUS_OK, 3, STATUS_OK, STATUS_OK, STATUS_OK]);
+        // 重试成功时即使此前已经达到最大重试次数也标记为成功
+        let mut page_status = PageStatus::from([STATUS_OK, 4, STATUS_OK, STATUS_OK, STATUS_OK]);
+        assert!(apply_task_result(&mut page_status, 1, ExecutionStatus::Succeeded, max_retries).is_ok());
+        let statuses: [u32; 5] = page_status.into();
+        assert_eq!(statuses, [STATUS_OK; 5]);
+        // 失败次数达到配置的自动重试次数后标记为最终失败
+        let mut page_status = PageStatus::from([STATUS_OK, 1, STATUS_OK, STATUS_OK, STATUS_OK]);
+        let result = ExecutionStatus::Failed(anyhow::anyhow!("network error"));
+        assert!(apply_task_result(&mut page_status, 1, result, 2).is_err());
+        let statuses: [u32; 5] = page_status.into();
+        assert_eq!(statuses, [STATUS_OK, 4, STATUS_OK, STATUS_OK, STATUS_OK]);
+    }
+
+    #[tokio::test]
+    async fn test_delete_video_files() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let video_path = test_dir.join("videos").join("测试视频");
+        tokio::fs::create_dir_all(&video_path).await?;
+        tokio::fs::write(video_path.join("测试视频.mp4"), b"video").await?;
+        let download_status: u32 = VideoStatus::from([STATUS_OK, STATUS_OK, STATUS_OK, STATUS_OK, 1]).into();
+        let video = video::ActiveModel {
+            path: Set(video_path.to_string_lossy().to_string()),
+            cover: Set(String::new()),
+            download_status: Set(download_status),
+            single_page: Set(Some(true)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        let unhandled = || filter_unhandled_video_pages(video::Column::Id.eq(video.id), &connection);
+        assert_eq!(unhandled().await?.len(), 1);
+
+        let Ok(response) = delete_video_files(Path(video.id), Extension(connection.clone())).await else {
+            panic!("failed to delete video files");
+        };
+        let response = serde_json::to_value(response)?;
+        assert!(response["data"]["warning"].is_null());
+        assert_eq!(response["data"]["video"]["files_deleted"], true);
+        assert!(!video_path.exists());
+        let video_model = video::Entity::find_by_id(video.id)
+            .one(&connection)
+            .await?
+            .context("video not found")?;
+        assert!(video_model.files_deleted);
+        assert_eq!(video_model.download_status, download_status);
+        // 删除文件后的视频不会被定时任务重新下载
+        assert!(unhandled().await?.is_empty());
+
+        // 本地路径不存在时返回警告
+        let Ok(response) = delete_video_files(Path(video.id), Extension(connection.clone())).await else {
+            panic!("failed to delete video files");
+        };
+        let response = serde_json::to_value(response)?;
+        assert!(response["data"]["warning"].is_string());
+
+        // 清空重置后视频会重新参与下载
+        let Ok(_) = clear_and_reset_video_status(Path(video.id), Extension(connection.clone())).await else {
+            panic!("failed to clear and reset video status");
+        };
+        let video_model = video::Entity::find_by_id(video.id)
+            .one(&connection)
+            .await?
+            .context("video not found")?;
+        assert!(!video_model.files_deleted);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_videos_by_upper_name() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        for (bvid, name, upper_name) in [
+            ("BV1test00001", "测试视频一", "Alice"),
+            ("BV1test00002", "测试视频二", "Bob"),
+        ] {
+            video::ActiveModel {
+                upper_name: Set(upper_name.to_string()),
+                name: Set(name.to_string()),
+                single_page: Set(Some(true)),
+                ..test_video(bvid)
+            }
+            .insert(&connection)
+            .await?;
+        }
+        let search = |query: &str, search_fields: Option<Vec<String>>| {
+            let connection = connection.clone();
+            let params = VideosRequest {
+                query: Some(query.to_string()),
+                search_fields,
+                ..Default::default()
+            };
+            async move {
+                let Ok(response) = get_videos(Extension(connection), Query(params)).await else {
+                    panic!("failed to search videos");
+                };
+                let response = serde_json::to_value(response).expect("failed to serialize response");
+                response["data"]["videos"]
+                    .as_array()
+                    .expect("videos should be an array")
+                    .iter()
+                    .map(|video| video["bvid"].as_str().expect("bvid should be a string").to_owned())
+                    .collect::<Vec<_>>()
+            }
+        };
+        // 默认同时搜索 UP 主名称，且与名称的搜索一样不区分大小写
+        assert_eq!(search("alice", None).await, vec!["BV1test00001"]);
+        // 限定搜索字段后不再匹配 UP 主名称
+        assert!(
+            search("alice", Some(vec!["name".to_string(), "bvid".to_string()]))
+                .await
+                .is_empty()
+        );
+        assert_eq!(
+            search("BV1test00002", Some(vec!["bvid".to_string()])).await,
+            vec!["BV1test00002"]
+        );
+        // 不支持的搜索字段
+        assert!(
+            get_videos(
+                Extension(connection.clone()),
+                Query(VideosRequest {
+                    query: Some("alice".to_string()),
+                    search_fields: Some(vec!["intro".to_string()]),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .is_err()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_filter_videos_by_tag() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let failed_status: u32 = VideoStatus::from([STATUS_OK, STATUS_OK, STATUS_OK, STATUS_OK, 1]).into();
+        let succeeded_status: u32 = VideoStatus::from([STATUS_OK; 5]).into();
+        for (bvid, tags, rule_tags, download_status) in [
+            ("BV1test00001", vec!["教程"], vec![], failed_status),
+            ("BV1test00002", vec!["游戏"], vec!["教程"], succeeded_status),
+            ("BV1test00003", vec!["Rust教程", "100%_done"], vec![], failed_status),
+            ("BV1test00004", vec!["游戏"], vec!["教程"], failed_status),
+        ] {
+            let to_string_vec = |tags: Vec<&str>| tags.into_iter().map(str::to_owned).collect::<Vec<_>>().into();
+            video::ActiveModel {
+                name: Set(bvid.to_string()),
+                cover: Set(String::new()),
+                download_status: Set(download_status),
+                tags: Set(Some(to_string_vec(tags))),
+                rule_tags: Set((!rule_tags.is_empty()).then(|| to_string_vec(rule_tags))),
+                single_page: Set(Some(true)),
+                ..test_video(bvid)
+            }
+            .insert(&connection)
+            .await?;
+        }
+        let filter = |tag: &str, status_filter: Option<StatusFilter>| {
+            let connection = connection.clone();
+            let params = VideosRequest {
+                tag: Some(tag.to_string()),
+                status_filter,
+                ..Default::default()
+            };
+            async move {
+                let Ok(response) = get_videos(Extension(connection), Query(params)).await else {
+                    panic!("failed to filter videos");
+                };
+                let response = serde_json::to_value(response).expect("failed to serialize response");
+                let mut bvids = response["data"]["videos"]
+                    .as_array()
+                    .expect("videos should be an array")
+                    .iter()
+                    .map(|video| video["bvid"].as_str().expect("bvid should be a string").to_owned())
+                    .collect::<Vec<_>>();
+                bvids.sort();
+                bvids
+            }
+        };
+        // 同时匹配 B 站标签与自动标签，但不会命中仅部分相同的标签
+        assert_eq!(
+            filter("教程", None).await,
+            vec!["BV1test00001", "BV1test00002", "BV1test00004"]
+        );
+        // 与状态筛选取交集
+        assert_eq!(
+            filter("教程", Some(StatusFilter::Failed)).await,
+            vec!["BV1test00001", "BV1test00004"]
+        );
+        assert_eq!(
+            filter("教程", Some(StatusFilter::Succeeded)).await,
+            vec!["BV1test00002"]
+        );
+        assert!(filter("游戏", Some(StatusFilter::Waiting)).await.is_empty());
+        // 标签中的 LIKE 通配符按字面匹配
+        assert!(filter("100%", None).await.is_empty());
+        assert_eq!(filter("100%_done", None).await, vec!["BV1test00003"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reset_filtered_video_status_by_task_index() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        // 视频自身的封面失败，分页的弹幕与字幕均失败
+        let video = video::ActiveModel {
+            download_status: Set(VideoStatus::from([2, STATUS_OK, STATUS_OK, STATUS_OK, STATUS_OK]).into()),
+            single_page: Set(Some(true)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        let page = page::ActiveModel {
+            download_status: Set(PageStatus::from([STATUS_OK, STATUS_OK, STATUS_OK, 4, 1]).into()),
+            ..test_page(video.id, 1)
+        }
+        .insert(&connection)
+        .await?;
+        let reset = |task_index: Option<usize>| {
+            reset_filtered_video_status(
+                Extension(connection.clone()),
+                Json(ResetFilteredVideoStatusRequest {
+                    collection: None,
+                    favorite: None,
+                    submission: None,
+                    watch_later: None,
+                    query: None,
+                    status_filter: None,
+                    force: false,
+                    task_index,
+                }),
+            )
+        };
+        assert!(reset(Some(5)).await.is_err());
+        let Ok(response) = reset(Some(3)).await else {
+            panic!("failed to reset filtered video status");
+        };
+        let response = serde_json::to_value(response)?;
+        assert_eq!(response["data"]["resetted_videos_count"], 1);
+        assert_eq!(response["data"]["resetted_pages_count"], 1);
+        // 仅弹幕被重置，视频封面与分页字幕的失败状态保持不变
+        let page = page::Entity::find_by_id(page.id)
+            .one(&connection)
+            .await?
+            .context("page not found")?;
+        assert_eq!(
+            <[u32; 5]>::from(PageStatus::from(page.download_status)),
+            [STATUS_OK, STATUS_OK, STATUS_OK, 0, 1]
+        );
+        let video = video::Entity::find_by_id(video.id)
+            .one(&connection)
+            .await?
+            .context("video not found")?;
+        assert_eq!(
+            <[u32; 5]>::from(VideoStatus::from(video.download_status)),
+            [2, STATUS_OK, STATUS_OK, STATUS_OK, 0]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reset_video_status_by_task_indices() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        // 视频自身的封面失败，两个分页的视频、弹幕与字幕各有失败
+        let video = video::ActiveModel {
+            cover: Set(String::new()),
+            download_status: Set(VideoStatus::from([2, STATUS_OK, STATUS_OK, STATUS_OK, STATUS_OK]).into()),
+            single_page: Set(Some(false)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        let mut pages = Vec::new();
+        for (pid, statuses) in [(1, [STATUS_OK, 2, STATUS_OK, 4, 1]), (2, [STATUS_OK; 5])] {
+            let page = page::ActiveModel {
+                download_status: Set(PageStatus::from(statuses).into()),
+                ..test_page(video.id, pid)
+            }
+            .insert(&connection)
+            .await?;
+            pages.push(page);
+        }
+        let reset = |task_indices: Vec<usize>| {
+            reset_video_status(
+                Path(video.id),
+                Extension(connection.clone()),
+                Json(ResetVideoStatusRequest {
+                    force: false,
+                    task_indices,
+                }),
+            )
+        };
+        assert!(reset(vec![3, 5]).await.is_err());
+        let Ok(response) = reset(vec![3]).await else {
+            panic!("failed to reset video status");
+        };
+        let response = serde_json::to_value(response)?;
+        assert_eq!(response["data"]["resetted"], true);
+        assert_eq!(response["data"]["pages"].as_array().map(Vec::len), Some(1));
+        // 仅弹幕被重置，其余失败状态与未失败的分页保持不变
+        let expected = [[STATUS_OK, 2, STATUS_OK, 0, 1], [STATUS_OK; 5]];
+        for (page, expected) in pages.iter().zip(expected) {
+            let page = page::Entity::find_by_id(page.id)
+                .one(&connection)
+                .await?
+                .context("page not found")?;
+            assert_eq!(<[u32; 5]>::from(PageStatus::from(page.download_status)), expected);
+        }
+        let video = video::Entity::find_by_id(video.id)
+            .one(&connection)
+            .await?
+            .context("video not found")?;
+        assert_eq!(
+            <[u32; 5]>::from(VideoStatus::from(video.download_status)),
+            [2, STATUS_OK, STATUS_OK, STATUS_OK, 0]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_videos() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let favorite = test_favorite(String::new()).insert(&connection).await?;
+        for (bvid, name, favorite_id) in [
+            ("BV1test00001", "普通视频", Some(favorite.id)),
+            ("BV1test00002", "带\"引号\",和逗号的视频", Some(favorite.id)),
+            ("BV1test00003", "其它来源的视频", None),
+        ] {
+            video::ActiveModel {
+                favorite_id: Set(favorite_id),
+                name: Set(name.to_string()),
+                single_page: Set(Some(true)),
+                ..test_video(bvid)
+            }
+            .insert(&connection)
+            .await?;
+        }
+        let Ok(response) = export_videos(
+            Extension(connection.clone()),
+            Query(VideosRequest {
+                favorite: Some(favorite.id),
+                sort_by: Some(VideoSortBy::PublishTime),
+                sort_order: Some(SortOrder::Asc),
+                ..Default::default()
+            }),
+        )
+        .await
+        else {
+            panic!("failed to export videos");
+        };
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"videos.csv\""
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+        let body = String::from_utf8(body.to_vec())?;
+        let lines = body.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "id,bvid,name,upper_name,download_status,created_at");
+        let rows = lines[1..]
+            .iter()
+            .map(|line| {
+                // 名称中的逗号位于引号内，按首尾的固定列切分即可
+                let (id, rest) = line.split_once(',').expect("missing id column");
+                let (bvid, rest) = rest.split_once(',').expect("missing bvid column");
+                let mut tail = rest.rsplitn(4, ',');
+                let (created_at, download_status, upper_name, name) = (
+                    tail.next().expect("missing created_at column"),
+                    tail.next().expect("missing download_status column"),
+                    tail.next().expect("missing upper_name column"),
+                    tail.next().expect("missing name column"),
+                );
+                assert!(!id.is_empty() && !created_at.is_empty());
+                assert_eq!((upper_name, download_status), ("测试作者", "0"));
+                (bvid.to_owned(), name.to_owned())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                ("BV1test00001".to_owned(), "普通视频".to_owned()),
+                ("BV1test00002".to_owned(), "\"带\"\"引号\"\",和逗号的视频\"".to_owned()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_video_stats() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let succeeded = VideoStatus::from([STATUS_OK; 5]).into();
+        let failed = VideoStatus::from([STATUS_OK, 1, STATUS_OK, STATUS_OK, STATUS_OK]).into();
+        // (收藏夹 ID, 下载状态, 是否需要下载, 是否为收费视频)
+        let videos = [
+            (1, succeeded, true, false),
+            (1, succeeded, true, false),
+            (1, failed, true, false),
+            (1, 0, true, false),
+            (1, 0, false, false),
+            (1, 0, false, true),
+            (2, failed, true, false),
+        ];
+        for (idx, (favorite_id, download_status, should_download, is_paid_video)) in videos.into_iter().enumerate() {
+            video::ActiveModel {
+                favorite_id: Set(Some(favorite_id)),
+                name: Set(format!("测试视频 {}", idx)),
+                download_status: Set(download_status),
+                should_download: Set(should_download),
+                is_paid_video: Set(is_paid_video),
+                single_page: Set(Some(true)),
+                ..test_video(&format!("BV1test{:0>5}", idx))
+            }
+            .insert(&connection)
+            .await?;
+        }
+        let stats = |favorite: Option<i32>| {
+            let connection = connection.clone();
+            async move {
+                let params = VideoStatsRequest {
+                    favorite,
+                    ..Default::default()
+                };
+                let Ok(response) = get_video_stats(Extension(connection), Query(params)).await else {
+                    panic!("failed to get video stats");
+                };
+                let response = serde_json::to_value(response).expect("failed to serialize response");
+                ["total", "succeeded", "failed", "waiting", "skipped", "paid"]
+                    .map(|key| response["data"][key].as_u64().expect("count should be a number"))
+            }
+        };
+        assert_eq!(stats(None).await, [7, 2, 2, 1, 1, 1]);
+        assert_eq!(stats(Some(1)).await, [6, 2, 1, 1, 1, 1]);
+        assert_eq!(stats(Some(2)).await, [1, 0, 1, 0, 0, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_page_task_paths_with_mkv_container() {
+        let config = Config {
+            container: VideoContainer::Mkv,
+            ..Default::default()
+        };
+        let template = TEMPLATE.read();
+        let video_source = VideoSourceEnum::Favorite(favorite::Model {
+            path: "/videos".to_string(),
+            ..Default::default()
+        });
+        let mut video_model = video::Model {
+            bvid: "BV1test00001".to_string(),
+            path: "/videos/测试视频".to_string(),
+            single_page: Some(false),
+            ..Default::default()
+        };
+        let mut page_model = page::Model {
+            pid: 2,
+            ..Default::default()
+        };
+        let season_path = std::path::Path::new("/videos/测试视频/Season 1");
+        let Ok(paths) = page_task_paths(&video_model, &page_model, &video_source, &template, &config) else {
+            panic!("failed to build page task paths");
+        };
+        assert_eq!(paths.video, season_path.join("BV1test00001 - S01E02.mkv"));
+        assert_eq!(paths.poster, season_path.join("BV1test00001 - S01E02-thumb.jpg"));
+        assert_eq!(paths.nfo, season_path.join("BV1test00001 - S01E02.nfo"));
+        assert_eq!(
+            paths.danmaku,
+            season_path.join("BV1test00001 - S01E02.zh-CN.default.ass")
+        );
+        assert_eq!(paths.subtitle, season_path.join("BV1test00001 - S01E02.srt"));
+        // 已记录为 mp4 的单页视频切换为 mkv 后仍沿用已下载的 mp4 文件，附属文件使用相同的文件名
+        video_model.single_page = Some(true);
+        page_model.path = Some("/videos/测试视频/测试视频.mp4".to_string());
+        let base_path = std::path::Path::new("/videos/测试视频");
+        let Ok(paths) = page_task_paths(&video_model, &page_model, &video_source, &template, &config) else {
+            panic!("failed to build page task paths");
+        };
+        assert_eq!(paths.video, base_path.join("测试视频.mp4"));
+        assert_eq!(paths.poster, base_path.join("测试视频-poster.jpg"));
+        assert_eq!(paths.nfo, base_path.join("测试视频.nfo"));
+        assert_eq!(paths.fanart, Some(base_path.join("测试视频-fanart.jpg")));
+        assert_eq!(paths.subtitle, base_path.join("测试视频.srt"));
+    }
+
+    #[tokio::test]
+    async fn test_get_video_progress() -> Result<()> {
+        // 使用不会与其它测试冲突的视频 id
+        let video_id = i32::MAX - 65;
+        let response = serde_json::to_value(get_video_progress(Path(video_id)).await?)?;
+        assert!(response["data"]["progress"].is_null());
+        ProgressRegistry::get().add_total(video_id, 1, 1000);
+        ProgressRegistry::get().add_downloaded(video_id, 1, 300);
+        ProgressRegistry::get().add_total(video_id, 2, 400);
+        ProgressRegistry::get().add_downloaded(video_id, 2, 200);
+        let response = serde_json::to_value(get_video_progress(Path(video_id)).await?)?;
+        assert_eq!(
+            response["data"]["progress"],
+            serde_json::json!({"downloaded_bytes": 500, "total_bytes": 1400})
+        );
+        // 单个分页处理结束后仅移除该分页的进度
+        ProgressRegistry::get().remove_page(video_id, 2);
+        let response = serde_json::to_value(get_video_progress(Path(video_id)).await?)?;
+        assert_eq!(
+            response["data"]["progress"],
+            serde_json::json!({"downloaded_bytes": 300, "total_bytes": 1000})
+        );
+        // 视频处理结束后清除进度
+        ProgressRegistry::get().remove(video_id);
+        let response = serde_json::to_value(get_video_progress(Path(video_id)).await?)?;
+        assert!(response["data"]["progress"].is_null());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_duplicate_videos() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let mut favorite_ids = Vec::new();
+        for f_id in 1..=2 {
+            let path = test_dir.join(format!("favorite-{}", f_id));
+            let favorite = favorite::ActiveModel {
+                f_id: Set(f_id),
+                name: Set(format!("收藏夹{}", f_id)),
+                ..test_favorite(path.to_string_lossy().to_string())
+            }
+            .insert(&connection)
+            .await?;
+            favorite_ids.push(favorite.id);
+        }
+        // 同一个视频被收藏到两个收藏夹，另有一个仅出现一次的视频
+        for (favorite_id, bvid) in [
+            (favorite_ids[0], "BV1duplicate"),
+            (favorite_ids[1], "BV1duplicate"),
+            (favorite_ids[1], "BV1unique0001"),
+        ] {
+            video::ActiveModel {
+                favorite_id: Set(Some(favorite_id)),
+                name: Set(bvid.to_string()),
+                path: Set(test_dir
+                    .join(format!("favorite-{}", favorite_id))
+                    .join(bvid)
+                    .to_string_lossy()
+                    .to_string()),
+                ..test_video(bvid)
+            }
+            .insert(&connection)
+            .await?;
+        }
+        let Ok(response) = get_duplicate_videos(Extension(connection.clone())).await else {
+            panic!("failed to get duplicate videos");
+        };
+        let response = serde_json::to_value(response)?;
+        let groups = response["data"]["groups"]
+            .as_array()
+            .expect("groups should be an array");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0]["bvid"], "BV1duplicate");
+        let videos = groups[0]["videos"].as_array().expect("videos should be an array");
+        assert_eq!(
+            videos.iter().map(|v| v["favorite_id"].as_i64()).collect::<Vec<_>>(),
+            favorite_ids.iter().map(|id| Some(*id as i64)).collect::<Vec<_>>()
+        );
+        assert!(videos[0]["path"] != videos[1]["path"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_filtered_video_task() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let video_dir = test_dir.join("videos");
+        let favorite = test_favorite(video_dir.to_string_lossy().to_string())
+            .insert(&connection)
+            .await?;
+        // 前两个视频的“详情”任务失败，第三个视频所有任务均已成功
+        let mut videos = Vec::new();
+        for (idx, detail_status) in [1, 2, STATUS_OK].into_iter().enumerate() {
+            let name = format!("测试视频{}", idx);
+            let video = video::ActiveModel {
+                favorite_id: Set(Some(favorite.id)),
+                name: Set(name.clone()),
+                path: Set(video_dir.join(&name).to_string_lossy().to_string()),
+                cover: Set(String::new()),
+                download_status: Set(
+                    VideoStatus::from([STATUS_OK, detail_status, STATUS_OK, STATUS_OK, STATUS_OK]).into(),
+                ),
+                single_page: Set(Some(false)),
+                ..test_video(&format!("BV1test0000{}", idx))
+            }
+            .insert(&connection)
+            .await?;
+            videos.push(video);
+        }
+        let Ok(response) = retry_filtered_video_task(
+            Extension(connection.clone()),
+            Extension(Arc::new(BiliClient::new()?)),
+            ValidatedJson(RetryFilteredVideoTaskRequest {
+                collection: None,
+                favorite: Some(favorite.id),
+                submission: None,
+                watch_later: None,
+                query: None,
+                search_fields: None,
+                status_filter: Some(StatusFilter::Failed),
+                tag: None,
+                task_index: 1,
+            }),
+        )
+        .await
+        else {
+            panic!("failed to retry filtered video task");
+        };
+        let response = serde_json::to_value(response)?;
+        let batch_id = response["data"]["batch"]["id"]
+            .as_i64()
+            .expect("batch id should be a number") as i32;
+        assert_eq!(response["data"]["batch"]["total_count"], 2);
+        // 等待重试队列执行完批次中的任务
+        let batch = tokio::time::timeout(std::time::Duration::from_secs(30), async {
+            loop {
+                let batch = RETRY_QUEUE.batch(batch_id).expect("batch should exist");
+                if batch.succeeded_count + batch.failed_count + batch.cancelled_count == batch.total_count {
+                    break batch;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+        })
+        .await?;
+        assert_eq!((batch.succeeded_count, batch.failed_count), (2, 0));
+        // 仅筛选出的视频执行了任务
+        for (idx, video) in videos.iter().enumerate() {
+            assert_eq!(PathBuf::from(&video.path).join("tvshow.nfo").exists(), idx < 2);
+            let video = video::Entity::find_by_id(video.id)
+                .one(&connection)
+                .await?
+                .expect("video should exist");
+            let statuses: [u32; 5] = VideoStatus::from(video.download_status).into();
+            assert_eq!(statuses, [STATUS_OK; 5]);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_task_errors() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        // 图片地址的路径即为返回的状态码，412 会被识别为风控，404 为普通错误
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let app = axum::Router::new().route(
+            "/{status}",
+            axum::routing::any(|Path(status): Path<u16>| async move {
+                axum::http::StatusCode::from_u16(status).expect("invalid status code")
+            }),
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let favorite = test_favorite(test_dir.join("videos").to_string_lossy().to_string())
+            .insert(&connection)
+            .await?;
+        let video = video::ActiveModel {
+            favorite_id: Set(Some(favorite.id)),
+            single_page: Set(Some(false)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        let page = test_page(video.id, 1).insert(&connection).await?;
+        let bili_client = Arc::new(BiliClient::new()?);
+        for (status, risk_control) in [(412, true), (404, false)] {
+            let cover = format!("http://{}/{}", addr, status);
+            let mut video_active_model = video.clone().into_active_model();
+            video_active_model.cover = Set(cover.clone());
+            video_active_model.update(&connection).await?;
+            let mut page_active_model = page.clone().into_active_model();
+            page_active_model.image = Set(Some(cover));
+            page_active_model.update(&connection).await?;
+            let video_result = retry_video_task(
+                Path(video.id),
+                Extension(connection.clone()),
+                Extension(bili_client.clone()),
+                ValidatedJson(RetryVideoTaskRequest { task_index: 0 }),
+            )
+            .await;
+            let page_result = retry_page_task(
+                Path(page.id),
+                Extension(connection.clone()),
+                Extension(bili_client.clone()),
+                ValidatedJson(RetryPageTaskRequest { task_index: 0 }),
+            )
+            .await;
+            let video_status: [u32; 5] = VideoStatus::from(
+                video::Entity::find_by_id(video.id)
+                    .one(&connection)
+                    .await?
+                    .context("video not found")?
+                    .download_status,
+            )
+            .into();
+            let page_status: [u32; 5] = PageStatus::from(
+                page::Entity::find_by_id(page.id)
+                    .one(&connection)
+                    .await?
+                    .context("page not found")?
+                    .download_status,
+            )
+            .into();
+            if risk_control {
+                // 风控时返回风控错误，不计入失败次数
+                for result in [video_result.map(|_| ()), page_result.map(|_| ())] {
+                    let Err(e) = result else {
+                        panic!("risk control should be returned as an error");
+                    };
+                    assert!(e.to_string().contains("Risk control occurred"), "{}", e);
+                }
+                assert_eq!((video_status[0], page_status[0]), (0, 0));
+            } else {
+                // 普通错误与定时任务一致，计入失败次数后正常返回
+                assert!(video_result.is_ok() && page_result.is_ok());
+                assert_eq!((video_status[0], page_status[0]), (1, 1));
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_video_paginated_pages() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let video = video::ActiveModel {
+            cover: Set(String::new()),
+            single_page: Set(Some(false)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        page::Entity::insert_many((1..=200).map(|pid| test_page(video.id, pid)))
+            .exec(&connection)
+            .await?;
+        let get = |page: Option<u64>, page_size: Option<u64>| {
+            get_video(
+                Path(video.id),
+                Extension(connection.clone()),
+                Query(VideoRequest {
+                    page,
+                    page_size,
+                    pages_sort_by: None,
+                    pages_sort_order: None,
+                }),
+            )
+        };
+        let Ok(response) = get(Some(1), Some(50)).await else {
+            panic!("failed to get video");
+        };
+        let response = serde_json::to_value(response)?;
+        assert_eq!(response["data"]["total_pages_count"], 200);
+        let pids = response["data"]["pages"]
+            .as_array()
+            .expect("pages should be an array")
+            .iter()
+            .map(|page| page["pid"].as_i64().expect("pid should be a number"))
+            .collect::<Vec<_>>();
+        assert_eq!(pids, (51..=100).collect::<Vec<_>>());
+        // 未传入分页参数时返回全部分页
+        let Ok(response) = get(None, None).await else {
+            panic!("failed to get video");
+        };
+        let response = serde_json::to_value(response)?;
+        assert_eq!(response["data"]["total_pages_count"], 200);
+        assert_eq!(response["data"]["pages"].as_array().map(Vec::len), Some(200));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_video_pages_sorted_by_status() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let video = video::ActiveModel {
+            cover: Set(String::new()),
+            single_page: Set(Some(false)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        // pid 1 成功，pid 2 等待，pid 3 与 pid 4 存在失败的子任务
+        let statuses: [[u32; 5]; 4] = [
+            [STATUS_OK; 5],
+            [STATUS_OK, STATUS_NOT_STARTED, STATUS_OK, STATUS_OK, STATUS_NOT_STARTED],
+            [STATUS_OK, 1, STATUS_OK, STATUS_OK, STATUS_OK],
+            [STATUS_OK, STATUS_OK, STATUS_OK, STATUS_OK, 4],
+        ];
+        page::Entity::insert_many(statuses.into_iter().enumerate().map(|(idx, status)| page::ActiveModel {
+            download_status: Set(PageStatus::from(status).into()),
+            ..test_page(video.id, idx as i32 + 1)
+        }))
+        .exec(&connection)
+        .await?;
+        let get_pids = |pages_sort_by: PageSortBy, pages_sort_order: SortOrder| {
+            let connection = connection.clone();
+            async move {
+                let Ok(response) = get_video(
+                    Path(video.id),
+                    Extension(connection),
+                    Query(VideoRequest {
+                        page: None,
+                        page_size: None,
+                        pages_sort_by: Some(pages_sort_by),
+                        pages_sort_order: Some(pages_sort_order),
+                    }),
+                )
+                .await
+                else {
+                    panic!("failed to get video");
+                };
+                serde_json::to_value(response).map(|response| {
+                    response["data"]["pages"]
+                        .as_array()
+                        .expect("pages should be an array")
+                        .iter()
+                        .map(|page| page["pid"].as_i64().expect("pid should be a number"))
+                        .collect::<Vec<_>>()
+                })
+            }
+        };
+        // 升序时失败的分页排在最前，同一状态内按 cid 排序
+        assert_eq!(get_pids(PageSortBy::Status, SortOrder::Asc).await?, vec![3, 4, 2, 1]);
+        assert_eq!(get_pids(PageSortBy::Status, SortOrder::Desc).await?, vec![1, 2, 3, 4]);
+        assert_eq!(get_pids(PageSortBy::Pid, SortOrder::Desc).await?, vec![4, 3, 2, 1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_recent_videos() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let favorite = test_favorite(String::new()).insert(&connection).await?;
+        let submission = submission::ActiveModel {
+            upper_id: Set(1),
+            upper_name: Set("测试作者".to_string()),
+            path: Set(String::new()),
+            enabled: Set(true),
+            ..Default::default()
+        }
+        .insert(&connection)
+        .await?;
+        let succeeded: u32 = VideoStatus::from([STATUS_OK; 5]).into();
+        let failed: u32 = VideoStatus::from([STATUS_OK, STATUS_OK, 1, STATUS_OK, STATUS_OK]).into();
+        let waiting: u32 = VideoStatus::from([STATUS_OK, STATUS_OK, STATUS_NOT_STARTED, STATUS_OK, STATUS_OK]).into();
+        // 入库时间越晚的视频 bvid 序号越大，只有成功的视频应当出现在结果中
+        for (idx, (download_status, favorite_id, submission_id)) in [
+            (succeeded, Some(favorite.id), None),
+            (succeeded, None, Some(submission.id)),
+            (failed, Some(favorite.id), None),
+            (waiting, None, Some(submission.id)),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            video::ActiveModel {
+                favorite_id: Set(favorite_id),
+                submission_id: Set(submission_id),
+                name: Set(format!("视频{}", idx + 1)),
+                cover: Set(String::new()),
+                download_status: Set(download_status),
+                created_at: Set(format!("2025-01-0{} 00:00:00", idx + 1)),
+                ..test_video(&format!("BV1test0000{}", idx + 1))
+            }
+            .insert(&connection)
+            .await?;
+        }
+        let get =
+            |limit: Option<u64>| get_recent_videos(Extension(connection.clone()), Query(RecentVideosRequest { limit }));
+        let Ok(response) = get(None).await else {
+            panic!("failed to get recent videos");
+        };
+        let response = serde_json::to_value(response)?;
+        let videos = response["data"]["videos"]
+            .as_array()
+            .expect("videos should be an array")
+            .iter()
+            .map(|video| (video["bvid"].as_str(), video["source_name"].as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            videos,
+            vec![
+                (Some("BV1test00002"), Some("「测试作者」投稿")),
+                (Some("BV1test00001"), Some("收藏夹「测试收藏夹」")),
+            ]
+        );
+        let Ok(response) = get(Some(1)).await else {
+            panic!("failed to get recent videos");
+        };
+        let response = serde_json::to_value(response)?;
+        assert_eq!(response["data"]["videos"].as_array().map(Vec::len), Some(1));
+        assert_eq!(response["data"]["videos"][0]["bvid"], "BV1test00002");
+        Ok(())
     }
-    
-    // 重新查询更新后的数据
-    let (video_info, pages_info) = tokio::try_join!(
-        video::Entity::find_by_id(video_id).into_partial_model::<VideoInfo>().one(&db),
-        page::Entity::find()
-            .filter(page::Column::VideoId.eq(video_id))
-            .order_by_asc(page::Column::Cid)
-            .into_partial_model::<PageInfo>()
-            .all(&db)
-    )?;
-    
-    Ok(ApiResponse::ok(UpdateVideoStatusResponse {
-        success: true,
-        video: video_info.ok_or_else(|| InnerApiError::NotFound(video_id))?,
-        pages: pages_info,
-    }))
 }
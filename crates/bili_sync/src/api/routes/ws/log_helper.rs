@@ -1,8 +1,11 @@
 use std::collections::VecDeque;
 use std::sync::Arc;
 
+use futures::{Stream, StreamExt};
 use parking_lot::RwLock;
 use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use tracing_subscriber::fmt::MakeWriter;
 
 pub const MAX_HISTORY_LOGS: usize = 200;
@@ -17,6 +20,27 @@ impl LogHelper {
     pub fn new(sender: broadcast::Sender<String>, log_history: Arc<RwLock<VecDeque<String>>>) -> Self {
         LogHelper { sender, log_history }
     }
+
+    /// 订阅日志，先返回历史日志，再返回实时日志
+    /// 消费过慢时广播通道会丢弃最旧的日志，此时插入一条日志提示丢弃的数量，而不会阻塞日志的写入
+    pub fn subscribe(&self) -> impl Stream<Item = String> + Send + use<> {
+        let history_logs = self.log_history.read().iter().cloned().collect::<Vec<String>>();
+        let log_rx = self.sender.subscribe();
+        futures::stream::iter(history_logs).chain(BroadcastStream::new(log_rx).map(|msg| match msg {
+            Ok(log_message) => log_message,
+            Err(BroadcastStreamRecvError::Lagged(count)) => dropped_logs_message(count),
+        }))
+    }
+}
+
+/// 构造与 json 日志格式一致的丢弃提示，便于前端统一展示
+fn dropped_logs_message(count: u64) -> String {
+    serde_json::json!({
+        "timestamp": chrono::Local::now().format("%b %d %H:%M:%S").to_string(),
+        "level": "WARN",
+        "message": format!("日志推送过慢，已丢弃 {} 条日志", count),
+    })
+    .to_string()
 }
 
 impl<'a> MakeWriter<'a> for LogHelper {
@@ -52,3 +76,28 @@ impl Clone for LogHelper {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribe_reports_dropped_logs() {
+        let (sender, _) = broadcast::channel(2);
+        let mut log_helper = LogHelper::new(sender, Arc::new(RwLock::new(VecDeque::new())));
+        log_helper.write_all(b"history").unwrap();
+        let stream = log_helper.subscribe();
+        // 写入日志时不会等待订阅者，超出缓冲区的旧日志被直接丢弃
+        for i in 0..5 {
+            log_helper.write_all(format!("log {}", i).as_bytes()).unwrap();
+        }
+        let logs = stream.take(4).collect::<Vec<_>>().await;
+        assert_eq!(logs[0], "history");
+        let dropped: serde_json::Value = serde_json::from_str(&logs[1]).unwrap();
+        assert_eq!(dropped["level"], "WARN");
+        assert_eq!(dropped["message"], "日志推送过慢，已丢弃 3 条日志");
+        assert_eq!(&logs[2..], ["log 3", "log 4"]);
+    }
+}
@@ -1,5 +1,6 @@
 mod log_helper;
 
+use std::fmt::Debug;
 use std::sync::{Arc, LazyLock};
 use std::time::Duration;
 
@@ -10,7 +11,7 @@ use axum::routing::any;
 use axum::{Extension, Router};
 use dashmap::DashMap;
 use futures::stream::{SplitSink, SplitStream};
-use futures::{SinkExt, StreamExt, future};
+use futures::{Sink, SinkExt, StreamExt, future};
 use itertools::Itertools;
 pub use log_helper::{LogHelper, MAX_HISTORY_LOGS};
 use parking_lot::RwLock;
@@ -19,9 +20,9 @@ use sysinfo::{
     CpuRefreshKind, DiskRefreshKind, Disks, MemoryRefreshKind, Pid, ProcessRefreshKind, ProcessesToUpdate, System,
     get_current_pid,
 };
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use tokio::{pin, select};
-use tokio_stream::wrappers::{BroadcastStream, WatchStream};
+use tokio_stream::wrappers::WatchStream;
 use tokio_util::future::FutureExt;
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
@@ -32,13 +33,52 @@ use crate::task::{DownloadTaskManager, TaskStatus};
 static WEBSOCKET_HANDLER: LazyLock<WebSocketHandler> = LazyLock::new(WebSocketHandler::new);
 
 pub(super) fn router() -> Router {
-    Router::new().route("/ws", any(websocket_handler))
+    Router::new()
+        .route("/ws", any(websocket_handler))
+        .route("/ws/tasks", any(task_status_handler))
 }
 
 async fn websocket_handler(ws: WebSocketUpgrade, Extension(log_writer): Extension<LogHelper>) -> impl IntoResponse {
     ws.on_upgrade(|socket| handle_socket(socket, log_writer))
 }
 
+/// 仅推送任务状态的 ws 连接，无需订阅，连接后立即推送当前状态并在状态变化时推送
+async fn task_status_handler(ws: WebSocketUpgrade) -> impl IntoResponse {
+    ws.on_upgrade(|socket| async move {
+        let (sender, mut receiver) = socket.split();
+        select! {
+            _ = push_watch_updates(DownloadTaskManager::get().subscribe(), sender) => {}
+            // 客户端不会发送有效消息，读到连接关闭或出错时结束推送
+            _ = async { while let Some(Ok(_)) = receiver.next().await {} } => {}
+        }
+    })
+}
+
+/// 将 watch 通道中的值序列化为 json 推送给客户端，首先推送一次当前值作为快照
+/// watch 通道的发送方不会等待接收方，只需保证不跨 await 持有 borrow，客户端断开时发送失败即退出
+async fn push_watch_updates<T, S>(mut rx: watch::Receiver<T>, mut sender: S)
+where
+    T: Serialize,
+    S: Sink<Message> + Unpin,
+    S::Error: Debug,
+{
+    loop {
+        let text = serde_json::to_string(&*rx.borrow_and_update());
+        match text {
+            Ok(text) => {
+                if let Err(e) = sender.send(Message::Text(text.into())).await {
+                    error!("Failed to send message: {:?}", e);
+                    break;
+                }
+            }
+            Err(e) => error!("Failed to serialize event: {:?}", e),
+        }
+        if rx.changed().await.is_err() {
+            break;
+        }
+    }
+}
+
 // 事件类型枚举
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -180,18 +220,11 @@ impl WebSocketHandler {
     /// 创建异步日志推送任务，返回任务的取消令牌
     fn new_log_handler(&self, tx: mpsc::Sender<ServerEvent>, log_writer: &LogHelper) -> CancellationToken {
         let cancel_token = CancellationToken::new();
-        // 读取历史日志
-        let history = log_writer.log_history.read();
-        let history_logs = history.iter().cloned().collect::<Vec<String>>();
-        drop(history);
-        // 获取日志广播接收器
-        let log_rx = log_writer.sender.subscribe();
+        // 合并历史日志和实时日志流
+        let log_stream = log_writer.subscribe();
         tokio::spawn(
             async move {
-                // 合并历史日志和实时日志流
-                let log_stream = futures::stream::iter(history_logs)
-                    .chain(BroadcastStream::new(log_rx).filter_map(async |msg| msg.ok()))
-                    .map(ServerEvent::Logs);
+                let log_stream = log_stream.map(ServerEvent::Logs);
                 pin!(log_stream);
                 while let Some(event) = log_stream.next().await {
                     if let Err(e) = tx.send(event).await {
@@ -336,3 +369,33 @@ impl SysInfoExt for Disks {
         self.refresh_specifics(true, DiskRefreshKind::nothing().with_storage());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_push_watch_updates() {
+        let (tx, rx) = watch::channel(serde_json::json!({"is_running": false}));
+        let (sender, mut frames) = futures::channel::mpsc::unbounded();
+        let handle = tokio::spawn(push_watch_updates(rx, sender));
+        // 连接后立即收到当前状态的快照
+        assert_eq!(
+            frames.next().await,
+            Some(Message::Text(r#"{"is_running":false}"#.into()))
+        );
+        tx.send(serde_json::json!({"is_running": true})).unwrap();
+        assert_eq!(
+            frames.next().await,
+            Some(Message::Text(r#"{"is_running":true}"#.into()))
+        );
+        // 客户端断开后，下一次状态变化时推送任务退出，且不影响发送方
+        drop(frames);
+        tx.send(serde_json::json!({"is_running": false})).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("push task should exit after client dropped")
+            .unwrap();
+        tx.send(serde_json::json!({"is_running": true})).unwrap_err();
+    }
+}
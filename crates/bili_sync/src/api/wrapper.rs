@@ -11,6 +11,7 @@ use serde::de::DeserializeOwned;
 use validator::Validate;
 
 use crate::api::error::InnerApiError;
+use crate::bilibili::is_risk_control_error;
 
 #[derive(Serialize)]
 pub struct ApiResponse<T: Serialize> {
@@ -19,6 +20,9 @@ pub struct ApiResponse<T: Serialize> {
     data: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
     message: Option<Cow<'static, str>>,
+    /// 供客户端区分错误类型的错误码，成功时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
 }
 
 impl<T: Serialize> ApiResponse<T> {
@@ -27,6 +31,7 @@ impl<T: Serialize> ApiResponse<T> {
             status_code: 200,
             data: Some(data),
             message: None,
+            code: None,
         }
     }
 
@@ -35,6 +40,7 @@ impl<T: Serialize> ApiResponse<T> {
             status_code: 400,
             data: None,
             message: Some(message.into()),
+            code: Some("BAD_REQUEST"),
         }
     }
 
@@ -43,6 +49,7 @@ impl<T: Serialize> ApiResponse<T> {
             status_code: 401,
             data: None,
             message: Some(message.into()),
+            code: Some("UNAUTHORIZED"),
         }
     }
 
@@ -51,6 +58,33 @@ impl<T: Serialize> ApiResponse<T> {
             status_code: 404,
             data: None,
             message: Some(message.into()),
+            code: Some("NOT_FOUND"),
+        }
+    }
+
+    pub fn conflict(message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            status_code: 409,
+            data: None,
+            message: Some(message.into()),
+            code: Some("CONFLICT"),
+        }
+    }
+
+    pub fn too_many_requests(message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            status_code: 429,
+            data: None,
+            message: Some(message.into()),
+            code: Some("TOO_MANY_REQUESTS"),
+        }
+    }
+
+    /// 替换默认的错误码，用于同一 HTTP 状态码下更细分的错误类型
+    pub fn with_code(self, code: &'static str) -> Self {
+        Self {
+            code: Some(code),
+            ..self
         }
     }
 
@@ -59,6 +93,7 @@ impl<T: Serialize> ApiResponse<T> {
             status_code: 500,
             data: None,
             message: Some(message.into()),
+            code: Some("INTERNAL_ERROR"),
         }
     }
 }
@@ -75,6 +110,12 @@ impl<T: Serialize> IntoResponse for ApiResponse<T> {
 
 pub struct ApiError(Error);
 
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.0, f)
+    }
+}
+
 impl<E> From<E> for ApiError
 where
     E: Into<anyhow::Error>,
@@ -86,15 +127,18 @@ where
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
-        if let Some(inner_error) = self.0.downcast_ref::<InnerApiError>() {
-            match inner_error {
-                InnerApiError::NotFound(_) => return ApiResponse::<()>::not_found(self.0.to_string()).into_response(),
-                InnerApiError::BadRequest(_) => {
-                    return ApiResponse::<()>::bad_request(self.0.to_string()).into_response();
-                }
-            }
-        }
-        ApiResponse::<()>::internal_server_error(self.0.to_string()).into_response()
+        let message = self.0.to_string();
+        let response = match self.0.downcast_ref::<InnerApiError>() {
+            Some(InnerApiError::NotFound(_)) => ApiResponse::<()>::not_found(message),
+            Some(InnerApiError::BadRequest(_)) => ApiResponse::bad_request(message),
+            Some(InnerApiError::Conflict(_)) => ApiResponse::conflict(message),
+            Some(InnerApiError::TemplateError(_)) => ApiResponse::bad_request(message).with_code("TEMPLATE_ERROR"),
+            Some(InnerApiError::RiskControl(_)) => ApiResponse::too_many_requests(message).with_code("RISK_CONTROL"),
+            // 未显式转换的风控错误同样返回风控的错误码，便于客户端提示稍后重试
+            None if is_risk_control_error(&self.0) => ApiResponse::too_many_requests(message).with_code("RISK_CONTROL"),
+            None => ApiResponse::internal_server_error(message),
+        };
+        response.into_response()
     }
 }
 
@@ -117,3 +161,38 @@ where
         Ok(ValidatedJson(value))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+
+    use super::*;
+    use crate::bilibili::BiliError;
+
+    async fn error_body(error: ApiError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("failed to read body");
+        (status, serde_json::from_slice(&body).expect("body should be json"))
+    }
+
+    #[tokio::test]
+    async fn test_api_error_code() {
+        let (status, body) = error_body(InnerApiError::NotFound(1).into()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "NOT_FOUND");
+        assert_eq!(body["message"], "Primary key not found: 1");
+        let (status, body) = error_body(InnerApiError::TemplateError("invalid".to_owned()).into()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "TEMPLATE_ERROR");
+        let error =
+            anyhow::Error::from(BiliError::RiskControlOccurred("-352".to_owned())).context("fetch video failed");
+        let (status, body) = error_body(error.into()).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["code"], "RISK_CONTROL");
+        let (_, body) = error_body(anyhow::anyhow!("unknown").into()).await;
+        assert_eq!(body["code"], "INTERNAL_ERROR");
+    }
+}
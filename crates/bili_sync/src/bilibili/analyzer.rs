@@ -1,7 +1,12 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
 use anyhow::{Context, Result, bail};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::bilibili::error::BiliError;
+use crate::config::{Config, VersionedCache};
 
 pub struct PageAnalyzer {
     pub(crate) info: serde_json::Value,
@@ -91,6 +96,36 @@ pub struct FilterOption {
     pub no_dolby_audio: bool,
     pub no_hdr: bool,
     pub no_hires: bool,
+    /// 期望的视频清晰度（qn），会选择不高于该值的最高清晰度，没有满足条件的流时回退到可用的最高清晰度
+    #[serde(default)]
+    pub preferred_quality: Option<u32>,
+    /// 期望的视频编码，同清晰度下优先选择该编码
+    #[serde(default)]
+    pub prefer_codec: Option<VideoCodecs>,
+    /// 视频总时长的下限（秒），短于该值的视频不会被下载
+    #[serde(default)]
+    pub min_duration_secs: Option<u32>,
+    /// 视频总时长的上限（秒），长于该值的视频不会被下载
+    #[serde(default)]
+    pub max_duration_secs: Option<u32>,
+    /// 标题需要包含其中任一关键词才会下载，与 title_include_regex 均为空时不做限制
+    #[serde(default)]
+    pub title_include: Vec<String>,
+    /// 标题包含其中任一关键词的视频不会被下载，优先于包含条件
+    #[serde(default)]
+    pub title_exclude: Vec<String>,
+    /// 正则形式的包含条件，与 title_include 任一匹配即可
+    #[serde(default)]
+    pub title_include_regex: Vec<String>,
+    /// 正则形式的排除条件，与 title_exclude 任一匹配即排除
+    #[serde(default)]
+    pub title_exclude_regex: Vec<String>,
+    /// 播放量下限，低于该值的视频不会被下载
+    #[serde(default)]
+    pub min_view: Option<u64>,
+    /// 点赞数下限，低于该值的视频不会被下载
+    #[serde(default)]
+    pub min_like: Option<u64>,
 }
 
 impl Default for FilterOption {
@@ -105,10 +140,117 @@ impl Default for FilterOption {
             no_dolby_audio: false,
             no_hdr: false,
             no_hires: false,
+            preferred_quality: None,
+            prefer_codec: None,
+            min_duration_secs: None,
+            max_duration_secs: None,
+            title_include: Vec::new(),
+            title_exclude: Vec::new(),
+            title_include_regex: Vec::new(),
+            title_exclude_regex: Vec::new(),
+            min_view: None,
+            min_like: None,
         }
     }
 }
 
+/// 标题过滤使用的正则表达式仅在配置修改时变化，跟随配置重新编译，非法的表达式直接跳过
+static TITLE_REGEX_CACHE: LazyLock<VersionedCache<HashMap<String, Regex>>> =
+    LazyLock::new(|| VersionedCache::new(build_title_regexes).expect("failed to create title regex cache"));
+
+fn build_title_regexes(config: &Config) -> Result<HashMap<String, Regex>> {
+    Ok(config
+        .filter_option
+        .title_include_regex
+        .iter()
+        .chain(&config.filter_option.title_exclude_regex)
+        .filter_map(|pattern| Regex::new(pattern).ok().map(|regex| (pattern.clone(), regex)))
+        .collect())
+}
+
+/// 优先使用缓存中编译好的正则表达式，不在当前配置中的表达式临时编译
+fn cached_regex(pattern: &str) -> Option<Regex> {
+    TITLE_REGEX_CACHE
+        .read()
+        .get(pattern)
+        .cloned()
+        .or_else(|| Regex::new(pattern).ok())
+}
+
+impl FilterOption {
+    /// 检查 preferred_quality 是否为已知的 qn 值
+    pub fn is_preferred_quality_valid(&self) -> bool {
+        self.preferred_quality
+            .is_none_or(|qn| VideoQuality::from_repr(qn as usize).is_some())
+    }
+
+    /// 检查时长上下限是否有效，同时设置时下限不能大于上限
+    pub fn is_duration_range_valid(&self) -> bool {
+        match (self.min_duration_secs, self.max_duration_secs) {
+            (Some(min), Some(max)) => min <= max,
+            _ => true,
+        }
+    }
+
+    /// 检查视频总时长是否处于设置的上下限之内，未设置的一侧不做限制
+    pub fn is_duration_allowed(&self, duration: u32) -> bool {
+        self.min_duration_secs.is_none_or(|min| duration >= min)
+            && self.max_duration_secs.is_none_or(|max| duration <= max)
+    }
+
+    /// 检查标题过滤使用的正则表达式是否均合法
+    pub fn is_title_regex_valid(&self) -> bool {
+        self.title_include_regex
+            .iter()
+            .chain(&self.title_exclude_regex)
+            .all(|pattern| Regex::new(pattern).is_ok())
+    }
+
+    /// 检查视频的标题是否满足关键词过滤条件，同时命中包含与排除条件时以排除为准
+    pub fn is_title_allowed(&self, title: &str) -> bool {
+        let matches = |keywords: &[String], patterns: &[String]| {
+            keywords
+                .iter()
+                .filter(|keyword| !keyword.is_empty())
+                .any(|keyword| title.contains(keyword.as_str()))
+                || patterns
+                    .iter()
+                    .filter_map(|pattern| cached_regex(pattern))
+                    .any(|regex| regex.is_match(title))
+        };
+        if matches(&self.title_exclude, &self.title_exclude_regex) {
+            return false;
+        }
+        (self.title_include.is_empty() && self.title_include_regex.is_empty())
+            || matches(&self.title_include, &self.title_include_regex)
+    }
+
+    /// 检查视频的播放量与点赞数是否达到设置的下限，接口未返回对应的统计数据时视为满足条件
+    pub fn is_stat_allowed(&self, view: Option<u64>, like: Option<u64>) -> bool {
+        let reaches = |threshold: Option<u64>, value: Option<u64>| match (threshold, value) {
+            (Some(threshold), Some(value)) => value >= threshold,
+            _ => true,
+        };
+        reaches(self.min_view, view) && reaches(self.min_like, like)
+    }
+
+    /// 视频流的排序键，越大越优先
+    fn video_sort_key(&self, quality: &VideoQuality, codecs: &VideoCodecs) -> (bool, VideoQuality, bool, isize) {
+        let within_preferred = self.preferred_quality.is_none_or(|qn| quality.clone() as u32 <= qn);
+        let codec_rank = self
+            .codecs
+            .iter()
+            .position(|c| c == codecs)
+            .map_or(isize::MIN, |pos| -(pos as isize));
+        (
+            within_preferred,
+            quality.clone(),
+            self.prefer_codec.as_ref() == Some(codecs),
+            codec_rank,
+        )
+    }
+}
+
 // 上游项目中的五种流类型，不过目测应该只有 Flv、DashVideo、DashAudio 三种会被用到
 #[derive(Debug, PartialEq, PartialOrd)]
 pub enum Stream {
@@ -130,6 +272,14 @@ pub enum Stream {
 
 // 通用的获取流链接的方法，交由 Downloader 使用
 impl Stream {
+    /// dash 音频流的音质，其它类型的流返回 None
+    pub fn audio_quality(&self) -> Option<AudioQuality> {
+        match self {
+            Self::DashAudio { quality, .. } => Some(*quality),
+            _ => None,
+        }
+    }
+
     pub fn urls(&self, enable_cdn_sorting: bool) -> Vec<&str> {
         match self {
             Self::Flv(url) | Self::Html5Mp4(url) | Self::EpisodeTryMp4(url) => vec![url],
@@ -165,6 +315,16 @@ pub enum BestStream {
     Mixed(Stream),
 }
 
+impl BestStream {
+    /// 取出独立的音频流，仅视频、音频分离且存在音频时返回 Some
+    pub fn into_audio(self) -> Option<Stream> {
+        match self {
+            BestStream::VideoAudio { audio, .. } => audio,
+            BestStream::Mixed(_) => None,
+        }
+    }
+}
+
 impl PageAnalyzer {
     pub fn new(info: serde_json::Value) -> Self {
         Self { info }
@@ -311,28 +471,8 @@ impl PageAnalyzer {
         Ok(BestStream::VideoAudio {
             video: videos
                 .into_iter()
-                .max_by(|a, b| match (a, b) {
-                    (
-                        Stream::DashVideo {
-                            quality: a_quality,
-                            codecs: a_codecs,
-                            ..
-                        },
-                        Stream::DashVideo {
-                            quality: b_quality,
-                            codecs: b_codecs,
-                            ..
-                        },
-                    ) => {
-                        if a_quality != b_quality {
-                            return a_quality.cmp(b_quality);
-                        };
-                        filter_option
-                            .codecs
-                            .iter()
-                            .position(|c| c == b_codecs)
-                            .cmp(&filter_option.codecs.iter().position(|c| c == a_codecs))
-                    }
+                .max_by_key(|s| match s {
+                    Stream::DashVideo { quality, codecs, .. } => filter_option.video_sort_key(quality, codecs),
                     _ => unreachable!(),
                 })
                 .context("no video stream found")?,
@@ -425,7 +565,7 @@ mod tests {
         ];
         let config = VersionedConfig::get().read();
         for (bvid, video_quality, video_codec, audio_quality) in testcases.into_iter() {
-            let client = BiliClient::new();
+            let client = BiliClient::new().expect("failed to build client");
             let video = Video::new(&client, bvid.to_owned(), &config.credential);
             let pages = video.get_pages().await.expect("failed to get pages");
             let first_page = pages.into_iter().next().expect("no page found");
@@ -456,6 +596,102 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_preferred_quality_and_codec() {
+        let playurl = serde_json::json!({
+            "dash": {
+                "video": [
+                    { "id": 120, "baseUrl": "4k-hev", "codecid": 12 },
+                    { "id": 80, "baseUrl": "1080p-avc", "codecid": 7 },
+                    { "id": 80, "baseUrl": "1080p-hev", "codecid": 12 },
+                    { "id": 64, "baseUrl": "720p-avc", "codecid": 7 },
+                ],
+                "audio": [
+                    { "id": 30280, "baseUrl": "audio-192k" },
+                ],
+            }
+        });
+        let testcases = [
+            // 未设置偏好时选择最高清晰度
+            (None, None, "4k-hev"),
+            // 选择不高于期望值的最高清晰度，同清晰度下按照 codecs 的顺序
+            (Some(80), None, "1080p-avc"),
+            (Some(116), None, "1080p-avc"),
+            // 同清晰度下优先选择期望的编码
+            (Some(80), Some(VideoCodecs::HEV), "1080p-hev"),
+            // 期望的编码不会影响清晰度的选择
+            (Some(64), Some(VideoCodecs::HEV), "720p-avc"),
+            // 没有满足条件的流时回退到最高清晰度
+            (Some(32), None, "4k-hev"),
+        ];
+        for (preferred_quality, prefer_codec, expected_url) in testcases {
+            let filter_option = FilterOption {
+                preferred_quality,
+                prefer_codec,
+                ..Default::default()
+            };
+            let best_stream = PageAnalyzer::new(playurl.clone())
+                .best_stream(&filter_option)
+                .expect("failed to get best stream");
+            match best_stream {
+                BestStream::VideoAudio {
+                    video: Stream::DashVideo { url, .. },
+                    audio: Some(Stream::DashAudio { url: audio_url, .. }),
+                } => {
+                    assert_eq!(url, expected_url);
+                    assert_eq!(audio_url, "audio-192k");
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_title_filter() {
+        let filter_option = |include: &[&str], exclude: &[&str], include_regex: &[&str], exclude_regex: &[&str]| {
+            let to_vec = |items: &[&str]| items.iter().map(|item| item.to_string()).collect::<Vec<_>>();
+            FilterOption {
+                title_include: to_vec(include),
+                title_exclude: to_vec(exclude),
+                title_include_regex: to_vec(include_regex),
+                title_exclude_regex: to_vec(exclude_regex),
+                ..Default::default()
+            }
+        };
+        // 未设置任何条件时不做限制
+        assert!(filter_option(&[], &[], &[], &[]).is_title_allowed("任意标题"));
+        // 包含条件：标题命中任一关键词即可
+        let include = filter_option(&["教程"], &[], &[r"^\[直播回放\]"], &[]);
+        assert!(include.is_title_allowed("Rust 教程 第一集"));
+        assert!(include.is_title_allowed("[直播回放] 周五"));
+        assert!(!include.is_title_allowed("日常 vlog"));
+        // 排除条件：标题命中任一关键词即排除
+        let exclude = filter_option(&[], &["抽奖"], &[], &["(?i)shorts?"]);
+        assert!(!exclude.is_title_allowed("粉丝抽奖"));
+        assert!(!exclude.is_title_allowed("今日 Shorts"));
+        assert!(exclude.is_title_allowed("Rust 教程"));
+        // 同时命中包含与排除条件时以排除为准
+        let both = filter_option(&["教程"], &["抽奖"], &[], &[]);
+        assert!(both.is_title_allowed("Rust 教程"));
+        assert!(!both.is_title_allowed("Rust 教程 抽奖"));
+        assert!(!both.is_title_allowed("抽奖"));
+        // 非法的正则表达式被忽略
+        assert!(filter_option(&[], &[], &[], &["("]).is_title_allowed("Rust 教程"));
+        assert!(both.is_title_regex_valid());
+        assert!(!filter_option(&[], &[], &["("], &[]).is_title_regex_valid());
+    }
+
+    #[test]
+    fn test_preferred_quality_validation() {
+        for (preferred_quality, valid) in [(None, true), (Some(80), true), (Some(127), true), (Some(81), false)] {
+            let filter_option = FilterOption {
+                preferred_quality,
+                ..Default::default()
+            };
+            assert_eq!(filter_option.is_preferred_quality_valid(), valid);
+        }
+    }
+
     #[test]
     fn test_url_sort() {
         let stream = Stream::DashVideo {
@@ -478,4 +714,37 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_best_stream_into_audio() {
+        let video = || Stream::DashVideo {
+            url: "https://example.com/video.m4s".to_owned(),
+            backup_url: vec![],
+            quality: VideoQuality::Quality1080p,
+            codecs: VideoCodecs::AVC,
+        };
+        let audio = Stream::DashAudio {
+            url: "https://example.com/audio.m4s".to_owned(),
+            backup_url: vec![],
+            quality: AudioQuality::Quality192k,
+        };
+        let best_stream = BestStream::VideoAudio {
+            video: video(),
+            audio: Some(audio),
+        };
+        assert!(matches!(
+            best_stream.into_audio(),
+            Some(Stream::DashAudio { url, .. }) if url == "https://example.com/audio.m4s"
+        ));
+        let silent = BestStream::VideoAudio {
+            video: video(),
+            audio: None,
+        };
+        assert!(silent.into_audio().is_none());
+        assert!(
+            BestStream::Mixed(Stream::Flv("https://example.com/video.flv".to_owned()))
+                .into_audio()
+                .is_none()
+        );
+    }
 }
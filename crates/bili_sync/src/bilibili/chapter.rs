@@ -0,0 +1,98 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+/// 视频的“看点”，即播放器进度条上的章节
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct Chapter {
+    #[serde(rename = "content")]
+    pub title: String,
+    /// 章节的起始时间（秒）
+    pub from: u32,
+    /// 章节的结束时间（秒）
+    pub to: u32,
+}
+
+/// 从 player/wbi/v2 接口返回的 view_points 中解析章节，不存在看点时返回空列表
+pub fn parse_chapters(view_points: serde_json::Value) -> Result<Vec<Chapter>> {
+    let mut chapters = serde_json::from_value::<Option<Vec<Chapter>>>(view_points)?
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|chapter| chapter.to > chapter.from)
+        .collect::<Vec<_>>();
+    chapters.sort_by_key(|chapter| chapter.from);
+    Ok(chapters)
+}
+
+/// 将章节渲染为 ffmpeg 的 FFMETADATA 格式，用于封装进视频文件
+pub fn chapters_to_ffmetadata(chapters: &[Chapter]) -> String {
+    let mut output = String::from(";FFMETADATA1\n");
+    for chapter in chapters {
+        let _ = write!(
+            output,
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            chapter.from as u64 * 1000,
+            chapter.to as u64 * 1000,
+            escape_ffmetadata(&chapter.title)
+        );
+    }
+    output
+}
+
+/// FFMETADATA 中的 =、;、#、\ 与换行符需要使用反斜杠转义
+fn escape_ffmetadata(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chapters() {
+        let response = serde_json::json!({
+            "code": 0,
+            "data": {
+                "view_points": [
+                    {"type": 2, "from": 65, "to": 180, "content": "正片", "imgUrl": "", "logoUrl": ""},
+                    {"type": 2, "from": 0, "to": 65, "content": "开场", "imgUrl": "", "logoUrl": ""},
+                    {"type": 2, "from": 180, "to": 180, "content": "无效章节", "imgUrl": "", "logoUrl": ""}
+                ]
+            }
+        });
+        let chapters = parse_chapters(response["data"]["view_points"].clone()).unwrap();
+        assert_eq!(
+            chapters,
+            vec![
+                Chapter {
+                    title: "开场".to_string(),
+                    from: 0,
+                    to: 65,
+                },
+                Chapter {
+                    title: "正片".to_string(),
+                    from: 65,
+                    to: 180,
+                },
+            ]
+        );
+        // 没有看点的视频不返回 view_points 字段
+        assert!(parse_chapters(serde_json::Value::Null).unwrap().is_empty());
+        assert_eq!(
+            chapters_to_ffmetadata(&[Chapter {
+                title: "第一章=开始".to_string(),
+                from: 0,
+                to: 65,
+            }]),
+            ";FFMETADATA1\n\n[CHAPTER]\nTIMEBASE=1/1000\nSTART=0\nEND=65000\ntitle=第一章\\=开始\n"
+        );
+    }
+}
@@ -1,15 +1,43 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use leaky_bucket::RateLimiter;
 use parking_lot::Once;
 use reqwest::{Method, header};
 use ua_generator::ua;
 
 use crate::bilibili::Credential;
-use crate::bilibili::credential::WbiImg;
-use crate::config::{RateLimit, VersionedCache};
+use crate::bilibili::credential::{CredentialStatus, RefreshHosts, WbiImg};
+use crate::config::{Config, RateLimit, VersionedCache, VersionedConfig};
+
+/// 构造 Client 时使用的网络选项
+pub struct ClientOptions<'a> {
+    pub proxy: Option<&'a str>,
+    pub connect_timeout: Duration,
+    // 连续该时长未收到任何数据时视为超时，不限制大文件下载的总耗时
+    pub request_timeout: Duration,
+}
+
+impl<'a> ClientOptions<'a> {
+    pub fn from_config(config: &'a Config) -> Self {
+        Self {
+            proxy: config.proxy.as_deref(),
+            connect_timeout: Duration::from_secs(config.connect_timeout_secs),
+            request_timeout: Duration::from_secs(config.request_timeout_secs),
+        }
+    }
+}
+
+impl Default for ClientOptions<'_> {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
 
 // 一个对 reqwest::Client 的简单封装，用于 Bilibili 请求
 #[derive(Clone)]
@@ -17,6 +45,11 @@ pub struct Client(reqwest::Client);
 
 impl Client {
     pub fn new() -> Self {
+        Self::with_options(&ClientOptions::default()).expect("failed to build reqwest client")
+    }
+
+    /// 构造 Client，设置了 proxy 时所有请求都会经由该代理发出，支持 http、https 与 socks5 代理
+    pub fn with_options(options: &ClientOptions<'_>) -> Result<Self> {
         static INIT: Once = Once::new();
         INIT.call_once(|| {
             rustls::crypto::ring::default_provider()
@@ -33,15 +66,15 @@ impl Client {
             header::REFERER,
             header::HeaderValue::from_static("https://www.bilibili.com"),
         );
-        Self(
-            reqwest::Client::builder()
-                .default_headers(headers)
-                .gzip(true)
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .read_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .expect("failed to build reqwest client"),
-        )
+        let mut builder = reqwest::Client::builder()
+            .default_headers(headers)
+            .gzip(true)
+            .connect_timeout(options.connect_timeout)
+            .read_timeout(options.request_timeout);
+        if let Some(proxy) = options.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        Ok(Self(builder.build()?))
     }
 
     // a wrapper of reqwest::Client::request to add credential to the request
@@ -74,12 +107,30 @@ enum Limiter {
 
 pub struct BiliClient {
     pub client: Client,
+    notifier_client: reqwest::Client,
     limiter: Limiter,
 }
 
 impl BiliClient {
-    pub fn new() -> Self {
-        let client = Client::new();
+    /// 根据当前配置中的代理与超时构造 BiliClient，这些选项在构造时确定，修改后需重启才能生效
+    /// 代理地址无效时返回错误
+    pub fn new() -> Result<Self> {
+        let config = VersionedConfig::get().read();
+        let options = ClientOptions::from_config(&config);
+        let client = Client::with_options(&options).context("failed to build bilibili client")?;
+        // 未单独设置通知代理时，通知与 Bilibili 请求共用同一个 client
+        let notifier_client = match config.notifier_proxy.as_deref() {
+            Some(notifier_proxy) => {
+                let options = ClientOptions {
+                    proxy: Some(notifier_proxy),
+                    ..options
+                };
+                Client::with_options(&options)
+                    .context("failed to build notifier client")?
+                    .0
+            }
+            None => client.0.clone(),
+        };
         let limiter = Limiter::Latest(
             VersionedCache::new(|config| {
                 Ok(config
@@ -97,7 +148,11 @@ impl BiliClient {
             })
             .expect("failed to create rate limiter"),
         );
-        Self { client, limiter }
+        Ok(Self {
+            client,
+            notifier_client,
+            limiter,
+        })
     }
 
     /// 获取当前 BiliClient 的快照，快照中的限流器固定不变
@@ -108,6 +163,7 @@ impl BiliClient {
         };
         Ok(Self {
             client: self.client.clone(),
+            notifier_client: self.notifier_client.clone(),
             limiter: Limiter::Snapshot(inner.snapshot()),
         })
     }
@@ -129,12 +185,35 @@ impl BiliClient {
         self.client.request(method, url, Some(credential))
     }
 
-    /// 检查并刷新 Credential，不需要刷新返回 Ok(None)，需要刷新返回 Ok(Some(new_credential))
-    pub async fn check_refresh(&self, credential: &Credential) -> Result<Option<Credential>> {
-        if !credential.need_refresh(&self.client).await? {
+    /// 检查 Credential 是否需要刷新，不需要刷新返回 Ok(None)，需要刷新返回 Ok(Some(refresh_csrf))
+    ///
+    /// 这两步请求均不会使旧凭据失效，失败后可以安全地重试
+    pub async fn prepare_refresh(&self, credential: &Credential) -> Result<Option<String>> {
+        self.prepare_refresh_at(credential, RefreshHosts::default()).await
+    }
+
+    /// 向 hosts 指定的站点检查 Credential 是否需要刷新
+    pub async fn prepare_refresh_at(&self, credential: &Credential, hosts: RefreshHosts<'_>) -> Result<Option<String>> {
+        if !credential.need_refresh(&self.client, hosts).await? {
             return Ok(None);
         }
-        Ok(Some(credential.refresh(&self.client).await?))
+        Ok(Some(credential.refresh_csrf(&self.client, hosts).await?))
+    }
+
+    /// 使用 prepare_refresh 获取的 refresh_csrf 刷新 Credential
+    pub async fn refresh_credential(&self, credential: &Credential, csrf: &str) -> Result<Credential> {
+        self.refresh_credential_at(credential, RefreshHosts::default(), csrf)
+            .await
+    }
+
+    /// 向 hosts 指定的站点刷新 Credential
+    pub async fn refresh_credential_at(
+        &self,
+        credential: &Credential,
+        hosts: RefreshHosts<'_>,
+        csrf: &str,
+    ) -> Result<Credential> {
+        credential.refresh(&self.client, hosts, csrf).await
     }
 
     /// 获取 wbi img，用于生成请求签名
@@ -142,7 +221,91 @@ impl BiliClient {
         credential.wbi_img(&self.client).await
     }
 
-    pub fn inner_client(&self) -> &reqwest::Client {
-        &self.client.0
+    /// 检查 Credential 是否有效，不会修改 Credential
+    pub async fn credential_status(&self, credential: &Credential) -> Result<CredentialStatus> {
+        credential.status(&self.client).await
+    }
+
+    /// 获取用于发送通知的 client
+    pub fn notifier_client(&self) -> &reqwest::Client {
+        &self.notifier_client
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::error::ExecutionStatus;
+
+    #[tokio::test]
+    async fn test_client_with_proxy() -> Result<()> {
+        let options = ClientOptions {
+            proxy: Some("not a proxy url"),
+            ..Default::default()
+        };
+        assert!(Client::with_options(&options).is_err());
+        // 在本地启动一个假的 http 代理，检查请求是否经由代理发出
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let proxy = format!("http://{}", listener.local_addr()?);
+        let proxy_server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await?;
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await?;
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+                .await?;
+            anyhow::Ok(String::from_utf8_lossy(&buffer[..n]).to_string())
+        });
+        let client = Client::with_options(&ClientOptions {
+            proxy: Some(&proxy),
+            ..Default::default()
+        })?;
+        let resp = client
+            .request(Method::GET, "http://api.bilibili.com/x/ping", None)
+            .send()
+            .await?;
+        assert_eq!(resp.status(), reqwest::StatusCode::NO_CONTENT);
+        let request = proxy_server.await??;
+        assert!(request.starts_with("GET http://api.bilibili.com/x/ping HTTP/1.1"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_request_timeout() -> Result<()> {
+        // 在本地启动一个只返回响应头、迟迟不返回响应体的服务，模拟卡住的 CDN 节点
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/video.m4s", listener.local_addr()?);
+        let slow_server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await?;
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await?;
+            assert!(n > 0);
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1024\r\n\r\n")
+                .await?;
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            anyhow::Ok(())
+        });
+        let client = Client::with_options(&ClientOptions {
+            request_timeout: Duration::from_secs(1),
+            ..Default::default()
+        })?;
+        let start = Instant::now();
+        let res: Result<ExecutionStatus> = async {
+            client.request(Method::GET, &url, None).send().await?.bytes().await?;
+            Ok(ExecutionStatus::Succeeded)
+        }
+        .await;
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(900) && elapsed < Duration::from_secs(3));
+        // 超时被视为可重试的常见错误，不会将任务标记为失败
+        assert!(matches!(ExecutionStatus::from(res), ExecutionStatus::Ignored(_)));
+        slow_server.abort();
+        Ok(())
     }
 }
@@ -203,9 +203,6 @@ impl<'a> Collection<'a> {
                         self.collection, page
                     )
                 })?;
-                for video_info in videos_info {
-                    yield video_info;
-                }
                 let page_info = &videos["data"]["page"];
                 let fields = match self.collection.collection_type {
                     CollectionType::Series => ["num", "size", "total"],
@@ -216,6 +213,13 @@ impl<'a> Collection<'a> {
                     .map(|f| page_info[f].as_i64())
                     .collect::<Vec<Option<i64>>>();
                 if let [Some(num), Some(size), Some(total)] = values[..] {
+                    // 接口按照从新到旧的顺序返回，倒序计算视频在合集中的位置
+                    for (index, mut video_info) in videos_info.into_iter().enumerate() {
+                        if let VideoInfo::Collection { order, .. } = &mut video_info {
+                            *order = i32::try_from(total - (num - 1) * size - index as i64).ok();
+                        }
+                        yield video_info;
+                    }
                     if num * size < total {
                         page += 1;
                         continue;
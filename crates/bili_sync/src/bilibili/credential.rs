@@ -24,6 +24,22 @@ mod qrcode_status_code {
     pub const EXPIRED: i64 = 86038;
 }
 
+/// 检查与刷新凭据时请求的站点地址，测试时可以替换为本地的模拟服务
+#[derive(Debug, Clone, Copy)]
+pub struct RefreshHosts<'a> {
+    pub passport: &'a str,
+    pub www: &'a str,
+}
+
+impl Default for RefreshHosts<'_> {
+    fn default() -> Self {
+        Self {
+            passport: "https://passport.bilibili.com",
+            www: "https://www.bilibili.com",
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Credential {
     pub sessdata: String,
@@ -33,6 +49,16 @@ pub struct Credential {
     pub ac_time_value: String,
 }
 
+/// 通过 nav 接口检查得到的 Credential 状态
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CredentialStatus {
+    pub valid: bool,
+    pub vip_status: i64,
+    /// SESSDATA 的剩余有效期（秒），无法从 SESSDATA 中解析出过期时间时为 None
+    pub expires_in: Option<i64>,
+    pub mid: Option<i64>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct WbiImg {
     pub(crate) img_url: String,
@@ -85,6 +111,53 @@ impl Credential {
         Ok(serde_json::from_value(res["data"]["wbi_img"].take())?)
     }
 
+    /// 检查 Credential 当前是否有效，仅读取账号信息，不会修改 Credential
+    pub async fn status(&self, client: &Client) -> Result<CredentialStatus> {
+        let res = client
+            .request(Method::GET, "https://api.bilibili.com/x/web-interface/nav", Some(self))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<serde_json::Value>()
+            .await?;
+        self.status_from_nav(res, chrono::Utc::now().timestamp())
+    }
+
+    fn status_from_nav(&self, res: serde_json::Value, now: i64) -> Result<CredentialStatus> {
+        // -101 表示账号未登录，即 Credential 已失效
+        if res["code"].as_i64() == Some(-101) {
+            return Ok(CredentialStatus {
+                valid: false,
+                vip_status: 0,
+                expires_in: None,
+                mid: None,
+            });
+        }
+        let res = res.validate()?;
+        let data = &res["data"];
+        let valid = data["isLogin"].as_bool().unwrap_or_default();
+        Ok(CredentialStatus {
+            valid,
+            vip_status: data["vipStatus"].as_i64().unwrap_or_default(),
+            expires_in: valid
+                .then(|| self.sessdata_expires_at())
+                .flatten()
+                .map(|expires_at| expires_at - now),
+            mid: data["mid"].as_i64().filter(|_| valid),
+        })
+    }
+
+    /// SESSDATA 形如 "{hash},{过期时间戳},{...}"，逗号可能以 %2C 的形式出现
+    fn sessdata_expires_at(&self) -> Option<i64> {
+        self.sessdata
+            .replace("%2C", ",")
+            .replace("%2c", ",")
+            .split(',')
+            .nth(1)?
+            .parse()
+            .ok()
+    }
+
     pub async fn generate_qrcode(client: &Client) -> Result<Qrcode> {
         let mut res = client
             .request(
@@ -158,11 +231,11 @@ impl Credential {
     }
 
     /// 检查凭据是否有效
-    pub async fn need_refresh(&self, client: &Client) -> Result<bool> {
+    pub async fn need_refresh(&self, client: &Client, hosts: RefreshHosts<'_>) -> Result<bool> {
         let res = client
             .request(
                 Method::GET,
-                "https://passport.bilibili.com/x/passport-login/web/cookie/info",
+                format!("{}/x/passport-login/web/cookie/info", hosts.passport).as_str(),
                 Some(self),
             )
             .send()
@@ -174,19 +247,25 @@ impl Credential {
         res["data"]["refresh"].as_bool().context("check refresh failed")
     }
 
-    pub async fn refresh(&self, client: &Client) -> Result<Self> {
+    /// 获取刷新凭据所需的 refresh_csrf，该请求不会使旧凭据失效，失败后可以重试
+    pub async fn refresh_csrf(&self, client: &Client, hosts: RefreshHosts<'_>) -> Result<String> {
         let correspond_path = Self::get_correspond_path();
-        let csrf = self
-            .get_refresh_csrf(client, correspond_path)
+        self.get_refresh_csrf(client, hosts, correspond_path)
             .await
-            .context("获取 refresh_csrf 失败")?;
+            .context("获取 refresh_csrf 失败")
+    }
+
+    /// 使用 refresh_csrf 刷新凭据，刷新请求发出后旧的 refresh_token 可能已经失效，失败后不应重试
+    pub async fn refresh(&self, client: &Client, hosts: RefreshHosts<'_>, csrf: &str) -> Result<Self> {
         let new_credential = self
-            .get_new_credential(client, &csrf)
+            .get_new_credential(client, hosts, csrf)
             .await
             .context("刷新 Credential 失败")?;
-        self.confirm_refresh(client, &new_credential)
-            .await
-            .context("确认更新 Credential 失败")?;
+        // 刷新请求成功后旧的 refresh_token 已经失效，新的凭据无论确认是否成功都可以使用，
+        // 确认失败仅导致旧凭据不会被立即注销，不应丢弃新的凭据
+        if let Err(e) = self.confirm_refresh(client, hosts, &new_credential).await {
+            warn!("确认更新 Credential 失败，继续使用刷新后的凭据：{:#}", e);
+        }
         Ok(new_credential)
     }
 
@@ -210,11 +289,16 @@ JNrRuoEUXpabUzGB8QIDAQAB
         hex::encode(encrypted)
     }
 
-    async fn get_refresh_csrf(&self, client: &Client, correspond_path: String) -> Result<String> {
+    async fn get_refresh_csrf(
+        &self,
+        client: &Client,
+        hosts: RefreshHosts<'_>,
+        correspond_path: String,
+    ) -> Result<String> {
         let res = client
             .request(
                 Method::GET,
-                format!("https://www.bilibili.com/correspond/1/{}", correspond_path).as_str(),
+                format!("{}/correspond/1/{}", hosts.www, correspond_path).as_str(),
                 Some(self),
             )
             .header(header::COOKIE, "Domain=.bilibili.com")
@@ -224,11 +308,11 @@ JNrRuoEUXpabUzGB8QIDAQAB
         regex_find(r#"<div id="1-name">(.+?)</div>"#, res.text().await?.as_str())
     }
 
-    async fn get_new_credential(&self, client: &Client, csrf: &str) -> Result<Credential> {
+    async fn get_new_credential(&self, client: &Client, hosts: RefreshHosts<'_>, csrf: &str) -> Result<Credential> {
         let mut resp = client
             .request(
                 Method::POST,
-                "https://passport.bilibili.com/x/passport-login/web/cookie/refresh",
+                format!("{}/x/passport-login/web/cookie/refresh", hosts.passport).as_str(),
                 Some(self),
             )
             .header(header::COOKIE, "Domain=.bilibili.com")
@@ -249,11 +333,16 @@ JNrRuoEUXpabUzGB8QIDAQAB
         Ok(credential)
     }
 
-    async fn confirm_refresh(&self, client: &Client, new_credential: &Credential) -> Result<()> {
+    async fn confirm_refresh(
+        &self,
+        client: &Client,
+        hosts: RefreshHosts<'_>,
+        new_credential: &Credential,
+    ) -> Result<()> {
         client
             .request(
                 Method::POST,
-                "https://passport.bilibili.com/x/passport-login/web/confirm/refresh",
+                format!("{}/x/passport-login/web/confirm/refresh", hosts.passport).as_str(),
                 // 此处用的是新的凭证
                 Some(new_credential),
             )
@@ -440,4 +529,42 @@ mod tests {
         }
         bail!("轮询超时")
     }
+
+    #[test]
+    fn test_credential_status_from_nav() {
+        let credential = Credential {
+            sessdata: "0a1b2c3d%2C1760000000%2Cabcde%2A41".to_owned(),
+            ..Default::default()
+        };
+        let nav = serde_json::json!({
+            "code": 0,
+            "message": "0",
+            "data": {"isLogin": true, "mid": 1234, "vipStatus": 1, "wbi_img": {}}
+        });
+        assert_eq!(
+            credential.status_from_nav(nav, 1759990000).unwrap(),
+            CredentialStatus {
+                valid: true,
+                vip_status: 1,
+                expires_in: Some(10000),
+                mid: Some(1234),
+            }
+        );
+        let nav = serde_json::json!({
+            "code": -101,
+            "message": "账号未登录",
+            "data": {"isLogin": false, "wbi_img": {}}
+        });
+        assert_eq!(
+            credential.status_from_nav(nav, 1759990000).unwrap(),
+            CredentialStatus {
+                valid: false,
+                vip_status: 0,
+                expires_in: None,
+                mid: None,
+            }
+        );
+        let nav = serde_json::json!({"code": -352, "message": "-352", "data": {"v_voucher": "voucher"}});
+        assert!(credential.status_from_nav(nav, 1759990000).is_err());
+    }
 }
@@ -10,6 +10,26 @@ use crate::bilibili::danmaku::canvas::lane::Collision;
 use crate::bilibili::danmaku::danmu::DanmuType;
 use crate::bilibili::danmaku::{Danmu, DrawEffect, Drawable};
 
+/// 弹幕文件的保存格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DanmakuFormat {
+    /// 滚动的弹幕，样式由 DanmakuOption 中的其它选项控制
+    #[default]
+    Ass,
+    /// 按时间顺序依次显示的纯文本字幕，适用于无法渲染 ass 的设备
+    Srt,
+}
+
+impl DanmakuFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DanmakuFormat::Ass => "ass",
+            DanmakuFormat::Srt => "srt",
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct DanmakuOption {
     pub duration: f64,
@@ -32,6 +52,15 @@ pub struct DanmakuOption {
     pub outline: f64,
     /// 时间轴偏移
     pub time_offset: f64,
+    /// 每秒最多保留的弹幕数量，超出时保留该秒内最早出现的弹幕
+    #[serde(default)]
+    pub max_per_second: Option<u32>,
+    /// 仅保留出现时间（秒）位于该区间内的弹幕
+    #[serde(default)]
+    pub time_window: Option<(f64, f64)>,
+    /// 弹幕文件的保存格式
+    #[serde(default)]
+    pub danmaku_format: DanmakuFormat,
 }
 
 impl Default for DanmakuOption {
@@ -49,10 +78,46 @@ impl Default for DanmakuOption {
             bold: true,
             outline: 0.8,
             time_offset: 0.0,
+            max_per_second: None,
+            time_window: None,
+            danmaku_format: DanmakuFormat::default(),
         }
     }
 }
 
+impl DanmakuOption {
+    /// 时间区间的起止时间需为非负数，且起始时间小于结束时间
+    pub fn is_time_window_valid(&self) -> bool {
+        self.time_window
+            .is_none_or(|(start, end)| start.is_finite() && end.is_finite() && start >= 0.0 && start < end)
+    }
+
+    /// 按时间区间与每秒数量上限过滤弹幕，传入的弹幕需已按出现时间排序
+    pub fn filter(&self, danmaku: Vec<Danmu>) -> Vec<Danmu> {
+        let mut current_second = None;
+        let mut count = 0;
+        danmaku
+            .into_iter()
+            .filter(|danmu| {
+                self.time_window
+                    .is_none_or(|(start, end)| (start..=end).contains(&danmu.timeline_s))
+            })
+            .filter(|danmu| {
+                let Some(max_per_second) = self.max_per_second else {
+                    return true;
+                };
+                let second = danmu.timeline_s.floor() as i64;
+                if current_second != Some(second) {
+                    current_second = Some(second);
+                    count = 0;
+                }
+                count += 1;
+                count <= max_per_second
+            })
+            .collect()
+    }
+}
+
 #[derive(Clone)]
 pub struct CanvasConfig<'a> {
     pub width: u64,
@@ -170,3 +235,74 @@ impl<'a> Canvas<'a> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn danmaku(timelines: &[f64]) -> Vec<Danmu> {
+        timelines
+            .iter()
+            .enumerate()
+            .map(|(idx, &timeline_s)| Danmu {
+                timeline_s,
+                content: idx.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_danmaku() {
+        let timelines = [0.1, 0.2, 0.3, 0.9, 1.0, 1.5, 2.2, 2.3, 2.4, 2.5, 5.0];
+        let option = DanmakuOption {
+            max_per_second: Some(2),
+            ..Default::default()
+        };
+        let filtered = option.filter(danmaku(&timelines));
+        // 每秒最多保留 2 条，且保留的是该秒内最早出现的弹幕
+        assert_eq!(
+            filtered.iter().map(|d| d.timeline_s).collect::<Vec<_>>(),
+            [0.1, 0.2, 1.0, 1.5, 2.2, 2.3, 5.0]
+        );
+        for second in 0..6 {
+            assert!(
+                filtered
+                    .iter()
+                    .filter(|d| d.timeline_s.floor() as i64 == second)
+                    .count()
+                    <= 2
+            );
+        }
+        let option = DanmakuOption {
+            max_per_second: Some(1),
+            time_window: Some((1.0, 2.4)),
+            ..Default::default()
+        };
+        assert_eq!(
+            option
+                .filter(danmaku(&timelines))
+                .iter()
+                .map(|d| d.timeline_s)
+                .collect::<Vec<_>>(),
+            [1.0, 2.2]
+        );
+        assert_eq!(
+            DanmakuOption::default().filter(danmaku(&timelines)).len(),
+            timelines.len()
+        );
+    }
+
+    #[test]
+    fn test_time_window_valid() {
+        let option = |time_window| DanmakuOption {
+            time_window,
+            ..Default::default()
+        };
+        assert!(option(None).is_time_window_valid());
+        assert!(option(Some((0.0, 60.0))).is_time_window_valid());
+        assert!(!option(Some((60.0, 60.0))).is_time_window_valid());
+        assert!(!option(Some((-1.0, 60.0))).is_time_window_valid());
+        assert!(!option(Some((0.0, f64::NAN))).is_time_window_valid());
+    }
+}
@@ -1,12 +1,17 @@
+use std::fmt::Write;
 use std::path::PathBuf;
 
 use anyhow::Result;
 use tokio::fs::{self, File};
 
 use crate::bilibili::danmaku::canvas::CanvasConfig;
-use crate::bilibili::danmaku::{AssWriter, Danmu};
+use crate::bilibili::danmaku::{AssWriter, DanmakuFormat, Danmu};
+use crate::bilibili::subtitle::format_time;
 use crate::bilibili::{DanmakuOption, PageInfo};
 
+/// 导出为 srt 时每条弹幕的显示时长（秒）
+const SRT_CUE_DURATION: f64 = 3.0;
+
 pub struct DanmakuWriter<'a> {
     page: &'a PageInfo,
     danmaku: Vec<Danmu>,
@@ -21,11 +26,16 @@ impl<'a> DanmakuWriter<'a> {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).await?;
         }
+        if danmaku_option.danmaku_format == DanmakuFormat::Srt {
+            let content = to_srt(&danmaku_option.filter(self.danmaku), danmaku_option.time_offset);
+            fs::write(path, content).await?;
+            return Ok(());
+        }
         let canvas_config = CanvasConfig::new(danmaku_option, self.page);
         let mut writer =
             AssWriter::construct(File::create(path).await?, self.page.name.clone(), canvas_config.clone()).await?;
         let mut canvas = canvas_config.canvas();
-        for danmuku in self.danmaku {
+        for danmuku in danmaku_option.filter(self.danmaku) {
             if let Some(drawable) = canvas.draw(danmuku)? {
                 writer.write(drawable).await?;
             }
@@ -34,3 +44,45 @@ impl<'a> DanmakuWriter<'a> {
         Ok(())
     }
 }
+
+/// 将弹幕转换为依次显示的 srt 字幕，每条弹幕显示固定的时长
+fn to_srt(danmaku: &[Danmu], time_offset: f64) -> String {
+    let mut output = String::new();
+    for (idx, danmu) in danmaku.iter().enumerate() {
+        let start = (danmu.timeline_s + time_offset).max(0.0);
+        let _ = writeln!(
+            output,
+            "{}\n{} --> {}\n{}\n",
+            idx + 1,
+            format_time(start),
+            format_time(start + SRT_CUE_DURATION),
+            danmu.content
+        );
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_danmaku_to_srt() {
+        let danmaku = [(1.5, "第一条"), (1.5, "第二条"), (62.0, "第三条")]
+            .into_iter()
+            .map(|(timeline_s, content)| Danmu {
+                timeline_s,
+                content: content.to_owned(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            to_srt(&danmaku, 0.0),
+            "1\n00:00:01,500 --> 00:00:04,500\n第一条\n\n\
+             2\n00:00:01,500 --> 00:00:04,500\n第二条\n\n\
+             3\n00:01:02,000 --> 00:01:05,000\n第三条\n\n"
+        );
+        // 时间轴偏移后不会出现负数的时间
+        assert!(to_srt(&danmaku[..1], -2.0).starts_with("1\n00:00:00,000 --> 00:00:03,000\n"));
+    }
+}
@@ -17,3 +17,15 @@ impl BiliError {
         matches!(self, BiliError::RiskControlOccurred(_) | BiliError::VideoStreamsEmpty)
     }
 }
+
+/// 判断错误是否由风控导致，除了接口返回的风控错误外，HTTP 412 也是 B 站触发风控时常见的响应
+pub fn is_risk_control_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<BiliError>()
+            .is_some_and(BiliError::is_risk_control_related)
+            || cause
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(|e| e.status() == Some(reqwest::StatusCode::PRECONDITION_FAILED))
+    })
+}
@@ -1,27 +1,30 @@
 use std::borrow::Cow;
 use std::sync::Arc;
 
-pub use analyzer::{BestStream, FilterOption};
+pub use analyzer::{AudioQuality, BestStream, FilterOption};
 use anyhow::{Context, Result, bail, ensure};
 use arc_swap::ArcSwapOption;
+pub use chapter::{Chapter, chapters_to_ffmetadata};
 use chrono::serde::ts_seconds;
 use chrono::{DateTime, Utc};
 pub use client::{BiliClient, Client};
 pub use collection::{Collection, CollectionItem, CollectionType};
-pub use credential::{Credential, PollStatus, Qrcode};
-pub use danmaku::DanmakuOption;
+pub use credential::{Credential, CredentialStatus, PollStatus, Qrcode, RefreshHosts};
+pub use danmaku::{DanmakuFormat, DanmakuOption};
 pub use dynamic::Dynamic;
-pub use error::BiliError;
+pub use error::{BiliError, is_risk_control_error};
 pub use favorite_list::FavoriteList;
 use favorite_list::Upper;
 pub use me::Me;
 use once_cell::sync::Lazy;
 use reqwest::RequestBuilder;
 pub use submission::Submission;
+pub use subtitle::{SubTitle, SubtitleFormat};
 pub use video::{Dimension, PageInfo, Video};
 pub use watch_later::WatchLater;
 
 mod analyzer;
+mod chapter;
 mod client;
 mod collection;
 mod credential;
@@ -95,6 +98,29 @@ fn sign_request(req: &mut reqwest::Request, mixin_key: &str, timestamp: i64) ->
     Ok(())
 }
 
+/// 视频详情中的权限信息，用于判断视频是否需要付费观看
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct VideoRights {
+    /// 付费合集中的视频
+    #[serde(default)]
+    pub arc_pay: i32,
+    /// 单独付费的视频
+    #[serde(default)]
+    pub ugc_pay: i32,
+    /// 付费视频处于限时免费
+    #[serde(default)]
+    pub free_watch: i32,
+}
+
+/// 视频详情中的统计数据，接口未返回时为 None
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct VideoStat {
+    /// 播放量
+    pub view: Option<u64>,
+    /// 点赞数
+    pub like: Option<u64>,
+}
+
 #[derive(Debug, serde::Deserialize)]
 #[serde(untagged)]
 /// 注意此处的顺序是有要求的，因为对于 untagged 的 enum 来说，serde 会按照顺序匹配
@@ -118,6 +144,10 @@ pub enum VideoInfo {
         is_upower_exclusive: bool,
         is_upower_play: bool,
         redirect_url: Option<String>,
+        #[serde(default)]
+        rights: VideoRights,
+        #[serde(default)]
+        stat: VideoStat,
         pages: Vec<PageInfo>,
         state: i32,
     },
@@ -165,6 +195,9 @@ pub enum VideoInfo {
         ctime: DateTime<Utc>,
         #[serde(rename = "pubdate", with = "ts_seconds")]
         pubtime: DateTime<Utc>,
+        /// 视频在合集中的位置（从 1 开始），由分页信息计算得到
+        #[serde(skip)]
+        order: Option<i32>,
     },
     // 从用户投稿接口获取的视频信息
     Submission {
@@ -201,14 +234,15 @@ mod tests {
     use crate::config::VersionedConfig;
     use crate::database::setup_database;
     use crate::utils::init_logger;
+    use crate::utils::rotating_file::LogRotation;
 
     #[ignore = "only for manual test"]
     #[tokio::test]
     async fn test_video_info_type() -> Result<()> {
         VersionedConfig::init_for_test(&setup_database(Path::new("./test.sqlite")).await?).await?;
         let credential = &VersionedConfig::get().read().credential;
-        init_logger("None,bili_sync=debug", None);
-        let bili_client = BiliClient::new();
+        init_logger("None,bili_sync=debug", None, LogRotation::default());
+        let bili_client = BiliClient::new()?;
         // 请求 UP 主视频必须要获取 mixin key，使用 key 计算请求参数的签名，否则直接提示权限不足返回空
         let mixin_key = bili_client
             .wbi_img(credential)
@@ -281,7 +315,7 @@ mod tests {
     async fn test_subtitle_parse() -> Result<()> {
         VersionedConfig::init_for_test(&setup_database(Path::new("./test.sqlite")).await?).await?;
         let credential = &VersionedConfig::get().read().credential;
-        let bili_client = BiliClient::new();
+        let bili_client = BiliClient::new()?;
         let mixin_key = bili_client
             .wbi_img(credential)
             .await?
@@ -291,7 +325,7 @@ mod tests {
         let video = Video::new(&bili_client, "BV1gLfnY8E6D".to_string(), &credential);
         let pages = video.get_pages().await?;
         println!("pages: {:?}", pages);
-        let subtitles = video.get_subtitles(&pages[0]).await?;
+        let subtitles = video.get_subtitles(&pages[0], &[]).await?;
         for subtitle in subtitles {
             println!(
                 "{}: {}",
@@ -307,7 +341,7 @@ mod tests {
     async fn test_upower_parse() -> Result<()> {
         VersionedConfig::init_for_test(&setup_database(Path::new("./test.sqlite")).await?).await?;
         let credential = &VersionedConfig::get().read().credential;
-        let bili_client = BiliClient::new();
+        let bili_client = BiliClient::new()?;
         let mixin_key = bili_client
             .wbi_img(credential)
             .await?
@@ -340,7 +374,7 @@ mod tests {
     async fn test_ep_parse() -> Result<()> {
         VersionedConfig::init_for_test(&setup_database(Path::new("./test.sqlite")).await?).await?;
         let credential = &VersionedConfig::get().read().credential;
-        let bili_client = BiliClient::new();
+        let bili_client = BiliClient::new()?;
         let mixin_key = bili_client
             .wbi_img(credential)
             .await?
@@ -1,4 +1,26 @@
-use std::fmt::Display;
+use std::fmt::{Display, Write};
+
+use serde::{Deserialize, Serialize};
+
+/// 字幕文件的保存格式
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    #[default]
+    Srt,
+    Ass,
+    Vtt,
+}
+
+impl SubtitleFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::Ass => "ass",
+            SubtitleFormat::Vtt => "vtt",
+        }
+    }
+}
 
 #[derive(Debug, serde::Deserialize)]
 pub struct SubTitlesInfo {
@@ -26,6 +48,17 @@ pub struct SubTitleItem {
     content: String,
 }
 
+impl SubTitlesInfo {
+    /// 选出需要下载的字幕，忽略 AI 生成的字幕；指定了语言时仅保留这些语言，视频未提供的语言直接跳过
+    pub fn select(self, languages: &[String]) -> Vec<SubTitleInfo> {
+        self.subtitles
+            .into_iter()
+            .filter(|v| !v.is_ai_sub())
+            .filter(|v| languages.is_empty() || languages.contains(&v.lan))
+            .collect()
+    }
+}
+
 impl SubTitleInfo {
     pub fn is_ai_sub(&self) -> bool {
         // ai： aisubtitle.hdslb.com/bfs/ai_subtitle/xxxx
@@ -34,6 +67,59 @@ impl SubTitleInfo {
     }
 }
 
+impl SubTitleBody {
+    /// 将字幕转换为指定格式的文本
+    pub fn render(&self, format: SubtitleFormat) -> String {
+        match format {
+            SubtitleFormat::Srt => self.to_string(),
+            SubtitleFormat::Ass => self.to_ass(),
+            SubtitleFormat::Vtt => self.to_vtt(),
+        }
+    }
+
+    fn to_vtt(&self) -> String {
+        let mut output = String::from("WEBVTT\n\n");
+        for item in &self.0 {
+            let _ = writeln!(
+                output,
+                "{} --> {}\n{}\n",
+                format_time(item.from).replace(',', "."),
+                format_time(item.to).replace(',', "."),
+                item.content
+            );
+        }
+        output
+    }
+
+    fn to_ass(&self) -> String {
+        let mut output = String::from(ASS_HEADER);
+        for item in &self.0 {
+            let _ = writeln!(
+                output,
+                "Dialogue: 0,{},{},Default,,0,0,0,,{}",
+                format_ass_time(item.from),
+                format_ass_time(item.to),
+                item.content.replace('\n', "\\N")
+            );
+        }
+        output
+    }
+}
+
+const ASS_HEADER: &str = "[Script Info]
+ScriptType: v4.00+
+PlayResX: 1920
+PlayResY: 1080
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, \
+StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,sans-serif,64,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,0,0,0,0,100,100,0,0,1,3,0,2,40,40,40,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+";
+
 impl Display for SubTitleBody {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for (idx, item) in self.0.iter().enumerate() {
@@ -46,7 +132,7 @@ impl Display for SubTitleBody {
     }
 }
 
-fn format_time(time: f64) -> String {
+pub(crate) fn format_time(time: f64) -> String {
     let (second, millisecond) = (time.trunc(), (time.fract() * 1e3) as u32);
     let (hour, minute, second) = (
         (second / 3600.0) as u32,
@@ -56,6 +142,18 @@ fn format_time(time: f64) -> String {
     format!("{:02}:{:02}:{:02},{:03}", hour, minute, second, millisecond)
 }
 
+/// ass 的时间格式为 H:MM:SS.cc，精确到百分之一秒
+fn format_ass_time(time: f64) -> String {
+    let centisecond = (time * 100.0).round() as u64;
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        centisecond / 360000,
+        centisecond / 6000 % 60,
+        centisecond / 100 % 60,
+        centisecond % 100
+    )
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -72,4 +170,53 @@ mod tests {
             assert_eq!(super::format_time(*time), *expect);
         }
     }
+
+    #[test]
+    fn test_select_subtitles() {
+        use super::*;
+
+        let response = serde_json::json!({
+            "allow_submit": false,
+            "subtitles": [
+                {"lan": "zh-CN", "lan_doc": "中文（中国）", "subtitle_url": "//aisubtitle.hdslb.com/bfs/subtitle/1.json"},
+                {"lan": "en", "lan_doc": "English", "subtitle_url": "//aisubtitle.hdslb.com/bfs/subtitle/2.json"},
+                {"lan": "ai-zh", "lan_doc": "中文（自动生成）", "subtitle_url": "//aisubtitle.hdslb.com/bfs/ai_subtitle/3.json"}
+            ]
+        });
+        let languages = |subtitles: Vec<SubTitleInfo>| subtitles.into_iter().map(|v| v.lan).collect::<Vec<_>>();
+        let info = || serde_json::from_value::<SubTitlesInfo>(response.clone()).unwrap();
+        // 未指定语言时下载全部非 AI 字幕
+        assert_eq!(languages(info().select(&[])), vec!["zh-CN", "en"]);
+        // 视频未提供的语言直接跳过
+        assert_eq!(
+            languages(info().select(&["en".to_owned(), "ja".to_owned()])),
+            vec!["en"]
+        );
+        assert_eq!(
+            languages(info().select(&["zh-CN".to_owned(), "en".to_owned()])),
+            vec!["zh-CN", "en"]
+        );
+    }
+
+    #[test]
+    fn test_render_subtitle() {
+        use super::*;
+
+        let body: SubTitleBody = serde_json::from_value(serde_json::json!([
+            {"from": 0.5, "to": 2.0, "content": "第一句"},
+            {"from": 3723.25, "to": 3725.0, "content": "第二句\n换行"}
+        ]))
+        .unwrap();
+        assert_eq!(
+            body.render(SubtitleFormat::Vtt),
+            "WEBVTT\n\n00:00:00.500 --> 00:00:02.000\n第一句\n\n01:02:03.250 --> 01:02:05.000\n第二句\n换行\n\n"
+        );
+        assert_eq!(body.render(SubtitleFormat::Srt), body.to_string());
+        let ass = body.render(SubtitleFormat::Ass);
+        assert!(ass.starts_with("[Script Info]"));
+        assert!(ass.ends_with(
+            "Dialogue: 0,0:00:00.50,0:00:02.00,Default,,0,0,0,,第一句\n\
+             Dialogue: 0,1:02:03.25,1:02:05.00,Default,,0,0,0,,第二句\\N换行\n"
+        ));
+    }
 }
@@ -5,6 +5,7 @@ use prost::Message;
 use reqwest::Method;
 
 use crate::bilibili::analyzer::PageAnalyzer;
+use crate::bilibili::chapter::{Chapter, parse_chapters};
 use crate::bilibili::client::BiliClient;
 use crate::bilibili::danmaku::{DanmakuElem, DanmakuWriter, DmSegMobileReply};
 use crate::bilibili::subtitle::{SubTitle, SubTitleBody, SubTitleInfo, SubTitlesInfo};
@@ -171,7 +172,8 @@ impl<'a> Video<'a> {
         Ok(PageAnalyzer::new(res["data"].take()))
     }
 
-    pub async fn get_subtitles(&self, page: &PageInfo) -> Result<Vec<SubTitle>> {
+    /// 获取分页的字幕，languages 为空时获取全部语言
+    pub async fn get_subtitles(&self, page: &PageInfo, languages: &[String]) -> Result<Vec<SubTitle>> {
         let mut res = self
             .client
             .request(Method::GET, "https://api.bilibili.com/x/player/wbi/v2", self.credential)
@@ -189,9 +191,8 @@ impl<'a> Video<'a> {
         match serde_json::from_value::<Option<SubTitlesInfo>>(res["data"]["subtitle"].take())? {
             Some(subtitles_info) => {
                 let tasks = subtitles_info
-                    .subtitles
+                    .select(languages)
                     .into_iter()
-                    .filter(|v| !v.is_ai_sub())
                     .map(|v| self.get_subtitle(v))
                     .collect::<FuturesUnordered<_>>();
                 tasks.try_collect().await
@@ -200,6 +201,24 @@ impl<'a> Video<'a> {
         }
     }
 
+    /// 获取分页的“看点”章节，不存在看点时返回空列表
+    pub async fn get_chapters(&self, page: &PageInfo) -> Result<Vec<Chapter>> {
+        let mut res = self
+            .client
+            .request(Method::GET, "https://api.bilibili.com/x/player/wbi/v2", self.credential)
+            .await
+            .query(&[("bvid", self.bvid.as_str())])
+            .query(&[("cid", page.cid)])
+            .wbi_sign(MIXIN_KEY.load().as_deref())?
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<serde_json::Value>()
+            .await?
+            .validate()?;
+        parse_chapters(res["data"]["view_points"].take())
+    }
+
     async fn get_subtitle(&self, info: SubTitleInfo) -> Result<SubTitle> {
         let mut res = self
             .client
@@ -1,4 +1,4 @@
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result, anyhow, ensure};
 use async_stream::try_stream;
 use futures::Stream;
 use serde_json::Value;
@@ -30,6 +30,26 @@ impl<'a> WatchLater<'a> {
             .validate()
     }
 
+    /// 将视频从稍后再看列表中移除
+    pub async fn remove_video(&self, bvid: &str) -> Result<()> {
+        let aid = bvid_to_aid(bvid)?;
+        self.client
+            .request(
+                reqwest::Method::POST,
+                "https://api.bilibili.com/x/v2/history/toview/del",
+                self.credential,
+            )
+            .await
+            .form(&[("aid", aid.to_string()), ("csrf", self.credential.bili_jct.clone())])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<serde_json::Value>()
+            .await?
+            .validate()?;
+        Ok(())
+    }
+
     pub fn into_video_stream(self) -> impl Stream<Item = Result<VideoInfo>> + 'a {
         try_stream! {
             let mut videos = self
@@ -48,3 +68,36 @@ impl<'a> WatchLater<'a> {
         }
     }
 }
+
+/// 将 bvid 转换为 aid，稍后再看的移除接口只接受 aid
+fn bvid_to_aid(bvid: &str) -> Result<u64> {
+    const TABLE: &[u8] = b"FcwAPNKTMug3GV5Lj7EJnHpWsx4tb8haYeviqBz6rkCy12mUSDQX9RdoZf";
+    const XOR_CODE: u64 = 23442827791579;
+    const MASK_CODE: u64 = 2251799813685247;
+    let mut chars = bvid.as_bytes().to_vec();
+    ensure!(chars.len() == 12 && chars.starts_with(b"BV1"), "invalid bvid: {}", bvid);
+    chars.swap(3, 9);
+    chars.swap(4, 7);
+    let mut tmp = 0u64;
+    for c in &chars[3..] {
+        let index = TABLE
+            .iter()
+            .position(|t| t == c)
+            .with_context(|| format!("invalid bvid: {}", bvid))?;
+        tmp = tmp * TABLE.len() as u64 + index as u64;
+    }
+    Ok((tmp & MASK_CODE) ^ XOR_CODE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bvid_to_aid() {
+        assert_eq!(bvid_to_aid("BV17x411w7KC").unwrap(), 170001);
+        assert_eq!(bvid_to_aid("BV1L9Uoa9EUx").unwrap(), 111298867365120);
+        assert!(bvid_to_aid("BV1test").is_err());
+        assert!(bvid_to_aid("BV1test0000l").is_err());
+    }
+}
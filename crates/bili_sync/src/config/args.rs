@@ -16,6 +16,21 @@ pub struct Args {
 
     #[arg(short, long, env = "DISABLE_CREDENTIAL_REFRESH")]
     pub disable_credential_refresh: bool,
+
+    #[arg(long, default_value_t = 256, env = "LOG_BUFFER_SIZE")]
+    pub log_buffer_size: usize,
+
+    /// 单个日志文件的最大大小（MB），超出后滚动到新文件，为 0 时不滚动
+    #[arg(long, default_value_t = 10, env = "LOG_MAX_SIZE_MB")]
+    pub log_max_size_mb: u64,
+
+    /// 滚动后保留的历史日志文件数量，更早的日志文件会被删除
+    #[arg(long, default_value_t = 5, env = "LOG_MAX_FILES")]
+    pub log_max_files: usize,
+
+    /// 收到终止信号后等待正在下载的视频与分页完成的最长时间（秒），超时后强制终止
+    #[arg(long, default_value_t = 300, env = "SHUTDOWN_TIMEOUT_SECS")]
+    pub shutdown_timeout_secs: u64,
 }
 
 mod built_info {
@@ -1,36 +1,77 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, LazyLock};
 
 use anyhow::{Result, bail};
+use bili_sync_entity::rule::TagRule;
+use chrono::{DateTime, TimeZone, Timelike, Weekday};
 use croner::parser::CronParser;
 use sea_orm::DatabaseConnection;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use crate::bilibili::{Credential, DanmakuOption, FilterOption};
+use crate::bilibili::{Credential, DanmakuOption, FilterOption, SubtitleFormat};
 use crate::config::default::{
-    default_auth_token, default_bind_address, default_collection_path, default_daily_summary_cron, default_enable_notification_quiet_hours,
-    default_enable_video_source_on_subscribe, default_favorite_path, default_notification_interval, default_notify_daily_summary,
-    default_notify_new_videos, default_quiet_hours_end, default_quiet_hours_start, default_submission_path, default_time_format,
+    default_audio_normalize_target_lufs, default_auth_token, default_bind_address, default_collection_path,
+    default_connect_timeout_secs, default_cover_quality, default_daily_summary_cron, default_download_referer,
+    default_enable_notification_quiet_hours, default_enable_video_source_on_subscribe, default_favorite_path,
+    default_max_auto_retries, default_notification_interval, default_notification_queue_capacity,
+    default_notify_daily_summary, default_notify_new_videos, default_post_download_command_concurrency,
+    default_post_download_command_timeout_secs, default_quiet_hours_end, default_quiet_hours_start,
+    default_request_timeout_secs, default_risk_control_cooldown_max, default_season_template, default_startup_jitter,
+    default_submission_path, default_task_event_retention_days, default_time_format,
 };
-use crate::config::item::{ConcurrentLimit, NFOTimeType, SkipOption, Trigger};
-use crate::notifier::Notifier;
-use crate::utils::model::{load_db_config, save_db_config};
+use crate::config::handlebar::{validate_nfo_template, validate_season_template};
+use crate::config::item::{
+    ConcurrentLimit, CoverFormat, DedupStrategy, DownloaderBackend, NFOMode, NFOTemplate, NFOTimeType, PosterSource,
+    ScheduleWindow, SkipOption, Trigger, VideoContainer,
+};
+use crate::notifier::{NotificationEvent, Notifier};
+use crate::utils::model::{load_config_by_version, load_db_config, save_db_config};
 
 pub static CONFIG_DIR: LazyLock<PathBuf> =
     LazyLock::new(|| dirs::config_dir().expect("No config path found").join("bili-sync"));
 
+/// 全局共享的 Cron 解析器，格式为：秒 分 时 日 月 周，避免每次解析都重新构造
+pub static CRON_PARSER: LazyLock<CronParser> = LazyLock::new(|| {
+    CronParser::builder()
+        .seconds(croner::parser::Seconds::Required)
+        .dom_and_dow(true)
+        .build()
+});
+
 #[derive(Serialize, Deserialize, Validate, Clone)]
 pub struct Config {
     pub auth_token: String,
     pub bind_address: String,
     pub credential: Credential,
+    /// 备用账号的凭据，扫描时当前账号触发风控会依次切换到下一个账号
+    #[serde(default)]
+    pub backup_credentials: Vec<Credential>,
     pub filter_option: FilterOption,
     pub danmaku_option: DanmakuOption,
     #[serde(default)]
     pub skip_option: SkipOption,
     pub video_name: String,
     pub page_name: String,
+    #[serde(default = "default_season_template")]
+    pub season_template: String, // 多页视频中分页所属的季，渲染结果需为季序号，如 {{#if (gt pid 10)}}2{{else}}1{{/if}}
+    #[serde(default)]
+    pub container: VideoContainer, // 视频文件的封装格式，修改后仅对新下载的分页生效
+    #[serde(default)]
+    pub dedup_strategy: DedupStrategy, // 视频已被其它视频源下载过时，通过链接复用已有文件而不是重新下载
+    #[serde(default)]
+    pub subtitle_format: SubtitleFormat, // 字幕文件的保存格式
+    #[serde(default)]
+    pub subtitle_languages: Vec<String>, // 需要下载的字幕语言，如 zh-CN、en，为空时下载全部语言
+    #[serde(default)]
+    pub embed_subtitles: bool, // 视频与字幕下载完成后通过 ffmpeg 将字幕封装进视频文件
+    #[serde(default)]
+    pub remove_embedded_subtitle_files: bool, // 字幕成功封装进视频文件后删除外挂的字幕文件
+    #[serde(default)]
+    pub audio_normalize: bool, // 仅下载音频时，下载完成后通过 ffmpeg loudnorm 统一音频的响度
+    #[serde(default = "default_audio_normalize_target_lufs")]
+    pub audio_normalize_target_lufs: f64, // 响度标准化的目标综合响度（LUFS），取值 -70 ~ -5
     #[serde(default)]
     pub notifiers: Option<Arc<Vec<Notifier>>>,
     #[serde(default = "default_favorite_path")]
@@ -40,30 +81,112 @@ pub struct Config {
     #[serde(default = "default_submission_path")]
     pub submission_default_path: String,
     pub interval: Trigger,
+    #[serde(default = "default_startup_jitter")]
+    pub startup_jitter: u64, // 启动后首次执行下载任务前的随机延迟上限（秒）
+    #[serde(default)]
+    pub risk_control_cooldown: u64, // 触发风控后暂停下载任务的时长（秒），0 表示不暂停
+    #[serde(default = "default_risk_control_cooldown_max")]
+    pub risk_control_cooldown_max: u64, // 连续触发风控时暂停时长逐次翻倍，此为暂停时长的上限（秒）
+    #[serde(default)]
+    pub proxy: Option<String>, // Bilibili 请求与下载使用的代理，支持 http、https 与 socks5（aria2 后端仅支持 http），修改后需重启生效
+    #[serde(default)]
+    pub notifier_proxy: Option<String>, // 发送通知使用的代理，为空时与 proxy 保持一致，修改后需重启生效
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64, // 建立连接的超时时间（秒），修改后需重启生效
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64, // 请求超时时间（秒），连续该时长未收到数据即视为超时，修改后需重启生效
     pub upper_path: PathBuf,
     pub nfo_time_type: NFOTimeType,
+    #[serde(default)]
+    pub nfo_mode: NFOMode, // 单页视频生成的 NFO 类型，多页视频始终按剧集生成
+    #[serde(default)]
+    pub nfo_include_uploader_actor: bool, // 在视频 NFO 中以 Uploader 角色写入 UP 主，头像指向本地下载的 folder.jpg
+    #[serde(default)]
+    pub collection_episode_order: bool, // 合集中的单页视频按照其在合集中的位置编号剧集，而非分页序号
+    #[serde(default)]
+    pub poster_source: PosterSource, // 封面的来源，可选择使用视频封面或截取视频的第一帧
+    #[serde(default)]
+    pub cover_format: CoverFormat, // 封面、横幅与 UP 主头像的保存格式，非 jpg 时通过 ffmpeg 转换
+    #[serde(default = "default_cover_quality")]
+    pub cover_quality: u8, // 转换图片格式时的质量，取值 1 ~ 100
+    #[serde(default)]
+    pub nfo_template: NFOTemplate,
+    #[serde(default)]
+    pub tag_rules: Vec<TagRule>, // 自动标签规则，命中的标签会写入 NFO 并保存到数据库
+    /// 视为可忽略的 B 站接口错误码，命中时任务不标记为失败，下次执行时重试
+    /// 为空时保持默认行为：仅文件权限错误与请求超时、响应读取失败等网络错误会被忽略
+    #[serde(default)]
+    pub ignored_error_codes: Vec<i64>,
+    /// 子任务自动重试的次数上限，失败次数达到上限后标记为最终失败，不再在后续的扫描中重试，需要手动重置
+    #[serde(default = "default_max_auto_retries")]
+    pub max_auto_retries: u32,
     pub concurrent_limit: ConcurrentLimit,
+    /// 下载的总速率限制（字节/秒），为空时不限速
+    #[serde(default)]
+    pub download_rate_limit_bytes: Option<u64>,
+    /// 按时间段调整的并发与限速设置，命中多个时间段时使用第一个，不在任何时间段内时使用上面的全局配置
+    #[serde(default)]
+    pub schedule: Vec<ScheduleWindow>,
+    /// 下载视频、音频与图片时使用的下载后端，默认由 bili-sync 自身下载
+    #[serde(default)]
+    pub downloader_backend: DownloaderBackend,
+    /// 下载视频、音频与图片时使用的 User-Agent，为空时使用随机生成的 Chrome User-Agent
+    #[serde(default)]
+    pub download_user_agent: Option<String>,
+    /// 下载视频、音频与图片时使用的 Referer
+    #[serde(default = "default_download_referer")]
+    pub download_referer: String,
     pub time_format: String,
     pub cdn_sorting: bool,
     #[serde(default)]
     pub enable_cover_background: bool,
+    /// 是否在每个视频源的根目录下维护 manifest.json，记录已下载视频的路径、大小与校验和
+    #[serde(default)]
+    pub write_source_manifest: bool,
+    /// 稍后再看中的视频全部下载成功后，是否自动将其从 B 站的稍后再看列表中移除
+    #[serde(default)]
+    pub watch_later_auto_remove: bool,
+    /// 视频全部下载成功后在后台执行的命令，通过系统 shell 执行
+    /// 视频信息通过环境变量 BILI_VIDEO_DIR、BILI_BVID、BILI_TITLE、BILI_UPPER_NAME 传入
+    #[serde(default)]
+    pub post_download_command: Option<String>,
+    /// 下载后命令的超时时间（秒），超时后结束命令进程
+    #[serde(default = "default_post_download_command_timeout_secs")]
+    pub post_download_command_timeout_secs: u64,
+    /// 同时执行的下载后命令数量上限，超出的命令排队等待
+    #[serde(default = "default_post_download_command_concurrency")]
+    pub post_download_command_concurrency: usize,
     /// 订阅收藏夹 / 合集 / UP 投稿时，是否自动将对应视频源标记为启用
     #[serde(default = "default_enable_video_source_on_subscribe")]
     pub enable_video_source_on_subscribe: bool,
     #[serde(default = "default_notify_new_videos")]
     pub notify_new_videos: bool,
+    #[serde(default)]
+    pub notify_invalid_videos: bool, // 视频被删除或失效时发送通知
+    #[serde(default)]
+    pub notify_paid_videos: bool, // 扫描时识别到收费视频并跳过下载时发送通知
     #[serde(default = "default_notify_daily_summary")]
     pub notify_daily_summary: bool,
+    /// 自定义的通知消息模板，键为事件名称（message、error、new_videos、invalid_video、paid_video、daily_summary）
+    /// 使用 handlebars 语法渲染，未配置模板的事件使用内置的消息，error 未配置时沿用 message 的模板
+    #[serde(default)]
+    pub message_templates: HashMap<String, String>,
     #[serde(default = "default_daily_summary_cron")]
     pub daily_summary_cron: String, // 每日汇总任务的 cron 表达式（格式：秒 分 时 日 月 周）
     #[serde(default = "default_notification_interval")]
     pub notification_interval: u64, // 消息队列等待时间（秒）
+    #[serde(default = "default_notification_queue_capacity")]
+    pub notification_queue_capacity: usize, // 消息队列最多积压的消息数量，已满时优先丢弃最早的例行通知
     #[serde(default = "default_enable_notification_quiet_hours")]
     pub enable_notification_quiet_hours: bool, // 是否开启通知静默时间段
     #[serde(default = "default_quiet_hours_start")]
     pub quiet_hours_start: u8, // 静默开始时间（小时，0-23）
     #[serde(default = "default_quiet_hours_end")]
     pub quiet_hours_end: u8, // 静默结束时间（小时，0-23）
+    #[serde(default)]
+    pub quiet_days: Option<Vec<Weekday>>, // 全天静默的星期，例如周末
+    #[serde(default = "default_task_event_retention_days")]
+    pub task_event_retention_days: u64, // 任务状态变更记录的保留天数，为 0 时不清理
     pub version: u64,
 }
 
@@ -73,7 +196,22 @@ impl Config {
     }
 
     pub async fn save_to_database(&self, connection: &DatabaseConnection) -> Result<()> {
-        save_db_config(self, connection).await
+        save_db_config(self, true, connection).await
+    }
+
+    /// 保存配置但不记录配置历史，用于凭据刷新这类仅凭据变化的保存
+    pub async fn save_to_database_without_history(&self, connection: &DatabaseConnection) -> Result<()> {
+        save_db_config(self, false, connection).await
+    }
+
+    /// 从配置历史中加载指定版本的配置
+    pub async fn load_version_from_database(version: u64, connection: &DatabaseConnection) -> Result<Option<Self>> {
+        load_config_by_version(version, connection).await
+    }
+
+    /// 依次返回主账号与所有备用账号的凭据
+    pub fn credentials(&self) -> impl Iterator<Item = &Credential> {
+        std::iter::once(&self.credential).chain(self.backup_credentials.iter())
     }
 
     pub fn check(&self) -> Result<()> {
@@ -87,51 +225,186 @@ impl Config {
         if self.page_name.is_empty() {
             errors.push("未设置 page_name 模板");
         }
-        let credential = &self.credential;
-        if credential.sessdata.is_empty()
-            || credential.bili_jct.is_empty()
-            || credential.buvid3.is_empty()
-            || credential.dedeuserid.is_empty()
-            || credential.ac_time_value.is_empty()
-        {
-            errors.push("Credential 信息不完整，请确保填写完整");
+        if validate_season_template(&self.season_template).is_err() {
+            errors.push("season_template 模板的渲染结果不是有效的季序号");
+        }
+        for credential in self.credentials() {
+            if credential.sessdata.is_empty()
+                || credential.bili_jct.is_empty()
+                || credential.buvid3.is_empty()
+                || credential.dedeuserid.is_empty()
+                || credential.ac_time_value.is_empty()
+            {
+                errors.push("Credential 信息不完整，请确保填写完整");
+                break;
+            }
         }
         if !(self.concurrent_limit.video > 0 && self.concurrent_limit.page > 0) {
             errors.push("video 和 page 允许的并发数必须大于 0");
         }
+        if self.concurrent_limit.prefetch == 0 {
+            errors.push("视频详情预取的并发数必须大于 0");
+        }
+        if self.concurrent_limit.source == 0 {
+            errors.push("同时扫描的视频源数量必须大于 0");
+        }
+        if !(1..=4).contains(&self.max_auto_retries) {
+            errors.push("自动重试次数上限必须在 1 到 4 之间");
+        }
+        if !self.filter_option.is_preferred_quality_valid() {
+            errors.push("期望的视频清晰度不是有效的 qn 值");
+        }
+        if !self.filter_option.is_duration_range_valid() {
+            errors.push("视频时长下限不能大于时长上限");
+        }
+        if !self.filter_option.is_title_regex_valid() {
+            errors.push("标题过滤的正则表达式无效");
+        }
+        if !self.danmaku_option.is_time_window_valid() {
+            errors.push("弹幕时间区间无效，起始时间需为非负数且小于结束时间");
+        }
+        if self.danmaku_option.max_per_second == Some(0) {
+            errors.push("每秒最多保留的弹幕数量必须大于 0，如需不限制请留空");
+        }
+        if self.download_rate_limit_bytes == Some(0) {
+            errors.push("下载速率限制必须大于 0，如需关闭限速请留空");
+        }
+        for window in &self.schedule {
+            if CRON_PARSER.parse(&window.cron_window).is_err() {
+                errors.push("时间段的 Cron 表达式无效，正确格式为：秒 分 时 日 月 周");
+            }
+            if let Some(limit) = &window.concurrent_limit
+                && (limit.video == 0 || limit.page == 0 || limit.prefetch == 0 || limit.source == 0)
+            {
+                errors.push("时间段内的并发数必须大于 0");
+            }
+            if window.rate_limit == Some(0) {
+                errors.push("时间段内的下载速率限制必须大于 0，如需沿用全局配置请留空");
+            }
+        }
+        for (template, error) in [
+            (&self.nfo_template.video, "视频的 NFO 模板无法渲染为合法的 XML"),
+            (&self.nfo_template.page, "分页的 NFO 模板无法渲染为合法的 XML"),
+            (&self.nfo_template.upper, "UP 主的 NFO 模板无法渲染为合法的 XML"),
+        ] {
+            if let Some(template) = template
+                && validate_nfo_template(template).is_err()
+            {
+                errors.push(error);
+            }
+        }
+        if self.tag_rules.iter().any(|tag_rule| tag_rule.tag.trim().is_empty()) {
+            errors.push("自动标签规则的标签不能为空");
+        }
+        if self.risk_control_cooldown > 0 && self.risk_control_cooldown_max < self.risk_control_cooldown {
+            errors.push("风控暂停时长的上限不能小于初始的暂停时长");
+        }
         match &self.interval {
             Trigger::Interval(secs) => {
                 if *secs <= 60 {
                     errors.push("下载任务执行间隔时间必须大于 60 秒");
                 }
+                if self.startup_jitter >= *secs {
+                    errors.push("启动随机延迟必须小于下载任务执行间隔时间");
+                }
             }
             Trigger::Cron(cron) => {
-                if CronParser::builder()
-                    .seconds(croner::parser::Seconds::Required)
-                    .dom_and_dow(true)
-                    .build()
-                    .parse(cron)
-                    .is_err()
-                {
+                if CRON_PARSER.parse(cron).is_err() {
                     errors.push("Cron 表达式无效，正确格式为：秒 分 时 日 月 周");
                 }
             }
         };
         // 验证每日汇总任务的 cron 表达式
-        if CronParser::builder()
-            .seconds(croner::parser::Seconds::Required)
-            .dom_and_dow(true)
-            .build()
-            .parse(&self.daily_summary_cron)
-            .is_err()
-        {
+        if CRON_PARSER.parse(&self.daily_summary_cron).is_err() {
             errors.push("每日汇总任务的 Cron 表达式无效，正确格式为：秒 分 时 日 月 周");
         }
+        if let DownloaderBackend::Aria2 {
+            rpc_url,
+            download_dir,
+            local_dir,
+            ..
+        } = &self.downloader_backend
+        {
+            if !reqwest::Url::parse(rpc_url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")) {
+                errors.push("aria2 RPC 地址无效，正确格式如：http://127.0.0.1:6800/jsonrpc");
+            }
+            if local_dir.is_some() && download_dir.is_none() {
+                errors.push("设置 aria2 下载目录的本地挂载路径时需要同时设置 aria2 一侧的下载目录");
+            }
+            // aria2 的 all-proxy 选项仅支持 http 代理
+            if self
+                .proxy
+                .as_deref()
+                .is_some_and(|proxy| !reqwest::Url::parse(proxy).is_ok_and(|url| url.scheme() == "http"))
+            {
+                errors.push("使用 aria2 下载时仅支持 http 代理，正确格式如：http://127.0.0.1:7890");
+            }
+        }
+        if self
+            .download_user_agent
+            .iter()
+            .chain([&self.download_referer])
+            .any(|value| reqwest::header::HeaderValue::from_str(value).is_err())
+        {
+            errors.push("下载使用的 User-Agent 与 Referer 不能包含换行等非法字符");
+        }
+        if !(-70.0..=-5.0).contains(&self.audio_normalize_target_lufs) {
+            errors.push("响度标准化的目标响度必须在 -70 ~ -5 LUFS 之间");
+        }
+        if self.post_download_command_timeout_secs == 0 {
+            errors.push("下载后命令的超时时间必须大于 0");
+        }
+        if self.post_download_command_concurrency == 0 {
+            errors.push("下载后命令的并发数必须大于 0");
+        }
+        if self.connect_timeout_secs == 0 || self.request_timeout_secs == 0 {
+            errors.push("连接超时与请求超时时间必须大于 0");
+        }
+        if !(1..=100).contains(&self.cover_quality) {
+            errors.push("图片转换质量必须在 1 ~ 100 之间");
+        }
+        if self
+            .proxy
+            .as_deref()
+            .is_some_and(|proxy| reqwest::Proxy::all(proxy).is_err())
+        {
+            errors.push("代理地址无效，正确格式如：http://127.0.0.1:7890 或 socks5://127.0.0.1:1080");
+        }
+        if self
+            .notifier_proxy
+            .as_deref()
+            .is_some_and(|proxy| reqwest::Proxy::all(proxy).is_err())
+        {
+            errors.push("通知代理地址无效，正确格式如：http://127.0.0.1:7890 或 socks5://127.0.0.1:1080");
+        }
+        for (event, template) in &self.message_templates {
+            if !NotificationEvent::ALL.iter().any(|e| e.name() == event) {
+                errors.push("通知消息模板的事件名称无效，可选值为：message、error、new_videos、invalid_video、paid_video、daily_summary");
+            } else if handlebars::Template::compile(template).is_err() {
+                errors.push("通知消息模板的语法无效");
+            }
+        }
+        if self.notification_queue_capacity == 0 {
+            errors.push("通知队列的容量必须大于 0");
+        }
         // 验证静默时间段配置
         if self.enable_notification_quiet_hours {
             if self.quiet_hours_start > 23 || self.quiet_hours_end > 23 {
                 errors.push("静默时间段的开始和结束时间必须在 0-23 之间");
             }
+            if let Some(quiet_days) = &self.quiet_days {
+                let mut days = quiet_days
+                    .iter()
+                    .map(|day| day.num_days_from_monday())
+                    .collect::<Vec<_>>();
+                days.sort_unstable();
+                days.dedup();
+                if days.len() != quiet_days.len() {
+                    errors.push("全天静默的星期中存在重复项");
+                } else if days.len() == 7 {
+                    errors.push("全天静默的星期不能包含一周中的每一天，否则通知将永远无法发送");
+                }
+            }
         }
         if !errors.is_empty() {
             bail!(
@@ -146,37 +419,178 @@ impl Config {
     }
 }
 
+impl Config {
+    /// 返回 now 所处时间段生效的配置，不在任何时间段内时返回 None，表示直接使用当前配置
+    pub fn scheduled_at<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Option<Config> {
+        // 时间段按分钟匹配，忽略扫描开始时刻的秒数
+        let now = now.with_second(0)?.with_nanosecond(0)?;
+        let window = self.schedule.iter().find(|window| {
+            CRON_PARSER
+                .parse(&window.cron_window)
+                .is_ok_and(|cron| cron.is_time_matching(&now).unwrap_or(false))
+        })?;
+        let mut config = self.clone();
+        if let Some(concurrent_limit) = &window.concurrent_limit {
+            config.concurrent_limit = concurrent_limit.clone();
+        }
+        if let Some(rate_limit) = window.rate_limit {
+            config.download_rate_limit_bytes = Some(rate_limit);
+        }
+        Some(config)
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             auth_token: default_auth_token(),
             bind_address: default_bind_address(),
             credential: Credential::default(),
+            backup_credentials: Vec::new(),
             filter_option: FilterOption::default(),
             danmaku_option: DanmakuOption::default(),
             skip_option: SkipOption::default(),
             video_name: "{{title}}".to_owned(),
             page_name: "{{bvid}}".to_owned(),
+            season_template: default_season_template(),
+            container: VideoContainer::default(),
+            dedup_strategy: DedupStrategy::default(),
+            subtitle_format: SubtitleFormat::default(),
+            subtitle_languages: Vec::new(),
+            embed_subtitles: false,
+            remove_embedded_subtitle_files: false,
+            audio_normalize: false,
+            audio_normalize_target_lufs: default_audio_normalize_target_lufs(),
             notifiers: None,
             favorite_default_path: default_favorite_path(),
             collection_default_path: default_collection_path(),
             submission_default_path: default_submission_path(),
             interval: Trigger::default(),
+            startup_jitter: default_startup_jitter(),
+            risk_control_cooldown: 0,
+            risk_control_cooldown_max: default_risk_control_cooldown_max(),
+            proxy: None,
+            notifier_proxy: None,
+            connect_timeout_secs: default_connect_timeout_secs(),
+            request_timeout_secs: default_request_timeout_secs(),
             upper_path: CONFIG_DIR.join("upper_face"),
             nfo_time_type: NFOTimeType::FavTime,
+            nfo_mode: NFOMode::default(),
+            nfo_include_uploader_actor: false,
+            collection_episode_order: false,
+            poster_source: PosterSource::default(),
+            cover_format: CoverFormat::default(),
+            cover_quality: default_cover_quality(),
+            nfo_template: NFOTemplate::default(),
+            tag_rules: Vec::new(),
+            ignored_error_codes: Vec::new(),
+            max_auto_retries: default_max_auto_retries(),
             concurrent_limit: ConcurrentLimit::default(),
+            download_rate_limit_bytes: None,
+            schedule: Vec::new(),
+            downloader_backend: DownloaderBackend::default(),
+            download_user_agent: None,
+            download_referer: default_download_referer(),
             time_format: default_time_format(),
             cdn_sorting: false,
             enable_cover_background: false,
+            write_source_manifest: false,
+            watch_later_auto_remove: false,
+            post_download_command: None,
+            post_download_command_timeout_secs: default_post_download_command_timeout_secs(),
+            post_download_command_concurrency: default_post_download_command_concurrency(),
             enable_video_source_on_subscribe: default_enable_video_source_on_subscribe(),
             notify_new_videos: default_notify_new_videos(),
+            notify_invalid_videos: false,
+            notify_paid_videos: false,
             notify_daily_summary: default_notify_daily_summary(),
+            message_templates: HashMap::new(),
             daily_summary_cron: default_daily_summary_cron(),
             notification_interval: default_notification_interval(),
+            notification_queue_capacity: default_notification_queue_capacity(),
             enable_notification_quiet_hours: default_enable_notification_quiet_hours(),
             quiet_hours_start: default_quiet_hours_start(),
             quiet_hours_end: default_quiet_hours_end(),
+            quiet_days: None,
+            task_event_retention_days: default_task_event_retention_days(),
             version: 0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+
+    use super::*;
+
+    #[test]
+    fn test_scheduled_at() {
+        let config = Config {
+            download_rate_limit_bytes: None,
+            schedule: vec![ScheduleWindow {
+                // 工作日的 9 点至 18 点限速并降低并发
+                cron_window: "0 * 9-17 * * 1-5".to_owned(),
+                concurrent_limit: Some(ConcurrentLimit {
+                    video: 1,
+                    page: 1,
+                    ..Default::default()
+                }),
+                rate_limit: Some(1 << 20),
+            }],
+            ..Default::default()
+        };
+        assert!(config.check().is_ok());
+        let effective_limits = |config: &Config| {
+            (
+                config.concurrent_limit.video,
+                config.concurrent_limit.page,
+                config.download_rate_limit_bytes,
+            )
+        };
+        // 2026-10-14 是周三，工作时间内使用时间段的配置
+        let work_hours = Local.with_ymd_and_hms(2026, 10, 14, 10, 30, 15).unwrap();
+        let scheduled = config.scheduled_at(&work_hours).expect("should be in the window");
+        assert_eq!(effective_limits(&scheduled), (1, 1, Some(1 << 20)));
+        // 夜间与周末不在时间段内，使用全局配置
+        let night = Local.with_ymd_and_hms(2026, 10, 14, 23, 0, 0).unwrap();
+        assert!(config.scheduled_at(&night).is_none());
+        let weekend = Local.with_ymd_and_hms(2026, 10, 17, 10, 30, 0).unwrap();
+        assert!(config.scheduled_at(&weekend).is_none());
+        assert_eq!(effective_limits(&config), (3, 2, None));
+        // 时间段的限速为空时沿用全局配置
+        let config = Config {
+            download_rate_limit_bytes: Some(1 << 30),
+            schedule: vec![ScheduleWindow {
+                cron_window: "0 * * * * *".to_owned(),
+                concurrent_limit: None,
+                rate_limit: None,
+            }],
+            ..Default::default()
+        };
+        let scheduled = config.scheduled_at(&night).expect("should be in the window");
+        assert_eq!(effective_limits(&scheduled), (3, 2, Some(1 << 30)));
+    }
+
+    #[test]
+    fn test_check_aria2_proxy() {
+        let aria2_config = |proxy: &str| Config {
+            proxy: Some(proxy.to_owned()),
+            downloader_backend: DownloaderBackend::Aria2 {
+                rpc_url: "http://127.0.0.1:6800/jsonrpc".to_owned(),
+                secret: None,
+                download_dir: None,
+                local_dir: None,
+            },
+            ..Default::default()
+        };
+        assert!(aria2_config("http://127.0.0.1:7890").check().is_ok());
+        assert!(aria2_config("socks5://127.0.0.1:1080").check().is_err());
+        // Native 后端支持 socks5 代理
+        let config = Config {
+            proxy: Some("socks5://127.0.0.1:1080".to_owned()),
+            ..Default::default()
+        };
+        assert!(config.check().is_ok());
+    }
+}
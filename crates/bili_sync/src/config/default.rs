@@ -29,6 +29,64 @@ pub fn default_submission_path() -> String {
     "投稿/{{name}}".to_owned()
 }
 
+/// 默认不启用视频详情预取，与此前先获取详情再下载的行为保持一致
+pub(super) fn default_prefetch() -> usize {
+    1
+}
+
+pub(super) fn default_source_concurrency() -> usize {
+    1
+}
+
+/// 默认在启动后的 30 秒内随机选择首次执行下载任务的时间
+pub(super) fn default_startup_jitter() -> u64 {
+    30
+}
+
+/// 转换封面格式时默认的图片质量
+pub(super) fn default_cover_quality() -> u8 {
+    80
+}
+
+/// 连续触发风控时最多暂停下载任务 6 小时
+pub(super) fn default_risk_control_cooldown_max() -> u64 {
+    6 * 3600
+}
+
+/// 下载请求默认携带的 Referer，不设置时部分 CDN 节点会返回 403
+pub(super) fn default_download_referer() -> String {
+    "https://www.bilibili.com".to_owned()
+}
+
+/// 响度标准化默认的目标响度，与主流音乐流媒体平台一致
+pub(super) fn default_audio_normalize_target_lufs() -> f64 {
+    -14.0
+}
+
+/// 下载后命令默认的超时时间，超时后结束命令进程
+pub(super) fn default_post_download_command_timeout_secs() -> u64 {
+    3600
+}
+
+/// 默认同时执行的下载后命令数量，避免大量视频同时完成时占满系统资源
+pub(super) fn default_post_download_command_concurrency() -> usize {
+    2
+}
+
+/// 默认的自动重试次数与状态编码允许的最大值一致
+pub(super) fn default_max_auto_retries() -> u32 {
+    4
+}
+
+/// 默认的连接与请求超时时间与此前硬编码的值保持一致
+pub(super) fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+pub(super) fn default_request_timeout_secs() -> u64 {
+    10
+}
+
 pub(super) fn default_notify_new_videos() -> bool {
     false
 }
@@ -41,6 +99,10 @@ pub(super) fn default_notification_interval() -> u64 {
     5 // 默认5秒，建议范围3-10秒
 }
 
+pub(super) fn default_notification_queue_capacity() -> usize {
+    100
+}
+
 pub(super) fn default_daily_summary_cron() -> String {
     "0 0 9 * * *".to_string() // 默认每天早上9点
 }
@@ -60,4 +122,12 @@ pub(super) fn default_quiet_hours_end() -> u8 {
 /// 默认：订阅收藏夹/合集/UP 投稿时，自动将对应视频源标记为启用
 pub(super) fn default_enable_video_source_on_subscribe() -> bool {
     true
-}
\ No newline at end of file
+}
+
+pub(super) fn default_task_event_retention_days() -> u64 {
+    30 // 默认保留 30 天
+}
+
+pub(super) fn default_season_template() -> String {
+    "1".to_owned()
+}
@@ -1,11 +1,15 @@
+use std::path::Path;
 use std::sync::LazyLock;
 
-use anyhow::Result;
+use anyhow::{Result, bail, ensure};
+use bili_sync_entity::*;
 use handlebars::handlebars_helper;
 
 use crate::config::versioned_cache::VersionedCache;
 use crate::config::{Config, PathSafeTemplate};
 use crate::notifier::{Notifier, webhook_template_content, webhook_template_key};
+use crate::utils::format_arg::{nfo_format_args, page_format_args};
+use crate::utils::nfo::validate_xml;
 
 pub static TEMPLATE: LazyLock<VersionedCache<handlebars::Handlebars<'static>>> =
     LazyLock::new(|| VersionedCache::new(create_template).expect("Failed to create handlebars template"));
@@ -15,9 +19,20 @@ fn create_template(config: &Config) -> Result<handlebars::Handlebars<'static>> {
     handlebars.register_helper("truncate", Box::new(truncate));
     handlebars.path_safe_register("video", config.video_name.clone())?;
     handlebars.path_safe_register("page", config.page_name.clone())?;
+    handlebars.register_template_string("season", &config.season_template)?;
     handlebars.path_safe_register("favorite_default_path", config.favorite_default_path.clone())?;
     handlebars.path_safe_register("collection_default_path", config.collection_default_path.clone())?;
     handlebars.path_safe_register("submission_default_path", config.submission_default_path.clone())?;
+    // 自定义的 nfo 模板不是路径，使用默认的 html 转义即可保证渲染出的文本在 xml 中是安全的
+    for (name, template) in [
+        ("nfo_video", &config.nfo_template.video),
+        ("nfo_page", &config.nfo_template.page),
+        ("nfo_upper", &config.nfo_template.upper),
+    ] {
+        if let Some(template) = template {
+            handlebars.register_template_string(name, template)?;
+        }
+    }
     if let Some(notifiers) = &config.notifiers {
         for notifier in notifiers.iter() {
             if let Notifier::Webhook { url, template, .. } = notifier {
@@ -28,6 +43,47 @@ fn create_template(config: &Config) -> Result<handlebars::Handlebars<'static>> {
     Ok(handlebars)
 }
 
+/// 使用示例数据渲染自定义的 nfo 模板，检查渲染结果是否为合法的 XML
+pub(super) fn validate_nfo_template(template: &str) -> Result<()> {
+    let mut handlebars = handlebars::Handlebars::new();
+    handlebars.register_helper("truncate", Box::new(truncate));
+    handlebars.register_template_string("nfo", template)?;
+    let (video_model, page_model) = (video::Model::default(), page::Model::default());
+    let content = handlebars.render("nfo", &nfo_format_args(&video_model, Some(&page_model), "%Y-%m-%d"))?;
+    validate_xml(&content)
+}
+
+/// 使用示例数据渲染候选的路径模板，模板中引用了不存在的变量时直接报错，渲染结果不能为空或绝对路径
+pub fn preview_path_template(template: &str, data: &serde_json::Value) -> Result<String> {
+    let mut handlebars = handlebars::Handlebars::new();
+    handlebars.set_strict_mode(true);
+    handlebars.register_helper("truncate", Box::new(truncate));
+    handlebars.path_safe_register("preview", template)?;
+    let path = handlebars.path_safe_render("preview", data)?;
+    ensure!(!path.trim().is_empty(), "模板渲染结果为空");
+    ensure!(!Path::new(&path).is_absolute(), "模板渲染结果不能是绝对路径：{}", path);
+    Ok(path)
+}
+
+/// 渲染多页视频中分页所属的季序号，季序号从 1 开始
+pub fn render_season(handlebars: &handlebars::Handlebars<'_>, data: &serde_json::Value) -> Result<u32> {
+    let season = handlebars.render("season", data)?;
+    let season = season.trim();
+    match season.parse() {
+        Ok(season) if season > 0 => Ok(season),
+        _ => bail!("季模板的渲染结果「{}」不是有效的季序号", season),
+    }
+}
+
+/// 使用示例数据渲染季模板，检查渲染结果是否为有效的季序号
+pub(super) fn validate_season_template(template: &str) -> Result<()> {
+    let mut handlebars = handlebars::Handlebars::new();
+    handlebars.register_template_string("season", template)?;
+    let (video_model, page_model) = (video::Model::default(), page::Model::default());
+    render_season(&handlebars, &page_format_args(&video_model, &page_model, "%Y-%m-%d"))?;
+    Ok(())
+}
+
 handlebars_helper!(truncate: |s: String, len: usize| {
     if s.chars().count() > len {
         s.chars().take(len).collect::<String>()
@@ -99,4 +155,66 @@ mod tests {
             "哈哈，你说得对，但是 Rust 是由 Mozilla 自主研发的一"
         );
     }
+
+    #[test]
+    fn test_validate_nfo_template() {
+        assert!(
+            validate_nfo_template(
+                "<movie><title>{{title}}</title><studio>bilibili</studio>{{#each tags}}<tag>{{this}}</tag>{{/each}}</movie>"
+            )
+            .is_ok()
+        );
+        // 未闭合的元素
+        assert!(validate_nfo_template("<movie><title>{{title}}</title>").is_err());
+        // 不匹配的闭合标签
+        assert!(validate_nfo_template("<movie><title>{{title}}</plot></movie>").is_err());
+        // 没有根元素
+        assert!(validate_nfo_template("{{title}}").is_err());
+        // 模板语法错误
+        assert!(validate_nfo_template("<movie>{{#if title}}</movie>").is_err());
+    }
+
+    #[test]
+    fn test_preview_path_template() {
+        let (video_model, page_model) = (
+            video::Model {
+                bvid: "BV1b5411h7g7".to_owned(),
+                name: "测试视频".to_owned(),
+                upper_name: "测试 UP 主".to_owned(),
+                ..Default::default()
+            },
+            page::Model {
+                pid: 2,
+                name: "第二集".to_owned(),
+                ..Default::default()
+            },
+        );
+        let data = page_format_args(&video_model, &page_model, "%Y-%m-%d");
+        assert_eq!(
+            preview_path_template("{{upper_name}} - {{title}} - P{{pid}}", &data).unwrap(),
+            "测试 UP 主 - 测试视频 - P2"
+        );
+        // 引用了不存在的变量
+        assert!(preview_path_template("{{bvid}} - {{episode}}", &data).is_err());
+        // 渲染结果为空
+        assert!(preview_path_template("{{#if valid}}{{title}}{{/if}}", &json!({"valid": false})).is_err());
+        #[cfg(not(windows))]
+        assert!(preview_path_template("/{{title}}", &data).is_err());
+    }
+
+    #[test]
+    fn test_render_season() {
+        let mut handlebars = handlebars::Handlebars::new();
+        handlebars
+            .register_template_string("season", "{{#if (gt pid 2)}}2{{else}}1{{/if}}")
+            .unwrap();
+        let render = |pid| render_season(&handlebars, &json!({"pid": pid}));
+        assert_eq!(render(1).unwrap(), 1);
+        assert_eq!(render(2).unwrap(), 1);
+        assert_eq!(render(3).unwrap(), 2);
+        assert!(validate_season_template("1").is_ok());
+        assert!(validate_season_template("{{#if (gt pid 10)}}2{{else}}1{{/if}}").is_ok());
+        assert!(validate_season_template("Season 1").is_err());
+        assert!(validate_season_template("0").is_err());
+    }
 }
@@ -1,6 +1,9 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+use crate::config::default::{default_prefetch, default_source_concurrency};
 use crate::utils::filenamify::filenamify;
 
 /// NFO 文件使用的时间类型
@@ -12,6 +15,71 @@ pub enum NFOTimeType {
     PubTime,
 }
 
+/// 单页视频生成的 NFO 类型
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NFOMode {
+    /// 与多页视频一致，生成 tvshow.nfo 与剧集 NFO，适用于剧集类型的媒体库
+    TvShow,
+    /// 生成以 movie 为根节点的 NFO，适用于电影类型的媒体库
+    #[default]
+    Movie,
+}
+
+/// 视频与分页封面的来源
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PosterSource {
+    /// 下载 B 站视频的封面
+    #[default]
+    Cover,
+    /// 视频下载完成后通过 ffmpeg 截取第一帧，截取失败时回退为视频封面
+    FirstFrame,
+}
+
+/// 封面、横幅与 UP 主头像等图片的保存格式
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverFormat {
+    /// 保存 B 站返回的原始图片
+    #[default]
+    Jpg,
+    /// 下载后通过 ffmpeg 转换为 webp
+    Webp,
+    /// 下载后通过 ffmpeg 转换为 avif，体积最小但编码较慢
+    Avif,
+}
+
+impl CoverFormat {
+    /// 图片文件的扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CoverFormat::Jpg => "jpg",
+            CoverFormat::Webp => "webp",
+            CoverFormat::Avif => "avif",
+        }
+    }
+
+    /// 使用 ffmpeg 编码该格式的图片时的参数，quality 取值 1 ~ 100，越大质量越高
+    pub fn ffmpeg_args(&self, quality: u8) -> Vec<String> {
+        let quality = quality.clamp(1, 100) as u32;
+        match self {
+            // mjpeg 的 q:v 取值 2 ~ 31，越小质量越高
+            CoverFormat::Jpg => vec!["-q:v".into(), (2 + (100 - quality) * 29 / 100).to_string()],
+            CoverFormat::Webp => vec!["-c:v".into(), "libwebp".into(), "-quality".into(), quality.to_string()],
+            // libaom 的 crf 取值 0 ~ 63，越小质量越高
+            CoverFormat::Avif => vec![
+                "-c:v".into(),
+                "libaom-av1".into(),
+                "-still-picture".into(),
+                "1".into(),
+                "-crf".into(),
+                ((100 - quality) * 63 / 100).to_string(),
+            ],
+        }
+    }
+}
+
 /// 并发下载相关的配置
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ConcurrentLimit {
@@ -20,6 +88,12 @@ pub struct ConcurrentLimit {
     pub rate_limit: Option<RateLimit>,
     #[serde(default)]
     pub download: ConcurrentDownloadLimit,
+    /// 预取视频详情的并发数，大于 1 时会在获取视频详情的同时下载已就绪的视频
+    #[serde(default = "default_prefetch")]
+    pub prefetch: usize,
+    /// 同时扫描的视频源数量，每个视频源内部仍然遵循 video 与 page 的并发限制
+    #[serde(default = "default_source_concurrency")]
+    pub source: usize,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -39,6 +113,19 @@ impl Default for ConcurrentDownloadLimit {
     }
 }
 
+/// 按时间段调整的并发与限速设置，扫描开始时命中时间段则覆盖全局的配置
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScheduleWindow {
+    /// 时间段的 Cron 表达式（秒 分 时 日 月 周），扫描开始的时刻按分钟匹配，如 `0 * 9-18 * * 1-5` 表示工作日的 9 点至 18 点
+    pub cron_window: String,
+    /// 时间段内使用的并发限制，为空时沿用全局配置，其中的请求频率限制不随时间段变化
+    #[serde(default)]
+    pub concurrent_limit: Option<ConcurrentLimit>,
+    /// 时间段内下载的总速率限制（字节/秒），为空时沿用全局配置
+    #[serde(default)]
+    pub rate_limit: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct RateLimit {
     pub limit: usize,
@@ -56,10 +143,23 @@ impl Default for ConcurrentLimit {
                 duration: 250,
             }),
             download: ConcurrentDownloadLimit::default(),
+            prefetch: default_prefetch(),
+            source: default_source_concurrency(),
         }
     }
 }
 
+/// 用户自定义的 nfo 模板，设置后会覆盖内置的 nfo 生成逻辑
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct NFOTemplate {
+    /// 视频的 nfo 模板，用于多页视频的 tvshow.nfo 与单页视频的 nfo
+    pub video: Option<String>,
+    /// 分页的 nfo 模板，用于多页视频中每一集的 nfo
+    pub page: Option<String>,
+    /// up 主的 nfo 模板，用于 person.nfo
+    pub upper: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct SkipOption {
     pub no_poster: bool,
@@ -69,6 +169,67 @@ pub struct SkipOption {
     pub no_subtitle: bool,
 }
 
+/// 视频文件的封装格式
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VideoContainer {
+    #[default]
+    Mp4,
+    /// mkv 对章节等元数据的支持更好
+    Mkv,
+}
+
+/// 同一视频已被其它视频源下载过时，避免重复下载的方式
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupStrategy {
+    /// 不去重，每个视频源分别下载
+    #[default]
+    None,
+    /// 创建硬链接，源文件与目标不在同一文件系统时退化为复制
+    Hardlink,
+    /// 创建指向已下载文件的符号链接
+    Symlink,
+}
+
+impl VideoContainer {
+    /// 视频文件的扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            VideoContainer::Mp4 => "mp4",
+            VideoContainer::Mkv => "mkv",
+        }
+    }
+
+    /// 传递给 ffmpeg -f 参数的封装格式名称
+    pub fn ffmpeg_format(&self) -> &'static str {
+        match self {
+            VideoContainer::Mp4 => "mp4",
+            VideoContainer::Mkv => "matroska",
+        }
+    }
+}
+
+/// 实际传输文件内容所使用的下载后端
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DownloaderBackend {
+    /// 由 bili-sync 自身完成下载
+    #[default]
+    Native,
+    /// 通过 JSON-RPC 交由 aria2 下载，未设置 download_dir 时要求 aria2 与 bili-sync 能够访问相同的文件路径
+    /// aria2 与 bili-sync 位于不同的容器或主机时，可以设置 aria2 一侧的下载目录 download_dir 与该目录在 bili-sync 一侧的挂载路径 local_dir，
+    /// aria2 先下载到 download_dir 中，完成后由 bili-sync 移动到目标位置，local_dir 为空时与 download_dir 相同
+    Aria2 {
+        rpc_url: String,
+        secret: Option<String>,
+        #[serde(default)]
+        download_dir: Option<String>,
+        #[serde(default)]
+        local_dir: Option<PathBuf>,
+    },
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 pub enum Trigger {
@@ -7,9 +7,12 @@ mod versioned_cache;
 mod versioned_config;
 
 pub use crate::config::args::{ARGS, version};
-pub use crate::config::current::{CONFIG_DIR, Config};
+pub use crate::config::current::{CONFIG_DIR, CRON_PARSER, Config};
 pub(crate) use crate::config::default::default_bind_address;
-pub use crate::config::handlebar::TEMPLATE;
-pub use crate::config::item::{ConcurrentDownloadLimit, NFOTimeType, PathSafeTemplate, RateLimit, Trigger};
+pub use crate::config::handlebar::{TEMPLATE, preview_path_template, render_season};
+pub use crate::config::item::{
+    ConcurrentDownloadLimit, CoverFormat, DedupStrategy, DownloaderBackend, NFOMode, NFOTemplate, NFOTimeType,
+    PathSafeTemplate, PosterSource, RateLimit, ScheduleWindow, Trigger, VideoContainer,
+};
 pub use crate::config::versioned_cache::VersionedCache;
 pub use crate::config::versioned_config::VersionedConfig;
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use arc_swap::{ArcSwap, Guard};
+use tokio::sync::watch;
 use tokio_util::future::FutureExt;
 use tokio_util::sync::CancellationToken;
 
@@ -15,17 +16,38 @@ pub struct VersionedCache<T> {
 /// 一个跟随全局配置变化自动更新的缓存
 impl<T: Send + Sync + 'static> VersionedCache<T> {
     pub fn new(builder: fn(&Config) -> Result<T>) -> Result<Self> {
-        let mut rx = VersionedConfig::get().subscribe();
-        let initial_value = builder(&rx.borrow_and_update())?;
+        Self::with_receiver(VersionedConfig::get().subscribe(), builder, |_, _| true)
+    }
+
+    /// 仅在 key 返回的值变化时重新构建，其它配置变化时保留缓存的值及其内部状态
+    pub fn with_key<K: PartialEq + 'static>(builder: fn(&Config) -> Result<T>, key: fn(&Config) -> K) -> Result<Self> {
+        Self::with_receiver(VersionedConfig::get().subscribe(), builder, move |old, new| {
+            key(old) != key(new)
+        })
+    }
+
+    fn with_receiver(
+        mut rx: watch::Receiver<Arc<Config>>,
+        builder: fn(&Config) -> Result<T>,
+        should_rebuild: impl Fn(&Config, &Config) -> bool + Send + 'static,
+    ) -> Result<Self> {
+        // 记录最近一次成功构建时使用的配置
+        let mut built_config = rx.borrow_and_update().clone();
+        let initial_value = builder(&built_config)?;
         let cancel_token = CancellationToken::new();
         let inner = Arc::new(ArcSwap::from_pointee(initial_value));
         let inner_clone = inner.clone();
         tokio::spawn(
             async move {
                 while rx.changed().await.is_ok() {
-                    match builder(&rx.borrow()) {
+                    let new_config = rx.borrow().clone();
+                    if !should_rebuild(&built_config, &new_config) {
+                        continue;
+                    }
+                    match builder(&new_config) {
                         Ok(new_value) => {
                             inner_clone.store(Arc::new(new_value));
+                            built_config = new_config;
                         }
                         Err(e) => {
                             error!("Failed to update versioned cache: {:?}", e);
@@ -54,3 +76,42 @@ impl<T> Drop for VersionedCache<T> {
         self.cancel_token.cancel();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::config::Trigger;
+
+    #[tokio::test]
+    async fn test_rebuild_on_key_change() -> Result<()> {
+        let (tx, rx) = watch::channel(Arc::new(Config::default()));
+        let key = |config: &Config| config.download_rate_limit_bytes;
+        let cache = VersionedCache::with_receiver(
+            rx,
+            |config| Ok(config.download_rate_limit_bytes),
+            move |old, new| key(old) != key(new),
+        )?;
+        let initial = cache.snapshot();
+        // 无关的配置变化不会重新构建
+        tx.send_replace(Arc::new(Config {
+            interval: Trigger::Interval(60),
+            ..Default::default()
+        }));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(Arc::ptr_eq(&initial, &cache.snapshot()));
+        tx.send_replace(Arc::new(Config {
+            download_rate_limit_bytes: Some(1 << 20),
+            ..Default::default()
+        }));
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while cache.read().is_none() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await?;
+        assert_eq!(*cache.read(), Some(1 << 20));
+        Ok(())
+    }
+}
@@ -22,7 +22,7 @@ impl VersionedConfig {
     pub async fn init(connection: &DatabaseConnection) -> Result<&'static VersionedConfig> {
         VERSIONED_CONFIG
             .get_or_try_init(|| async move {
-                let mut config = match Config::load_from_database(connection).await? {
+                let config = match Config::load_from_database(connection).await? {
                     Some(Ok(config)) => config,
                     Some(Err(e)) => bail!("解析数据库配置失败： {}", e),
                     None => {
@@ -35,8 +35,7 @@ impl VersionedConfig {
                         config
                     }
                 };
-                // version 本身不具有实际意义，仅用于并发更新时的版本控制，在初始化时可以直接清空
-                config.version = 0;
+                // version 同时作为配置历史的索引，需要在重启后继续递增，因此不能清空
                 Ok(VersionedConfig::new(config))
             })
             .await
@@ -93,16 +92,25 @@ impl VersionedConfig {
         self.rx.clone()
     }
 
+    /// 更新第 index 个账号的凭据，0 为主账号，之后依次为备用账号
     pub async fn update_credential(
         &self,
+        index: usize,
         new_credential: Credential,
         connection: &DatabaseConnection,
     ) -> Result<Arc<Config>> {
         let _lock = self.update_lock.lock().await;
         let mut new_config = self.inner.load().as_ref().clone();
-        new_config.credential = new_credential;
+        match index {
+            0 => new_config.credential = new_credential,
+            _ => match new_config.backup_credentials.get_mut(index - 1) {
+                Some(credential) => *credential = new_credential,
+                None => bail!("第 {} 个账号的凭据不存在，可能已被移除", index + 1),
+            },
+        }
         new_config.version += 1;
-        new_config.save_to_database(connection).await?;
+        // 凭据刷新较为频繁，不记录配置历史，避免挤掉用户真正修改过的配置
+        new_config.save_to_database_without_history(connection).await?;
         let new_config = Arc::new(new_config);
         self.inner.store(new_config.clone());
         self.tx.send(new_config.clone())?;
@@ -123,4 +131,184 @@ impl VersionedConfig {
         self.tx.send(new_config.clone())?;
         Ok(new_config)
     }
+
+    /// 导入配置时调用，导入的配置来自其它实例，其版本号没有意义，直接覆盖当前配置
+    pub async fn import(&self, mut new_config: Config, connection: &DatabaseConnection) -> Result<Arc<Config>> {
+        let _lock = self.update_lock.lock().await;
+        new_config.version = self.inner.load().version + 1;
+        new_config.save_to_database(connection).await?;
+        let new_config = Arc::new(new_config);
+        self.inner.store(new_config.clone());
+        self.tx.send(new_config.clone())?;
+        Ok(new_config)
+    }
+
+    /// 回滚到配置历史中的某个版本，回滚本身作为一次普通的更新，版本号继续向前递增
+    ///
+    /// 历史中的凭据和 auth_token 可能早已失效，回滚时保留当前的值
+    pub async fn rollback(&self, version: u64, connection: &DatabaseConnection) -> Result<Arc<Config>> {
+        let Some(mut history_config) = Config::load_version_from_database(version, connection).await? else {
+            bail!("配置历史中不存在版本 {}", version);
+        };
+        let current = self.inner.load();
+        history_config.version = current.version;
+        history_config.credential = current.credential.clone();
+        history_config.backup_credentials = current.backup_credentials.clone();
+        history_config.auth_token = current.auth_token.clone();
+        history_config.check()?;
+        self.update(history_config, connection).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bili_sync_entity::config_history;
+    use sea_orm::{EntityTrait, QueryOrder};
+
+    use super::*;
+    use crate::utils::test_utils::TestDatabase;
+
+    #[tokio::test]
+    async fn test_update_credential_by_index() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let credential = |sessdata: &str| Credential {
+            sessdata: sessdata.to_owned(),
+            ..Default::default()
+        };
+        let versioned_config = VersionedConfig::new(Config {
+            credential: credential("primary"),
+            backup_credentials: vec![credential("backup")],
+            ..Default::default()
+        });
+        // 两个账号的凭据分别刷新，互不影响
+        versioned_config
+            .update_credential(1, credential("backup-refreshed"), &connection)
+            .await?;
+        let config = versioned_config.read();
+        assert_eq!(config.credential.sessdata, "primary");
+        assert_eq!(config.backup_credentials[0].sessdata, "backup-refreshed");
+        versioned_config
+            .update_credential(0, credential("primary-refreshed"), &connection)
+            .await?;
+        assert!(
+            versioned_config
+                .update_credential(2, credential("missing"), &connection)
+                .await
+                .is_err()
+        );
+        let Some(Ok(saved)) = Config::load_from_database(&connection).await? else {
+            panic!("config should be saved");
+        };
+        assert_eq!(saved.credential.sessdata, "primary-refreshed");
+        assert_eq!(saved.backup_credentials.len(), 1);
+        assert_eq!(saved.backup_credentials[0].sessdata, "backup-refreshed");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let source = VersionedConfig::new(Config {
+            credential: Credential {
+                sessdata: "exported".to_owned(),
+                bili_jct: "bili_jct".to_owned(),
+                buvid3: "buvid3".to_owned(),
+                dedeuserid: "dedeuserid".to_owned(),
+                ac_time_value: "ac_time_value".to_owned(),
+            },
+            video_name: "{{upper_name}}/{{title}}".to_owned(),
+            ignored_error_codes: vec![62002],
+            version: 7,
+            ..Default::default()
+        });
+        let exported = serde_json::to_string(&*source.snapshot())?;
+        // 导入到另一个版本号不同的实例，敏感字段一并导入
+        let target = VersionedConfig::new(Config::default());
+        let imported: Config = serde_json::from_str(&exported)?;
+        imported.check()?;
+        let imported = target.import(imported, &connection).await?;
+        assert_eq!(imported.version, 1);
+        assert_eq!(imported.credential.sessdata, "exported");
+        let without_version = |config: &Config| {
+            let mut value = serde_json::to_value(config).expect("failed to serialize config");
+            value["version"] = serde_json::Value::Null;
+            value
+        };
+        assert_eq!(without_version(&target.snapshot()), without_version(&source.snapshot()));
+        let Some(Ok(saved)) = Config::load_from_database(&connection).await? else {
+            panic!("config should be saved");
+        };
+        assert_eq!(without_version(&saved), without_version(&source.snapshot()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_history_and_rollback() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let versioned_config = VersionedConfig::new(Config {
+            credential: Credential {
+                sessdata: "sessdata".to_owned(),
+                bili_jct: "bili_jct".to_owned(),
+                buvid3: "buvid3".to_owned(),
+                dedeuserid: "dedeuserid".to_owned(),
+                ac_time_value: "ac_time_value".to_owned(),
+            },
+            ..Default::default()
+        });
+        let first = versioned_config
+            .update(
+                Config {
+                    video_name: "first".to_owned(),
+                    ..(*versioned_config.snapshot()).clone()
+                },
+                &connection,
+            )
+            .await?;
+        let second = versioned_config
+            .update(
+                Config {
+                    video_name: "second".to_owned(),
+                    ..(*versioned_config.snapshot()).clone()
+                },
+                &connection,
+            )
+            .await?;
+        let history = config_history::Entity::find()
+            .order_by_desc(config_history::Column::Version)
+            .all(&connection)
+            .await?;
+        assert_eq!(
+            history.iter().map(|h| h.version).collect::<Vec<_>>(),
+            vec![second.version as i64, first.version as i64]
+        );
+        // 仅更新凭据时不记录配置历史
+        let refreshed = Credential {
+            sessdata: "refreshed".to_owned(),
+            ..versioned_config.read().credential.clone()
+        };
+        versioned_config.update_credential(0, refreshed, &connection).await?;
+        assert_eq!(config_history::Entity::find().all(&connection).await?.len(), 2);
+        versioned_config
+            .update(
+                Config {
+                    auth_token: "new_token".to_owned(),
+                    ..(*versioned_config.snapshot()).clone()
+                },
+                &connection,
+            )
+            .await?;
+        // 回滚后内容与第一次保存的一致，但版本号继续递增，且保留当前的凭据和 auth_token
+        let rolled_back = versioned_config.rollback(first.version, &connection).await?;
+        assert_eq!(rolled_back.video_name, "first");
+        assert_eq!(rolled_back.version, second.version + 3);
+        assert_eq!(rolled_back.credential.sessdata, "refreshed");
+        assert_eq!(rolled_back.auth_token, "new_token");
+        assert_eq!(versioned_config.read().video_name, "first");
+        assert_eq!(config_history::Entity::find().all(&connection).await?.len(), 4);
+        assert!(versioned_config.rollback(100, &connection).await.is_err());
+        Ok(())
+    }
 }
@@ -7,6 +7,9 @@ use sea_orm::sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqliteSynch
 use sea_orm::sqlx::{ConnectOptions as SqlxConnectOptions, Sqlite};
 use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection, SqlxSqliteConnector, Statement};
 
+/// 数据库文件在配置目录中的文件名
+pub const DATABASE_FILE_NAME: &str = "data.sqlite";
+
 fn database_url(path: &Path) -> String {
     format!("sqlite://{}?mode=rwc", path.to_string_lossy())
 }
@@ -69,3 +72,57 @@ pub async fn setup_database(path: &Path) -> Result<DatabaseConnection> {
         .await
         .context("Failed to connect to database")
 }
+
+/// 数据库文件占用的磁盘空间，WAL 模式下需要同时计入 -wal 文件
+async fn database_size(path: &Path) -> Result<u64> {
+    let mut size = tokio::fs::metadata(path).await?.len();
+    let mut wal_path = path.as_os_str().to_owned();
+    wal_path.push("-wal");
+    if let Ok(metadata) = tokio::fs::metadata(&wal_path).await {
+        size += metadata.len();
+    }
+    Ok(size)
+}
+
+/// 对数据库执行 VACUUM 与 ANALYZE 以回收空间并更新查询统计信息，返回整理前后的数据库文件大小（字节）
+pub async fn vacuum_database(connection: &DatabaseConnection, path: &Path) -> Result<(u64, u64)> {
+    let before = database_size(path).await?;
+    for sql in ["VACUUM;", "ANALYZE;", "PRAGMA wal_checkpoint(TRUNCATE);"] {
+        connection
+            .execute_unprepared(sql)
+            .await
+            .with_context(|| format!("Failed to execute {}", sql))?;
+    }
+    let after = database_size(path).await?;
+    Ok((before, after))
+}
+
+#[cfg(test)]
+mod tests {
+    use bili_sync_entity::task_event;
+    use sea_orm::ActiveValue::Set;
+    use sea_orm::EntityTrait;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_vacuum_database() -> Result<()> {
+        let test_dir = std::env::temp_dir().join(format!("bili-sync-vacuum-{}", uuid::Uuid::new_v4()));
+        let path = test_dir.join("data.sqlite");
+        let connection = setup_database(&path).await?;
+        let events = (0..2000).map(|i| task_event::ActiveModel {
+            video_id: Set(i),
+            task_index: Set(0),
+            status: Set(1),
+            ..Default::default()
+        });
+        task_event::Entity::insert_many(events).exec(&connection).await?;
+        task_event::Entity::delete_many().exec(&connection).await?;
+        let (before, after) = vacuum_database(&connection, &path).await?;
+        assert!(before > 0);
+        assert!(after <= before);
+        drop(connection);
+        let _ = tokio::fs::remove_dir_all(&test_dir).await;
+        Ok(())
+    }
+}
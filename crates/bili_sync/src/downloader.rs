@@ -1,136 +1,234 @@
 use core::str;
-use std::io::SeekFrom;
-use std::path::Path;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, bail, ensure};
 use async_tempfile::TempFile;
-use futures::TryStreamExt;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use futures::{Stream, TryStreamExt};
+use leaky_bucket::RateLimiter;
 use reqwest::{Method, StatusCode, header};
+use serde_json::json;
+use thiserror::Error;
 use tokio::fs::{self};
-use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 use tokio::task::JoinSet;
 use tokio_util::io::StreamReader;
+use ua_generator::ua;
 
 use crate::bilibili::Client;
-use crate::config::ConcurrentDownloadLimit;
+use crate::config::{ConcurrentDownloadLimit, Config, DownloaderBackend, VersionedCache, VideoContainer};
+use crate::utils::progress::ProgressReporter;
 use tracing;
 
+/// 轮询 aria2 下载状态的间隔
+const ARIA2_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// aria2 下载任务持续该时长没有任何进度时视为失败
+const ARIA2_STALL_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// 全局配置与各个时间段中出现的限速对应的限速器，所有 Downloader 按生效的限速共享同一个限速器
+/// 仅在限速配置变化时重新构建，修改其它配置不会重置限速器中的令牌
+static DOWNLOAD_RATE_LIMITERS: LazyLock<VersionedCache<HashMap<u64, Arc<RateLimiter>>>> = LazyLock::new(|| {
+    VersionedCache::with_key(build_rate_limiters, rate_limits).expect("failed to create download rate limiters")
+});
+
+/// 全局配置与各个时间段中设置的限速
+fn rate_limits(config: &Config) -> Vec<u64> {
+    config
+        .download_rate_limit_bytes
+        .into_iter()
+        .chain(config.schedule.iter().filter_map(|window| window.rate_limit))
+        .collect()
+}
+
+fn build_rate_limiters(config: &Config) -> Result<HashMap<u64, Arc<RateLimiter>>> {
+    Ok(rate_limits(config)
+        .into_iter()
+        .map(|bytes| (bytes, build_rate_limiter(bytes)))
+        .collect())
+}
+
+/// 构造总速率为 bytes 字节/秒的限速器
+fn build_rate_limiter(bytes: u64) -> Arc<RateLimiter> {
+    // 每 100ms 补充一次令牌，令牌桶容量为一秒的配额，限制空闲后的突发流量
+    Arc::new(
+        RateLimiter::builder()
+            .initial(0)
+            .refill((bytes / 10).max(1) as usize)
+            .max(bytes.max(1) as usize)
+            .interval(Duration::from_millis(100))
+            .build(),
+    )
+}
+
+/// 下载内容校验失败，说明文件不完整或已损坏，需要将任务标记为失败并重试
+#[derive(Error, Debug)]
+pub enum DownloadError {
+    #[error("downloaded bytes mismatch: expected {expected}, got {received}")]
+    SizeMismatch { expected: u64, received: u64 },
+    #[error("content md5 mismatch: expected {expected}, got {actual}")]
+    Md5Mismatch { expected: String, actual: String },
+}
+
+#[derive(Clone)]
 pub struct Downloader {
     client: Client,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    backend: DownloaderBackend,
+    // 下载请求携带的 User-Agent 与 Referer，部分 CDN 节点会根据它们拒绝请求
+    user_agent: String,
+    referer: String,
+    // aria2 后端下载时使用的代理，Native 后端经由 client 中设置的代理下载
+    proxy: Option<String>,
+    // 记录下载进度的视频，为空时不记录
+    progress: Option<ProgressReporter>,
 }
 
 impl Downloader {
     // Downloader 使用带有默认 Header 的 Client 构建
     // 拿到 url 后下载文件不需要任何 cookie 作为身份凭证
     // 但如果不设置默认 Header，下载时会遇到 403 Forbidden 错误
-    pub fn new(client: Client) -> Self {
-        Self { client }
+    // rate_limit_bytes 为所有经由该 Downloader 的下载共享的总速率限制（字节/秒），仅对 Native 后端生效
+    pub fn new(client: Client, rate_limit_bytes: Option<u64>, backend: DownloaderBackend) -> Self {
+        Self::with_rate_limiter(client, rate_limit_bytes.map(build_rate_limiter), backend)
     }
 
-    pub async fn fetch(&self, url: &str, path: &Path, concurrent_download: &ConcurrentDownloadLimit) -> Result<()> {
-        let mut temp_file = TempFile::new().await?;
-        self.fetch_internal(url, &mut temp_file, false, concurrent_download)
-            .await?;
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).await
-                .map_err(|e| {
-                    tracing::error!("创建父目录失败 {}: {}", parent.display(), e);
-                    e
-                })?;
+    fn with_rate_limiter(client: Client, rate_limiter: Option<Arc<RateLimiter>>, backend: DownloaderBackend) -> Self {
+        Self {
+            client,
+            rate_limiter,
+            backend,
+            user_agent: ua::spoof_chrome_ua().to_owned(),
+            referer: "https://www.bilibili.com".to_owned(),
+            proxy: None,
+            progress: None,
         }
-        // 先尝试复制，如果失败且是因为文件已存在，则删除后重试
-        match fs::copy(temp_file.file_path(), path).await {
-            Ok(_) => {
-                // 复制成功
-            }
-            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied && path.exists() => {
-                // 权限错误且文件已存在，删除后重试
-                tracing::warn!("复制文件失败（权限错误），尝试删除已存在的文件后重试 {}: {}", path.display(), e);
-                fs::remove_file(path).await
-                    .map_err(|remove_err| {
-                        tracing::error!("删除已存在的文件失败 {}: {}", path.display(), remove_err);
-                        remove_err
-                    })?;
-                fs::copy(temp_file.file_path(), path).await
-                    .map_err(|e| {
-                        tracing::error!("删除后重新复制文件失败 {}: {}", path.display(), e);
-                        e
-                    })?;
-            }
-            Err(e) => {
-                tracing::error!("复制文件失败 {}: {}", path.display(), e);
-                return Err(e.into());
-            }
+    }
+
+    /// 根据配置中的限速、下载后端与下载请求头构造 Downloader
+    /// 限速与全局配置或其中某个时间段一致时复用共享的限速器，使同时进行的扫描与手动下载共同受总速率限制
+    pub fn from_config(client: Client, config: &Config) -> Self {
+        let rate_limiter = config.download_rate_limit_bytes.map(|bytes| {
+            DOWNLOAD_RATE_LIMITERS
+                .read()
+                .get(&bytes)
+                .cloned()
+                .unwrap_or_else(|| build_rate_limiter(bytes))
+        });
+        let mut downloader = Self::with_rate_limiter(client, rate_limiter, config.downloader_backend.clone());
+        if let Some(user_agent) = &config.download_user_agent {
+            downloader.user_agent = user_agent.clone();
         }
-        // temp_file 的 drop 需要 std::fs::remove_file
-        // 如果交由 rust 自动执行虽然逻辑正确但会略微阻塞异步上下文
-        // 尽量主动调用，保证正常执行的情况下文件清除操作由 spawn_blocking 在专门线程中完成
-        temp_file.drop_async().await;
-        Ok(())
+        downloader.referer = config.download_referer.clone();
+        downloader.proxy = config.proxy.clone();
+        downloader
+    }
+
+    /// 构造下载请求，下载文件不需要携带 cookie
+    fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url, None)
+            .header(header::USER_AGENT, &self.user_agent)
+            .header(header::REFERER, &self.referer)
     }
 
+    /// 返回共享限速的 Downloader 副本，经由该副本下载的字节数会计入指定视频的下载进度，aria2 后端不记录进度
+    pub fn with_progress(&self, video_id: i32, cid: i64) -> Self {
+        Self {
+            progress: Some(ProgressReporter::new(video_id, cid)),
+            ..self.clone()
+        }
+    }
+
+    /// 返回单独记录本次下载尝试进度的 Downloader 副本，尝试失败时可以扣除已计入的进度
+    fn progress_attempt(&self) -> Self {
+        Self {
+            progress: self.progress.as_ref().map(ProgressReporter::new_attempt),
+            ..self.clone()
+        }
+    }
+
+    fn report_total(&self, bytes: Option<u64>) {
+        if let (Some(progress), Some(bytes)) = (&self.progress, bytes) {
+            progress.add_total(bytes);
+        }
+    }
+
+    /// 下载单个文件，下载过程中写入同目录下的 .part 文件，中断后再次下载时会从已下载的位置继续
+    pub async fn fetch(&self, url: &str, path: &Path, concurrent_download: &ConcurrentDownloadLimit) -> Result<()> {
+        self.fetch_to(&[url], path, false, concurrent_download).await
+    }
+
+    /// 从互为镜像的多个 url 中下载视频流或音频流，与 fetch 相同，中断后再次下载时会从已下载的位置继续
     pub async fn multi_fetch(
         &self,
         urls: &[&str],
         path: &Path,
         concurrent_download: &ConcurrentDownloadLimit,
     ) -> Result<()> {
-        let temp_file = self.multi_fetch_internal(urls, true, concurrent_download).await?;
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).await?;
-        }
-        // 先尝试复制，如果失败且是因为文件已存在，则删除后重试
-        if let Err(e) = fs::copy(temp_file.file_path(), path).await {
-            if e.kind() == std::io::ErrorKind::PermissionDenied && path.exists() {
-                // 权限错误且文件已存在，删除后重试
-                fs::remove_file(path).await?;
-                fs::copy(temp_file.file_path(), path).await?;
-            } else {
-                return Err(e.into());
-            }
-        }
-        temp_file.drop_async().await;
-        Ok(())
+        self.fetch_to(urls, path, true, concurrent_download).await
     }
 
+    /// 下载视频流与音频流（可选）并通过 ffmpeg 封装为指定格式，未提供音频流时仅对视频流进行重新封装
+    /// 视频流与音频流分别下载到同目录下的 .video.part 与 .audio.part 文件，下载中断时保留用于续传，封装结束后删除
     pub async fn multi_fetch_and_merge(
         &self,
         video_urls: &[&str],
-        audio_urls: &[&str],
+        audio_urls: Option<&[&str]>,
         path: &Path,
+        container: VideoContainer,
         concurrent_download: &ConcurrentDownloadLimit,
     ) -> Result<()> {
-        let (video_temp_file, audio_temp_file) = tokio::try_join!(
-            self.multi_fetch_internal(video_urls, true, concurrent_download),
-            self.multi_fetch_internal(audio_urls, true, concurrent_download)
-        )?;
-        let final_temp_file = TempFile::new().await?;
-        let output = Command::new("ffmpeg")
-            .args([
-                "-i",
-                video_temp_file.file_path().to_string_lossy().as_ref(),
-                "-i",
-                audio_temp_file.file_path().to_string_lossy().as_ref(),
-                "-c",
-                "copy",
-                "-strict",
-                "unofficial",
-                "-f",
-                "mp4",
-                "-y",
-                final_temp_file.file_path().to_string_lossy().as_ref(),
-            ])
-            .output()
-            .await
-            .context("failed to run ffmpeg")?;
-        if !output.status.success() {
-            bail!("ffmpeg error: {}", str::from_utf8(&output.stderr).unwrap_or("unknown"));
-        }
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).await?;
         }
+        let (video_part_path, audio_part_path) = (stream_part_path(path, "video"), stream_part_path(path, "audio"));
+        tokio::try_join!(
+            self.fetch_urls(video_urls, &video_part_path, true, concurrent_download),
+            async {
+                match audio_urls {
+                    Some(audio_urls) => {
+                        self.fetch_urls(audio_urls, &audio_part_path, true, concurrent_download)
+                            .await
+                    }
+                    None => Ok(()),
+                }
+            }
+        )?;
+        let merged = async {
+            let final_temp_file = TempFile::new().await?;
+            let mut command = Command::new("ffmpeg");
+            command.arg("-i").arg(&video_part_path);
+            if audio_urls.is_some() {
+                command.arg("-i").arg(&audio_part_path);
+            }
+            let output = command
+                .args([
+                    "-c",
+                    "copy",
+                    "-strict",
+                    "unofficial",
+                    "-f",
+                    container.ffmpeg_format(),
+                    "-y",
+                ])
+                .arg(final_temp_file.file_path())
+                .output()
+                .await
+                .context("failed to run ffmpeg")?;
+            if !output.status.success() {
+                bail!("ffmpeg error: {}", str::from_utf8(&output.stderr).unwrap_or("unknown"));
+            }
+            anyhow::Ok(final_temp_file)
+        }
+        .await;
+        // 封装完成后不再需要视频流与音频流，封装失败说明已下载的内容已损坏，同样直接删除
+        tokio::join!(remove_part_files(&video_part_path), remove_part_files(&audio_part_path));
+        let final_temp_file = merged?;
         // 先尝试复制，如果失败且是因为文件已存在，则删除后重试
         if let Err(e) = fs::copy(final_temp_file.file_path(), path).await {
             if e.kind() == std::io::ErrorKind::PermissionDenied && path.exists() {
@@ -141,37 +239,82 @@ impl Downloader {
                 return Err(e.into());
             }
         }
-        tokio::join!(
-            video_temp_file.drop_async(),
-            audio_temp_file.drop_async(),
-            final_temp_file.drop_async()
-        );
+        final_temp_file.drop_async().await;
+        Ok(())
+    }
+
+    /// 下载到 path 对应的 .part 文件，完成后重命名为 path
+    /// aria2 后端通过自身的控制文件继续未完成的下载，直接下载到 path
+    async fn fetch_to(
+        &self,
+        urls: &[&str],
+        path: &Path,
+        is_stream: bool,
+        concurrent_download: &ConcurrentDownloadLimit,
+    ) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await.map_err(|e| {
+                tracing::error!("创建父目录失败 {}: {}", parent.display(), e);
+                e
+            })?;
+        }
+        if let DownloaderBackend::Aria2 { .. } = &self.backend {
+            return self.fetch_urls(urls, path, is_stream, concurrent_download).await;
+        }
+        let part_path = part_path(path);
+        self.fetch_urls(urls, &part_path, is_stream, concurrent_download)
+            .await?;
+        fs::rename(&part_path, path).await.map_err(|e| {
+            tracing::error!("重命名文件失败 {} -> {}: {}", part_path.display(), path.display(), e);
+            e
+        })?;
+        // 分块方式变化后改为串行下载时，可能遗留之前的分块文件
+        remove_part_files(&part_path).await;
         Ok(())
     }
 
-    async fn multi_fetch_internal(
+    /// 依次尝试各个 url 下载到 target
+    /// 网络错误或超时时保留未下载完成的文件，下次下载时从已下载的位置继续，
+    /// 仅在内容已损坏、切换到下一个 url 或下载被中止时删除
+    async fn fetch_urls(
         &self,
         urls: &[&str],
+        target: &Path,
         is_stream: bool,
         concurrent_download: &ConcurrentDownloadLimit,
-    ) -> Result<TempFile> {
+    ) -> Result<()> {
         if urls.is_empty() {
             bail!("no urls provided");
         }
-        let mut temp_file = TempFile::new().await?;
+        if let DownloaderBackend::Aria2 { .. } = &self.backend {
+            // 多个 url 互为镜像，交由 aria2 自行选择
+            return self.fetch_aria2(urls, target).await;
+        }
+        let guard = PartFilesGuard(target);
         for (idx, url) in urls.iter().enumerate() {
-            match self
-                .fetch_internal(url, &mut temp_file, is_stream, concurrent_download)
+            let attempt = self.progress_attempt();
+            match attempt
+                .fetch_internal(url, target, is_stream, concurrent_download)
                 .await
             {
-                Ok(_) => return Ok(temp_file),
+                Ok(_) => {
+                    guard.disarm();
+                    return Ok(());
+                }
                 Err(e) => {
-                    if idx == urls.len() - 1 {
-                        temp_file.drop_async().await;
+                    // 失败的尝试计入的进度作废，避免下一个 url 重复计入总字节数
+                    if let Some(progress) = &attempt.progress {
+                        progress.rollback();
+                    }
+                    let is_last = idx == urls.len() - 1;
+                    // 已下载的部分不用于下一个 url，内容已损坏时也无法续传
+                    if !is_last || is_corrupted(&e) {
+                        remove_part_files(target).await;
+                    }
+                    if is_last {
+                        guard.disarm();
                         return Err(e).with_context(|| format!("failed to download file from all {} urls", urls.len()));
                     }
-                    temp_file.set_len(0).await?;
-                    temp_file.rewind().await?;
                 }
             }
         }
@@ -181,133 +324,520 @@ impl Downloader {
     async fn fetch_internal(
         &self,
         url: &str,
-        file: &mut TempFile,
+        part_path: &Path,
         is_stream: bool,
         concurrent_download: &ConcurrentDownloadLimit,
     ) -> Result<()> {
         if concurrent_download.enable {
-            self.fetch_parallel(url, file, is_stream, concurrent_download).await
+            self.fetch_parallel(url, part_path, is_stream, concurrent_download)
+                .await
         } else {
-            self.fetch_serial(url, file).await
+            self.fetch_serial(url, part_path).await
         }
     }
 
-    async fn fetch_serial(&self, url: &str, file: &mut TempFile) -> Result<()> {
-        let resp = self
+    /// 通过 aria2.addUri 提交下载任务，并轮询 aria2.tellStatus 直到下载完成
+    /// 下载失败或长时间没有进度时，从 aria2 中移除该任务并删除未下载完成的文件
+    async fn fetch_aria2(&self, urls: &[&str], path: &Path) -> Result<()> {
+        let DownloaderBackend::Aria2 {
+            rpc_url,
+            secret,
+            download_dir,
+            local_dir,
+        } = &self.backend
+        else {
+            bail!("aria2 backend is not enabled");
+        };
+        let secret = secret.as_deref();
+        let (Some(dir), Some(out)) = (path.parent(), path.file_name()) else {
+            bail!("invalid download path: {}", path.display());
+        };
+        // 设置了 aria2 一侧的下载目录时使用随机的文件名下载到该目录，local_path 为该文件在 bili-sync 一侧的路径
+        let (dir, out, local_path) = match download_dir {
+            Some(download_dir) => {
+                let out = format!("bili-sync-{}.download", uuid::Uuid::new_v4());
+                let local_dir = local_dir.clone().unwrap_or_else(|| PathBuf::from(download_dir));
+                (download_dir.clone(), out.clone(), local_dir.join(out))
+            }
+            None => (
+                dir.to_string_lossy().into_owned(),
+                out.to_string_lossy().into_owned(),
+                path.to_path_buf(),
+            ),
+        };
+        let mut options = json!({
+            "dir": dir,
+            "out": out,
+            "allow-overwrite": "true",
+            "auto-file-renaming": "false",
+            // 与 Native 后端保持一致，不设置 Referer 时会遇到 403 Forbidden 错误
+            "header": [
+                format!("User-Agent: {}", self.user_agent),
+                format!("Referer: {}", self.referer),
+            ],
+        });
+        if let Some(proxy) = &self.proxy {
+            options["all-proxy"] = json!(proxy);
+        }
+        let gid = self
+            .aria2_call(rpc_url, secret, "aria2.addUri", vec![json!(urls), options])
+            .await?;
+        let gid = gid.as_str().context("aria2.addUri returned an invalid gid")?.to_owned();
+        if let Err(e) = self.wait_aria2(rpc_url, secret, &gid).await {
+            // 尽力清理 aria2 中的任务与已下载的部分，清理失败不影响返回的错误
+            for method in ["aria2.forceRemove", "aria2.removeDownloadResult"] {
+                if let Err(err) = self.aria2_call(rpc_url, secret, method, vec![json!(gid)]).await {
+                    tracing::debug!("清理 aria2 下载任务 {} 失败：{:#}", gid, err);
+                }
+            }
+            let mut control_file = local_path.clone().into_os_string();
+            control_file.push(".aria2");
+            let _ = fs::remove_file(&local_path).await;
+            let _ = fs::remove_file(control_file).await;
+            return Err(e);
+        }
+        if download_dir.is_some() {
+            move_file(&local_path, path).await?;
+        }
+        Ok(())
+    }
+
+    /// 轮询 aria2.tellStatus 直到下载完成，持续 ARIA2_STALL_TIMEOUT 没有进度时返回错误
+    /// 与 Native 后端一致，收到停机信号时等待进行中的下载完成，避免停机被计为一次失败
+    async fn wait_aria2(&self, rpc_url: &str, secret: Option<&str>, gid: &str) -> Result<()> {
+        let (mut completed_length, mut last_progress) = (None, Instant::now());
+        loop {
+            let status = self
+                .aria2_call(
+                    rpc_url,
+                    secret,
+                    "aria2.tellStatus",
+                    vec![
+                        json!(gid),
+                        json!(["status", "completedLength", "errorCode", "errorMessage"]),
+                    ],
+                )
+                .await?;
+            match status["status"].as_str() {
+                Some("complete") => return Ok(()),
+                Some("active" | "waiting" | "paused") => {
+                    let length = status["completedLength"].as_str().map(ToOwned::to_owned);
+                    if length != completed_length {
+                        (completed_length, last_progress) = (length, Instant::now());
+                    }
+                    ensure!(
+                        last_progress.elapsed() < ARIA2_STALL_TIMEOUT,
+                        "aria2 download {} made no progress for {} seconds",
+                        gid,
+                        ARIA2_STALL_TIMEOUT.as_secs()
+                    );
+                    tokio::time::sleep(ARIA2_POLL_INTERVAL).await;
+                }
+                Some(state) => bail!(
+                    "aria2 download {} {}: [{}] {}",
+                    gid,
+                    state,
+                    status["errorCode"].as_str().unwrap_or_default(),
+                    status["errorMessage"].as_str().unwrap_or_default()
+                ),
+                None => bail!("aria2.tellStatus returned an invalid response: {}", status),
+            }
+        }
+    }
+
+    async fn aria2_call(
+        &self,
+        rpc_url: &str,
+        secret: Option<&str>,
+        method: &str,
+        mut params: Vec<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        if let Some(secret) = secret {
+            params.insert(0, json!(format!("token:{}", secret)));
+        }
+        let mut resp = self
             .client
-            .request(Method::GET, url, None)
+            .request(Method::POST, rpc_url, None)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": "bili-sync",
+                "method": method,
+                "params": params,
+            }))
             .send()
             .await?
-            .error_for_status()?;
-        let expected = resp.header_content_length();
-        let mut stream_reader = StreamReader::new(resp.bytes_stream().map_err(std::io::Error::other));
-        let received = tokio::io::copy(&mut stream_reader, file).await?;
+            .json::<serde_json::Value>()
+            .await?;
+        if let Some(error) = resp.get("error") {
+            bail!("aria2 rpc {} failed: {}", method, error);
+        }
+        Ok(resp["result"].take())
+    }
+
+    /// 下载到 part_path，已存在部分内容时使用 Range 请求剩余部分并追加写入，服务器不支持时重新下载完整文件
+    async fn fetch_serial(&self, url: &str, part_path: &Path) -> Result<()> {
+        let mut existing = file_len(part_path).await?;
+        let resp = loop {
+            let mut req = self.request(Method::GET, url);
+            if existing > 0 {
+                req = req.header(header::RANGE, format!("bytes={}-", existing));
+            }
+            let resp = req.send().await?;
+            if existing == 0 || resp.status() != StatusCode::RANGE_NOT_SATISFIABLE {
+                break resp.error_for_status()?;
+            }
+            // 上次中断时恰好已经下载完整，剩余部分为空
+            if resp.header_file_size() == Some(existing) {
+                return Ok(());
+            }
+            // 已下载的部分超出了服务器上的文件大小，说明文件已经变化，删除后重新下载完整文件
+            tracing::warn!(
+                "已下载的部分与服务器上的文件不一致，重新下载完整文件 {}",
+                part_path.display()
+            );
+            fs::remove_file(part_path).await?;
+            existing = 0;
+        };
+        let resumed =
+            existing > 0 && resp.status() == StatusCode::PARTIAL_CONTENT && resp.header_range_start() == Some(existing);
+        if existing > 0 && !resumed {
+            tracing::warn!(
+                "服务器未按 Range 返回剩余部分，重新下载完整文件 {}",
+                part_path.display()
+            );
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resumed)
+            .truncate(!resumed)
+            .open(part_path)
+            .await?;
+        // 续传时 Content-Length 与 Content-MD5 均只针对本次返回的剩余部分
+        let (expected, expected_md5) = (resp.header_content_length(), resp.header_content_md5());
+        self.report_total(expected);
+        let (mut received, mut md5_context) = (0u64, expected_md5.map(|_| md5::Context::new()));
+        let stream = resp.bytes_stream().inspect_ok(|chunk| {
+            received += chunk.len() as u64;
+            if let Some(md5_context) = md5_context.as_mut() {
+                md5_context.consume(chunk);
+            }
+        });
+        let mut stream_reader = StreamReader::new(throttle(stream, self.rate_limiter.clone(), self.progress.clone()));
+        let res = tokio::io::copy(&mut stream_reader, &mut file).await;
+        drop(stream_reader);
+        if let Err(e) = res {
+            // 连接提前断开导致内容不完整时，视为下载失败而非可忽略的网络错误
+            if let Some(expected) = expected
+                && received < expected
+            {
+                return Err(anyhow::Error::new(e).context(DownloadError::SizeMismatch { expected, received }));
+            }
+            return Err(e.into());
+        }
         file.flush().await?;
         if let Some(expected) = expected {
+            ensure!(received == expected, DownloadError::SizeMismatch { expected, received });
+        }
+        if let (Some(expected), Some(md5_context)) = (expected_md5, md5_context) {
+            let actual = md5_context.finalize().0;
             ensure!(
-                received == expected,
-                "downloaded bytes mismatch: expected {}, got {}",
-                expected,
-                received
+                actual == expected,
+                DownloadError::Md5Mismatch {
+                    expected: hex(&expected),
+                    actual: hex(&actual),
+                }
             );
         }
         Ok(())
     }
 
+    /// 将文件按照并发数分块下载到 part_path 同目录下的分块文件中，全部完成后按顺序合并为 part_path
+    /// 每个分块单独续传，中断后再次下载时只请求各个分块剩余的部分
     async fn fetch_parallel(
         &self,
         url: &str,
-        file: &mut TempFile,
+        part_path: &Path,
         is_stream: bool,
         concurrent_download: &ConcurrentDownloadLimit,
     ) -> Result<()> {
+        // 存在串行下载或合并分块时中断遗留的部分内容，继续串行下载剩余部分
+        if file_len(part_path).await? > 0 {
+            return self.fetch_serial(url, part_path).await;
+        }
         let (concurrency, threshold) = (concurrent_download.concurrency, concurrent_download.threshold);
         let file_size = if is_stream {
             // B 站视频、音频流存在 HEAD 为 404 但 GET 正常的情况，此处假设支持分块，直接使用携带 Range 头的 GET 请求探测
             let resp = self
-                .client
-                .request(Method::GET, url, None)
+                .request(Method::GET, url)
                 .header(header::RANGE, "bytes=0-0")
                 .send()
                 .await?
                 .error_for_status()?;
             if resp.status() != StatusCode::PARTIAL_CONTENT {
-                return self.fetch_serial(url, file).await;
+                return self.fetch_serial(url, part_path).await;
             }
             resp.header_file_size()
         } else {
             // 对于普通文件，直接使用常规的 HEAD 请求探测
-            let resp = self
-                .client
-                .request(Method::HEAD, url, None)
-                .send()
-                .await?
-                .error_for_status()?;
+            let resp = self.request(Method::HEAD, url).send().await?.error_for_status()?;
             if resp
                 .headers()
                 .get(header::ACCEPT_RANGES)
                 // https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Headers/Accept-Ranges#none
                 .is_none_or(|v| v.to_str().unwrap_or_default() == "none")
             {
-                return self.fetch_serial(url, file).await;
+                return self.fetch_serial(url, part_path).await;
             }
             resp.header_content_length()
         };
         let Some(file_size) = file_size else {
-            return self.fetch_serial(url, file).await;
+            return self.fetch_serial(url, part_path).await;
         };
         let chunk_size = file_size / concurrency as u64;
         if chunk_size < threshold {
-            return self.fetch_serial(url, file).await;
+            return self.fetch_serial(url, part_path).await;
         }
-        file.set_len(file_size).await?;
+        let chunks = (0..concurrency)
+            .map(|i| {
+                let start = i as u64 * chunk_size;
+                let end = if i == concurrency - 1 {
+                    file_size
+                } else {
+                    start + chunk_size
+                } - 1;
+                (start, end, chunk_path(part_path, start, end))
+            })
+            .collect::<Vec<_>>();
+        // 文件大小或并发数变化后，之前的分块无法继续使用
+        for path in chunk_files(part_path)? {
+            if chunks
+                .iter()
+                .all(|(_, _, chunk_path)| chunk_path.file_name() != path.file_name())
+            {
+                fs::remove_file(path).await?;
+            }
+        }
+        self.report_total(Some(file_size));
         let mut tasks = JoinSet::new();
         let url = Arc::new(url.to_string());
-        for i in 0..concurrency {
-            let start = i as u64 * chunk_size;
-            let end = if i == concurrency - 1 {
-                file_size
-            } else {
-                start + chunk_size
-            } - 1;
-            let (url_clone, client_clone) = (url.clone(), self.client.clone());
-            let mut file_clone = file.open_rw().await?;
-            tasks.spawn(async move {
-                file_clone.seek(SeekFrom::Start(start)).await?;
-                let range_header = format!("bytes={}-{}", start, end);
-                let resp = client_clone
-                    .request(Method::GET, &url_clone, None)
-                    .header(header::RANGE, &range_header)
-                    .send()
-                    .await?
-                    .error_for_status()?;
-                if let Some(content_length) = resp.header_content_length() {
-                    ensure!(
-                        content_length == end - start + 1,
-                        "content length mismatch: expected {}, got {}",
-                        end - start + 1,
-                        content_length
-                    );
-                }
-                let mut stream_reader = StreamReader::new(resp.bytes_stream().map_err(std::io::Error::other));
-                let received = tokio::io::copy(&mut stream_reader, &mut file_clone).await?;
-                file_clone.flush().await?;
-                ensure!(
-                    received == end - start + 1,
-                    "downloaded bytes mismatch: expected {}, got {}",
-                    end - start + 1,
-                    received,
-                );
-                Ok(())
-            });
+        for (start, end, chunk_path) in chunks.iter().cloned() {
+            let (url_clone, downloader) = (url.clone(), self.clone());
+            tasks.spawn(async move { downloader.fetch_chunk(&url_clone, &chunk_path, start, end).await });
         }
         while let Some(res) = tasks.join_next().await {
             res??;
         }
+        // 第一个分块直接作为 part_path，其余分块按顺序追加，追加完成的分块立即删除
+        fs::rename(&chunks[0].2, part_path).await?;
+        let mut file = fs::OpenOptions::new().append(true).open(part_path).await?;
+        for (_, _, chunk_path) in &chunks[1..] {
+            let mut chunk_file = fs::File::open(chunk_path).await?;
+            tokio::io::copy(&mut chunk_file, &mut file).await?;
+            file.flush().await?;
+            fs::remove_file(chunk_path).await?;
+        }
+        Ok(())
+    }
+
+    /// 下载 [start, end] 范围内的分块到 chunk_path，已存在部分内容时只请求剩余的部分
+    async fn fetch_chunk(&self, url: &str, chunk_path: &Path, start: u64, end: u64) -> Result<()> {
+        let chunk_len = end - start + 1;
+        // 分块文件超出分块大小时说明已经损坏，重新下载该分块
+        let existing = match file_len(chunk_path).await? {
+            existing if existing <= chunk_len => existing,
+            _ => 0,
+        };
+        if existing == chunk_len {
+            return Ok(());
+        }
+        if let (Some(progress), true) = (&self.progress, existing > 0) {
+            progress.add_downloaded(existing);
+        }
+        let remaining = chunk_len - existing;
+        let resp = self
+            .request(Method::GET, url)
+            .header(header::RANGE, format!("bytes={}-{}", start + existing, end))
+            .send()
+            .await?
+            .error_for_status()?;
+        if let Some(content_length) = resp.header_content_length() {
+            ensure!(
+                content_length == remaining,
+                "content length mismatch: expected {}, got {}",
+                remaining,
+                content_length
+            );
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(existing > 0)
+            .truncate(existing == 0)
+            .open(chunk_path)
+            .await?;
+        let mut stream_reader = StreamReader::new(throttle(
+            resp.bytes_stream(),
+            self.rate_limiter.clone(),
+            self.progress.clone(),
+        ));
+        let received = tokio::io::copy(&mut stream_reader, &mut file).await?;
+        file.flush().await?;
+        ensure!(
+            received == remaining,
+            DownloadError::SizeMismatch {
+                expected: remaining,
+                received,
+            }
+        );
         Ok(())
     }
 }
 
+/// 未下载完成的文件使用的路径，即在原文件名后追加 .part
+fn part_path(path: &Path) -> PathBuf {
+    let mut part_path = path.as_os_str().to_owned();
+    part_path.push(".part");
+    PathBuf::from(part_path)
+}
+
+/// 未下载完成的视频流或音频流使用的路径，即在原文件名后追加 .{kind}.part
+fn stream_part_path(path: &Path, kind: &str) -> PathBuf {
+    let mut part_path = path.as_os_str().to_owned();
+    part_path.push(format!(".{}.part", kind));
+    PathBuf::from(part_path)
+}
+
+/// 分块下载时 [start, end] 范围内的分块使用的路径，即在 part_path 后追加 .{start}-{end}
+fn chunk_path(part_path: &Path, start: u64, end: u64) -> PathBuf {
+    let mut chunk_path = part_path.as_os_str().to_owned();
+    chunk_path.push(format!(".{}-{}", start, end));
+    PathBuf::from(chunk_path)
+}
+
+/// 列出 part_path 同目录下属于它的所有分块文件，仅读取一次目录，为了能在 Drop 中使用而采用同步实现
+fn chunk_files(part_path: &Path) -> Result<Vec<PathBuf>> {
+    let (Some(dir), Some(name)) = (part_path.parent(), part_path.file_name()) else {
+        return Ok(Vec::new());
+    };
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let prefix = format!("{}.", name.to_string_lossy());
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut chunk_files = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            chunk_files.push(entry.path());
+        }
+    }
+    Ok(chunk_files)
+}
+
+/// 删除 part_path 及其分块文件，删除失败时仅记录日志
+async fn remove_part_files(part_path: &Path) {
+    let chunk_files = chunk_files(part_path).unwrap_or_default();
+    for path in std::iter::once(part_path.to_path_buf()).chain(chunk_files) {
+        if let Err(e) = fs::remove_file(&path).await
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!("删除未下载完成的文件 {} 失败：{}", path.display(), e);
+        }
+    }
+}
+
+/// 下载过程中被丢弃（如强制停机时中止了下载任务）时删除 part_path 及其分块文件，避免在媒体库中留下写入到一半的文件
+/// 正常返回前需要调用 disarm，此时已经无法等待异步操作完成，因此同步删除
+struct PartFilesGuard<'a>(&'a Path);
+
+impl PartFilesGuard<'_> {
+    fn disarm(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for PartFilesGuard<'_> {
+    fn drop(&mut self) {
+        let chunk_files = chunk_files(self.0).unwrap_or_default();
+        for path in std::iter::once(self.0.to_path_buf()).chain(chunk_files) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// 下载的内容与校验信息不符，说明已下载的部分已经损坏，不能用于续传
+/// 连接提前断开等只下载了部分内容的情况不属于损坏，已下载的部分仍然有效
+fn is_corrupted(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<DownloadError>() {
+        Some(DownloadError::Md5Mismatch { .. }) => true,
+        Some(DownloadError::SizeMismatch { expected, received }) => received > expected,
+        None => false,
+    }
+}
+
+/// 获取文件大小，文件不存在时返回 0
+async fn file_len(path: &Path) -> Result<u64> {
+    match fs::metadata(path).await {
+        Ok(metadata) => Ok(metadata.len()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 为字节流附加限速，每个分块在交给下游前都需要从令牌桶中获取与其大小相等的令牌
+/// 指定了 progress 时，交给下游的字节数会计入对应视频的下载进度
+fn throttle<S, B>(
+    stream: S,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    progress: Option<ProgressReporter>,
+) -> impl Stream<Item = std::io::Result<B>>
+where
+    S: Stream<Item = reqwest::Result<B>>,
+    B: AsRef<[u8]>,
+{
+    stream.map_err(std::io::Error::other).and_then(move |chunk| {
+        let (rate_limiter, progress) = (rate_limiter.clone(), progress.clone());
+        async move {
+            if let Some(rate_limiter) = rate_limiter {
+                // 单次获取的令牌数不能超过令牌桶的容量，较大的分块需要拆分获取
+                let mut remaining = chunk.as_ref().len();
+                while remaining > 0 {
+                    let permits = remaining.min(rate_limiter.max());
+                    rate_limiter.acquire(permits).await;
+                    remaining -= permits;
+                }
+            }
+            if let Some(progress) = progress {
+                progress.add_downloaded(chunk.as_ref().len() as u64);
+            }
+            Ok(chunk)
+        }
+    })
+}
+
+/// 将文件移动到目标路径，跨文件系统时退化为复制后删除原文件
+async fn move_file(from: &Path, to: &Path) -> Result<()> {
+    match fs::rename(from, to).await {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            fs::copy(from, to).await?;
+            fs::remove_file(from).await?;
+        }
+        res => res.with_context(|| format!("failed to move {} to {}", from.display(), to.display()))?,
+    }
+    Ok(())
+}
+
 /// reqwest.content_length() 居然指的是 body_size 而非 content-length header，没办法自己实现一下
 /// https://github.com/seanmonstar/reqwest/issues/1814
 trait ResponseExt {
@@ -315,6 +845,10 @@ trait ResponseExt {
     fn header_content_length(&self) -> Option<u64>;
     /// 获取 Content-Range 头中的文件总大小部分
     fn header_file_size(&self) -> Option<u64>;
+    /// 获取 Content-Range 头中的起始位置部分
+    fn header_range_start(&self) -> Option<u64>;
+    /// 获取 Content-MD5 头中经过 base64 编码的 md5 值
+    fn header_content_md5(&self) -> Option<[u8; 16]>;
 }
 
 impl ResponseExt for reqwest::Response {
@@ -335,25 +869,638 @@ impl ResponseExt for reqwest::Response {
             })
             .and_then(|(_, size_str)| size_str.parse::<u64>().ok())
     }
+
+    fn header_range_start(&self) -> Option<u64> {
+        self.headers()
+            .get(header::CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| {
+                // Content-Range: bytes 100-800945/800946
+                s.strip_prefix("bytes ")?.split_once('-')
+            })
+            .and_then(|(start_str, _)| start_str.parse::<u64>().ok())
+    }
+
+    fn header_content_md5(&self) -> Option<[u8; 16]> {
+        self.headers()
+            .get("content-md5")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| BASE64_STANDARD.decode(s.trim()).ok())
+            .and_then(|bytes| bytes.try_into().ok())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::path::Path;
 
+    use std::sync::Arc;
+
     use anyhow::Result;
+    use axum::Json;
+    use axum::http::HeaderMap;
+    use axum::response::IntoResponse;
+    use parking_lot::Mutex;
+    use reqwest::{StatusCode, header};
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-    use crate::bilibili::{BestStream, BiliClient, Video};
-    use crate::config::VersionedConfig;
+    use crate::bilibili::{BestStream, BiliClient, Client, Video};
+    use crate::config::{ConcurrentDownloadLimit, Config, DownloaderBackend, ScheduleWindow, VersionedConfig};
     use crate::database::setup_database;
-    use crate::downloader::Downloader;
+    use crate::downloader::{Downloader, build_rate_limiters, chunk_path, part_path};
+    use crate::error::ExecutionStatus;
+    use crate::utils::progress::{ProgressRegistry, VideoProgress};
+    use crate::utils::test_utils::TestDatabase;
+
+    #[tokio::test]
+    async fn test_download_rate_limit() -> Result<()> {
+        const BODY_SIZE: usize = 64 * 1024;
+        const RATE_LIMIT: u64 = 32 * 1024;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let app = axum::Router::new().route("/file", axum::routing::get(|| async { vec![0u8; BODY_SIZE] }));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let downloader = Downloader::new(Client::new(), Some(RATE_LIMIT), DownloaderBackend::Native);
+        let path = std::env::temp_dir().join(format!("bili-sync-rate-limit-{}", uuid::Uuid::new_v4()));
+        let start = std::time::Instant::now();
+        downloader
+            .fetch(
+                &format!("http://{}/file", addr),
+                &path,
+                &ConcurrentDownloadLimit {
+                    enable: false,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let elapsed = start.elapsed();
+        assert_eq!(tokio::fs::metadata(&path).await?.len(), BODY_SIZE as u64);
+        tokio::fs::remove_file(&path).await?;
+        // 令牌桶初始为空，64KB 的内容在 32KB/s 的限速下至少需要约 2 秒，留出一定的误差
+        assert!(
+            elapsed.as_secs_f64() >= 1.8,
+            "download finished too fast: {:?}",
+            elapsed
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_rate_limiters() -> Result<()> {
+        let config = Config {
+            download_rate_limit_bytes: Some(1 << 20),
+            schedule: vec![
+                ScheduleWindow {
+                    cron_window: "* * 9-18 * * 1-5".to_owned(),
+                    concurrent_limit: None,
+                    rate_limit: Some(1 << 10),
+                },
+                ScheduleWindow {
+                    cron_window: "* * 0-6 * * *".to_owned(),
+                    concurrent_limit: None,
+                    rate_limit: Some(1 << 20),
+                },
+                ScheduleWindow {
+                    cron_window: "* * 7-8 * * *".to_owned(),
+                    concurrent_limit: None,
+                    rate_limit: None,
+                },
+            ],
+            ..Default::default()
+        };
+        // 相同的限速只构建一个限速器，供全局配置与对应的时间段共享
+        let limiters = build_rate_limiters(&config)?;
+        assert_eq!(limiters.len(), 2);
+        assert_eq!(limiters[&(1 << 20)].max(), 1 << 20);
+        assert_eq!(limiters[&(1 << 10)].max(), 1 << 10);
+        assert!(build_rate_limiters(&Config::default())?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resume_partial_download() -> Result<()> {
+        const CONTENT: &[u8] = b"hello, bili-sync";
+        // /range 会按照 Range 头返回剩余部分，/full 则忽略 Range 头始终返回完整内容
+        let ranges = Arc::new(Mutex::new(Vec::<Option<String>>::new()));
+        let ranges_clone = ranges.clone();
+        let app = axum::Router::new()
+            .route(
+                "/range",
+                axum::routing::get(move |headers: HeaderMap| {
+                    let ranges = ranges_clone.clone();
+                    async move {
+                        let range = headers.get(header::RANGE).map(|v| v.to_str().unwrap().to_owned());
+                        ranges.lock().push(range.clone());
+                        let start = range
+                            .and_then(|range| range.strip_prefix("bytes=")?.strip_suffix('-')?.parse::<usize>().ok())
+                            .unwrap_or_default();
+                        (
+                            StatusCode::PARTIAL_CONTENT,
+                            [(
+                                header::CONTENT_RANGE,
+                                format!("bytes {}-{}/{}", start, CONTENT.len() - 1, CONTENT.len()),
+                            )],
+                            &CONTENT[start..],
+                        )
+                    }
+                }),
+            )
+            .route("/full", axum::routing::get(|| async { CONTENT }))
+            // /shrunk 上的文件已经变小，续传请求的起始位置超出了文件大小
+            .route(
+                "/shrunk",
+                axum::routing::get(|headers: HeaderMap| async move {
+                    match headers.get(header::RANGE) {
+                        Some(_) => (
+                            StatusCode::RANGE_NOT_SATISFIABLE,
+                            [(header::CONTENT_RANGE, "bytes */3")],
+                        )
+                            .into_response(),
+                        None => "abc".into_response(),
+                    }
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let downloader = Downloader::new(Client::new(), None, DownloaderBackend::Native);
+        let test_dir = std::env::temp_dir().join(format!("bili-sync-resume-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&test_dir).await?;
+        let concurrent_download = ConcurrentDownloadLimit {
+            enable: false,
+            ..Default::default()
+        };
+        for route in ["range", "full"] {
+            let path = test_dir.join(format!("{}.jpg", route));
+            tokio::fs::write(part_path(&path), &CONTENT[..5]).await?;
+            downloader
+                .fetch(&format!("http://{}/{}", addr, route), &path, &concurrent_download)
+                .await?;
+            assert_eq!(tokio::fs::read(&path).await?, CONTENT);
+            assert!(!tokio::fs::try_exists(part_path(&path)).await?);
+        }
+        assert_eq!(*ranges.lock(), [Some("bytes=5-".to_owned())]);
+        // 返回 416 且文件大小与已下载的部分不一致时，删除 .part 后重新下载完整文件
+        let path = test_dir.join("shrunk.jpg");
+        tokio::fs::write(part_path(&path), &CONTENT[..5]).await?;
+        downloader
+            .fetch(&format!("http://{}/shrunk", addr), &path, &concurrent_download)
+            .await?;
+        assert_eq!(tokio::fs::read(&path).await?, b"abc");
+        assert!(!tokio::fs::try_exists(part_path(&path)).await?);
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resume_chunked_download() -> Result<()> {
+        const CONTENT: &[u8] = b"hello, bili-sync";
+        // 按照 Range 头返回对应的部分，/missing 始终返回 404
+        let ranges = Arc::new(Mutex::new(Vec::<String>::new()));
+        let ranges_clone = ranges.clone();
+        let app = axum::Router::new().route(
+            "/video.m4s",
+            axum::routing::get(move |headers: HeaderMap| {
+                let ranges = ranges_clone.clone();
+                async move {
+                    let range = headers[header::RANGE].to_str().unwrap().to_owned();
+                    ranges.lock().push(range.clone());
+                    let (start, end) = range.strip_prefix("bytes=").unwrap().split_once('-').unwrap();
+                    let (start, end) = (start.parse::<usize>().unwrap(), end.parse::<usize>().unwrap());
+                    (
+                        StatusCode::PARTIAL_CONTENT,
+                        [(
+                            header::CONTENT_RANGE,
+                            format!("bytes {}-{}/{}", start, end, CONTENT.len()),
+                        )],
+                        &CONTENT[start..=end],
+                    )
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let downloader = Downloader::new(Client::new(), None, DownloaderBackend::Native);
+        let db = TestDatabase::new().await?;
+        let path = db.dir.join("video.mp4");
+        let concurrent_download = ConcurrentDownloadLimit {
+            enable: true,
+            concurrency: 2,
+            threshold: 1,
+        };
+        // 第二个分块已下载 3 字节，另有一个分块方式不同的历史分块
+        let part_path = part_path(&path);
+        tokio::fs::write(chunk_path(&part_path, 8, 15), &CONTENT[8..11]).await?;
+        tokio::fs::write(chunk_path(&part_path, 0, 3), b"????").await?;
+        downloader
+            .multi_fetch(
+                &[format!("http://{}/video.m4s", addr).as_str()],
+                &path,
+                &concurrent_download,
+            )
+            .await?;
+        assert_eq!(tokio::fs::read(&path).await?, CONTENT);
+        let mut ranges = ranges.lock().clone();
+        ranges.sort();
+        assert_eq!(ranges, ["bytes=0-0", "bytes=0-7", "bytes=11-15"]);
+        let mut entries = tokio::fs::read_dir(&db.dir).await?;
+        let mut files = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if file_name.starts_with("video.mp4") {
+                files.push(file_name);
+            }
+        }
+        assert_eq!(files, ["video.mp4"]);
+        // 下载失败时保留已下载的分块，下次下载时继续
+        let path = db.dir.join("missing.mp4");
+        tokio::fs::write(chunk_path(&part_path(&path), 0, 7), &CONTENT[..3]).await?;
+        assert!(
+            downloader
+                .multi_fetch(
+                    &[format!("http://{}/missing.m4s", addr).as_str()],
+                    &path,
+                    &concurrent_download,
+                )
+                .await
+                .is_err()
+        );
+        assert!(tokio::fs::try_exists(chunk_path(&part_path(&path), 0, 7)).await?);
+        // 切换到下一个 url 时删除之前 url 下载的部分，从头下载
+        downloader
+            .multi_fetch(
+                &[
+                    format!("http://{}/missing.m4s", addr).as_str(),
+                    format!("http://{}/video.m4s", addr).as_str(),
+                ],
+                &path,
+                &concurrent_download,
+            )
+            .await?;
+        assert_eq!(tokio::fs::read(&path).await?, CONTENT);
+        assert!(!tokio::fs::try_exists(chunk_path(&part_path(&path), 0, 7)).await?);
+        assert!(!tokio::fs::try_exists(part_path(&path)).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_headers() -> Result<()> {
+        let headers = Arc::new(Mutex::new(Vec::<(String, String)>::new()));
+        let headers_clone = headers.clone();
+        let app = axum::Router::new().route(
+            "/cover.jpg",
+            axum::routing::get(move |request_headers: HeaderMap| {
+                let headers = headers_clone.clone();
+                async move {
+                    let header_value = |name| {
+                        request_headers
+                            .get(name)
+                            .map(|v| v.to_str().unwrap().to_owned())
+                            .unwrap_or_default()
+                    };
+                    headers
+                        .lock()
+                        .push((header_value(header::USER_AGENT), header_value(header::REFERER)));
+                    "cover"
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let config = Config {
+            download_user_agent: Some("Mozilla/5.0 bili-sync-test".to_owned()),
+            download_referer: "https://www.bilibili.com/video/".to_owned(),
+            ..Default::default()
+        };
+        let downloader = Downloader::from_config(Client::new(), &config);
+        let path = std::env::temp_dir().join(format!("bili-sync-headers-{}.jpg", uuid::Uuid::new_v4()));
+        downloader
+            .fetch(
+                &format!("http://{}/cover.jpg", addr),
+                &path,
+                &ConcurrentDownloadLimit {
+                    enable: false,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        tokio::fs::remove_file(&path).await?;
+        assert_eq!(
+            *headers.lock(),
+            [(
+                "Mozilla/5.0 bili-sync-test".to_owned(),
+                "https://www.bilibili.com/video/".to_owned()
+            )]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_incomplete_download_failed() -> Result<()> {
+        // 声明 1024 字节却只返回 16 字节后断开连接，模拟 CDN 节点提前结束响应
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let short_read_url = format!("http://{}/video.m4s", listener.local_addr()?);
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await?;
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await?;
+            assert!(n > 0);
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1024\r\n\r\n0123456789abcdef")
+                .await?;
+            anyhow::Ok(())
+        });
+        // 返回完整内容，但 Content-MD5 与内容不符
+        let app = axum::Router::new().route(
+            "/video.m4s",
+            axum::routing::get(|| async { ([("content-md5", "XUFAKrxLKna5cZ2REBfFkg==")], "corrupted") }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let corrupted_url = format!("http://{}/video.m4s", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let downloader = Downloader::new(Client::new(), None, DownloaderBackend::Native);
+        let path = std::env::temp_dir().join(format!("bili-sync-incomplete-{}.mp4", uuid::Uuid::new_v4()));
+        let concurrent_download = ConcurrentDownloadLimit {
+            enable: false,
+            ..Default::default()
+        };
+        // 提前断开时已下载的部分保留用于续传，内容损坏时删除
+        for (url, part_kept) in [(short_read_url, true), (corrupted_url, false)] {
+            let res = downloader
+                .multi_fetch(&[url.as_str()], &path, &concurrent_download)
+                .await
+                .map(|_| ExecutionStatus::Succeeded);
+            assert!(matches!(ExecutionStatus::from(res), ExecutionStatus::Failed(_)));
+            assert!(!tokio::fs::try_exists(&path).await?);
+            assert_eq!(tokio::fs::try_exists(part_path(&path)).await?, part_kept);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_progress_rollback_on_fallback() -> Result<()> {
+        // 第一个 url 声明 1024 字节却只返回 16 字节后断开连接，第二个 url 返回完整内容
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let short_read_url = format!("http://{}/video.m4s", listener.local_addr()?);
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await?;
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await?;
+            assert!(n > 0);
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1024\r\n\r\n0123456789abcdef")
+                .await?;
+            anyhow::Ok(())
+        });
+        let app = axum::Router::new().route("/video.m4s", axum::routing::get(|| async { "complete" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let complete_url = format!("http://{}/video.m4s", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let video_id = -rand::random_range(1..=i32::MAX);
+        let downloader = Downloader::new(Client::new(), None, DownloaderBackend::Native).with_progress(video_id, 1);
+        let path = std::env::temp_dir().join(format!("bili-sync-progress-{}.mp4", uuid::Uuid::new_v4()));
+        let concurrent_download = ConcurrentDownloadLimit {
+            enable: false,
+            ..Default::default()
+        };
+        downloader
+            .multi_fetch(
+                &[short_read_url.as_str(), complete_url.as_str()],
+                &path,
+                &concurrent_download,
+            )
+            .await?;
+        // 失败的尝试计入的字节数被扣除，进度不会超过 100%
+        let progress = ProgressRegistry::get().progress(video_id);
+        ProgressRegistry::get().remove(video_id);
+        assert_eq!(
+            progress,
+            Some(VideoProgress {
+                downloaded_bytes: 8,
+                total_bytes: 8,
+            })
+        );
+        tokio::fs::remove_file(&path).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_aborted_download_removes_part_file() -> Result<()> {
+        // 返回 16 字节后挂起连接，模拟强制停机时仍在进行中的下载
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/video.m4s", listener.local_addr()?);
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await?;
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await?;
+            assert!(n > 0);
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1024\r\n\r\n0123456789abcdef")
+                .await?;
+            std::future::pending::<()>().await;
+            anyhow::Ok(())
+        });
+        let downloader = Downloader::new(Client::new(), None, DownloaderBackend::Native);
+        let path = std::env::temp_dir().join(format!("bili-sync-aborted-{}.jpg", uuid::Uuid::new_v4()));
+        let concurrent_download = ConcurrentDownloadLimit {
+            enable: false,
+            ..Default::default()
+        };
+        let res = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            downloader.fetch(&url, &path, &concurrent_download),
+        )
+        .await;
+        assert!(res.is_err());
+        assert!(!tokio::fs::try_exists(&path).await?);
+        assert!(!tokio::fs::try_exists(part_path(&path)).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_aria2_fetch() -> Result<()> {
+        // 模拟 aria2 的 JSON-RPC 接口：添加任务后前两次查询返回下载中，之后写入文件并返回下载完成
+        let requests = Arc::new(Mutex::new(Vec::<serde_json::Value>::new()));
+        let requests_clone = requests.clone();
+        let app = axum::Router::new().route(
+            "/jsonrpc",
+            axum::routing::post(move |Json(request): Json<serde_json::Value>| {
+                let requests = requests_clone.clone();
+                async move {
+                    let polled = {
+                        let mut requests = requests.lock();
+                        requests.push(request.clone());
+                        requests.len() - 1
+                    };
+                    let result = match request["method"].as_str() {
+                        Some("aria2.addUri") => json!("2089b05ecca3d829"),
+                        Some("aria2.tellStatus") if polled < 3 => json!({"status": "active"}),
+                        Some("aria2.tellStatus") => {
+                            let options = requests.lock()[0]["params"][2].clone();
+                            let path = std::path::Path::new(options["dir"].as_str().unwrap())
+                                .join(options["out"].as_str().unwrap());
+                            tokio::fs::write(path, "aria2").await.unwrap();
+                            json!({"status": "complete"})
+                        }
+                        _ => unreachable!(),
+                    };
+                    Json(json!({"jsonrpc": "2.0", "id": request["id"], "result": result}))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let rpc_url = format!("http://{}/jsonrpc", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let config = Config {
+            proxy: Some("http://127.0.0.1:7890".to_owned()),
+            downloader_backend: DownloaderBackend::Aria2 {
+                rpc_url,
+                secret: Some("secret".to_owned()),
+                download_dir: None,
+                local_dir: None,
+            },
+            ..Default::default()
+        };
+        let downloader = Downloader::from_config(Client::new(), &config);
+        let test_dir = std::env::temp_dir().join(format!("bili-sync-aria2-{}", uuid::Uuid::new_v4()));
+        let path = test_dir.join("cover.jpg");
+        downloader
+            .fetch(
+                "https://example.com/cover.jpg",
+                &path,
+                &ConcurrentDownloadLimit::default(),
+            )
+            .await?;
+        assert_eq!(tokio::fs::read_to_string(&path).await?, "aria2");
+        let requests = requests.lock().clone();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[0]["method"], "aria2.addUri");
+        assert_eq!(requests[0]["params"][0], "token:secret");
+        assert_eq!(requests[0]["params"][1], json!(["https://example.com/cover.jpg"]));
+        assert_eq!(requests[0]["params"][2]["out"], "cover.jpg");
+        assert_eq!(requests[0]["params"][2]["all-proxy"], "http://127.0.0.1:7890");
+        assert!(
+            requests[1..]
+                .iter()
+                .all(|request| request["method"] == "aria2.tellStatus" && request["params"][1] == "2089b05ecca3d829")
+        );
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_aria2_multi_fetch() -> Result<()> {
+        // 模拟 aria2 的 JSON-RPC 接口：url 包含 missing 的任务写入部分内容后失败，其余任务写入完整内容后完成
+        let requests = Arc::new(Mutex::new(Vec::<serde_json::Value>::new()));
+        let requests_clone = requests.clone();
+        let app = axum::Router::new().route(
+            "/jsonrpc",
+            axum::routing::post(move |Json(request): Json<serde_json::Value>| {
+                let requests = requests_clone.clone();
+                async move {
+                    let index = {
+                        let mut requests = requests.lock();
+                        requests.push(request.clone());
+                        requests.len() - 1
+                    };
+                    let result = match request["method"].as_str() {
+                        Some("aria2.addUri") => json!(index.to_string()),
+                        Some("aria2.tellStatus") => {
+                            let add_uri = requests.lock()
+                                [request["params"][0].as_str().unwrap().parse::<usize>().unwrap()]
+                            .clone();
+                            let options = &add_uri["params"][1];
+                            let path = std::path::Path::new(options["dir"].as_str().unwrap())
+                                .join(options["out"].as_str().unwrap());
+                            if add_uri["params"][0][0].as_str().unwrap().contains("missing") {
+                                tokio::fs::write(path, "partial").await.unwrap();
+                                json!({"status": "error", "errorCode": "3", "errorMessage": "Resource not found"})
+                            } else {
+                                tokio::fs::write(path, "aria2").await.unwrap();
+                                json!({"status": "complete"})
+                            }
+                        }
+                        Some("aria2.forceRemove" | "aria2.removeDownloadResult") => json!("OK"),
+                        _ => unreachable!(),
+                    };
+                    Json(json!({"jsonrpc": "2.0", "id": request["id"], "result": result}))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let rpc_url = format!("http://{}/jsonrpc", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let db = TestDatabase::new().await?;
+        let download_dir = db.dir.join("aria2");
+        tokio::fs::create_dir_all(&download_dir).await?;
+        let downloader = Downloader::new(
+            Client::new(),
+            None,
+            DownloaderBackend::Aria2 {
+                rpc_url,
+                secret: None,
+                download_dir: Some(download_dir.to_string_lossy().into_owned()),
+                local_dir: None,
+            },
+        );
+        // aria2 下载到单独的下载目录，完成后移动到目标路径，多个 url 作为镜像一并提交
+        let path = db.dir.join("videos").join("video.mp4");
+        let urls = ["https://example.com/video.m4s", "https://mirror.example.com/video.m4s"];
+        downloader
+            .multi_fetch(&urls, &path, &ConcurrentDownloadLimit::default())
+            .await?;
+        assert_eq!(tokio::fs::read_to_string(&path).await?, "aria2");
+        assert!(tokio::fs::read_dir(&download_dir).await?.next_entry().await?.is_none());
+        let add_uri = requests.lock()[0].clone();
+        assert_eq!(add_uri["params"][0], json!(urls));
+        assert_eq!(add_uri["params"][1]["dir"], download_dir.to_string_lossy().as_ref());
+        assert!(add_uri["params"][1]["out"].as_str().unwrap().starts_with("bili-sync-"));
+        // 下载失败时从 aria2 中移除任务，并删除已下载的部分
+        requests.lock().clear();
+        let path = db.dir.join("videos").join("missing.mp4");
+        assert!(
+            downloader
+                .multi_fetch(
+                    &["https://example.com/missing.m4s"],
+                    &path,
+                    &ConcurrentDownloadLimit::default()
+                )
+                .await
+                .is_err()
+        );
+        assert!(!path.exists());
+        assert!(tokio::fs::read_dir(&download_dir).await?.next_entry().await?.is_none());
+        let methods = requests
+            .lock()
+            .iter()
+            .map(|request| {
+                (
+                    request["method"].as_str().unwrap().to_owned(),
+                    request["params"][0].clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            methods,
+            [
+                ("aria2.addUri".to_owned(), json!(["https://example.com/missing.m4s"])),
+                ("aria2.tellStatus".to_owned(), json!("0")),
+                ("aria2.forceRemove".to_owned(), json!("0")),
+                ("aria2.removeDownloadResult".to_owned(), json!("0")),
+            ]
+        );
+        Ok(())
+    }
 
     #[ignore = "only for manual test"]
     #[tokio::test(flavor = "multi_thread")]
     async fn test_parse_and_download_video() -> Result<()> {
         VersionedConfig::init_for_test(&setup_database(Path::new("./test.sqlite")).await?).await?;
         let config = VersionedConfig::get().read();
-        let client = BiliClient::new();
+        let client = BiliClient::new()?;
         let video = Video::new(&client, "BV1QJmaYKEv4".to_owned(), &config.credential);
         let pages = video.get_pages().await.expect("failed to get pages");
         let first_page = pages.into_iter().next().expect("no page found");
@@ -375,12 +1522,13 @@ mod tests {
         };
         dbg!(&video);
         dbg!(&audio);
-        let downloader = Downloader::new(client.client);
+        let downloader = Downloader::from_config(client.client, &config);
         downloader
             .multi_fetch_and_merge(
                 &video.urls(true),
-                &audio.urls(true),
+                Some(&audio.urls(true)),
                 Path::new("./output.mp4"),
+                VideoContainer::Mp4,
                 &config.concurrent_limit.download,
             )
             .await
@@ -1,6 +1,15 @@
 use std::io;
 
 use anyhow::Result;
+use thiserror::Error;
+
+use crate::bilibili::BiliError;
+use crate::downloader::DownloadError;
+
+/// 收到停机信号后，尚未开始的视频与分页不再处理，以该错误结束对应的任务
+#[derive(Error, Debug)]
+#[error("download cancelled due to shutdown")]
+pub struct DownloadCancelled;
 
 pub enum ExecutionStatus {
     Skipped,
@@ -12,11 +21,26 @@ pub enum ExecutionStatus {
 }
 
 // 目前 stable rust 似乎不支持自定义类型使用 ? 运算符，只能先在返回值使用 Result，再这样套层娃
+// 不读取配置中的 ignored_error_codes，下载任务中应当使用 DownloadContext::execution_status 按本次执行的配置转换
 impl From<Result<ExecutionStatus>> for ExecutionStatus {
     fn from(res: Result<ExecutionStatus>) -> Self {
+        Self::from_result(res, &[])
+    }
+}
+
+impl ExecutionStatus {
+    /// 将任务的执行结果转换为状态，以下错误始终被忽略而不标记为失败：
+    /// 1. 文件权限错误；
+    /// 2. 请求超时、响应体读取或解码失败等网络错误（下载内容不完整的情况除外）；
+    /// 3. B 站接口返回的错误码位于 ignored_error_codes 中。
+    pub fn from_result(res: Result<ExecutionStatus>, ignored_error_codes: &[i64]) -> Self {
         match res {
             Ok(status) => status,
             Err(err) => {
+                // 下载的内容不完整或已损坏，即使由网络错误导致也需要标记为失败
+                if err.chain().any(|cause| cause.is::<DownloadError>()) {
+                    return ExecutionStatus::Failed(err);
+                }
                 for cause in err.chain() {
                     if let Some(io_err) = cause.downcast_ref::<io::Error>() {
                         // 权限错误
@@ -38,6 +62,12 @@ impl From<Result<ExecutionStatus>> for ExecutionStatus {
                     {
                         return ExecutionStatus::Ignored(err);
                     }
+                    // 用户配置为可忽略的 B 站错误码
+                    if let Some(BiliError::ErrorResponse(code, _)) = cause.downcast_ref::<BiliError>()
+                        && ignored_error_codes.contains(code)
+                    {
+                        return ExecutionStatus::Ignored(err);
+                    }
                 }
                 ExecutionStatus::Failed(err)
             }
@@ -48,3 +78,30 @@ impl From<Result<ExecutionStatus>> for ExecutionStatus {
 fn is_ignored_reqwest_error(err: &reqwest::Error) -> bool {
     err.is_decode() || err.is_body() || err.is_timeout()
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn test_ignored_error_codes() {
+        let result = || -> Result<ExecutionStatus> {
+            Err(BiliError::ErrorResponse(62002, "稿件不可见".to_owned())).context("fetch video detail failed")
+        };
+        assert!(matches!(
+            ExecutionStatus::from_result(result(), &[-404, 62002]),
+            ExecutionStatus::Ignored(_)
+        ));
+        // 从列表中移除后恢复为默认行为，标记为失败
+        assert!(matches!(
+            ExecutionStatus::from_result(result(), &[-404]),
+            ExecutionStatus::Failed(_)
+        ));
+        assert!(matches!(
+            ExecutionStatus::from_result(result(), &[]),
+            ExecutionStatus::Failed(_)
+        ));
+    }
+}
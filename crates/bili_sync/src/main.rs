@@ -17,24 +17,26 @@ use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bilibili::BiliClient;
 use parking_lot::RwLock;
 use sea_orm::DatabaseConnection;
-use task::{http_server, video_downloader};
+use task::{http_server, shutdown_video_downloader, video_downloader};
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::TaskTracker;
 
 use crate::api::{LogHelper, MAX_HISTORY_LOGS};
 use crate::config::{ARGS, CONFIG_DIR, VersionedConfig};
-use crate::database::setup_database;
+use crate::database::{DATABASE_FILE_NAME, setup_database};
 use crate::utils::init_logger;
+use crate::utils::rotating_file::LogRotation;
 use crate::utils::signal::terminate;
 
 #[tokio::main]
 async fn main() {
     let (connection, log_writer) = init().await;
-    let bili_client = Arc::new(BiliClient::new());
+    let bili_client = Arc::new(BiliClient::new().expect("Bilibili 客户端初始化失败"));
 
     let token = CancellationToken::new();
     let tracker = TaskTracker::new();
@@ -78,14 +80,19 @@ fn spawn_task(
 
 /// 初始化日志系统、打印欢迎信息，初始化数据库连接和全局配置
 async fn init() -> (DatabaseConnection, LogHelper) {
-    let (tx, _rx) = tokio::sync::broadcast::channel(30);
+    // 实时日志的广播缓冲区，推送过慢的客户端会丢弃最旧的日志，不会阻塞日志的写入
+    let (tx, _rx) = tokio::sync::broadcast::channel(ARGS.log_buffer_size.max(1));
     let log_history = Arc::new(RwLock::new(VecDeque::with_capacity(MAX_HISTORY_LOGS + 1)));
     let log_writer = LogHelper::new(tx, log_history.clone());
 
-    init_logger(&ARGS.log_level, Some(log_writer.clone()));
+    let log_rotation = LogRotation {
+        max_size: ARGS.log_max_size_mb * 1024 * 1024,
+        max_files: ARGS.log_max_files,
+    };
+    init_logger(&ARGS.log_level, Some(log_writer.clone()), log_rotation);
     info!("欢迎使用 Bili-Sync，当前程序版本：{}", config::version());
     info!("项目地址：https://github.com/amtoaer/bili-sync");
-    let connection = setup_database(&CONFIG_DIR.join("data.sqlite"))
+    let connection = setup_database(&CONFIG_DIR.join(DATABASE_FILE_NAME))
         .await
         .expect("数据库初始化失败");
     info!("数据库初始化完成");
@@ -101,7 +108,21 @@ async fn handle_shutdown(connection: DatabaseConnection, tracker: TaskTracker, t
             error!("所有任务均已终止..")
         }
         _ = terminate() => {
-            info!("接收到终止信号，开始终止任务..");
+            info!("接收到终止信号，等待正在下载的视频与分页完成，再次发送终止信号可强制终止..");
+            // 先让下载任务在视频与分页的间隙停止，再终止其它任务，避免留下写入到一半的文件
+            // 等待超时或再次收到终止信号时直接终止，被中止的下载会删除未下载完成的 .part 文件
+            let shutdown_timeout = Duration::from_secs(ARGS.shutdown_timeout_secs);
+            tokio::select! {
+                res = tokio::time::timeout(shutdown_timeout, shutdown_video_downloader()) => {
+                    match res {
+                        Ok(()) => info!("下载任务已停止，开始终止任务.."),
+                        Err(_) => warn!("等待下载任务停止超时，强制终止任务.."),
+                    }
+                }
+                _ = terminate() => {
+                    warn!("再次接收到终止信号，强制终止任务..");
+                }
+            }
             token.cancel();
             tracker.wait().await;
             info!("所有任务均已终止..");
@@ -1,17 +1,17 @@
 mod queue;
 mod global;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use futures::future;
 use reqwest::header;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, LazyLock, Mutex};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::config::TEMPLATE;
 
-pub use queue::NotificationQueue;
+pub use queue::{NotificationQueue, NotificationQueueStatus};
 pub use global::NOTIFICATION_QUEUE;
 
 /// 全局消息缓存：按通知器维度缓存最近一次发送的“逻辑消息内容”
@@ -24,30 +24,324 @@ pub enum Notifier {
     Telegram {
         bot_token: String,
         chat_id: String,
+        // 开启了话题的群组中，消息发送到的话题 id，普通聊天留空
+        #[serde(default)]
+        message_thread_id: Option<i64>,
     },
     Webhook {
         url: String,
         template: Option<String>,
+        #[serde(default)]
+        payload_mode: WebhookPayloadMode,
         #[serde(skip)]
         // 一个内部辅助字段，用于决定是否强制渲染当前模板，在测试时使用
         ignore_cache: Option<()>,
     },
+    Gotify {
+        server: String,
+        app_token: String,
+        // 消息优先级，为空时使用应用的默认优先级
+        #[serde(default)]
+        priority: Option<u8>,
+    },
+    ServerChan {
+        send_key: String,
+    },
+    WeComBot {
+        webhook_key: String,
+    },
+}
+
+/// Webhook 请求体的生成方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookPayloadMode {
+    /// 使用模板渲染格式化后的文本消息
+    #[default]
+    Template,
+    /// 直接发送包含完整事件信息的 JSON 对象，忽略模板
+    Structured,
+}
+
+/// 通知对应的事件类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    /// 没有结构化信息的普通消息，如测试通知
+    #[default]
+    Message,
+    /// 任务执行失败、触发风控等错误提示
+    Error,
+    NewVideos,
+    InvalidVideo,
+    PaidVideo,
+    DailySummary,
+}
+
+impl NotificationEvent {
+    pub const ALL: [NotificationEvent; 6] = [
+        NotificationEvent::Message,
+        NotificationEvent::Error,
+        NotificationEvent::NewVideos,
+        NotificationEvent::InvalidVideo,
+        NotificationEvent::PaidVideo,
+        NotificationEvent::DailySummary,
+    ];
+
+    /// 是否为例行通知，错误提示之外的事件均为例行通知，通知队列已满时优先丢弃
+    pub fn is_routine(&self) -> bool {
+        *self != NotificationEvent::Error
+    }
+
+    /// 事件的名称，同时作为自定义消息模板的键
+    pub fn name(&self) -> &'static str {
+        match self {
+            NotificationEvent::Message => "message",
+            NotificationEvent::Error => "error",
+            NotificationEvent::NewVideos => "new_videos",
+            NotificationEvent::InvalidVideo => "invalid_video",
+            NotificationEvent::PaidVideo => "paid_video",
+            NotificationEvent::DailySummary => "daily_summary",
+        }
+    }
+}
+
+/// 使用用户为事件配置的模板渲染通知消息，未配置模板或渲染失败时使用内置的消息
+/// 模板中除了事件的上下文变量外，还可以通过 message 引用内置的消息
+/// 错误提示此前与普通消息共用 message 模板，未单独配置 error 模板时沿用 message 模板
+pub fn render_message(
+    templates: &HashMap<String, String>,
+    event: NotificationEvent,
+    context: serde_json::Value,
+    builtin: String,
+) -> String {
+    let template = templates.get(event.name()).or_else(|| {
+        (event == NotificationEvent::Error)
+            .then(|| templates.get(NotificationEvent::Message.name()))
+            .flatten()
+    });
+    let Some(template) = template else {
+        return builtin;
+    };
+    let mut context = context;
+    if let Some(context) = context.as_object_mut() {
+        context.insert("message".to_owned(), builtin.clone().into());
+    }
+    let mut handlebars = handlebars::Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+    match handlebars.render_template(template, &context) {
+        Ok(message) => message,
+        Err(e) => {
+            warn!("渲染「{}」事件的消息模板失败，使用内置的消息：{:#}", event.name(), e);
+            builtin
+        }
+    }
+}
+
+/// 通知的内容，message 为格式化后的文本，其余字段供结构化的 Webhook 使用
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NotificationPayload {
+    pub event: NotificationEvent,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_name: Option<String>,
+    /// 视频源的类型，可选 favorite / collection / submission / watch_later
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub succeeded: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bvid: Option<String>,
+}
+
+impl From<String> for NotificationPayload {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            ..Default::default()
+        }
+    }
+}
+
+impl NotificationPayload {
+    /// 错误提示，通知队列已满时优先保留
+    pub fn error(message: String) -> Self {
+        Self {
+            event: NotificationEvent::Error,
+            message,
+            ..Default::default()
+        }
+    }
+}
+
+/// 生成结构化模式下 Webhook 的请求体，在事件信息的基础上附加生成与推送时间
+pub fn structured_webhook_body(payload: &NotificationPayload, created_at: &str, sent_at: &str) -> Result<String> {
+    let mut body = serde_json::to_value(payload)?;
+    if let Some(body) = body.as_object_mut() {
+        body.insert("created_at".to_owned(), created_at.into());
+        body.insert("sent_at".to_owned(), sent_at.into());
+    }
+    Ok(serde_json::to_string(&body)?)
+}
+
+/// 生成模板模式下 Webhook 模板的渲染数据，除了消息与时间外还包含事件信息，没有对应信息的字段渲染为空
+fn webhook_template_data(payload: &NotificationPayload, created_at: &str, sent_at: &str) -> serde_json::Value {
+    serde_json::json!({
+        // 替换换行符为空格，避免 Webhook 不支持换行符
+        "message": payload.message.replace('\n', " "),
+        "created_at": created_at,
+        "sent_at": sent_at,
+        "event": payload.event.name(),
+        "source_name": payload.source_name.as_deref().unwrap_or_default(),
+        "source_type": payload.source_type.as_deref().unwrap_or_default(),
+        "bvid": payload.bvid.as_deref().unwrap_or_default(),
+    })
 }
 
 fn notifier_cache_key(notifier: &Notifier) -> String {
     match notifier {
-        Notifier::Telegram { bot_token, chat_id } => {
-            format!("telegram:{}:{}", bot_token, chat_id)
-        }
+        Notifier::Telegram {
+            bot_token,
+            chat_id,
+            message_thread_id,
+        } => match message_thread_id {
+            Some(message_thread_id) => format!("telegram:{}:{}:{}", bot_token, chat_id, message_thread_id),
+            None => format!("telegram:{}:{}", bot_token, chat_id),
+        },
         Notifier::Webhook { url, .. } => format!("webhook:{}", url),
+        Notifier::Gotify { server, app_token, .. } => format!("gotify:{}:{}", server, app_token),
+        Notifier::ServerChan { send_key } => format!("serverchan:{}", send_key),
+        Notifier::WeComBot { webhook_key } => format!("wecombot:{}", webhook_key),
     }
 }
 
 /// 归一化消息内容用于去重。
 /// 这里直接使用业务侧传入的原始 message，不包含后续追加的时间信息，
 /// 这样即使只是生成时间 / 推送时间不同，也会被视为“同一条消息”而被去重。
-fn normalize_message_for_cache(_notifier: &Notifier, message: &str) -> String {
-    message.trim().to_string()
+/// 自定义模板可能不包含 bvid，因此针对单个视频的通知需要将 bvid 一并计入，避免不同视频的通知被合并。
+fn normalize_message_for_cache(_notifier: &Notifier, payload: &NotificationPayload) -> String {
+    match payload.bvid.as_deref() {
+        Some(bvid) => format!("{}:{}", bvid, payload.message.trim()),
+        None => payload.message.trim().to_string(),
+    }
+}
+
+/// 生成 Telegram sendMessage 的表单参数，仅在指定了话题时携带 message_thread_id
+fn telegram_form_params(chat_id: &str, message_thread_id: Option<i64>, text: &str) -> Vec<(&'static str, String)> {
+    let mut params = vec![("chat_id", chat_id.to_owned()), ("text", text.to_owned())];
+    if let Some(message_thread_id) = message_thread_id {
+        params.push(("message_thread_id", message_thread_id.to_string()));
+    }
+    params
+}
+
+/// 有时间信息时，将生成时间与推送时间附加到消息末尾
+fn message_with_time(
+    message: &str,
+    created_at: Option<chrono::DateTime<chrono::Local>>,
+    sent_at: Option<chrono::DateTime<chrono::Local>>,
+) -> String {
+    if let (Some(created_at), Some(sent_at)) = (created_at, sent_at) {
+        let created_time = created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+        let sent_time = sent_at.format("%Y-%m-%d %H:%M:%S").to_string();
+        format!("{}\n\n⌛️ 生成时间: {}\n⌛️ 推送时间: {}", message, created_time, sent_time)
+    } else {
+        message.to_string()
+    }
+}
+
+/// 将消息拆分为标题与正文，第一行作为标题，其余内容作为正文，单行消息的正文为空
+fn split_title(message: &str) -> (&str, &str) {
+    match message.trim().split_once('\n') {
+        Some((title, content)) => (title.trim(), content.trim()),
+        None => (message.trim(), ""),
+    }
+}
+
+/// 生成 Gotify 推送消息的请求，单行消息同时作为标题与正文
+fn gotify_request(
+    client: &reqwest::Client,
+    server: &str,
+    app_token: &str,
+    priority: Option<u8>,
+    message: &str,
+) -> Result<reqwest::Request> {
+    let (title, content) = match split_title(message) {
+        (title, "") => (title, title),
+        (title, content) => (title, content),
+    };
+    let mut body = serde_json::json!({
+        "title": title,
+        "message": content,
+    });
+    if let Some(priority) = priority {
+        body["priority"] = priority.into();
+    }
+    Ok(client
+        .post(format!("{}/message", server.trim_end_matches('/')))
+        .query(&[("token", app_token)])
+        .json(&body)
+        .build()?)
+}
+
+/// 生成 Server酱 的表单参数，正文按 Markdown 渲染，使用空行分隔以保留原有的换行
+fn serverchan_form_params(message: &str) -> [(&'static str, String); 2] {
+    let (title, content) = split_title(message);
+    let desp = content.lines().map(str::trim_end).collect::<Vec<_>>().join("\n\n");
+    [("title", title.to_owned()), ("desp", desp)]
+}
+
+/// 企业微信群机器人文本消息内容的最大字节数
+const WECOM_CONTENT_MAX_BYTES: usize = 2048;
+
+/// 生成企业微信群机器人的文本消息，超出长度限制的内容按字符边界截断
+fn wecom_message_body(message: &str) -> serde_json::Value {
+    let mut end = message.len().min(WECOM_CONTENT_MAX_BYTES);
+    while !message.is_char_boundary(end) {
+        end -= 1;
+    }
+    serde_json::json!({
+        "msgtype": "text",
+        "text": {
+            "content": &message[..end],
+        },
+    })
+}
+
+/// 企业微信在请求失败时同样返回 200 状态码，需要检查响应体中的 errcode
+fn check_wecom_response(body: &str) -> Result<()> {
+    #[derive(Deserialize)]
+    struct WeComResponse {
+        errcode: i64,
+        #[serde(default)]
+        errmsg: String,
+    }
+    let response: WeComResponse =
+        serde_json::from_str(body).with_context(|| format!("无法解析企业微信的响应: {}", body))?;
+    if response.errcode != 0 {
+        anyhow::bail!("企业微信返回错误 (errcode: {}): {}", response.errcode, response.errmsg);
+    }
+    Ok(())
+}
+
+/// Server酱在推送失败时同样可能返回 200 状态码，需要检查响应体中的 code
+fn check_serverchan_response(body: &str) -> Result<()> {
+    #[derive(Deserialize)]
+    struct ServerChanResponse {
+        code: i64,
+        #[serde(default)]
+        message: String,
+    }
+    let response: ServerChanResponse =
+        serde_json::from_str(body).with_context(|| format!("无法解析 Server酱 的响应: {}", body))?;
+    if response.code != 0 {
+        anyhow::bail!("Server酱 返回错误 (code: {}): {}", response.code, response.message);
+    }
+    Ok(())
 }
 
 pub fn webhook_template_key(url: &str) -> String {
@@ -63,7 +357,19 @@ pub fn webhook_template_content(template: &Option<String>) -> &str {
 
 pub trait NotifierAllExt {
     async fn notify_all(&self, client: &reqwest::Client, message: &str) -> Result<()>;
-    fn notify_all_queued(&self, queue: &NotificationQueue, client: reqwest::Client, message: String) -> Result<()>;
+    fn notify_all_queued(
+        &self,
+        queue: &NotificationQueue,
+        client: reqwest::Client,
+        payload: impl Into<NotificationPayload>,
+    ) -> Result<()>;
+    /// 加入通知队列，发送时不走消息去重，也不会影响之后消息的去重
+    fn notify_all_queued_without_cache(
+        &self,
+        queue: &NotificationQueue,
+        client: reqwest::Client,
+        payload: impl Into<NotificationPayload>,
+    ) -> Result<()>;
 }
 
 impl NotifierAllExt for Vec<Notifier> {
@@ -72,12 +378,33 @@ impl NotifierAllExt for Vec<Notifier> {
         Ok(())
     }
     
-    fn notify_all_queued(&self, queue: &NotificationQueue, client: reqwest::Client, message: String) -> Result<()> {
+    fn notify_all_queued(
+        &self,
+        queue: &NotificationQueue,
+        client: reqwest::Client,
+        payload: impl Into<NotificationPayload>,
+    ) -> Result<()> {
         queue.enqueue(queue::NotificationMessage {
             notifiers: Arc::new(self.clone()),
-            message,
+            payload: payload.into(),
             client,
             created_at: chrono::Local::now(),
+            bypass_cache: false,
+        })
+    }
+
+    fn notify_all_queued_without_cache(
+        &self,
+        queue: &NotificationQueue,
+        client: reqwest::Client,
+        payload: impl Into<NotificationPayload>,
+    ) -> Result<()> {
+        queue.enqueue(queue::NotificationMessage {
+            notifiers: Arc::new(self.clone()),
+            payload: payload.into(),
+            client,
+            created_at: chrono::Local::now(),
+            bypass_cache: true,
         })
     }
 }
@@ -85,18 +412,18 @@ impl NotifierAllExt for Vec<Notifier> {
 impl Notifier {
     /// 普通通知（走消息去重）
     pub async fn notify(&self, client: &reqwest::Client, message: &str) -> Result<()> {
-        self.notify_internal(client, message, None, None, false).await
+        self.notify_internal(client, &message.to_owned().into(), None, None, false).await
     }
     
     /// 携带时间信息的通知（走消息去重）
     pub async fn notify_with_time(
         &self,
         client: &reqwest::Client,
-        message: &str,
+        payload: &NotificationPayload,
         created_at: Option<chrono::DateTime<chrono::Local>>,
         sent_at: Option<chrono::DateTime<chrono::Local>>,
     ) -> Result<()> {
-        self.notify_internal(client, message, created_at, sent_at, false).await
+        self.notify_internal(client, payload, created_at, sent_at, false).await
     }
 
     /// 强制发送通知，不走消息去重逻辑（用于测试通知）
@@ -105,48 +432,45 @@ impl Notifier {
         client: &reqwest::Client,
         message: &str,
     ) -> Result<()> {
-        self.notify_internal(client, message, None, None, true).await
+        self.notify_internal(client, &message.to_owned().into(), None, None, true).await
+    }
+
+    /// 消息去重：同一个通知器，如果本次“逻辑消息内容”和上次完全一致，则跳过发送
+    fn is_duplicate(&self, payload: &NotificationPayload) -> bool {
+        let key = notifier_cache_key(self);
+        let normalized = normalize_message_for_cache(self, payload);
+        let mut cache = LAST_MESSAGES.lock().expect("LAST_MESSAGES mutex poisoned");
+        if cache.get(&key) == Some(&normalized) {
+            info!("通知内容与上次完全相同，已跳过发送（key = {}）", key);
+            return true;
+        }
+        cache.insert(key, normalized);
+        false
     }
 
     async fn notify_internal(
         &self,
         client: &reqwest::Client,
-        message: &str,
+        payload: &NotificationPayload,
         created_at: Option<chrono::DateTime<chrono::Local>>,
         sent_at: Option<chrono::DateTime<chrono::Local>>,
         bypass_cache: bool,
     ) -> Result<()> {
-        // 消息去重：同一个通知器，如果本次“逻辑消息内容”和上次完全一致，则跳过发送
-        if !bypass_cache {
-            let key = notifier_cache_key(self);
-            let normalized = normalize_message_for_cache(self, message);
-            let mut cache = LAST_MESSAGES
-                .lock()
-                .expect("LAST_MESSAGES mutex poisoned");
-
-            if let Some(last) = cache.get(&key) {
-                if last == &normalized {
-                    info!("通知内容与上次完全相同，已跳过发送（key = {}）", key);
-                    return Ok(());
-                }
-            }
-
-            cache.insert(key, normalized);
+        let message = payload.message.as_str();
+        if !bypass_cache && self.is_duplicate(payload) {
+            return Ok(());
         }
 
         match self {
-            Notifier::Telegram { bot_token, chat_id } => {
+            Notifier::Telegram {
+                bot_token,
+                chat_id,
+                message_thread_id,
+            } => {
                 // 如果有时间信息，添加到消息末尾
-                let final_message = if let (Some(created_at), Some(sent_at)) = (created_at, sent_at) {
-                    let created_time = created_at.format("%Y-%m-%d %H:%M:%S").to_string();
-                    let sent_time = sent_at.format("%Y-%m-%d %H:%M:%S").to_string();
-                    format!("{}\n\n⌛️ 生成时间: {}\n⌛️ 推送时间: {}", message, created_time, sent_time)
-                } else {
-                    message.to_string()
-                };
-                
+                let final_message = message_with_time(message, created_at, sent_at);
                 let url = format!("https://api.telegram.org/bot{}/sendMessage", bot_token);
-                let params = [("chat_id", chat_id.as_str()), ("text", final_message.as_str())];
+                let params = telegram_form_params(chat_id, *message_thread_id, &final_message);
                 let response = client.post(&url).form(&params).send().await?;
                 let status = response.status();
                 if !status.is_success() {
@@ -154,13 +478,56 @@ impl Notifier {
                     anyhow::bail!("Telegram API 返回错误 (状态码: {}): {}", status, error_text);
                 }
             }
+            Notifier::Gotify {
+                server,
+                app_token,
+                priority,
+            } => {
+                let final_message = message_with_time(message, created_at, sent_at);
+                let request = gotify_request(client, server, app_token, *priority, &final_message)?;
+                let response = client.execute(request).await?;
+                let status = response.status();
+                if !status.is_success() {
+                    let error_text = response.text().await.unwrap_or_else(|_| "未知错误".to_string());
+                    anyhow::bail!("Gotify 返回错误 (状态码: {}): {}", status, error_text);
+                }
+            }
+            Notifier::ServerChan { send_key } => {
+                let final_message = message_with_time(message, created_at, sent_at);
+                let url = format!("https://sctapi.ftqq.com/{}.send", send_key);
+                let response = client
+                    .post(&url)
+                    .form(&serverchan_form_params(&final_message))
+                    .send()
+                    .await?;
+                let status = response.status();
+                let body = response.text().await.unwrap_or_else(|_| "未知错误".to_string());
+                if !status.is_success() {
+                    anyhow::bail!("Server酱 返回错误 (状态码: {}): {}", status, body);
+                }
+                check_serverchan_response(&body)?;
+            }
+            Notifier::WeComBot { webhook_key } => {
+                let final_message = message_with_time(message, created_at, sent_at);
+                let response = client
+                    .post("https://qyapi.weixin.qq.com/cgi-bin/webhook/send")
+                    .query(&[("key", webhook_key)])
+                    .json(&wecom_message_body(&final_message))
+                    .send()
+                    .await?;
+                let status = response.status();
+                let body = response.text().await.unwrap_or_else(|_| "未知错误".to_string());
+                if !status.is_success() {
+                    anyhow::bail!("企业微信返回错误 (状态码: {}): {}", status, body);
+                }
+                check_wecom_response(&body)?;
+            }
             Notifier::Webhook {
                 url,
                 template,
+                payload_mode,
                 ignore_cache,
             } => {
-                // 替换换行符为空格，避免 Webhook 不支持换行符
-                let sanitized_message = message.replace('\n', " ");
                 let key = webhook_template_key(url);
                 let handlebar = TEMPLATE.read();
                 let now = chrono::Local::now();
@@ -170,14 +537,15 @@ impl Notifier {
                 let sent_at_str = sent_at
                     .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
                     .unwrap_or_else(|| now.format("%Y-%m-%d %H:%M:%S").to_string());
-                let data = serde_json::json!({
-                    "message": sanitized_message,
-                    "created_at": created_at_str,
-                    "sent_at": sent_at_str,
-                });
-                let payload = match ignore_cache {
-                    Some(_) => handlebar.render_template(webhook_template_content(template), &data)?,
-                    None => handlebar.render(&key, &data)?,
+                let data = webhook_template_data(payload, &created_at_str, &sent_at_str);
+                let payload = match (payload_mode, ignore_cache) {
+                    (WebhookPayloadMode::Structured, _) => {
+                        structured_webhook_body(payload, &created_at_str, &sent_at_str)?
+                    }
+                    (WebhookPayloadMode::Template, Some(_)) => {
+                        handlebar.render_template(webhook_template_content(template), &data)?
+                    }
+                    (WebhookPayloadMode::Template, None) => handlebar.render(&key, &data)?,
                 };
                 let response = client
                     .post(url)
@@ -204,3 +572,169 @@ impl Notifier {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_message_for_cache() {
+        let notifier = Notifier::ServerChan {
+            send_key: "key".to_owned(),
+        };
+        let payload = |bvid: Option<&str>| NotificationPayload {
+            event: NotificationEvent::InvalidVideo,
+            message: "视频已失效\n".to_owned(),
+            bvid: bvid.map(str::to_owned),
+            ..Default::default()
+        };
+        // 渲染结果相同但对应不同视频的通知不会被视为同一条消息
+        assert_ne!(
+            normalize_message_for_cache(&notifier, &payload(Some("BV1test00001"))),
+            normalize_message_for_cache(&notifier, &payload(Some("BV1test00002")))
+        );
+        assert_eq!(
+            normalize_message_for_cache(&notifier, &payload(Some("BV1test00001"))),
+            normalize_message_for_cache(&notifier, &payload(Some("BV1test00001")))
+        );
+        assert_eq!(normalize_message_for_cache(&notifier, &payload(None)), "视频已失效");
+    }
+
+    #[test]
+    fn test_is_duplicate() {
+        let notifier = Notifier::ServerChan {
+            send_key: uuid::Uuid::new_v4().to_string(),
+        };
+        let payload = NotificationPayload::from("每日汇总".to_owned());
+        assert!(!notifier.is_duplicate(&payload));
+        assert!(notifier.is_duplicate(&payload));
+        assert!(!notifier.is_duplicate(&NotificationPayload::from("另一条消息".to_owned())));
+    }
+
+    #[test]
+    fn test_render_message() {
+        let mut templates = HashMap::new();
+        templates.insert("message".to_owned(), "[bili-sync] {{message}}".to_owned());
+        let render = |templates: &HashMap<String, String>, event| {
+            render_message(templates, event, serde_json::json!({}), "内置消息".to_owned())
+        };
+        assert_eq!(render(&templates, NotificationEvent::Message), "[bili-sync] 内置消息");
+        // 未配置 error 模板时错误提示沿用 message 模板，其余事件不受影响
+        assert_eq!(render(&templates, NotificationEvent::Error), "[bili-sync] 内置消息");
+        assert_eq!(render(&templates, NotificationEvent::NewVideos), "内置消息");
+        templates.insert("error".to_owned(), "[错误] {{message}}".to_owned());
+        assert_eq!(render(&templates, NotificationEvent::Error), "[错误] 内置消息");
+    }
+
+    #[test]
+    fn test_telegram_form_params() {
+        let params = telegram_form_params("-1001234567890", Some(42), "测试消息");
+        assert_eq!(
+            params,
+            vec![
+                ("chat_id", "-1001234567890".to_owned()),
+                ("text", "测试消息".to_owned()),
+                ("message_thread_id", "42".to_owned()),
+            ]
+        );
+        // 普通聊天不携带 message_thread_id
+        let params = telegram_form_params("-1001234567890", None, "测试消息");
+        assert!(params.iter().all(|(key, _)| *key != "message_thread_id"));
+        // 旧配置中没有该字段时反序列化为空
+        let notifier: Notifier =
+            serde_json::from_str(r#"{"type": "telegram", "bot_token": "token", "chat_id": "123"}"#).unwrap();
+        assert!(matches!(
+            notifier,
+            Notifier::Telegram {
+                message_thread_id: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_gotify_request() -> Result<()> {
+        let client = reqwest::Client::new();
+        let request = gotify_request(
+            &client,
+            "https://gotify.example.com/",
+            "AbCdEf",
+            Some(8),
+            "🎬 收藏夹 有更新\n📹 本次更新视频数：3",
+        )?;
+        assert_eq!(request.method(), reqwest::Method::POST);
+        assert_eq!(
+            request.url().as_str(),
+            "https://gotify.example.com/message?token=AbCdEf"
+        );
+        let body: serde_json::Value = serde_json::from_slice(request.body().and_then(|b| b.as_bytes()).unwrap())?;
+        assert_eq!(
+            body,
+            serde_json::json!({"title": "🎬 收藏夹 有更新", "message": "📹 本次更新视频数：3", "priority": 8})
+        );
+        // 单行消息同时作为标题与正文，未设置优先级时不携带该字段
+        let request = gotify_request(&client, "https://gotify.example.com", "AbCdEf", None, "测试消息")?;
+        let body: serde_json::Value = serde_json::from_slice(request.body().and_then(|b| b.as_bytes()).unwrap())?;
+        assert_eq!(body, serde_json::json!({"title": "测试消息", "message": "测试消息"}));
+        Ok(())
+    }
+
+    #[test]
+    fn test_serverchan_form_params() {
+        let [(_, title), (_, desp)] =
+            serverchan_form_params("🎬 收藏夹 有更新\n📹 本次更新视频数：3\n  |  ✅ 成功: 3 个");
+        assert_eq!(title, "🎬 收藏夹 有更新");
+        assert_eq!(desp, "📹 本次更新视频数：3\n\n  |  ✅ 成功: 3 个");
+        // 单行消息仅有标题
+        let [(_, title), (_, desp)] = serverchan_form_params("测试消息");
+        assert_eq!(title, "测试消息");
+        assert!(desp.is_empty());
+        // 接口层面的错误同样以 200 状态码返回，需要从响应体中识别
+        assert!(check_serverchan_response(r#"{"code":0,"message":"","data":{"pushid":"1"}}"#).is_ok());
+        let err = check_serverchan_response(r#"{"code":40001,"message":"bad pushtoken"}"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("40001"));
+        assert!(check_serverchan_response("<html></html>").is_err());
+    }
+
+    #[test]
+    fn test_wecom_bot() {
+        assert!(check_wecom_response(r#"{"errcode":0,"errmsg":"ok"}"#).is_ok());
+        // 企业微信以 200 状态码返回错误，需要从响应体中识别
+        let err = check_wecom_response(r#"{"errcode":93000,"errmsg":"invalid webhook url"}"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("93000"));
+        assert!(check_wecom_response("<html></html>").is_err());
+        // 超长消息在不超过 2048 字节的字符边界处截断
+        let body = wecom_message_body(&"视".repeat(1000));
+        let content = body["text"]["content"].as_str().unwrap();
+        assert_eq!(content.len(), 2046);
+        assert_eq!(body["msgtype"], "text");
+    }
+
+    #[test]
+    fn test_webhook_template_data() -> Result<()> {
+        let handlebars = handlebars::Handlebars::new();
+        let template =
+            r#"{"event": "{{event}}", "source": "{{source_name}}", "type": "{{source_type}}", "text": "{{message}}"}"#;
+        let payload = NotificationPayload {
+            event: NotificationEvent::InvalidVideo,
+            message: "视频失效\nBV1test00001".to_owned(),
+            source_name: Some("收藏夹「测试收藏夹」".to_owned()),
+            source_type: Some("favorite".to_owned()),
+            bvid: Some("BV1test00001".to_owned()),
+            ..Default::default()
+        };
+        let data = webhook_template_data(&payload, "2025-01-01 00:00:00", "2025-01-01 00:00:01");
+        assert_eq!(
+            handlebars.render_template(template, &data)?,
+            r#"{"event": "invalid_video", "source": "收藏夹「测试收藏夹」", "type": "favorite", "text": "视频失效 BV1test00001"}"#
+        );
+        // 没有视频源信息的普通消息，对应字段渲染为空
+        let payload = NotificationPayload::from("测试消息".to_owned());
+        let data = webhook_template_data(&payload, "2025-01-01 00:00:00", "2025-01-01 00:00:01");
+        assert_eq!(
+            handlebars.render_template(template, &data)?,
+            r#"{"event": "message", "source": "", "type": "", "text": "测试消息"}"#
+        );
+        Ok(())
+    }
+}
@@ -1,131 +1,160 @@
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
 use anyhow::Result;
-use chrono::Timelike;
-use tokio::sync::mpsc;
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::sync::Notify;
 use tokio::time::sleep;
 use tracing::{error, info, warn};
 
-use crate::config::VersionedConfig;
+use crate::config::{Config, VersionedConfig};
 
-use super::Notifier;
+use super::{NotificationPayload, Notifier};
 
 /// 消息队列，用于控制通知发送频率
 pub struct NotificationQueue {
-    sender: mpsc::UnboundedSender<NotificationMessage>,
+    buffer: Arc<MessageBuffer>,
+    /// 已入队但尚未发送的消息数量，静默时间段内保留在缓冲区中的消息同样计入
+    pending: Arc<AtomicUsize>,
+    /// 最近一次发送消息的时间
+    last_sent_at: Arc<Mutex<Option<chrono::DateTime<chrono::Local>>>>,
+}
+
+/// 消息队列的积压情况
+#[derive(Serialize, Debug)]
+pub struct NotificationQueueStatus {
+    pub pending: usize,
+    pub last_sent_at: Option<chrono::DateTime<chrono::Local>>,
+    /// 相邻两条消息之间的发送间隔（秒）
+    pub interval_secs: u64,
 }
 
 #[derive(Clone)]
 pub struct NotificationMessage {
     pub notifiers: Arc<Vec<Notifier>>,
-    pub message: String,
+    pub payload: NotificationPayload,
     pub client: reqwest::Client,
     pub created_at: chrono::DateTime<chrono::Local>,
+    /// 手动触发的消息跳过去重，连续触发时每次都会发送
+    pub bypass_cache: bool,
+}
+
+/// 有容量上限的消息缓冲区
+#[derive(Default)]
+struct MessageBuffer {
+    messages: Mutex<VecDeque<NotificationMessage>>,
+    notify: Notify,
+}
+
+impl MessageBuffer {
+    /// 放入一条消息，缓冲区已满时返回被丢弃的消息
+    ///
+    /// 优先丢弃最早的例行通知，缓冲区中全部为错误提示时，新的例行通知直接被丢弃，新的错误提示则替换最早的错误提示
+    fn push(&self, msg: NotificationMessage, capacity: usize) -> Option<NotificationMessage> {
+        let mut messages = self.messages.lock();
+        let dropped = if messages.len() >= capacity.max(1) {
+            match messages.iter().position(|m| m.payload.event.is_routine()) {
+                Some(index) => messages.remove(index),
+                None if msg.payload.event.is_routine() => return Some(msg),
+                None => messages.pop_front(),
+            }
+        } else {
+            None
+        };
+        messages.push_back(msg);
+        drop(messages);
+        self.notify.notify_one();
+        dropped
+    }
+
+    /// 等待缓冲区中存在消息，消息仍保留在缓冲区中
+    async fn wait(&self) {
+        while self.messages.lock().is_empty() {
+            self.notify.notified().await;
+        }
+    }
+
+    /// 取出最早的一条消息
+    fn pop(&self) -> Option<NotificationMessage> {
+        self.messages.lock().pop_front()
+    }
+}
+
+/// 静默时间段内重新检查静默状态的最长间隔，使静默配置的修改能及时生效
+const QUIET_RECHECK_SECS: u64 = 60;
+
+/// 按配置的容量将消息放入缓冲区，被丢弃的消息不再计入积压数量
+fn push_message(buffer: &MessageBuffer, pending: &AtomicUsize, msg: NotificationMessage) {
+    let capacity = VersionedConfig::get().read().notification_queue_capacity;
+    if let Some(dropped) = buffer.push(msg, capacity) {
+        pending.fetch_sub(1, Ordering::Relaxed);
+        warn!(
+            "通知队列已满（容量 {}），丢弃一条「{}」消息：{}",
+            capacity,
+            dropped.payload.event.name(),
+            dropped.payload.message
+        );
+    }
 }
 
 impl NotificationQueue {
     /// 创建新的消息队列
     pub fn new() -> Self {
-        let (sender, mut receiver) = mpsc::unbounded_channel::<NotificationMessage>();
+        let buffer = Arc::new(MessageBuffer::default());
+        let pending = Arc::new(AtomicUsize::new(0));
+        let last_sent_at = Arc::new(Mutex::new(None));
         
         // 启动后台任务处理消息队列
-        let sender_for_delay = sender.clone();
+        let (buffer_for_task, pending_for_task, last_sent_at_for_task) =
+            (buffer.clone(), pending.clone(), last_sent_at.clone());
         tokio::spawn(async move {
-            while let Some(msg) = receiver.recv().await {
-                // 检查静默时间段
-                let config = VersionedConfig::get().read();
-                let mut should_delay = false;
-                
-                if config.enable_notification_quiet_hours {
-                    let now = chrono::Local::now();
-                    let hour = now.hour() as u8;
-                    let start_hour = config.quiet_hours_start;
-                    let end_hour = config.quiet_hours_end;
-                    
-                    // 判断是否在静默时间段内
-                    let is_quiet_time = if start_hour > end_hour {
-                        // 跨天的情况，例如 22:00-09:00
-                        hour >= start_hour || hour < end_hour
-                    } else {
-                        // 不跨天的情况，例如 22:00-23:00
-                        hour >= start_hour && hour < end_hour
-                    };
-                    
-                    if is_quiet_time {
-                        // 计算到静默结束时间的延迟时间
-                        let target_time = if start_hour > end_hour {
-                            // 跨天的情况
-                            if hour >= start_hour {
-                                // 如果是开始时间之后，目标时间是明天的结束时间
-                                now.date_naive()
-                                    .succ_opt()
-                                    .unwrap_or(now.date_naive())
-                                    .and_hms_opt(end_hour as u32, 0, 0)
-                                    .unwrap()
-                                    .and_local_timezone(chrono::Local)
-                                    .unwrap()
-                            } else {
-                                // 如果是结束时间之前，目标时间是今天的结束时间
-                                now.date_naive()
-                                    .and_hms_opt(end_hour as u32, 0, 0)
-                                    .unwrap()
-                                    .and_local_timezone(chrono::Local)
-                                    .unwrap()
-                            }
-                        } else {
-                            // 不跨天的情况，目标时间是今天的结束时间
-                            now.date_naive()
-                                .and_hms_opt(end_hour as u32, 0, 0)
-                                .unwrap()
-                                .and_local_timezone(chrono::Local)
-                                .unwrap()
-                        };
-                        
-                        let delay = target_time.signed_duration_since(now);
-                        if delay.num_seconds() > 0 {
-                            info!("当前时间在静默时间段内（{}:00-{}:00），延迟到 {}:00 发送通知（延迟 {} 秒）", 
-                                start_hour, end_hour, end_hour, delay.num_seconds());
-                            // 延迟后重新入队到主队列，以遵循队列间隔配置
-                            let msg_clone = msg.clone();
-                            let sender_for_delay_clone = sender_for_delay.clone();
-                            tokio::spawn(async move {
-                                tokio::time::sleep(Duration::from_secs(delay.num_seconds() as u64)).await;
-                                // 延迟后重新入队到主队列，这样会遵循队列间隔配置
-                                if let Err(e) = sender_for_delay_clone.send(msg_clone) {
-                                    error!("延迟发送后重新入队失败: {:#}", e);
-                                }
-                            });
-                            // 继续处理下一条消息，不等待延迟发送完成
-                            should_delay = true;
-                        }
+            let mut quiet_logged = false;
+            loop {
+                buffer_for_task.wait().await;
+                // 静默时间段内消息保留在缓冲区中，同样受容量上限与丢弃策略的约束
+                let now = chrono::Local::now().naive_local();
+                let quiet_end = quiet_time_end(&VersionedConfig::get().read(), now);
+                if let Some(target_time) = quiet_end {
+                    let delay = target_time.signed_duration_since(now).num_seconds().max(1) as u64;
+                    if !quiet_logged {
+                        info!(
+                            "当前时间在静默时间段内，延迟到 {} 发送通知（延迟 {} 秒）",
+                            target_time.format("%Y-%m-%d %H:%M"),
+                            delay
+                        );
+                        quiet_logged = true;
                     }
+                    sleep(Duration::from_secs(delay.min(QUIET_RECHECK_SECS))).await;
+                    continue;
                 }
-                
-                if !should_delay {
-                    // 不在静默时间段，立即发送
-                    info!("开始发送通知消息（共 {} 个通知器）", msg.notifiers.len());
-                    match Self::send_notification(&msg).await {
-                        Ok(_) => {
-                            info!("通知消息发送成功");
-                        }
-                        Err(e) => {
-                            error!("发送通知失败: {:#}", e);
-                        }
+                quiet_logged = false;
+                let Some(msg) = buffer_for_task.pop() else {
+                    continue;
+                };
+                info!("开始发送通知消息（共 {} 个通知器）", msg.notifiers.len());
+                match Self::send_notification(&msg).await {
+                    Ok(_) => {
+                        info!("通知消息发送成功");
+                    }
+                    Err(e) => {
+                        error!("发送通知失败: {:#}", e);
                     }
                 }
-                
-                // 从配置中读取等待时间（默认5秒）
-                let interval = VersionedConfig::get()
-                    .read()
-                    .notification_interval
-                    .max(1) // 至少1秒
-                    .min(60); // 最多60秒，避免过长
-                sleep(Duration::from_secs(interval)).await;
+                pending_for_task.fetch_sub(1, Ordering::Relaxed);
+                *last_sent_at_for_task.lock() = Some(chrono::Local::now());
+                sleep(Duration::from_secs(send_interval(&VersionedConfig::get().read()))).await;
             }
         });
         
-        Self { sender }
+        Self {
+            buffer,
+            pending,
+            last_sent_at,
+        }
     }
     
     /// 发送通知（实际执行）
@@ -141,10 +170,15 @@ impl NotificationQueue {
             let notifier_type = match notifier {
                 Notifier::Telegram { .. } => "Telegram",
                 Notifier::Webhook { .. } => "Webhook",
+                Notifier::Gotify { .. } => "Gotify",
+                Notifier::ServerChan { .. } => "Server酱",
+                Notifier::WeComBot { .. } => "企业微信",
             };
             
             // 统一使用原始消息和时间参数，让每个通知器自己决定如何显示时间
-            let result = notifier.notify_with_time(&msg.client, &msg.message, Some(created_at), Some(sent_at)).await;
+            let result = notifier
+                .notify_internal(&msg.client, &msg.payload, Some(created_at), Some(sent_at), msg.bypass_cache)
+                .await;
             
             match result {
                 Ok(_) => {
@@ -173,9 +207,20 @@ impl NotificationQueue {
     
     /// 将消息加入队列
     pub fn enqueue(&self, msg: NotificationMessage) -> Result<()> {
-        self.sender.send(msg)?;
+        // 先计数再入队，避免消息在计数前就被发送导致计数下溢
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        push_message(&self.buffer, &self.pending, msg);
         Ok(())
     }
+
+    /// 获取队列当前的积压情况
+    pub fn status(&self) -> NotificationQueueStatus {
+        NotificationQueueStatus {
+            pending: self.pending.load(Ordering::Relaxed),
+            last_sent_at: *self.last_sent_at.lock(),
+            interval_secs: send_interval(&VersionedConfig::get().read()),
+        }
+    }
 }
 
 impl Default for NotificationQueue {
@@ -184,3 +229,178 @@ impl Default for NotificationQueue {
     }
 }
 
+/// 从配置中读取相邻两条消息的发送间隔，限制在 1 到 60 秒之间，避免过短或过长
+fn send_interval(config: &Config) -> u64 {
+    config.notification_interval.clamp(1, 60)
+}
+
+/// 判断给定时间是否处于静默状态：位于每天的静默时间段内，或当天属于全天静默的星期
+fn is_quiet_time(config: &Config, time: NaiveDateTime) -> bool {
+    if !config.enable_notification_quiet_hours {
+        return false;
+    }
+    if config
+        .quiet_days
+        .as_ref()
+        .is_some_and(|days| days.contains(&time.weekday()))
+    {
+        return true;
+    }
+    let hour = time.hour() as u8;
+    let (start_hour, end_hour) = (config.quiet_hours_start, config.quiet_hours_end);
+    if start_hour > end_hour {
+        // 跨天的情况，例如 22:00-09:00
+        hour >= start_hour || hour < end_hour
+    } else {
+        // 不跨天的情况，例如 22:00-23:00
+        hour >= start_hour && hour < end_hour
+    }
+}
+
+/// 计算本次静默结束的时间，不处于静默状态时返回 None
+///
+/// 静默状态总是以整点为边界切换，因此逐小时向后查找第一个不处于静默状态的整点即可，
+/// 这样在静默跨过午夜、进入另一个同样可能静默的日期时也能得到正确的结果
+fn quiet_time_end(config: &Config, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    if !is_quiet_time(config, now) {
+        return None;
+    }
+    let mut target = now.date().and_hms_opt(now.hour(), 0, 0)?;
+    // 配置校验保证全天静默的星期不会覆盖整周，八天之内一定能找到静默结束的时间
+    for _ in 0..8 * 24 {
+        target += chrono::Duration::hours(1);
+        if !is_quiet_time(config, target) {
+            return Some(target);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Weekday};
+
+    use super::*;
+    use crate::notifier::NotificationEvent;
+
+    fn time(day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        // 2026-10-17 是星期六
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .and_then(|date| date.and_hms_opt(hour, min, 0))
+            .unwrap()
+    }
+
+    fn quiet_config(quiet_days: Option<Vec<Weekday>>) -> Config {
+        Config {
+            enable_notification_quiet_hours: true,
+            quiet_hours_start: 22,
+            quiet_hours_end: 9,
+            quiet_days,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_quiet_days_saturday_all_day() {
+        let config = quiet_config(Some(vec![Weekday::Sat]));
+        assert_eq!(time(17, 0, 0).weekday(), Weekday::Sat);
+        // 周六全天静默，而周日的 0 点到 9 点仍处于每天的静默时间段内，需要一直延迟到周日 9 点
+        for now in [time(17, 0, 0), time(17, 12, 30), time(17, 23, 59)] {
+            assert!(is_quiet_time(&config, now));
+            assert_eq!(quiet_time_end(&config, now), Some(time(18, 9, 0)));
+        }
+        // 周五晚上的静默时间段会延续整个周六
+        assert_eq!(quiet_time_end(&config, time(16, 22, 10)), Some(time(18, 9, 0)));
+        // 周末两天均静默时延迟到周一早上
+        let config = quiet_config(Some(vec![Weekday::Sat, Weekday::Sun]));
+        assert_eq!(quiet_time_end(&config, time(17, 12, 30)), Some(time(19, 9, 0)));
+        // 仅设置全天静默的星期时，其余日期不受影响
+        let config = Config {
+            quiet_hours_start: 0,
+            quiet_hours_end: 0,
+            ..config
+        };
+        assert_eq!(quiet_time_end(&config, time(18, 15, 0)), Some(time(19, 0, 0)));
+    }
+
+    #[tokio::test]
+    async fn test_queue_status_pending() -> Result<()> {
+        let queue = NotificationQueue::new();
+        assert_eq!(queue.status().pending, 0);
+        for i in 0..3 {
+            queue.enqueue(NotificationMessage {
+                notifiers: Arc::new(Vec::new()),
+                payload: format!("消息 {}", i).into(),
+                client: reqwest::Client::new(),
+                created_at: chrono::Local::now(),
+                bypass_cache: false,
+            })?;
+        }
+        // 相邻消息之间至少间隔 1 秒，后入队的消息仍在排队
+        let status = queue.status();
+        assert!(status.pending > 0);
+        assert!((1..=60).contains(&status.interval_secs));
+        Ok(())
+    }
+
+    #[test]
+    fn test_buffer_drop_policy() {
+        let message = |event: NotificationEvent, text: &str| NotificationMessage {
+            notifiers: Arc::new(Vec::new()),
+            payload: NotificationPayload {
+                event,
+                message: text.to_owned(),
+                ..Default::default()
+            },
+            client: reqwest::Client::new(),
+            created_at: chrono::Local::now(),
+            bypass_cache: false,
+        };
+        let buffer = MessageBuffer::default();
+        let mut dropped = Vec::new();
+        for (event, text) in [
+            (NotificationEvent::NewVideos, "routine-1"),
+            (NotificationEvent::Error, "error-1"),
+            (NotificationEvent::NewVideos, "routine-2"),
+            (NotificationEvent::DailySummary, "routine-3"),
+            (NotificationEvent::Error, "error-2"),
+            (NotificationEvent::Error, "error-3"),
+            (NotificationEvent::InvalidVideo, "routine-4"),
+            // 测试通知等普通消息同样属于例行通知
+            (NotificationEvent::Message, "routine-5"),
+        ] {
+            dropped.extend(buffer.push(message(event, text), 3).map(|m| m.payload.message));
+        }
+        // 超出容量时依次丢弃最早的例行通知，缓冲区全部为错误提示后新的例行通知直接丢弃
+        assert_eq!(dropped, ["routine-1", "routine-2", "routine-3", "routine-4", "routine-5"]);
+        let remaining = buffer
+            .messages
+            .lock()
+            .iter()
+            .map(|m| m.payload.message.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(remaining, ["error-1", "error-2", "error-3"]);
+        // 缓冲区全部为错误提示时，新的错误提示替换最早的错误提示
+        let dropped = buffer.push(message(NotificationEvent::Error, "error-4"), 3);
+        assert_eq!(dropped.map(|m| m.payload.message).as_deref(), Some("error-1"));
+        assert_eq!(buffer.pop().map(|m| m.payload.message).as_deref(), Some("error-2"));
+    }
+
+    #[test]
+    fn test_quiet_days_weekday_passthrough() {
+        let config = quiet_config(Some(vec![Weekday::Sat, Weekday::Sun]));
+        // 工作日白天不处于静默状态，消息直接发送
+        for now in [time(19, 9, 0), time(21, 12, 30), time(23, 21, 59)] {
+            assert!(!is_quiet_time(&config, now));
+            assert_eq!(quiet_time_end(&config, now), None);
+        }
+        // 工作日夜间仍遵循每天的静默时间段
+        assert_eq!(quiet_time_end(&config, time(20, 23, 0)), Some(time(21, 9, 0)));
+        // 未开启静默时间段时全天静默的星期同样不生效
+        let config = Config {
+            enable_notification_quiet_hours: false,
+            ..config
+        };
+        assert_eq!(quiet_time_end(&config, time(17, 12, 30)), None);
+    }
+}
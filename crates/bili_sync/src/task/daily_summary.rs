@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
@@ -6,8 +7,10 @@ use sea_orm::entity::prelude::*;
 use tokio_cron_scheduler::{Job, JobScheduler};
 
 use crate::bilibili::BiliClient;
-use crate::config::VersionedConfig;
-use crate::notifier::{NotifierAllExt, NOTIFICATION_QUEUE};
+use crate::config::{Config, VersionedConfig};
+use crate::notifier::{
+    NOTIFICATION_QUEUE, NotificationEvent, NotificationPayload, NotificationQueue, NotifierAllExt, render_message,
+};
 use crate::utils::model::get_enabled_video_sources;
 use crate::utils::status::VideoStatus;
 use bili_sync_entity::{video, favorite, collection, submission};
@@ -37,14 +40,10 @@ pub async fn init_daily_summary_task(
                 if let Some(notifiers) = &config.notifiers
                     && !notifiers.is_empty()
                 {
-                    match generate_daily_summary(&connection).await {
+                    match generate_daily_summary(&connection, &config.message_templates).await {
                         Ok(summary) => {
-                            let client = bili_client.inner_client().clone();
-                            let _ = notifiers.notify_all_queued(
-                                &NOTIFICATION_QUEUE,
-                                client,
-                                summary,
-                            );
+                            let client = bili_client.notifier_client().clone();
+                            enqueue_daily_summary(&config, &NOTIFICATION_QUEUE, client, summary, false);
                         }
                         Err(e) => {
                             tracing::error!("生成每日汇总失败: {:#}", e);
@@ -59,8 +58,36 @@ pub async fn init_daily_summary_task(
     Ok(task_id)
 }
 
+/// 将每日汇总加入通知队列，未开启每日汇总通知或未配置通知器时不发送，返回是否已加入队列
+/// 手动触发时跳过消息去重，统计数据未变化时也会发送，且不会导致之后的定时汇总被去重
+pub(crate) fn enqueue_daily_summary(
+    config: &Config,
+    queue: &NotificationQueue,
+    client: reqwest::Client,
+    summary: NotificationPayload,
+    manual: bool,
+) -> bool {
+    if !config.notify_daily_summary {
+        return false;
+    }
+    match &config.notifiers {
+        Some(notifiers) if !notifiers.is_empty() => {
+            let result = if manual {
+                notifiers.notify_all_queued_without_cache(queue, client, summary)
+            } else {
+                notifiers.notify_all_queued(queue, client, summary)
+            };
+            result.is_ok()
+        }
+        _ => false,
+    }
+}
+
 /// 生成每日汇总消息
-async fn generate_daily_summary(connection: &DatabaseConnection) -> Result<String> {
+pub(crate) async fn generate_daily_summary(
+    connection: &DatabaseConnection,
+    templates: &HashMap<String, String>,
+) -> Result<NotificationPayload> {
     // 获取所有视频源
     let video_sources = get_enabled_video_sources(connection)
         .await
@@ -140,9 +167,138 @@ async fn generate_daily_summary(connection: &DatabaseConnection) -> Result<Strin
         format!("  |  UP投稿: {} 个", submission_count),
         format!("  |  总  计: {} 个", video_sources.len()),
     ].join("\n");
-    
-            
-    
-    Ok(summary)
+    let summary = render_message(
+        templates,
+        NotificationEvent::DailySummary,
+        serde_json::json!({
+            "total_videos": total_videos,
+            "succeeded_videos": succeeded_videos,
+            "failed_videos": failed_videos,
+            "waiting_videos": waiting_videos,
+            "skipped_videos": skipped_videos,
+            "paid_videos": paid_videos,
+            "favorite_count": favorite_count,
+            "collection_count": collection_count,
+            "submission_count": submission_count,
+            "source_count": video_sources.len(),
+        }),
+        summary,
+    );
+
+    Ok(NotificationPayload {
+        event: NotificationEvent::DailySummary,
+        message: summary,
+        video_count: Some(total_videos),
+        succeeded: Some(succeeded_videos),
+        failed: Some(failed_videos),
+        ..Default::default()
+    })
 }
 
+#[cfg(test)]
+mod tests {
+    use sea_orm::ActiveModelTrait;
+    use sea_orm::ActiveValue::Set;
+
+    use super::*;
+    use crate::notifier::{Notifier, structured_webhook_body};
+    use crate::utils::status::STATUS_OK;
+    use crate::utils::test_utils::{TestDatabase, test_video};
+
+    #[tokio::test]
+    async fn test_daily_summary_structured_payload() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        for (bvid, status) in [("BV1test00001", [STATUS_OK; 5]), ("BV1test00002", [0; 5])] {
+            video::ActiveModel {
+                name: Set(bvid.to_string()),
+                path: Set(test_dir.join(bvid).to_string_lossy().to_string()),
+                cover: Set(String::new()),
+                download_status: Set(VideoStatus::from(status).into()),
+                ..test_video(bvid)
+            }
+            .insert(&connection)
+            .await?;
+        }
+        let payload = generate_daily_summary(&connection, &HashMap::new()).await?;
+        let body: serde_json::Value = serde_json::from_str(&structured_webhook_body(
+            &payload,
+            "2026-10-17 09:00:00",
+            "2026-10-17 09:00:05",
+        )?)?;
+        assert_eq!(body["event"], "daily_summary");
+        assert_eq!(body["video_count"], 2);
+        assert_eq!(body["succeeded"], 1);
+        assert_eq!(body["failed"], 0);
+        assert_eq!(body["created_at"], "2026-10-17 09:00:00");
+        assert_eq!(body["sent_at"], "2026-10-17 09:00:05");
+        assert!(
+            body["message"]
+                .as_str()
+                .is_some_and(|message| message.contains("每日汇总"))
+        );
+        // 与具体视频无关的字段不会出现在请求体中
+        assert!(body.get("bvid").is_none() && body.get("source_name").is_none());
+        // 自定义模板可以引用统计变量，未配置模板的事件不受影响
+        let templates = HashMap::from([
+            (
+                "daily_summary".to_owned(),
+                "{{succeeded_videos}}/{{total_videos}} done, {{waiting_videos}} waiting, {{source_count}} sources"
+                    .to_owned(),
+            ),
+            ("new_videos".to_owned(), "unused".to_owned()),
+        ]);
+        let payload = generate_daily_summary(&connection, &templates).await?;
+        assert_eq!(payload.message, "1/2 done, 1 waiting, 0 sources");
+        assert_eq!(payload.event, NotificationEvent::DailySummary);
+        // 模板中可以通过 message 引用内置的消息
+        let templates = HashMap::from([("daily_summary".to_owned(), "[BiliSync] {{message}}".to_owned())]);
+        let payload = generate_daily_summary(&connection, &templates).await?;
+        assert!(payload.message.starts_with("[BiliSync] 📊 BiliSync 每日汇总"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_daily_summary() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let summary = generate_daily_summary(&connection, &HashMap::new()).await?;
+        assert!(!summary.message.is_empty());
+        let notifiers = Arc::new(vec![Notifier::Webhook {
+            url: "http://127.0.0.1:9/webhook".to_owned(),
+            template: None,
+            payload_mode: Default::default(),
+            ignore_cache: None,
+        }]);
+        let (queue, client) = (NotificationQueue::new(), reqwest::Client::new());
+        let enqueue = |config: &Config, manual: bool| {
+            enqueue_daily_summary(config, &queue, client.clone(), summary.clone(), manual)
+        };
+        // 未开启每日汇总通知或未配置通知器时不会加入队列
+        for config in [
+            Config {
+                notify_daily_summary: false,
+                notifiers: Some(notifiers.clone()),
+                ..Default::default()
+            },
+            Config {
+                notify_daily_summary: true,
+                notifiers: Some(Arc::new(Vec::new())),
+                ..Default::default()
+            },
+        ] {
+            assert!(!enqueue(&config, false));
+        }
+        assert_eq!(queue.status().pending, 0);
+        let config = Config {
+            notify_daily_summary: true,
+            notifiers: Some(notifiers),
+            ..Default::default()
+        };
+        // 手动触发的汇总跳过去重，与定时汇总同样加入队列
+        assert!(enqueue(&config, false));
+        assert!(enqueue(&config, true));
+        assert_eq!(queue.status().pending, 2);
+        Ok(())
+    }
+}
@@ -1,6 +1,12 @@
 mod daily_summary;
 mod http_server;
+mod retry_queue;
 mod video_downloader;
 
+pub(crate) use daily_summary::{enqueue_daily_summary, generate_daily_summary};
 pub use http_server::http_server;
-pub use video_downloader::{DownloadTaskManager, TaskStatus, video_downloader};
+pub use retry_queue::{RETRY_QUEUE, RetryBatch, RetryJob};
+pub use video_downloader::{
+    CredentialRefreshResult, DownloadTaskManager, ScanSourceResult, TaskStatus, shutdown_video_downloader,
+    video_downloader,
+};
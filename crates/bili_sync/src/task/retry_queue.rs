@@ -0,0 +1,437 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Arc, LazyLock};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::sync::{Notify, oneshot};
+use tokio::task::AbortHandle;
+
+use crate::config::VersionedConfig;
+use crate::task::DownloadTaskManager;
+
+/// 全局重试队列实例，同时执行的任务数与视频并发数一致
+pub static RETRY_QUEUE: LazyLock<RetryQueue> =
+    LazyLock::new(|| RetryQueue::with_concurrency(|| VersionedConfig::get().read().concurrent_limit.video));
+
+/// 重试任务的执行状态
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RetryJobStatus {
+    Queued,
+    Running,
+}
+
+/// 重试队列中的一个任务，对应一个视频所有失败子任务的重试
+#[derive(Serialize, Clone, Debug)]
+pub struct RetryJob {
+    pub id: i32,
+    pub video_id: i32,
+    pub video_name: String,
+    pub status: RetryJobStatus,
+    pub created_at: chrono::DateTime<chrono::Local>,
+    /// 通过批量重试加入队列的任务所属的批次
+    pub batch_id: Option<i32>,
+}
+
+/// 批量重试的执行结果统计，保留在内存中直到程序重启
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RetryBatch {
+    pub id: i32,
+    pub total_count: usize,
+    pub succeeded_count: usize,
+    pub failed_count: usize,
+    /// 开始执行前被取消的任务数
+    pub cancelled_count: usize,
+}
+
+struct QueuedJob {
+    job: RetryJob,
+    /// 单个视频的重试由用户等待结果，排在批量加入的任务之前
+    priority: bool,
+    handle: AbortHandle,
+}
+
+struct RetryQueueInner {
+    next_id: AtomicI32,
+    next_batch_id: AtomicI32,
+    queued: Mutex<VecDeque<QueuedJob>>,
+    running: Mutex<Vec<RetryJob>>,
+    batches: Mutex<HashMap<i32, RetryBatch>>,
+    /// 同时执行的任务数上限，每次开始任务时读取，修改配置后立即生效
+    concurrency: Box<dyn Fn() -> usize + Send + Sync>,
+    /// 正在执行的任务结束或队列发生变化时唤醒等待的任务，由队首的任务开始执行
+    turn: Notify,
+}
+
+impl RetryQueueInner {
+    /// 正在执行的任务数未达到上限且 id 位于队首时，将其移出队列并标记为正在执行
+    fn try_start(&self, id: i32) -> Option<bool> {
+        let mut queued = self.queued.lock();
+        if queued.front()?.job.id != id {
+            return Some(false);
+        }
+        let mut running = self.running.lock();
+        if running.len() >= (self.concurrency)().max(1) {
+            return Some(false);
+        }
+        let job = queued.pop_front()?.job;
+        running.push(RetryJob {
+            status: RetryJobStatus::Running,
+            ..job
+        });
+        Some(true)
+    }
+
+    /// 记录批量重试中一个任务的执行结果
+    fn record_batch_result(&self, batch_id: i32, succeeded: bool) {
+        if let Some(batch) = self.batches.lock().get_mut(&batch_id) {
+            match succeeded {
+                true => batch.succeeded_count += 1,
+                false => batch.failed_count += 1,
+            }
+        }
+    }
+}
+
+/// 任务结束时清除正在执行的任务并唤醒下一个任务，任务 panic 或被中止时同样生效
+struct RunningGuard<'a>(&'a RetryQueueInner, i32);
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.running.lock().retain(|job| job.id != self.1);
+        self.0.turn.notify_waiters();
+    }
+}
+
+/// 重试队列，任务按加入的顺序开始执行，尚未开始的任务可以被取消
+pub struct RetryQueue {
+    inner: Arc<RetryQueueInner>,
+}
+
+impl RetryQueue {
+    /// 创建逐个执行任务的重试队列
+    pub fn new() -> Self {
+        Self::with_concurrency(|| 1)
+    }
+
+    /// 创建重试队列，同时执行的任务数上限由 concurrency 给出
+    pub fn with_concurrency(concurrency: impl Fn() -> usize + Send + Sync + 'static) -> Self {
+        Self {
+            inner: Arc::new(RetryQueueInner {
+                next_id: AtomicI32::new(0),
+                next_batch_id: AtomicI32::new(0),
+                queued: Mutex::new(VecDeque::new()),
+                running: Mutex::new(Vec::new()),
+                batches: Mutex::new(HashMap::new()),
+                concurrency: Box::new(concurrency),
+                turn: Notify::new(),
+            }),
+        }
+    }
+
+    /// 将重试任务加入队列末尾，返回任务 ID
+    ///
+    /// 每个任务运行在独立的 tokio 任务中，单个任务 panic 不会影响后续的任务
+    pub fn enqueue(&self, video_id: i32, video_name: String, future: impl Future<Output = ()> + Send + 'static) -> i32 {
+        self.push(video_id, video_name, false, None, future)
+    }
+
+    /// 将一批重试任务加入队列末尾，任务返回是否执行成功，执行结果汇总到返回的批次中，可以通过 batch 查询
+    pub fn enqueue_batch<F>(&self, jobs: Vec<(i32, String, F)>) -> RetryBatch
+    where
+        F: Future<Output = bool> + Send + 'static,
+    {
+        let batch = RetryBatch {
+            id: self.inner.next_batch_id.fetch_add(1, Ordering::Relaxed) + 1,
+            total_count: jobs.len(),
+            ..Default::default()
+        };
+        self.inner.batches.lock().insert(batch.id, batch);
+        for (video_id, video_name, future) in jobs {
+            let inner = self.inner.clone();
+            self.push(video_id, video_name, false, Some(batch.id), async move {
+                let succeeded = future.await;
+                inner.record_batch_result(batch.id, succeeded);
+            });
+        }
+        batch
+    }
+
+    /// 查询批量重试当前的执行结果
+    pub fn batch(&self, id: i32) -> Option<RetryBatch> {
+        self.inner.batches.lock().get(&id).copied()
+    }
+
+    /// 将重试任务加入队列，priority 为 true 时排在所有批量加入的任务之前，同类任务按加入的先后顺序执行
+    ///
+    /// 任务开始执行后持有下载任务的运行锁，避免与定时的下载任务同时处理同一视频
+    fn push(
+        &self,
+        video_id: i32,
+        video_name: String,
+        priority: bool,
+        batch_id: Option<i32>,
+        future: impl Future<Output = ()> + Send + 'static,
+    ) -> i32 {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let job = RetryJob {
+            id,
+            video_id,
+            video_name,
+            status: RetryJobStatus::Queued,
+            created_at: chrono::Local::now(),
+            batch_id,
+        };
+        let inner = self.inner.clone();
+        // 持有锁直到任务加入队列，避免任务在入队前就开始执行
+        let mut queued = self.inner.queued.lock();
+        let handle = tokio::spawn(async move {
+            loop {
+                // 先注册唤醒再检查，避免检查与等待之间错过通知
+                let notified = inner.turn.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+                match inner.try_start(id) {
+                    Some(true) => break,
+                    Some(false) => notified.await,
+                    // 任务已被取消
+                    None => return,
+                }
+            }
+            let _running = RunningGuard(&inner, id);
+            let _lock = match DownloadTaskManager::try_get() {
+                Some(task_manager) => Some(task_manager.lock_running().await),
+                None => None,
+            };
+            future.await;
+        });
+        let index = if priority {
+            queued
+                .iter()
+                .position(|queued_job| !queued_job.priority)
+                .unwrap_or(queued.len())
+        } else {
+            queued.len()
+        };
+        queued.insert(
+            index,
+            QueuedJob {
+                job,
+                priority,
+                handle: handle.abort_handle(),
+            },
+        );
+        drop(queued);
+        self.inner.turn.notify_waiters();
+        id
+    }
+
+    /// 将重试任务优先加入队列并等待其执行完成，任务在开始执行前被取消或执行时 panic 返回 None
+    pub async fn run<T: Send + 'static>(
+        &self,
+        video_id: i32,
+        video_name: String,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Option<T> {
+        let (tx, rx) = oneshot::channel();
+        self.push(video_id, video_name, true, None, async move {
+            let _ = tx.send(future.await);
+        });
+        rx.await.ok()
+    }
+
+    /// 列出正在执行与等待执行的重试任务，正在执行的任务排在最前
+    pub fn list(&self) -> Vec<RetryJob> {
+        let running = self.inner.running.lock().clone();
+        running
+            .into_iter()
+            .chain(self.inner.queued.lock().iter().map(|queued_job| queued_job.job.clone()))
+            .collect()
+    }
+
+    /// 取消尚未开始执行的重试任务，返回被取消的任务，任务不存在或已开始执行时返回 None
+    pub fn cancel(&self, id: i32) -> Option<RetryJob> {
+        let mut queued = self.inner.queued.lock();
+        let index = queued.iter().position(|queued_job| queued_job.job.id == id)?;
+        let queued_job = queued.remove(index)?;
+        queued_job.handle.abort();
+        drop(queued);
+        if let Some(batch_id) = queued_job.job.batch_id
+            && let Some(batch) = self.inner.batches.lock().get_mut(&batch_id)
+        {
+            batch.cancelled_count += 1;
+        }
+        // 被取消的任务可能位于队首，唤醒下一个任务
+        self.inner.turn.notify_waiters();
+        Some(queued_job.job)
+    }
+}
+
+impl Default for RetryQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use tokio::sync::oneshot;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancel_queued_retry() {
+        let queue = RetryQueue::new();
+        let executed = Arc::new(AtomicUsize::new(0));
+        // 第一个任务会一直执行到手动放行，后面的任务在此期间保持排队
+        let (release_tx, release_rx) = oneshot::channel::<()>();
+        let (started_tx, started_rx) = oneshot::channel::<()>();
+        let first = queue.enqueue(1, "视频1".to_owned(), async move {
+            let _ = started_tx.send(());
+            let _ = release_rx.await;
+        });
+        started_rx.await.expect("first job should start");
+        let (done_tx, done_rx) = oneshot::channel::<()>();
+        let ids = (2..=3)
+            .map(|video_id| {
+                let executed = executed.clone();
+                queue.enqueue(video_id, format!("视频{}", video_id), async move {
+                    executed.fetch_add(1, Ordering::SeqCst);
+                })
+            })
+            .collect::<Vec<_>>();
+        let done = queue.enqueue(4, "视频4".to_owned(), async move {
+            let _ = done_tx.send(());
+        });
+        let jobs = queue.list();
+        assert_eq!(
+            jobs.iter().map(|job| (job.id, job.status)).collect::<Vec<_>>(),
+            vec![
+                (first, RetryJobStatus::Running),
+                (ids[0], RetryJobStatus::Queued),
+                (ids[1], RetryJobStatus::Queued),
+                (done, RetryJobStatus::Queued),
+            ]
+        );
+        // 正在执行的任务无法取消，排队中的任务取消后不再执行
+        assert!(queue.cancel(first).is_none());
+        assert_eq!(queue.cancel(ids[0]).map(|job| job.video_id), Some(2));
+        assert!(queue.cancel(ids[0]).is_none());
+        release_tx.send(()).expect("first job should be waiting");
+        done_rx.await.expect("last job should run");
+        assert_eq!(executed.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_order() {
+        let queue = RetryQueue::new();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let (release_tx, release_rx) = oneshot::channel::<()>();
+        let (started_tx, started_rx) = oneshot::channel::<()>();
+        let first = queue.enqueue(1, "视频1".to_owned(), async move {
+            let _ = started_tx.send(());
+            let _ = release_rx.await;
+        });
+        started_rx.await.expect("first job should start");
+        // 批量加入的任务按加入顺序执行，单个视频的重试排在批量任务之前
+        let (done_tx, done_rx) = oneshot::channel::<()>();
+        let mut done_tx = Some(done_tx);
+        let mut ids = Vec::new();
+        for (video_id, priority) in [(2, false), (3, false), (4, true), (5, true)] {
+            let order = order.clone();
+            let done_tx = (video_id == 3).then(|| done_tx.take()).flatten();
+            ids.push(
+                queue.push(video_id, format!("视频{}", video_id), priority, None, async move {
+                    order.lock().push(video_id);
+                    if let Some(done_tx) = done_tx {
+                        let _ = done_tx.send(());
+                    }
+                }),
+            );
+        }
+        assert_eq!(
+            queue.list().iter().map(|job| job.id).collect::<Vec<_>>(),
+            vec![first, ids[2], ids[3], ids[0], ids[1]]
+        );
+        release_tx.send(()).expect("first job should be waiting");
+        done_rx.await.expect("last job should run");
+        assert_eq!(*order.lock(), vec![4, 5, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_retry_batch() {
+        let queue = RetryQueue::with_concurrency(|| 2);
+        let (release_tx, release_rx) = oneshot::channel::<()>();
+        let (started_tx, started_rx) = oneshot::channel::<()>();
+        queue.enqueue(1, "视频1".to_owned(), async move {
+            let _ = started_tx.send(());
+            let _ = release_rx.await;
+        });
+        started_rx.await.expect("first job should start");
+        // 第一个任务执行期间，批量任务中的第一个任务可以同时执行，其余任务排队
+        let (gate_tx, gate_rx) = oneshot::channel::<()>();
+        let (batch_started_tx, batch_started_rx) = oneshot::channel::<()>();
+        let mut gate = Some((batch_started_tx, gate_rx));
+        let jobs = (2..=4)
+            .map(|video_id| {
+                let gate = gate.take();
+                (video_id, format!("视频{}", video_id), async move {
+                    if let Some((started_tx, gate_rx)) = gate {
+                        let _ = started_tx.send(());
+                        let _ = gate_rx.await;
+                    }
+                    video_id != 3
+                })
+            })
+            .collect();
+        let batch = queue.enqueue_batch(jobs);
+        assert_eq!(batch.total_count, 3);
+        batch_started_rx.await.expect("first batch job should start");
+        let jobs = queue.list();
+        assert_eq!(
+            jobs.iter()
+                .map(|job| (job.video_id, job.status, job.batch_id))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, RetryJobStatus::Running, None),
+                (2, RetryJobStatus::Running, Some(batch.id)),
+                (3, RetryJobStatus::Queued, Some(batch.id)),
+                (4, RetryJobStatus::Queued, Some(batch.id)),
+            ]
+        );
+        assert!(queue.cancel(jobs[3].id).is_some());
+        gate_tx.send(()).expect("first batch job should be waiting");
+        release_tx.send(()).expect("first job should be waiting");
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while !queue.list().is_empty() {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("queue should drain");
+        assert_eq!(
+            queue.batch(batch.id),
+            Some(RetryBatch {
+                id: batch.id,
+                total_count: 3,
+                succeeded_count: 1,
+                failed_count: 1,
+                cancelled_count: 1,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_panicked_retry_does_not_block_queue() {
+        let queue = RetryQueue::new();
+        let panicked = queue.run(1, "视频1".to_owned(), async { panic!("retry panicked") });
+        assert_eq!(panicked.await, None::<()>);
+        // 任务 panic 后正在执行的任务被清除，后续任务正常执行
+        assert!(queue.list().is_empty());
+        assert_eq!(queue.run(2, "视频2".to_owned(), async { 2 }).await, Some(2));
+        assert!(queue.list().is_empty());
+    }
+}
@@ -1,28 +1,49 @@
 use std::pin::Pin;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::time::Duration;
 
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
+use futures::StreamExt;
+use parking_lot::Mutex;
 use sea_orm::DatabaseConnection;
 use serde::Serialize;
 use tokio::sync::{OnceCell, watch};
 use tokio_cron_scheduler::{Job, JobScheduler};
+use tokio_util::sync::CancellationToken;
 
-use crate::adapter::{VideoSource, VideoSourceEnum};
-use crate::bilibili::{self, BiliClient, BiliError};
-use crate::config::{ARGS, Config, TEMPLATE, Trigger, VersionedConfig};
-use crate::utils::model::get_enabled_video_sources;
+use crate::adapter::{VideoSource, VideoSourceEnum, VideoSourceKey};
+use crate::bilibili::{self, BiliClient, Credential};
+use crate::config::{ARGS, CONFIG_DIR, Config, TEMPLATE, Trigger, VersionedConfig};
+use crate::database::{DATABASE_FILE_NAME, vacuum_database};
+use crate::error::DownloadCancelled;
+use crate::notifier::NotificationPayload;
+use crate::utils::model::{get_enabled_video_sources, prune_task_events};
 use crate::utils::notify::{error_and_notify, notify};
+use crate::utils::signal::SHUTDOWN;
 use crate::workflow::process_video_source;
 
 static INSTANCE: OnceCell<DownloadTaskManager> = OnceCell::const_new();
 
+/// 检查凭据是否需要刷新的最大尝试次数，用于应对检查接口偶发的网络错误
+const CREDENTIAL_REFRESH_ATTEMPTS: usize = 3;
+/// 检查刷新凭据失败后，再次尝试前等待的时长
+const CREDENTIAL_REFRESH_RETRY_DELAY: Duration = Duration::from_secs(5);
+
 /// 启动周期下载视频的任务
 pub async fn video_downloader(connection: DatabaseConnection, bili_client: Arc<BiliClient>) -> Result<()> {
     let task_manager = DownloadTaskManager::init(connection, bili_client).await?;
     task_manager.start().await
 }
 
+/// 收到终止信号后停止视频下载任务，等待正在下载的视频与分页完成后返回
+pub async fn shutdown_video_downloader() {
+    match INSTANCE.get() {
+        Some(task_manager) => task_manager.shutdown().await,
+        None => SHUTDOWN.cancel(),
+    }
+}
+
 pub struct DownloadTaskManager {
     sched: Arc<tokio::sync::Mutex<JobScheduler>>,
     cx: Arc<TaskContext>,
@@ -35,16 +56,74 @@ pub struct TaskStatus {
     last_run: Option<chrono::DateTime<chrono::Local>>,
     last_finish: Option<chrono::DateTime<chrono::Local>>,
     next_run: Option<chrono::DateTime<chrono::Local>>,
+    /// 触发风控后的冷却截止时间，冷却期间定时任务与手动触发的任务都会被跳过
+    cooldown_until: Option<chrono::DateTime<chrono::Local>>,
+    /// 下载任务是否被手动暂停
+    paused: bool,
+}
+
+impl TaskStatus {
+    /// 最近一次下载任务的结束时间
+    pub fn last_finish(&self) -> Option<chrono::DateTime<chrono::Local>> {
+        self.last_finish
+    }
+}
+
+/// 凭据检查与刷新的结果
+#[derive(Serialize, Default, Clone, Debug)]
+pub struct CredentialRefreshResult {
+    /// 是否有账号的凭据被刷新并保存
+    refreshed: bool,
+    /// 检查或刷新失败的账号及错误信息
+    error: Option<String>,
+}
+
+/// 手动扫描单个视频源的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanSourceResult {
+    /// 扫描任务已加入调度器，稍后开始执行
+    Scheduled,
+    /// 下载任务执行中，本次扫描被拒绝
+    Busy,
 }
 
 struct TaskContext {
     connection: DatabaseConnection,
     bili_client: Arc<BiliClient>,
-    running: tokio::sync::Mutex<()>,
+    // 下载任务等需要独占执行的任务持有写锁，重试队列中同时执行的任务共享读锁
+    running: tokio::sync::RwLock<()>,
     status_tx: watch::Sender<TaskStatus>,
     status_rx: watch::Receiver<TaskStatus>,
     video_task_id: tokio::sync::Mutex<Option<uuid::Uuid>>, // 存储当前视频下载任务的 UUID
     daily_summary_task_id: tokio::sync::Mutex<Option<uuid::Uuid>>, // 存储每日汇总任务的 UUID
+    // 暂停后视频下载任务在执行时会直接跳过，不影响正在执行的任务
+    paused: AtomicBool,
+    // 单独设置了扫描计划的视频源及其下载任务的 UUID
+    source_task_ids: tokio::sync::Mutex<Vec<(VideoSourceKey, uuid::Uuid)>>,
+    // 连续触发风控的轮数，用于计算逐次翻倍的冷却时长，完整执行一轮未触发风控后清零
+    risk_control_hits: AtomicU32,
+}
+
+/// 一轮下载任务需要处理的视频源范围
+#[derive(Clone, Copy)]
+enum ScanScope {
+    /// 所有启用的视频源，用于手动触发的下载任务
+    All,
+    /// 未单独设置扫描计划的视频源，用于按全局 interval 执行的下载任务
+    Shared,
+    /// 单独设置了扫描计划或被手动触发扫描的某个视频源
+    Single(VideoSourceKey),
+}
+
+impl ScanScope {
+    /// 判断视频源是否需要由本轮下载任务处理
+    fn contains(&self, video_source: &VideoSourceEnum, scheduled_sources: &[VideoSourceKey]) -> bool {
+        match self {
+            ScanScope::All => true,
+            ScanScope::Shared => !scheduled_sources.contains(&video_source.key()),
+            ScanScope::Single(key) => video_source.key() == *key,
+        }
+    }
 }
 
 impl DownloadTaskManager {
@@ -63,30 +142,115 @@ impl DownloadTaskManager {
         INSTANCE.get().expect("DownloadTaskManager is not initialized")
     }
 
+    /// 获取 DownloadTaskManager 单例，未初始化时返回 None
+    pub fn try_get() -> Option<&'static DownloadTaskManager> {
+        INSTANCE.get()
+    }
+
     /// 订阅下载任务的状态更新
     pub fn subscribe(&self) -> watch::Receiver<TaskStatus> {
         self.cx.status_rx.clone()
     }
 
-    /// 手动执行一次下载任务
-    pub async fn download_once(&self) -> Result<()> {
+    /// 获取当前生效的风控冷却截止时间，未处于冷却期时返回 None
+    pub fn cooldown_until(&self) -> Option<chrono::DateTime<chrono::Local>> {
+        self.cx
+            .status_rx
+            .borrow()
+            .cooldown_until
+            .filter(|until| *until > chrono::Local::now())
+    }
+
+    /// 下载任务是否处于暂停状态
+    pub fn is_paused(&self) -> bool {
+        self.cx.paused.load(Ordering::Relaxed)
+    }
+
+    /// 暂停视频下载任务，正在执行的任务会继续执行至结束，此后的定时与手动触发均会被跳过
+    pub fn pause(&self) {
+        self.cx.set_paused(true);
+    }
+
+    /// 恢复被暂停的视频下载任务
+    pub fn resume(&self) {
+        self.cx.set_paused(false);
+    }
+
+    /// 停止视频下载任务：尚未开始的视频源、视频与分页不再处理，正在下载的内容完成后返回
+    pub async fn shutdown(&self) {
+        SHUTDOWN.cancel();
+        let _lock = self.cx.running.write().await;
+    }
+
+    /// 手动检查并刷新凭据，与下载任务共用运行锁，下载任务或定时的刷新任务执行中时返回 None
+    pub async fn refresh_credential(&self) -> Option<CredentialRefreshResult> {
+        let _lock = self.cx.running.try_write().ok()?;
+        let config = VersionedConfig::get().snapshot();
+        info!("开始手动检查与刷新凭据..");
+        Some(check_and_refresh_credential(&self.cx.connection, &self.cx.bili_client, &config).await)
+    }
+
+    /// 整理数据库文件，与下载任务共用运行锁，下载任务执行中时返回 None
+    pub async fn vacuum_database(&self) -> Option<Result<(u64, u64)>> {
+        let _lock = self.cx.running.try_write().ok()?;
+        info!("开始整理数据库..");
+        Some(vacuum_database(&self.cx.connection, &CONFIG_DIR.join(DATABASE_FILE_NAME)).await)
+    }
+
+    /// 等待下载任务结束后获取共享的运行锁，多个持有者可以同时执行，持有期间不会开始新的下载任务
+    pub async fn lock_running(&self) -> tokio::sync::RwLockReadGuard<'_, ()> {
+        self.cx.running.read().await
+    }
+
+    /// 获取下载任务的运行锁，下载任务执行中时返回 None，持有期间不会开始新的下载任务
+    pub fn try_lock_running(&self) -> Option<tokio::sync::RwLockWriteGuard<'_, ()>> {
+        self.cx.running.try_write().ok()
+    }
+
+    /// 手动执行一次下载任务，dry_run 为 true 时仅演练，不实际下载与写入
+    pub async fn download_once(&self, dry_run: bool) -> Result<()> {
         let _ = self
             .sched
             .lock()
             .await
             .add(Job::new_one_shot_async(
                 Duration::from_secs(0),
-                DownloadTaskManager::download_video_task(self.cx.clone()),
+                DownloadTaskManager::download_video_task(self.cx.clone(), dry_run, ScanScope::All),
             )?)
             .await?;
         Ok(())
     }
 
+    /// 手动扫描并下载单个视频源，与其它下载任务共用运行锁，下载任务执行中时返回 ScanSourceResult::Busy
+    pub async fn scan_source(&self, key: VideoSourceKey) -> Result<ScanSourceResult> {
+        if self.cx.running.try_write().is_err() {
+            return Ok(ScanSourceResult::Busy);
+        }
+        let _ = self
+            .sched
+            .lock()
+            .await
+            .add(Job::new_one_shot_async(
+                Duration::from_secs(0),
+                DownloadTaskManager::download_video_task(self.cx.clone(), false, ScanScope::Single(key)),
+            )?)
+            .await?;
+        Ok(ScanSourceResult::Scheduled)
+    }
+
+    /// 视频源的扫描计划或启用状态修改后，重新创建视频源单独的下载任务
+    pub async fn reload_scan_schedules(&self) -> Result<()> {
+        let config = VersionedConfig::get().snapshot();
+        DownloadTaskManager::reload_source_tasks(&self.cx, &self.sched, &config).await
+    }
+
     /// 启动任务调度器
     async fn start(&self) -> Result<()> {
         self.sched.lock().await.start().await?;
         let mut shutdown_rx = self.shutdown_rx.clone();
         shutdown_rx.changed().await?;
+        // 等待正在执行的下载任务停止，避免关闭调度器时留下写入到一半的文件
+        self.shutdown().await;
         self.sched.lock().await.shutdown().await.context("任务调度器关闭失败")?;
         if let Err(e) = &*shutdown_rx.borrow() {
             bail!("{:#}", e);
@@ -99,7 +263,7 @@ impl DownloadTaskManager {
         let sched = Arc::new(tokio::sync::Mutex::new(JobScheduler::new().await?));
         let (status_tx, status_rx) = watch::channel(TaskStatus::default());
         let (running, video_task_id, daily_summary_task_id) = (
-            tokio::sync::Mutex::new(()),
+            tokio::sync::RwLock::new(()),
             tokio::sync::Mutex::new(None),
             tokio::sync::Mutex::new(None),
         );
@@ -111,6 +275,9 @@ impl DownloadTaskManager {
             status_rx,
             video_task_id,
             daily_summary_task_id,
+            paused: AtomicBool::new(false),
+            source_task_ids: tokio::sync::Mutex::new(Vec::new()),
+            risk_control_hits: AtomicU32::new(0),
         });
         // 读取初始配置
         let mut rx = VersionedConfig::get().subscribe();
@@ -130,12 +297,13 @@ impl DownloadTaskManager {
                 .await?;
         }
         // 初始化并添加视频下载任务，将任务 ID 保存到 TaskManager 中
+        // 首次执行前随机延迟一段时间，避免重启后立刻请求触发风控，也避免多个实例同时请求
         let video_task_id = async {
-            let job_run = DownloadTaskManager::download_video_task(cx.clone());
-            let job = match &initial_config.interval {
-                Trigger::Interval(interval) => Job::new_repeated_async(Duration::from_secs(*interval), job_run)?,
-                Trigger::Cron(cron) => Job::new_async_tz(cron, chrono::Local, job_run)?,
-            };
+            let job_run = DownloadTaskManager::with_startup_jitter(
+                DownloadTaskManager::download_video_task(cx.clone(), false, ScanScope::Shared),
+                initial_config.startup_jitter,
+            );
+            let job = new_job(&initial_config.interval, job_run)?;
             Result::<_, anyhow::Error>::Ok(sched.lock().await.add(job).await?)
         }
         .await;
@@ -162,6 +330,14 @@ impl DownloadTaskManager {
                 )?)
                 .await?;
         }
+        // 为单独设置了扫描计划的视频源添加独立的下载任务
+        if let Err(err) = DownloadTaskManager::reload_source_tasks(&cx, &sched, &initial_config).await {
+            error_and_notify(
+                &initial_config,
+                &cx.bili_client,
+                format!("❌ 初始化视频源下载任务失败 错误信息: {:#}", err),
+            );
+        }
         // 初始化每日汇总任务
         let daily_summary_task_id = crate::task::daily_summary::init_daily_summary_task(
             cx.connection.clone(),
@@ -192,13 +368,8 @@ impl DownloadTaskManager {
                             .context("移除旧的视频下载任务失败")?;
                     }
                     let new_video_task_id = async {
-                        let job_run = DownloadTaskManager::download_video_task(cx.clone());
-                        let job = match &new_config.interval {
-                            Trigger::Interval(interval) => {
-                                Job::new_repeated_async(Duration::from_secs(*interval), job_run)?
-                            }
-                            Trigger::Cron(cron) => Job::new_async_tz(cron, chrono::Local, job_run)?,
-                        };
+                        let job_run = DownloadTaskManager::download_video_task(cx.clone(), false, ScanScope::Shared);
+                        let job = new_job(&new_config.interval, job_run)?;
                         Result::<_, anyhow::Error>::Ok(sched_clone.lock().await.add(job).await?)
                     }
                     .await;
@@ -224,6 +395,14 @@ impl DownloadTaskManager {
                             )?)
                             .await?;
                     }
+                    // 重载配置时顺带重新读取视频源的扫描计划
+                    if let Err(err) = DownloadTaskManager::reload_source_tasks(&cx, &sched_clone, &new_config).await {
+                        error_and_notify(
+                            &new_config,
+                            &cx.bili_client,
+                            format!("❌ 重载视频源下载任务失败 错误信息: {:#}", err),
+                        );
+                    }
                     
                     // 更新每日汇总任务
                     let mut daily_summary_task_id = cx.daily_summary_task_id.lock().await;
@@ -272,16 +451,19 @@ impl DownloadTaskManager {
         move |_uuid, _l| {
             let cx = cx.clone();
             Box::pin(async move {
-                let _lock = cx.running.lock().await;
+                let _lock = cx.running.write().await;
                 let config = VersionedConfig::get().read();
                 info!("开始执行本轮凭据检查与刷新任务..");
-                match check_and_refresh_credential(&cx.connection, &cx.bili_client, &config).await {
-                    Ok(_) => info!("本轮凭据检查与刷新任务执行完毕"),
-                    Err(e) => {
+                match check_and_refresh_credential(&cx.connection, &cx.bili_client, &config)
+                    .await
+                    .error
+                {
+                    None => info!("本轮凭据检查与刷新任务执行完毕"),
+                    Some(e) => {
                         error_and_notify(
                             &config,
                             &cx.bili_client,
-                            format!("❌ 凭据检查与刷新任务执行失败 错误信息: {:#}", e),
+                            format!("❌ 凭据检查与刷新任务执行失败 错误信息: {}", e),
                         );
                     }
                 }
@@ -308,98 +490,558 @@ impl DownloadTaskManager {
         }
     }
 
+    /// 为单独设置了扫描计划的视频源创建独立的下载任务，已有的视频源下载任务会先被移除
+    /// 创建失败的视频源不会记录在 source_task_ids 中，仍然跟随全局的下载任务执行
+    async fn reload_source_tasks(
+        cx: &Arc<TaskContext>,
+        sched: &tokio::sync::Mutex<JobScheduler>,
+        config: &Config,
+    ) -> Result<()> {
+        let mut source_task_ids = cx.source_task_ids.lock().await;
+        while let Some((_, task_id)) = source_task_ids.pop() {
+            sched
+                .lock()
+                .await
+                .remove(&task_id)
+                .await
+                .context("移除旧的视频源下载任务失败")?;
+        }
+        for video_source in get_enabled_video_sources(&cx.connection).await? {
+            let Some(trigger) = video_source.scan_trigger() else {
+                continue;
+            };
+            let job_run =
+                DownloadTaskManager::download_video_task(cx.clone(), false, ScanScope::Single(video_source.key()));
+            let job = match trigger {
+                Trigger::Interval(secs) if secs <= 60 => Err(anyhow!("扫描间隔时间必须大于 60 秒")),
+                trigger => new_job(&trigger, job_run),
+            };
+            match job {
+                Ok(job) => {
+                    let task_id = sched.lock().await.add(job).await?;
+                    source_task_ids.push((video_source.key(), task_id));
+                }
+                Err(err) => error_and_notify(
+                    config,
+                    &cx.bili_client,
+                    format!(
+                        "❌ 初始化{}的下载任务失败，将跟随全局下载任务执行 错误信息: {:#}",
+                        video_source.display_name(),
+                        err
+                    ),
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// 为任务的首次执行添加 [0, jitter] 秒内的随机延迟
+    fn with_startup_jitter(
+        mut job_run: impl FnMut(uuid::Uuid, JobScheduler) -> Pin<Box<dyn Future<Output = ()> + Send>>,
+        jitter: u64,
+    ) -> impl FnMut(uuid::Uuid, JobScheduler) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let mut delay = Some(Duration::from_secs(rand::random_range(0..=jitter)));
+        move |uuid, l| {
+            let job = job_run(uuid, l);
+            let delay = delay.take();
+            Box::pin(async move {
+                if let Some(delay) = delay.filter(|d| !d.is_zero()) {
+                    info!("首次执行视频下载任务前随机等待 {} 秒..", delay.as_secs());
+                    tokio::time::sleep(delay).await;
+                }
+                job.await
+            })
+        }
+    }
+
     fn download_video_task(
         cx: Arc<TaskContext>,
+        dry_run: bool,
+        scope: ScanScope,
     ) -> impl FnMut(uuid::Uuid, JobScheduler) -> Pin<Box<dyn Future<Output = ()> + Send>> {
         move |uuid, mut l| {
             let cx = cx.clone();
             Box::pin(async move {
-                let Ok(_lock) = cx.running.try_lock() else {
+                if SHUTDOWN.is_cancelled() {
+                    info!("程序正在停止，跳过本次视频下载任务..");
+                    return;
+                }
+                // 暂停时在获取运行锁之前返回，不占用锁也不修改运行状态
+                if cx.paused.load(Ordering::Relaxed) {
+                    info!("视频下载任务已暂停，跳过本次执行..");
+                    let next_run = cx.next_run(uuid, &mut l).await;
+                    cx.status_tx.send_modify(|status| status.next_run = next_run);
+                    return;
+                }
+                let Ok(_lock) = cx.running.try_write() else {
                     warn!("上一次视频下载任务尚未结束，跳过本次执行..");
                     return;
                 };
+                let last_status = *cx.status_rx.borrow();
+                if let Some(cooldown_until) = last_status.cooldown_until
+                    && cooldown_until > chrono::Local::now()
+                {
+                    info!(
+                        "风控冷却中，跳过本次视频下载任务，冷却将于 {} 结束",
+                        cooldown_until.format("%H:%M")
+                    );
+                    let next_run = cx.next_run(uuid, &mut l).await;
+                    let _ = cx.status_tx.send(TaskStatus {
+                        next_run,
+                        ..last_status
+                    });
+                    return;
+                }
                 let _ = cx.status_tx.send(TaskStatus {
                     is_running: true,
                     last_run: Some(chrono::Local::now()),
                     last_finish: None,
                     next_run: None,
+                    cooldown_until: None,
+                    paused: cx.paused.load(Ordering::Relaxed),
                 });
-                info!("开始执行本轮视频下载任务..");
+                if dry_run {
+                    info!("开始执行本轮视频下载任务（演练模式）..");
+                } else {
+                    info!("开始执行本轮视频下载任务..");
+                }
                 let mut config = VersionedConfig::get().snapshot();
-                match download_video(&cx.connection, &cx.bili_client, &mut config).await {
-                    Ok(_) => info!("本轮视频下载任务执行完毕"),
+                let scheduled_sources = cx
+                    .source_task_ids
+                    .lock()
+                    .await
+                    .iter()
+                    .map(|(key, _)| *key)
+                    .collect::<Vec<_>>();
+                let cooldown_until = match download_video(
+                    &cx.connection,
+                    &cx.bili_client,
+                    &mut config,
+                    dry_run,
+                    scope,
+                    &scheduled_sources,
+                    &SHUTDOWN,
+                )
+                .await
+                {
+                    Ok(risk_control_triggered) => {
+                        info!("本轮视频下载任务执行完毕");
+                        cx.record_risk_control(risk_control_triggered, &config).map(|cooldown| {
+                            let cooldown_until = chrono::Local::now() + cooldown;
+                            let msg = format!(
+                                "⚠️ 视频下载任务连续 {} 轮触发风控，{} 前将跳过视频下载任务",
+                                cx.risk_control_hits.load(Ordering::Relaxed),
+                                cooldown_until.format("%m-%d %H:%M")
+                            );
+                            warn!("{}", msg);
+                            notify(&config, &cx.bili_client, NotificationPayload::error(msg));
+                            cooldown_until
+                        })
+                    }
                     Err(e) => {
                         error_and_notify(
                             &config,
                             &cx.bili_client,
                             format!("❌ 视频下载任务执行失败 错误信息: {:#}", e),
                         );
+                        None
                     }
-                }
-                // 注意此处尽量从 updating 中读取 uuid，因为当前任务可能是不存在 next_tick 的 oneshot 任务
-                let task_uuid = (*cx.video_task_id.lock().await).unwrap_or(uuid);
-                let next_run = l
-                    .next_tick_for_job(task_uuid)
-                    .await
-                    .ok()
-                    .flatten()
-                    .map(|dt| dt.with_timezone(&chrono::Local));
+                };
+                let next_run = cx.next_run(uuid, &mut l).await;
                 let last_status = *cx.status_rx.borrow();
                 let _ = cx.status_tx.send(TaskStatus {
                     is_running: false,
                     last_run: last_status.last_run,
                     last_finish: Some(chrono::Local::now()),
                     next_run,
+                    cooldown_until,
+                    paused: cx.paused.load(Ordering::Relaxed),
                 });
             })
         }
     }
 }
 
+impl TaskContext {
+    fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+        self.status_tx.send_modify(|status| status.paused = paused);
+    }
+
+    /// 记录一轮下载任务的风控结果，返回本次需要冷却的时长
+    /// 连续触发风控时冷却时长逐次翻倍直至上限，未触发风控时重置连续次数
+    fn record_risk_control(&self, risk_control_triggered: bool, config: &Config) -> Option<chrono::Duration> {
+        if !risk_control_triggered {
+            self.risk_control_hits.store(0, Ordering::Relaxed);
+            return None;
+        }
+        let hits = self.risk_control_hits.fetch_add(1, Ordering::Relaxed) + 1;
+        if config.risk_control_cooldown == 0 {
+            return None;
+        }
+        let cooldown = config
+            .risk_control_cooldown
+            .saturating_mul(1 << (hits - 1).min(32))
+            .min(config.risk_control_cooldown_max.max(config.risk_control_cooldown));
+        Some(chrono::Duration::seconds(cooldown as i64))
+    }
+
+    /// 获取视频下载任务的下次运行时间
+    async fn next_run(&self, uuid: uuid::Uuid, sched: &mut JobScheduler) -> Option<chrono::DateTime<chrono::Local>> {
+        // 注意此处尽量从 updating 中读取 uuid，因为当前任务可能是不存在 next_tick 的 oneshot 任务
+        let task_uuid = (*self.video_task_id.lock().await).unwrap_or(uuid);
+        sched
+            .next_tick_for_job(task_uuid)
+            .await
+            .ok()
+            .flatten()
+            .map(|dt| dt.with_timezone(&chrono::Local))
+    }
+}
+
+/// 根据触发方式构造定时任务
+fn new_job(
+    trigger: &Trigger,
+    job_run: impl FnMut(uuid::Uuid, JobScheduler) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync + 'static,
+) -> Result<Job> {
+    Ok(match trigger {
+        Trigger::Interval(interval) => Job::new_repeated_async(Duration::from_secs(*interval), job_run)?,
+        Trigger::Cron(cron) => Job::new_async_tz(cron, chrono::Local, job_run)?,
+    })
+}
+
+/// 依次检查并刷新每个账号的凭据，单个账号刷新失败不影响其它账号
 async fn check_and_refresh_credential(
     connection: &DatabaseConnection,
     bili_client: &BiliClient,
     config: &Config,
-) -> Result<()> {
-    match bili_client
-        .check_refresh(&config.credential)
-        .await
-        .context("检查刷新 Credential 失败")?
-    {
-        None => {
-            info!("Credential 无需刷新");
-        }
-        Some(new_credential) => {
+) -> CredentialRefreshResult {
+    refresh_credentials(
+        config,
+        bili_client,
+        CREDENTIAL_REFRESH_RETRY_DELAY,
+        |credential| async move { bili_client.prepare_refresh(&credential).await },
+        |credential, csrf| async move { bili_client.refresh_credential(&credential, &csrf).await },
+        |index, credential| async move {
             VersionedConfig::get()
-                .update_credential(new_credential, connection)
+                .update_credential(index, credential, connection)
+                .await
+                .map(|_| ())
+        },
+    )
+    .await
+}
+
+/// 使用 prepare_refresh 检查每个账号的凭据是否需要刷新，需要时使用 refresh 刷新，刷新后的凭据交由 persist 保存
+async fn refresh_credentials<C, CF, R, RF, P, PF>(
+    config: &Config,
+    bili_client: &BiliClient,
+    retry_delay: Duration,
+    prepare_refresh: C,
+    refresh: R,
+    persist: P,
+) -> CredentialRefreshResult
+where
+    C: Fn(Credential) -> CF,
+    CF: Future<Output = Result<Option<String>>>,
+    R: Fn(Credential, String) -> RF,
+    RF: Future<Output = Result<Credential>>,
+    P: Fn(usize, Credential) -> PF,
+    PF: Future<Output = Result<()>>,
+{
+    let (mut refreshed, mut errors) = (false, Vec::new());
+    for (index, credential) in config.credentials().enumerate() {
+        let account = account_name(index);
+        let res = async {
+            let Some(csrf) = prepare_refresh_with_retry(&prepare_refresh, credential, &account, retry_delay).await?
+            else {
+                info!("{}的 Credential 无需刷新", account);
+                return Ok(false);
+            };
+            // 刷新请求发出后旧的 refresh_token 可能已经失效，重试只会继续失败，因此仅尝试一次
+            let new_credential = refresh(credential.clone(), csrf).await?;
+            persist(index, new_credential)
                 .await
                 .context("新 Credential 持久化失败")?;
-            info!("Credential 已刷新并保存");
+            info!("{}的 Credential 已刷新并保存", account);
             // 通知用户凭据已刷新
-            let config = VersionedConfig::get().read();
             notify(
-                &config,
+                &VersionedConfig::get().read(),
                 bili_client,
-                "✅ 凭据已刷新 Credential 已自动刷新并保存，系统将继续正常运行。".to_string(),
+                format!(
+                    "✅ 凭据已刷新 {}的 Credential 已自动刷新并保存，系统将继续正常运行。",
+                    account
+                ),
             );
+            anyhow::Ok(true)
+        }
+        .await;
+        match res {
+            Ok(res) => refreshed |= res,
+            Err(e) => errors.push(format!("{}：{:#}", account, e)),
+        }
+    }
+    CredentialRefreshResult {
+        refreshed,
+        error: (!errors.is_empty()).then(|| errors.join("；")),
+    }
+}
+
+/// 检查单个账号的凭据是否需要刷新并获取 refresh_csrf，遇到短暂的网络错误时间隔 retry_delay 重试，尝试 CREDENTIAL_REFRESH_ATTEMPTS 次后仍失败才返回错误
+/// 返回 Ok(None) 表示凭据无需刷新，与检查失败区分开
+async fn prepare_refresh_with_retry<C, CF>(
+    prepare_refresh: &C,
+    credential: &Credential,
+    account: &str,
+    retry_delay: Duration,
+) -> Result<Option<String>>
+where
+    C: Fn(Credential) -> CF,
+    CF: Future<Output = Result<Option<String>>>,
+{
+    let mut attempt = 1;
+    loop {
+        match prepare_refresh(credential.clone()).await {
+            Ok(res) => return Ok(res),
+            Err(e) if attempt < CREDENTIAL_REFRESH_ATTEMPTS && is_transient_error(&e) => {
+                warn!(
+                    "{}的 Credential 检查刷新失败（第 {} 次尝试），{} 秒后重试: {:#}",
+                    account,
+                    attempt,
+                    retry_delay.as_secs(),
+                    e
+                );
+                tokio::time::sleep(retry_delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e.context(format!("检查刷新 Credential 失败，已尝试 {} 次", attempt))),
+        }
+    }
+}
+
+/// 是否为连接失败、超时或服务端 5xx 等短暂的网络错误，响应解析失败等其它错误重试也无济于事
+fn is_transient_error(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|e| e.is_connect() || e.is_timeout() || e.status().is_some_and(|status| status.is_server_error()))
+}
+
+fn account_name(index: usize) -> String {
+    match index {
+        0 => "主账号".to_owned(),
+        _ => format!("备用账号 {}", index),
+    }
+}
+
+/// 扫描过程中的账号轮换，当前账号触发风控时切换到下一个账号，所有账号都触发风控后不再切换
+/// 多个视频源并发扫描时共享同一个轮换状态，切换后的账号对所有视频源生效
+struct CredentialRotation {
+    credentials: Vec<Credential>,
+    current: Mutex<(usize, Arc<Config>)>,
+}
+
+impl CredentialRotation {
+    fn new(config: Arc<Config>) -> Self {
+        Self {
+            credentials: config.credentials().cloned().collect(),
+            current: Mutex::new((0, config)),
+        }
+    }
+
+    /// 当前使用的配置
+    fn config(&self) -> Arc<Config> {
+        self.current.lock().1.clone()
+    }
+
+    /// 使用 failed 时触发了风控，切换到下一个账号并返回使用该账号凭据的配置
+    /// 其它视频源已经切换过账号时直接返回当前的配置，没有剩余账号时返回 None
+    fn rotate(&self, failed: &Arc<Config>, err: &anyhow::Error) -> Option<Arc<Config>> {
+        let mut current = self.current.lock();
+        if !Arc::ptr_eq(&current.1, failed) {
+            return Some(current.1.clone());
+        }
+        let credential = self.credentials.get(current.0 + 1)?.clone();
+        warn!(
+            "{}触发风控，切换到{}后重试: {:#}",
+            account_name(current.0),
+            account_name(current.0 + 1),
+            err
+        );
+        let config = Arc::new(Config {
+            credential,
+            ..(**failed).clone()
+        });
+        *current = (current.0 + 1, config.clone());
+        Some(config)
+    }
+
+    /// 执行 f，触发风控时切换账号后重试，所有账号都触发风控时返回最后一次的错误
+    async fn run<T, Fut>(&self, mut f: impl FnMut(Arc<Config>) -> Fut) -> Result<T>
+    where
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut config = self.config();
+        loop {
+            match f(config.clone()).await {
+                Err(e) if bilibili::is_risk_control_error(&e) => match self.rotate(&config, &e) {
+                    Some(new_config) => config = new_config,
+                    None => return Err(e),
+                },
+                res => return res,
+            }
+        }
+    }
+}
+
+/// 某一类视频源在一轮任务中的扫描统计
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+struct SourceScanCount {
+    total: usize,
+    succeeded: usize,
+    /// 因风控未完成扫描的视频源数量，包括触发风控的视频源与之后未开始扫描的视频源
+    risk_controlled: usize,
+}
+
+/// 一轮任务中各类视频源的扫描统计
+#[derive(Default, Debug)]
+struct ScanStats {
+    collection: SourceScanCount,
+    favorite: SourceScanCount,
+    submission: SourceScanCount,
+    watch_later: SourceScanCount,
+    /// 是否因风控中断
+    risk_control_triggered: bool,
+}
+
+impl ScanStats {
+    fn count_mut(&mut self, video_source: &VideoSourceEnum) -> &mut SourceScanCount {
+        match video_source {
+            VideoSourceEnum::Collection(_) => &mut self.collection,
+            VideoSourceEnum::Favorite(_) => &mut self.favorite,
+            VideoSourceEnum::Submission(_) => &mut self.submission,
+            VideoSourceEnum::WatchLater(_) => &mut self.watch_later,
+        }
+    }
+
+    fn message(&self) -> String {
+        let stats_parts = [
+            ("合集", self.collection),
+            ("收藏夹", self.favorite),
+            ("投稿", self.submission),
+            ("稍后再看", self.watch_later),
+        ]
+        .into_iter()
+        .filter(|(_, count)| count.total > 0)
+        .map(|(name, count)| {
+            if count.risk_controlled > 0 {
+                format!(
+                    "{}: {} / {} - 待扫描: {}",
+                    name, count.succeeded, count.total, count.risk_controlled
+                )
+            } else {
+                format!("{}: {} / {}", name, count.succeeded, count.total)
+            }
+        })
+        .collect::<Vec<_>>();
+        format!("视频源扫描统计 - {}", stats_parts.join(" | "))
+    }
+}
+
+/// 单个视频源的扫描结果
+enum SourceScanResult {
+    Succeeded,
+    Failed,
+    /// 所有账号都触发了风控
+    RiskControlled,
+    /// 其它视频源触发风控后，尚未开始扫描的视频源不再扫描
+    NotStarted,
+}
+
+/// 以至多 concurrency 的并发度扫描视频源，任一视频源触发风控后不再开始扫描新的视频源
+async fn scan_video_sources<Fut>(
+    video_sources: Vec<VideoSourceEnum>,
+    concurrency: usize,
+    process: impl Fn(VideoSourceEnum) -> Fut,
+) -> ScanStats
+where
+    Fut: Future<Output = Result<()>>,
+{
+    let mut stats = ScanStats::default();
+    for video_source in &video_sources {
+        stats.count_mut(video_source).total += 1;
+    }
+    let stopped = AtomicBool::new(false);
+    let (stopped, process) = (&stopped, &process);
+    let mut results = futures::stream::iter(video_sources)
+        .map(|video_source| async move {
+            if stopped.load(Ordering::Relaxed) {
+                return (video_source, SourceScanResult::NotStarted);
+            }
+            let result = match process(video_source.clone()).await {
+                Ok(()) => SourceScanResult::Succeeded,
+                Err(e) if bilibili::is_risk_control_error(&e) => {
+                    warn!(
+                        "检测到风控，终止此轮视频下载任务 处理 {} 时触发风控: {:#}",
+                        video_source.display_name(),
+                        e
+                    );
+                    stopped.store(true, Ordering::Relaxed);
+                    SourceScanResult::RiskControlled
+                }
+                Err(_) => SourceScanResult::Failed,
+            };
+            (video_source, result)
+        })
+        .buffer_unordered(concurrency.max(1));
+    while let Some((video_source, result)) = results.next().await {
+        let count = stats.count_mut(&video_source);
+        match result {
+            SourceScanResult::Succeeded => count.succeeded += 1,
+            SourceScanResult::Failed => {}
+            SourceScanResult::RiskControlled | SourceScanResult::NotStarted => count.risk_controlled += 1,
         }
     }
-    Ok(())
+    stats.risk_control_triggered = stopped.load(Ordering::Relaxed);
+    stats
+}
+
+/// 从启用的视频源中筛选出本轮下载任务需要处理的视频源
+fn select_video_sources(
+    mut video_sources: Vec<VideoSourceEnum>,
+    scope: ScanScope,
+    scheduled_sources: &[VideoSourceKey],
+) -> Vec<VideoSourceEnum> {
+    video_sources.retain(|video_source| scope.contains(video_source, scheduled_sources));
+    video_sources
 }
 
+/// 执行一轮视频下载任务，返回值表示本轮任务是否因触发风控而中断
+/// scheduled_sources 为单独设置了扫描计划的视频源，按全局 interval 执行时会跳过这些视频源，cancel 被取消后不再开始处理新的视频与分页
 async fn download_video(
     connection: &DatabaseConnection,
     bili_client: &BiliClient,
     config: &mut Arc<Config>,
-) -> Result<()> {
+    dry_run: bool,
+    scope: ScanScope,
+    scheduled_sources: &[VideoSourceKey],
+    cancel: &CancellationToken,
+) -> Result<bool> {
     config.check().context("配置检查失败")?;
-    let mixin_key = bili_client
-        .wbi_img(&config.credential)
-        .await
-        .context("获取 wbi_img 失败")?
-        .into_mixin_key()
-        .context("解析 mixin key 失败")?;
-    bilibili::set_global_mixin_key(mixin_key);
+    // 按扫描开始的时间调整本轮使用的并发与限速
+    if let Some(scheduled) = config.scheduled_at(&chrono::Local::now()) {
+        info!("本轮视频下载任务处于自定义的时间段内，使用该时间段的并发与限速设置");
+        *config = Arc::new(scheduled);
+    }
+    // 演练模式下不请求视频列表，无需获取 mixin key
+    if !dry_run {
+        let mixin_key = bili_client
+            .wbi_img(&config.credential)
+            .await
+            .context("获取 wbi_img 失败")?
+            .into_mixin_key()
+            .context("解析 mixin key 失败")?;
+        bilibili::set_global_mixin_key(mixin_key);
+    }
     let template = TEMPLATE.snapshot();
     let bili_client = bili_client.snapshot()?;
     let video_sources = get_enabled_video_sources(connection)
@@ -407,148 +1049,633 @@ async fn download_video(
         .context("获取视频源列表失败")?;
     if video_sources.is_empty() {
         let msg = "⚠️ 没有可用的视频源 所有视频源均未启用，请检查视频源配置。";
-        notify(config, &bili_client, msg.to_string());
+        notify(config, &bili_client, NotificationPayload::error(msg.to_string()));
         bail!("没有可用的视频源");
     }
-    
-    // 统计待扫描的视频源数量（总计）
-    let mut total_collections = 0;
-    let mut total_favorites = 0;
-    let mut total_submissions = 0;
-    let mut total_watch_later = 0;
-    for source in &video_sources {
-        match source {
-            VideoSourceEnum::Collection(_) => total_collections += 1,
-            VideoSourceEnum::Favorite(_) => total_favorites += 1,
-            VideoSourceEnum::Submission(_) => total_submissions += 1,
-            VideoSourceEnum::WatchLater(_) => total_watch_later += 1,
-        }
-    }
-    
-    // 统计扫描成功的数量
-    let mut succeeded_collections = 0;
-    let mut succeeded_favorites = 0;
-    let mut succeeded_submissions = 0;
-    let mut succeeded_watch_later = 0;
-    
-    // 记录因风控未扫描的视频源数量
-    let mut risk_control_collections = 0;
-    let mut risk_control_favorites = 0;
-    let mut risk_control_submissions = 0;
-    let mut risk_control_watch_later = 0;
-    
-    // 记录是否因风控中断
-    let mut risk_control_triggered = false;
-    let mut risk_control_source_type: Option<&str> = None;
-    
-    // 直接消费 video_sources，记录每个视频源的类型以便统计
-    let mut remaining_sources: Vec<&str> = Vec::new();
-    for video_source in &video_sources {
-        let source_type = match video_source {
-            VideoSourceEnum::Collection(_) => "collection",
-            VideoSourceEnum::Favorite(_) => "favorite",
-            VideoSourceEnum::Submission(_) => "submission",
-            VideoSourceEnum::WatchLater(_) => "watch_later",
-        };
-        remaining_sources.push(source_type);
+    let video_sources = select_video_sources(video_sources, scope, scheduled_sources);
+    if video_sources.is_empty() {
+        info!("本轮视频下载任务没有需要处理的视频源");
+        return Ok(false);
     }
-    
-    // 遍历并处理视频源
-    for (index, video_source) in video_sources.into_iter().enumerate() {
-        let display_name = video_source.display_name();
-        let source_type = match &video_source {
-            VideoSourceEnum::Collection(_) => "collection",
-            VideoSourceEnum::Favorite(_) => "favorite",
-            VideoSourceEnum::Submission(_) => "submission",
-            VideoSourceEnum::WatchLater(_) => "watch_later",
-        };
-        
-        if let Err(e) = process_video_source(video_source, &bili_client, connection, &template, config).await {
-            // 检查是否是风控相关错误（使用 downcast_ref 避免消费错误）
-            if let Some(bili_err) = e.downcast_ref::<BiliError>() 
-                && bili_err.is_risk_control_related()
-            {
-                warn!("检测到风控，终止此轮视频下载任务 处理 {} 时触发风控: {:#}", display_name, e);
-                risk_control_triggered = true;
-                risk_control_source_type = Some(source_type);
-                // 记录当前和后续未扫描的视频源
-                for remaining_type in remaining_sources.iter().skip(index) {
-                    match *remaining_type {
-                        "collection" => risk_control_collections += 1,
-                        "favorite" => risk_control_favorites += 1,
-                        "submission" => risk_control_submissions += 1,
-                        "watch_later" => risk_control_watch_later += 1,
-                        _ => {}
+
+    // 并发处理视频源，触发风控时依次切换到备用账号
+    let rotation = CredentialRotation::new(config.clone());
+    let process = |video_source: VideoSourceEnum| {
+        let (rotation, bili_client, template) = (&rotation, &bili_client, &template);
+        async move {
+            let res = rotation
+                .run(|config| {
+                    let video_source = video_source.clone();
+                    async move {
+                        process_video_source(
+                            video_source,
+                            bili_client,
+                            connection,
+                            template,
+                            &config,
+                            dry_run,
+                            cancel,
+                        )
+                        .await
                     }
-                }
-                break;
-            }
-            // 其他错误正常通知
-            error_and_notify(
-                config,
-                &bili_client,
-                format!("❌ 处理 {} 失败 错误信息: {:#} 已跳过该视频源", display_name, e),
-            );
-        } else {
-            // 处理成功，根据类型增加计数
-            match source_type {
-                "collection" => succeeded_collections += 1,
-                "favorite" => succeeded_favorites += 1,
-                "submission" => succeeded_submissions += 1,
-                "watch_later" => succeeded_watch_later += 1,
-                _ => {}
+                })
+                .await;
+            // 所有账号都触发风控时由 scan_video_sources 终止此轮任务，因停机中断的视频源无需通知，其他错误正常通知
+            if let Err(e) = &res
+                && !bilibili::is_risk_control_error(e)
+                && !e.is::<DownloadCancelled>()
+            {
+                error_and_notify(
+                    &rotation.config(),
+                    bili_client,
+                    format!(
+                        "❌ 处理 {} 失败 错误信息: {:#} 已跳过该视频源",
+                        video_source.display_name(),
+                        e
+                    ),
+                );
             }
+            res
         }
-    }
-    
-    // 输出统计信息
-    let mut stats_parts = Vec::new();
+    };
+    let stats = scan_video_sources(video_sources, config.concurrent_limit.source, process).await;
+    *config = rotation.config();
+
+    let stats_message = stats.message();
+    info!("{}", stats_message);
     
-    // 合集统计
-    if total_collections > 0 {
-        if risk_control_collections > 0 {
-            stats_parts.push(format!("合集: {} / {} - 待扫描: {}", 
-                succeeded_collections, total_collections, risk_control_collections));
-        } else {
-            stats_parts.push(format!("合集: {} / {}", succeeded_collections, total_collections));
+    // 发送统计通知（静默时间段检查在 NotificationQueue 中统一处理），演练模式下不发送
+    if !dry_run {
+        notify(config, &bili_client, stats_message);
+    }
+
+    // 清理过期的任务状态变更记录
+    if !dry_run {
+        match prune_task_events(config.task_event_retention_days, connection).await {
+            Ok(0) => {}
+            Ok(count) => info!("已清理 {} 条过期的任务状态变更记录", count),
+            Err(e) => warn!("清理过期的任务状态变更记录失败：{:#}", e),
         }
     }
     
-    // 收藏夹统计
-    if total_favorites > 0 {
-        if risk_control_favorites > 0 {
-            stats_parts.push(format!("收藏夹: {} / {} - 待扫描: {}", 
-                succeeded_favorites, total_favorites, risk_control_favorites));
-        } else {
-            stats_parts.push(format!("收藏夹: {} / {}", succeeded_favorites, total_favorites));
+    Ok(stats.risk_control_triggered)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bili_sync_entity::*;
+    use sea_orm::ActiveModelTrait;
+    use sea_orm::ActiveValue::Set;
+
+    use super::*;
+    use crate::bilibili::RefreshHosts;
+    use crate::utils::test_utils::{TestDatabase, test_favorite};
+
+    fn test_context(connection: DatabaseConnection) -> Arc<TaskContext> {
+        let (status_tx, status_rx) = watch::channel(TaskStatus::default());
+        Arc::new(TaskContext {
+            connection,
+            bili_client: Arc::new(BiliClient::new().unwrap()),
+            running: tokio::sync::RwLock::new(()),
+            status_tx,
+            status_rx,
+            video_task_id: tokio::sync::Mutex::new(None),
+            daily_summary_task_id: tokio::sync::Mutex::new(None),
+            paused: AtomicBool::new(false),
+            source_task_ids: tokio::sync::Mutex::new(Vec::new()),
+            risk_control_hits: AtomicU32::new(0),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_paused_download_task_skips() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let cx = test_context(connection.clone());
+        let sched = JobScheduler::new().await?;
+        // 任务体以演练模式运行，且数据库中没有启用的视频源，恢复后执行的 download_video 不会发出任何网络请求
+        assert!(get_enabled_video_sources(&connection).await?.is_empty());
+        let mut job_run = DownloadTaskManager::download_video_task(cx.clone(), true, ScanScope::All);
+        cx.set_paused(true);
+        job_run(uuid::Uuid::new_v4(), sched.clone()).await;
+        let status = *cx.status_rx.borrow();
+        assert!(status.paused);
+        // 暂停时任务体不会执行，也不会占用运行锁
+        assert!(status.last_run.is_none() && status.last_finish.is_none());
+        assert!(cx.running.try_write().is_ok());
+        cx.set_paused(false);
+        job_run(uuid::Uuid::new_v4(), sched).await;
+        let status = *cx.status_rx.borrow();
+        assert!(!status.paused && !status.is_running);
+        assert!(status.last_run.is_some() && status.last_finish.is_some());
+        assert!(cx.running.try_write().is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_source_tasks_have_distinct_next_run() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        // 收藏夹每 10 分钟扫描一次，投稿每天凌晨三点扫描一次，合集未设置，跟随全局的下载任务
+        let favorite = favorite::ActiveModel {
+            scan_interval_secs: Set(Some(600)),
+            ..test_favorite(String::new())
+        }
+        .insert(&connection)
+        .await?;
+        let submission = submission::ActiveModel {
+            upper_id: Set(1),
+            upper_name: Set("测试作者".to_string()),
+            path: Set(String::new()),
+            enabled: Set(true),
+            scan_cron: Set(Some("0 0 3 * * *".to_string())),
+            ..Default::default()
+        }
+        .insert(&connection)
+        .await?;
+        collection::ActiveModel {
+            s_id: Set(1),
+            m_id: Set(1),
+            name: Set("测试合集".to_string()),
+            r#type: Set(1),
+            path: Set(String::new()),
+            enabled: Set(true),
+            ..Default::default()
+        }
+        .insert(&connection)
+        .await?;
+        let cx = test_context(connection.clone());
+        let sched = tokio::sync::Mutex::new(JobScheduler::new().await?);
+        let config = Config::default();
+        DownloadTaskManager::reload_source_tasks(&cx, &sched, &config).await?;
+        // 重复加载时会先移除旧任务，不会重复创建
+        DownloadTaskManager::reload_source_tasks(&cx, &sched, &config).await?;
+        let source_task_ids = cx.source_task_ids.lock().await.clone();
+        assert_eq!(source_task_ids.len(), 2);
+        let mut next_runs = HashMap::new();
+        for (key, task_id) in source_task_ids {
+            let next_run = sched
+                .lock()
+                .await
+                .next_tick_for_job(task_id)
+                .await?
+                .context("next tick should exist")?;
+            next_runs.insert(key, next_run);
         }
+        let favorite_next_run = next_runs[&VideoSourceKey::Favorite(favorite.id)];
+        let submission_next_run = next_runs[&VideoSourceKey::Submission(submission.id)];
+        assert_ne!(favorite_next_run, submission_next_run);
+        let until_favorite = favorite_next_run - chrono::Utc::now();
+        assert!(until_favorite > chrono::Duration::seconds(590) && until_favorite <= chrono::Duration::seconds(600));
+        assert_eq!(
+            submission_next_run
+                .with_timezone(&chrono::Local)
+                .format("%H:%M:%S")
+                .to_string(),
+            "03:00:00"
+        );
+        Ok(())
     }
-    
-    // 投稿统计
-    if total_submissions > 0 {
-        if risk_control_submissions > 0 {
-            stats_parts.push(format!("投稿: {} / {} - 待扫描: {}", 
-                succeeded_submissions, total_submissions, risk_control_submissions));
-        } else {
-            stats_parts.push(format!("投稿: {} / {}", succeeded_submissions, total_submissions));
+
+    #[tokio::test]
+    async fn test_single_source_scan() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let mut favorite_ids = Vec::new();
+        for f_id in 1..=2 {
+            let favorite = favorite::ActiveModel {
+                f_id: Set(f_id),
+                name: Set(format!("测试收藏夹 {}", f_id)),
+                ..test_favorite(String::new())
+            }
+            .insert(&connection)
+            .await?;
+            favorite_ids.push(favorite.id);
         }
+        // 单个视频源的扫描只会处理该视频源，即使它同时被单独调度
+        let scope = ScanScope::Single(VideoSourceKey::Favorite(favorite_ids[0]));
+        let scheduled_sources = [VideoSourceKey::Favorite(favorite_ids[0])];
+        let processed = Mutex::new(Vec::new());
+        let process = |video_source: VideoSourceEnum| {
+            processed.lock().push(video_source.key());
+            async { Ok(()) }
+        };
+        let video_sources = get_enabled_video_sources(&connection).await?;
+        assert_eq!(video_sources.len(), 2);
+        let stats = scan_video_sources(
+            select_video_sources(video_sources, scope, &scheduled_sources),
+            2,
+            process,
+        )
+        .await;
+        assert!(!stats.risk_control_triggered);
+        let selected = processed.into_inner();
+        assert_eq!(selected, [VideoSourceKey::Favorite(favorite_ids[0])]);
+        // 全局下载任务执行期间，手动扫描直接被拒绝，已排队的单个视频源扫描也会被跳过，避免同一视频源被并发处理
+        let task_manager = DownloadTaskManager {
+            sched: Arc::new(tokio::sync::Mutex::new(JobScheduler::new().await?)),
+            cx: test_context(connection.clone()),
+            shutdown_rx: watch::channel(Ok(())).1,
+        };
+        let cx = task_manager.cx.clone();
+        let mut job_run = DownloadTaskManager::download_video_task(cx.clone(), true, scope);
+        let running = cx.running.write().await;
+        assert_eq!(task_manager.scan_source(selected[0]).await?, ScanSourceResult::Busy);
+        job_run(uuid::Uuid::new_v4(), JobScheduler::new().await?).await;
+        assert!(cx.status_rx.borrow().last_run.is_none());
+        drop(running);
+        assert_eq!(
+            task_manager.scan_source(selected[0]).await?,
+            ScanSourceResult::Scheduled
+        );
+        Ok(())
     }
-    
-    // 稍后再看统计
-    if total_watch_later > 0 {
-        if risk_control_watch_later > 0 {
-            stats_parts.push(format!("稍后再看: {} / {} - 待扫描: {}", 
-                succeeded_watch_later, total_watch_later, risk_control_watch_later));
-        } else {
-            stats_parts.push(format!("稍后再看: {} / {}", succeeded_watch_later, total_watch_later));
+
+    #[tokio::test]
+    async fn test_scan_priority_order() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let mut keys = HashMap::new();
+        for (f_id, scan_priority) in [(1, 0), (2, 10), (3, 0), (4, -5)] {
+            let favorite = favorite::ActiveModel {
+                f_id: Set(f_id),
+                name: Set(format!("测试收藏夹 {}", f_id)),
+                scan_priority: Set(scan_priority),
+                ..test_favorite(String::new())
+            }
+            .insert(&connection)
+            .await?;
+            keys.insert(VideoSourceKey::Favorite(favorite.id), f_id);
         }
+        // 串行扫描时按优先级从高到低处理，优先级相同的视频源之间顺序不定
+        let processed = Mutex::new(Vec::new());
+        let process = |video_source: VideoSourceEnum| {
+            processed.lock().push(keys[&video_source.key()]);
+            async { Ok(()) }
+        };
+        for _ in 0..5 {
+            processed.lock().clear();
+            scan_video_sources(get_enabled_video_sources(&connection).await?, 1, process).await;
+            let processed = processed.lock().clone();
+            assert_eq!(processed.len(), 4);
+            assert_eq!(processed[0], 2);
+            assert!(processed[1..3].contains(&1) && processed[1..3].contains(&3));
+            assert_eq!(processed[3], 4);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_credential_rotation_on_risk_control() -> Result<()> {
+        use axum::Router;
+        use axum::extract::Query;
+        use axum::http::StatusCode;
+        use axum::routing::get;
+
+        // 模拟 B 站接口：账号 a 和 b 触发风控返回 412，其余账号正常返回
+        let app = Router::new().route(
+            "/nav",
+            get(|Query(query): Query<HashMap<String, String>>| async move {
+                match query.get("sessdata").map(String::as_str) {
+                    Some("a" | "b") => StatusCode::PRECONDITION_FAILED,
+                    _ => StatusCode::OK,
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/nav", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let client = reqwest::Client::new();
+        let request = |config: Arc<Config>| {
+            let (client, url) = (client.clone(), url.clone());
+            async move {
+                client
+                    .get(&url)
+                    .query(&[("sessdata", &config.credential.sessdata)])
+                    .send()
+                    .await?
+                    .error_for_status()?;
+                anyhow::Ok(config.credential.sessdata.clone())
+            }
+        };
+        let credential = |sessdata: &str| Credential {
+            sessdata: sessdata.to_owned(),
+            ..Default::default()
+        };
+        let base_config = Config {
+            credential: credential("a"),
+            backup_credentials: vec![credential("b"), credential("c")],
+            ..Default::default()
+        };
+        // 依次切换到第一个未触发风控的账号，后续视频源继续使用该账号
+        let rotation = CredentialRotation::new(Arc::new(base_config.clone()));
+        assert_eq!(rotation.run(request).await?, "c");
+        assert_eq!(rotation.config().credential.sessdata, "c");
+        assert_eq!(rotation.run(request).await?, "c");
+        // 所有账号都触发风控时返回风控错误，由调用方终止此轮任务
+        let rotation = CredentialRotation::new(Arc::new(Config {
+            backup_credentials: vec![credential("b")],
+            ..base_config
+        }));
+        let err = rotation.run(request).await.unwrap_err();
+        assert!(bilibili::is_risk_control_error(&err));
+        assert_eq!(rotation.config().credential.sessdata, "b");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_manual_credential_refresh() -> Result<()> {
+        use axum::Router;
+        use axum::http::{HeaderMap, StatusCode, header};
+        use axum::routing::{get, post};
+
+        // 模拟 B 站的刷新接口：账号 old 需要刷新，fresh 无需刷新，其余账号接口报错
+        let sessdata = |headers: &HeaderMap| {
+            headers
+                .get_all(header::COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok()?.strip_prefix("SESSDATA="))
+                .map(ToOwned::to_owned)
+                .next()
+        };
+        let app = Router::new()
+            .route(
+                "/x/passport-login/web/cookie/info",
+                get(move |headers: HeaderMap| async move {
+                    match sessdata(&headers).as_deref() {
+                        Some("old") => Ok(axum::Json(
+                            serde_json::json!({ "code": 0, "data": { "refresh": true } }),
+                        )),
+                        Some("fresh") => Ok(axum::Json(
+                            serde_json::json!({ "code": 0, "data": { "refresh": false } }),
+                        )),
+                        _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
+                    }
+                }),
+            )
+            .route(
+                "/correspond/1/{path}",
+                get(|| async { r#"<div id="1-name">refresh_csrf</div>"# }),
+            )
+            .route(
+                "/x/passport-login/web/cookie/refresh",
+                post(|| async {
+                    (
+                        [
+                            (header::SET_COOKIE, "SESSDATA=new; Path=/"),
+                            (header::SET_COOKIE, "bili_jct=new_jct; Path=/"),
+                            (header::SET_COOKIE, "DedeUserID=1; Path=/"),
+                        ],
+                        axum::Json(serde_json::json!({ "code": 0, "data": { "refresh_token": "new_token" } })),
+                    )
+                }),
+            )
+            .route(
+                "/x/passport-login/web/confirm/refresh",
+                post(|| async { axum::Json(serde_json::json!({ "code": 0 })) }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let host = format!("http://{}", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let bili_client = BiliClient::new()?;
+        let hosts = RefreshHosts {
+            passport: &host,
+            www: &host,
+        };
+        let prepare_refresh = |credential: Credential| {
+            let bili_client = &bili_client;
+            async move { bili_client.prepare_refresh_at(&credential, hosts).await }
+        };
+        let refresh = |credential: Credential, csrf: String| {
+            let bili_client = &bili_client;
+            async move { bili_client.refresh_credential_at(&credential, hosts, &csrf).await }
+        };
+        let persisted = Mutex::new(Vec::new());
+        let persist = |index: usize, credential: Credential| {
+            persisted.lock().push((index, credential));
+            async { anyhow::Ok(()) }
+        };
+        let credential = |sessdata: &str| Credential {
+            sessdata: sessdata.to_owned(),
+            buvid3: "buvid3".to_owned(),
+            ..Default::default()
+        };
+        let config = Config {
+            credential: credential("old"),
+            backup_credentials: vec![credential("fresh"), credential("broken")],
+            ..Default::default()
+        };
+        let result =
+            refresh_credentials(&config, &bili_client, Duration::ZERO, prepare_refresh, refresh, persist).await;
+        // 主账号的凭据被刷新并保存，备用账号 2 的错误不影响其它账号
+        assert!(result.refreshed);
+        let error = result.error.unwrap_or_default();
+        assert!(error.starts_with("备用账号 2：") && !error.contains("主账号"));
+        assert!(error.contains("已尝试 3 次"));
+        let persisted = persisted.into_inner();
+        assert_eq!(persisted.len(), 1);
+        let (index, new_credential) = &persisted[0];
+        assert_eq!(*index, 0);
+        // 新凭据由响应头中的 cookie 与响应体中的 refresh_token 解析得到，buvid3 沿用旧凭据
+        assert_eq!(
+            (
+                new_credential.sessdata.as_str(),
+                new_credential.bili_jct.as_str(),
+                new_credential.dedeuserid.as_str(),
+                new_credential.ac_time_value.as_str(),
+                new_credential.buvid3.as_str(),
+            ),
+            ("new", "new_jct", "1", "new_token", "buvid3")
+        );
+        // 手动刷新与下载任务共用运行锁，下载任务执行中时不会刷新
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let task_manager = DownloadTaskManager {
+            sched: Arc::new(tokio::sync::Mutex::new(JobScheduler::new().await?)),
+            cx: test_context(connection.clone()),
+            shutdown_rx: watch::channel(Ok(())).1,
+        };
+        let _running = task_manager.cx.running.write().await;
+        assert!(task_manager.refresh_credential().await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_credential_refresh_retry() -> Result<()> {
+        // 连接一个已关闭的端口，得到连接失败的错误
+        let closed_addr = tokio::net::TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+        let connect_error = || async move {
+            reqwest::get(format!("http://{}", closed_addr))
+                .await
+                .map(|_| ())
+                .context("检查是否需要刷新失败")
+        };
+        // 检查接口第一次连接失败，第二次返回需要刷新
+        let attempts = AtomicU32::new(0);
+        let prepare_refresh = |_: Credential| {
+            let attempt = attempts.fetch_add(1, Ordering::Relaxed);
+            async move {
+                if attempt == 0 {
+                    connect_error().await?;
+                }
+                anyhow::Ok(Some("refresh_csrf".to_owned()))
+            }
+        };
+        let refreshes = AtomicU32::new(0);
+        let refresh = |_: Credential, csrf: String| {
+            refreshes.fetch_add(1, Ordering::Relaxed);
+            async move {
+                assert_eq!(csrf, "refresh_csrf");
+                anyhow::Ok(Credential {
+                    sessdata: "new".to_owned(),
+                    ..Default::default()
+                })
+            }
+        };
+        let persisted = Mutex::new(Vec::new());
+        let persist = |index: usize, credential: Credential| {
+            persisted.lock().push((index, credential.sessdata));
+            async { anyhow::Ok(()) }
+        };
+        let config = Config::default();
+        let bili_client = BiliClient::new()?;
+        let result =
+            refresh_credentials(&config, &bili_client, Duration::ZERO, prepare_refresh, refresh, persist).await;
+        assert!(result.refreshed && result.error.is_none());
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+        assert_eq!(refreshes.load(Ordering::Relaxed), 1);
+        assert_eq!(*persisted.lock(), [(0, "new".to_owned())]);
+        // 刷新请求发出后旧的 refresh_token 可能已经失效，即使是短暂的网络错误也不会重试
+        let (attempts, refreshes) = (AtomicU32::new(0), AtomicU32::new(0));
+        let prepare_refresh = |_: Credential| {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            async { anyhow::Ok(Some("refresh_csrf".to_owned())) }
+        };
+        let refresh = |_: Credential, _: String| {
+            refreshes.fetch_add(1, Ordering::Relaxed);
+            async move {
+                connect_error().await?;
+                anyhow::Ok(Credential::default())
+            }
+        };
+        let persist = |_: usize, _: Credential| async { anyhow::Ok(()) };
+        let result =
+            refresh_credentials(&config, &bili_client, Duration::ZERO, prepare_refresh, refresh, persist).await;
+        assert!(!result.refreshed && result.error.is_some());
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+        assert_eq!(refreshes.load(Ordering::Relaxed), 1);
+        // 检查阶段的非网络错误同样不会重试，也不会发出刷新请求
+        let (attempts, refreshes) = (AtomicU32::new(0), AtomicU32::new(0));
+        let prepare_refresh = |_: Credential| {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            async { anyhow::Result::<Option<String>>::Err(anyhow!("解析检查结果失败")) }
+        };
+        let refresh = |_: Credential, _: String| {
+            refreshes.fetch_add(1, Ordering::Relaxed);
+            async { anyhow::Ok(Credential::default()) }
+        };
+        let persist = |_: usize, _: Credential| async { anyhow::Ok(()) };
+        let result =
+            refresh_credentials(&config, &bili_client, Duration::ZERO, prepare_refresh, refresh, persist).await;
+        assert!(!result.refreshed && result.error.is_some());
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+        assert_eq!(refreshes.load(Ordering::Relaxed), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_risk_control_cooldown_backoff() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let cx = test_context(connection.clone());
+        let config = Config {
+            risk_control_cooldown: 600,
+            risk_control_cooldown_max: 3600,
+            ..Default::default()
+        };
+        // 连续触发风控时冷却时长逐次翻倍，直至达到上限
+        let cooldowns = (0..5)
+            .map(|_| cx.record_risk_control(true, &config).map(|d| d.num_seconds()))
+            .collect::<Vec<_>>();
+        assert_eq!(cooldowns, [Some(600), Some(1200), Some(2400), Some(3600), Some(3600)]);
+        // 完整执行一轮未触发风控后重新从初始时长开始计算
+        assert_eq!(cx.record_risk_control(false, &config), None);
+        assert_eq!(
+            cx.record_risk_control(true, &config).map(|d| d.num_seconds()),
+            Some(600)
+        );
+        // 未设置冷却时长时不会暂停
+        let config = Config::default();
+        assert_eq!(cx.record_risk_control(true, &config), None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scan_video_sources_concurrently() -> Result<()> {
+        use std::sync::atomic::AtomicUsize;
+
+        use crate::bilibili::BiliError;
+
+        let video_sources = (1..=6)
+            .map(|id| match id % 2 {
+                0 => VideoSourceEnum::Favorite(favorite::Model {
+                    id,
+                    ..Default::default()
+                }),
+                _ => VideoSourceEnum::Submission(submission::Model {
+                    id,
+                    ..Default::default()
+                }),
+            })
+            .collect::<Vec<_>>();
+        // 模拟的视频源处理：记录同时处理的视频源数量，id 越大处理耗时越长，指定的视频源处理失败或触发风控
+        let (running, max_running) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        let process = |fail: Option<i32>, risk_control: Option<i32>| {
+            let (running, max_running) = (&running, &max_running);
+            move |video_source: VideoSourceEnum| async move {
+                let id = match video_source.key() {
+                    VideoSourceKey::Favorite(id) | VideoSourceKey::Submission(id) => id,
+                    _ => unreachable!(),
+                };
+                let current = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10 * id as u64)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                if Some(id) == risk_control {
+                    bail!(BiliError::RiskControlOccurred(String::new()));
+                }
+                if Some(id) == fail {
+                    bail!("failed to process video source {}", id);
+                }
+                Ok(())
+            }
+        };
+        let stats = scan_video_sources(video_sources.clone(), 3, process(Some(3), None)).await;
+        assert_eq!(max_running.load(Ordering::SeqCst), 3);
+        assert!(!stats.risk_control_triggered);
+        let count = |total, succeeded, risk_controlled| SourceScanCount {
+            total,
+            succeeded,
+            risk_controlled,
+        };
+        assert_eq!(stats.favorite, count(3, 3, 0));
+        assert_eq!(stats.submission, count(3, 2, 0));
+        assert_eq!(stats.message(), "视频源扫描统计 - 收藏夹: 3 / 3 | 投稿: 2 / 3");
+        // 顺序扫描时，触发风控的视频源与之后的视频源都计入待扫描
+        max_running.store(0, Ordering::SeqCst);
+        let stats = scan_video_sources(video_sources.clone(), 1, process(None, Some(2))).await;
+        assert_eq!(max_running.load(Ordering::SeqCst), 1);
+        assert!(stats.risk_control_triggered);
+        assert_eq!(stats.submission, count(3, 1, 2));
+        assert_eq!(stats.favorite, count(3, 0, 3));
+        // 并发扫描时，触发风控前已经开始的视频源会正常完成，之后的视频源不再开始
+        let stats = scan_video_sources(video_sources, 2, process(None, Some(1))).await;
+        assert!(stats.risk_control_triggered);
+        let (favorite, submission) = (stats.favorite, stats.submission);
+        assert_eq!(favorite.succeeded + submission.succeeded, 1);
+        assert_eq!(favorite.risk_controlled + submission.risk_controlled, 5);
+        Ok(())
     }
-    
-    let stats_message = format!("视频源扫描统计 - {}", stats_parts.join(" | "));
-    info!("{}", stats_message);
-    
-    // 发送统计通知（静默时间段检查在 NotificationQueue 中统一处理）
-    notify(config, &bili_client, stats_message);
-    
-    Ok(())
 }
@@ -19,6 +19,7 @@ impl VideoInfo {
                 cover,
                 ctime,
                 pubtime,
+                order,
             } => bili_sync_entity::video::ActiveModel {
                 bvid: Set(bvid),
                 cover: Set(cover),
@@ -26,6 +27,7 @@ impl VideoInfo {
                 pubtime: Set(pubtime.naive_utc()),
                 category: Set(2), // 视频合集里的内容类型肯定是视频
                 valid: Set(true),
+                order: Set(order),
                 ..default
             },
             VideoInfo::Favorite {
@@ -117,6 +119,26 @@ impl VideoInfo {
         }
     }
 
+    /// 视频详情表明该视频需要付费观看（付费合集或单独付费）
+    pub fn requires_payment(&self) -> bool {
+        match self {
+            VideoInfo::Detail { rights, .. } => (rights.arc_pay == 1 || rights.ugc_pay == 1) && rights.free_watch != 1,
+            _ => false,
+        }
+    }
+
+    /// 视频详情表明该视频为充电专属视频且当前账号未充电，这类视频会被标记为失效，不属于收费视频
+    pub fn requires_upower(&self) -> bool {
+        match self {
+            VideoInfo::Detail {
+                is_upower_exclusive,
+                is_upower_play,
+                ..
+            } => *is_upower_exclusive && !*is_upower_play,
+            _ => false,
+        }
+    }
+
     /// 填充视频详情时调用，该方法会将视频详情附加到原有的 Model 上
     /// 特殊地，如果在检测视频更新时记录了 favtime，那么 favtime 会维持原样，否则会使用 pubtime 填充
     pub fn into_detail_model(self, base_model: bili_sync_entity::video::Model) -> bili_sync_entity::video::ActiveModel {
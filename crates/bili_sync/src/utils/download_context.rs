@@ -1,9 +1,13 @@
+use anyhow::Result;
 use sea_orm::DatabaseConnection;
+use tokio_util::sync::CancellationToken;
 
-use crate::adapter::VideoSourceEnum;
+use crate::adapter::{VideoSource, VideoSourceEnum};
 use crate::bilibili::BiliClient;
 use crate::config::Config;
 use crate::downloader::Downloader;
+use crate::error::ExecutionStatus;
+use crate::utils::signal::SHUTDOWN;
 
 #[derive(Clone, Copy)]
 pub struct DownloadContext<'a> {
@@ -13,6 +17,12 @@ pub struct DownloadContext<'a> {
     pub connection: &'a DatabaseConnection,
     pub downloader: &'a Downloader,
     pub config: &'a Config,
+    /// 视频源单独设置的 video_name 模板，设置后覆盖全局模板
+    pub video_name_override: Option<&'a str>,
+    /// 演练模式下仅打印将要执行的操作与目标路径，不发起实际的下载与写入
+    pub dry_run: bool,
+    /// 收到停机信号后被取消，尚未开始的视频与分页不再处理，正在下载的内容会继续完成
+    pub cancel: &'a CancellationToken,
 }
 
 impl<'a> DownloadContext<'a> {
@@ -23,6 +33,7 @@ impl<'a> DownloadContext<'a> {
         connection: &'a DatabaseConnection,
         downloader: &'a Downloader,
         config: &'a Config,
+        dry_run: bool,
     ) -> Self {
         Self {
             bili_client,
@@ -31,6 +42,14 @@ impl<'a> DownloadContext<'a> {
             connection,
             downloader,
             config,
+            video_name_override: video_source.video_name(),
+            dry_run,
+            cancel: &SHUTDOWN,
         }
     }
+
+    /// 按本次执行使用的配置将任务的执行结果转换为状态
+    pub fn execution_status(&self, res: Result<ExecutionStatus>) -> ExecutionStatus {
+        ExecutionStatus::from_result(res, &self.config.ignored_error_codes)
+    }
 }
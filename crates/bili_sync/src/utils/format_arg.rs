@@ -27,3 +27,37 @@ pub fn page_format_args(
         "fav_time": video_model.favtime.and_utc().format(time_format).to_string(),
     })
 }
+
+/// 自定义 nfo 模板使用的参数，包含视频（与分页）的完整元数据
+pub fn nfo_format_args(
+    video_model: &bili_sync_entity::video::Model,
+    page_model: Option<&bili_sync_entity::page::Model>,
+    time_format: &str,
+) -> serde_json::Value {
+    let mut args = json!({
+        "bvid": &video_model.bvid,
+        "title": &video_model.name,
+        "intro": &video_model.intro,
+        "cover": &video_model.cover,
+        "category": video_model.category,
+        "upper_name": &video_model.upper_name,
+        "upper_mid": &video_model.upper_id,
+        "upper_face": &video_model.upper_face,
+        "tags": video_model.tags.as_ref().map(|tags| &tags.0),
+        "rule_tags": video_model.rule_tags.as_ref().map(|tags| &tags.0),
+        "single_page": video_model.single_page,
+        "ctime": video_model.ctime.and_utc().format(time_format).to_string(),
+        "pubtime": video_model.pubtime.and_utc().format(time_format).to_string(),
+        "fav_time": video_model.favtime.and_utc().format(time_format).to_string(),
+    });
+    if let Some(page_model) = page_model {
+        args["ptitle"] = json!(&page_model.name);
+        args["pid"] = json!(page_model.pid);
+        args["cid"] = json!(page_model.cid);
+        args["duration"] = json!(page_model.duration);
+        args["width"] = json!(page_model.width);
+        args["height"] = json!(page_model.height);
+        args["page_cover"] = json!(&page_model.image);
+    }
+    args
+}
@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use bili_sync_entity::*;
+use sea_orm::entity::prelude::*;
+use sea_orm::{DatabaseConnection, QueryOrder};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+
+/// 清单文件的名称，位于每个视频源的根目录下
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// 清单中的单个视频，与数据库无关，仅依赖文件系统即可还原视频与文件的对应关系
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ManifestVideo {
+    pub bvid: String,
+    pub title: String,
+    pub path: String,
+    pub files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ManifestFile {
+    pub path: String,
+    pub size: u64,
+    pub md5: String,
+}
+
+/// 以 bvid 为键的清单内容，使用 BTreeMap 保证输出顺序稳定，便于阅读与比对
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub videos: BTreeMap<String, ManifestVideo>,
+}
+
+/// 将指定的视频写入视频源根目录下的清单，已存在的视频会被覆盖
+pub async fn update_source_manifest(
+    source_path: &Path,
+    video_ids: &[i32],
+    connection: &DatabaseConnection,
+) -> Result<()> {
+    if video_ids.is_empty() {
+        return Ok(());
+    }
+    let (videos, pages) = tokio::try_join!(
+        video::Entity::find()
+            .filter(video::Column::Id.is_in(video_ids.iter().copied()))
+            .all(connection),
+        page::Entity::find()
+            .filter(page::Column::VideoId.is_in(video_ids.iter().copied()))
+            .order_by_asc(page::Column::Pid)
+            .all(connection),
+    )?;
+    let manifest_path = source_path.join(MANIFEST_FILE_NAME);
+    let mut manifest = read_manifest(&manifest_path).await?;
+    for video_model in videos {
+        let mut files = Vec::new();
+        for page_path in pages
+            .iter()
+            .filter(|page| page.video_id == video_model.id)
+            .filter_map(|page| page.path.as_deref())
+        {
+            let page_path = PathBuf::from(page_path);
+            // 视频文件可能被用户手动删除或移动，此时跳过即可
+            if fs::try_exists(&page_path).await.unwrap_or(false) {
+                files.push(manifest_file(&page_path).await?);
+            }
+        }
+        manifest.videos.insert(
+            video_model.bvid.clone(),
+            ManifestVideo {
+                bvid: video_model.bvid,
+                title: video_model.name,
+                path: video_model.path,
+                files,
+            },
+        );
+    }
+    write_manifest(&manifest_path, &manifest).await
+}
+
+async fn read_manifest(manifest_path: &Path) -> Result<Manifest> {
+    match fs::read(manifest_path).await {
+        Ok(content) => serde_json::from_slice(&content)
+            .with_context(|| format!("failed to parse manifest {}", manifest_path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Manifest::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 先写入临时文件再重命名，避免写入中断时留下损坏的清单
+async fn write_manifest(manifest_path: &Path, manifest: &Manifest) -> Result<()> {
+    if let Some(parent) = manifest_path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    let tmp_path = manifest_path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_vec_pretty(manifest)?).await?;
+    fs::rename(&tmp_path, manifest_path).await?;
+    Ok(())
+}
+
+async fn manifest_file(path: &Path) -> Result<ManifestFile> {
+    let mut file = fs::File::open(path).await?;
+    let (mut context, mut buffer, mut size) = (md5::Context::new(), vec![0u8; 1 << 16], 0u64);
+    loop {
+        let n = file.read(&mut buffer).await?;
+        if n == 0 {
+            break;
+        }
+        context.consume(&buffer[..n]);
+        size += n as u64;
+    }
+    Ok(ManifestFile {
+        path: path.to_string_lossy().to_string(),
+        size,
+        md5: format!("{:x}", context.finalize()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::ActiveValue::Set;
+
+    use super::*;
+    use crate::utils::test_utils::{TestDatabase, test_page, test_video};
+
+    #[tokio::test]
+    async fn test_update_source_manifest() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let source_path = test_dir.join("videos");
+        let mut video_ids = Vec::new();
+        for (bvid, content) in [("BV1test00001", "hello"), ("BV1test00002", "world")] {
+            let video_path = source_path.join(bvid);
+            let page_path = video_path.join(format!("{}.mp4", bvid));
+            fs::create_dir_all(&video_path).await?;
+            fs::write(&page_path, content).await?;
+            let video = video::ActiveModel {
+                name: Set(format!("测试视频 {}", bvid)),
+                path: Set(video_path.to_string_lossy().to_string()),
+                single_page: Set(Some(true)),
+                ..test_video(bvid)
+            }
+            .insert(&connection)
+            .await?;
+            page::ActiveModel {
+                path: Set(Some(page_path.to_string_lossy().to_string())),
+                ..test_page(video.id, 1)
+            }
+            .insert(&connection)
+            .await?;
+            video_ids.push(video.id);
+        }
+        // 分两次写入，第二次写入时应追加而非覆盖整个清单
+        update_source_manifest(&source_path, &video_ids[..1], &connection).await?;
+        update_source_manifest(&source_path, &video_ids[1..], &connection).await?;
+        let manifest = read_manifest(&source_path.join(MANIFEST_FILE_NAME)).await?;
+        assert_eq!(manifest.videos.len(), 2);
+        let files = &manifest.videos["BV1test00001"].files;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].size, 5);
+        assert_eq!(files[0].md5, "5d41402abc4b2a76b9719d911017c592");
+        assert_eq!(manifest.videos["BV1test00002"].title, "测试视频 BV1test00002");
+        Ok(())
+    }
+}
@@ -2,31 +2,40 @@ pub mod convert;
 pub mod download_context;
 pub mod filenamify;
 pub mod format_arg;
+pub mod manifest;
 pub mod model;
 pub mod nfo;
 pub mod notify;
+pub mod post_download;
+pub mod progress;
+pub mod relocate;
+pub mod rotating_file;
 pub mod rule;
 pub mod signal;
 pub mod status;
+#[cfg(test)]
+pub mod test_utils;
 pub mod validation;
-use std::fs::OpenOptions;
 use std::io::Write;
-use std::sync::{Arc, Mutex};
-use tracing_subscriber::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use anyhow::{Context, Result};
 use tracing_subscriber::fmt::MakeWriter;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Registry, fmt, reload};
 
 use crate::api::LogHelper;
 use crate::config::CONFIG_DIR;
+use crate::utils::rotating_file::{LogRotation, RotatingFile};
 
 /// 线程安全的文件写入器，用于日志持久化
 struct FileWriter {
-    file: Arc<Mutex<std::fs::File>>,
+    file: Arc<Mutex<RotatingFile>>,
 }
 
 impl FileWriter {
-    fn new(file: std::fs::File) -> Self {
+    fn new(file: RotatingFile) -> Self {
         Self {
             file: Arc::new(Mutex::new(file)),
         }
@@ -63,7 +72,24 @@ impl Clone for FileWriter {
     }
 }
 
-pub fn init_logger(log_level: &str, log_writer: Option<LogHelper>) {
+/// 全局日志过滤器的句柄，用于在运行时调整日志等级
+static LOG_FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// 在运行时替换全局的日志等级，日志等级的格式与 RUST_LOG 环境变量一致
+pub fn set_log_level(log_level: &str) -> Result<()> {
+    let handle = LOG_FILTER_HANDLE.get().context("日志系统尚未初始化")?;
+    reload_log_filter(handle, log_level)
+}
+
+/// 校验日志等级后替换过滤器，日志等级不合法时保持原有的过滤器不变
+fn reload_log_filter<S>(handle: &reload::Handle<EnvFilter, S>, log_level: &str) -> Result<()> {
+    let filter = EnvFilter::builder()
+        .parse(log_level)
+        .with_context(|| format!("日志等级「{}」不合法", log_level))?;
+    handle.reload(filter).context("替换日志过滤器失败")
+}
+
+pub fn init_logger(log_level: &str, log_writer: Option<LogHelper>, rotation: LogRotation) {
     // 创建日志目录
     let log_dir = CONFIG_DIR.join("logs");
     if let Err(e) = std::fs::create_dir_all(&log_dir) {
@@ -73,12 +99,8 @@ pub fn init_logger(log_level: &str, log_writer: Option<LogHelper>) {
     // 创建日志文件路径
     let log_file = log_dir.join("bili-sync.log");
 
-    // 尝试打开日志文件（追加模式）
-    let file_writer = match OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_file)
-    {
+    // 尝试打开日志文件（追加模式），超出大小限制后自动滚动
+    let file_writer = match RotatingFile::open(&log_file, rotation) {
         Ok(file) => {
             eprintln!("日志文件已创建: {}", log_file.display());
             Some(FileWriter::new(file))
@@ -89,9 +111,10 @@ pub fn init_logger(log_level: &str, log_writer: Option<LogHelper>) {
         }
     };
 
-    // 使用 Registry 作为基础，然后添加多个 layer
-    let registry = tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::builder().parse_lossy(log_level));
+    // 使用 Registry 作为基础，然后添加多个 layer，过滤器包装为可重载的 layer 以便运行时调整日志等级
+    let (filter, handle) = reload::Layer::new(EnvFilter::builder().parse_lossy(log_level));
+    let _ = LOG_FILTER_HANDLE.set(handle);
+    let registry = tracing_subscriber::registry().with(filter);
 
     // 添加标准输出 layer（始终存在）
     let stdout_layer = fmt::layer()
@@ -217,3 +240,42 @@ pub fn init_logger(log_level: &str, log_writer: Option<LogHelper>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use tracing::Subscriber;
+    use tracing_subscriber::Layer;
+    use tracing_subscriber::layer::Context;
+
+    use super::*;
+
+    struct EventCounter(Arc<AtomicUsize>);
+
+    impl<S: Subscriber> Layer<S> for EventCounter {
+        fn on_event(&self, _event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_reload_log_filter() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (filter, handle) = reload::Layer::new(EnvFilter::new("info"));
+        let subscriber = tracing_subscriber::registry()
+            .with(filter)
+            .with(EventCounter(counter.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!("filtered");
+            assert_eq!(counter.load(Ordering::SeqCst), 0);
+            // 不合法的日志等级不会替换原有的过滤器
+            assert!(reload_log_filter(&handle, "bili_sync=verbose").is_err());
+            tracing::debug!("still filtered");
+            assert_eq!(counter.load(Ordering::SeqCst), 0);
+            reload_log_filter(&handle, "debug").unwrap();
+            tracing::debug!("passes");
+            assert_eq!(counter.load(Ordering::SeqCst), 1);
+        });
+    }
+}
@@ -2,14 +2,14 @@ use anyhow::{Context, Result, anyhow};
 use bili_sync_entity::*;
 use rand::seq::SliceRandom;
 use sea_orm::ActiveValue::Set;
-use sea_orm::DatabaseTransaction;
 use sea_orm::entity::prelude::*;
-use sea_orm::sea_query::{OnConflict, SimpleExpr};
+use sea_orm::sea_query::{Expr, OnConflict, SimpleExpr};
+use sea_orm::{DatabaseTransaction, QueryOrder, QuerySelect, TransactionTrait};
 
 use crate::adapter::{VideoSource, VideoSourceEnum};
 use crate::bilibili::VideoInfo;
 use crate::config::Config;
-use crate::utils::status::STATUS_COMPLETED;
+use crate::utils::status::{PageStatus, STATUS_COMPLETED, Status, VideoStatus};
 
 /// 筛选未填充的视频
 pub async fn filter_unfilled_videos(
@@ -30,6 +30,33 @@ pub async fn filter_unfilled_videos(
         .context("filter unfilled videos failed")
 }
 
+/// 筛选发布时间晚于 since、仅因播放量或点赞数未达到下限而跳过下载，且上次检查早于 checked_before 的视频
+pub async fn filter_below_stat_threshold_videos(
+    additional_expr: SimpleExpr,
+    since: chrono::NaiveDateTime,
+    checked_before: chrono::NaiveDateTime,
+    conn: &DatabaseConnection,
+) -> Result<Vec<video::Model>> {
+    video::Entity::find()
+        .filter(
+            video::Column::Valid
+                .eq(true)
+                .and(video::Column::BelowStatThreshold.eq(true))
+                .and(video::Column::ShouldDownload.eq(false))
+                .and(video::Column::DownloadStatus.eq(0))
+                .and(video::Column::Pubtime.gte(since))
+                .and(
+                    video::Column::StatCheckedAt
+                        .is_null()
+                        .or(video::Column::StatCheckedAt.lt(checked_before)),
+                )
+                .and(additional_expr),
+        )
+        .all(conn)
+        .await
+        .context("filter below stat threshold videos failed")
+}
+
 /// 筛选未处理完成的视频和视频页
 pub async fn filter_unhandled_video_pages(
     additional_expr: SimpleExpr,
@@ -43,6 +70,8 @@ pub async fn filter_unhandled_video_pages(
                 .and(video::Column::Category.eq(2))
                 .and(video::Column::SinglePage.is_not_null())
                 .and(video::Column::ShouldDownload.eq(true))
+                // 用户手动删除了本地文件的视频不再重新下载
+                .and(video::Column::FilesDeleted.eq(false))
                 .and(additional_expr),
         )
         .find_with_related(page::Entity)
@@ -52,11 +81,13 @@ pub async fn filter_unhandled_video_pages(
 }
 
 /// 尝试创建 Video Model，如果发生冲突则忽略
+///
+/// 已存在且有效的视频在视频列表中表明已失效时，将其标记为失效并返回这些视频
 pub async fn create_videos(
     videos_info: Vec<VideoInfo>,
     video_source: &VideoSourceEnum,
     connection: &DatabaseConnection,
-) -> Result<()> {
+) -> Result<Vec<video::Model>> {
     let video_models = videos_info
         .into_iter()
         .map(|v| {
@@ -65,12 +96,67 @@ pub async fn create_videos(
             model
         })
         .collect::<Vec<_>>();
+    let invalid_bvids = video_models
+        .iter()
+        .filter(|model| matches!(model.valid, Set(false)))
+        .filter_map(|model| match &model.bvid {
+            Set(bvid) => Some(bvid.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let invalidated_videos = if invalid_bvids.is_empty() {
+        Vec::new()
+    } else {
+        video::Entity::find()
+            .filter(
+                video::Column::Valid
+                    .eq(true)
+                    .and(video::Column::Bvid.is_in(invalid_bvids))
+                    .and(video_source.filter_expr()),
+            )
+            .all(connection)
+            .await?
+    };
+    if !invalidated_videos.is_empty() {
+        video::Entity::update_many()
+            .col_expr(video::Column::Valid, Expr::value(false))
+            .filter(video::Column::Id.is_in(invalidated_videos.iter().map(|v| v.id)))
+            .exec(connection)
+            .await?;
+    }
     video::Entity::insert_many(video_models)
         // 这里想表达的是 on 索引名，但 sea-orm 的 api 似乎只支持列名而不支持索引名，好在留空可以达到相同的目的
         .on_conflict(OnConflict::new().do_nothing().to_owned())
         .do_nothing()
         .exec(connection)
         .await?;
+    Ok(invalidated_videos)
+}
+
+/// 根据全量拉取到的合集顺序更新已有视频的 order
+/// create_videos 仅在首次插入时写入 order，已有视频（包括增加该字段前创建的视频）需要在每次扫描时补全或随合集调整更新
+pub async fn update_video_orders(
+    orders: &[(String, i32)],
+    video_source: &VideoSourceEnum,
+    connection: &DatabaseConnection,
+) -> Result<()> {
+    if orders.is_empty() {
+        return Ok(());
+    }
+    let txn = connection.begin().await?;
+    for (bvid, order) in orders {
+        video::Entity::update_many()
+            .col_expr(video::Column::Order, Expr::value(*order))
+            .filter(
+                video::Column::Bvid
+                    .eq(bvid)
+                    .and(video_source.filter_expr())
+                    .and(video::Column::Order.is_null().or(video::Column::Order.ne(*order))),
+            )
+            .exec(&txn)
+            .await?;
+    }
+    txn.commit().await?;
     Ok(())
 }
 
@@ -95,7 +181,11 @@ pub async fn update_videos_model(videos: Vec<video::ActiveModel>, connection: &D
     video::Entity::insert_many(videos)
         .on_conflict(
             OnConflict::column(video::Column::Id)
-                .update_columns([video::Column::DownloadStatus, video::Column::Path])
+                .update_columns([
+                    video::Column::DownloadStatus,
+                    video::Column::Path,
+                    video::Column::RuleTags,
+                ])
                 .to_owned(),
         )
         .exec(connection)
@@ -107,13 +197,105 @@ pub async fn update_videos_model(videos: Vec<video::ActiveModel>, connection: &D
 pub async fn update_pages_model(pages: Vec<page::ActiveModel>, connection: &DatabaseConnection) -> Result<()> {
     let query = page::Entity::insert_many(pages).on_conflict(
         OnConflict::column(page::Column::Id)
-            .update_columns([page::Column::DownloadStatus, page::Column::Path])
+            .update_columns([
+                page::Column::DownloadStatus,
+                page::Column::Path,
+                page::Column::FileSize,
+                page::Column::DownloadDurationMs,
+            ])
             .to_owned(),
     );
     query.exec(connection).await?;
     Ok(())
 }
 
+/// 对比子任务的新旧状态，为每个发生变化的子任务记录一条状态变更事件
+pub async fn record_task_events<const N: usize, C>(
+    video_id: i32,
+    page_id: Option<i32>,
+    old_status: Status<N, C>,
+    new_status: Status<N, C>,
+    connection: &DatabaseConnection,
+) -> Result<()> {
+    let (old_status, new_status): ([u32; N], [u32; N]) = (old_status.into(), new_status.into());
+    let events = old_status
+        .into_iter()
+        .zip(new_status)
+        .enumerate()
+        .filter(|(_, (old, new))| old != new)
+        .map(|(task_index, (_, new))| task_event::ActiveModel {
+            video_id: Set(video_id),
+            page_id: Set(page_id),
+            task_index: Set(task_index as u32),
+            status: Set(new),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    if events.is_empty() {
+        return Ok(());
+    }
+    task_event::Entity::insert_many(events).exec(connection).await?;
+    Ok(())
+}
+
+/// 记录视频子任务的状态变更事件，记录失败不影响后续流程，仅输出警告
+pub async fn record_video_task_events(
+    video_model: &video::Model,
+    new_status: VideoStatus,
+    connection: &DatabaseConnection,
+) {
+    if let Err(e) = record_task_events(
+        video_model.id,
+        None,
+        VideoStatus::from(video_model.download_status),
+        new_status,
+        connection,
+    )
+    .await
+    {
+        warn!("记录视频「{}」的任务状态变更失败：{:#}", &video_model.name, e);
+    }
+}
+
+/// 记录分页子任务的状态变更事件，记录失败不影响后续流程，仅输出警告
+pub async fn record_page_task_events(
+    video_model: &video::Model,
+    page_model: &page::Model,
+    new_status: PageStatus,
+    connection: &DatabaseConnection,
+) {
+    if let Err(e) = record_task_events(
+        video_model.id,
+        Some(page_model.id),
+        PageStatus::from(page_model.download_status),
+        new_status,
+        connection,
+    )
+    .await
+    {
+        warn!(
+            "记录视频「{}」第 {} 页的任务状态变更失败：{:#}",
+            &video_model.name, page_model.pid, e
+        );
+    }
+}
+
+/// 清理超出保留天数的任务状态变更事件，保留天数为 0 时不清理，返回清理的事件数量
+pub async fn prune_task_events(retention_days: u64, connection: &DatabaseConnection) -> Result<u64> {
+    if retention_days == 0 {
+        return Ok(0);
+    }
+    // created_at 由数据库以 UTC 时间填充，格式与 CURRENT_TIMESTAMP 保持一致
+    let deadline = (chrono::Utc::now() - chrono::Duration::days(retention_days as i64))
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    let res = task_event::Entity::delete_many()
+        .filter(task_event::Column::CreatedAt.lt(deadline))
+        .exec(connection)
+        .await?;
+    Ok(res.rows_affected)
+}
+
 /// 获取所有已经启用的视频源
 pub async fn get_enabled_video_sources(connection: &DatabaseConnection) -> Result<Vec<VideoSourceEnum>> {
     let (favorite, watch_later, submission, collection) = tokio::try_join!(
@@ -137,6 +319,8 @@ pub async fn get_enabled_video_sources(connection: &DatabaseConnection) -> Resul
     sources.extend(collection.into_iter().map(VideoSourceEnum::from));
     // 此处将视频源随机打乱顺序，从概率上确保每个视频源都有机会优先执行，避免后面视频源的长期饥饿问题
     sources.shuffle(&mut rand::rng());
+    // 再按优先级从高到低排列，排序是稳定的，优先级相同的视频源仍保持随机顺序
+    sources.sort_by_key(|source| std::cmp::Reverse(source.scan_priority()));
     Ok(sources)
 }
 
@@ -150,9 +334,44 @@ pub async fn load_db_config(connection: &DatabaseConnection) -> Result<Option<Re
         }))
 }
 
-/// 保存配置到数据库
-pub async fn save_db_config(config: &Config, connection: &DatabaseConnection) -> Result<()> {
+/// 最多保留的配置历史数量
+const CONFIG_HISTORY_LIMIT: u64 = 20;
+
+/// 保存配置到数据库，with_history 为 true 时同时记录一份配置历史，超出数量限制的旧历史会被清理
+pub async fn save_db_config(config: &Config, with_history: bool, connection: &DatabaseConnection) -> Result<()> {
     let data = serde_json::to_string(config).context("Failed to serialize config data")?;
+    // 历史配置与当前配置在同一事务中写入，避免两者不一致
+    let txn = connection.begin().await?;
+    if with_history {
+        let history = config_history::ActiveModel {
+            version: Set(config.version as i64),
+            data: Set(data.clone()),
+            ..Default::default()
+        };
+        config_history::Entity::insert(history)
+            .on_conflict(
+                OnConflict::column(config_history::Column::Version)
+                    .update_column(config_history::Column::Data)
+                    .to_owned(),
+            )
+            .exec(&txn)
+            .await
+            .context("Failed to save config history to database")?;
+        if let Some(stale_version) = config_history::Entity::find()
+            .select_only()
+            .column(config_history::Column::Version)
+            .order_by_desc(config_history::Column::Version)
+            .offset(CONFIG_HISTORY_LIMIT - 1)
+            .into_tuple::<i64>()
+            .one(&txn)
+            .await?
+        {
+            config_history::Entity::delete_many()
+                .filter(config_history::Column::Version.lt(stale_version))
+                .exec(&txn)
+                .await?;
+        }
+    }
     let model = bili_sync_entity::config::ActiveModel {
         id: Set(1),
         data: Set(data),
@@ -164,8 +383,173 @@ pub async fn save_db_config(config: &Config, connection: &DatabaseConnection) ->
                 .update_column(bili_sync_entity::config::Column::Data)
                 .to_owned(),
         )
-        .exec(connection)
+        .exec(&txn)
         .await
         .context("Failed to save config to database")?;
+    txn.commit().await?;
     Ok(())
 }
+
+/// 获取指定版本的历史配置，不存在时返回 None
+pub async fn load_config_by_version(version: u64, connection: &DatabaseConnection) -> Result<Option<Config>> {
+    config_history::Entity::find()
+        .filter(config_history::Column::Version.eq(version as i64))
+        .one(connection)
+        .await?
+        .map(|model| serde_json::from_str(&model.data).context("Failed to deserialize config history data"))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::status::{PageStatus, STATUS_OK};
+    use crate::utils::test_utils::{TestDatabase, test_favorite, test_video};
+
+    #[tokio::test]
+    async fn test_record_and_prune_task_events() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        // 仅记录状态发生变化的子任务
+        record_task_events(
+            1,
+            Some(2),
+            PageStatus::from([STATUS_OK, 0, 1, 0, 0]),
+            PageStatus::from([STATUS_OK, STATUS_OK, 2, 0, 0]),
+            &connection,
+        )
+        .await?;
+        let events = task_event::Entity::find()
+            .order_by_asc(task_event::Column::TaskIndex)
+            .all(&connection)
+            .await?;
+        assert_eq!(
+            events
+                .iter()
+                .map(|e| (e.video_id, e.page_id, e.task_index, e.status))
+                .collect::<Vec<_>>(),
+            vec![(1, Some(2), 1, STATUS_OK), (1, Some(2), 2, 2)]
+        );
+        // 将其中一条事件的时间改为很久之前，清理时只应删除这一条
+        task_event::ActiveModel {
+            id: Set(events[0].id),
+            created_at: Set("2000-01-01 00:00:00".to_string()),
+            ..Default::default()
+        }
+        .update(&connection)
+        .await?;
+        assert_eq!(prune_task_events(0, &connection).await?, 0);
+        assert_eq!(prune_task_events(30, &connection).await?, 1);
+        assert_eq!(task_event::Entity::find().count(&connection).await?, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_videos_invalidates_existing_videos() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let favorite = test_favorite(db.dir.to_string_lossy().into_owned())
+            .insert(&connection)
+            .await?;
+        let downloaded = video::ActiveModel {
+            favorite_id: Set(Some(favorite.id)),
+            download_status: Set(STATUS_COMPLETED),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        let video_source = VideoSourceEnum::Favorite(favorite);
+        // attr 不为 0 表示收藏夹中的视频已失效
+        let favorite_info = |bvid: &str| -> Result<VideoInfo> {
+            Ok(serde_json::from_value(serde_json::json!({
+                "title": "测试视频",
+                "type": 2,
+                "bvid": bvid,
+                "intro": "",
+                "cover": "https://example.com/cover.jpg",
+                "upper": {"mid": 12345, "name": "测试作者", "face": "https://example.com/face.jpg"},
+                "ctime": 1700000000,
+                "fav_time": 1700000000,
+                "pubtime": 1700000000,
+                "attr": 9
+            }))?)
+        };
+        // 已下载的视频从有效变为失效时返回该视频，首次出现即失效的视频不返回
+        let invalidated = create_videos(
+            vec![favorite_info("BV1test00001")?, favorite_info("BV1test00002")?],
+            &video_source,
+            &connection,
+        )
+        .await?;
+        assert_eq!(invalidated.iter().map(|v| v.id).collect::<Vec<_>>(), [downloaded.id]);
+        let videos = video::Entity::find()
+            .order_by_asc(video::Column::Bvid)
+            .all(&connection)
+            .await?;
+        assert_eq!(
+            videos.iter().map(|v| (v.bvid.as_str(), v.valid)).collect::<Vec<_>>(),
+            [("BV1test00001", false), ("BV1test00002", false)]
+        );
+        // 已经标记为失效的视频不会重复返回
+        assert!(
+            create_videos(vec![favorite_info("BV1test00001")?], &video_source, &connection)
+                .await?
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_video_orders() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let collection = collection::ActiveModel {
+            s_id: Set(1),
+            m_id: Set(1),
+            name: Set("测试合集".to_string()),
+            r#type: Set(1),
+            path: Set(db.dir.to_string_lossy().into_owned()),
+            enabled: Set(true),
+            ..Default::default()
+        }
+        .insert(&connection)
+        .await?;
+        // 增加 order 字段前创建的视频为 NULL，另一个视频的位置在合集中发生了变化
+        for (bvid, order) in [("BV1test00001", None), ("BV1test00002", Some(1))] {
+            video::ActiveModel {
+                collection_id: Set(Some(collection.id)),
+                order: Set(order),
+                ..test_video(bvid)
+            }
+            .insert(&connection)
+            .await?;
+        }
+        // 其它视频源中的同一视频不受影响
+        video::ActiveModel {
+            order: Set(None),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        let video_source = VideoSourceEnum::Collection(collection);
+        update_video_orders(
+            &[("BV1test00001".to_owned(), 1), ("BV1test00002".to_owned(), 2)],
+            &video_source,
+            &connection,
+        )
+        .await?;
+        let videos = video::Entity::find()
+            .order_by_asc(video::Column::Id)
+            .all(&connection)
+            .await?;
+        assert_eq!(
+            videos.iter().map(|v| (v.bvid.as_str(), v.order)).collect::<Vec<_>>(),
+            [
+                ("BV1test00001", Some(1)),
+                ("BV1test00002", Some(2)),
+                ("BV1test00001", None)
+            ]
+        );
+        Ok(())
+    }
+}
@@ -1,11 +1,12 @@
-use anyhow::Result;
+use anyhow::{Context, Result, ensure};
 use bili_sync_entity::*;
 use chrono::NaiveDateTime;
-use quick_xml::Error;
-use quick_xml::events::{BytesCData, BytesText};
+use quick_xml::events::{BytesCData, BytesText, Event};
 use quick_xml::writer::Writer;
-use tokio::io::{AsyncWriteExt, BufWriter};
+use quick_xml::{Error, Reader};
+use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
 
+use crate::bilibili::Chapter;
 use crate::config::NFOTimeType;
 
 #[allow(clippy::upper_case_acronyms)]
@@ -25,6 +26,10 @@ pub struct Movie<'a> {
     pub upper_thumb: &'a str,
     pub premiered: NaiveDateTime,
     pub tags: Option<Vec<String>>,
+    pub rule_tags: &'a [String],
+    pub chapters: &'a [Chapter],
+    /// 设置时以 Uploader 角色写入 UP 主，thumb 指向本地已下载的 UP 主头像
+    pub uploader_thumb: Option<String>,
 }
 
 pub struct TVShow<'a> {
@@ -36,6 +41,9 @@ pub struct TVShow<'a> {
     pub upper_thumb: &'a str,
     pub premiered: NaiveDateTime,
     pub tags: Option<Vec<String>>,
+    pub rule_tags: &'a [String],
+    /// 设置时以 Uploader 角色写入 UP 主，thumb 指向本地已下载的 UP 主头像
+    pub uploader_thumb: Option<String>,
 }
 
 pub struct Upper {
@@ -45,7 +53,11 @@ pub struct Upper {
 
 pub struct Episode<'a> {
     pub name: &'a str,
+    pub season: u32,
     pub pid: String,
+    /// 分页所属视频命中的自动标签
+    pub rule_tags: &'a [String],
+    pub chapters: &'a [Chapter],
 }
 
 impl NFO<'_> {
@@ -87,24 +99,11 @@ impl NFO<'_> {
                     .create_element("title")
                     .write_text_content_async(BytesText::new(movie.name))
                     .await?;
-                writer
-                    .create_element("actor")
-                    .write_inner_content_async::<_, _, Error>(|writer| async move {
-                        writer
-                            .create_element("name")
-                            .write_text_content_async(BytesText::new(&movie.upper_id.to_string()))
-                            .await?;
-                        writer
-                            .create_element("role")
-                            .write_text_content_async(BytesText::new(movie.upper_name))
-                            .await?;
-                        writer
-                            .create_element("thumb")
-                            .write_text_content_async(BytesText::new(movie.upper_thumb))
-                            .await?;
-                        Ok(writer)
-                    })
-                    .await?;
+                let (name, role, thumb) = match &movie.uploader_thumb {
+                    Some(thumb) => (movie.upper_name.to_owned(), "Uploader", thumb.as_str()),
+                    None => (movie.upper_id.to_string(), movie.upper_name, movie.upper_thumb),
+                };
+                Self::write_actor(writer, &name, role, thumb).await?;
                 writer
                     .create_element("year")
                     .write_text_content_async(BytesText::new(&movie.premiered.format("%Y").to_string()))
@@ -117,6 +116,12 @@ impl NFO<'_> {
                             .await?;
                     }
                 }
+                for tag in movie.rule_tags {
+                    writer
+                        .create_element("tag")
+                        .write_text_content_async(BytesText::new(tag))
+                        .await?;
+                }
                 writer
                     .create_element("uniqueid")
                     .with_attribute(("type", "bilibili"))
@@ -126,6 +131,7 @@ impl NFO<'_> {
                     .create_element("premiered")
                     .write_text_content_async(BytesText::new(&movie.premiered.format("%Y-%m-%d").to_string()))
                     .await?;
+                Self::write_chapters(writer, movie.chapters).await?;
                 Ok(writer)
             })
             .await?;
@@ -145,24 +151,11 @@ impl NFO<'_> {
                     .create_element("title")
                     .write_text_content_async(BytesText::new(tvshow.name))
                     .await?;
-                writer
-                    .create_element("actor")
-                    .write_inner_content_async::<_, _, Error>(|writer| async move {
-                        writer
-                            .create_element("name")
-                            .write_text_content_async(BytesText::new(&tvshow.upper_id.to_string()))
-                            .await?;
-                        writer
-                            .create_element("role")
-                            .write_text_content_async(BytesText::new(tvshow.upper_name))
-                            .await?;
-                        writer
-                            .create_element("thumb")
-                            .write_text_content_async(BytesText::new(tvshow.upper_thumb))
-                            .await?;
-                        Ok(writer)
-                    })
-                    .await?;
+                let (name, role, thumb) = match &tvshow.uploader_thumb {
+                    Some(thumb) => (tvshow.upper_name.to_owned(), "Uploader", thumb.as_str()),
+                    None => (tvshow.upper_id.to_string(), tvshow.upper_name, tvshow.upper_thumb),
+                };
+                Self::write_actor(writer, &name, role, thumb).await?;
                 writer
                     .create_element("year")
                     .write_text_content_async(BytesText::new(&tvshow.premiered.format("%Y").to_string()))
@@ -175,6 +168,12 @@ impl NFO<'_> {
                             .await?;
                     }
                 }
+                for tag in tvshow.rule_tags {
+                    writer
+                        .create_element("tag")
+                        .write_text_content_async(BytesText::new(tag))
+                        .await?;
+                }
                 writer
                     .create_element("uniqueid")
                     .with_attribute(("type", "bilibili"))
@@ -230,18 +229,77 @@ impl NFO<'_> {
                     .await?;
                 writer
                     .create_element("season")
-                    .write_text_content_async(BytesText::new("1"))
+                    .write_text_content_async(BytesText::new(&episode.season.to_string()))
                     .await?;
                 writer
                     .create_element("episode")
                     .write_text_content_async(BytesText::new(&episode.pid))
                     .await?;
+                for tag in episode.rule_tags {
+                    writer
+                        .create_element("tag")
+                        .write_text_content_async(BytesText::new(tag))
+                        .await?;
+                }
+                Self::write_chapters(writer, episode.chapters).await?;
                 Ok(writer)
             })
             .await?;
         Ok(())
     }
 
+    async fn write_actor<W: AsyncWrite + Unpin>(
+        writer: &mut Writer<W>,
+        name: &str,
+        role: &str,
+        thumb: &str,
+    ) -> Result<(), Error> {
+        writer
+            .create_element("actor")
+            .write_inner_content_async::<_, _, Error>(|writer| async move {
+                writer
+                    .create_element("name")
+                    .write_text_content_async(BytesText::new(name))
+                    .await?;
+                writer
+                    .create_element("role")
+                    .write_text_content_async(BytesText::new(role))
+                    .await?;
+                writer
+                    .create_element("thumb")
+                    .write_text_content_async(BytesText::new(thumb))
+                    .await?;
+                Ok(writer)
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// 写入视频的“看点”章节，时间单位为秒
+    async fn write_chapters<W: AsyncWrite + Unpin>(writer: &mut Writer<W>, chapters: &[Chapter]) -> Result<(), Error> {
+        for chapter in chapters {
+            writer
+                .create_element("chapter")
+                .write_inner_content_async::<_, _, Error>(|writer| async move {
+                    writer
+                        .create_element("name")
+                        .write_text_content_async(BytesText::new(&chapter.title))
+                        .await?;
+                    writer
+                        .create_element("start")
+                        .write_text_content_async(BytesText::new(&chapter.from.to_string()))
+                        .await?;
+                    writer
+                        .create_element("end")
+                        .write_text_content_async(BytesText::new(&chapter.to.to_string()))
+                        .await?;
+                    Ok(writer)
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
     #[inline]
     fn format_plot(bvid: &str, intro: &str) -> String {
         format!(
@@ -251,6 +309,27 @@ impl NFO<'_> {
     }
 }
 
+/// 检查内容是否为合法的 XML（标签正确闭合且至少包含一个元素）
+pub fn validate_xml(content: &str) -> Result<()> {
+    let mut reader = Reader::from_str(content);
+    let (mut depth, mut has_element) = (0usize, false);
+    loop {
+        match reader.read_event()? {
+            Event::Start(_) => {
+                depth += 1;
+                has_element = true;
+            }
+            Event::End(_) => depth = depth.checked_sub(1).context("xml 中存在多余的闭合标签")?,
+            Event::Empty(_) => has_element = true,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    ensure!(has_element, "xml 中不包含任何元素");
+    ensure!(depth == 0, "xml 中存在未闭合的元素");
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -354,6 +433,127 @@ mod tests {
 </episodedetails>"#,
         );
     }
+
+    #[tokio::test]
+    async fn test_generate_nfo_with_rule_tags() {
+        use bili_sync_entity::rule::{Condition, Rule, RuleTarget, TagRule};
+
+        use crate::utils::rule::resolve_rule_tags;
+
+        let tag_rule = |tag: &str, target: RuleTarget| TagRule {
+            tag: tag.to_owned(),
+            rule: Rule(vec![vec![target]]),
+        };
+        let tag_rules = vec![
+            tag_rule(
+                "教程",
+                RuleTarget::Title(Condition::MatchesRegex(
+                    r"^\[教程\]".to_owned(),
+                    regex::Regex::new(r"^\[教程\]").unwrap(),
+                )),
+            ),
+            tag_rule("长视频", RuleTarget::Duration(Condition::GreaterThan(3600))),
+            tag_rule("其他作者", RuleTarget::UpperName(Condition::Equals("other".to_owned()))),
+        ];
+        let mut video = video::Model {
+            name: "[教程] Rust 入门".to_string(),
+            upper_name: "upper_name".to_string(),
+            bvid: "BV1nWcSeeEkV".to_string(),
+            tags: Some(vec!["tag1".to_owned()].into()),
+            ..Default::default()
+        };
+        let pages = vec![page::Model {
+            duration: 600,
+            ..Default::default()
+        }];
+        let rule_tags = resolve_rule_tags(&tag_rules, &video, &pages);
+        assert_eq!(rule_tags, ["教程"]);
+        video.rule_tags = Some(rule_tags.into());
+        for nfo in [
+            NFO::Movie((&video).to_nfo(NFOTimeType::FavTime)),
+            NFO::TVShow((&video).to_nfo(NFOTimeType::FavTime)),
+        ] {
+            let content = nfo.generate_nfo().await.unwrap();
+            assert!(content.contains("    <genre>tag1</genre>\n    <tag>教程</tag>\n"));
+            assert!(!content.contains("长视频") && !content.contains("其他作者"));
+        }
+        // 分页的 NFO 同样写入所属视频的自动标签
+        let content = NFO::Episode(Episode {
+            rule_tags: video
+                .rule_tags
+                .as_ref()
+                .map(|tags| tags.0.as_slice())
+                .unwrap_or_default(),
+            ..(&pages[0]).to_nfo(NFOTimeType::FavTime)
+        })
+        .generate_nfo()
+        .await
+        .unwrap();
+        assert!(content.contains("    <episode>0</episode>\n    <tag>教程</tag>\n"));
+        // 标题不匹配正则时不会打上标签
+        video.name = "Rust 入门[教程]".to_string();
+        assert!(resolve_rule_tags(&tag_rules, &video, &pages).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_generate_nfo_with_chapters() {
+        let page = page::Model {
+            name: "name".to_string(),
+            pid: 1,
+            ..Default::default()
+        };
+        let chapters = [
+            Chapter {
+                title: "开场".to_string(),
+                from: 0,
+                to: 65,
+            },
+            Chapter {
+                title: "正片".to_string(),
+                from: 65,
+                to: 180,
+            },
+        ];
+        assert_eq!(
+            NFO::Episode(Episode {
+                chapters: &chapters,
+                ..(&page).to_nfo(NFOTimeType::FavTime)
+            })
+            .generate_nfo()
+            .await
+            .unwrap(),
+            r#"<?xml version="1.0" encoding="utf-8" standalone="yes"?>
+<episodedetails>
+    <plot/>
+    <outline/>
+    <title>name</title>
+    <season>1</season>
+    <episode>1</episode>
+    <chapter>
+        <name>开场</name>
+        <start>0</start>
+        <end>65</end>
+    </chapter>
+    <chapter>
+        <name>正片</name>
+        <start>65</start>
+        <end>180</end>
+    </chapter>
+</episodedetails>"#,
+        );
+        let video = video::Model {
+            bvid: "BV1nWcSeeEkV".to_string(),
+            ..Default::default()
+        };
+        let content = NFO::Movie(Movie {
+            chapters: &chapters,
+            ..(&video).to_nfo(NFOTimeType::FavTime)
+        })
+        .generate_nfo()
+        .await
+        .unwrap();
+        assert!(content.contains("    <premiered>1970-01-01</premiered>\n    <chapter>\n        <name>开场</name>"));
+    }
 }
 
 pub trait ToNFO<'a, T> {
@@ -374,6 +574,13 @@ impl<'a> ToNFO<'a, Movie<'a>> for &'a video::Model {
                 NFOTimeType::PubTime => self.pubtime,
             },
             tags: self.tags.as_ref().map(|tags| tags.clone().into()),
+            rule_tags: self
+                .rule_tags
+                .as_ref()
+                .map(|tags| tags.0.as_slice())
+                .unwrap_or_default(),
+            chapters: &[],
+            uploader_thumb: None,
         }
     }
 }
@@ -392,6 +599,12 @@ impl<'a> ToNFO<'a, TVShow<'a>> for &'a video::Model {
                 NFOTimeType::PubTime => self.pubtime,
             },
             tags: self.tags.as_ref().map(|tags| tags.clone().into()),
+            rule_tags: self
+                .rule_tags
+                .as_ref()
+                .map(|tags| tags.0.as_slice())
+                .unwrap_or_default(),
+            uploader_thumb: None,
         }
     }
 }
@@ -409,7 +622,10 @@ impl<'a> ToNFO<'a, Episode<'a>> for &'a page::Model {
     fn to_nfo(&'a self, _nfo_time_type: NFOTimeType) -> Episode<'a> {
         Episode {
             name: &self.name,
+            season: 1,
             pid: self.pid.to_string(),
+            rule_tags: &[],
+            chapters: &[],
         }
     }
 }
@@ -1,19 +1,29 @@
 use crate::bilibili::BiliClient;
 use crate::config::Config;
-use crate::notifier::{NotifierAllExt, NOTIFICATION_QUEUE};
+use crate::notifier::{NotificationEvent, NotificationPayload, NotifierAllExt, NOTIFICATION_QUEUE, render_message};
 
 pub fn error_and_notify(config: &Config, bili_client: &BiliClient, msg: String) {
     error!("{msg}");
     // 使用消息队列发送，以便统一处理静默时间段
-    notify(config, bili_client, msg);
+    notify(config, bili_client, NotificationPayload::error(msg));
 }
 
 /// 发送通知消息（使用消息队列）
-pub fn notify(config: &Config, bili_client: &BiliClient, msg: String) {
+pub fn notify(config: &Config, bili_client: &BiliClient, msg: impl Into<NotificationPayload>) {
     if let Some(notifiers) = &config.notifiers
         && !notifiers.is_empty()
     {
-        let (notifiers, inner_client) = (notifiers.clone(), bili_client.inner_client().clone());
-        let _ = notifiers.notify_all_queued(&NOTIFICATION_QUEUE, inner_client, msg);
+        let mut payload: NotificationPayload = msg.into();
+        // 普通消息与错误提示没有额外的上下文，其余事件在生成消息时已经应用过模板
+        if matches!(payload.event, NotificationEvent::Message | NotificationEvent::Error) {
+            payload.message = render_message(
+                &config.message_templates,
+                payload.event,
+                serde_json::json!({}),
+                payload.message,
+            );
+        }
+        let (notifiers, inner_client) = (notifiers.clone(), bili_client.notifier_client().clone());
+        let _ = notifiers.notify_all_queued(&NOTIFICATION_QUEUE, inner_client, payload);
     }
 }
@@ -0,0 +1,194 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use bili_sync_entity::*;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+
+use crate::config::{Config, VersionedCache};
+
+/// 限制同时执行的下载后命令数量，避免大量视频同时完成时一次性启动过多进程
+static POST_DOWNLOAD_SEMAPHORE: LazyLock<VersionedCache<Semaphore>> = LazyLock::new(|| {
+    VersionedCache::new(|config| Ok(Semaphore::new(config.post_download_command_concurrency)))
+        .expect("failed to create post download semaphore")
+});
+
+/// 视频下载完成后执行外部命令所需的信息，在后台执行时需要持有所有权
+pub struct PostDownloadCommand {
+    pub command: String,
+    pub timeout: Duration,
+    /// 视频所在的目录，分页文件均保存在该目录下
+    pub video_dir: PathBuf,
+    pub bvid: String,
+    pub title: String,
+    pub upper_name: String,
+}
+
+impl PostDownloadCommand {
+    pub fn new(command: &str, timeout: Duration, video_dir: &Path, video_model: &video::Model) -> Self {
+        Self {
+            command: command.to_owned(),
+            timeout,
+            video_dir: video_dir.to_path_buf(),
+            bvid: video_model.bvid.clone(),
+            title: video_model.name.clone(),
+            upper_name: video_model.upper_name.clone(),
+        }
+    }
+
+    /// 根据配置构造下载后命令，未配置命令时返回 None
+    pub fn from_config(config: &Config, video_dir: &Path, video_model: &video::Model) -> Option<Self> {
+        let command = config
+            .post_download_command
+            .as_deref()
+            .filter(|c| !c.trim().is_empty())?;
+        Some(Self::new(
+            command,
+            Duration::from_secs(config.post_download_command_timeout_secs),
+            video_dir,
+            video_model,
+        ))
+    }
+
+    /// 在后台执行命令，不阻塞视频的下载流程，执行失败或超时仅输出日志
+    /// 同时执行的命令数量受配置的并发数限制，超出的命令排队等待
+    pub fn spawn(self) {
+        let semaphore = POST_DOWNLOAD_SEMAPHORE.snapshot();
+        tokio::spawn(async move {
+            let Ok(_permit) = semaphore.acquire().await else {
+                return;
+            };
+            if let Err(e) = self.run().await {
+                warn!("视频「{}」的下载后命令执行失败：{:#}", self.title, e);
+            }
+        });
+    }
+
+    /// 通过系统 shell 执行命令，视频信息通过环境变量传入，避免标题中的特殊字符被 shell 解析
+    pub async fn run(&self) -> Result<()> {
+        #[cfg(windows)]
+        let mut command = {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(&self.command);
+            command
+        };
+        #[cfg(not(windows))]
+        let mut command = {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(&self.command);
+            command
+        };
+        command
+            .env("BILI_VIDEO_DIR", &self.video_dir)
+            .env("BILI_BVID", &self.bvid)
+            .env("BILI_TITLE", &self.title)
+            .env("BILI_UPPER_NAME", &self.upper_name)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // 超时后 wait_with_output 的 future 被丢弃，此时结束子进程
+            .kill_on_drop(true);
+        // shell 启动的其它进程不会随 shell 一起结束，因此放入单独的进程组，超时后结束整个进程组
+        #[cfg(unix)]
+        command.process_group(0);
+        let child = command.spawn().context("failed to run command")?;
+        let pid = child.id();
+        let output = match tokio::time::timeout(self.timeout, child.wait_with_output()).await {
+            Ok(output) => output.context("failed to run command")?,
+            Err(_) => {
+                #[cfg(unix)]
+                if let Some(pid) = pid {
+                    // 进程组 id 与 shell 的 pid 相同，传入负数表示向整个进程组发送信号
+                    unsafe {
+                        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+                    }
+                }
+                #[cfg(not(unix))]
+                let _ = pid;
+                bail!("command timed out after {} seconds", self.timeout.as_secs());
+            }
+        };
+        if !output.status.success() {
+            bail!(
+                "command exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_post_download_command() -> Result<()> {
+        let test_dir = std::env::temp_dir().join(format!("bili-sync-post-download-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&test_dir).await?;
+        let marker = test_dir.join("marker");
+        let video_model = video::Model {
+            bvid: "BV1test00001".to_owned(),
+            name: "测试视频 $(echo injected)".to_owned(),
+            upper_name: "测试作者".to_owned(),
+            ..Default::default()
+        };
+        let video_dir = test_dir.join("测试视频");
+        let command = format!(
+            r#"printf '%s|%s|%s' "$BILI_BVID" "$BILI_TITLE" "$BILI_VIDEO_DIR" > '{}'"#,
+            marker.display()
+        );
+        PostDownloadCommand::new(&command, Duration::from_secs(10), &video_dir, &video_model)
+            .run()
+            .await?;
+        // 标题通过环境变量原样传入，不会被 shell 解析
+        assert_eq!(
+            tokio::fs::read_to_string(&marker).await?,
+            format!("BV1test00001|测试视频 $(echo injected)|{}", video_dir.display())
+        );
+        // 非零退出码与超时均视为执行失败
+        let err = PostDownloadCommand::new(
+            "echo failed >&2; exit 3",
+            Duration::from_secs(10),
+            &video_dir,
+            &video_model,
+        )
+        .run()
+        .await
+        .unwrap_err();
+        assert!(format!("{:#}", err).contains("failed"));
+        assert!(
+            PostDownloadCommand::new("sleep 5", Duration::from_millis(100), &video_dir, &video_model)
+                .run()
+                .await
+                .is_err()
+        );
+        // 超时后 shell 启动的后台进程也会被结束
+        let pid_file = test_dir.join("pid");
+        let command = format!("sleep 30 & echo $! > '{}'; wait", pid_file.display());
+        assert!(
+            PostDownloadCommand::new(&command, Duration::from_millis(500), &video_dir, &video_model)
+                .run()
+                .await
+                .is_err()
+        );
+        let pid: libc::pid_t = tokio::fs::read_to_string(&pid_file).await?.trim().parse()?;
+        let mut alive = true;
+        for _ in 0..20 {
+            // 被结束的进程由 init 回收，回收前 kill(pid, 0) 仍然成功，因此同时将僵尸进程视为已结束
+            let zombie = std::fs::read_to_string(format!("/proc/{pid}/stat")).is_ok_and(|s| s.contains(") Z "));
+            if zombie || unsafe { libc::kill(pid, 0) } != 0 {
+                alive = false;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(!alive, "background process {pid} survived the timeout");
+        let _ = tokio::fs::remove_dir_all(&test_dir).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+static PROGRESS_REGISTRY: LazyLock<ProgressRegistry> = LazyLock::new(ProgressRegistry::default);
+
+/// 视频的实时下载进度，多页视频的各分页累计在一起
+#[derive(Serialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct VideoProgress {
+    /// 已下载的字节数
+    pub downloaded_bytes: u64,
+    /// 已知的总字节数，随着各分页、各个流开始下载逐渐累加
+    pub total_bytes: u64,
+}
+
+/// 以视频 id 为键的下载进度登记表，各分页的进度按 cid 分开记录，视频或分页处理结束后移除对应的条目
+#[derive(Default)]
+pub struct ProgressRegistry {
+    videos: Mutex<HashMap<i32, HashMap<i64, VideoProgress>>>,
+}
+
+impl ProgressRegistry {
+    pub fn get() -> &'static ProgressRegistry {
+        &PROGRESS_REGISTRY
+    }
+
+    /// 视频的下载进度，为各分页进度之和
+    pub fn progress(&self, video_id: i32) -> Option<VideoProgress> {
+        self.videos.lock().get(&video_id).map(|pages| {
+            pages
+                .values()
+                .fold(VideoProgress::default(), |acc, page| VideoProgress {
+                    downloaded_bytes: acc.downloaded_bytes + page.downloaded_bytes,
+                    total_bytes: acc.total_bytes + page.total_bytes,
+                })
+        })
+    }
+
+    pub fn add_total(&self, video_id: i32, cid: i64, bytes: u64) {
+        self.videos
+            .lock()
+            .entry(video_id)
+            .or_default()
+            .entry(cid)
+            .or_default()
+            .total_bytes += bytes;
+    }
+
+    pub fn add_downloaded(&self, video_id: i32, cid: i64, bytes: u64) {
+        self.videos
+            .lock()
+            .entry(video_id)
+            .or_default()
+            .entry(cid)
+            .or_default()
+            .downloaded_bytes += bytes;
+    }
+
+    /// 从分页的进度中扣除 progress 对应的字节数
+    pub fn subtract(&self, video_id: i32, cid: i64, progress: VideoProgress) {
+        if let Some(entry) = self
+            .videos
+            .lock()
+            .get_mut(&video_id)
+            .and_then(|pages| pages.get_mut(&cid))
+        {
+            entry.total_bytes = entry.total_bytes.saturating_sub(progress.total_bytes);
+            entry.downloaded_bytes = entry.downloaded_bytes.saturating_sub(progress.downloaded_bytes);
+        }
+    }
+
+    /// 移除视频所有分页的进度
+    pub fn remove(&self, video_id: i32) {
+        self.videos.lock().remove(&video_id);
+    }
+
+    /// 仅移除单个分页的进度，不影响同一视频中其它分页的进度
+    pub fn remove_page(&self, video_id: i32, cid: i64) {
+        let mut videos = self.videos.lock();
+        if let Some(pages) = videos.get_mut(&video_id) {
+            pages.remove(&cid);
+            if pages.is_empty() {
+                videos.remove(&video_id);
+            }
+        }
+    }
+}
+
+/// 记录单次下载尝试计入视频进度的字节数，尝试失败时调用 rollback 扣除
+/// 避免切换 url 或重新下载时重复计入总字节数，使进度超过 100%
+#[derive(Clone)]
+pub struct ProgressReporter {
+    video_id: i32,
+    cid: i64,
+    reported: Arc<Mutex<VideoProgress>>,
+}
+
+impl ProgressReporter {
+    pub fn new(video_id: i32, cid: i64) -> Self {
+        Self {
+            video_id,
+            cid,
+            reported: Arc::default(),
+        }
+    }
+
+    /// 开始新一次下载尝试，之后计入的字节数与此前的尝试分开记录
+    pub fn new_attempt(&self) -> Self {
+        Self::new(self.video_id, self.cid)
+    }
+
+    pub fn add_total(&self, bytes: u64) {
+        self.reported.lock().total_bytes += bytes;
+        ProgressRegistry::get().add_total(self.video_id, self.cid, bytes);
+    }
+
+    pub fn add_downloaded(&self, bytes: u64) {
+        self.reported.lock().downloaded_bytes += bytes;
+        ProgressRegistry::get().add_downloaded(self.video_id, self.cid, bytes);
+    }
+
+    /// 扣除本次尝试计入的全部字节数
+    pub fn rollback(&self) {
+        let reported = std::mem::take(&mut *self.reported.lock());
+        ProgressRegistry::get().subtract(self.video_id, self.cid, reported);
+    }
+}
@@ -0,0 +1,450 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, ensure};
+use bili_sync_entity::*;
+use sea_orm::ActiveValue::{Set, Unchanged};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, TransactionTrait};
+use tokio::fs;
+
+use crate::adapter::{VideoSource, VideoSourceEnum};
+
+/// 移动视频源文件的结果
+#[derive(Default, Debug)]
+pub struct RelocateStats {
+    /// 实际移动的文件数量
+    pub moved_files: usize,
+    /// 目标位置已存在同名文件而未移动的文件，这些文件保留在原位置
+    pub skipped_files: Vec<PathBuf>,
+    pub updated_videos: usize,
+    pub updated_pages: usize,
+    /// 存在被跳过的文件而保留原路径记录的视频数量
+    pub conflicted_videos: usize,
+    /// 文件被跳过而保留原路径记录的分页数量
+    pub conflicted_pages: usize,
+    /// 其它视频源中指向本视频源文件、已被重新指向新位置的去重软链接数量
+    pub relinked_files: usize,
+}
+
+/// 将视频源目录下的所有文件移动到新的路径，并在同一事务中更新视频源、视频与分页记录的路径
+///
+/// 原路径不存在时（例如文件已被手动迁移）仅更新数据库中记录的路径
+/// 因目标位置已存在同名文件而被跳过的文件仍位于原位置，对应的视频与分页保留原路径记录，避免数据库指向并非本视频源下载的文件
+pub async fn relocate_video_source(
+    video_source: &VideoSourceEnum,
+    new_path: &Path,
+    connection: &DatabaseConnection,
+) -> Result<RelocateStats> {
+    let old_path = video_source.path().to_path_buf();
+    ensure!(
+        !new_path.starts_with(&old_path) && !old_path.starts_with(new_path),
+        "新路径 {} 不能与原路径 {} 相同，也不能是原路径的上级或下级目录",
+        new_path.display(),
+        old_path.display()
+    );
+    let mut stats = RelocateStats::default();
+    // 去重软链接指向的是真实的绝对路径，移动前记录原路径的真实位置，用于之后修正这些软链接
+    let old_real_path = fs::canonicalize(&old_path).await.ok();
+    if fs::try_exists(&old_path).await? {
+        move_dir_merge(&old_path, new_path, &mut stats).await?;
+    } else {
+        warn!("视频源原路径 {} 不存在，仅更新数据库中记录的路径", old_path.display());
+    }
+    // 文件移动完成后再更新数据库，确保数据库中记录的路径与磁盘上的文件一致
+    let skipped = stats.skipped_files.iter().cloned().collect::<HashSet<_>>();
+    let relocate = |path: &str| {
+        Path::new(path)
+            .strip_prefix(&old_path)
+            .ok()
+            .map(|relative| new_path.join(relative).to_string_lossy().to_string())
+    };
+    let (mut video_ids, mut cids) = (Vec::new(), Vec::new());
+    let txn = connection.begin().await?;
+    let videos = video::Entity::find()
+        .filter(video_source.filter_expr())
+        .find_with_related(page::Entity)
+        .all(&txn)
+        .await?;
+    for (video_model, page_models) in videos {
+        video_ids.push(video_model.id);
+        let video_conflicted = !video_model.path.is_empty()
+            && skipped
+                .iter()
+                .any(|skipped_file| skipped_file.starts_with(&video_model.path));
+        if video_conflicted {
+            warn!(
+                "视频「{}」的部分文件与目标位置的文件冲突，保留原路径 {}",
+                &video_model.name, &video_model.path
+            );
+            stats.conflicted_videos += 1;
+        } else if let Some(path) = relocate(&video_model.path) {
+            video::ActiveModel {
+                id: Unchanged(video_model.id),
+                path: Set(path),
+                ..Default::default()
+            }
+            .update(&txn)
+            .await?;
+            stats.updated_videos += 1;
+        }
+        for page_model in page_models {
+            if page_model
+                .path
+                .as_deref()
+                .is_some_and(|path| skipped.contains(Path::new(path)))
+            {
+                stats.conflicted_pages += 1;
+            } else if let Some(path) = page_model.path.as_deref().and_then(&relocate) {
+                cids.push(page_model.cid);
+                page::ActiveModel {
+                    id: Unchanged(page_model.id),
+                    path: Set(Some(path)),
+                    ..Default::default()
+                }
+                .update(&txn)
+                .await?;
+                stats.updated_pages += 1;
+            }
+        }
+    }
+    video_source
+        .update_path(new_path.to_string_lossy().to_string())
+        .save(&txn)
+        .await?;
+    txn.commit().await?;
+    if let Some(old_real_path) = old_real_path
+        && !cids.is_empty()
+    {
+        let new_real_path = fs::canonicalize(new_path).await?;
+        stats.relinked_files = relink_symlinks(&old_real_path, &new_real_path, video_ids, cids, connection).await?;
+    }
+    Ok(stats)
+}
+
+/// 将其它视频源中指向 old_path 内文件的去重软链接重新指向 new_path 内对应的文件，返回修正的软链接数量
+async fn relink_symlinks(
+    old_path: &Path,
+    new_path: &Path,
+    video_ids: Vec<i32>,
+    cids: Vec<i64>,
+    connection: &DatabaseConnection,
+) -> Result<usize> {
+    let pages = page::Entity::find()
+        .filter(page::Column::Cid.is_in(cids))
+        .filter(page::Column::VideoId.is_not_in(video_ids))
+        .filter(page::Column::Path.is_not_null())
+        .all(connection)
+        .await?;
+    let mut relinked = 0;
+    for path in pages.into_iter().filter_map(|page| page.path).map(PathBuf::from) {
+        let Ok(target) = fs::read_link(&path).await else {
+            continue;
+        };
+        let Ok(relative) = target.strip_prefix(old_path) else {
+            continue;
+        };
+        let new_target = new_path.join(relative);
+        // 目标文件因同名冲突未被移动时，原软链接仍然有效
+        if !fs::try_exists(&new_target).await? {
+            continue;
+        }
+        fs::remove_file(&path).await?;
+        #[cfg(unix)]
+        fs::symlink(&new_target, &path).await?;
+        #[cfg(windows)]
+        fs::symlink_file(&new_target, &path).await?;
+        relinked += 1;
+    }
+    Ok(relinked)
+}
+
+/// 删除视频的本地文件前，将其它视频源中指向该视频目录内文件的去重软链接替换为文件副本，返回替换的软链接数量
+///
+/// 硬链接在删除原文件后仍然有效，无需处理
+pub async fn detach_dependent_symlinks(video_model: &video::Model, connection: &DatabaseConnection) -> Result<usize> {
+    let Ok(video_real_path) = fs::canonicalize(&video_model.path).await else {
+        return Ok(0);
+    };
+    let cids = page::Entity::find()
+        .filter(page::Column::VideoId.eq(video_model.id))
+        .all(connection)
+        .await?
+        .into_iter()
+        .map(|page| page.cid)
+        .collect::<Vec<_>>();
+    if cids.is_empty() {
+        return Ok(0);
+    }
+    let pages = page::Entity::find()
+        .filter(page::Column::Cid.is_in(cids))
+        .filter(page::Column::VideoId.ne(video_model.id))
+        .filter(page::Column::Path.is_not_null())
+        .all(connection)
+        .await?;
+    let mut detached = 0;
+    for path in pages.into_iter().filter_map(|page| page.path).map(PathBuf::from) {
+        let Ok(target) = fs::read_link(&path).await else {
+            continue;
+        };
+        if !target.starts_with(&video_real_path) {
+            continue;
+        }
+        // 先复制到临时文件再替换软链接，复制失败时原软链接保持不变
+        let mut temp_path = path.clone().into_os_string();
+        temp_path.push(".tmp");
+        let temp_path = PathBuf::from(temp_path);
+        fs::copy(&target, &temp_path)
+            .await
+            .with_context(|| format!("failed to copy {} to {}", target.display(), temp_path.display()))?;
+        fs::rename(&temp_path, &path).await?;
+        detached += 1;
+    }
+    Ok(detached)
+}
+
+/// 将 src 目录中的内容合并到 dst 目录，目标位置已存在同名文件时保留目标文件，原文件记录为跳过
+async fn move_dir_merge(src: &Path, dst: &Path, stats: &mut RelocateStats) -> Result<()> {
+    fs::create_dir_all(dst)
+        .await
+        .with_context(|| format!("failed to create directory {}", dst.display()))?;
+    let mut entries = fs::read_dir(src).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let (from, to) = (entry.path(), dst.join(entry.file_name()));
+        if entry.file_type().await?.is_dir() {
+            Box::pin(move_dir_merge(&from, &to, stats)).await?;
+        } else if fs::try_exists(&to).await? {
+            stats.skipped_files.push(from);
+        } else {
+            move_file(&from, &to).await?;
+            stats.moved_files += 1;
+        }
+    }
+    // 存在被跳过的文件时目录非空，删除失败，保留原目录
+    let _ = fs::remove_dir(src).await;
+    Ok(())
+}
+
+async fn move_file(from: &Path, to: &Path) -> Result<()> {
+    match fs::rename(from, to).await {
+        // 无法跨文件系统重命名，此时退化为复制后删除，软链接需要重新创建，直接复制会得到链接目标的完整副本
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            if fs::symlink_metadata(from).await?.file_type().is_symlink() {
+                let target = fs::read_link(from).await?;
+                #[cfg(unix)]
+                fs::symlink(&target, to).await?;
+                #[cfg(windows)]
+                fs::symlink_file(&target, to).await?;
+            } else {
+                fs::copy(from, to).await?;
+            }
+            fs::remove_file(from).await?;
+        }
+        res => res.with_context(|| format!("failed to move {} to {}", from.display(), to.display()))?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::{TestDatabase, test_favorite, test_page, test_video};
+
+    #[tokio::test]
+    async fn test_relocate_video_source() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let (old_path, new_path) = (test_dir.join("old"), test_dir.join("new"));
+        let favorite = test_favorite(old_path.to_string_lossy().to_string())
+            .insert(&connection)
+            .await?;
+        // 第一个视频已下载，第二个视频尚未下载，路径为空
+        let mut videos = Vec::new();
+        for (idx, path) in [old_path.join("视频1"), PathBuf::new()].into_iter().enumerate() {
+            let video = video::ActiveModel {
+                favorite_id: Set(Some(favorite.id)),
+                name: Set(format!("视频{}", idx + 1)),
+                path: Set(path.to_string_lossy().to_string()),
+                cover: Set(String::new()),
+                single_page: Set(Some(false)),
+                ..test_video(&format!("BV1test0000{}", idx + 1))
+            }
+            .insert(&connection)
+            .await?;
+            videos.push(video);
+        }
+        let page_path = old_path.join("视频1").join("Season 1").join("视频1 - S01E01.mp4");
+        let page = page::ActiveModel {
+            path: Set(Some(page_path.to_string_lossy().to_string())),
+            ..test_page(videos[0].id, 1)
+        }
+        .insert(&connection)
+        .await?;
+        fs::create_dir_all(page_path.parent().context("page path has no parent")?).await?;
+        fs::write(&page_path, b"video").await?;
+        fs::write(old_path.join("视频1").join("tvshow.nfo"), b"old nfo").await?;
+        // 目标位置已存在同名文件时保留目标文件
+        fs::create_dir_all(new_path.join("视频1")).await?;
+        fs::write(new_path.join("视频1").join("tvshow.nfo"), b"new nfo").await?;
+        // 分页文件与目标位置冲突时，分页保留原路径
+        let conflicted_page_path = old_path.join("视频1").join("Season 1").join("视频1 - S01E02.mp4");
+        let conflicted_page = page::ActiveModel {
+            path: Set(Some(conflicted_page_path.to_string_lossy().to_string())),
+            ..test_page(videos[0].id, 2)
+        }
+        .insert(&connection)
+        .await?;
+        fs::write(&conflicted_page_path, b"old video").await?;
+        fs::create_dir_all(new_path.join("视频1").join("Season 1")).await?;
+        fs::write(
+            new_path.join("视频1").join("Season 1").join("视频1 - S01E02.mp4"),
+            b"other video",
+        )
+        .await?;
+
+        let mut stats = relocate_video_source(&VideoSourceEnum::from(favorite.clone()), &new_path, &connection).await?;
+        assert_eq!(stats.moved_files, 1);
+        stats.skipped_files.sort();
+        assert_eq!(
+            stats.skipped_files,
+            vec![conflicted_page_path.clone(), old_path.join("视频1").join("tvshow.nfo")]
+        );
+        // 视频目录中存在冲突的文件，视频保留原路径，已移动的分页更新为新路径
+        assert_eq!((stats.updated_videos, stats.updated_pages), (0, 1));
+        assert_eq!((stats.conflicted_videos, stats.conflicted_pages), (1, 1));
+
+        let new_page_path = new_path.join("视频1").join("Season 1").join("视频1 - S01E01.mp4");
+        assert_eq!(fs::read(&new_page_path).await?, b"video");
+        assert!(!fs::try_exists(&page_path).await?);
+        assert_eq!(fs::read(new_path.join("视频1").join("tvshow.nfo")).await?, b"new nfo");
+        assert!(fs::try_exists(old_path.join("视频1").join("tvshow.nfo")).await?);
+
+        let favorite = favorite::Entity::find_by_id(favorite.id)
+            .one(&connection)
+            .await?
+            .context("favorite not found")?;
+        assert_eq!(favorite.path, new_path.to_string_lossy());
+        let video_paths = video::Entity::find()
+            .all(&connection)
+            .await?
+            .into_iter()
+            .map(|video| video.path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            video_paths,
+            vec![old_path.join("视频1").to_string_lossy().to_string(), String::new()]
+        );
+        let page = page::Entity::find_by_id(page.id)
+            .one(&connection)
+            .await?
+            .context("page not found")?;
+        assert_eq!(page.path, Some(new_page_path.to_string_lossy().to_string()));
+        let conflicted_page = page::Entity::find_by_id(conflicted_page.id)
+            .one(&connection)
+            .await?
+            .context("page not found")?;
+        assert_eq!(
+            conflicted_page.path,
+            Some(conflicted_page_path.to_string_lossy().to_string())
+        );
+        assert_eq!(fs::read(&conflicted_page_path).await?, b"old video");
+        // 新路径不能与原路径相同，也不能是原路径的上级或下级目录
+        let favorite = VideoSourceEnum::from(favorite);
+        for path in [new_path.clone(), new_path.join("sub"), test_dir.to_path_buf()] {
+            assert!(relocate_video_source(&favorite, &path, &connection).await.is_err());
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_relocate_rewrites_dependent_symlinks() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let (old_path, new_path, other_path) = (test_dir.join("old"), test_dir.join("new"), test_dir.join("other"));
+        let mut page_paths = Vec::new();
+        let mut favorites = Vec::new();
+        for (f_id, path) in [(1, &old_path), (2, &other_path)] {
+            let favorite = favorite::ActiveModel {
+                f_id: Set(f_id),
+                ..test_favorite(path.to_string_lossy().to_string())
+            }
+            .insert(&connection)
+            .await?;
+            let video = video::ActiveModel {
+                favorite_id: Set(Some(favorite.id)),
+                path: Set(path.join("视频").to_string_lossy().to_string()),
+                ..test_video("BV1test00001")
+            }
+            .insert(&connection)
+            .await?;
+            let page_path = path.join("视频").join("视频.mp4");
+            page::ActiveModel {
+                path: Set(Some(page_path.to_string_lossy().to_string())),
+                ..test_page(video.id, 1)
+            }
+            .insert(&connection)
+            .await?;
+            fs::create_dir_all(page_path.parent().context("page path has no parent")?).await?;
+            page_paths.push(page_path);
+            favorites.push(favorite);
+        }
+        // 另一个视频源中的分页是指向本视频源文件的去重软链接
+        fs::write(&page_paths[0], b"video").await?;
+        fs::symlink(fs::canonicalize(&page_paths[0]).await?, &page_paths[1]).await?;
+
+        let stats = relocate_video_source(&VideoSourceEnum::from(favorites[0].clone()), &new_path, &connection).await?;
+        assert_eq!(stats.relinked_files, 1);
+        assert_eq!(
+            fs::read_link(&page_paths[1]).await?,
+            fs::canonicalize(new_path.join("视频").join("视频.mp4")).await?
+        );
+        assert_eq!(fs::read(&page_paths[1]).await?, b"video");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_detach_dependent_symlinks() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let mut page_paths = Vec::new();
+        let mut videos = Vec::new();
+        for (f_id, name) in [(1, "source"), (2, "other")] {
+            let path = test_dir.join(name);
+            let favorite = favorite::ActiveModel {
+                f_id: Set(f_id),
+                ..test_favorite(path.to_string_lossy().to_string())
+            }
+            .insert(&connection)
+            .await?;
+            let video = video::ActiveModel {
+                favorite_id: Set(Some(favorite.id)),
+                path: Set(path.join("视频").to_string_lossy().to_string()),
+                ..test_video("BV1test00001")
+            }
+            .insert(&connection)
+            .await?;
+            let page_path = path.join("视频").join("视频.mp4");
+            page::ActiveModel {
+                path: Set(Some(page_path.to_string_lossy().to_string())),
+                ..test_page(video.id, 1)
+            }
+            .insert(&connection)
+            .await?;
+            fs::create_dir_all(page_path.parent().context("page path has no parent")?).await?;
+            page_paths.push(page_path);
+            videos.push(video);
+        }
+        fs::write(&page_paths[0], b"video").await?;
+        fs::symlink(fs::canonicalize(&page_paths[0]).await?, &page_paths[1]).await?;
+
+        // 删除被链接的视频目录前，软链接被替换为文件副本，删除后仍可读取
+        assert_eq!(detach_dependent_symlinks(&videos[0], &connection).await?, 1);
+        fs::remove_dir_all(&videos[0].path).await?;
+        assert!(!fs::symlink_metadata(&page_paths[1]).await?.file_type().is_symlink());
+        assert_eq!(fs::read(&page_paths[1]).await?, b"video");
+        // 另一视频本身不被其它软链接依赖
+        assert_eq!(detach_dependent_symlinks(&videos[1], &connection).await?, 0);
+        Ok(())
+    }
+}
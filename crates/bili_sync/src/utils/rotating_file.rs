@@ -0,0 +1,137 @@
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{Result, Write};
+use std::path::{Path, PathBuf};
+
+/// 日志文件的滚动设置
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogRotation {
+    /// 单个日志文件的最大字节数，为 0 时不滚动
+    pub max_size: u64,
+    /// 保留的历史日志文件数量（不含正在写入的文件）
+    pub max_files: usize,
+}
+
+/// 按大小滚动的日志文件
+/// 写入将超出大小限制时，当前文件依次重命名为 xxx.log.1、xxx.log.2...，序号越大越旧，超出保留数量的文件会被删除
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    rotation: LogRotation,
+}
+
+impl RotatingFile {
+    pub fn open(path: impl Into<PathBuf>, rotation: LogRotation) -> Result<Self> {
+        let path = path.into();
+        let file = Self::open_file(&path)?;
+        let size = file.metadata()?.len();
+        let rotating_file = Self {
+            path,
+            file,
+            size,
+            rotation,
+        };
+        // 保留数量调小后，启动时清理多出来的历史文件
+        let mut index = rotation.max_files + 1;
+        while rotating_file.rotated_path(index).exists() {
+            std::fs::remove_file(rotating_file.rotated_path(index))?;
+            index += 1;
+        }
+        Ok(rotating_file)
+    }
+
+    fn open_file(path: &Path) -> Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        self.file.flush()?;
+        let max_files = self.rotation.max_files;
+        // 先删除最旧的文件，再将其余的历史文件序号依次后移，最后将当前文件作为第一个历史文件
+        if self.rotated_path(max_files).exists() {
+            std::fs::remove_file(self.rotated_path(max_files))?;
+        }
+        for index in (1..max_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                std::fs::rename(from, self.rotated_path(index + 1))?;
+            }
+        }
+        if max_files > 0 {
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        } else {
+            std::fs::remove_file(&self.path)?;
+        }
+        self.file = Self::open_file(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        // 日志按事件整体写入，只在两次写入之间滚动，避免把一条日志拆到两个文件中
+        if self.rotation.max_size > 0 && self.size > 0 && self.size + buf.len() as u64 > self.rotation.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotating_file() -> Result<()> {
+        let test_dir = std::env::temp_dir().join(format!("bili-sync-rotating-log-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&test_dir)?;
+        let path = test_dir.join("bili-sync.log");
+        let rotation = LogRotation {
+            max_size: 100,
+            max_files: 2,
+        };
+        let mut file = RotatingFile::open(&path, rotation)?;
+        for index in 0..10 {
+            file.write_all(format!("{:039}\n", index).as_bytes())?;
+        }
+        file.flush()?;
+        // 每个文件最多容纳两条日志，最终保留当前文件与两个历史文件，更早的文件被删除
+        assert_eq!(std::fs::read_to_string(&path)?, format!("{:039}\n{:039}\n", 8, 9));
+        assert_eq!(
+            std::fs::read_to_string(file.rotated_path(1))?,
+            format!("{:039}\n{:039}\n", 6, 7)
+        );
+        assert_eq!(
+            std::fs::read_to_string(file.rotated_path(2))?,
+            format!("{:039}\n{:039}\n", 4, 5)
+        );
+        assert!(!file.rotated_path(3).exists());
+        drop(file);
+        // 调小保留数量后重新打开，多出的历史文件会被清理，当前文件继续追加写入
+        let file = RotatingFile::open(
+            &path,
+            LogRotation {
+                max_files: 1,
+                ..rotation
+            },
+        )?;
+        assert!(file.rotated_path(1).exists() && !file.rotated_path(2).exists());
+        assert_eq!(file.size, 80);
+        let _ = std::fs::remove_dir_all(&test_dir);
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use bili_sync_entity::rule::{AndGroup, Condition, Rule, RuleTarget};
+use bili_sync_entity::rule::{AndGroup, Condition, Rule, RuleTarget, TagRule};
 use bili_sync_entity::{page, video};
 use chrono::{Local, NaiveDateTime};
 
@@ -72,6 +72,17 @@ impl FieldEvaluatable for RuleTarget {
                 .map(|pub_time| pub_time.and_utc().with_timezone(&Local).naive_local())
                 .is_some_and(|pub_time| cond.evaluate(&pub_time)),
             RuleTarget::PageCount(cond) => cond.evaluate(pages.len()),
+            RuleTarget::UpperName(cond) => video
+                .upper_name
+                .try_as_ref()
+                .is_some_and(|upper_name| cond.evaluate(upper_name)),
+            RuleTarget::Duration(cond) => cond.evaluate(
+                pages
+                    .iter()
+                    .filter_map(|page| page.duration.try_as_ref())
+                    .map(|duration| *duration as usize)
+                    .sum(),
+            ),
             RuleTarget::Not(inner) => !inner.evaluate(video, pages),
         }
     }
@@ -89,6 +100,8 @@ impl FieldEvaluatable for RuleTarget {
             RuleTarget::FavTime(cond) => cond.evaluate(&video.favtime.and_utc().with_timezone(&Local).naive_local()),
             RuleTarget::PubTime(cond) => cond.evaluate(&video.pubtime.and_utc().with_timezone(&Local).naive_local()),
             RuleTarget::PageCount(cond) => cond.evaluate(pages.len()),
+            RuleTarget::UpperName(cond) => cond.evaluate(&video.upper_name),
+            RuleTarget::Duration(cond) => cond.evaluate(pages.iter().map(|page| page.duration as usize).sum()),
             RuleTarget::Not(inner) => !inner.evaluate_model(video, pages),
         }
     }
@@ -131,6 +144,17 @@ impl FieldEvaluatable for Option<Rule> {
     }
 }
 
+/// 依次评估自动标签规则，返回视频命中的所有标签，同名标签只保留一次
+pub(crate) fn resolve_rule_tags(tag_rules: &[TagRule], video: &video::Model, pages: &[page::Model]) -> Vec<String> {
+    let mut tags = Vec::new();
+    for tag_rule in tag_rules {
+        if !tags.contains(&tag_rule.tag) && tag_rule.rule.evaluate_model(video, pages) {
+            tags.push(tag_rule.tag.clone());
+        }
+    }
+    tags
+}
+
 #[cfg(test)]
 mod tests {
     use bili_sync_entity::page;
@@ -1,6 +1,11 @@
 use std::io;
+use std::sync::LazyLock;
 
 use tokio::signal;
+use tokio_util::sync::CancellationToken;
+
+/// 停机信号，收到终止信号后被取消，下载任务会在视频与分页的间隙检查该信号并停止
+pub static SHUTDOWN: LazyLock<CancellationToken> = LazyLock::new(CancellationToken::new);
 
 #[cfg(target_family = "windows")]
 pub async fn terminate() -> io::Result<()> {
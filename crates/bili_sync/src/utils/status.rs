@@ -15,6 +15,7 @@ pub static STATUS_COMPLETED: u32 = 1 << 31;
 /// 用来表示下载的状态，不想写太多列了，所以仅使用一个 u32 表示。
 /// 从低位开始，固定每三位表示一种子任务的状态。
 /// 子任务状态从 0b000 开始，每执行失败一次将状态加一，最多 0b100（即允许重试 4 次），该值定义为 STATUS_MAX_RETRY。
+/// 自动重试的次数可以调小，失败次数达到上限时直接将状态设置为 STATUS_MAX_RETRY，表示不再自动重试的最终失败。
 /// 如果子任务执行成功，将状态设置为 0b111，该值定义为 STATUS_OK。
 /// 子任务达到最大失败次数或者执行成功时，认为该子任务已经完成。
 /// 当所有子任务都已经完成时，为最高位打上标记 1，表示整个下载任务已经完成。
@@ -59,6 +60,17 @@ impl<const N: usize, C> Status<N, C> {
         changed
     }
 
+    /// 仅重置指定子任务的失败状态，返回值表示 status 是否发生了变化
+    pub fn reset_failed_at(&mut self, offset: usize) -> bool {
+        let status = self.get_status(offset);
+        if status == STATUS_NOT_STARTED || status == STATUS_OK {
+            return false;
+        }
+        self.set_status(offset, STATUS_NOT_STARTED);
+        self.set_completed(false);
+        true
+    }
+
     /// 重置所有失败的状态，将状态设置为 0b000，返回值表示 status 是否发生了变化
     /// force 版本在普通版本的基础上，会额外检查是否存在需要运行的任务，如果存在则修正 completed 标记位为“未完成”
     /// 这个方法的典型用例是在引入新的任务状态后重置历史视频，允许历史视频执行新引入的任务
@@ -89,9 +101,14 @@ impl<const N: usize, C> Status<N, C> {
     /// 根据任务结果更新状态，任务结果是一个 Result 数组，需要与子任务一一对应
     /// 如果所有子任务都已经完成，那么打上最高位的完成标记
     pub fn update_status(&mut self, result: &[ExecutionStatus]) {
+        self.update_status_with_max_retries(result, STATUS_MAX_RETRY);
+    }
+
+    /// 与 update_status 相同，但子任务失败 max_retries 次后即标记为最终失败，不再自动重试
+    pub fn update_status_with_max_retries(&mut self, result: &[ExecutionStatus], max_retries: u32) {
         assert!(result.len() == N, "result length should be equal to N");
         for (i, res) in result.iter().enumerate() {
-            self.set_result(res, i);
+            self.set_result(res, i, max_retries);
         }
         if self.should_run().into_iter().all(|x| !x) {
             self.set_completed(true);
@@ -135,7 +152,7 @@ impl<const N: usize, C> Status<N, C> {
     }
 
     /// 根据子任务执行结果更新子任务的状态
-    fn set_result(&mut self, result: &ExecutionStatus, offset: usize) {
+    fn set_result(&mut self, result: &ExecutionStatus, offset: usize, max_retries: u32) {
         // 如果任务返回 Fixed 状态，那么无论之前的状态如何，都将状态设置为 Fixed 的状态
         if let ExecutionStatus::Fixed(status) = result {
             assert!(*status < 0b1000, "status should be less than 0b1000");
@@ -143,6 +160,9 @@ impl<const N: usize, C> Status<N, C> {
         } else if self.get_status(offset) < STATUS_MAX_RETRY {
             match result {
                 ExecutionStatus::Succeeded | ExecutionStatus::Skipped => self.set_ok(offset),
+                ExecutionStatus::Failed(_) if self.get_status(offset) + 1 >= max_retries => {
+                    self.set_status(offset, STATUS_MAX_RETRY)
+                }
                 ExecutionStatus::Failed(_) => self.plus_one(offset),
                 _ => {}
             }
@@ -333,6 +353,57 @@ mod tests {
         assert_eq!(<[u32; 3]>::from(status), [0, 0, 0]);
     }
 
+    #[test]
+    fn test_status_max_retries() {
+        let failed = || {
+            [
+                ExecutionStatus::Failed(anyhow!("")),
+                ExecutionStatus::Failed(anyhow!("")),
+                ExecutionStatus::Succeeded,
+            ]
+        };
+        // 限制为失败 2 次后不再自动重试
+        let mut status = Status::<3, video::Column>::default();
+        status.update_status_with_max_retries(&failed(), 2);
+        assert_eq!(<[u32; 3]>::from(status), [1, 1, 7]);
+        assert_eq!(status.should_run(), [true, true, false]);
+        status.update_status_with_max_retries(&failed(), 2);
+        // 达到上限后标记为最终失败，与仍可重试的失败区分开
+        assert_eq!(<[u32; 3]>::from(status), [STATUS_MAX_RETRY, STATUS_MAX_RETRY, 7]);
+        assert_eq!(status.should_run(), [false, false, false]);
+        assert!(status.get_completed());
+        // 再次执行也不会改变最终失败的状态
+        status.update_status_with_max_retries(&failed(), 2);
+        assert_eq!(<[u32; 3]>::from(status), [STATUS_MAX_RETRY, STATUS_MAX_RETRY, 7]);
+        // 手动重置后清空失败次数，恢复自动重试
+        assert!(status.reset_failed());
+        assert_eq!(<[u32; 3]>::from(status), [0, 0, 7]);
+        assert_eq!(status.should_run(), [true, true, false]);
+        assert!(!status.get_completed());
+        // 上限为 1 时失败一次即为最终失败
+        let mut status = Status::<3, video::Column>::default();
+        status.update_status_with_max_retries(&failed(), 1);
+        assert_eq!(<[u32; 3]>::from(status), [STATUS_MAX_RETRY, STATUS_MAX_RETRY, 7]);
+    }
+
+    #[test]
+    fn test_status_reset_failed_at() {
+        // 仅重置指定的失败子任务，其它失败的子任务保持不变
+        let mut status = Status::<3, video::Column>::from([4, 2, 7]);
+        assert!(!status.get_completed());
+        assert!(status.reset_failed_at(1));
+        assert_eq!(<[u32; 3]>::from(status), [4, 0, 7]);
+        // 未开始或已成功的子任务不会被重置
+        assert!(!status.reset_failed_at(1));
+        assert!(!status.reset_failed_at(2));
+        // 全部完成（失败次数耗尽）的任务被重置后，completed 标记位应被清除
+        let mut status = Status::<3, video::Column>::from([4, 7, 7]);
+        assert!(status.get_completed());
+        assert!(status.reset_failed_at(0));
+        assert!(!status.get_completed());
+        assert_eq!(<[u32; 3]>::from(status), [0, 7, 7]);
+    }
+
     #[test]
     fn test_status_set() {
         // 设置子状态，从 completed 到 uncompleted
@@ -0,0 +1,95 @@
+//! 测试中共用的临时数据库与数据构造方法
+use std::path::PathBuf;
+
+use anyhow::Result;
+use bili_sync_entity::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::DatabaseConnection;
+
+use crate::database::{DATABASE_FILE_NAME, setup_database};
+
+/// 位于独立临时目录中的测试数据库，离开作用域时删除整个目录，测试失败时同样会清理
+pub struct TestDatabase {
+    pub dir: PathBuf,
+    pub connection: DatabaseConnection,
+}
+
+impl TestDatabase {
+    pub async fn new() -> Result<Self> {
+        let dir = std::env::temp_dir().join(format!("bili-sync-test-{}", uuid::Uuid::new_v4()));
+        let connection = setup_database(&dir.join(DATABASE_FILE_NAME)).await?;
+        Ok(Self { dir, connection })
+    }
+}
+
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// 启用状态的测试收藏夹
+pub fn test_favorite(path: String) -> favorite::ActiveModel {
+    favorite::ActiveModel {
+        f_id: Set(1),
+        name: Set("测试收藏夹".to_owned()),
+        path: Set(path),
+        enabled: Set(true),
+        ..Default::default()
+    }
+}
+
+/// 已获取到基本信息、尚未开始下载的测试视频，不同的字段可以通过结构体更新语法覆盖
+pub fn test_video(bvid: &str) -> video::ActiveModel {
+    let now = chrono::Utc::now().naive_utc();
+    video::ActiveModel {
+        upper_id: Set(12345),
+        upper_name: Set("测试作者".to_owned()),
+        upper_face: Set("https://example.com/face.jpg".to_owned()),
+        name: Set("测试视频".to_owned()),
+        path: Set(String::new()),
+        category: Set(2),
+        bvid: Set(bvid.to_owned()),
+        intro: Set(String::new()),
+        cover: Set("https://example.com/cover.jpg".to_owned()),
+        ctime: Set(now),
+        pubtime: Set(now),
+        favtime: Set(now),
+        download_status: Set(0),
+        valid: Set(true),
+        should_download: Set(true),
+        is_paid_video: Set(false),
+        ..Default::default()
+    }
+}
+
+/// 时长 60 秒、尚未开始下载的测试分页，cid 与 pid 相同
+pub fn test_page(video_id: i32, pid: i32) -> page::ActiveModel {
+    page::ActiveModel {
+        video_id: Set(video_id),
+        cid: Set(pid as i64),
+        pid: Set(pid),
+        name: Set(format!("P{}", pid)),
+        duration: Set(60),
+        download_status: Set(0),
+        ..Default::default()
+    }
+}
+
+/// 模拟视频详情接口返回的单分页免费视频，可以修改其中的字段后再反序列化为 VideoInfo
+pub fn test_view_info(bvid: &str) -> serde_json::Value {
+    serde_json::json!({
+        "title": format!("测试视频 {}", bvid),
+        "bvid": bvid,
+        "desc": "",
+        "pic": "https://example.com/cover.jpg",
+        "owner": {"mid": 12345, "name": "测试作者", "face": "https://example.com/face.jpg"},
+        "ctime": 1700000000,
+        "pubdate": 1700000000,
+        "is_upower_exclusive": false,
+        "is_upower_play": false,
+        "rights": {"arc_pay": 0, "ugc_pay": 0, "free_watch": 0},
+        "pages": [{"cid": 1, "page": 1, "part": "P1", "duration": 60}],
+        "state": 0
+    })
+}
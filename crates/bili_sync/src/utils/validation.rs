@@ -1,7 +1,10 @@
 use std::path::Path;
 
+use bili_sync_entity::video;
 use validator::ValidationError;
 
+use crate::config::{CRON_PARSER, VersionedConfig, preview_path_template};
+use crate::utils::format_arg::video_format_args;
 use crate::utils::status::{STATUS_NOT_STARTED, STATUS_OK};
 
 pub fn validate_status_value(value: u32) -> Result<(), ValidationError> {
@@ -21,3 +24,41 @@ pub fn validate_path(path: &str) -> Result<(), ValidationError> {
         Ok(())
     }
 }
+
+/// 视频源单独设置的扫描间隔需要大于 60 秒，0 表示清除设置
+pub fn validate_scan_interval_secs(secs: u32) -> Result<(), ValidationError> {
+    if secs == 0 || secs > 60 {
+        Ok(())
+    } else {
+        Err(ValidationError::new("scan_interval_secs must be greater than 60"))
+    }
+}
+
+/// 视频源单独设置的扫描 cron 表达式格式为：秒 分 时 日 月 周，空字符串表示清除设置
+pub fn validate_scan_cron(cron: &str) -> Result<(), ValidationError> {
+    if cron.trim().is_empty()
+        || CRON_PARSER.parse(cron)
+            .is_ok()
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("scan_cron must be a valid cron expression"))
+    }
+}
+
+/// 视频源单独设置的 video_name 模板需要能够使用示例视频渲染出合法的相对路径，空字符串表示清除设置
+pub fn validate_video_name(template: &str) -> Result<(), ValidationError> {
+    if template.trim().is_empty() {
+        return Ok(());
+    }
+    let video_model = video::Model {
+        bvid: "BV1GJ411x7h7".to_owned(),
+        name: "示例视频".to_owned(),
+        upper_name: "示例 UP 主".to_owned(),
+        ..Default::default()
+    };
+    let data = video_format_args(&video_model, &VersionedConfig::get().read().time_format);
+    preview_path_template(template, &data)
+        .map(|_| ())
+        .map_err(|_| ValidationError::new("video_name must be a valid path template"))
+}
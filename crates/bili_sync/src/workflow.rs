@@ -1,31 +1,44 @@
-use std::collections::HashSet;
-use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
 use std::pin::Pin;
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Context, Result, anyhow, bail, ensure};
 use bili_sync_entity::*;
 use futures::stream::FuturesUnordered;
 use futures::{Stream, StreamExt, TryStreamExt};
 use sea_orm::ActiveValue::Set;
-use sea_orm::TransactionTrait;
 use sea_orm::entity::prelude::*;
+use sea_orm::{QueryOrder, QuerySelect, TransactionTrait};
 use tokio::fs;
+use tokio::process::Command;
 use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
 
 use crate::adapter::{VideoSource, VideoSourceEnum};
-use crate::bilibili::{BestStream, BiliClient, BiliError, Dimension, PageInfo, Video, VideoInfo};
-use crate::config::{ARGS, Config, PathSafeTemplate};
+use crate::bilibili::{
+    AudioQuality, BestStream, BiliClient, BiliError, Chapter, Dimension, FilterOption, PageInfo, SubTitle,
+    SubtitleFormat, Video, VideoInfo, WatchLater, chapters_to_ffmetadata,
+};
+use crate::config::{
+    ARGS, Config, CoverFormat, DedupStrategy, NFOMode, PathSafeTemplate, PosterSource, VideoContainer, render_season,
+};
 use crate::downloader::Downloader;
-use crate::error::ExecutionStatus;
-use crate::notifier::{NotifierAllExt, NOTIFICATION_QUEUE};
+use crate::error::{DownloadCancelled, ExecutionStatus};
+use crate::notifier::{NOTIFICATION_QUEUE, NotificationEvent, NotificationPayload, NotifierAllExt, render_message};
 use crate::utils::download_context::DownloadContext;
-use crate::utils::format_arg::{page_format_args, video_format_args};
+use crate::utils::format_arg::{nfo_format_args, page_format_args, video_format_args};
+use crate::utils::manifest::update_source_manifest;
 use crate::utils::model::{
-    create_pages, create_videos, filter_unfilled_videos, filter_unhandled_video_pages, update_pages_model,
-    update_videos_model,
+    create_pages, create_videos, filter_below_stat_threshold_videos, filter_unfilled_videos,
+    filter_unhandled_video_pages, record_page_task_events, record_video_task_events, update_pages_model,
+    update_video_orders, update_videos_model,
 };
-use crate::utils::nfo::{NFO, ToNFO};
-use crate::utils::rule::FieldEvaluatable;
+use crate::utils::nfo::{Episode, Movie, NFO, TVShow, ToNFO};
+use crate::utils::notify::notify;
+use crate::utils::post_download::PostDownloadCommand;
+use crate::utils::progress::ProgressRegistry;
+use crate::utils::rule::{FieldEvaluatable, resolve_rule_tags};
 use crate::utils::status::{PageStatus, STATUS_OK, VideoStatus};
 
 /// 完整地处理某个视频来源
@@ -35,22 +48,62 @@ pub async fn process_video_source(
     connection: &DatabaseConnection,
     template: &handlebars::Handlebars<'_>,
     config: &Config,
+    dry_run: bool,
+    cancel: &CancellationToken,
 ) -> Result<()> {
+    // 收到停机信号后不再开始处理新的视频源
+    ensure!(!cancel.is_cancelled(), DownloadCancelled);
     // 预创建视频源目录，提前检测目录是否可写
-    video_source.create_dir_all().await?;
-    // 从参数中获取视频列表的 Model 与视频流
+    if !dry_run {
+        video_source.create_dir_all().await?;
+    }
+    // 从参数中获取视频列表的 Model 与视频流，演练模式下不将视频源的最新信息写入数据库
     let (video_source, video_streams) = video_source
-        .refresh(bili_client, &config.credential, connection)
+        .refresh(bili_client, &config.credential, (!dry_run).then_some(connection))
         .await?;
     // 从视频流中获取新视频的简要信息，写入数据库
-    let new_bvids = refresh_video_source(&video_source, video_streams, connection).await?;
-    // 单独请求视频详情接口，获取视频的详情信息与所有的分页，写入数据库
-    fetch_video_details(bili_client, &video_source, connection, config).await?;
+    let (new_bvids, invalidated_videos) =
+        refresh_video_source(&video_source, video_streams, connection, dry_run).await?;
+    for video_model in &invalidated_videos {
+        notify_invalid_video(&video_source, video_model, bili_client, config);
+    }
+    let downloader = Downloader::from_config(bili_client.client.clone(), config);
+    let cx = DownloadContext {
+        cancel,
+        ..DownloadContext::new(
+            bili_client,
+            &video_source,
+            template,
+            connection,
+            &downloader,
+            config,
+            dry_run,
+        )
+    };
+    if dry_run {
+        // 演练模式下仅请求新视频与尚未获取详情的视频的详情，输出将要执行的操作，随后输出已入库视频的下载计划
+        let mut videos_model = new_bvids
+            .into_iter()
+            .map(|bvid| video::Model {
+                bvid,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        videos_model.extend(filter_unfilled_videos(video_source.filter_expr(), connection).await?);
+        plan_video_details(bili_client, &video_source, videos_model, config).await;
+        return download_unprocessed_videos(cx).await;
+    }
     if ARGS.scan_only {
+        // 单独请求视频详情接口，获取视频的详情信息与所有的分页，写入数据库
+        fetch_video_details(bili_client, &video_source, connection, config).await?;
         warn!("已开启仅扫描模式，跳过视频下载..");
+    } else if config.concurrent_limit.prefetch > 1 {
+        // 预取视频详情的同时，将已获取到详情的视频送入下载队列
+        prefetch_and_download_videos(cx).await?;
     } else {
+        fetch_video_details(bili_client, &video_source, connection, config).await?;
         // 从数据库中查找所有未下载的视频与分页，下载并处理
-        download_unprocessed_videos(bili_client, &video_source, connection, template, config).await?;
+        download_unprocessed_videos(cx).await?;
     }
     
     // 如果启用了新视频通知且有新视频，统计并发送通知
@@ -93,12 +146,33 @@ pub async fn process_video_source(
                 message_parts.push(format!("  |  ⏳ 等待中: {} 个", waiting_count));
             }
             
-            let message = message_parts.join("\n");
-            let client = bili_client.inner_client().clone();
+            let message = render_message(
+                &config.message_templates,
+                NotificationEvent::NewVideos,
+                serde_json::json!({
+                    "source_name": source_name,
+                    "video_count": total_count,
+                    "succeeded": succeeded_count,
+                    "failed": failed_count,
+                    "waiting": waiting_count,
+                }),
+                message_parts.join("\n"),
+            );
+            let payload = NotificationPayload {
+                event: NotificationEvent::NewVideos,
+                message,
+                source_name: Some(source_name.into_owned()),
+                source_type: Some(video_source.key().source_type().to_owned()),
+                video_count: Some(total_count as u64),
+                succeeded: Some(succeeded_count),
+                failed: Some(failed_count),
+                bvid: None,
+            };
+            let client = bili_client.notifier_client().clone();
             let _ = notifiers.notify_all_queued(
                 &NOTIFICATION_QUEUE,
                 client,
-                message,
+                payload,
             );
         }
     }
@@ -107,16 +181,21 @@ pub async fn process_video_source(
 }
 
 /// 请求接口，获取视频列表中所有新添加的视频信息，将其写入数据库
-/// 返回新视频的 bvid 列表
+/// 返回新视频的 bvid 列表，以及此前有效、本次在视频列表中表明已失效的视频
+///
+/// 演练模式下不写入数据库，仅输出并返回尚未入库的新视频
 pub async fn refresh_video_source<'a>(
     video_source: &VideoSourceEnum,
     video_streams: Pin<Box<dyn Stream<Item = Result<VideoInfo>> + 'a + Send>>,
     connection: &DatabaseConnection,
-) -> Result<Vec<String>> {
+    dry_run: bool,
+) -> Result<(Vec<String>, Vec<video::Model>)> {
     video_source.log_refresh_video_start();
     let latest_row_at = video_source.get_latest_row_at().and_utc();
     let mut max_datetime = latest_row_at;
     let mut error = Ok(());
+    // 合集每次都全量拉取，记录所有视频的位置用于更新已有视频的 order
+    let mut orders = Vec::new();
     let mut video_streams = video_streams
         .enumerate()
         .take_while(|(idx, res)| {
@@ -140,6 +219,14 @@ pub async fn refresh_video_source<'a>(
                     if release_datetime > &max_datetime {
                         max_datetime = *release_datetime;
                     }
+                    if let VideoInfo::Collection {
+                        bvid,
+                        order: Some(order),
+                        ..
+                    } = v
+                    {
+                        orders.push((bvid.clone(), *order));
+                    }
                     futures::future::ready(video_source.should_take(*idx, release_datetime, &latest_row_at))
                 }
             }
@@ -147,25 +234,46 @@ pub async fn refresh_video_source<'a>(
         .filter_map(|(idx, res)| futures::future::ready(video_source.should_filter(idx, res, &latest_row_at)))
         .chunks(10);
     let mut count = 0;
-    let mut new_bvids = Vec::new();
+    let (mut new_bvids, mut invalidated_videos) = (Vec::new(), Vec::new());
     while let Some(videos_info) = video_streams.next().await {
         count += videos_info.len();
+        let bvids = videos_info.iter().map(|v| match v {
+            VideoInfo::Detail { bvid, .. } => bvid.clone(),
+            VideoInfo::Favorite { bvid, .. } => bvid.clone(),
+            VideoInfo::WatchLater { bvid, .. } => bvid.clone(),
+            VideoInfo::Collection { bvid, .. } => bvid.clone(),
+            VideoInfo::Submission { bvid, .. } => bvid.clone(),
+            VideoInfo::Dynamic { bvid, .. } => bvid.clone(),
+        });
+        if dry_run {
+            let bvids = bvids.collect::<Vec<_>>();
+            let existing_bvids = video::Entity::find()
+                .select_only()
+                .column(video::Column::Bvid)
+                .filter(video::Column::Bvid.is_in(bvids.clone()).and(video_source.filter_expr()))
+                .into_tuple::<String>()
+                .all(connection)
+                .await?;
+            for bvid in bvids.into_iter().filter(|bvid| !existing_bvids.contains(bvid)) {
+                info!(
+                    "[演练] {}中发现新视频 {}，将写入数据库",
+                    video_source.display_name(),
+                    bvid
+                );
+                new_bvids.push(bvid);
+            }
+            continue;
+        }
         // 保存新视频的 bvid 用于后续统计
-        new_bvids.extend(videos_info.iter().map(|v| {
-            match v {
-                VideoInfo::Detail { bvid, .. } => bvid.clone(),
-                VideoInfo::Favorite { bvid, .. } => bvid.clone(),
-                VideoInfo::WatchLater { bvid, .. } => bvid.clone(),
-                VideoInfo::Collection { bvid, .. } => bvid.clone(),
-                VideoInfo::Submission { bvid, .. } => bvid.clone(),
-                VideoInfo::Dynamic { bvid, .. } => bvid.clone(),
-            }
-        }));
-        create_videos(videos_info, video_source, connection).await?;
+        new_bvids.extend(bvids);
+        invalidated_videos.extend(create_videos(videos_info, video_source, connection).await?);
+    }
+    if !dry_run {
+        update_video_orders(&orders, video_source, connection).await?;
     }
     // 如果获取视频分页过程中发生了错误，直接在此处返回，不更新 latest_row_at
     error?;
-    if max_datetime != latest_row_at {
+    if !dry_run && max_datetime != latest_row_at {
         video_source
             .update_latest_row_at(max_datetime.naive_utc())
             .save(connection)
@@ -173,10 +281,19 @@ pub async fn refresh_video_source<'a>(
     }
     video_source.log_refresh_video_end(count);
     
-    Ok(new_bvids)
+    Ok((new_bvids, invalidated_videos))
 }
 
+/// 新发布的视频在该天数内会重新检查播放量与点赞数
+const STAT_RECHECK_DAYS: i64 = 7;
+
+/// 同一视频两次检查播放量与点赞数之间的最短间隔小时数
+const STAT_RECHECK_INTERVAL_HOURS: i64 = 6;
+
 /// 筛选出所有未获取到全部信息的视频，尝试补充其详细信息
+///
+/// 新发布的视频通常需要一段时间才能达到播放量与点赞数的下限，
+/// 发布时间在 STAT_RECHECK_DAYS 天内、仅因此跳过下载的视频会重新获取详情并检查，每个视频至多每 STAT_RECHECK_INTERVAL_HOURS 小时检查一次
 pub async fn fetch_video_details(
     bili_client: &BiliClient,
     video_source: &VideoSourceEnum,
@@ -184,50 +301,24 @@ pub async fn fetch_video_details(
     config: &Config,
 ) -> Result<()> {
     video_source.log_fetch_video_start();
-    let videos_model = filter_unfilled_videos(video_source.filter_expr(), connection).await?;
+    let mut videos_model = filter_unfilled_videos(video_source.filter_expr(), connection).await?;
+    let now = chrono::Utc::now().naive_utc();
+    videos_model.extend(
+        filter_below_stat_threshold_videos(
+            video_source.filter_expr(),
+            now - chrono::Duration::days(STAT_RECHECK_DAYS),
+            now - chrono::Duration::hours(STAT_RECHECK_INTERVAL_HOURS),
+            connection,
+        )
+        .await?,
+    );
     let semaphore = Semaphore::new(config.concurrent_limit.video);
     let semaphore_ref = &semaphore;
     let tasks = videos_model
         .into_iter()
         .map(|video_model| async move {
             let _permit = semaphore_ref.acquire().await.context("acquire semaphore failed")?;
-            let video = Video::new(bili_client, video_model.bvid.clone(), &config.credential);
-            let info: Result<_> = async { Ok((video.get_tags().await?, video.get_view_info().await?)) }.await;
-            match info {
-                Err(e) => {
-                    error!(
-                        "获取视频 {} - {} 的详细信息失败，错误为：{:#}",
-                        &video_model.bvid, &video_model.name, e
-                    );
-                    if let Some(BiliError::ErrorResponse(-404, _)) = e.downcast_ref::<BiliError>() {
-                        let mut video_active_model: bili_sync_entity::video::ActiveModel = video_model.into();
-                        video_active_model.valid = Set(false);
-                        video_active_model.save(connection).await?;
-                    }
-                }
-                Ok((tags, mut view_info)) => {
-                    let VideoInfo::Detail { pages, .. } = &mut view_info else {
-                        unreachable!()
-                    };
-                    // 构造 page model
-                    let pages = std::mem::take(pages);
-                    let pages = pages
-                        .into_iter()
-                        .map(|p| p.into_active_model(video_model.id))
-                        .collect::<Vec<page::ActiveModel>>();
-                    // 更新 video model 的各项有关属性
-                    let mut video_active_model = view_info.into_detail_model(video_model);
-                    video_source.set_relation_id(&mut video_active_model);
-                    video_active_model.single_page = Set(Some(pages.len() == 1));
-                    video_active_model.tags = Set(Some(tags.into()));
-                    video_active_model.should_download = Set(video_source.rule().evaluate(&video_active_model, &pages));
-                    let txn = connection.begin().await?;
-                    create_pages(pages, &txn).await?;
-                    video_active_model.save(&txn).await?;
-                    txn.commit().await?;
-                }
-            };
-            Ok::<_, anyhow::Error>(())
+            fetch_video_detail(bili_client, video_source, video_model, connection, config).await
         })
         .collect::<FuturesUnordered<_>>();
     tasks.try_collect::<Vec<_>>().await?;
@@ -235,28 +326,334 @@ pub async fn fetch_video_details(
     Ok(())
 }
 
-/// 下载所有未处理成功的视频
-pub async fn download_unprocessed_videos(
+/// 请求单个视频的详情信息与分页并写入数据库，成功补充详情时返回 true
+///
+/// 请求失败（包括触发风控）时仅记录错误并跳过该视频，下次扫描时会重新请求
+async fn fetch_video_detail(
     bili_client: &BiliClient,
     video_source: &VideoSourceEnum,
+    video_model: video::Model,
     connection: &DatabaseConnection,
-    template: &handlebars::Handlebars<'_>,
     config: &Config,
+) -> Result<bool> {
+    let video = Video::new(bili_client, video_model.bvid.clone(), &config.credential);
+    let info: Result<_> = async { Ok((video.get_tags().await?, video.get_view_info().await?)) }.await;
+    match info {
+        Err(e) => {
+            error!(
+                "获取视频 {} - {} 的详细信息失败，错误为：{:#}",
+                &video_model.bvid, &video_model.name, e
+            );
+            if let Some(BiliError::ErrorResponse(-404, _)) = e.downcast_ref::<BiliError>() {
+                notify_invalid_video(video_source, &video_model, bili_client, config);
+                let mut video_active_model: bili_sync_entity::video::ActiveModel = video_model.into();
+                video_active_model.valid = Set(false);
+                video_active_model.save(connection).await?;
+            }
+            Ok(false)
+        }
+        Ok((tags, view_info)) => {
+            let was_valid = video_model.valid;
+            let video_model = save_video_detail(
+                video_source,
+                video_model,
+                tags,
+                view_info,
+                &config.filter_option,
+                connection,
+            )
+            .await?;
+            // 视频详情表明视频已不可观看（如被删除或转为番剧等特殊视频）
+            if was_valid && !video_model.valid {
+                notify_invalid_video(video_source, &video_model, bili_client, config);
+            }
+            if video_model.is_paid_video {
+                info!("视频「{}」需要付费观看，已标记为收费视频并跳过下载", &video_model.name);
+                if config.notify_paid_videos {
+                    notify(
+                        config,
+                        bili_client,
+                        paid_video_message(video_source, &video_model, &config.message_templates),
+                    );
+                }
+            }
+            Ok(true)
+        }
+    }
+}
+
+/// 演练模式下请求视频的详情信息，输出视频是否会被下载以及需要下载的分页数，不写入数据库也不发送通知
+///
+/// 与实际下载时一致，获取详情失败时仅输出日志
+async fn plan_video_details(
+    bili_client: &BiliClient,
+    video_source: &VideoSourceEnum,
+    videos_model: Vec<video::Model>,
+    config: &Config,
+) {
+    futures::stream::iter(videos_model)
+        .for_each_concurrent(config.concurrent_limit.video, |video_model| async move {
+            let video = Video::new(bili_client, video_model.bvid.clone(), &config.credential);
+            let (tags, view_info) = match async { Ok((video.get_tags().await?, video.get_view_info().await?)) }.await {
+                Ok(info) => info,
+                Err(e) => {
+                    error!("[演练] 获取视频 {} 的详细信息失败，错误为：{:#}", &video_model.bvid, e);
+                    return;
+                }
+            };
+            let (video_active_model, pages) =
+                build_video_detail(video_source, video_model, tags, view_info, &config.filter_option);
+            let name = video_active_model.name.as_ref();
+            if *video_active_model.should_download.as_ref() {
+                info!("[演练] 视频「{}」将写入详情并下载 {} 个分页", name, pages.len());
+            } else {
+                info!("[演练] 视频「{}」将写入详情，但不满足下载条件，跳过下载", name);
+            }
+        })
+        .await;
+}
+
+/// 将视频详情与分页写入数据库，返回更新后的视频
+async fn save_video_detail(
+    video_source: &VideoSourceEnum,
+    video_model: video::Model,
+    tags: Vec<String>,
+    view_info: VideoInfo,
+    filter_option: &FilterOption,
+    connection: &DatabaseConnection,
+) -> Result<video::Model> {
+    let (video_active_model, pages) = build_video_detail(video_source, video_model, tags, view_info, filter_option);
+    let txn = connection.begin().await?;
+    create_pages(pages, &txn).await?;
+    let video_model = video_active_model.update(&txn).await?;
+    txn.commit().await?;
+    Ok(video_model)
+}
+
+/// 根据视频详情构造需要更新的视频与分页，不写入数据库
+///
+/// 视频详情表明需要付费观看时，与手动标记收费视频一致，设置 is_paid_video 并跳过下载
+/// 视频总时长超出设置的上下限、标题不满足关键词过滤条件或播放量与点赞数未达到下限时，与不满足过滤规则的视频一致，跳过下载
+/// 仅因播放量或点赞数未达到下限而跳过的视频会标记 below_stat_threshold，供之后的扫描重新检查，并记录本次检查的时间 stat_checked_at
+fn build_video_detail(
+    video_source: &VideoSourceEnum,
+    video_model: video::Model,
+    tags: Vec<String>,
+    mut view_info: VideoInfo,
+    filter_option: &FilterOption,
+) -> (video::ActiveModel, Vec<page::ActiveModel>) {
+    let requires_payment = view_info.requires_payment();
+    if view_info.requires_upower() {
+        info!("视频「{}」为充电专属视频，当前账号未充电，跳过下载", &video_model.name);
+    }
+    let VideoInfo::Detail { pages, title, stat, .. } = &mut view_info else {
+        unreachable!()
+    };
+    let duration = pages.iter().map(|p| p.duration).sum::<u32>();
+    let duration_allowed = filter_option.is_duration_allowed(duration);
+    if !duration_allowed {
+        info!(
+            "视频「{}」的总时长 {} 秒不在设置的时长范围内，跳过下载",
+            &video_model.name, duration
+        );
+    }
+    let title_allowed = filter_option.is_title_allowed(title);
+    if !title_allowed {
+        info!("视频「{}」的标题不满足关键词过滤条件，跳过下载", title);
+    }
+    let stat_allowed = filter_option.is_stat_allowed(stat.view, stat.like);
+    if !stat_allowed {
+        info!("视频「{}」的播放量或点赞数未达到设置的下限，跳过下载", title);
+    }
+    // 构造 page model
+    let pages = std::mem::take(pages);
+    let pages = pages
+        .into_iter()
+        .map(|p| p.into_active_model(video_model.id))
+        .collect::<Vec<page::ActiveModel>>();
+    // 更新 video model 的各项有关属性
+    let mut video_active_model = view_info.into_detail_model(video_model);
+    video_source.set_relation_id(&mut video_active_model);
+    video_active_model.single_page = Set(Some(pages.len() == 1));
+    video_active_model.tags = Set(Some(tags.into()));
+    let other_allowed = duration_allowed && title_allowed && video_source.rule().evaluate(&video_active_model, &pages);
+    video_active_model.should_download = Set(other_allowed && stat_allowed);
+    video_active_model.below_stat_threshold = Set(other_allowed && !stat_allowed && !requires_payment);
+    video_active_model.stat_checked_at = Set(Some(chrono::Utc::now().naive_utc()));
+    if requires_payment {
+        video_active_model.is_paid_video = Set(true);
+        video_active_model.should_download = Set(false);
+    }
+    (video_active_model, pages)
+}
+
+/// 扫描时识别到收费视频时发送的通知，每个视频单独发送
+fn paid_video_message(
+    video_source: &VideoSourceEnum,
+    video_model: &video::Model,
+    templates: &HashMap<String, String>,
+) -> NotificationPayload {
+    let source_name = video_source.display_name();
+    let message = render_message(
+        templates,
+        NotificationEvent::PaidVideo,
+        serde_json::json!({
+            "source_name": source_name,
+            "name": video_model.name,
+            "bvid": video_model.bvid,
+        }),
+        format!(
+            "💰 {} 中的视频需要付费观看，已跳过下载\n📹 {}（{}）",
+            source_name, video_model.name, video_model.bvid
+        ),
+    );
+    NotificationPayload {
+        event: NotificationEvent::PaidVideo,
+        message,
+        source_name: Some(source_name.into_owned()),
+        source_type: Some(video_source.key().source_type().to_owned()),
+        video_count: Some(1),
+        bvid: Some(video_model.bvid.clone()),
+        ..Default::default()
+    }
+}
+
+/// 开启了视频失效通知时，为从有效变为失效的视频发送通知
+fn notify_invalid_video(
+    video_source: &VideoSourceEnum,
+    video_model: &video::Model,
+    bili_client: &BiliClient,
+    config: &Config,
+) {
+    if config.notify_invalid_videos {
+        notify(
+            config,
+            bili_client,
+            invalid_video_message(video_source, video_model, &config.message_templates),
+        );
+    }
+}
+
+/// 视频失效时发送的通知，每个视频单独发送，内容包含 bvid 以免不同视频的通知被去重
+fn invalid_video_message(
+    video_source: &VideoSourceEnum,
+    video_model: &video::Model,
+    templates: &HashMap<String, String>,
+) -> NotificationPayload {
+    let source_name = video_source.display_name();
+    let message = render_message(
+        templates,
+        NotificationEvent::InvalidVideo,
+        serde_json::json!({
+            "source_name": source_name,
+            "name": video_model.name,
+            "bvid": video_model.bvid,
+        }),
+        format!(
+            "🚫 {} 中的视频已失效\n📹 {}（{}）",
+            source_name, video_model.name, video_model.bvid
+        ),
+    );
+    NotificationPayload {
+        event: NotificationEvent::InvalidVideo,
+        message,
+        source_name: Some(source_name.into_owned()),
+        source_type: Some(video_source.key().source_type().to_owned()),
+        video_count: Some(1),
+        bvid: Some(video_model.bvid.clone()),
+        ..Default::default()
+    }
+}
+
+/// 以流水线的方式处理视频：按照 prefetch 的并发数预取视频详情，已获取到详情的视频会立即送入下载队列
+pub async fn prefetch_and_download_videos(cx: DownloadContext<'_>) -> Result<()> {
+    let (bili_client, video_source, connection, config) = (cx.bili_client, cx.video_source, cx.connection, cx.config);
+    video_source.log_fetch_video_start();
+    // 需要在预取开始前获取此前已有详情但未下载完成的视频，避免与预取到的视频重复
+    let unhandled_videos_pages = filter_unhandled_video_pages(video_source.filter_expr(), connection).await?;
+    let videos_model = filter_unfilled_videos(video_source.filter_expr(), connection).await?;
+    let mut prefetch_error = Ok(());
+    let prefetched_videos_pages = prefetch_video_pages(
+        videos_model,
+        |video_model| fetch_video_detail(bili_client, video_source, video_model, connection, config),
+        cx,
+        &mut prefetch_error,
+    );
+    let videos_pages = futures::stream::iter(unhandled_videos_pages).chain(prefetched_videos_pages);
+    download_videos(videos_pages, cx).await?;
+    // 预取过程中出错时，已送入队列的视频仍会下载完成，随后再返回错误
+    prefetch_error?;
+    video_source.log_fetch_video_end();
+    Ok(())
+}
+
+/// 按照 prefetch 的并发数依次对视频执行 fetch 补充详情，将需要下载的视频与分页送入返回的流中
+///
+/// fetch 返回错误时终止流并将错误写入 error，收到停机信号后不再开始新的 fetch
+fn prefetch_video_pages<'a, Fut>(
+    videos_model: Vec<video::Model>,
+    fetch: impl Fn(video::Model) -> Fut + 'a,
+    cx: DownloadContext<'a>,
+    error: &'a mut Result<()>,
+) -> impl Stream<Item = (video::Model, Vec<page::Model>)> + 'a
+where
+    Fut: Future<Output = Result<bool>> + 'a,
+{
+    futures::stream::iter(videos_model)
+        .map(move |video_model| {
+            let video_id = video_model.id;
+            let fetched = (!cx.cancel.is_cancelled()).then(|| fetch(video_model));
+            async move {
+                let Some(fetched) = fetched else {
+                    return Ok(None);
+                };
+                if !fetched.await? {
+                    return Ok::<_, anyhow::Error>(None);
+                }
+                // 复用下载时的筛选条件，仅将需要下载的视频送入下载队列
+                let mut videos_pages =
+                    filter_unhandled_video_pages(video::Column::Id.eq(video_id), cx.connection).await?;
+                Ok(videos_pages.pop())
+            }
+        })
+        .buffer_unordered(cx.config.concurrent_limit.prefetch)
+        .take_while(move |res| {
+            if let Err(e) = res {
+                *error = Err(anyhow!("{:#}", e));
+            }
+            futures::future::ready(res.is_ok())
+        })
+        .filter_map(|res| futures::future::ready(res.ok().flatten()))
+}
+
+/// 下载所有未处理成功的视频
+///
+/// 演练模式下仅打印将要执行的操作与文件路径，不进行网络请求与磁盘写入，也不更新数据库中的下载状态
+pub async fn download_unprocessed_videos(cx: DownloadContext<'_>) -> Result<()> {
+    let unhandled_videos_pages = filter_unhandled_video_pages(cx.video_source.filter_expr(), cx.connection).await?;
+    download_videos(futures::stream::iter(unhandled_videos_pages), cx).await
+}
+
+/// 从视频流中依次取出视频与分页进行下载，直到视频流结束或触发风控
+async fn download_videos(
+    videos_pages: impl Stream<Item = (video::Model, Vec<page::Model>)>,
+    cx: DownloadContext<'_>,
 ) -> Result<()> {
+    let (video_source, connection, config, dry_run) = (cx.video_source, cx.connection, cx.config, cx.dry_run);
     video_source.log_download_video_start();
+    if dry_run {
+        info!("当前处于演练模式，以下仅输出将要执行的操作，不会实际下载或写入文件");
+    }
     let semaphore = Semaphore::new(config.concurrent_limit.video);
-    let downloader = Downloader::new(bili_client.client.clone());
-    let cx = DownloadContext::new(bili_client, video_source, template, connection, &downloader, config);
-    let unhandled_videos_pages = filter_unhandled_video_pages(video_source.filter_expr(), connection).await?;
     let mut assigned_upper = HashSet::new();
-    let tasks = unhandled_videos_pages
-        .into_iter()
+    let tasks = videos_pages
         .map(|(video_model, pages_model)| {
             let should_download_upper = !assigned_upper.contains(&video_model.upper_id);
             assigned_upper.insert(video_model.upper_id);
             download_video_pages(video_model, pages_model, &semaphore, should_download_upper, cx)
         })
-        .collect::<FuturesUnordered<_>>();
+        // 实际的并发数由 semaphore 控制，此处额外允许 prefetch 个视频等待下载，使预取可以领先于下载但不会无限堆积
+        .buffer_unordered(config.concurrent_limit.video + config.concurrent_limit.prefetch);
     let mut risk_control_related_error = None;
     let mut stream = tasks
         // 触发风控时设置 download_aborted 标记并终止流
@@ -274,55 +671,87 @@ pub async fn download_unprocessed_videos(
         // 将成功返回的 Model 按十个一组合并
         .chunks(10);
     while let Some(models) = stream.next().await {
+        // 演练模式下不修改数据库中的下载状态
+        if dry_run {
+            continue;
+        }
+        // 仅将所有子任务均成功的视频写入清单
+        let succeeded_ids = models
+            .iter()
+            .filter(|model| {
+                model.download_status.try_as_ref().is_some_and(|status| {
+                    <[u32; 5]>::from(VideoStatus::from(*status))
+                        .into_iter()
+                        .all(|s| s == STATUS_OK)
+                })
+            })
+            .filter_map(|model| model.id.try_as_ref().copied())
+            .collect::<Vec<_>>();
         update_videos_model(models, connection).await?;
+        if config.write_source_manifest
+            && let Err(e) = update_source_manifest(video_source.path(), &succeeded_ids, connection).await
+        {
+            warn!("更新 {} 的清单文件失败：{:#}", video_source.display_name(), e);
+        }
     }
     if let Some(e) = risk_control_related_error {
         bail!(e);
     }
+    // 停机时部分视频未开始处理，不视为该视频源处理完成
+    ensure!(!cx.cancel.is_cancelled(), DownloadCancelled);
     video_source.log_download_video_end();
     Ok(())
 }
 
 pub async fn download_video_pages(
-    video_model: video::Model,
+    mut video_model: video::Model,
     page_models: Vec<page::Model>,
     semaphore: &Semaphore,
     should_download_upper: bool,
     cx: DownloadContext<'_>,
 ) -> Result<video::ActiveModel> {
     let _permit = semaphore.acquire().await.context("acquire semaphore failed")?;
+    // 等待并发许可期间可能收到了停机信号，此时不再开始处理该视频
+    ensure!(!cx.cancel.is_cancelled(), DownloadCancelled);
+    // 按照当前的自动标签规则重新计算视频的标签，供 nfo 使用并随下载状态一起保存
+    let rule_tags = resolve_rule_tags(&cx.config.tag_rules, &video_model, &page_models);
+    video_model.rule_tags = (!rule_tags.is_empty()).then(|| rule_tags.into());
     let mut status = VideoStatus::from(video_model.download_status);
     let separate_status = status.should_run();
     // 未记录路径时填充，已经填充过路径时使用现有的
     let base_path = if !video_model.path.is_empty() {
         PathBuf::from(&video_model.path)
     } else {
-        cx.video_source.path().join(
-            cx.template
-                .path_safe_render("video", &video_format_args(&video_model, &cx.config.time_format))?,
-        )
+        cx.video_source.path().join(render_video_name(
+            cx.template,
+            cx.video_name_override,
+            &video_model,
+            &cx.config.time_format,
+        )?)
     };
-    let upper_id = video_model.upper_id.to_string();
-    let base_upper_path = cx
-        .config
-        .upper_path
-        .join(upper_id.chars().next().context("upper_id is empty")?.to_string())
-        .join(upper_id);
+    let base_upper_path = upper_base_path(&cx.config.upper_path, video_model.upper_id);
     let is_single_page = video_model.single_page.context("single_page is null")?;
     // 对于单页视频，page 的下载已经足够
     // 对于多页视频，page 下载仅包含了分集内容，需要额外补上视频的 poster 的 tvshow.nfo
+    // 单页视频按剧集生成 NFO 时，同样需要 tvshow.nfo 供媒体库识别
+    let has_tvshow_nfo = !is_single_page || cx.config.nfo_mode == NFOMode::TvShow;
+    let image_ext = cx.config.cover_format.extension();
+    let poster_should_run = separate_status[0] && !is_single_page && !cx.config.skip_option.no_poster;
+    // 从视频首帧截取封面时需要等待分页下载完成
+    let poster_from_frame = poster_from_frame(cx);
     let (res_1, res_2, res_3, res_4, res_5) = tokio::join!(
         // 下载视频封面
         fetch_video_poster(
-            separate_status[0] && !is_single_page && !cx.config.skip_option.no_poster,
+            poster_should_run && !poster_from_frame,
             &video_model,
-            base_path.join("poster.jpg"),
-            base_path.join("fanart.jpg"),
+            None,
+            base_path.join(format!("poster.{}", image_ext)),
+            base_path.join(format!("fanart.{}", image_ext)),
             cx
         ),
         // 生成视频信息的 nfo
         generate_video_nfo(
-            separate_status[1] && !is_single_page && !cx.config.skip_option.no_video_nfo,
+            separate_status[1] && has_tvshow_nfo && !cx.config.skip_option.no_video_nfo,
             &video_model,
             base_path.join("tvshow.nfo"),
             cx
@@ -331,7 +760,7 @@ pub async fn download_video_pages(
         fetch_upper_face(
             separate_status[2] && should_download_upper && !cx.config.skip_option.no_upper,
             &video_model,
-            base_upper_path.join("folder.jpg"),
+            base_upper_path.join(format!("folder.{}", image_ext)),
             cx
         ),
         // 生成 Up 主信息的 nfo
@@ -344,11 +773,38 @@ pub async fn download_video_pages(
         // 分发并执行分页下载的任务
         dispatch_download_page(separate_status[4], &video_model, page_models, &base_path, cx)
     );
-    let results = [res_1.into(), res_2.into(), res_3.into(), res_4.into(), res_5.into()];
-    status.update_status(&results);
+    // 分页均已处理结束，不再需要展示下载进度
+    ProgressRegistry::get().remove(video_model.id);
+    // 停机导致部分分页未下载时不更新视频的状态，已完成的分页状态已经写入，下次运行时会继续处理剩余的分页
+    if let Err(e) = &res_5
+        && e.is::<DownloadCancelled>()
+    {
+        bail!(DownloadCancelled);
+    }
+    let res_1 = if poster_should_run && poster_from_frame {
+        let frame_source = first_page_video_path(&video_model, cx).await;
+        fetch_video_poster(
+            true,
+            &video_model,
+            frame_source.as_deref(),
+            base_path.join(format!("poster.{}", image_ext)),
+            base_path.join(format!("fanart.{}", image_ext)),
+            cx,
+        )
+        .await
+    } else {
+        res_1
+    };
+    let results = [res_1, res_2, res_3, res_4, res_5].map(|res| cx.execution_status(res));
+    status.update_status_with_max_retries(&results, cx.config.max_auto_retries);
+    if !cx.dry_run {
+        record_video_task_events(&video_model, status, cx.connection).await;
+    }
     results
         .iter()
         .take(4)
+        // 演练模式下所有子任务均未实际执行，不输出执行结果
+        .filter(|_| !cx.dry_run)
         .zip(["封面", "详情", "作者头像", "作者详情"])
         .for_each(|(res, task_name)| match res {
             ExecutionStatus::Skipped => info!("处理视频「{}」{}已成功过，跳过", &video_model.name, task_name),
@@ -372,12 +828,48 @@ pub async fn download_video_pages(
             bail!(e);
         }
     }
+    remove_from_watch_later(VideoStatus::from(video_model.download_status), status, &video_model, cx).await;
+    // 视频在本轮全部下载完成时，在后台执行用户配置的下载后命令
+    if !cx.dry_run
+        && newly_fully_succeeded(VideoStatus::from(video_model.download_status), status)
+        && let Some(command) = PostDownloadCommand::from_config(cx.config, &base_path, &video_model)
+    {
+        command.spawn();
+    }
     let mut video_active_model: video::ActiveModel = video_model.into();
     video_active_model.download_status = Set(status.into());
     video_active_model.path = Set(base_path.to_string_lossy().to_string());
     Ok(video_active_model)
 }
 
+/// 渲染视频的文件夹名，视频源单独设置了 video_name 模板时使用该模板，否则使用全局模板
+pub fn render_video_name(
+    template: &handlebars::Handlebars<'_>,
+    video_name_override: Option<&str>,
+    video_model: &video::Model,
+    time_format: &str,
+) -> Result<String> {
+    let data = video_format_args(video_model, time_format);
+    let Some(video_name) = video_name_override else {
+        return template.path_safe_render("video", &data);
+    };
+    // 复制一份全局模板以保留注册的 helper，仅替换其中的 video 模板
+    let mut template = template.clone();
+    template.path_safe_register("video", video_name)?;
+    template.path_safe_render("video", &data)
+}
+
+/// 计算 UP 主信息的保存路径，按照 upper_id 的首个字符分桶
+/// 对于异常的 upper_id（如 0 或负数），统一放入 unknown 目录，避免因元数据问题阻塞整个视频的下载
+pub fn upper_base_path(upper_path: &Path, upper_id: i64) -> PathBuf {
+    let upper_id = upper_id.to_string();
+    let bucket = match upper_id.chars().next() {
+        Some(first_char) if first_char.is_ascii_digit() && first_char != '0' => first_char.to_string(),
+        _ => "unknown".to_owned(),
+    };
+    upper_path.join(bucket).join(upper_id)
+}
+
 /// 分发并执行分页下载任务，当且仅当所有分页成功下载或达到最大重试次数时返回 Ok，否则根据失败原因返回对应的错误
 pub async fn dispatch_download_page(
     should_run: bool,
@@ -394,7 +886,7 @@ pub async fn dispatch_download_page(
         .into_iter()
         .map(|page_model| download_page(video_model, page_model, &child_semaphore, base_path, cx))
         .collect::<FuturesUnordered<_>>();
-    let (mut risk_control_related_error, mut target_status) = (None, STATUS_OK);
+    let (mut risk_control_related_error, mut target_status, mut cancelled) = (None, STATUS_OK, false);
     let mut stream = tasks
         .take_while(|res| {
             match res {
@@ -415,6 +907,7 @@ pub async fn dispatch_download_page(
                     {
                         risk_control_related_error = Some(e.clone());
                     }
+                    cancelled |= e.is::<DownloadCancelled>();
                 }
             }
             // 仅在发生风控时终止流，其它情况继续执行
@@ -423,15 +916,78 @@ pub async fn dispatch_download_page(
         .filter_map(|res| futures::future::ready(res.ok()))
         .chunks(10);
     while let Some(models) = stream.next().await {
-        update_pages_model(models, cx.connection).await?;
+        if !cx.dry_run {
+            update_pages_model(models, cx.connection).await?;
+        }
     }
     if let Some(e) = risk_control_related_error {
         bail!(e);
     }
+    // 存在因停机而未开始的分页时，无法确定视频层“分页下载”任务的状态
+    ensure!(!cancelled, DownloadCancelled);
     // 视频中“分页下载”任务的状态始终与所有分页的最小状态一致
     Ok(ExecutionStatus::Fixed(target_status))
 }
 
+/// 分页各个子任务对应的文件路径
+pub struct PagePaths {
+    pub poster: PathBuf,
+    pub video: PathBuf,
+    pub nfo: PathBuf,
+    pub danmaku: PathBuf,
+    /// 仅单页视频需要单独写入 fanart，多页视频的 fanart 由视频层的封面任务写入
+    pub fanart: Option<PathBuf>,
+    pub subtitle: PathBuf,
+}
+
+/// 按照分页所在的目录与文件名计算各个子任务的文件路径，定时任务与手动重试、状态校准共用同一套规则
+pub fn page_paths(
+    video_model: &video::Model,
+    page_model: &page::Model,
+    base_path: &Path,
+    base_name: &str,
+    video_source: &VideoSourceEnum,
+    template: &handlebars::Handlebars<'_>,
+    config: &Config,
+) -> Result<PagePaths> {
+    let is_single_page = video_model.single_page.context("single_page is null")?;
+    let container = page_video_container(page_model.path.as_deref().map(Path::new), config.container);
+    let extension = page_video_extension(video_source.audio_only(), container);
+    let image_ext = config.cover_format.extension();
+    Ok(if is_single_page {
+        PagePaths {
+            poster: base_path.join(format!("{}-poster.{}", base_name, image_ext)),
+            video: base_path.join(format!("{}.{}", base_name, extension)),
+            nfo: base_path.join(format!("{}.nfo", base_name)),
+            danmaku: base_path.join(format!(
+                "{}.zh-CN.default.{}",
+                base_name,
+                config.danmaku_option.danmaku_format.extension()
+            )),
+            fanart: Some(base_path.join(format!("{}-fanart.{}", base_name, image_ext))),
+            subtitle: base_path.join(format!("{}.{}", base_name, config.subtitle_format.extension())),
+        }
+    } else {
+        let season = render_season(
+            template,
+            &page_format_args(video_model, page_model, &config.time_format),
+        )?;
+        let (season_path, episode_name) = page_episode_path(base_path, base_name, season, page_model.pid);
+        PagePaths {
+            poster: season_path.join(format!("{}-thumb.{}", &episode_name, image_ext)),
+            video: season_path.join(format!("{}.{}", &episode_name, extension)),
+            nfo: season_path.join(format!("{}.nfo", &episode_name)),
+            danmaku: season_path.join(format!(
+                "{}.zh-CN.default.{}",
+                &episode_name,
+                config.danmaku_option.danmaku_format.extension()
+            )),
+            fanart: None,
+            subtitle: season_path.join(format!("{}.{}", &episode_name, config.subtitle_format.extension())),
+        }
+    })
+}
+
 /// 下载某个分页，未发生风控且正常运行时返回 Ok(Page::ActiveModel)，其中 status 字段存储了新的下载状态，发生风控时返回 DownloadAbortError
 pub async fn download_page(
     video_model: &video::Model,
@@ -441,6 +997,8 @@ pub async fn download_page(
     cx: DownloadContext<'_>,
 ) -> Result<page::ActiveModel> {
     let _permit = semaphore.acquire().await.context("acquire semaphore failed")?;
+    // 等待并发许可期间可能收到了停机信号，此时不再开始下载该分页
+    ensure!(!cx.cancel.is_cancelled(), DownloadCancelled);
     let mut status = PageStatus::from(page_model.download_status);
     let separate_status = status.should_run();
     let is_single_page = video_model.single_page.context("single_page is null")?;
@@ -454,13 +1012,13 @@ pub async fn download_page(
             .context("invalid page path format")?
             .to_string_lossy();
         if is_single_page {
-            // 单页下的路径是 {base_path}/{base_name}.mp4
+            // 单页下的路径是 {base_path}/{base_name}.mp4，仅下载音频时扩展名为 m4a
             (
                 old_video_path.parent().context("invalid page path format")?,
-                old_video_filename.trim_end_matches(".mp4").to_string(),
+                trim_page_video_extension(&old_video_filename).to_string(),
             )
         } else {
-            // 多页下的路径是 {base_path}/Season 1/{base_name} - S01Exx.mp4
+            // 多页下的路径是 {base_path}/Season xx/{base_name} - SxxExx.mp4
             (
                 old_video_path
                     .parent()
@@ -482,36 +1040,23 @@ pub async fn download_page(
             )?,
         )
     };
-    let (poster_path, video_path, nfo_path, danmaku_path, fanart_path, subtitle_path) = if is_single_page {
-        (
-            base_path.join(format!("{}-poster.jpg", &base_name)),
-            base_path.join(format!("{}.mp4", &base_name)),
-            base_path.join(format!("{}.nfo", &base_name)),
-            base_path.join(format!("{}.zh-CN.default.ass", &base_name)),
-            Some(base_path.join(format!("{}-fanart.jpg", &base_name))),
-            base_path.join(format!("{}.srt", &base_name)),
-        )
-    } else {
-        (
-            base_path
-                .join("Season 1")
-                .join(format!("{} - S01E{:0>2}-thumb.jpg", &base_name, page_model.pid)),
-            base_path
-                .join("Season 1")
-                .join(format!("{} - S01E{:0>2}.mp4", &base_name, page_model.pid)),
-            base_path
-                .join("Season 1")
-                .join(format!("{} - S01E{:0>2}.nfo", &base_name, page_model.pid)),
-            base_path
-                .join("Season 1")
-                .join(format!("{} - S01E{:0>2}.zh-CN.default.ass", &base_name, page_model.pid)),
-            // 对于多页视频，会在上一步 fetch_video_poster 中获取剧集的 fanart，无需在此处下载单集的
-            None,
-            base_path
-                .join("Season 1")
-                .join(format!("{} - S01E{:0>2}.srt", &base_name, page_model.pid)),
-        )
-    };
+    let container = page_video_container(page_model.path.as_deref().map(Path::new), cx.config.container);
+    let PagePaths {
+        poster: poster_path,
+        video: video_path,
+        nfo: nfo_path,
+        danmaku: danmaku_path,
+        fanart: fanart_path,
+        subtitle: subtitle_path,
+    } = page_paths(
+        video_model,
+        &page_model,
+        base_path,
+        &base_name,
+        cx.video_source,
+        cx.template,
+        cx.config,
+    )?;
     let dimension = match (page_model.width, page_model.height) {
         (Some(width), Some(height)) => Some(Dimension {
             width,
@@ -522,30 +1067,37 @@ pub async fn download_page(
     };
     let page_info = PageInfo {
         cid: page_model.cid,
+        page: page_model.pid,
         duration: page_model.duration,
         dimension,
         ..Default::default()
     };
+    let poster_should_run = separate_status[0] && !cx.config.skip_option.no_poster;
+    // 从视频首帧截取封面时需要等待视频下载完成
+    let poster_from_frame = poster_from_frame(cx);
+    let embed_chapters = container == VideoContainer::Mkv && !cx.video_source.audio_only();
+    let nfo_should_run = separate_status[2] && !cx.config.skip_option.no_video_nfo;
+    // 看点章节仅在需要写入 nfo 或封装进 mkv 时获取
+    let chapters = if nfo_should_run || (separate_status[1] && embed_chapters) {
+        fetch_page_chapters(video_model, &page_info, cx).await
+    } else {
+        Vec::new()
+    };
     let (res_1, res_2, res_3, res_4, res_5) = tokio::join!(
         // 下载分页封面
         fetch_page_poster(
-            separate_status[0] && !cx.config.skip_option.no_poster,
+            poster_should_run && !poster_from_frame,
             video_model,
             &page_model,
-            poster_path,
-            fanart_path,
+            None,
+            poster_path.clone(),
+            fanart_path.clone(),
             cx
         ),
         // 下载分页视频
         fetch_page_video(separate_status[1], video_model, &page_info, &video_path, cx),
         // 生成分页视频信息的 nfo
-        generate_page_nfo(
-            separate_status[2] && !cx.config.skip_option.no_video_nfo,
-            video_model,
-            &page_model,
-            nfo_path,
-            cx,
-        ),
+        generate_page_nfo(nfo_should_run, video_model, &page_model, &chapters, nfo_path, cx),
         // 下载分页弹幕
         fetch_page_danmaku(
             separate_status[3] && !cx.config.skip_option.no_danmaku,
@@ -563,10 +1115,56 @@ pub async fn download_page(
             cx
         )
     );
-    let results = [res_1.into(), res_2.into(), res_3.into(), res_4.into(), res_5.into()];
-    status.update_status(&results);
+    let (res_2, video_metrics) = match res_2 {
+        Ok((status, metrics)) => (Ok(status), metrics),
+        Err(e) => (Err(e), None),
+    };
+    let res_1 = if poster_should_run && poster_from_frame {
+        fetch_page_poster(
+            true,
+            video_model,
+            &page_model,
+            Some(&video_path),
+            poster_path,
+            fanart_path,
+            cx,
+        )
+        .await
+    } else {
+        res_1
+    };
+    let results = [res_1, res_2, res_3, res_4, res_5].map(|res| cx.execution_status(res));
+    // 去重复用的视频文件已在原视频源中完成封装，原地封装会用新文件替换链接，使去重失效
+    // 未启用去重时不检查，避免用户自行创建的链接导致跳过封装
+    let linked = cx.config.dedup_strategy != DedupStrategy::None && is_linked_file(&video_path).await;
+    // 仅下载音频时没有可供封装字幕的视频文件
+    if cx.config.embed_subtitles && !cx.video_source.audio_only() && !linked {
+        let mux = || embed_page_subtitles(&video_path, &subtitle_path, cx.config.remove_embedded_subtitle_files);
+        if let Err(e) = embed_subtitles_if_ready(&results[1], &results[4], mux).await {
+            warn!(
+                "处理视频「{}」第 {} 页字幕封装失败，保留外挂字幕文件：{:#}",
+                &video_model.name, page_model.pid, e
+            );
+        }
+    }
+    if embed_chapters
+        && !linked
+        && !chapters.is_empty()
+        && matches!(results[1], ExecutionStatus::Succeeded)
+        && let Err(e) = embed_page_chapters(&video_path, &chapters).await
+    {
+        warn!(
+            "处理视频「{}」第 {} 页章节封装失败：{:#}",
+            &video_model.name, page_model.pid, e
+        );
+    }
+    status.update_status_with_max_retries(&results, cx.config.max_auto_retries);
+    if !cx.dry_run {
+        record_page_task_events(&video_model, &page_model, status, cx.connection).await;
+    }
     results
         .iter()
+        .filter(|_| !cx.dry_run)
         .zip(["封面", "视频", "详情", "弹幕", "字幕"])
         .for_each(|(res, task_name)| match res {
             ExecutionStatus::Skipped => info!(
@@ -600,13 +1198,58 @@ pub async fn download_page(
     let mut page_active_model: page::ActiveModel = page_model.into();
     page_active_model.download_status = Set(status.into());
     page_active_model.path = Set(Some(video_path.to_string_lossy().to_string()));
+    if let Some(metrics) = video_metrics {
+        metrics.set_to(&mut page_active_model);
+    }
     Ok(page_active_model)
 }
 
+/// 分页视频文件的扩展名，仅下载音频时保存为 m4a，否则由配置的封装格式决定
+pub fn page_video_extension(audio_only: bool, container: VideoContainer) -> &'static str {
+    if audio_only { "m4a" } else { container.extension() }
+}
+
+/// 分页视频文件使用的封装格式，已记录路径的分页沿用路径扩展名对应的封装格式，使修改封装格式仅对新下载的分页生效
+pub fn page_video_container(page_path: Option<&Path>, container: VideoContainer) -> VideoContainer {
+    match page_path.and_then(Path::extension).and_then(|ext| ext.to_str()) {
+        Some("mp4") => VideoContainer::Mp4,
+        Some("mkv") => VideoContainer::Mkv,
+        _ => container,
+    }
+}
+
+/// 多页视频中分页所在的季目录与文件名（不含扩展名），形如 {base_path}/Season x/{base_name} - SxxExx
+pub fn page_episode_path(base_path: &Path, base_name: &str, season: u32, pid: i32) -> (PathBuf, String) {
+    (
+        base_path.join(format!("Season {}", season)),
+        format!("{} - S{:0>2}E{:0>2}", base_name, season, pid),
+    )
+}
+
+/// 分页在剧集 NFO 中的集数，默认使用分页序号
+/// 开启按合集顺序编号时，合集中的单页视频使用其在合集中的位置，使多次扫描得到的剧集保持连续
+/// 多页视频的各个分页位于同一视频目录下，仍然使用分页序号编号
+fn page_episode_number(video_model: &video::Model, page_model: &page::Model, by_collection_order: bool) -> i32 {
+    match video_model.order {
+        Some(order) if by_collection_order && video_model.single_page == Some(true) => order,
+        _ => page_model.pid,
+    }
+}
+
+/// 去除分页视频文件名的扩展名，兼容切换封装格式或开启仅下载音频前后生成的文件名
+pub fn trim_page_video_extension(filename: &str) -> &str {
+    filename
+        .strip_suffix(".mp4")
+        .or_else(|| filename.strip_suffix(".mkv"))
+        .or_else(|| filename.strip_suffix(".m4a"))
+        .unwrap_or(filename)
+}
+
 pub async fn fetch_page_poster(
     should_run: bool,
     video_model: &video::Model,
     page_model: &page::Model,
+    frame_source: Option<&Path>,
     poster_path: PathBuf,
     fanart_path: Option<PathBuf>,
     cx: DownloadContext<'_>,
@@ -614,6 +1257,15 @@ pub async fn fetch_page_poster(
     if !should_run {
         return Ok(ExecutionStatus::Skipped);
     }
+    if cx.dry_run {
+        info!(
+            "[演练] 处理视频「{}」第 {} 页封面，将写入 {}",
+            &video_model.name,
+            page_model.pid,
+            poster_path.display()
+        );
+        return Ok(ExecutionStatus::Skipped);
+    }
     let single_page = video_model.single_page.context("single_page is null")?;
     let url = if single_page {
         // 单页视频直接用视频的封面
@@ -625,9 +1277,12 @@ pub async fn fetch_page_poster(
             None => video_model.cover.as_str(),
         }
     };
-    cx.downloader
-        .fetch(url, &poster_path, &cx.config.concurrent_limit.download)
-        .await?;
+    write_poster(
+        cx.config.poster_source,
+        frame_source.map(|video_path| extract_first_frame(video_path, &poster_path, cx)),
+        fetch_image(url, &poster_path, cx),
+    )
+    .await?;
     if let Some(fanart_path) = fanart_path {
     // 确保 fanart_path 的父目录存在（虽然理论上应该已经存在，但为了确保权限正确）
     if let Some(parent) = fanart_path.parent() {
@@ -659,58 +1314,298 @@ pub async fn fetch_page_poster(
     Ok(ExecutionStatus::Succeeded)
 }
 
+/// 是否需要在视频下载完成后从首帧截取封面，仅下载音频时没有可供截取的画面
+pub fn poster_from_frame(cx: DownloadContext<'_>) -> bool {
+    cx.config.poster_source == PosterSource::FirstFrame && !cx.video_source.audio_only()
+}
+
+/// 按照配置的封面来源写入封面，从视频首帧截取失败或没有可用的视频文件时回退为下载视频封面，返回实际使用的来源
+async fn write_poster(
+    poster_source: PosterSource,
+    extract_frame: Option<impl Future<Output = Result<()>>>,
+    download_cover: impl Future<Output = Result<()>>,
+) -> Result<PosterSource> {
+    if poster_source == PosterSource::FirstFrame
+        && let Some(extract_frame) = extract_frame
+    {
+        match extract_frame.await {
+            Ok(()) => return Ok(PosterSource::FirstFrame),
+            Err(e) => warn!("从视频首帧截取封面失败，回退为视频封面：{:#}", e),
+        }
+    }
+    download_cover.await?;
+    Ok(PosterSource::Cover)
+}
+
+/// 下载图片并按照配置的格式保存，非 jpg 格式时先下载原图再通过 ffmpeg 转换
+async fn fetch_image(url: &str, path: &Path, cx: DownloadContext<'_>) -> Result<()> {
+    if cx.config.cover_format == CoverFormat::Jpg {
+        return cx
+            .downloader
+            .fetch(url, path, &cx.config.concurrent_limit.download)
+            .await;
+    }
+    let source_path = path.with_extension("source.jpg");
+    cx.downloader
+        .fetch(url, &source_path, &cx.config.concurrent_limit.download)
+        .await?;
+    let res = encode_image(&source_path, path, cx.config.cover_format, cx.config.cover_quality).await;
+    let _ = fs::remove_file(&source_path).await;
+    res
+}
+
+/// 截取视频的第一帧，按照配置的格式保存为封面
+async fn extract_first_frame(video_path: &Path, poster_path: &Path, cx: DownloadContext<'_>) -> Result<()> {
+    encode_image(video_path, poster_path, cx.config.cover_format, cx.config.cover_quality).await
+}
+
+/// 使用 ffmpeg 将输入的第一帧编码为指定格式的图片，输入既可以是视频也可以是图片
+async fn encode_image(input_path: &Path, output_path: &Path, format: CoverFormat, quality: u8) -> Result<()> {
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    let output = Command::new("ffmpeg")
+        .arg("-i")
+        .arg(input_path)
+        .args(["-frames:v", "1"])
+        .args(format.ffmpeg_args(quality))
+        .arg("-y")
+        .arg(output_path)
+        .output()
+        .await
+        .context("failed to run ffmpeg")?;
+    if !output.status.success() {
+        let _ = fs::remove_file(output_path).await;
+        bail!("ffmpeg error: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    Ok(())
+}
+
+/// 获取视频第一个分页的视频文件路径，用于截取多页视频的封面
+pub async fn first_page_video_path(video_model: &video::Model, cx: DownloadContext<'_>) -> Option<PathBuf> {
+    let first_page = page::Entity::find()
+        .filter(page::Column::VideoId.eq(video_model.id))
+        .order_by_asc(page::Column::Pid)
+        .one(cx.connection)
+        .await;
+    match first_page {
+        Ok(page) => page
+            .and_then(|page| page.path)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from),
+        Err(e) => {
+            warn!("获取视频「{}」的首个分页失败：{:#}", &video_model.name, e);
+            None
+        }
+    }
+}
+
+/// 分页视频的下载指标，用于排查下载缓慢的 CDN 节点
+#[derive(Debug, Clone, Copy)]
+pub struct DownloadMetrics {
+    /// 最终写入磁盘的文件大小（字节）
+    pub file_size: u64,
+    /// 从开始下载到文件写入完成的耗时
+    pub duration: Duration,
+}
+
+impl DownloadMetrics {
+    /// 将下载指标写入分页记录
+    pub fn set_to(self, page_active_model: &mut page::ActiveModel) {
+        page_active_model.file_size = Set(Some(self.file_size as i64));
+        page_active_model.download_duration_ms = Set(Some(self.duration.as_millis() as i64));
+    }
+}
+
+/// 下载分页视频，实际发生下载时一并返回下载指标
 pub async fn fetch_page_video(
     should_run: bool,
     video_model: &video::Model,
     page_info: &PageInfo,
     page_path: &Path,
     cx: DownloadContext<'_>,
-) -> Result<ExecutionStatus> {
+) -> Result<(ExecutionStatus, Option<DownloadMetrics>)> {
     if !should_run {
-        return Ok(ExecutionStatus::Skipped);
+        return Ok((ExecutionStatus::Skipped, None));
+    }
+    if cx.dry_run {
+        info!(
+            "[演练] 处理视频「{}」第 {} 页视频，将写入 {}",
+            &video_model.name,
+            page_info.page,
+            page_path.display()
+        );
+        return Ok((ExecutionStatus::Skipped, None));
+    }
+    if let Some(existing_path) = find_downloaded_page_video(video_model, page_info.cid, page_path, cx).await? {
+        let metrics = measure_download(
+            page_path,
+            link_page_video(cx.config.dedup_strategy, &existing_path, page_path),
+        )
+        .await?;
+        info!(
+            "视频「{}」第 {} 页已由其它视频源下载至 {}，直接复用",
+            &video_model.name,
+            page_info.page,
+            existing_path.display()
+        );
+        return Ok((ExecutionStatus::Succeeded, Some(metrics)));
     }
     let bili_video = Video::new(cx.bili_client, video_model.bvid.clone(), &cx.config.credential);
     let streams = bili_video
         .get_page_analyzer(page_info)
         .await?
         .best_stream(&cx.config.filter_option)?;
-    match streams {
-        BestStream::Mixed(mix_stream) => {
-            cx.downloader
+    // 视频流与音频流的下载字节数计入视频的下载进度，供前端展示进度条
+    let downloader = cx.downloader.with_progress(video_model.id, page_info.cid);
+    let metrics = measure_download(page_path, async {
+        if cx.video_source.audio_only() {
+            // 仅下载音频时直接保存 dash 音频流，无需与视频流合并
+            let audio_stream = streams
+                .into_audio()
+                .context("no separate audio stream available for audio only mode")?;
+            downloader
                 .multi_fetch(
-                    &mix_stream.urls(cx.config.cdn_sorting),
+                    &audio_stream.urls(cx.config.cdn_sorting),
                     page_path,
                     &cx.config.concurrent_limit.download,
                 )
-                .await?
+                .await?;
+            if let Some(encoder) = audio_normalize_encoder(cx.config, audio_stream.audio_quality())
+                && let Err(e) =
+                    normalize_audio_loudness(page_path, cx.config.audio_normalize_target_lufs, encoder).await
+            {
+                warn!(
+                    "视频「{}」第 {} 页响度标准化失败，保留原始音频：{:#}",
+                    &video_model.name, page_info.page, e
+                );
+            }
+            return Ok(());
         }
-        BestStream::VideoAudio {
-            video: video_stream,
-            audio: None,
-        } => {
-            cx.downloader
-                .multi_fetch(
-                    &video_stream.urls(cx.config.cdn_sorting),
-                    page_path,
-                    &cx.config.concurrent_limit.download,
-                )
-                .await?
+        let (video_stream, audio_stream) = match streams {
+            BestStream::Mixed(mix_stream) => (mix_stream, None),
+            BestStream::VideoAudio {
+                video: video_stream,
+                audio: audio_stream,
+            } => (video_stream, audio_stream),
+        };
+        let video_urls = video_stream.urls(cx.config.cdn_sorting);
+        match (audio_stream, page_video_container(Some(page_path), cx.config.container)) {
+            // 单个流且封装格式为 mp4 时直接保存，无需经过 ffmpeg
+            (None, VideoContainer::Mp4) => {
+                downloader
+                    .multi_fetch(&video_urls, page_path, &cx.config.concurrent_limit.download)
+                    .await
+            }
+            (audio_stream, container) => {
+                downloader
+                    .multi_fetch_and_merge(
+                        &video_urls,
+                        audio_stream.as_ref().map(|s| s.urls(cx.config.cdn_sorting)).as_deref(),
+                        page_path,
+                        container,
+                        &cx.config.concurrent_limit.download,
+                    )
+                    .await
+            }
         }
-        BestStream::VideoAudio {
-            video: video_stream,
-            audio: Some(audio_stream),
-        } => {
-            cx.downloader
-                .multi_fetch_and_merge(
-                    &video_stream.urls(cx.config.cdn_sorting),
-                    &audio_stream.urls(cx.config.cdn_sorting),
-                    page_path,
-                    &cx.config.concurrent_limit.download,
-                )
-                .await?
+    })
+    .await?;
+    Ok((ExecutionStatus::Succeeded, Some(metrics)))
+}
+
+/// 查找其它视频源中已下载完成的同一分页视频，未开启去重或找不到可复用的文件时返回 None
+async fn find_downloaded_page_video(
+    video_model: &video::Model,
+    cid: i64,
+    page_path: &Path,
+    cx: DownloadContext<'_>,
+) -> Result<Option<PathBuf>> {
+    if cx.config.dedup_strategy == DedupStrategy::None {
+        return Ok(None);
+    }
+    let pages = page::Entity::find()
+        .inner_join(video::Entity)
+        .filter(video::Column::Bvid.eq(&video_model.bvid))
+        .filter(page::Column::Cid.eq(cid))
+        .filter(page::Column::VideoId.ne(video_model.id))
+        .filter(page::Column::Path.is_not_null())
+        .all(cx.connection)
+        .await?;
+    for page in pages {
+        let statuses: [u32; 5] = PageStatus::from(page.download_status).into();
+        let Some(path) = page.path.map(PathBuf::from) else {
+            continue;
+        };
+        // 仅复用视频下载成功且封装格式一致的文件
+        if statuses[1] != STATUS_OK || path == page_path || path.extension() != page_path.extension() {
+            continue;
+        }
+        if fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(Some(path));
         }
     }
-    Ok(ExecutionStatus::Succeeded)
+    Ok(None)
+}
+
+/// 按去重方式将已下载的文件链接到目标路径，目标路径已存在时会被替换
+async fn link_page_video(strategy: DedupStrategy, source: &Path, target: &Path) -> Result<()> {
+    // 已有文件本身可能也是链接，统一链接到最终的真实文件
+    let source = fs::canonicalize(source)
+        .await
+        .with_context(|| format!("failed to resolve {}", source.display()))?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    if fs::symlink_metadata(target).await.is_ok() {
+        fs::remove_file(target).await?;
+    }
+    match strategy {
+        DedupStrategy::Hardlink => match fs::hard_link(&source, target).await {
+            // 硬链接无法跨文件系统创建，此时退化为复制
+            Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+                fs::copy(&source, target).await?;
+            }
+            res => res?,
+        },
+        DedupStrategy::Symlink => {
+            #[cfg(unix)]
+            fs::symlink(&source, target).await?;
+            #[cfg(windows)]
+            fs::symlink_file(&source, target).await?;
+        }
+        DedupStrategy::None => bail!("dedup strategy is not enabled"),
+    }
+    Ok(())
+}
+
+/// 文件是否为软链接或存在多个硬链接
+async fn is_linked_file(path: &Path) -> bool {
+    let Ok(metadata) = fs::symlink_metadata(path).await else {
+        return false;
+    };
+    if metadata.file_type().is_symlink() {
+        return true;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.nlink() > 1
+    }
+    #[cfg(not(unix))]
+    false
+}
+
+/// 执行下载，并在完成后统计耗时与最终文件的大小
+async fn measure_download(path: &Path, download: impl Future<Output = Result<()>>) -> Result<DownloadMetrics> {
+    let start = Instant::now();
+    download.await?;
+    let duration = start.elapsed();
+    let file_size = fs::metadata(path)
+        .await
+        .with_context(|| format!("failed to read metadata of {}", path.display()))?
+        .len();
+    Ok(DownloadMetrics { file_size, duration })
 }
 
 pub async fn fetch_page_danmaku(
@@ -723,6 +1618,15 @@ pub async fn fetch_page_danmaku(
     if !should_run {
         return Ok(ExecutionStatus::Skipped);
     }
+    if cx.dry_run {
+        info!(
+            "[演练] 处理视频「{}」第 {} 页弹幕，将写入 {}",
+            &video_model.name,
+            page_info.page,
+            danmaku_path.display()
+        );
+        return Ok(ExecutionStatus::Skipped);
+    }
     let bili_video = Video::new(cx.bili_client, video_model.bvid.clone(), &cx.config.credential);
     bili_video
         .get_danmaku_writer(page_info)
@@ -742,42 +1646,322 @@ pub async fn fetch_page_subtitle(
     if !should_run {
         return Ok(ExecutionStatus::Skipped);
     }
+    if cx.dry_run {
+        info!(
+            "[演练] 处理视频「{}」第 {} 页字幕，将写入 {}",
+            &video_model.name,
+            page_info.page,
+            subtitle_path.display()
+        );
+        return Ok(ExecutionStatus::Skipped);
+    }
     let bili_video = Video::new(cx.bili_client, video_model.bvid.clone(), &cx.config.credential);
-    let subtitles = bili_video.get_subtitles(page_info).await?;
+    let subtitles = bili_video
+        .get_subtitles(page_info, &cx.config.subtitle_languages)
+        .await?;
+    write_page_subtitles(subtitles, subtitle_path, cx.config.subtitle_format).await?;
+    Ok(ExecutionStatus::Succeeded)
+}
+
+/// 将各语言的字幕分别写入 {subtitle_path 去除扩展名}.{lan}.{ext}
+async fn write_page_subtitles(subtitles: Vec<SubTitle>, subtitle_path: &Path, format: SubtitleFormat) -> Result<()> {
     let tasks = subtitles
         .into_iter()
         .map(|subtitle| async move {
-            let path = subtitle_path.with_extension(format!("{}.srt", subtitle.lan));
-            tokio::fs::write(path, subtitle.body.to_string()).await
+            let path = subtitle_path.with_extension(format!("{}.{}", subtitle.lan, format.extension()));
+            tokio::fs::write(path, subtitle.body.render(format)).await
         })
         .collect::<FuturesUnordered<_>>();
     tasks.try_collect::<Vec<()>>().await?;
-    Ok(ExecutionStatus::Succeeded)
+    Ok(())
+}
+
+/// 视频的全部子任务是否在本轮变为成功，部分失败或之前已经成功过的视频返回 false
+pub(crate) fn newly_fully_succeeded(old_status: VideoStatus, new_status: VideoStatus) -> bool {
+    let fully_succeeded = |status: VideoStatus| <[u32; 5]>::from(status).into_iter().all(|s| s == STATUS_OK);
+    !fully_succeeded(old_status) && fully_succeeded(new_status)
+}
+
+/// 稍后再看中的视频全部子任务在本轮变为成功时，按配置将其从稍后再看中移除，移除失败仅记录日志
+pub(crate) async fn remove_from_watch_later(
+    old_status: VideoStatus,
+    new_status: VideoStatus,
+    video_model: &video::Model,
+    cx: DownloadContext<'_>,
+) {
+    if cx.dry_run || !cx.config.watch_later_auto_remove || !matches!(cx.video_source, VideoSourceEnum::WatchLater(_)) {
+        return;
+    }
+    if let Err(e) = remove_if_fully_succeeded(old_status, new_status, || {
+        WatchLater::new(cx.bili_client, &cx.config.credential).remove_video(&video_model.bvid)
+    })
+    .await
+    {
+        warn!("将视频「{}」从稍后再看中移除失败：{:#}", &video_model.name, e);
+    }
+}
+
+/// 仅当视频的全部子任务在本轮变为成功时执行移除，部分失败或之前已经成功过的视频不会重复移除，返回是否执行了移除
+async fn remove_if_fully_succeeded<Fut>(
+    old_status: VideoStatus,
+    new_status: VideoStatus,
+    remove: impl FnOnce() -> Fut,
+) -> Result<bool>
+where
+    Fut: Future<Output = Result<()>>,
+{
+    if !newly_fully_succeeded(old_status, new_status) {
+        return Ok(false);
+    }
+    remove().await?;
+    Ok(true)
+}
+
+/// 仅当视频与字幕均在本轮下载成功时执行字幕封装，返回是否执行了封装
+async fn embed_subtitles_if_ready<Fut>(
+    video_status: &ExecutionStatus,
+    subtitle_status: &ExecutionStatus,
+    mux: impl FnOnce() -> Fut,
+) -> Result<bool>
+where
+    Fut: Future<Output = Result<()>>,
+{
+    if !matches!(
+        (video_status, subtitle_status),
+        (ExecutionStatus::Succeeded, ExecutionStatus::Succeeded)
+    ) {
+        return Ok(false);
+    }
+    mux().await?;
+    Ok(true)
+}
+
+/// 弹幕文件名中位于语言位置的部分，即 {base_name}.zh-CN.default.{ext}
+const DANMAKU_FILE_LANGUAGE: &str = "zh-CN.default";
+
+/// 列出 fetch_page_subtitle 写入的 {subtitle_path 去除扩展名}.{lan}.{ext} 字幕文件，按语言排序
+/// 弹幕保存为 srt 格式时与字幕的文件名格式相同，需要排除
+pub async fn page_subtitle_files(subtitle_path: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let (Some(dir), Some(stem)) = (subtitle_path.parent(), subtitle_path.file_stem()) else {
+        bail!("invalid subtitle path: {}", subtitle_path.display());
+    };
+    let prefix = format!("{}.", stem.to_string_lossy());
+    let suffix = format!(".{}", subtitle_path.extension().unwrap_or_default().to_string_lossy());
+    let mut subtitles = Vec::new();
+    let mut entries = fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if let Some(lan) = file_name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(&suffix))
+            .filter(|lan| !lan.is_empty() && *lan != DANMAKU_FILE_LANGUAGE)
+        {
+            subtitles.push((lan.to_owned(), entry.path()));
+        }
+    }
+    subtitles.sort();
+    Ok(subtitles)
+}
+
+/// 将 fetch_page_subtitle 写入的字幕封装进视频文件
+async fn embed_page_subtitles(video_path: &Path, subtitle_path: &Path, remove_subtitle_files: bool) -> Result<()> {
+    let subtitles = page_subtitle_files(subtitle_path).await?;
+    if subtitles.is_empty() {
+        return Ok(());
+    }
+    let extension = video_path.extension().unwrap_or_default().to_string_lossy();
+    // mkv 支持直接封装各种格式的字幕，mp4 仅支持 mov_text 格式的字幕轨道
+    let subtitle_codec = if extension == "mkv" { "copy" } else { "mov_text" };
+    let output_path = video_path.with_extension(format!("embed.{}", extension));
+    let mut command = Command::new("ffmpeg");
+    command.arg("-i").arg(video_path);
+    for (_, path) in &subtitles {
+        command.arg("-i").arg(path);
+    }
+    command.args(["-map", "0:v?", "-map", "0:a?"]);
+    for (index, (lan, _)) in subtitles.iter().enumerate() {
+        command
+            .arg("-map")
+            .arg(format!("{}:s", index + 1))
+            .arg(format!("-metadata:s:s:{}", index))
+            .arg(format!("language={}", lan));
+    }
+    let output = command
+        .args(["-c", "copy", "-c:s", subtitle_codec, "-y"])
+        .arg(&output_path)
+        .output()
+        .await
+        .context("failed to run ffmpeg")?;
+    if !output.status.success() {
+        let _ = fs::remove_file(&output_path).await;
+        bail!("ffmpeg error: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    fs::rename(&output_path, video_path).await?;
+    if remove_subtitle_files {
+        for (_, path) in &subtitles {
+            fs::remove_file(path).await?;
+        }
+    }
+    Ok(())
+}
+
+/// 获取分页的看点章节，视频没有看点或获取失败时返回空列表，不影响其它任务
+pub async fn fetch_page_chapters(
+    video_model: &video::Model,
+    page_info: &PageInfo,
+    cx: DownloadContext<'_>,
+) -> Vec<Chapter> {
+    if cx.dry_run {
+        return Vec::new();
+    }
+    let bili_video = Video::new(cx.bili_client, video_model.bvid.clone(), &cx.config.credential);
+    match bili_video.get_chapters(page_info).await {
+        Ok(chapters) => chapters,
+        Err(e) => {
+            warn!(
+                "获取视频「{}」第 {} 页的看点失败，跳过章节信息：{:#}",
+                &video_model.name, page_info.page, e
+            );
+            Vec::new()
+        }
+    }
+}
+
+/// 使用 ffmpeg 将章节信息封装进视频文件
+async fn embed_page_chapters(video_path: &Path, chapters: &[Chapter]) -> Result<()> {
+    let metadata_path = video_path.with_extension("chapters.txt");
+    let output_path = video_path.with_extension("chapters.mkv");
+    fs::write(&metadata_path, chapters_to_ffmetadata(chapters)).await?;
+    let output = Command::new("ffmpeg")
+        .arg("-i")
+        .arg(video_path)
+        .args(["-f", "ffmetadata", "-i"])
+        .arg(&metadata_path)
+        .args(["-map", "0", "-map_chapters", "1", "-c", "copy", "-y"])
+        .arg(&output_path)
+        .output()
+        .await
+        .context("failed to run ffmpeg");
+    let _ = fs::remove_file(&metadata_path).await;
+    let output = output?;
+    if !output.status.success() {
+        let _ = fs::remove_file(&output_path).await;
+        bail!("ffmpeg error: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    fs::rename(&output_path, video_path).await?;
+    Ok(())
+}
+
+/// 响度标准化时重新编码使用的编码参数，与原始音频的编码保持一致，避免将无损音频转为有损编码
+/// 未开启响度标准化时返回 None，杜比全景声音频重新编码会丢失多声道信息，同样返回 None 跳过处理
+fn audio_normalize_encoder(config: &Config, quality: Option<AudioQuality>) -> Option<&'static [&'static str]> {
+    if !config.audio_normalize {
+        return None;
+    }
+    match quality {
+        Some(AudioQuality::QualityDolby) => None,
+        // Hi-Res 音频为 flac 编码，重新编码为 flac 保持无损，输出仍然封装在 m4a 中
+        Some(AudioQuality::QualityHiRES) => Some(&["-c:a", "flac"]),
+        _ => Some(&["-c:a", "aac", "-b:a", "320k"]),
+    }
+}
+
+/// 通过 ffprobe 读取音频的采样率
+async fn probe_sample_rate(audio_path: &Path) -> Option<u32> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "a:0"])
+        .args(["-show_entries", "stream=sample_rate", "-of", "csv=p=0"])
+        .arg(audio_path)
+        .output()
+        .await
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// 使用 ffmpeg 的 loudnorm 滤镜将音频的综合响度统一到目标值，失败时保留原始文件
+async fn normalize_audio_loudness(audio_path: &Path, target_lufs: f64, encoder: &[&str]) -> Result<()> {
+    let output_path = audio_path.with_extension("loudnorm.m4a");
+    // loudnorm 会将采样率提升至 192kHz，需要还原为原始音频的采样率
+    let sample_rate = probe_sample_rate(audio_path).await.unwrap_or(48000);
+    let output = Command::new("ffmpeg")
+        .arg("-i")
+        .arg(audio_path)
+        .args(["-map", "0:a", "-af"])
+        .arg(format!("loudnorm=I={}:TP=-1.5:LRA=11", target_lufs))
+        .arg("-ar")
+        .arg(sample_rate.to_string())
+        .args(encoder)
+        .arg("-y")
+        .arg(&output_path)
+        .output()
+        .await
+        .context("failed to run ffmpeg")?;
+    if !output.status.success() {
+        let _ = fs::remove_file(&output_path).await;
+        bail!("ffmpeg error: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    fs::rename(&output_path, audio_path).await?;
+    Ok(())
 }
 
 pub async fn generate_page_nfo(
     should_run: bool,
     video_model: &video::Model,
     page_model: &page::Model,
+    chapters: &[Chapter],
     nfo_path: PathBuf,
     cx: DownloadContext<'_>,
 ) -> Result<ExecutionStatus> {
     if !should_run {
         return Ok(ExecutionStatus::Skipped);
     }
+    if cx.dry_run {
+        info!(
+            "[演练] 处理视频「{}」第 {} 页详情，将写入 {}",
+            &video_model.name,
+            page_model.pid,
+            nfo_path.display()
+        );
+        return Ok(ExecutionStatus::Skipped);
+    }
     let single_page = video_model.single_page.context("single_page is null")?;
-    let nfo = if single_page {
-        NFO::Movie(video_model.to_nfo(cx.config.nfo_time_type))
+    let (nfo, custom_nfo) = if single_page && cx.config.nfo_mode == NFOMode::Movie {
+        (
+            NFO::Movie(Movie {
+                chapters,
+                uploader_thumb: uploader_actor_thumb(video_model, &nfo_path, cx),
+                ..video_model.to_nfo(cx.config.nfo_time_type)
+            }),
+            render_custom_nfo("nfo_video", video_model, Some(page_model), cx),
+        )
     } else {
-        NFO::Episode(page_model.to_nfo(cx.config.nfo_time_type))
+        let season = render_season(
+            cx.template,
+            &page_format_args(video_model, page_model, &cx.config.time_format),
+        )?;
+        (
+            NFO::Episode(Episode {
+                season,
+                pid: page_episode_number(video_model, page_model, cx.config.collection_episode_order).to_string(),
+                rule_tags: video_model
+                    .rule_tags
+                    .as_ref()
+                    .map(|tags| tags.0.as_slice())
+                    .unwrap_or_default(),
+                chapters,
+                ..page_model.to_nfo(cx.config.nfo_time_type)
+            }),
+            render_custom_nfo("nfo_page", video_model, Some(page_model), cx),
+        )
     };
-    generate_nfo(nfo, nfo_path).await?;
+    generate_nfo(nfo, custom_nfo, nfo_path).await?;
     Ok(ExecutionStatus::Succeeded)
 }
 
 pub async fn fetch_video_poster(
     should_run: bool,
     video_model: &video::Model,
+    frame_source: Option<&Path>,
     poster_path: PathBuf,
     fanart_path: PathBuf,
     cx: DownloadContext<'_>,
@@ -785,9 +1969,21 @@ pub async fn fetch_video_poster(
     if !should_run {
         return Ok(ExecutionStatus::Skipped);
     }
-    cx.downloader
-        .fetch(&video_model.cover, &poster_path, &cx.config.concurrent_limit.download)
-        .await?;
+    if cx.dry_run {
+        info!(
+            "[演练] 处理视频「{}」封面，将写入 {} 与 {}",
+            &video_model.name,
+            poster_path.display(),
+            fanart_path.display()
+        );
+        return Ok(ExecutionStatus::Skipped);
+    }
+    write_poster(
+        cx.config.poster_source,
+        frame_source.map(|video_path| extract_first_frame(video_path, &poster_path, cx)),
+        fetch_image(&video_model.cover, &poster_path, cx),
+    )
+    .await?;
     // 确保 fanart_path 的父目录存在（虽然理论上应该已经存在，但为了确保权限正确）
     if let Some(parent) = fanart_path.parent() {
         fs::create_dir_all(parent).await
@@ -826,13 +2022,15 @@ pub async fn fetch_upper_face(
     if !should_run {
         return Ok(ExecutionStatus::Skipped);
     }
-    cx.downloader
-        .fetch(
-            &video_model.upper_face,
-            &upper_face_path,
-            &cx.config.concurrent_limit.download,
-        )
-        .await?;
+    if cx.dry_run {
+        info!(
+            "[演练] 处理视频「{}」作者头像，将写入 {}",
+            &video_model.name,
+            upper_face_path.display()
+        );
+        return Ok(ExecutionStatus::Skipped);
+    }
+    fetch_image(&video_model.upper_face, &upper_face_path, cx).await?;
     Ok(ExecutionStatus::Succeeded)
 }
 
@@ -845,7 +2043,20 @@ pub async fn generate_upper_nfo(
     if !should_run {
         return Ok(ExecutionStatus::Skipped);
     }
-    generate_nfo(NFO::Upper(video_model.to_nfo(cx.config.nfo_time_type)), nfo_path).await?;
+    if cx.dry_run {
+        info!(
+            "[演练] 处理视频「{}」作者详情，将写入 {}",
+            &video_model.name,
+            nfo_path.display()
+        );
+        return Ok(ExecutionStatus::Skipped);
+    }
+    generate_nfo(
+        NFO::Upper(video_model.to_nfo(cx.config.nfo_time_type)),
+        render_custom_nfo("nfo_upper", video_model, None, cx),
+        nfo_path,
+    )
+    .await?;
     Ok(ExecutionStatus::Succeeded)
 }
 
@@ -858,15 +2069,1237 @@ pub async fn generate_video_nfo(
     if !should_run {
         return Ok(ExecutionStatus::Skipped);
     }
-    generate_nfo(NFO::TVShow(video_model.to_nfo(cx.config.nfo_time_type)), nfo_path).await?;
+    if cx.dry_run {
+        info!(
+            "[演练] 处理视频「{}」详情，将写入 {}",
+            &video_model.name,
+            nfo_path.display()
+        );
+        return Ok(ExecutionStatus::Skipped);
+    }
+    generate_nfo(
+        NFO::TVShow(TVShow {
+            uploader_thumb: uploader_actor_thumb(video_model, &nfo_path, cx),
+            ..video_model.to_nfo(cx.config.nfo_time_type)
+        }),
+        render_custom_nfo("nfo_video", video_model, None, cx),
+        nfo_path,
+    )
+    .await?;
     Ok(ExecutionStatus::Succeeded)
 }
 
-/// 创建 nfo_path 的父目录，然后写入 nfo 文件
-async fn generate_nfo(nfo: NFO<'_>, nfo_path: PathBuf) -> Result<()> {
+/// 启用 nfo_include_uploader_actor 时，返回 nfo 所在目录到 UP 主头像 folder 图片的相对路径
+fn uploader_actor_thumb(video_model: &video::Model, nfo_path: &Path, cx: DownloadContext<'_>) -> Option<String> {
+    if !cx.config.nfo_include_uploader_actor {
+        return None;
+    }
+    let face_path = upper_base_path(&cx.config.upper_path, video_model.upper_id)
+        .join(format!("folder.{}", cx.config.cover_format.extension()));
+    let thumb = match nfo_path.parent() {
+        Some(nfo_dir) => relative_path(nfo_dir, &face_path),
+        None => face_path,
+    };
+    Some(thumb.to_string_lossy().into_owned())
+}
+
+/// 计算从 from 目录指向 to 的相对路径，两者没有公共前缀（如 Windows 下位于不同盘符）时返回 to 本身
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from = from.components().collect::<Vec<_>>();
+    let to = to.components().collect::<Vec<_>>();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return to.iter().collect();
+    }
+    std::iter::repeat_n(Component::ParentDir, from.len() - common)
+        .chain(to[common..].iter().copied())
+        .collect()
+}
+
+/// 存在用户自定义的 nfo 模板时使用模板渲染 nfo 内容，否则返回 None
+fn render_custom_nfo(
+    name: &str,
+    video_model: &video::Model,
+    page_model: Option<&page::Model>,
+    cx: DownloadContext<'_>,
+) -> Option<Result<String>> {
+    cx.template.has_template(name).then(|| {
+        Ok(cx
+            .template
+            .render(name, &nfo_format_args(video_model, page_model, &cx.config.time_format))?)
+    })
+}
+
+/// 创建 nfo_path 的父目录，然后写入 nfo 文件，优先使用自定义模板渲染的内容
+async fn generate_nfo(nfo: NFO<'_>, custom_nfo: Option<Result<String>>, nfo_path: PathBuf) -> Result<()> {
+    let content = match custom_nfo {
+        Some(content) => content?,
+        None => nfo.generate_nfo().await?,
+    };
     if let Some(parent) = nfo_path.parent() {
         fs::create_dir_all(parent).await?;
     }
-    fs::write(nfo_path, nfo.generate_nfo().await?.as_bytes()).await?;
+    fs::write(nfo_path, content.as_bytes()).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TEMPLATE;
+    use crate::utils::test_utils::{TestDatabase, test_favorite, test_page, test_video, test_view_info};
+
+    #[test]
+    fn test_invalid_video_message() {
+        let video_source = VideoSourceEnum::Favorite(favorite::Model {
+            name: "测试收藏夹".to_string(),
+            ..Default::default()
+        });
+        let video = |bvid: &str| video::Model {
+            bvid: bvid.to_string(),
+            name: "测试视频".to_string(),
+            valid: false,
+            ..Default::default()
+        };
+        let payload = invalid_video_message(&video_source, &video("BV1test00001"), &HashMap::new());
+        assert!(payload.message.contains("BV1test00001") && payload.message.contains("测试视频"));
+        assert_eq!(payload.bvid.as_deref(), Some("BV1test00001"));
+        // 同名的不同视频生成的通知内容不同，不会被去重
+        assert_ne!(
+            payload.message,
+            invalid_video_message(&video_source, &video("BV1test00002"), &HashMap::new()).message
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_paid_video_detail() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let favorite = test_favorite(test_dir.join("videos").to_string_lossy().to_string())
+            .insert(&connection)
+            .await?;
+        let video_source = VideoSourceEnum::from(favorite.clone());
+        // 模拟视频详情接口的返回，付费合集中的视频带有 arc_pay 标记
+        let view_info = |bvid: &str, arc_pay: i32| -> Result<VideoInfo> {
+            let mut info = test_view_info(bvid);
+            info["rights"]["arc_pay"] = arc_pay.into();
+            Ok(serde_json::from_value(info)?)
+        };
+        for (bvid, arc_pay) in [("BV1test00001", 1), ("BV1test00002", 0)] {
+            let video_model = video::ActiveModel {
+                favorite_id: Set(Some(favorite.id)),
+                upper_id: Set(0),
+                upper_name: Set(String::new()),
+                upper_face: Set(String::new()),
+                name: Set(String::new()),
+                cover: Set(String::new()),
+                ..test_video(bvid)
+            }
+            .insert(&connection)
+            .await?;
+            let info = view_info(bvid, arc_pay)?;
+            assert_eq!(info.requires_payment(), arc_pay == 1);
+            let video_model = save_video_detail(
+                &video_source,
+                video_model,
+                Vec::new(),
+                info,
+                &FilterOption::default(),
+                &connection,
+            )
+            .await?;
+            assert_eq!(video_model.is_paid_video, arc_pay == 1);
+            assert_eq!(video_model.should_download, arc_pay == 0);
+        }
+        // 未充电的充电专属视频标记为失效，不作为收费视频处理
+        let video_model = video::ActiveModel {
+            favorite_id: Set(Some(favorite.id)),
+            ..test_video("BV1test00003")
+        }
+        .insert(&connection)
+        .await?;
+        let mut info = test_view_info("BV1test00003");
+        info["is_upower_exclusive"] = true.into();
+        let info: VideoInfo = serde_json::from_value(info)?;
+        assert!(info.requires_upower() && !info.requires_payment());
+        let video_model = save_video_detail(
+            &video_source,
+            video_model,
+            Vec::new(),
+            info,
+            &FilterOption::default(),
+            &connection,
+        )
+        .await?;
+        assert!(!video_model.is_paid_video && !video_model.valid);
+        // 收费视频不会进入下载队列
+        let unhandled = filter_unhandled_video_pages(video_source.filter_expr(), &connection).await?;
+        assert_eq!(
+            unhandled.iter().map(|(v, _)| v.bvid.as_str()).collect::<Vec<_>>(),
+            ["BV1test00002"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_save_video_detail_duration_range() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let submission = submission::ActiveModel {
+            upper_id: Set(12345),
+            upper_name: Set("测试作者".to_string()),
+            path: Set(test_dir.join("videos").to_string_lossy().to_string()),
+            enabled: Set(true),
+            ..Default::default()
+        }
+        .insert(&connection)
+        .await?;
+        let video_source = VideoSourceEnum::from(submission.clone());
+        let filter_option = FilterOption {
+            min_duration_secs: Some(30),
+            max_duration_secs: Some(3600),
+            ..Default::default()
+        };
+        // 第三个视频由两个分页组成，总时长超过上限
+        let testcases: [(&str, &[u32], bool); 4] = [
+            ("BV1test00001", &[20], false),
+            ("BV1test00002", &[30], true),
+            ("BV1test00003", &[1800, 1801], false),
+            ("BV1test00004", &[600, 600], true),
+        ];
+        for (bvid, durations, expected) in testcases {
+            let video_model = video::ActiveModel {
+                submission_id: Set(Some(submission.id)),
+                name: Set(String::new()),
+                cover: Set(String::new()),
+                ..test_video(bvid)
+            }
+            .insert(&connection)
+            .await?;
+            let pages = durations
+                .iter()
+                .enumerate()
+                .map(|(idx, duration)| {
+                    let pid = idx + 1;
+                    serde_json::json!({"cid": pid, "page": pid, "part": format!("P{}", pid), "duration": duration})
+                })
+                .collect::<Vec<_>>();
+            let mut info = test_view_info(bvid);
+            info["pages"] = pages.into();
+            let info: VideoInfo = serde_json::from_value(info)?;
+            let video_model = save_video_detail(
+                &video_source,
+                video_model,
+                Vec::new(),
+                info,
+                &filter_option,
+                &connection,
+            )
+            .await?;
+            assert_eq!(video_model.should_download, expected, "{}", bvid);
+            assert!(!video_model.is_paid_video);
+        }
+        // 只有时长处于范围内的视频进入下载队列
+        let unhandled = filter_unhandled_video_pages(video_source.filter_expr(), &connection).await?;
+        let mut bvids = unhandled.iter().map(|(v, _)| v.bvid.as_str()).collect::<Vec<_>>();
+        bvids.sort();
+        assert_eq!(bvids, ["BV1test00002", "BV1test00004"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_save_video_detail_stat_threshold() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let submission = submission::ActiveModel {
+            upper_id: Set(12345),
+            upper_name: Set("测试作者".to_string()),
+            path: Set(test_dir.join("videos").to_string_lossy().to_string()),
+            enabled: Set(true),
+            ..Default::default()
+        }
+        .insert(&connection)
+        .await?;
+        let video_source = VideoSourceEnum::from(submission.clone());
+        let filter_option = FilterOption {
+            min_view: Some(10000),
+            min_like: Some(500),
+            ..Default::default()
+        };
+        // 缺少统计数据时视为满足条件
+        let testcases = [
+            (
+                "BV1test00001",
+                Some(serde_json::json!({"view": 20000, "like": 1000})),
+                true,
+            ),
+            (
+                "BV1test00002",
+                Some(serde_json::json!({"view": 9999, "like": 1000})),
+                false,
+            ),
+            (
+                "BV1test00003",
+                Some(serde_json::json!({"view": 20000, "like": 499})),
+                false,
+            ),
+            ("BV1test00004", Some(serde_json::json!({"like": 800})), true),
+            ("BV1test00005", None, true),
+        ];
+        for (bvid, stat, expected) in testcases {
+            let video_model = video::ActiveModel {
+                submission_id: Set(Some(submission.id)),
+                name: Set(String::new()),
+                cover: Set(String::new()),
+                ..test_video(bvid)
+            }
+            .insert(&connection)
+            .await?;
+            let mut info = test_view_info(bvid);
+            if let Some(stat) = stat {
+                info["stat"] = stat;
+            }
+            let video_model = save_video_detail(
+                &video_source,
+                video_model,
+                Vec::new(),
+                serde_json::from_value(info)?,
+                &filter_option,
+                &connection,
+            )
+            .await?;
+            assert_eq!(video_model.should_download, expected, "{}", bvid);
+            assert_eq!(video_model.below_stat_threshold, !expected, "{}", bvid);
+        }
+        // 仅因播放量或点赞数未达到下限而跳过的视频，发布时间在检查范围内时会被重新检查
+        let pubtime = chrono::DateTime::from_timestamp(1700000000, 0)
+            .context("invalid timestamp")?
+            .naive_utc();
+        let checked_before = chrono::Utc::now().naive_utc() + chrono::Duration::seconds(1);
+        let mut below =
+            filter_below_stat_threshold_videos(video_source.filter_expr(), pubtime, checked_before, &connection)
+                .await?;
+        below.sort_by_key(|video| video.id);
+        assert_eq!(
+            below.iter().map(|video| video.bvid.as_str()).collect::<Vec<_>>(),
+            ["BV1test00002", "BV1test00003"]
+        );
+        let since = pubtime + chrono::Duration::seconds(1);
+        assert!(
+            filter_below_stat_threshold_videos(video_source.filter_expr(), since, checked_before, &connection)
+                .await?
+                .is_empty()
+        );
+        // 距离上次检查不足 STAT_RECHECK_INTERVAL_HOURS 小时的视频不会被重新检查
+        let checked_before = chrono::Utc::now().naive_utc() - chrono::Duration::hours(STAT_RECHECK_INTERVAL_HOURS);
+        assert!(
+            filter_below_stat_threshold_videos(video_source.filter_expr(), pubtime, checked_before, &connection)
+                .await?
+                .is_empty()
+        );
+        // 播放量与点赞数达到下限后恢复下载
+        let mut info = test_view_info("BV1test00002");
+        info["stat"] = serde_json::json!({"view": 10000, "like": 500});
+        let video_model = save_video_detail(
+            &video_source,
+            below.remove(0),
+            Vec::new(),
+            serde_json::from_value(info)?,
+            &filter_option,
+            &connection,
+        )
+        .await?;
+        assert!(video_model.should_download);
+        assert!(!video_model.below_stat_threshold);
+        Ok(())
+    }
+
+    #[test]
+    fn test_upper_base_path() {
+        let upper_path = Path::new("/upper");
+        assert_eq!(upper_base_path(upper_path, 12345), upper_path.join("1").join("12345"));
+        assert_eq!(upper_base_path(upper_path, 0), upper_path.join("unknown").join("0"));
+        assert_eq!(upper_base_path(upper_path, -1), upper_path.join("unknown").join("-1"));
+    }
+
+    #[test]
+    fn test_video_name_override() {
+        let (bili_client, config) = (BiliClient::new().unwrap(), Config::default());
+        let connection = DatabaseConnection::Disconnected;
+        let downloader = Downloader::new(bili_client.client.clone(), None, config.downloader_backend.clone());
+        let template = TEMPLATE.snapshot();
+        let video_model = video::Model {
+            bvid: "BV1test00001".to_string(),
+            name: "测试视频".to_string(),
+            upper_name: "测试UP主".to_string(),
+            ..Default::default()
+        };
+        let sources = [
+            favorite::Model {
+                path: "/global".to_string(),
+                ..Default::default()
+            },
+            favorite::Model {
+                path: "/other-disk".to_string(),
+                video_name: Some("{{upper_name}}/{{bvid}}".to_string()),
+                ..Default::default()
+            },
+        ]
+        .map(VideoSourceEnum::from);
+        let video_paths = sources.iter().map(|video_source| {
+            let cx = DownloadContext::new(
+                &bili_client,
+                video_source,
+                &template,
+                &connection,
+                &downloader,
+                &config,
+                false,
+            );
+            let video_name = render_video_name(cx.template, cx.video_name_override, &video_model, "%Y-%m-%d");
+            cx.video_source.path().join(video_name.unwrap())
+        });
+        assert_eq!(
+            video_paths.collect::<Vec<_>>(),
+            vec![
+                // 未单独设置模板的视频源使用全局的 video_name
+                PathBuf::from("/global/测试视频"),
+                PathBuf::from("/other-disk/测试UP主").join("BV1test00001"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_page_video_extension() {
+        assert_eq!(page_video_extension(false, VideoContainer::Mp4), "mp4");
+        assert_eq!(page_video_extension(false, VideoContainer::Mkv), "mkv");
+        assert_eq!(page_video_extension(true, VideoContainer::Mp4), "m4a");
+        assert_eq!(page_video_extension(true, VideoContainer::Mkv), "m4a");
+        assert_eq!(
+            page_video_container(Some(Path::new("/videos/测试视频.mp4")), VideoContainer::Mkv),
+            VideoContainer::Mp4
+        );
+        assert_eq!(
+            page_video_container(Some(Path::new("/videos/测试视频.m4a")), VideoContainer::Mkv),
+            VideoContainer::Mkv
+        );
+        assert_eq!(page_video_container(None, VideoContainer::Mkv), VideoContainer::Mkv);
+        // 切换仅下载音频或封装格式后，已记录的路径仍能正确解析出文件名，并按新的扩展名生成路径
+        for filename in ["测试视频.mp4", "测试视频.mkv", "测试视频.m4a", "测试视频"] {
+            let base_name = trim_page_video_extension(filename);
+            assert_eq!(base_name, "测试视频");
+            assert_eq!(
+                format!("{}.{}", base_name, page_video_extension(true, VideoContainer::Mp4)),
+                "测试视频.m4a"
+            );
+        }
+        assert_eq!(trim_page_video_extension("v1.0 合集.mp4"), "v1.0 合集");
+    }
+
+    #[tokio::test]
+    async fn test_embed_subtitles_if_ready() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let invoked = AtomicUsize::new(0);
+        let mux = || async {
+            invoked.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        };
+        let cases = [
+            (ExecutionStatus::Succeeded, ExecutionStatus::Succeeded, true),
+            (ExecutionStatus::Succeeded, ExecutionStatus::Skipped, false),
+            (ExecutionStatus::Skipped, ExecutionStatus::Succeeded, false),
+            (
+                ExecutionStatus::Succeeded,
+                ExecutionStatus::Failed(anyhow!("subtitle failed")),
+                false,
+            ),
+            (
+                ExecutionStatus::Ignored(anyhow!("video failed")),
+                ExecutionStatus::Succeeded,
+                false,
+            ),
+        ];
+        for (video_status, subtitle_status, expected) in cases {
+            let before = invoked.load(Ordering::SeqCst);
+            assert_eq!(
+                embed_subtitles_if_ready(&video_status, &subtitle_status, mux)
+                    .await
+                    .unwrap(),
+                expected
+            );
+            assert_eq!(invoked.load(Ordering::SeqCst) - before, expected as usize);
+        }
+        // 封装失败时返回错误，由调用方保留外挂字幕并给出警告
+        let failed_mux = || async { bail!("ffmpeg not found") };
+        assert!(
+            embed_subtitles_if_ready(&ExecutionStatus::Succeeded, &ExecutionStatus::Succeeded, failed_mux)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remove_if_fully_succeeded() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let invoked = AtomicUsize::new(0);
+        let remove = || async {
+            invoked.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        };
+        let (not_started, succeeded) = (VideoStatus::default(), VideoStatus::from([STATUS_OK; 5]));
+        // 本轮全部成功时移除一次
+        assert!(remove_if_fully_succeeded(not_started, succeeded, remove).await.unwrap());
+        assert_eq!(invoked.load(Ordering::SeqCst), 1);
+        // 部分子任务失败时不移除，即使已经达到最大重试次数
+        let partially_failed = VideoStatus::from([STATUS_OK, STATUS_OK, STATUS_OK, STATUS_OK, 4]);
+        assert!(
+            !remove_if_fully_succeeded(not_started, partially_failed, remove)
+                .await
+                .unwrap()
+        );
+        // 之前已经成功过的视频不会重复移除
+        assert!(!remove_if_fully_succeeded(succeeded, succeeded, remove).await.unwrap());
+        assert_eq!(invoked.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_write_page_subtitles() -> Result<()> {
+        let test_dir = std::env::temp_dir().join(format!("bili-sync-subtitles-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&test_dir).await?;
+        let subtitle_path = test_dir.join("测试视频.srt");
+        let subtitles = ["zh-CN", "en"]
+            .into_iter()
+            .map(|lan| {
+                Ok(SubTitle {
+                    lan: lan.to_owned(),
+                    body: serde_json::from_value(serde_json::json!([{"from": 0.5, "to": 2.0, "content": lan}]))?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        write_page_subtitles(subtitles, &subtitle_path, SubtitleFormat::Srt).await?;
+        // 同目录下 srt 格式的弹幕文件不属于字幕
+        fs::write(test_dir.join("测试视频.zh-CN.default.srt"), "danmaku").await?;
+        // 每种语言写入单独的文件，重试时可以通过相同的路径找回全部语言
+        let files = page_subtitle_files(&subtitle_path).await?;
+        assert_eq!(
+            files,
+            vec![
+                ("en".to_owned(), test_dir.join("测试视频.en.srt")),
+                ("zh-CN".to_owned(), test_dir.join("测试视频.zh-CN.srt")),
+            ]
+        );
+        assert!(fs::read_to_string(&files[0].1).await?.contains("en"));
+        let _ = fs::remove_dir_all(&test_dir).await;
+        Ok(())
+    }
+
+    #[test]
+    fn test_page_episode_path() {
+        let mut template = handlebars::Handlebars::new();
+        template
+            .register_template_string("season", "{{#if (gt pid 2)}}2{{else}}1{{/if}}")
+            .unwrap();
+        let video_model = video::Model::default();
+        let base_path = Path::new("/video/测试视频");
+        let paths = [1, 2, 3, 4]
+            .into_iter()
+            .map(|pid| {
+                let page_model = page::Model {
+                    pid,
+                    ..Default::default()
+                };
+                let season = render_season(&template, &page_format_args(&video_model, &page_model, "%Y-%m-%d"))?;
+                let (season_path, episode_name) = page_episode_path(base_path, "测试视频", season, pid);
+                Ok(season_path.join(format!("{}.mp4", episode_name)))
+            })
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            paths,
+            [
+                base_path.join("Season 1").join("测试视频 - S01E01.mp4"),
+                base_path.join("Season 1").join("测试视频 - S01E02.mp4"),
+                base_path.join("Season 2").join("测试视频 - S02E03.mp4"),
+                base_path.join("Season 2").join("测试视频 - S02E04.mp4"),
+            ]
+        );
+        // 默认的季模板保持原有的 Season 1 路径不变
+        let (season_path, episode_name) = page_episode_path(base_path, "测试视频", 1, 12);
+        assert_eq!(season_path, base_path.join("Season 1"));
+        assert_eq!(episode_name, "测试视频 - S01E12");
+    }
+
+    #[test]
+    fn test_page_episode_number() {
+        let page_model = page::Model {
+            pid: 1,
+            ..Default::default()
+        };
+        // 合集中先后扫描到的两个单页视频
+        let videos = [1, 2].map(|order| video::Model {
+            collection_id: Some(1),
+            single_page: Some(true),
+            order: Some(order),
+            ..Default::default()
+        });
+        let episodes = |by_collection_order| {
+            videos
+                .each_ref()
+                .map(|v| page_episode_number(v, &page_model, by_collection_order))
+        };
+        assert_eq!(episodes(true), [1, 2]);
+        // 未开启时保持使用分页序号
+        assert_eq!(episodes(false), [1, 1]);
+        // 多页视频与没有记录合集位置的视频始终使用分页序号
+        let multi_page = video::Model {
+            single_page: Some(false),
+            ..videos[1].clone()
+        };
+        let page_model = page::Model {
+            pid: 3,
+            ..Default::default()
+        };
+        assert_eq!(page_episode_number(&multi_page, &page_model, true), 3);
+        assert_eq!(page_episode_number(&video::Model::default(), &page_model, true), 3);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_download() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let video_dir = test_dir.join("videos");
+        let favorite = test_favorite(video_dir.to_string_lossy().to_string())
+            .insert(&connection)
+            .await?;
+        // 第三个视频尚未获取详情，非演练模式下会请求视频详情接口
+        for (bvid, single_page) in [
+            ("BV1test00001", Some(true)),
+            ("BV1test00002", Some(false)),
+            ("BV1test00003", None),
+        ] {
+            let video = video::ActiveModel {
+                favorite_id: Set(Some(favorite.id)),
+                name: Set(format!("测试视频 {}", bvid)),
+                single_page: Set(single_page),
+                ..test_video(bvid)
+            }
+            .insert(&connection)
+            .await?;
+            let page_count = match single_page {
+                Some(true) => 1,
+                Some(false) => 3,
+                None => 0,
+            };
+            for pid in 1..=page_count {
+                test_page(video.id, pid).insert(&connection).await?;
+            }
+        }
+        let video_source = VideoSourceEnum::from(favorite.clone());
+        // 视频列表中已入库的视频不会重复输出，新视频仅输出而不写入数据库
+        let favorite_video = |bvid: &str| -> Result<VideoInfo> {
+            Ok(serde_json::from_value(serde_json::json!({
+                "title": format!("测试视频 {}", bvid),
+                "type": 2,
+                "bvid": bvid,
+                "intro": "",
+                "cover": "https://example.com/cover.jpg",
+                "upper": {"mid": 12345, "name": "测试作者", "face": "https://example.com/face.jpg"},
+                "ctime": 1700000000,
+                "fav_time": 1700000000,
+                "pubtime": 1700000000,
+                "attr": 0
+            }))?)
+        };
+        let video_streams = futures::stream::iter([favorite_video("BV1test00001"), favorite_video("BV1test00004")]);
+        let (new_bvids, invalidated_videos) =
+            refresh_video_source(&video_source, Box::pin(video_streams), &connection, true).await?;
+        assert_eq!(new_bvids, ["BV1test00004"]);
+        assert!(invalidated_videos.is_empty());
+        assert_eq!(video::Entity::find().count(&connection).await?, 3);
+        // 演练模式下获取到的视频详情仅用于输出下载计划，不写入数据库
+        let unfilled = filter_unfilled_videos(video_source.filter_expr(), &connection).await?;
+        assert_eq!(unfilled.len(), 1);
+        let (video_active_model, pages) = build_video_detail(
+            &video_source,
+            unfilled[0].clone(),
+            Vec::new(),
+            serde_json::from_value(test_view_info("BV1test00003"))?,
+            &FilterOption::default(),
+        );
+        assert!(*video_active_model.should_download.as_ref());
+        assert_eq!(pages.len(), 1);
+        // 输出已入库视频的下载计划
+        let bili_client = BiliClient::new()?;
+        let template = TEMPLATE.snapshot();
+        let config = Config::default();
+        let downloader = Downloader::from_config(bili_client.client.clone(), &config);
+        let cx = DownloadContext::new(
+            &bili_client,
+            &video_source,
+            &template,
+            &connection,
+            &downloader,
+            &config,
+            true,
+        );
+        download_unprocessed_videos(cx).await?;
+        // 演练模式下不应创建任何文件或目录
+        assert!(!video_dir.exists());
+        // 演练模式下不应更新视频源的扫描进度，也不应补充视频详情
+        let favorite_after = favorite::Entity::find_by_id(favorite.id)
+            .one(&connection)
+            .await?
+            .context("favorite not found")?;
+        assert_eq!(favorite_after.latest_row_at, favorite.latest_row_at);
+        let unfilled = video::Entity::find()
+            .filter(video::Column::Bvid.eq("BV1test00003"))
+            .one(&connection)
+            .await?
+            .context("video not found")?;
+        assert!(unfilled.single_page.is_none() && unfilled.tags.is_none());
+        // 演练模式下不应修改任何下载状态与路径
+        for video in video::Entity::find().all(&connection).await? {
+            assert_eq!(video.download_status, 0);
+            assert!(video.path.is_empty());
+        }
+        for page in page::Entity::find().all(&connection).await? {
+            assert_eq!(page.download_status, 0);
+            assert!(page.path.is_none());
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_video_pages() -> Result<()> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let db = TestDatabase::new().await?;
+        let connection = db.connection.clone();
+        let favorite = test_favorite(db.dir.join("videos").to_string_lossy().to_string())
+            .insert(&connection)
+            .await?;
+        let mut videos_model = Vec::new();
+        for i in 1..=5 {
+            let video = video::ActiveModel {
+                favorite_id: Set(Some(favorite.id)),
+                ..test_video(&format!("BV1test0000{}", i))
+            }
+            .insert(&connection)
+            .await?;
+            videos_model.push(video);
+        }
+        let video_source = VideoSourceEnum::from(favorite);
+        let bili_client = BiliClient::new()?;
+        let template = TEMPLATE.snapshot();
+        let mut config = Config::default();
+        config.concurrent_limit.prefetch = 2;
+        let downloader = Downloader::from_config(bili_client.client.clone(), &config);
+        let cx = DownloadContext::new(
+            &bili_client,
+            &video_source,
+            &template,
+            &connection,
+            &downloader,
+            &config,
+            false,
+        );
+        // 模拟视频详情接口：第二个视频获取详情失败，第五个视频写入数据库时出错，其余视频正常写入详情
+        let (running, max_running) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        let fetch = |video_model: video::Model| {
+            let (running, max_running, video_source, connection) = (&running, &max_running, &video_source, &connection);
+            async move {
+                max_running.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                match video_model.bvid.as_str() {
+                    "BV1test00002" => Ok(false),
+                    "BV1test00005" => bail!("database is locked"),
+                    bvid => {
+                        let view_info = serde_json::from_value(test_view_info(bvid))?;
+                        save_video_detail(
+                            video_source,
+                            video_model,
+                            Vec::new(),
+                            view_info,
+                            &FilterOption::default(),
+                            connection,
+                        )
+                        .await?;
+                        Ok(true)
+                    }
+                }
+            }
+        };
+        let mut error = Ok(());
+        let mut bvids = prefetch_video_pages(videos_model, fetch, cx, &mut error)
+            .map(|(video_model, pages_model)| {
+                assert_eq!(pages_model.len(), 1);
+                video_model.bvid
+            })
+            .collect::<Vec<_>>()
+            .await;
+        bvids.sort();
+        // 仅成功补充详情的视频送入下载队列，出错后不再继续预取
+        assert_eq!(bvids, ["BV1test00001", "BV1test00003", "BV1test00004"]);
+        assert!(error.is_err_and(|e| e.to_string().contains("database is locked")));
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+        // 收到停机信号后不再开始新的预取
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let cx = DownloadContext { cancel: &cancel, ..cx };
+        let videos_model = filter_unfilled_videos(video_source.filter_expr(), &connection).await?;
+        assert_eq!(videos_model.len(), 2);
+        let (mut error, fetched) = (Ok(()), AtomicUsize::new(0));
+        let fetch = |_: video::Model| {
+            fetched.fetch_add(1, Ordering::SeqCst);
+            async { Ok(true) }
+        };
+        let count = prefetch_video_pages(videos_model, fetch, cx, &mut error).count().await;
+        assert_eq!(count, 0);
+        assert_eq!(fetched.load(Ordering::SeqCst), 0);
+        assert!(error.is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_single_page_nfo_mode() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let video_model = video::Model {
+            name: "测试视频".to_string(),
+            intro: "简介".to_string(),
+            bvid: "BV1test00001".to_string(),
+            upper_name: "测试作者".to_string(),
+            single_page: Some(true),
+            favtime: chrono::NaiveDate::from_ymd_opt(2026, 10, 17)
+                .and_then(|date| date.and_hms_opt(8, 0, 0))
+                .expect("invalid datetime"),
+            ..Default::default()
+        };
+        let page_model = page::Model {
+            pid: 1,
+            name: "P1".to_string(),
+            ..Default::default()
+        };
+        let video_source = VideoSourceEnum::Favorite(favorite::Model::default());
+        let bili_client = BiliClient::new()?;
+        let template = TEMPLATE.snapshot();
+        let downloader = Downloader::new(bili_client.client.clone(), None, Default::default());
+        let generate = |nfo_mode: NFOMode| {
+            let (connection, video_source, template, downloader, bili_client) =
+                (&connection, &video_source, &template, &downloader, &bili_client);
+            let (video_model, page_model, test_dir) = (&video_model, &page_model, &test_dir);
+            async move {
+                let config = Config {
+                    nfo_mode,
+                    ..Default::default()
+                };
+                let cx = DownloadContext::new(
+                    bili_client,
+                    video_source,
+                    template,
+                    connection,
+                    downloader,
+                    &config,
+                    false,
+                );
+                let (page_nfo, video_nfo) = (test_dir.join("测试视频.nfo"), test_dir.join("tvshow.nfo"));
+                let _ = fs::remove_file(&video_nfo).await;
+                generate_page_nfo(true, video_model, page_model, &[], page_nfo.clone(), cx).await?;
+                if nfo_mode == NFOMode::TvShow {
+                    generate_video_nfo(true, video_model, video_nfo.clone(), cx).await?;
+                }
+                let video_nfo = match fs::try_exists(&video_nfo).await? {
+                    true => Some(fs::read_to_string(&video_nfo).await?),
+                    false => None,
+                };
+                Ok::<_, anyhow::Error>((fs::read_to_string(&page_nfo).await?, video_nfo))
+            }
+        };
+        // 升级前的单页视频均按电影生成 NFO，默认值保持不变以免已有媒体库的 NFO 结构发生变化
+        assert_eq!(NFOMode::default(), NFOMode::Movie);
+        // 电影模式下单页视频生成以 movie 为根节点的 NFO
+        let (page_nfo, video_nfo) = generate(NFOMode::Movie).await?;
+        assert!(page_nfo.contains("<movie>") && page_nfo.trim_end().ends_with("</movie>"));
+        assert!(page_nfo.contains("<title>测试视频</title>"));
+        assert!(page_nfo.contains("<plot><![CDATA[原始视频："));
+        assert!(page_nfo.contains("<premiered>2026-10-17</premiered>"));
+        assert!(!page_nfo.contains("<episodedetails>"));
+        assert!(video_nfo.is_none());
+        // 剧集模式下单页视频与多页视频一致，生成剧集 NFO 与 tvshow.nfo
+        let (page_nfo, video_nfo) = generate(NFOMode::TvShow).await?;
+        assert!(page_nfo.contains("<episodedetails>") && !page_nfo.contains("<movie>"));
+        assert!(page_nfo.contains("<title>P1</title>") && page_nfo.contains("<episode>1</episode>"));
+        assert!(video_nfo.is_some_and(|nfo| nfo.contains("<tvshow>") && nfo.contains("<title>测试视频</title>")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_poster_source() -> Result<()> {
+        let test_dir = std::env::temp_dir().join(format!("bili-sync-poster-source-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&test_dir).await?;
+        let poster_path = test_dir.join("poster.jpg");
+        let extract = |ok: bool| {
+            let poster_path = &poster_path;
+            async move {
+                ensure!(ok, "no video stream");
+                fs::write(poster_path, "frame").await?;
+                Ok::<_, anyhow::Error>(())
+            }
+        };
+        let download = || async {
+            fs::write(&poster_path, "cover").await?;
+            Ok::<_, anyhow::Error>(())
+        };
+        let cases = [
+            (PosterSource::Cover, Some(true), PosterSource::Cover, "cover"),
+            (PosterSource::FirstFrame, Some(true), PosterSource::FirstFrame, "frame"),
+            // 截取失败或者没有可用的视频文件时回退为视频封面
+            (PosterSource::FirstFrame, Some(false), PosterSource::Cover, "cover"),
+            (PosterSource::FirstFrame, None, PosterSource::Cover, "cover"),
+        ];
+        for (poster_source, extract_ok, expected_source, expected_content) in cases {
+            let _ = fs::remove_file(&poster_path).await;
+            let source = write_poster(poster_source, extract_ok.map(extract), download()).await?;
+            assert_eq!(source, expected_source);
+            assert_eq!(fs::read_to_string(&poster_path).await?, expected_content);
+        }
+        let _ = fs::remove_dir_all(&test_dir).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "需要安装 ffmpeg"]
+    async fn test_encode_image_webp() -> Result<()> {
+        let test_dir = std::env::temp_dir().join(format!("bili-sync-cover-format-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&test_dir).await?;
+        let (jpg_path, webp_path) = (test_dir.join("poster.jpg"), test_dir.join("poster.webp"));
+        // 使用 ffmpeg 生成一张高质量的 jpeg 作为样例封面
+        let output = Command::new("ffmpeg")
+            .args(["-f", "lavfi", "-i", "testsrc2=size=1280x720"])
+            .args(["-frames:v", "1", "-q:v", "2", "-y"])
+            .arg(&jpg_path)
+            .output()
+            .await?;
+        ensure!(output.status.success(), "failed to generate sample jpeg");
+        encode_image(&jpg_path, &webp_path, CoverFormat::Webp, 75).await?;
+        let (jpg, webp) = (fs::read(&jpg_path).await?, fs::read(&webp_path).await?);
+        assert!(webp.len() < jpg.len());
+        assert!(webp.starts_with(b"RIFF") && &webp[8..12] == b"WEBP");
+        let _ = fs::remove_dir_all(&test_dir).await;
+        Ok(())
+    }
+
+    #[test]
+    fn test_audio_normalize_encoder() {
+        let mut config = Config::default();
+        // 默认不开启响度标准化
+        assert!(audio_normalize_encoder(&config, Some(AudioQuality::Quality192k)).is_none());
+        config.audio_normalize = true;
+        assert_eq!(
+            audio_normalize_encoder(&config, Some(AudioQuality::Quality192k)),
+            Some(&["-c:a", "aac", "-b:a", "320k"][..])
+        );
+        // 无损音频保持无损，杜比全景声音频跳过处理
+        assert_eq!(
+            audio_normalize_encoder(&config, Some(AudioQuality::QualityHiRES)),
+            Some(&["-c:a", "flac"][..])
+        );
+        assert!(audio_normalize_encoder(&config, Some(AudioQuality::QualityDolby)).is_none());
+        // 目标响度超出 -70 ~ -5 LUFS 时配置校验失败
+        assert!(config.check().is_ok());
+        for target_lufs in [-80.0, 0.0] {
+            config.audio_normalize_target_lufs = target_lufs;
+            assert!(config.check().is_err());
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "需要安装 ffmpeg"]
+    async fn test_normalize_audio_loudness() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let audio_path = db.dir.join("audio.m4a");
+        // 使用 ffmpeg 生成一段音量很小的正弦波音频
+        let output = Command::new("ffmpeg")
+            .args(["-f", "lavfi", "-i", "sine=frequency=440:duration=3"])
+            .args(["-af", "volume=0.05", "-c:a", "aac", "-y"])
+            .arg(&audio_path)
+            .output()
+            .await?;
+        ensure!(output.status.success(), "failed to generate sample audio");
+        let original = fs::read(&audio_path).await?;
+        let config = Config {
+            audio_normalize: true,
+            ..Default::default()
+        };
+        let encoder = audio_normalize_encoder(&config, Some(AudioQuality::Quality192k)).unwrap();
+        normalize_audio_loudness(&audio_path, -14.0, encoder).await?;
+        assert_ne!(fs::read(&audio_path).await?, original);
+        assert!(!fs::try_exists(audio_path.with_extension("loudnorm.m4a")).await?);
+        // 无法解析的文件标准化失败，原始文件保持不变
+        let broken_path = db.dir.join("broken.m4a");
+        fs::write(&broken_path, b"not an audio file").await?;
+        assert!(normalize_audio_loudness(&broken_path, -14.0, encoder).await.is_err());
+        assert_eq!(fs::read(&broken_path).await?, b"not an audio file");
+        assert!(!fs::try_exists(broken_path.with_extension("loudnorm.m4a")).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_uploader_actor_nfo() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let video_model = video::Model {
+            name: "测试视频".to_string(),
+            bvid: "BV1test00001".to_string(),
+            upper_id: 12345,
+            upper_name: "测试作者".to_string(),
+            upper_face: "https://i0.hdslb.com/bfs/face/test.jpg".to_string(),
+            single_page: Some(false),
+            ..Default::default()
+        };
+        let video_source = VideoSourceEnum::Favorite(favorite::Model::default());
+        let bili_client = BiliClient::new()?;
+        let template = TEMPLATE.snapshot();
+        let downloader = Downloader::new(bili_client.client.clone(), None, Default::default());
+        let nfo_path = test_dir.join("videos").join("测试视频").join("tvshow.nfo");
+        for include_uploader_actor in [false, true] {
+            let config = Config {
+                upper_path: test_dir.join("upper"),
+                nfo_include_uploader_actor: include_uploader_actor,
+                ..Default::default()
+            };
+            let cx = DownloadContext::new(
+                &bili_client,
+                &video_source,
+                &template,
+                &connection,
+                &downloader,
+                &config,
+                false,
+            );
+            generate_video_nfo(true, &video_model, nfo_path.clone(), cx).await?;
+            let nfo = fs::read_to_string(&nfo_path).await?;
+            if include_uploader_actor {
+                // UP 主头像位于单独的 upper_path 下，thumb 为相对于 nfo 所在目录的路径
+                let thumb: PathBuf = ["..", "..", "upper", "1", "12345", "folder.jpg"].iter().collect();
+                assert!(nfo.contains(&format!(
+                    "<actor>\n        <name>测试作者</name>\n        <role>Uploader</role>\n        <thumb>{}</thumb>\n    </actor>",
+                    thumb.display()
+                )));
+            } else {
+                assert!(nfo.contains("<name>12345</name>\n        <role>测试作者</role>"));
+                assert!(!nfo.contains("<role>Uploader</role>"));
+            }
+        }
+        assert_eq!(
+            relative_path(Path::new("/videos/a"), Path::new("/upper/1/1/folder.jpg")),
+            Path::new("../../upper/1/1/folder.jpg")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancel_between_pages() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let video_dir = test_dir.join("videos");
+        let favorite = test_favorite(video_dir.to_string_lossy().to_string())
+            .insert(&connection)
+            .await?;
+        let video_model = video::ActiveModel {
+            favorite_id: Set(Some(favorite.id)),
+            cover: Set(String::new()),
+            single_page: Set(Some(false)),
+            ..test_video("BV1test00001")
+        }
+        .insert(&connection)
+        .await?;
+        let mut page_models = Vec::new();
+        for pid in 1..=3 {
+            page_models.push(test_page(video_model.id, pid).insert(&connection).await?);
+        }
+        let video_source = VideoSourceEnum::from(favorite);
+        let bili_client = BiliClient::new()?;
+        let template = TEMPLATE.snapshot();
+        let config = Config::default();
+        let downloader = Downloader::new(bili_client.client.clone(), None, config.downloader_backend.clone());
+        let token = CancellationToken::new();
+        let cx = DownloadContext {
+            cancel: &token,
+            ..DownloadContext::new(
+                &bili_client,
+                &video_source,
+                &template,
+                &connection,
+                &downloader,
+                &config,
+                true,
+            )
+        };
+        let semaphore = Semaphore::new(1);
+        // 第一个分页正常处理
+        let mut page_models = page_models.into_iter();
+        let first_page = page_models.next().expect("page should exist");
+        assert!(
+            download_page(&video_model, first_page, &semaphore, &video_dir, cx)
+                .await
+                .is_ok()
+        );
+        // 处理第一个分页后收到停机信号，之后的分页均不再开始
+        token.cancel();
+        let second_page = page_models.next().expect("page should exist");
+        let err = download_page(&video_model, second_page.clone(), &semaphore, &video_dir, cx)
+            .await
+            .expect_err("page should not start after cancellation");
+        assert!(err.is::<DownloadCancelled>());
+        // 存在未开始的分页时，不会给出视频层“分页下载”任务的状态
+        let res = dispatch_download_page(true, &video_model, vec![second_page], &video_dir, cx).await;
+        assert!(res.is_err_and(|e| e.is::<DownloadCancelled>()));
+        // 视频层面同样停止，且不会修改数据库中的下载状态
+        let res = download_video_pages(video_model, page_models.collect(), &semaphore, true, cx).await;
+        assert!(res.is_err_and(|e| e.is::<DownloadCancelled>()));
+        assert!(!video_dir.exists());
+        for page in page::Entity::find().all(&connection).await? {
+            assert_eq!(page.download_status, 0);
+        }
+        assert_eq!(
+            video::Entity::find()
+                .one(&connection)
+                .await?
+                .expect("video should exist")
+                .download_status,
+            0
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_measure_download_metrics() -> Result<()> {
+        const BODY_SIZE: usize = 256 * 1024;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let app = axum::Router::new().route(
+            "/file",
+            axum::routing::get(|| async {
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                vec![0u8; BODY_SIZE]
+            }),
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let downloader = Downloader::new(
+            crate::bilibili::Client::new(),
+            None,
+            crate::config::DownloaderBackend::Native,
+        );
+        let path = std::env::temp_dir().join(format!("bili-sync-metrics-{}", uuid::Uuid::new_v4()));
+        let metrics = measure_download(
+            &path,
+            downloader.fetch(
+                &format!("http://{}/file", addr),
+                &path,
+                &crate::config::ConcurrentDownloadLimit::default(),
+            ),
+        )
+        .await?;
+        fs::remove_file(&path).await?;
+        assert_eq!(metrics.file_size, BODY_SIZE as u64);
+        assert!(
+            metrics.duration >= Duration::from_millis(200) && metrics.duration < Duration::from_secs(30),
+            "unexpected download duration: {:?}",
+            metrics.duration
+        );
+        // 下载失败时不产生指标
+        let missing = std::env::temp_dir().join(format!("bili-sync-metrics-{}", uuid::Uuid::new_v4()));
+        assert!(
+            measure_download(&missing, async { Err(anyhow!("download failed")) })
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_video_dedup() -> Result<()> {
+        let db = TestDatabase::new().await?;
+        let (test_dir, connection) = (db.dir.as_path(), db.connection.clone());
+        let mut videos = Vec::new();
+        for f_id in 1..=2 {
+            let path = test_dir.join(format!("favorite-{}", f_id));
+            let favorite = favorite::ActiveModel {
+                f_id: Set(f_id),
+                name: Set(format!("收藏夹{}", f_id)),
+                ..test_favorite(path.to_string_lossy().to_string())
+            }
+            .insert(&connection)
+            .await?;
+            let video = video::ActiveModel {
+                favorite_id: Set(Some(favorite.id)),
+                ..test_video("BV1test00001")
+            }
+            .insert(&connection)
+            .await?;
+            videos.push((favorite, video));
+        }
+        // 第一个收藏夹中的视频已经下载完成
+        let existing_path = test_dir.join("favorite-1").join("测试视频.mp4");
+        fs::create_dir_all(existing_path.parent().expect("path should have parent")).await?;
+        fs::write(&existing_path, b"video").await?;
+        page::ActiveModel {
+            path: Set(Some(existing_path.to_string_lossy().to_string())),
+            download_status: Set(PageStatus::from([STATUS_OK; 5]).into()),
+            ..test_page(videos[0].1.id, 1)
+        }
+        .insert(&connection)
+        .await?;
+        let (favorite, video_model) = videos.pop().expect("video should exist");
+        let video_source = VideoSourceEnum::from(favorite);
+        let bili_client = BiliClient::new()?;
+        let template = TEMPLATE.snapshot();
+        let page_info = PageInfo {
+            cid: 1,
+            page: 1,
+            ..Default::default()
+        };
+        for (dedup_strategy, file_name) in [
+            (DedupStrategy::Hardlink, "hardlink.mp4"),
+            (DedupStrategy::Symlink, "symlink.mp4"),
+        ] {
+            let config = Config {
+                dedup_strategy,
+                ..Default::default()
+            };
+            let downloader = Downloader::new(bili_client.client.clone(), None, config.downloader_backend.clone());
+            let cx = DownloadContext::new(
+                &bili_client,
+                &video_source,
+                &template,
+                &connection,
+                &downloader,
+                &config,
+                false,
+            );
+            let target_path = test_dir.join("favorite-2").join(file_name);
+            // 复用已有文件，不会请求接口下载
+            let (status, metrics) = fetch_page_video(true, &video_model, &page_info, &target_path, cx).await?;
+            assert!(matches!(status, ExecutionStatus::Succeeded));
+            assert_eq!(metrics.map(|m| m.file_size), Some(5));
+            assert_eq!(fs::read(&target_path).await?, b"video");
+            assert_eq!(
+                fs::symlink_metadata(&target_path).await?.file_type().is_symlink(),
+                dedup_strategy == DedupStrategy::Symlink
+            );
+            // 链接得到的文件不会再被原地封装
+            assert!(is_linked_file(&target_path).await);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            assert_eq!(fs::metadata(&existing_path).await?.nlink(), 2);
+        }
+        assert!(!is_linked_file(&test_dir.join("favorite-2").join("missing.mp4")).await);
+        Ok(())
+    }
+}
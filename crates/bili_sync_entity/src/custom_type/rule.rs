@@ -30,6 +30,9 @@ pub enum RuleTarget {
     FavTime(Condition<DateTime>),
     PubTime(Condition<DateTime>),
     PageCount(Condition<usize>),
+    UpperName(Condition<String>),
+    // 视频所有分页的总时长，单位为秒
+    Duration(Condition<usize>),
     Not(Box<RuleTarget>),
 }
 
@@ -38,6 +41,13 @@ pub type AndGroup = Vec<RuleTarget>;
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
 pub struct Rule(pub Vec<AndGroup>);
 
+/// 自动标签规则，视频满足 rule 时会被打上 tag
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagRule {
+    pub tag: String,
+    pub rule: Rule,
+}
+
 impl<T: Serialize + Display> Display for Condition<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -63,6 +73,8 @@ impl Display for RuleTarget {
                 RuleTarget::FavTime(_) => "收藏时间",
                 RuleTarget::PubTime(_) => "发布时间",
                 RuleTarget::PageCount(_) => "视频分页数量",
+                RuleTarget::UpperName(_) => "UP 主名称",
+                RuleTarget::Duration(_) => "视频时长（秒）",
                 RuleTarget::Not(inner) => {
                     if depth == 0 {
                         get_field_name(inner, depth + 1)
@@ -75,18 +87,22 @@ impl Display for RuleTarget {
         let field_name = get_field_name(self, 0);
         match self {
             RuleTarget::Not(inner) => match inner.as_ref() {
-                RuleTarget::Title(cond) | RuleTarget::Tags(cond) => write!(f, "{}不{}", field_name, cond),
+                RuleTarget::Title(cond) | RuleTarget::Tags(cond) | RuleTarget::UpperName(cond) => {
+                    write!(f, "{}不{}", field_name, cond)
+                }
                 RuleTarget::FavTime(cond) | RuleTarget::PubTime(cond) => {
                     write!(f, "{}不{}", field_name, cond)
                 }
-                RuleTarget::PageCount(cond) => write!(f, "{}不{}", field_name, cond),
+                RuleTarget::PageCount(cond) | RuleTarget::Duration(cond) => write!(f, "{}不{}", field_name, cond),
                 RuleTarget::Not(_) => write!(f, "格式化失败"),
             },
-            RuleTarget::Title(cond) | RuleTarget::Tags(cond) => write!(f, "{}{}", field_name, cond),
+            RuleTarget::Title(cond) | RuleTarget::Tags(cond) | RuleTarget::UpperName(cond) => {
+                write!(f, "{}{}", field_name, cond)
+            }
             RuleTarget::FavTime(cond) | RuleTarget::PubTime(cond) => {
                 write!(f, "{}{}", field_name, cond)
             }
-            RuleTarget::PageCount(cond) => write!(f, "{}{}", field_name, cond),
+            RuleTarget::PageCount(cond) | RuleTarget::Duration(cond) => write!(f, "{}{}", field_name, cond),
         }
     }
 }
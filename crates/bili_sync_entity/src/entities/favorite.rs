@@ -4,7 +4,7 @@ use sea_orm::entity::prelude::*;
 
 use crate::rule::Rule;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Default)]
 #[sea_orm(table_name = "favorite")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -17,6 +17,11 @@ pub struct Model {
     pub latest_row_at: DateTime,
     pub rule: Option<Rule>,
     pub enabled: bool,
+    pub scan_interval_secs: Option<u32>,
+    pub scan_cron: Option<String>,
+    pub audio_only: bool,
+    pub scan_priority: i32,
+    pub video_name: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -4,8 +4,10 @@ pub mod prelude;
 
 pub mod collection;
 pub mod config;
+pub mod config_history;
 pub mod favorite;
 pub mod page;
 pub mod submission;
+pub mod task_event;
 pub mod video;
 pub mod watch_later;
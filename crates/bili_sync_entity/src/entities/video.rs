@@ -28,9 +28,14 @@ pub struct Model {
     pub download_status: u32,
     pub valid: bool,
     pub should_download: bool,
+    pub below_stat_threshold: bool,
+    pub stat_checked_at: Option<DateTime>,
     pub is_paid_video: bool,
+    pub files_deleted: bool,
     pub tags: Option<StringVec>,
+    pub rule_tags: Option<StringVec>,
     pub single_page: Option<bool>,
+    pub order: Option<i32>,
     pub created_at: String,
 }
 
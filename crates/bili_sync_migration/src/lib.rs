@@ -11,6 +11,18 @@ mod m20250712_080013_add_video_created_at_index;
 mod m20250903_094454_add_rule_and_should_download;
 mod m20251009_123713_add_use_dynamic_api;
 mod m20260130_020437_add_is_paid_video;
+mod m20261017_093012_add_task_event;
+mod m20261017_141520_add_source_scan_schedule;
+mod m20261017_160842_add_source_audio_only;
+mod m20261017_190215_add_files_deleted;
+mod m20261017_203118_add_video_rule_tags;
+mod m20261017_212406_add_page_download_metrics;
+mod m20261017_221047_add_source_scan_priority;
+mod m20261017_224530_add_source_video_name;
+mod m20261017_231508_add_video_order;
+mod m20261017_233046_add_config_history;
+mod m20261018_003512_add_video_below_stat_threshold;
+mod m20261018_041530_add_video_stat_checked_at;
 
 pub struct Migrator;
 
@@ -29,6 +41,18 @@ impl MigratorTrait for Migrator {
             Box::new(m20250903_094454_add_rule_and_should_download::Migration),
             Box::new(m20251009_123713_add_use_dynamic_api::Migration),
             Box::new(m20260130_020437_add_is_paid_video::Migration),
+            Box::new(m20261017_093012_add_task_event::Migration),
+            Box::new(m20261017_141520_add_source_scan_schedule::Migration),
+            Box::new(m20261017_160842_add_source_audio_only::Migration),
+            Box::new(m20261017_190215_add_files_deleted::Migration),
+            Box::new(m20261017_203118_add_video_rule_tags::Migration),
+            Box::new(m20261017_212406_add_page_download_metrics::Migration),
+            Box::new(m20261017_221047_add_source_scan_priority::Migration),
+            Box::new(m20261017_224530_add_source_video_name::Migration),
+            Box::new(m20261017_231508_add_video_order::Migration),
+            Box::new(m20261017_233046_add_config_history::Migration),
+            Box::new(m20261018_003512_add_video_below_stat_threshold::Migration),
+            Box::new(m20261018_041530_add_video_stat_checked_at::Migration),
         ]
     }
 }
@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskEvent::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TaskEvent::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TaskEvent::VideoId).integer().not_null())
+                    .col(ColumnDef::new(TaskEvent::PageId).integer())
+                    .col(ColumnDef::new(TaskEvent::TaskIndex).unsigned().not_null())
+                    .col(ColumnDef::new(TaskEvent::Status).unsigned().not_null())
+                    .col(
+                        ColumnDef::new(TaskEvent::CreatedAt)
+                            .timestamp()
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .table(TaskEvent::Table)
+                    .name("task_event_video_id_index")
+                    .col(TaskEvent::VideoId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .table(TaskEvent::Table)
+                    .name("task_event_created_at_index")
+                    .col(TaskEvent::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(TaskEvent::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TaskEvent {
+    Table,
+    Id,
+    VideoId,
+    PageId,
+    TaskIndex,
+    Status,
+    CreatedAt,
+}
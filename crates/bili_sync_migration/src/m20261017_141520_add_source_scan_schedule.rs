@@ -0,0 +1,90 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // sqlite 不支持在一条 alter table 语句中添加多列，因此逐列添加
+        for table in [
+            Collection::Table.into_iden(),
+            Favorite::Table.into_iden(),
+            Submission::Table.into_iden(),
+            WatchLater::Table.into_iden(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table.clone())
+                        .add_column(integer_null(VideoSource::ScanIntervalSecs))
+                        .to_owned(),
+                )
+                .await?;
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(string_null(VideoSource::ScanCron))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [
+            Collection::Table.into_iden(),
+            Favorite::Table.into_iden(),
+            Submission::Table.into_iden(),
+            WatchLater::Table.into_iden(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table.clone())
+                        .drop_column(VideoSource::ScanIntervalSecs)
+                        .to_owned(),
+                )
+                .await?;
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(VideoSource::ScanCron)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Collection {
+    Table,
+}
+
+#[derive(DeriveIden)]
+enum Favorite {
+    Table,
+}
+
+#[derive(DeriveIden)]
+enum Submission {
+    Table,
+}
+
+#[derive(DeriveIden)]
+enum WatchLater {
+    Table,
+}
+
+/// 四种视频源表中新增的列名相同，共用一个 Iden
+#[derive(DeriveIden)]
+enum VideoSource {
+    ScanIntervalSecs,
+    ScanCron,
+}
@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ConfigHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ConfigHistory::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ConfigHistory::Version).big_integer().not_null())
+                    .col(ColumnDef::new(ConfigHistory::Data).text().not_null())
+                    .col(
+                        ColumnDef::new(ConfigHistory::CreatedAt)
+                            .timestamp()
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .table(ConfigHistory::Table)
+                    .name("config_history_version_index")
+                    .col(ConfigHistory::Version)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ConfigHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ConfigHistory {
+    Table,
+    Id,
+    Version,
+    Data,
+    CreatedAt,
+}
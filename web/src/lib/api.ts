@@ -3,9 +3,13 @@ import type {
 	VideoSourcesResponse,
 	VideosRequest,
 	VideosResponse,
+	VideoUppersRequest,
+	VideoUppersResponse,
 	VideoResponse,
+	VideoRequest,
 	ResetVideoResponse,
 	ClearAndResetVideoResponse,
+	DeleteVideoFilesResponse,
 	ResetFilteredVideosResponse,
 	UpdateVideoStatusRequest,
 	UpdateVideoStatusResponse,
@@ -14,10 +18,13 @@ import type {
 	CollectionsResponse,
 	UppersResponse,
 	InsertFavoriteRequest,
+	InsertFavoritesResponse,
 	InsertCollectionRequest,
 	InsertSubmissionRequest,
 	VideoSourcesDetailsResponse,
 	UpdateVideoSourceRequest,
+	RelocateVideoSourceRequest,
+	RelocateVideoSourceResponse,
 	Config,
 	DashBoardResponse,
 	SysInfo,
@@ -26,10 +33,30 @@ import type {
 	UpdateVideoSourceResponse,
 	Notifier,
 	TestNotifierResponse,
+	CredentialStatus,
+	CredentialRefreshResult,
+	PreviewCronRequest,
+	ConfigHistoryInfo,
+	VacuumDatabaseResponse,
+	DuplicateVideosResponse,
+	RecentVideosResponse,
+	NotificationQueueStatus,
+	RetryJob,
+	RetryBatch,
+	RetryFilteredVideoTaskResponse,
+	PreviewTemplateRequest,
 	UpdateFilteredVideoStatusRequest,
 	UpdateFilteredVideoStatusResponse,
 	ResetFilteredVideoStatusRequest,
+	ReconcileVideoResponse,
+	ReconcileFilteredVideosRequest,
+	ReconcileFilteredVideosResponse,
+	RefreshMetadataResponse,
+	VideoProgressResponse,
+	RefreshFilteredMetadataRequest,
+	RefreshFilteredMetadataResponse,
 	RetryVideoTaskRequest,
+	RetryFilteredVideoTaskRequest,
 	RetryPageTaskRequest,
 	QrcodeGenerateResponse as GenerateQrcodeResponse,
 	QrcodePollResponse as PollQrcodeResponse
@@ -111,15 +138,18 @@ class ApiClient {
 			if (!response.ok) {
 				const errorText = await response.text();
 				let errorMessage: string;
+				let errorCode: ApiError['code'];
 				try {
 					const errorJson = JSON.parse(errorText);
 					errorMessage = errorJson.message || errorJson.error || '请求失败';
+					errorCode = errorJson.code;
 				} catch {
 					errorMessage = errorText || `HTTP ${response.status}: ${response.statusText}`;
 				}
 				throw {
 					message: errorMessage,
-					status: response.status
+					status: response.status,
+					code: errorCode
 				} as ApiError;
 			}
 
@@ -150,6 +180,11 @@ class ApiClient {
 		return this.request<T>(url, 'PUT', data);
 	}
 
+	// DELETE 请求
+	private async delete<T>(url: string): Promise<ApiResponse<T>> {
+		return this.request<T>(url, 'DELETE');
+	}
+
 	async getVideoSources(): Promise<ApiResponse<VideoSourcesResponse>> {
 		return this.get<VideoSourcesResponse>('/video-sources');
 	}
@@ -158,8 +193,24 @@ class ApiClient {
 		return this.get<VideosResponse>('/videos', params as Record<string, unknown>);
 	}
 
-	async getVideo(id: number): Promise<ApiResponse<VideoResponse>> {
-		return this.get<VideoResponse>(`/videos/${id}`);
+	async getVideoUppers(params?: VideoUppersRequest): Promise<ApiResponse<VideoUppersResponse>> {
+		return this.get<VideoUppersResponse>('/uppers', params as Record<string, unknown>);
+	}
+
+	async getDuplicateVideos(): Promise<ApiResponse<DuplicateVideosResponse>> {
+		return this.get<DuplicateVideosResponse>('/videos/duplicates');
+	}
+
+	async getRecentVideos(limit?: number): Promise<ApiResponse<RecentVideosResponse>> {
+		return this.get<RecentVideosResponse>('/videos/recent', { limit });
+	}
+
+	async getVideo(id: number, params?: VideoRequest): Promise<ApiResponse<VideoResponse>> {
+		return this.get<VideoResponse>(`/videos/${id}`, params as Record<string, unknown>);
+	}
+
+	async getVideoProgress(id: number): Promise<ApiResponse<VideoProgressResponse>> {
+		return this.get<VideoProgressResponse>(`/videos/${id}/progress`);
 	}
 
 	async resetVideoStatus(
@@ -173,12 +224,36 @@ class ApiClient {
 		return this.post<ClearAndResetVideoResponse>(`/videos/${id}/clear-and-reset-status`);
 	}
 
+	async deleteVideoFiles(id: number): Promise<ApiResponse<DeleteVideoFilesResponse>> {
+		return this.post<DeleteVideoFilesResponse>(`/videos/${id}/delete-files`);
+	}
+
 	async resetFilteredVideoStatus(
 		request: ResetFilteredVideoStatusRequest
 	): Promise<ApiResponse<ResetFilteredVideosResponse>> {
 		return this.post<ResetFilteredVideosResponse>('/videos/reset-status', request);
 	}
 
+	async reconcileVideo(id: number): Promise<ApiResponse<ReconcileVideoResponse>> {
+		return this.post<ReconcileVideoResponse>(`/videos/${id}/reconcile`);
+	}
+
+	async reconcileFilteredVideos(
+		request: ReconcileFilteredVideosRequest
+	): Promise<ApiResponse<ReconcileFilteredVideosResponse>> {
+		return this.post<ReconcileFilteredVideosResponse>('/videos/reconcile', request);
+	}
+
+	async refreshVideoMetadata(id: number): Promise<ApiResponse<RefreshMetadataResponse>> {
+		return this.post<RefreshMetadataResponse>(`/videos/${id}/refresh-metadata`);
+	}
+
+	async refreshFilteredVideosMetadata(
+		request: RefreshFilteredMetadataRequest
+	): Promise<ApiResponse<RefreshFilteredMetadataResponse>> {
+		return this.post<RefreshFilteredMetadataResponse>('/videos/refresh-metadata', request);
+	}
+
 	async updateVideoStatus(
 		id: number,
 		request: UpdateVideoStatusRequest
@@ -199,6 +274,12 @@ class ApiClient {
 		return this.post<UpdateVideoStatusResponse>(`/videos/${id}/retry-task`, request);
 	}
 
+	async retryFilteredVideoTask(
+		request: RetryFilteredVideoTaskRequest
+	): Promise<ApiResponse<RetryFilteredVideoTaskResponse>> {
+		return this.post<RetryFilteredVideoTaskResponse>('/videos/retry-task', request);
+	}
+
 	async retryPageTask(
 		id: number,
 		request: RetryPageTaskRequest
@@ -238,6 +319,12 @@ class ApiClient {
 		return this.post<boolean>('/video-sources/favorites', request);
 	}
 
+	async insertFavorites(
+		requests: InsertFavoriteRequest[]
+	): Promise<ApiResponse<InsertFavoritesResponse>> {
+		return this.post<InsertFavoritesResponse>('/video-sources/favorites/batch', requests);
+	}
+
 	async insertCollection(request: InsertCollectionRequest): Promise<ApiResponse<boolean>> {
 		return this.post<boolean>('/video-sources/collections', request);
 	}
@@ -266,6 +353,18 @@ class ApiClient {
 		return this.post<boolean>(`/video-sources/${type}/${id}/evaluate`, null);
 	}
 
+	async scanVideoSource(type: string, id: number): Promise<ApiResponse<boolean>> {
+		return this.post<boolean>(`/video-sources/${type}/${id}/scan`, null);
+	}
+
+	async relocateVideoSource(
+		type: string,
+		id: number,
+		request: RelocateVideoSourceRequest
+	): Promise<ApiResponse<RelocateVideoSourceResponse>> {
+		return this.post<RelocateVideoSourceResponse>(`/video-sources/${type}/${id}/relocate`, request);
+	}
+
 	async getDefaultPath(type: string, name: string): Promise<ApiResponse<string>> {
 		return this.get<string>(`/video-sources/${type}/default-path`, { name });
 	}
@@ -274,6 +373,30 @@ class ApiClient {
 		return this.post<TestNotifierResponse>('/config/notifiers/ping', notifier);
 	}
 
+	async getNotificationQueueStatus(): Promise<ApiResponse<NotificationQueueStatus>> {
+		return this.get<NotificationQueueStatus>('/config/notifiers/queue-status');
+	}
+
+	async getCredentialStatus(): Promise<ApiResponse<CredentialStatus>> {
+		return this.get<CredentialStatus>('/config/credential/status');
+	}
+
+	async refreshCredential(): Promise<ApiResponse<CredentialRefreshResult>> {
+		return this.post<CredentialRefreshResult>('/config/credential/refresh');
+	}
+
+	async setLogLevel(level: string): Promise<ApiResponse<string>> {
+		return this.post<string>('/config/log-level', { level });
+	}
+
+	async previewTemplate(request: PreviewTemplateRequest): Promise<ApiResponse<string>> {
+		return this.post<string>('/config/template/preview', request);
+	}
+
+	async previewCron(request: PreviewCronRequest): Promise<ApiResponse<string[]>> {
+		return this.post<string[]>('/config/cron/preview', request);
+	}
+
 	async getConfig(): Promise<ApiResponse<Config>> {
 		return this.get<Config>('/config');
 	}
@@ -282,6 +405,22 @@ class ApiClient {
 		return this.put<Config>('/config', config);
 	}
 
+	async importConfig(config: Config): Promise<ApiResponse<Config>> {
+		return this.post<Config>('/config/import', config);
+	}
+
+	async getConfigHistory(): Promise<ApiResponse<ConfigHistoryInfo[]>> {
+		return this.get<ConfigHistoryInfo[]>('/config/history');
+	}
+
+	async rollbackConfig(version: number): Promise<ApiResponse<Config>> {
+		return this.post<Config>(`/config/rollback/${version}`);
+	}
+
+	async vacuumDatabase(): Promise<ApiResponse<VacuumDatabaseResponse>> {
+		return this.post<VacuumDatabaseResponse>('/maintenance/vacuum');
+	}
+
 	async getDashboard(): Promise<ApiResponse<DashBoardResponse>> {
 		return this.get<DashBoardResponse>('/dashboard');
 	}
@@ -290,6 +429,26 @@ class ApiClient {
 		return this.post<boolean>('/task/download');
 	}
 
+	async runDailySummary(): Promise<ApiResponse<string>> {
+		return this.post<string>('/task/daily-summary/run');
+	}
+
+	async getRetries(): Promise<ApiResponse<RetryJob[]>> {
+		return this.get<RetryJob[]>('/tasks/retries');
+	}
+
+	async enqueueRetries(videoIds: number[]): Promise<ApiResponse<RetryJob[]>> {
+		return this.post<RetryJob[]>('/tasks/retries', { video_ids: videoIds });
+	}
+
+	async cancelRetry(id: number): Promise<ApiResponse<RetryJob>> {
+		return this.delete<RetryJob>(`/tasks/retries/${id}`);
+	}
+
+	async getRetryBatch(id: number): Promise<ApiResponse<RetryBatch>> {
+		return this.get<RetryBatch>(`/tasks/retries/batches/${id}`);
+	}
+
 	async generateQrcode(): Promise<ApiResponse<GenerateQrcodeResponse>> {
 		return this.post<GenerateQrcodeResponse>('/login/qrcode/generate');
 	}
@@ -316,18 +475,31 @@ export const apiClient = new ApiClient();
 const api = {
 	getVideoSources: () => apiClient.getVideoSources(),
 	getVideos: (params?: VideosRequest) => apiClient.getVideos(params),
-	getVideo: (id: number) => apiClient.getVideo(id),
+	getVideoUppers: (params?: VideoUppersRequest) => apiClient.getVideoUppers(params),
+	getDuplicateVideos: () => apiClient.getDuplicateVideos(),
+	getRecentVideos: (limit?: number) => apiClient.getRecentVideos(limit),
+	getVideo: (id: number, params?: VideoRequest) => apiClient.getVideo(id, params),
+	getVideoProgress: (id: number) => apiClient.getVideoProgress(id),
 	resetVideoStatus: (id: number, request: ResetVideoStatusRequest) =>
 		apiClient.resetVideoStatus(id, request),
 	clearAndResetVideoStatus: (id: number) => apiClient.clearAndResetVideoStatus(id),
+	deleteVideoFiles: (id: number) => apiClient.deleteVideoFiles(id),
 	resetFilteredVideoStatus: (request: ResetFilteredVideoStatusRequest) =>
 		apiClient.resetFilteredVideoStatus(request),
+	reconcileVideo: (id: number) => apiClient.reconcileVideo(id),
+	reconcileFilteredVideos: (request: ReconcileFilteredVideosRequest) =>
+		apiClient.reconcileFilteredVideos(request),
+	refreshVideoMetadata: (id: number) => apiClient.refreshVideoMetadata(id),
+	refreshFilteredVideosMetadata: (request: RefreshFilteredMetadataRequest) =>
+		apiClient.refreshFilteredVideosMetadata(request),
 	updateVideoStatus: (id: number, request: UpdateVideoStatusRequest) =>
 		apiClient.updateVideoStatus(id, request),
 	updateFilteredVideoStatus: (request: UpdateFilteredVideoStatusRequest) =>
 		apiClient.updateFilteredVideoStatus(request),
 	retryVideoTask: (id: number, request: RetryVideoTaskRequest) =>
 		apiClient.retryVideoTask(id, request),
+	retryFilteredVideoTask: (request: RetryFilteredVideoTaskRequest) =>
+		apiClient.retryFilteredVideoTask(request),
 	retryPageTask: (id: number, request: RetryPageTaskRequest) =>
 		apiClient.retryPageTask(id, request),
 	getCreatedFavorites: () => apiClient.getCreatedFavorites(),
@@ -336,6 +508,7 @@ const api = {
 	getFollowedUppers: (pageNum?: number, pageSize?: number, name?: string) =>
 		apiClient.getFollowedUppers(pageNum, pageSize, name),
 	insertFavorite: (request: InsertFavoriteRequest) => apiClient.insertFavorite(request),
+	insertFavorites: (requests: InsertFavoriteRequest[]) => apiClient.insertFavorites(requests),
 	insertCollection: (request: InsertCollectionRequest) => apiClient.insertCollection(request),
 	insertSubmission: (request: InsertSubmissionRequest) => apiClient.insertSubmission(request),
 	getVideoSourcesDetails: () => apiClient.getVideoSourcesDetails(),
@@ -344,12 +517,30 @@ const api = {
 	removeVideoSource: (type: string, id: number) => apiClient.removeVideoSource(type, id),
 	evaluateVideoSourceRules: (type: string, id: number) =>
 		apiClient.evaluateVideoSourceRules(type, id),
+	scanVideoSource: (type: string, id: number) => apiClient.scanVideoSource(type, id),
+	relocateVideoSource: (type: string, id: number, request: RelocateVideoSourceRequest) =>
+		apiClient.relocateVideoSource(type, id, request),
 	getDefaultPath: (type: string, name: string) => apiClient.getDefaultPath(type, name),
 	testNotifier: (notifier: Notifier) => apiClient.testNotifier(notifier),
+	getNotificationQueueStatus: () => apiClient.getNotificationQueueStatus(),
+	getCredentialStatus: () => apiClient.getCredentialStatus(),
+	refreshCredential: () => apiClient.refreshCredential(),
+	setLogLevel: (level: string) => apiClient.setLogLevel(level),
+	previewTemplate: (request: PreviewTemplateRequest) => apiClient.previewTemplate(request),
+	previewCron: (request: PreviewCronRequest) => apiClient.previewCron(request),
 	getConfig: () => apiClient.getConfig(),
 	updateConfig: (config: Config) => apiClient.updateConfig(config),
+	importConfig: (config: Config) => apiClient.importConfig(config),
+	getConfigHistory: () => apiClient.getConfigHistory(),
+	rollbackConfig: (version: number) => apiClient.rollbackConfig(version),
+	vacuumDatabase: () => apiClient.vacuumDatabase(),
 	getDashboard: () => apiClient.getDashboard(),
 	triggerDownloadTask: () => apiClient.triggerDownloadTask(),
+	runDailySummary: () => apiClient.runDailySummary(),
+	getRetries: () => apiClient.getRetries(),
+	enqueueRetries: (videoIds: number[]) => apiClient.enqueueRetries(videoIds),
+	cancelRetry: (id: number) => apiClient.cancelRetry(id),
+	getRetryBatch: (id: number) => apiClient.getRetryBatch(id),
 	generateQrcode: () => apiClient.generateQrcode(),
 	pollQrcode: (qrcodeKey: string) => apiClient.pollQrcode(qrcodeKey),
 	subscribeToSysInfo: (onMessage: (data: SysInfo) => void) =>
@@ -5,6 +5,7 @@ export interface ApiResponse<T> {
 
 export type VideoSortBy = 'publish_time' | 'subscribe_time' | 'download_time';
 export type VideoSortOrder = 'asc' | 'desc';
+export type PageSortBy = 'cid' | 'pid' | 'status';
 
 export interface VideosRequest {
 	collection?: number;
@@ -13,6 +14,7 @@ export interface VideosRequest {
 	watch_later?: number;
 	query?: string;
 	failed_only?: boolean;
+	tag?: string;
 	page?: number;
 	page_size?: number;
 	sort_by?: VideoSortBy;
@@ -46,6 +48,7 @@ export interface VideoInfo {
 	upper_name: string;
 	should_download: boolean;
 	is_paid_video: boolean;
+	files_deleted: boolean;
 	download_status: [number, number, number, number, number];
 	cover: string;
 }
@@ -55,16 +58,77 @@ export interface VideosResponse {
 	total_count: number;
 }
 
+export interface VideoUppersRequest {
+	page_num?: number;
+	page_size?: number;
+	name?: string;
+}
+
+export interface VideoUpper {
+	upper_id: number;
+	upper_name: string;
+	upper_face: string;
+	video_count: number;
+}
+
+export interface VideoUppersResponse {
+	uppers: VideoUpper[];
+	total: number;
+}
+
 export interface PageInfo {
 	id: number;
 	pid: number;
 	name: string;
 	download_status: [number, number, number, number, number];
+	file_size: number | null;
+	download_duration_ms: number | null;
 }
 
 export interface VideoResponse {
 	video: VideoInfo;
 	pages: PageInfo[];
+	total_pages_count: number; // 视频的分页总数，不受分页参数影响
+}
+
+export interface VideoRequest {
+	page?: number;
+	page_size?: number;
+	pages_sort_by?: PageSortBy;
+	pages_sort_order?: VideoSortOrder;
+}
+
+export interface DuplicateVideoInfo {
+	id: number;
+	name: string;
+	path: string;
+	collection_id: number | null;
+	favorite_id: number | null;
+	watch_later_id: number | null;
+	submission_id: number | null;
+}
+
+export interface DuplicateVideoGroup {
+	bvid: string;
+	videos: DuplicateVideoInfo[];
+}
+
+export interface DuplicateVideosResponse {
+	groups: DuplicateVideoGroup[];
+}
+
+export interface RecentVideoInfo {
+	id: number;
+	bvid: string;
+	name: string;
+	upper_name: string;
+	cover: string;
+	created_at: string;
+	source_name: string | null;
+}
+
+export interface RecentVideosResponse {
+	videos: RecentVideoInfo[];
 }
 
 export interface ResetVideoResponse {
@@ -78,12 +142,63 @@ export interface ClearAndResetVideoResponse {
 	video: VideoInfo;
 }
 
+export interface DeleteVideoFilesResponse {
+	warning?: string;
+	video: VideoInfo;
+}
+
 export interface ResetFilteredVideosResponse {
 	resetted: boolean;
 	resetted_videos_count: number;
 	resetted_pages_count: number;
 }
 
+export interface ReconciledTask {
+	page_id: number | null;
+	task_index: number;
+}
+
+export interface ReconcileVideoResponse {
+	reconciled: ReconciledTask[];
+	video: VideoInfo;
+	pages: PageInfo[];
+}
+
+export interface ReconcileFilteredVideosResponse {
+	reconciled_videos_count: number;
+	reconciled_pages_count: number;
+}
+
+// 视频的实时下载进度，多页视频的各分页累计计算
+export interface VideoProgress {
+	downloaded_bytes: number;
+	total_bytes: number;
+}
+
+export interface VideoProgressResponse {
+	// 视频未在下载时为空
+	progress: VideoProgress | null;
+}
+
+export interface RefreshMetadataResponse {
+	succeeded: ReconciledTask[];
+	failed: ReconciledTask[];
+	video: VideoInfo;
+	pages: PageInfo[];
+}
+
+export interface FailedVideo {
+	id: number;
+	name: string;
+	error: string;
+}
+
+export interface RefreshFilteredMetadataResponse {
+	refreshed_videos_count: number;
+	failed_tasks_count: number;
+	failed_videos: FailedVideo[];
+}
+
 export interface UpdateVideoStatusResponse {
 	success: boolean;
 	video: VideoInfo;
@@ -96,9 +211,20 @@ export interface UpdateFilteredVideoStatusResponse {
 	updated_pages_count: number;
 }
 
+export type ApiErrorCode =
+	| 'BAD_REQUEST'
+	| 'UNAUTHORIZED'
+	| 'NOT_FOUND'
+	| 'CONFLICT'
+	| 'TOO_MANY_REQUESTS'
+	| 'TEMPLATE_ERROR'
+	| 'RISK_CONTROL'
+	| 'INTERNAL_ERROR';
+
 export interface ApiError {
 	message: string;
 	status?: number;
+	code?: ApiErrorCode;
 }
 
 export interface StatusUpdate {
@@ -138,12 +264,25 @@ export interface UpdateFilteredVideoStatusRequest {
 
 export interface ResetVideoStatusRequest {
 	force: boolean;
+	task_indices?: number[]; // 仅重置分页中这些序号的失败子任务，0=视频封面, 1=视频内容, 2=视频信息, 3=视频弹幕, 4=视频字幕
 }
 
 export interface RetryVideoTaskRequest {
 	task_index: number; // 0=视频封面, 1=视频信息, 2=UP主头像, 3=UP主信息, 4=分页下载
 }
 
+export interface RetryFilteredVideoTaskRequest extends ReconcileFilteredVideosRequest {
+	search_fields?: string; // 以逗号分隔，可选 name / bvid / upper_name
+	status_filter?: 'failed' | 'succeeded' | 'waiting' | 'skipped' | 'paid';
+	tag?: string;
+	task_index: number; // 含义与 RetryVideoTaskRequest 相同
+}
+
+export interface RetryFilteredVideoTaskResponse {
+	batch: RetryBatch;
+	jobs: RetryJob[];
+}
+
 export interface RetryPageTaskRequest {
 	task_index: number; // 0=视频封面, 1=视频内容, 2=视频信息, 3=视频弹幕, 4=视频字幕
 }
@@ -157,8 +296,19 @@ export interface ResetFilteredVideoStatusRequest {
 	// 仅重置下载失败
 	failed_only?: boolean;
 	force: boolean;
+	task_index?: number; // 仅重置分页中该子任务的失败状态，0=视频封面, 1=视频内容, 2=视频信息, 3=视频弹幕, 4=视频字幕
 }
 
+export interface ReconcileFilteredVideosRequest {
+	collection?: number;
+	favorite?: number;
+	submission?: number;
+	watch_later?: number;
+	query?: string;
+}
+
+export type RefreshFilteredMetadataRequest = ReconcileFilteredVideosRequest;
+
 export type Followed =
 	| {
 			type: 'favorite';
@@ -207,6 +357,16 @@ export interface InsertFavoriteRequest {
 	path: string;
 }
 
+export interface InsertFavoriteResult {
+	fid: number;
+	success: boolean;
+	error: string | null;
+}
+
+export interface InsertFavoritesResponse {
+	results: InsertFavoriteResult[];
+}
+
 export interface InsertCollectionRequest {
 	sid: number;
 	mid: number;
@@ -232,6 +392,11 @@ export interface RuleTarget<T> {
 export type AndGroup = RuleTarget<string | number | Date>[];
 export type Rule = AndGroup[];
 
+export interface TagRule {
+	tag: string;
+	rule: Rule;
+}
+
 export interface VideoSourceDetail {
 	id: number;
 	name: string;
@@ -240,6 +405,11 @@ export interface VideoSourceDetail {
 	ruleDisplay: string | null;
 	useDynamicApi: boolean | null;
 	enabled: boolean;
+	audioOnly: boolean;
+	scanPriority: number;
+	videoName: string | null;
+	scanIntervalSecs: number | null;
+	scanCron: string | null;
 }
 
 export interface VideoSourcesDetailsResponse {
@@ -254,6 +424,12 @@ export interface UpdateVideoSourceRequest {
 	enabled: boolean;
 	rule?: Rule | null;
 	useDynamicApi?: boolean | null;
+	audioOnly?: boolean | null;
+	scanPriority?: number | null;
+	videoName?: string | null;
+	// 传入 0 或空字符串时清除视频源单独设置的扫描计划
+	scanIntervalSecs?: number | null;
+	scanCron?: string | null;
 }
 
 export interface Credential {
@@ -274,6 +450,14 @@ export interface FilterOption {
 	no_dolby_audio: boolean;
 	no_hdr: boolean;
 	no_hires: boolean;
+	min_duration_secs?: number | null;
+	max_duration_secs?: number | null;
+	title_include?: string[];
+	title_exclude?: string[];
+	title_include_regex?: string[];
+	title_exclude_regex?: string[];
+	min_view?: number | null;
+	min_like?: number | null;
 }
 
 export interface DanmakuOption {
@@ -289,6 +473,9 @@ export interface DanmakuOption {
 	bold: boolean;
 	outline: number;
 	time_offset: number;
+	max_per_second?: number | null;
+	time_window?: [number, number] | null;
+	danmaku_format?: 'ass' | 'srt';
 }
 
 export interface SkipOption {
@@ -315,21 +502,64 @@ export interface ConcurrentLimit {
 	page: number;
 	rate_limit?: RateLimit;
 	download: ConcurrentDownloadLimit;
+	source?: number;
+}
+
+// 按时间段调整的并发与限速设置，为空的项沿用全局配置
+export interface ScheduleWindow {
+	cron_window: string;
+	concurrent_limit?: ConcurrentLimit | null;
+	rate_limit?: number | null;
 }
 
+export type NotificationEventName =
+	| 'message'
+	| 'error'
+	| 'new_videos'
+	| 'invalid_video'
+	| 'paid_video'
+	| 'daily_summary';
+
 export interface TelegramNotifier {
 	type: 'telegram';
 	bot_token: string;
 	chat_id: string;
+	// 开启了话题的群组中，消息发送到的话题 id
+	message_thread_id?: number | null;
 }
 
 export interface WebhookNotifier {
 	type: 'webhook';
 	url: string;
 	template?: string | null;
+	// structured 模式下直接发送包含事件信息的 JSON 对象，忽略模板
+	payload_mode?: 'template' | 'structured';
+}
+
+export interface GotifyNotifier {
+	type: 'gotify';
+	server: string;
+	app_token: string;
+	// 消息优先级，为空时使用应用的默认优先级
+	priority?: number | null;
+}
+
+export interface ServerChanNotifier {
+	type: 'serverChan';
+	send_key: string;
+}
+
+export interface WeComBotNotifier {
+	type: 'weComBot';
+	webhook_key: string;
 }
 
-export type Notifier = TelegramNotifier | WebhookNotifier;
+export type Notifier =
+	| TelegramNotifier
+	| WebhookNotifier
+	| GotifyNotifier
+	| ServerChanNotifier
+	| WeComBotNotifier;
 
 export interface TestNotifierResponse {
 	success: boolean;
@@ -337,8 +567,69 @@ export interface TestNotifierResponse {
 	details?: string | null;
 }
 
+export interface PreviewCronRequest {
+	cron: string;
+	// 返回的触发时间数量，默认 5 个，最多 20 个
+	count?: number;
+}
+
+export interface ConfigHistoryInfo {
+	version: number;
+	created_at: string;
+}
+
+export interface RetryJob {
+	id: number;
+	video_id: number;
+	video_name: string;
+	status: 'queued' | 'running';
+	created_at: string;
+	batch_id: number | null;
+}
+
+export interface RetryBatch {
+	id: number;
+	total_count: number;
+	succeeded_count: number;
+	failed_count: number;
+	cancelled_count: number; // 开始执行前被取消的任务数
+}
+
+export interface NotificationQueueStatus {
+	// 已入队但尚未发送的消息数量，包括因静默时间段被延迟的消息
+	pending: number;
+	last_sent_at: string | null;
+	interval_secs: number;
+}
+
+export interface VacuumDatabaseResponse {
+	before_size: number;
+	after_size: number;
+}
+
+export interface PreviewTemplateRequest {
+	template: string;
+	target: 'video' | 'page';
+	videoId?: number;
+	pageId?: number;
+}
+
+export interface CredentialStatus {
+	valid: boolean;
+	vip_status: number;
+	expires_in: number | null;
+	mid: number | null;
+}
+
+export interface CredentialRefreshResult {
+	refreshed: boolean;
+	error: string | null;
+}
+
 export type Trigger = number | string;
 
+export type Weekday = 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat' | 'Sun';
+
 export interface Config {
 	auth_token: string;
 	bind_address: string;
@@ -348,6 +639,17 @@ export interface Config {
 	skip_option: SkipOption;
 	video_name: string;
 	page_name: string;
+	season_template: string;
+	container: 'mp4' | 'mkv';
+	dedup_strategy: 'none' | 'hardlink' | 'symlink';
+	subtitle_format: 'srt' | 'ass' | 'vtt';
+	// 需要下载的字幕语言，如 zh-CN、en，为空时下载全部语言
+	subtitle_languages?: string[];
+	embed_subtitles: boolean;
+	remove_embedded_subtitle_files: boolean;
+	// 仅下载音频时通过 ffmpeg loudnorm 将响度统一到目标值（LUFS）
+	audio_normalize?: boolean;
+	audio_normalize_target_lufs?: number;
 	notifiers: Notifier[] | null;
 	favorite_default_path: string;
 	collection_default_path: string;
@@ -355,19 +657,52 @@ export interface Config {
 	interval: Trigger;
 	upper_path: string;
 	nfo_time_type: string;
+	// 单页视频生成的 NFO 类型
+	nfo_mode?: 'tvshow' | 'movie';
+	// 在视频 NFO 中以 Uploader 角色写入 UP 主
+	nfo_include_uploader_actor?: boolean;
+	// 合集中的单页视频按照其在合集中的位置编号剧集
+	collection_episode_order?: boolean;
+	// 封面来源，first_frame 表示截取视频的第一帧
+	poster_source?: 'cover' | 'first_frame';
+	// 封面、横幅与 UP 主头像的保存格式及转换质量
+	cover_format?: 'jpg' | 'webp' | 'avif';
+	cover_quality?: number;
 	concurrent_limit: ConcurrentLimit;
+	// 按时间段调整的并发与限速设置，命中多个时间段时使用第一个
+	schedule?: ScheduleWindow[];
+	// 自动标签规则，命中的标签会写入 NFO 并保存到数据库
+	tag_rules?: TagRule[];
+	// 视为可忽略的 B 站接口错误码，命中时任务不标记为失败
+	ignored_error_codes?: number[];
+	// 子任务自动重试的次数上限（1 ~ 4），达到上限后需要手动重置
+	max_auto_retries?: number;
 	time_format: string;
 	cdn_sorting: boolean;
 	enable_cover_background: boolean;
 	// 订阅收藏夹 / 合集 / UP 投稿时，是否自动将对应视频源标记为启用
 	enable_video_source_on_subscribe: boolean;
+	// 稍后再看中的视频全部下载成功后自动从稍后再看列表中移除
+	watch_later_auto_remove?: boolean;
+	// 视频全部下载完成后在后台执行的命令，视频信息通过 BILI_* 环境变量传入
+	post_download_command?: string | null;
+	post_download_command_timeout_secs?: number;
+	post_download_command_concurrency?: number;
 	notify_new_videos: boolean;
+	notify_invalid_videos: boolean;
+	// 扫描时识别到收费视频并跳过下载时发送通知
+	notify_paid_videos?: boolean;
 	notify_daily_summary: boolean;
+	// 自定义的通知消息模板，键为事件名称，未配置的事件使用内置的消息
+	message_templates?: Partial<Record<NotificationEventName, string>>;
 	daily_summary_cron: string;
 	notification_interval: number;
+	notification_queue_capacity: number;
 	enable_notification_quiet_hours: boolean;
 	quiet_hours_start: number;
 	quiet_hours_end: number;
+	// 全天静默的星期，例如周末
+	quiet_days?: Weekday[] | null;
 	version: number;
 }
 
@@ -401,12 +736,27 @@ export interface TaskStatus {
 	last_run: Date | null;
 	last_finish: Date | null;
 	next_run: Date | null;
+	cooldown_until: Date | null;
+	paused: boolean;
 }
 
 export interface UpdateVideoSourceResponse {
 	ruleDisplay: string;
 }
 
+export interface RelocateVideoSourceRequest {
+	path: string;
+}
+
+export interface RelocateVideoSourceResponse {
+	moved_files: number;
+	skipped_files: string[]; // 目标位置已存在同名文件而保留在原位置的文件
+	updated_videos: number;
+	updated_pages: number;
+	conflicted_videos: number; // 存在冲突文件而保留原路径记录的视频数量
+	conflicted_pages: number; // 存在冲突文件而保留原路径记录的分页数量
+}
+
 // 扫码登录相关类型
 export interface QrcodeGenerateResponse {
 	url: string;